@@ -83,6 +83,9 @@ impl Cheatcodes<'static> {
                 Vm::ChainInfo::STRUCT.clone(),
                 Vm::AccountAccess::STRUCT.clone(),
                 Vm::StorageAccess::STRUCT.clone(),
+                Vm::StorageOverride::STRUCT.clone(),
+                Vm::BalanceOverride::STRUCT.clone(),
+                Vm::CodeOverride::STRUCT.clone(),
             ]),
             enums: Cow::Owned(vec![
                 Vm::CallerMode::ENUM.clone(),
@@ -213,6 +213,32 @@ interface Vm {
         bool reverted;
     }
 
+    /// A storage slot override. Passed to `withStateOverrides`.
+    struct StorageOverride {
+        /// The account whose storage is overridden.
+        address account;
+        /// The overridden slot.
+        bytes32 slot;
+        /// The value the slot is overridden with.
+        bytes32 value;
+    }
+
+    /// A balance override. Passed to `withStateOverrides`.
+    struct BalanceOverride {
+        /// The account whose balance is overridden.
+        address account;
+        /// The balance the account is overridden with.
+        uint256 balance;
+    }
+
+    /// A code override. Passed to `withStateOverrides`.
+    struct CodeOverride {
+        /// The account whose code is overridden.
+        address account;
+        /// The EraVM bytecode the account is overridden with.
+        bytes code;
+    }
+
     // ======== EVM ========
 
     /// Gets the address for a given private key.
@@ -223,6 +249,34 @@ interface Vm {
     #[cheatcode(group = Evm, safety = Safe)]
     function getNonce(address account) external view returns (uint64 nonce);
 
+    /// Gets the balance of `account` in the ERC-20 `token`, calling its `balanceOf`.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function erc20BalanceOf(address token, address account) external view returns (uint256 balance);
+
+    /// Gets the amount of the ERC-20 `token` `spender` may transfer on behalf of `owner`, calling
+    /// its `allowance`.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function erc20Allowance(address token, address owner, address spender) external view returns (uint256 allowance);
+
+    /// Gets the number of tokens of the ERC-721 `token` collection owned by `owner`, calling its
+    /// `balanceOf`.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function erc721BalanceOf(address token, address owner) external view returns (uint256 balance);
+
+    /// Gets the owner of `tokenId` in the ERC-721 `token` collection, calling its `ownerOf`.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function erc721OwnerOf(address token, uint256 tokenId) external view returns (address owner);
+
+    /// Gets the account approved to transfer `tokenId` of the ERC-721 `token` collection, calling
+    /// its `getApproved`.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function erc721GetApproved(address token, uint256 tokenId) external view returns (address approved);
+
+    /// Gets whether `operator` may transfer all the tokens of `owner` in the ERC-721 `token`
+    /// collection, calling its `isApprovedForAll`.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function erc721IsApprovedForAll(address token, address owner, address operator) external view returns (bool approved);
+
     /// Loads a storage slot from an address.
     #[cheatcode(group = Evm, safety = Safe)]
     function load(address target, bytes32 slot) external view returns (bytes32 data);
@@ -313,6 +367,14 @@ interface Vm {
     #[cheatcode(group = Evm, safety = Unsafe)]
     function txGasPrice(uint256 newGasPrice) external;
 
+    /// Sets `tx.origin`, independently of any prank or broadcast.
+    #[cheatcode(group = Evm, safety = Unsafe)]
+    function setTxOrigin(address newOrigin) external;
+
+    /// Gets the current `tx.origin`.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function txOrigin() external view returns (address origin);
+
     /// Sets `block.timestamp`.
     #[cheatcode(group = Evm, safety = Unsafe)]
     function warp(uint256 newTimestamp) external;
@@ -343,6 +405,10 @@ interface Vm {
     #[cheatcode(group = Evm, safety = Unsafe)]
     function store(address target, bytes32 slot, bytes32 value) external;
 
+    /// Stores `values[i]` to the storage slot `slots[i]` of `targets[i]`, for all `i`.
+    #[cheatcode(group = Evm, safety = Unsafe)]
+    function storeBatch(address[] calldata targets, bytes32[] calldata slots, bytes32[] calldata values) external;
+
     /// Marks the slots of an account and the account address as cold.
     #[cheatcode(group = Evm, safety = Unsafe)]
     function cool(address target) external;
@@ -375,6 +441,13 @@ interface Vm {
     function mockCallRevert(address callee, uint256 msgValue, bytes calldata data, bytes calldata revertData)
         external;
 
+    /// Whenever a call is made to `callee` with calldata `data`, this cheatcode instead runs the
+    /// code of `target` with the same calldata, in the context of `callee`. This functionality is
+    /// similar to a delegate call made to `target` contract from `callee`.
+    /// If calldata is not a strict match then partial match by selector is attempted.
+    #[cheatcode(group = Evm, safety = Unsafe)]
+    function mockFunction(address callee, address target, bytes calldata data) external;
+
     // --- Impersonation (pranks) ---
 
     /// Sets the *next* call's `msg.sender` to be the input address.
@@ -415,6 +488,23 @@ interface Vm {
     #[cheatcode(group = Evm, safety = Unsafe)]
     function revertTo(uint256 snapshotId) external returns (bool success);
 
+    /// Snapshot the current state of the evm under the given name, replacing any previous snapshot with the same name.
+    /// Returns the ID of the snapshot that was created.
+    /// To revert a named snapshot use `revertToNamed`.
+    #[cheatcode(group = Evm, safety = Unsafe)]
+    function snapshotNamed(string calldata name) external returns (uint256 snapshotId);
+
+    /// Revert the state of the EVM to a previous snapshot taken with `snapshotNamed`.
+    /// Returns false if no snapshot with the given name exists.
+    /// This deletes the snapshot and all snapshots taken after it.
+    #[cheatcode(group = Evm, safety = Unsafe)]
+    function revertToNamed(string calldata name) external returns (bool success);
+
+    /// Returns the changes to storage, balances and nonces between two snapshots as a JSON object.
+    /// Only supported in the zkEVM.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function diffSnapshots(uint256 fromSnapshotId, uint256 toSnapshotId) external view returns (string memory diff);
+
     // -------- Forking --------
     // --- Creation and Selection ---
 
@@ -521,6 +611,11 @@ interface Vm {
     #[cheatcode(group = Evm, safety = Safe)]
     function getRecordedLogs() external returns (Log[] memory logs);
 
+    /// Gets the recorded logs emitted by any of `emitters`, or by any address if empty, at most
+    /// `maxDepth` calls below the caller, `0` being the logs of the caller itself.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function getRecordedLogs(address[] calldata emitters, uint256 maxDepth) external returns (Log[] memory logs);
+
     // -------- Gas Metering --------
     // It's recommend to use the `noGasMetering` modifier included with forge-std, instead of
     // using these functions directly.
@@ -597,6 +692,15 @@ interface Vm {
     function expectCallMinGas(address callee, uint256 msgValue, uint64 minGas, bytes calldata data, uint64 count)
         external;
 
+    /// Starts recording the calls made, which are counted with `getCalls`.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function recordCalls() external;
+
+    /// Gets the number of calls made to `callee` with the specified calldata since `recordCalls`.
+    /// Calldata can either be a strict or a partial match.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function getCalls(address callee, bytes calldata data) external view returns (uint256 count);
+
     /// Prepare an expected log with (bool checkTopic1, bool checkTopic2, bool checkTopic3, bool checkData.).
     /// Call this function, then emit an event, then call a function. Internally after the call, we check if
     /// logs were emitted in the expected order with the expected topics and data (as specified by the booleans).
@@ -618,6 +722,11 @@ interface Vm {
     #[cheatcode(group = Testing, safety = Unsafe)]
     function expectEmit(address emitter) external;
 
+    /// Prepare an expected log with the topics marked as wildcards in `topicWildcards` left unchecked: bit 0 skips
+    /// topic 1, bit 1 skips topic 2 and bit 2 skips topic 3. The event signature is always checked.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectEmit(uint8 topicWildcards, bool checkData) external;
+
     /// Expects an error on next call with any revert data.
     #[cheatcode(group = Testing, safety = Unsafe)]
     function expectRevert() external;
@@ -630,6 +739,30 @@ interface Vm {
     #[cheatcode(group = Testing, safety = Unsafe)]
     function expectRevert(bytes calldata revertData) external;
 
+    /// Expects `count` consecutive calls to revert with any revert data.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectRevert(uint64 count) external;
+
+    /// Expects `count` consecutive calls to revert with revert data that starts with `revertData`.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectRevert(bytes4 revertData, uint64 count) external;
+
+    /// Expects `count` consecutive calls to revert with revert data that exactly matches `revertData`.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectRevert(bytes calldata revertData, uint64 count) external;
+
+    /// Expects `count` consecutive calls to revert with any revert data, originating from `reverter`.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectRevert(address reverter, uint64 count) external;
+
+    /// Expects `count` consecutive calls to revert with revert data that starts with `revertData`, originating from `reverter`.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectRevert(bytes4 revertData, address reverter, uint64 count) external;
+
+    /// Expects `count` consecutive calls to revert with revert data that exactly matches `revertData`, originating from `reverter`.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function expectRevert(bytes calldata revertData, address reverter, uint64 count) external;
+
     /// Only allows memory writes to offsets [0x00, 0x60) ∪ [min, max) in the current subcontext. If any other
     /// memory is written to, the test will fail. Can be called multiple times to add more ranges to the set.
     #[cheatcode(group = Testing, safety = Unsafe)]
@@ -645,6 +778,10 @@ interface Vm {
     #[cheatcode(group = Testing, safety = Unsafe)]
     function skip(bool skipTest) external;
 
+    /// Returns the version of the running Foundry binary.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function getFoundryVersion() external view returns (string memory version);
+
     // ======== OS and Filesystem ========
 
     // -------- Metadata --------
@@ -771,6 +908,11 @@ interface Vm {
     #[cheatcode(group = Filesystem)]
     function getDeployedCode(string calldata artifactPath) external view returns (bytes memory runtimeBytecode);
 
+    /// Gets the deployed bytecode of an account, fetching it from the fork if needed.
+    /// In the zkEVM, this is the EraVM bytecode of the account.
+    #[cheatcode(group = Evm, safety = Safe)]
+    function getDeployedCode(address account) external view returns (bytes memory runtimeBytecode);
+
     // -------- Foreign Function Interface --------
 
     /// Performs a foreign function call via the terminal.
@@ -969,6 +1111,21 @@ interface Vm {
     #[cheatcode(group = Scripting)]
     function stopBroadcast() external;
 
+    /// Returns the addresses of the wallets the script can broadcast with: the ones it was run
+    /// with, followed by the ones remembered with `rememberKey` or `startBroadcast(uint256)`.
+    #[cheatcode(group = Scripting)]
+    function getScriptWallets() external returns (address[] memory wallets);
+
+    /// Has the next call (at this call depth only) create a transaction with the script wallet at
+    /// `senderIndex` in `getScriptWallets` as the sender, that can later be signed and sent onchain.
+    #[cheatcode(group = Scripting)]
+    function broadcastWallet(uint256 senderIndex) external;
+
+    /// Has all subsequent calls (at this call depth only) create transactions with the script wallet
+    /// at `senderIndex` in `getScriptWallets` as the sender, that can later be signed and sent onchain.
+    #[cheatcode(group = Scripting)]
+    function startBroadcastWallet(uint256 senderIndex) external;
+
     // ======== Utilities ========
 
     // -------- Strings --------
@@ -1204,6 +1361,14 @@ interface Vm {
     #[cheatcode(group = Utilities)]
     function sign(Wallet calldata wallet, bytes32 digest) external returns (uint8 v, bytes32 r, bytes32 s);
 
+    /// Signs `digest` with `privateKey` using the secp256r1 curve.
+    #[cheatcode(group = Utilities)]
+    function signP256(uint256 privateKey, bytes32 digest) external pure returns (bytes32 r, bytes32 s);
+
+    /// Derives the secp256r1 public key from the provided `privateKey`.
+    #[cheatcode(group = Utilities)]
+    function publicKeyP256(uint256 privateKey) external pure returns (uint256 publicKeyX, uint256 publicKeyY);
+
     /// Derive a private key from a provided mnenomic string (or mnenomic file path)
     /// at the derivation path `m/44'/60'/0'/0/{index}`.
     #[cheatcode(group = Utilities)]
@@ -1255,5 +1420,77 @@ interface Vm {
     /// Compute the address of a contract created with CREATE2 using the default CREATE2 deployer.
     #[cheatcode(group = Utilities)]
     function computeCreate2Address(bytes32 salt, bytes32 initCodeHash) external pure returns (address);
+
+    // ======== zkSync ========
+
+    /// Returns true if the current execution is running in the zkEVM, else returns false.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function isZkVm() external view returns (bool isZk);
+
+    /// Sets the verbosity of the zkEVM tracer diagnostics for the remainder of the test.
+    /// `0` disables them, `1` logs far calls and returns, `2` additionally logs every executed opcode.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function zkSetTracing(uint8 level) external;
+
+    /// Returns the zkSync protocol version the zkEVM runs and the name of the multivm version executing it,
+    /// e.g. to branch around protocol-dependent behavior.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function zkProtocolVersion() external view returns (uint16 protocolVersion, string memory vmVersion);
+
+    /// Caps the ergs forwarded to the next call, to test out-of-gas handling in the zkEVM.
+    /// Calls to the cheatcode address and system contracts are not affected.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function zkSetNextCallGas(uint256 gas) external;
+
+    /// Makes the next write to the given storage slot fail in the zkEVM, reverting the call frame
+    /// performing it.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function zkFailNextStorageWrite(address target, bytes32 slot) external;
+
+    /// Overrides the given storage slots in the zkEVM until the next call returns, without writing
+    /// them. The writes the call makes are kept.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function withStateOverrides(StorageOverride[] calldata overrides) external;
+
+    /// Overrides the given balances, code and storage slots in the zkEVM until the next call
+    /// returns, without writing them. The writes the call makes are kept.
+    #[cheatcode(group = Testing, safety = Unsafe)]
+    function withStateOverrides(BalanceOverride[] calldata balances, CodeOverride[] calldata codes, StorageOverride[] calldata slots) external;
+
+    /// Reverts unless the code deployed at `account` has the bytecode hash of the given zksolc
+    /// artifact, reporting both hashes otherwise. Takes in the relative path to the json file in
+    /// `zkout`, or `File.sol:Contract`.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function zkAssertCodeMatchesArtifact(address account, string calldata artifactPath) external view;
+
+    /// Sets how the events expected with `expectEmit` are matched for the remainder of the test.
+    /// `0` expects them in order with other events in between (the default), `1` expects them in
+    /// order and consecutively, `2` expects them in any order.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function zkSetEmitOrdering(uint8 ordering) external;
+
+    /// Rejects the current fuzz run, like a failed `assume`, if the test reverts after a call ran
+    /// out of gas in the zkEVM, so that extreme inputs don't report failures.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function assumeNoZkOutOfGas() external;
+
+    /// Rejects the current fuzz run, like a failed `assume`, if the test uses more than `gas` zkEVM
+    /// gas from now on, so that fuzz inputs too expensive for the era limits are skipped.
+    #[cheatcode(group = Testing, safety = Safe)]
+    function assumeZkGasBelow(uint256 gas) external;
+
+    /// Sets the max fee per gas of all subsequently broadcasted transactions.
+    #[cheatcode(group = Scripting)]
+    function zkSetMaxFeePerGas(uint256 maxFeePerGas) external;
+
+    /// Sets the gas per pubdata limit of all subsequently broadcasted transactions.
+    #[cheatcode(group = Scripting)]
+    function zkSetGasPerPubdataLimit(uint256 gasPerPubdataLimit) external;
+
+    /// Builds the L1 call finalizing the withdrawal of the given L2 transaction, fetching the proof
+    /// of its message with `zks_getL2ToL1LogProof`. Takes in the RPC url or alias of the zkSync
+    /// chain, and returns the L1 contract to call along with the calldata.
+    #[cheatcode(group = Scripting)]
+    function zkFinalizeWithdrawal(string calldata urlOrAlias, bytes32 withdrawalHash) external returns (address l1Target, bytes memory data);
 }
 }
@@ -254,7 +254,7 @@ fn env_array_default<T: SolValue>(key: &str, delim: &str, default: &T, ty: &DynS
 }
 
 fn get_env(key: &str) -> Result<String> {
-    match env::var(key) {
+    match foundry_common::env_reads::var(key) {
         Ok(val) => Ok(val),
         Err(env::VarError::NotPresent) => Err(fmt_err!("environment variable {key:?} not found")),
         Err(env::VarError::NotUnicode(s)) => {
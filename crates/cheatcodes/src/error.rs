@@ -299,9 +299,33 @@ impl_from!(
     WalletError,
 );
 
+/// Describes why `calldata` sent to the cheatcode address could not be decoded.
+///
+/// If the selector belongs to a cheatcode, the error names the cheatcode and its expected
+/// arguments, otherwise it hints that the cheatcode is not supported yet.
+#[cold]
+pub fn decode_error(calldata: &[u8], error: &alloy_sol_types::Error) -> Error {
+    let Some(selector) = calldata.get(..4) else {
+        return fmt_err!("cheatcode calldata is too short: {}", hex::encode_prefixed(calldata))
+    };
+    match Vm::CHEATCODES.iter().find(|cheatcode| cheatcode.func.selector_bytes == selector) {
+        Some(cheatcode) => fmt_err!(
+            "failed to decode the arguments of `{}`, expected `{}`: {error}",
+            cheatcode.func.signature,
+            cheatcode.func.declaration,
+        ),
+        None => fmt_err!(
+            "unknown cheatcode with selector {}; \
+             it may belong to a cheatcode that is not supported yet",
+            hex::encode_prefixed(selector),
+        ),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloy_sol_types::{SolCall, SolInterface};
 
     #[test]
     fn encode() {
@@ -312,4 +336,22 @@ mod tests {
         assert_eq!(Error::from(b"hello").abi_encode(), b"hello");
         assert_eq!(Error::encode(b"hello"), b"hello"[..]);
     }
+
+    #[test]
+    fn decode_error_names_cheatcode() {
+        let calldata = Vm::warpCall::SELECTOR.to_vec();
+        let error = Vm::VmCalls::abi_decode(&calldata, true).unwrap_err();
+        let message = decode_error(&calldata, &error).to_string();
+        assert!(message.contains("`warp(uint256)`"), "{message}");
+        assert!(message.contains("function warp(uint256 newTimestamp) external;"), "{message}");
+    }
+
+    #[test]
+    fn decode_error_unknown_selector() {
+        let calldata = [0xde, 0xad, 0xbe, 0xef];
+        let error = Vm::VmCalls::abi_decode(&calldata, true).unwrap_err();
+        let message = decode_error(&calldata, &error).to_string();
+        assert!(message.contains("0xdeadbeef"), "{message}");
+        assert!(message.contains("not supported yet"), "{message}");
+    }
 }
@@ -27,6 +27,15 @@ pub struct RecordAccess {
     pub writes: HashMap<Address, Vec<U256>>,
 }
 
+/// A log recorded after `recordLogs`.
+#[derive(Clone, Debug)]
+pub struct RecordedLog {
+    /// The log.
+    pub log: Log,
+    /// Depth of the call that emitted the log.
+    pub depth: u64,
+}
+
 /// Records `deal` cheatcodes
 #[derive(Debug, Clone)]
 pub struct DealRecord {
@@ -53,6 +62,57 @@ impl Cheatcode for getNonce_0Call {
     }
 }
 
+impl Cheatcode for erc20BalanceOfCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { token: _, account: _ } = self;
+        bail!("`erc20BalanceOf` is only supported in the zkEVM")
+    }
+}
+
+impl Cheatcode for erc20AllowanceCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { token: _, owner: _, spender: _ } = self;
+        bail!("`erc20Allowance` is only supported in the zkEVM")
+    }
+}
+
+impl Cheatcode for erc721BalanceOfCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { token: _, owner: _ } = self;
+        bail!("`erc721BalanceOf` is only supported in the zkEVM")
+    }
+}
+
+impl Cheatcode for erc721OwnerOfCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { token: _, tokenId: _ } = self;
+        bail!("`erc721OwnerOf` is only supported in the zkEVM")
+    }
+}
+
+impl Cheatcode for erc721GetApprovedCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { token: _, tokenId: _ } = self;
+        bail!("`erc721GetApproved` is only supported in the zkEVM")
+    }
+}
+
+impl Cheatcode for erc721IsApprovedForAllCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { token: _, owner: _, operator: _ } = self;
+        bail!("`erc721IsApprovedForAll` is only supported in the zkEVM")
+    }
+}
+
+impl Cheatcode for getDeployedCode_1Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { account } = *self;
+        let (account, _) = ccx.data.journaled_state.load_code(account, ccx.data.db)?;
+        let code = account.info.code.as_ref().map(Bytecode::original_bytes).unwrap_or_default();
+        Ok(code.abi_encode())
+    }
+}
+
 impl Cheatcode for loadCall {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { target, slot } = *self;
@@ -126,10 +186,27 @@ impl Cheatcode for recordLogsCall {
     }
 }
 
-impl Cheatcode for getRecordedLogsCall {
+impl Cheatcode for getRecordedLogs_0Call {
     fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self {} = self;
-        Ok(state.recorded_logs.replace(Default::default()).unwrap_or_default().abi_encode())
+        let logs = state.recorded_logs.replace(Default::default()).unwrap_or_default();
+        Ok(logs.into_iter().map(|recorded| recorded.log).collect::<Vec<_>>().abi_encode())
+    }
+}
+
+impl Cheatcode for getRecordedLogs_1Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { emitters, maxDepth } = self;
+        let caller_depth = ccx.data.journaled_state.depth();
+        let max_depth = maxDepth.saturating_to::<u64>();
+        let logs = ccx.state.recorded_logs.replace(Default::default()).unwrap_or_default();
+        Ok(logs
+            .into_iter()
+            .filter(|recorded| emitters.is_empty() || emitters.contains(&recorded.log.emitter))
+            .filter(|recorded| recorded.depth.saturating_sub(caller_depth) <= max_depth)
+            .map(|recorded| recorded.log)
+            .collect::<Vec<_>>()
+            .abi_encode())
     }
 }
 
@@ -218,6 +295,21 @@ impl Cheatcode for txGasPriceCall {
     }
 }
 
+impl Cheatcode for setTxOriginCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { newOrigin } = self;
+        ccx.data.env.tx.caller = *newOrigin;
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for txOriginCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self {} = self;
+        Ok(ccx.data.env.tx.caller.abi_encode())
+    }
+}
+
 impl Cheatcode for warpCall {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { newTimestamp } = self;
@@ -299,6 +391,20 @@ impl Cheatcode for storeCall {
     }
 }
 
+impl Cheatcode for storeBatchCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { targets, slots, values } = self;
+        ensure!(
+            targets.len() == slots.len() && slots.len() == values.len(),
+            "targets, slots and values must have the same length"
+        );
+        for ((target, slot), value) in targets.iter().zip(slots).zip(values) {
+            storeCall { target: *target, slot: *slot, value: *value }.apply_full(ccx)?;
+        }
+        Ok(Default::default())
+    }
+}
+
 impl Cheatcode for coolCall {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { target } = self;
@@ -340,6 +446,27 @@ impl Cheatcode for revertToCall {
     }
 }
 
+impl Cheatcode for snapshotNamedCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { name } = self;
+        let snapshot_id = ccx.data.db.snapshot(&ccx.data.journaled_state, ccx.data.env);
+        ccx.state.named_snapshots.insert(name.clone(), snapshot_id);
+        Ok(snapshot_id.abi_encode())
+    }
+}
+
+impl Cheatcode for revertToNamedCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { name } = self;
+        let Some(&snapshot_id) = ccx.state.named_snapshots.get(name) else {
+            return Ok(false.abi_encode())
+        };
+        // the snapshot and all snapshots taken after it are deleted
+        ccx.state.named_snapshots.retain(|_, id| *id < snapshot_id);
+        revertToCall { snapshotId: snapshot_id }.apply_full(ccx)
+    }
+}
+
 impl Cheatcode for startStateDiffRecordingCall {
     fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self {} = self;
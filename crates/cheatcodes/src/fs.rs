@@ -254,7 +254,7 @@ impl Cheatcode for getCodeCall {
     }
 }
 
-impl Cheatcode for getDeployedCodeCall {
+impl Cheatcode for getDeployedCode_0Call {
     fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { artifactPath: path } = self;
         let object = read_bytecode(state, path)?;
@@ -5,7 +5,7 @@ use crate::{
         mapping::{self, MappingSlots},
         mock::{MockCallDataContext, MockCallReturnData},
         prank::Prank,
-        DealRecord, RecordAccess,
+        DealRecord, RecordAccess, RecordedLog,
     },
     script::Broadcast,
     test::expect::{
@@ -33,6 +33,7 @@ use revm::{
     primitives::{BlockEnv, CreateScheme, TransactTo},
     EVMData, Inspector,
 };
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{
     collections::{BTreeMap, HashMap, VecDeque},
@@ -84,6 +85,29 @@ pub struct BroadcastableTransaction {
 
     /// Additional factory deps of the tx
     pub factory_deps: Vec<Vec<u8>>,
+
+    /// Fee parameters pinned by the script, if any.
+    pub fee_overrides: FeeOverrides,
+}
+
+/// Fee parameters set from a script, which take precedence over the ones estimated when
+/// broadcasting a [BroadcastableTransaction].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeOverrides {
+    /// The gas price, set with `vm.txGasPrice`.
+    pub gas_price: Option<ethers_core::types::U256>,
+    /// The max fee per gas, set with `vm.zkSetMaxFeePerGas`.
+    pub max_fee_per_gas: Option<ethers_core::types::U256>,
+    /// The gas per pubdata limit, set with `vm.zkSetGasPerPubdataLimit`.
+    pub gas_per_pubdata_limit: Option<ethers_core::types::U256>,
+}
+
+impl FeeOverrides {
+    /// Returns true if no fee parameter is overridden.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
 }
 
 /// List of transactions that can be broadcasted.
@@ -157,7 +181,7 @@ pub struct Cheatcodes {
     pub recorded_account_diffs_stack: Option<Vec<Vec<AccountAccess>>>,
 
     /// Recorded logs
-    pub recorded_logs: Option<Vec<crate::Vm::Log>>,
+    pub recorded_logs: Option<Vec<RecordedLog>>,
 
     /// Mocked calls
     // **Note**: inner must a BTreeMap because of special `Ord` impl for `MockCallDataContext`
@@ -198,6 +222,9 @@ pub struct Cheatcodes {
     /// All recorded ETH `deal`s.
     pub eth_deals: Vec<DealRecord>,
 
+    /// Snapshot ids by the names given to `snapshotNamed`.
+    pub named_snapshots: HashMap<String, U256>,
+
     /// Holds the stored gas info for when we pause gas metering. It is an `Option<Option<..>>`
     /// because the `call` callback in an `Inspector` doesn't get access to
     /// the `revm::Interpreter` which holds the `revm::Gas` struct that
@@ -225,7 +252,18 @@ impl Cheatcodes {
     /// Creates a new `Cheatcodes` with the given settings.
     #[inline]
     pub fn new(config: Arc<CheatsConfig>) -> Self {
-        Self { config, fs_commit: true, ..Default::default() }
+        let recorded_logs = config.record_logs.then(Vec::new);
+        Self { config, fs_commit: true, recorded_logs, ..Default::default() }
+    }
+
+    /// Counts a revert that matched `expected_revert`, expecting the next call to revert as well
+    /// until the expected number of reverts is reached.
+    fn rearm_expected_revert(&mut self, expected_revert: &mut ExpectedRevert) {
+        expected_revert.actual_count += 1;
+        if expected_revert.actual_count < expected_revert.count {
+            expected_revert.reverted_by = None;
+            self.expected_revert = Some(expected_revert.clone());
+        }
     }
 
     fn apply_cheatcode<DB: DatabaseExt>(
@@ -234,9 +272,17 @@ impl Cheatcodes {
         call: &CallInputs,
     ) -> Result {
         // decode the cheatcode call
-        let decoded = Vm::VmCalls::abi_decode(&call.input, false)?;
+        let decoded = Vm::VmCalls::abi_decode(&call.input, false)
+            .map_err(|e| crate::decode_error(&call.input, &e))?;
         let caller = call.context.caller;
 
+        if let Some(cheatcode) =
+            Vm::CHEATCODES.iter().find(|c| c.func.selector_bytes == decoded.selector())
+        {
+            let id = cheatcode.func.id;
+            ensure!(self.config.cheatcode_access.is_enabled(id), "`{id}` is disabled by config");
+        }
+
         // ensure the caller is allowed to execute cheatcodes,
         // but only if the backend is in forking mode
         data.db.ensure_cheatcode_access_forking_mode(caller)?;
@@ -646,17 +692,16 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
         }
     }
 
-    fn log(&mut self, _: &mut EVMData<'_, DB>, address: &Address, topics: &[B256], data: &Bytes) {
+    fn log(&mut self, evm: &mut EVMData<'_, DB>, address: &Address, topics: &[B256], data: &Bytes) {
         if !self.expected_emits.is_empty() {
             expect::handle_expect_emit(self, address, topics, data);
         }
 
         // Stores this log if `recordLogs` has been called
         if let Some(storage_recorded_logs) = &mut self.recorded_logs {
-            storage_recorded_logs.push(Vm::Log {
-                topics: topics.to_vec(),
-                data: data.to_vec(),
-                emitter: *address,
+            storage_recorded_logs.push(RecordedLog {
+                log: Vm::Log { topics: topics.to_vec(), data: data.to_vec(), emitter: *address },
+                depth: evm.journaled_state.depth(),
             });
         }
     }
@@ -787,6 +832,7 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                     let tx = BroadcastableTransaction {
                         rpc: data.db.active_fork_url(),
                         factory_deps: vec![],
+                        fee_overrides: Default::default(),
                         transaction: TypedTransaction::Legacy(TransactionRequest {
                             from: Some(broadcast.new_origin.to_ethers()),
                             to: Some(NameOrAddress::Address(call.contract.to_ethers())),
@@ -908,20 +954,23 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
         }
 
         // Handle expected reverts
-        if let Some(expected_revert) = &self.expected_revert {
+        if let Some(expected_revert) = &mut self.expected_revert {
+            // the innermost reverting call is the one the revert originated from
+            if !status.is_ok() {
+                expected_revert.reverted_by.get_or_insert(call.contract);
+            }
             if data.journaled_state.depth() <= expected_revert.depth {
-                let expected_revert = std::mem::take(&mut self.expected_revert).unwrap();
-                return match expect::handle_expect_revert(
-                    false,
-                    expected_revert.reason.as_deref(),
-                    status,
-                    retdata,
-                ) {
+                let mut expected_revert = std::mem::take(&mut self.expected_revert).unwrap();
+                return match expect::handle_expect_revert(false, &expected_revert, status, retdata)
+                {
                     Err(error) => {
                         trace!(expected=?expected_revert, ?error, ?status, "Expected revert mismatch");
                         (InstructionResult::Revert, remaining_gas, error.abi_encode().into())
                     }
-                    Ok((_, retdata)) => (InstructionResult::Return, remaining_gas, retdata),
+                    Ok((_, retdata)) => {
+                        self.rearm_expected_revert(&mut expected_revert);
+                        (InstructionResult::Return, remaining_gas, retdata)
+                    }
                 }
             }
         }
@@ -1152,6 +1201,7 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
                     let tx = BroadcastableTransaction {
                         rpc: data.db.active_fork_url(),
                         factory_deps: vec![],
+                        fee_overrides: Default::default(),
                         transaction: TypedTransaction::Legacy(TransactionRequest {
                             from: Some(broadcast.new_origin.to_ethers()),
                             to: to.map(|a| NameOrAddress::Address(a.to_ethers())),
@@ -1243,16 +1293,17 @@ impl<DB: DatabaseExt> Inspector<DB> for Cheatcodes {
         }
 
         // Handle expected reverts
-        if let Some(expected_revert) = &self.expected_revert {
+        if let Some(expected_revert) = &mut self.expected_revert {
+            if !status.is_ok() {
+                if let Some(address) = address {
+                    expected_revert.reverted_by.get_or_insert(address);
+                }
+            }
             if data.journaled_state.depth() <= expected_revert.depth {
-                let expected_revert = std::mem::take(&mut self.expected_revert).unwrap();
-                return match expect::handle_expect_revert(
-                    true,
-                    expected_revert.reason.as_deref(),
-                    status,
-                    retdata,
-                ) {
+                let mut expected_revert = std::mem::take(&mut self.expected_revert).unwrap();
+                return match expect::handle_expect_revert(true, &expected_revert, status, retdata) {
                     Ok((address, retdata)) => {
+                        self.rearm_expected_revert(&mut expected_revert);
                         (InstructionResult::Return, address, remaining_gas, retdata)
                     }
                     Err(err) => {
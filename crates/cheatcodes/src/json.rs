@@ -12,10 +12,7 @@ use std::{borrow::Cow, collections::BTreeMap, fmt::Write};
 impl Cheatcode for keyExistsCall {
     fn apply(&self, _state: &mut Cheatcodes) -> Result {
         let Self { json, key } = self;
-        let json = parse_json_str(json)?;
-        let values = select(&json, key)?;
-        let exists = !values.is_empty();
-        Ok(exists.abi_encode())
+        key_exists(json, key)
     }
 }
 
@@ -134,16 +131,7 @@ impl Cheatcode for parseJsonBytes32ArrayCall {
 impl Cheatcode for parseJsonKeysCall {
     fn apply(&self, _state: &mut Cheatcodes) -> Result {
         let Self { json, key } = self;
-        let json = parse_json_str(json)?;
-        let values = select(&json, key)?;
-        let [value] = values[..] else {
-            bail!("key {key:?} must return exactly one JSON object");
-        };
-        let Value::Object(object) = value else {
-            bail!("JSON value at {key:?} is not an object");
-        };
-        let keys = object.keys().collect::<Vec<_>>();
-        Ok(keys.abi_encode())
+        parse_json_keys(json, key)
     }
 }
 
@@ -280,14 +268,18 @@ impl Cheatcode for writeJson_1Call {
     }
 }
 
-fn parse_json(json: &str, path: &str) -> Result {
+/// Parses the values at the JSONPath `path` of `json`, and ABI-encodes them as `bytes`, as
+/// returned by `vm.parseJson`.
+pub fn parse_json(json: &str, path: &str) -> Result {
     let value = parse_json_str(json)?;
     let selected = select(&value, path)?;
     let sol = json_to_sol(&selected)?;
     Ok(encode(sol))
 }
 
-fn parse_json_coerce(json: &str, path: &str, ty: &DynSolType) -> Result {
+/// Parses the value at the JSONPath `path` of `json` as `ty`, or as an array of `ty` if it's an
+/// array, and ABI-encodes it, as returned by the typed `vm.parseJson*` getters.
+pub fn parse_json_coerce(json: &str, path: &str, ty: &DynSolType) -> Result {
     let value = parse_json_str(json)?;
     let values = select(&value, path)?;
     ensure!(!values.is_empty(), "no matching value found at {path:?}");
@@ -311,6 +303,30 @@ fn parse_json_coerce(json: &str, path: &str, ty: &DynSolType) -> Result {
     }
 }
 
+/// Returns whether the JSONPath `key` matches any value of `json`, ABI-encoded as returned by
+/// `vm.keyExists`.
+pub fn key_exists(json: &str, key: &str) -> Result {
+    let json = parse_json_str(json)?;
+    let values = select(&json, key)?;
+    let exists = !values.is_empty();
+    Ok(exists.abi_encode())
+}
+
+/// Returns the keys of the object at the JSONPath `key` of `json`, ABI-encoded as returned by
+/// `vm.parseJsonKeys`.
+pub fn parse_json_keys(json: &str, key: &str) -> Result {
+    let json = parse_json_str(json)?;
+    let values = select(&json, key)?;
+    let [value] = values[..] else {
+        bail!("key {key:?} must return exactly one JSON object");
+    };
+    let Value::Object(object) = value else {
+        bail!("JSON value at {key:?} is not an object");
+    };
+    let keys = object.keys().collect::<Vec<_>>();
+    Ok(keys.abi_encode())
+}
+
 fn parse_json_str(json: &str) -> Result<Value> {
     serde_json::from_str(json).map_err(|e| fmt_err!("failed parsing JSON: {e}"))
 }
@@ -465,7 +481,8 @@ fn serialize_json(
     Ok(stringified.abi_encode())
 }
 
-fn array_str<I, T>(values: I, quoted: bool) -> String
+/// Formats the values as a JSON array, quoting each of them if `quoted` is set.
+pub fn array_str<I, T>(values: I, quoted: bool) -> String
 where
     I: IntoIterator,
     I::IntoIter: ExactSizeIterator<Item = T>,
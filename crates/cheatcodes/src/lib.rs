@@ -18,13 +18,15 @@ pub use spec::{CheatcodeDef, Vm};
 
 #[macro_use]
 mod error;
-pub use error::{Error, ErrorKind, Result};
+pub use error::{decode_error, Error, ErrorKind, Result};
 
 mod config;
 pub use config::CheatsConfig;
 
 mod inspector;
-pub use inspector::{BroadcastableTransaction, BroadcastableTransactions, Cheatcodes, Context};
+pub use inspector::{
+    BroadcastableTransaction, BroadcastableTransactions, Cheatcodes, Context, FeeOverrides,
+};
 
 mod env;
 mod evm;
@@ -34,7 +36,9 @@ mod script;
 mod string;
 mod test;
 mod utils;
+mod zk;
 
+pub use json::{array_str, key_exists, parse_json, parse_json_coerce, parse_json_keys};
 pub use test::expect::ExpectedCallTracker;
 
 /// Cheatcode implementation.
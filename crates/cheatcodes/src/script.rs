@@ -1,10 +1,12 @@
 //! Implementations of [`Scripting`](crate::Group::Scripting) cheatcodes.
 
-use crate::{Cheatcode, CheatsCtxt, DatabaseExt, Result, Vm::*};
+use crate::{Cheatcode, Cheatcodes, CheatsCtxt, DatabaseExt, Result, Vm::*};
 use alloy_primitives::{Address, U256};
+use alloy_sol_types::SolValue;
 use ethers_signers::Signer;
 use foundry_common::types::ToAlloy;
 use foundry_config::Config;
+use itertools::Itertools;
 
 impl Cheatcode for broadcast_0Call {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
@@ -27,6 +29,13 @@ impl Cheatcode for broadcast_2Call {
     }
 }
 
+impl Cheatcode for broadcastWalletCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { senderIndex } = self;
+        broadcast_wallet(ccx, senderIndex, true)
+    }
+}
+
 impl Cheatcode for startBroadcast_0Call {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self {} = self;
@@ -48,6 +57,13 @@ impl Cheatcode for startBroadcast_2Call {
     }
 }
 
+impl Cheatcode for startBroadcastWalletCall {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { senderIndex } = self;
+        broadcast_wallet(ccx, senderIndex, false)
+    }
+}
+
 impl Cheatcode for stopBroadcastCall {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self {} = self;
@@ -59,6 +75,26 @@ impl Cheatcode for stopBroadcastCall {
     }
 }
 
+impl Cheatcode for getScriptWalletsCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self {} = self;
+        Ok(script_wallets(state).abi_encode())
+    }
+}
+
+/// Returns the wallets the script can broadcast with: the ones it was run with, followed by the
+/// remembered ones.
+fn script_wallets(state: &Cheatcodes) -> Vec<Address> {
+    state
+        .config
+        .script_wallets
+        .iter()
+        .copied()
+        .chain(state.script_wallets.iter().map(|wallet| wallet.address().to_alloy()))
+        .unique()
+        .collect()
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct Broadcast {
     /// Address of the transaction origin
@@ -102,6 +138,22 @@ fn broadcast<DB: DatabaseExt>(
 /// Sets up broadcasting from a script with the sender derived from `private_key`.
 /// Adds this private key to `state`'s `script_wallets` vector to later be used for signing
 /// if broadcast is successful.
+/// Sets up broadcasting with the script wallet at `sender_index` in [script_wallets].
+fn broadcast_wallet<DB: DatabaseExt>(
+    ccx: &mut CheatsCtxt<DB>,
+    sender_index: &U256,
+    single_call: bool,
+) -> Result {
+    let wallets = script_wallets(ccx.state);
+    let Some(&new_origin) = usize::try_from(*sender_index).ok().and_then(|i| wallets.get(i)) else {
+        bail!(
+            "sender index {sender_index} is out of range, the script has {} wallets",
+            wallets.len()
+        )
+    };
+    broadcast(ccx, Some(&new_origin), single_call)
+}
+
 fn broadcast_key<DB: DatabaseExt>(
     ccx: &mut CheatsCtxt<DB>,
     private_key: &U256,
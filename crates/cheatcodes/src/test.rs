@@ -32,10 +32,17 @@ impl Cheatcode for breakpoint_1Call {
     }
 }
 
+impl Cheatcode for getFoundryVersionCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self {} = self;
+        Ok(env!("CARGO_PKG_VERSION").abi_encode())
+    }
+}
+
 impl Cheatcode for rpcUrlCall {
     fn apply(&self, state: &mut Cheatcodes) -> Result {
         let Self { rpcAlias } = self;
-        state.config.rpc_url(rpcAlias).map(|url| url.abi_encode())
+        state.config.redacted_rpc_url(rpcAlias).map(|url| url.abi_encode())
     }
 }
 
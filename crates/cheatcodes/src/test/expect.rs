@@ -59,6 +59,14 @@ pub struct ExpectedRevert {
     pub reason: Option<Vec<u8>>,
     /// The depth at which the revert is expected
     pub depth: u64,
+    /// The address expected to revert, None being any
+    pub reverter: Option<Address>,
+    /// The innermost address that reverted during the current call
+    pub reverted_by: Option<Address>,
+    /// The number of consecutive calls expected to revert
+    pub count: u64,
+    /// The number of calls that reverted as expected so far
+    pub actual_count: u64,
 }
 
 #[derive(Clone, Debug)]
@@ -219,6 +227,20 @@ impl Cheatcode for expectEmit_3Call {
     }
 }
 
+impl Cheatcode for expectEmit_4Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { topicWildcards, checkData } = *self;
+        let [checkTopic1, checkTopic2, checkTopic3] =
+            [0, 1, 2].map(|bit| topicWildcards & (1 << bit) == 0);
+        expect_emit(
+            ccx.state,
+            ccx.data.journaled_state.depth(),
+            [checkTopic1, checkTopic2, checkTopic3, checkData],
+            None,
+        )
+    }
+}
+
 impl Cheatcode for expectRevert_0Call {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self {} = self;
@@ -240,6 +262,53 @@ impl Cheatcode for expectRevert_2Call {
     }
 }
 
+impl Cheatcode for expectRevert_3Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { count } = *self;
+        expect_revert_count(ccx.state, None, None, count, ccx.data.journaled_state.depth())
+    }
+}
+
+impl Cheatcode for expectRevert_4Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { revertData, count } = self;
+        let depth = ccx.data.journaled_state.depth();
+        expect_revert_count(ccx.state, Some(revertData.as_ref()), None, *count, depth)
+    }
+}
+
+impl Cheatcode for expectRevert_5Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { revertData, count } = self;
+        let depth = ccx.data.journaled_state.depth();
+        expect_revert_count(ccx.state, Some(revertData), None, *count, depth)
+    }
+}
+
+impl Cheatcode for expectRevert_6Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { reverter, count } = *self;
+        let depth = ccx.data.journaled_state.depth();
+        expect_revert_count(ccx.state, None, Some(reverter), count, depth)
+    }
+}
+
+impl Cheatcode for expectRevert_7Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { revertData, reverter, count } = self;
+        let depth = ccx.data.journaled_state.depth();
+        expect_revert_count(ccx.state, Some(revertData.as_ref()), Some(*reverter), *count, depth)
+    }
+}
+
+impl Cheatcode for expectRevert_8Call {
+    fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
+        let Self { revertData, reverter, count } = self;
+        let depth = ccx.data.journaled_state.depth();
+        expect_revert_count(ccx.state, Some(revertData), Some(*reverter), *count, depth)
+    }
+}
+
 impl Cheatcode for expectSafeMemoryCall {
     fn apply_full<DB: DatabaseExt>(&self, ccx: &mut CheatsCtxt<DB>) -> Result {
         let Self { min, max } = *self;
@@ -431,17 +500,35 @@ pub(crate) fn handle_expect_emit(
 }
 
 fn expect_revert(state: &mut Cheatcodes, reason: Option<&[u8]>, depth: u64) -> Result {
+    expect_revert_count(state, reason, None, 1, depth)
+}
+
+fn expect_revert_count(
+    state: &mut Cheatcodes,
+    reason: Option<&[u8]>,
+    reverter: Option<Address>,
+    count: u64,
+    depth: u64,
+) -> Result {
     ensure!(
         state.expected_revert.is_none(),
         "you must call another function prior to expecting a second revert"
     );
-    state.expected_revert = Some(ExpectedRevert { reason: reason.map(<[_]>::to_vec), depth });
+    ensure!(count > 0, "the expected revert count must be greater than 0");
+    state.expected_revert = Some(ExpectedRevert {
+        reason: reason.map(<[_]>::to_vec),
+        depth,
+        reverter,
+        reverted_by: None,
+        count,
+        actual_count: 0,
+    });
     Ok(Default::default())
 }
 
 pub(crate) fn handle_expect_revert(
     is_create: bool,
-    expected_revert: Option<&[u8]>,
+    expected: &ExpectedRevert,
     status: InstructionResult,
     retdata: Bytes,
 ) -> Result<(Option<Address>, Bytes)> {
@@ -453,10 +540,27 @@ pub(crate) fn handle_expect_revert(
         }
     };
 
-    ensure!(!matches!(status, return_ok!()), "call did not revert as expected");
+    if matches!(status, return_ok!()) {
+        if expected.actual_count > 0 {
+            bail!(
+                "call did not revert as expected: expected {} reverts, but got {}",
+                expected.count,
+                expected.actual_count
+            );
+        }
+        bail!("call did not revert as expected");
+    }
+
+    if let Some(reverter) = expected.reverter {
+        if expected.reverted_by != Some(reverter) {
+            let reverted_by =
+                expected.reverted_by.map_or_else(|| "unknown".to_string(), |a| a.to_string());
+            bail!("Reverter != expected reverter: {reverted_by} != {reverter}");
+        }
+    }
 
     // If None, accept any revert
-    let Some(expected_revert) = expected_revert else { return Ok(success_return()) };
+    let Some(expected_revert) = expected.reason.as_deref() else { return Ok(success_return()) };
 
     if !expected_revert.is_empty() && retdata.is_empty() {
         bail!("call reverted as expected, but without data");
@@ -17,6 +17,7 @@ use ethers_signers::{
 };
 use foundry_common::types::{ToAlloy, ToEthers};
 use foundry_evm_core::constants::DEFAULT_CREATE2_DEPLOYER;
+use p256::{ecdsa::signature::hazmat::PrehashSigner, NistP256};
 
 /// The BIP32 default derivation path prefix.
 const DEFAULT_DERIVATION_PATH_PREFIX: &str = "m/44'/60'/0'/0/";
@@ -56,6 +57,28 @@ impl Cheatcode for sign_1Call {
     }
 }
 
+impl Cheatcode for signP256Call {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { privateKey, digest } = self;
+        let signature: p256::ecdsa::Signature =
+            parse_private_key_p256(privateKey)?.sign_prehash(digest.as_slice())?;
+        let r: [u8; 32] = signature.r().to_bytes().into();
+        let s: [u8; 32] = signature.s().to_bytes().into();
+        Ok((r, s).abi_encode())
+    }
+}
+
+impl Cheatcode for publicKeyP256Call {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { privateKey } = self;
+        let key = parse_private_key_p256(privateKey)?;
+        let pub_key = key.verifying_key().as_affine().to_encoded_point(false);
+        let pub_key_x = U256::from_be_bytes((*pub_key.x().unwrap()).into());
+        let pub_key_y = U256::from_be_bytes((*pub_key.y().unwrap()).into());
+        Ok((pub_key_x, pub_key_y).abi_encode())
+    }
+}
+
 impl Cheatcode for deriveKey_0Call {
     fn apply(&self, _state: &mut Cheatcodes) -> Result {
         let Self { mnemonic, index } = self;
@@ -182,6 +205,17 @@ pub(super) fn parse_private_key(private_key: &U256) -> Result<SigningKey> {
     SigningKey::from_bytes((&bytes).into()).map_err(Into::into)
 }
 
+pub(super) fn parse_private_key_p256(private_key: &U256) -> Result<p256::ecdsa::SigningKey> {
+    ensure!(*private_key != U256::ZERO, "private key cannot be 0");
+    ensure!(
+        *private_key < U256::from_limbs(*NistP256::ORDER.as_words()),
+        "private key must be less than the secp256r1 curve order \
+         (115792089210356248762697446949407573529996955224135760342422259061068512044369)",
+    );
+    let bytes = private_key.to_be_bytes();
+    p256::ecdsa::SigningKey::from_bytes((&bytes).into()).map_err(Into::into)
+}
+
 pub(super) fn parse_wallet(private_key: &U256) -> Result<LocalWallet> {
     parse_private_key(private_key).map(LocalWallet::from)
 }
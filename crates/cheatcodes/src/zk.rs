@@ -0,0 +1,137 @@
+//! Implementations of zkSync-specific cheatcodes when executing in the EVM.
+//!
+//! The actual implementations live in the `era_cheatcodes` crate, these only define the behavior
+//! of the cheatcodes when a test or script is not run in the zkEVM.
+
+use crate::{Cheatcode, Cheatcodes, Result, Vm::*};
+use alloy_primitives::Bytes;
+use alloy_sol_types::SolValue;
+use foundry_evm_core::fork::zksync_provider::finalize_withdrawal_call;
+
+impl Cheatcode for isZkVmCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self {} = self;
+        Ok(false.abi_encode())
+    }
+}
+
+impl Cheatcode for zkSetTracingCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { level: _ } = self;
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for zkProtocolVersionCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self {} = self;
+        bail!("`zkProtocolVersion` is only supported in the zkEVM")
+    }
+}
+
+impl Cheatcode for zkSetMaxFeePerGasCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { maxFeePerGas: _ } = self;
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for zkSetGasPerPubdataLimitCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { gasPerPubdataLimit: _ } = self;
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for zkFinalizeWithdrawalCall {
+    fn apply(&self, state: &mut Cheatcodes) -> Result {
+        let Self { urlOrAlias, withdrawalHash } = self;
+        let url = state.config.rpc_url(urlOrAlias)?;
+        let (target, data) = finalize_withdrawal_call(&url, *withdrawalHash, 0)?;
+        Ok((target, Bytes::from(data)).abi_encode_params())
+    }
+}
+
+impl Cheatcode for zkSetNextCallGasCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { gas: _ } = self;
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for zkFailNextStorageWriteCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { target: _, slot: _ } = self;
+        Ok(Default::default())
+    }
+}
+
+impl Cheatcode for zkAssertCodeMatchesArtifactCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { account: _, artifactPath: _ } = self;
+        bail!("`zkAssertCodeMatchesArtifact` is only supported in the zkEVM")
+    }
+}
+
+impl Cheatcode for zkSetEmitOrderingCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { ordering: _ } = self;
+        bail!("`zkSetEmitOrdering` is only supported in the zkEVM")
+    }
+}
+
+impl Cheatcode for assumeNoZkOutOfGasCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self {} = self;
+        bail!("`assumeNoZkOutOfGas` is only supported in the zkEVM")
+    }
+}
+
+impl Cheatcode for assumeZkGasBelowCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { gas: _ } = self;
+        bail!("`assumeZkGasBelow` is only supported in the zkEVM")
+    }
+}
+
+impl Cheatcode for withStateOverrides_0Call {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { overrides: _ } = self;
+        bail!("`withStateOverrides` is only supported in the zkEVM")
+    }
+}
+
+impl Cheatcode for withStateOverrides_1Call {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { balances: _, codes: _, slots: _ } = self;
+        bail!("`withStateOverrides` is only supported in the zkEVM")
+    }
+}
+
+impl Cheatcode for mockFunctionCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { callee: _, target: _, data: _ } = self;
+        bail!("`mockFunction` is only supported in the zkEVM")
+    }
+}
+
+impl Cheatcode for recordCallsCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self {} = self;
+        bail!("`recordCalls` is only supported in the zkEVM")
+    }
+}
+
+impl Cheatcode for getCallsCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { callee: _, data: _ } = self;
+        bail!("`getCalls` is only supported in the zkEVM")
+    }
+}
+
+impl Cheatcode for diffSnapshotsCall {
+    fn apply(&self, _state: &mut Cheatcodes) -> Result {
+        let Self { fromSnapshotId: _, toSnapshotId: _ } = self;
+        bail!("`diffSnapshots` is only supported in the zkEVM")
+    }
+}
@@ -74,6 +74,7 @@ fn get_empty_session_source() -> SessionSource {
             backend: None,
             traces: false,
             calldata: None,
+            zksync: false,
         },
     )
 }
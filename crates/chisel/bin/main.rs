@@ -51,6 +51,11 @@ pub struct ChiselParser {
     #[clap(long, help_heading = "REPL options")]
     pub prelude: Option<PathBuf>,
 
+    /// Compile the REPL contract with zksolc and execute it in an in-memory era VM, keeping the
+    /// VM state between inputs.
+    #[clap(long, help_heading = "REPL options")]
+    pub zksync: bool,
+
     #[clap(flatten)]
     pub opts: CoreBuildArgs,
 
@@ -106,6 +111,7 @@ async fn main() -> eyre::Result<()> {
         evm_opts,
         backend: None,
         calldata: None,
+        zksync: args.zksync,
     })?;
 
     // Execute prelude Solidity source files
@@ -16,7 +16,10 @@ use foundry_evm::{
     backend::Backend, decode::decode_console_logs, executors::ExecutorBuilder,
     inspectors::CheatsConfig,
 };
-use revm::primitives::SpecId;
+use revm::{
+    interpreter::{InstructionResult, Stack},
+    primitives::SpecId,
+};
 use solang_parser::pt::{self, CodeLocation};
 use std::str::FromStr;
 use yansi::Paint;
@@ -34,6 +37,13 @@ impl SessionSource {
     pub async fn execute(&mut self) -> Result<(Address, ChiselResult)> {
         // Recompile the project and ensure no errors occurred.
         let compiled = self.build()?;
+        if self.config.zksync {
+            if compiled.intermediate.run_func_body()?.is_empty() {
+                // Return a default result if no statements are present.
+                return Ok((Address::ZERO, ChiselResult::default()))
+            }
+            return self.execute_zksync().await
+        }
         if let Some((_, contract)) =
             compiled.clone().compiler_output.contracts_into_iter().find(|(name, _)| name == "REPL")
         {
@@ -118,6 +128,35 @@ impl SessionSource {
         }
     }
 
+    /// Runs the source in the era VM
+    ///
+    /// The source is compiled with zksolc. Like in the EVM, every run executes all the statements
+    /// of the session again, so it starts from the state before the first one. As the VM stack
+    /// and memory can't be inspected, the value of an inspected expression is returned by the
+    /// "run()" function instead, and laid out in the [ChiselResult]'s state as the EVM inspector
+    /// would.
+    async fn execute_zksync(&mut self) -> Result<(Address, ChiselResult)> {
+        let bytecode = self.compile_zksync().await?;
+
+        // the runner takes the session's backend, keep it untouched for the next run
+        let backend = self.config.backend.clone();
+        let mut runner = self.prepare_runner(0).await;
+        let result = runner.run(bytecode);
+        if backend.is_some() {
+            self.config.backend = backend;
+        }
+
+        let (address, mut res) = result?;
+        if res.success && self.is_inspecting() {
+            let mut stack = Stack::new();
+            let _ = stack.push(U256::ZERO);
+            let mut memory = U256::from(res.returned.len()).to_be_bytes_vec();
+            memory.extend_from_slice(&res.returned);
+            res.state = Some((stack, memory, InstructionResult::Return));
+        }
+        Ok((address, res))
+    }
+
     /// Inspect a contract element inside of the current session
     ///
     /// ### Takes
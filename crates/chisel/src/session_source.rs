@@ -4,11 +4,17 @@
 //! the REPL contract's source code. It provides simple compilation, parsing, and
 //! execution helpers.
 
+use alloy_primitives::Bytes;
 use eyre::Result;
 use forge_fmt::solang_ext::SafeUnwrap;
+use foundry_common::{
+    zk_compile::ZkSolc,
+    zksolc_manager::{setup_zksolc_manager, DEFAULT_ZKSOLC_VERSION},
+};
 use foundry_compilers::{
     artifacts::{Source, Sources},
-    CompilerInput, CompilerOutput, EvmVersion, Solc,
+    remappings::{RelativeRemapping, Remapping},
+    Artifact, CompilerInput, CompilerOutput, EvmVersion, Solc,
 };
 use foundry_config::{Config, SolcReq};
 use foundry_evm::{backend::Backend, opts::EvmOpts};
@@ -21,6 +27,10 @@ use yansi::Paint;
 /// Solidity source for the `Vm` interface in [forge-std](https://github.com/foundry-rs/forge-std)
 static VM_SOURCE: &str = include_str!("../../../testdata/cheats/Vm.sol");
 
+/// Statement appended to the `run()` function in zk mode to return the inspected value, as the
+/// EraVM memory can't be read back after the call.
+static ZK_INSPECT_RETURN: &str = "assembly { return(add(inspectoor, 0x20), mload(inspectoor)) }";
+
 /// Intermediate output for the compiled [SessionSource]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct IntermediateOutput {
@@ -76,6 +86,10 @@ pub struct SessionSourceConfig {
     pub traces: bool,
     /// Optionally set calldata for the REPL contract execution
     pub calldata: Option<Vec<u8>>,
+    /// Compile the REPL contract with zksolc and execute it in the era VM, keeping the VM state
+    /// between inputs
+    #[serde(default)]
+    pub zksync: bool,
 }
 
 impl SessionSourceConfig {
@@ -340,6 +354,72 @@ impl SessionSource {
         compiler_input
     }
 
+    /// Returns whether the last statement of the "run()" function is the variable inserted by
+    /// [SessionSource::inspect]
+    pub(crate) fn is_inspecting(&self) -> bool {
+        self.run_code
+            .lines()
+            .last()
+            .map_or(false, |line| line.starts_with("bytes memory inspectoor"))
+    }
+
+    /// Compiles the source with zksolc
+    ///
+    /// The sources are written to a temporary project, since zksolc can only compile files. When
+    /// inspecting, the inspected value is returned from the "run()" function.
+    ///
+    /// ### Returns
+    ///
+    /// The EraVM bytecode of the REPL contract, packed along with its factory dependencies.
+    pub async fn compile_zksync(&self) -> Result<Bytes> {
+        let mut source = self.shallow_clone();
+        if self.is_inspecting() {
+            source.with_run_code(ZK_INSPECT_RETURN);
+        }
+
+        let root = tempfile::tempdir()?;
+        let mut config = self.config.foundry_config.clone();
+        config.src = root.path().join("src");
+        config.out = root.path().join("zkout");
+        config.cache = false;
+        config.solc = Some(SolcReq::Local(self.solc.solc.clone()));
+
+        let sources = source.compiler_input().sources;
+        // `Vm.sol` is only part of the input if forge-std isn't remapped
+        if sources.keys().any(|path| path.starts_with("forge-std")) {
+            config.remappings.push(RelativeRemapping::new(
+                Remapping {
+                    context: None,
+                    name: "forge-std/".to_string(),
+                    path: root.path().join("forge-std").display().to_string(),
+                },
+                root.path(),
+            ));
+        }
+        for (path, source) in sources {
+            let path = if path.starts_with("forge-std") {
+                root.path().join(path)
+            } else {
+                config.src.join(path)
+            };
+            fs::create_dir_all(path.parent().expect("source has a parent"))?;
+            fs::write(path, source.content.as_bytes())?;
+        }
+
+        let mut zksolc_cfg = config.zk_solc_config().map_err(|e| eyre::eyre!(e))?;
+        zksolc_cfg.compiler_path = setup_zksolc_manager(DEFAULT_ZKSOLC_VERSION.to_owned()).await?;
+        let (output, _) = ZkSolc::new(zksolc_cfg, config.project()?)
+            .compile()
+            .map_err(|e| eyre::eyre!("Failed to compile with zksolc: {e}"))?;
+
+        let bytecode = output
+            .artifacts()
+            .find(|(name, _)| name == &self.contract_name)
+            .and_then(|(_, artifact)| artifact.get_bytecode_bytes())
+            .ok_or_else(|| eyre::eyre!("No zksolc bytecode found for `REPL` contract"))?;
+        Ok(bytecode.into_owned())
+    }
+
     /// Compiles the source using [solang_parser]
     ///
     /// ### Returns
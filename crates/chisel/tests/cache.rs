@@ -47,6 +47,7 @@ fn test_write_session() {
         backend: None,
         traces: false,
         calldata: None,
+        zksync: false,
     })
     .unwrap_or_else(|e| panic!("Failed to create ChiselSession!, {}", e));
 
@@ -215,9 +215,52 @@ impl MultiWallet {
     pub async fn find_all(
         &self,
         provider: Arc<RetryProvider>,
-        mut addresses: HashSet<Address>,
+        addresses: HashSet<Address>,
         script_wallets: &[LocalWallet],
     ) -> Result<HashMap<Address, WalletSigner>> {
+        let (local_wallets, addresses, mut unused_wallets) =
+            self.collect_wallets(provider, addresses, script_wallets).await?;
+        if addresses.is_empty() {
+            return Ok(local_wallets)
+        }
+
+        let mut error_msg = String::new();
+
+        // This is an actual used address
+        if addresses.contains(&Config::DEFAULT_SENDER) {
+            error_msg += "\nYou seem to be using Foundry's default sender. Be sure to set your own --sender.\n";
+        }
+
+        unused_wallets.extend(local_wallets.into_keys());
+        eyre::bail!(
+            "{}No associated wallet for addresses: {:?}. Unlocked wallets: {:?}",
+            error_msg,
+            addresses,
+            unused_wallets
+        )
+    }
+
+    /// Given a list of addresses, it finds the associated wallets that exist, and returns the
+    /// addresses it couldn't find a wallet for.
+    pub async fn find_available(
+        &self,
+        provider: Arc<RetryProvider>,
+        addresses: HashSet<Address>,
+        script_wallets: &[LocalWallet],
+    ) -> Result<(HashMap<Address, WalletSigner>, HashSet<Address>)> {
+        let (local_wallets, missing, _) =
+            self.collect_wallets(provider, addresses, script_wallets).await?;
+        Ok((local_wallets, missing))
+    }
+
+    /// Collects the wallets of the given addresses, returning the found wallets, the addresses
+    /// left without a wallet and the addresses of the unused wallets.
+    async fn collect_wallets(
+        &self,
+        provider: Arc<RetryProvider>,
+        mut addresses: HashSet<Address>,
+        script_wallets: &[LocalWallet],
+    ) -> Result<(HashMap<Address, WalletSigner>, HashSet<Address>, Vec<Address>)> {
         println!("\n###\nFinding wallets for all the necessary addresses...");
         let chain = provider.get_chainid().await?.as_u64();
 
@@ -245,7 +288,7 @@ impl MultiWallet {
                     local_wallets.insert(address.to_alloy(), signer);
 
                     if addresses.is_empty() {
-                        return Ok(local_wallets)
+                        return Ok((local_wallets, addresses, unused_wallets))
                     }
                 } else {
                     // Just to show on error.
@@ -254,20 +297,18 @@ impl MultiWallet {
             }
         );
 
-        let mut error_msg = String::new();
+        Ok((local_wallets, addresses, unused_wallets))
+    }
 
-        // This is an actual used address
-        if addresses.contains(&Config::DEFAULT_SENDER) {
-            error_msg += "\nYou seem to be using Foundry's default sender. Be sure to set your own --sender.\n";
+    /// Returns the addresses of the wallets that are known without prompting for or unlocking
+    /// them: the ones of the private keys and mnemonics, followed by the `--froms`.
+    pub fn known_addresses(&self) -> Result<Vec<Address>> {
+        let mut addresses = vec![];
+        for wallets in [self.private_keys()?, self.mnemonics()?].into_iter().flatten() {
+            addresses.extend(wallets.iter().map(|wallet| wallet.address().to_alloy()));
         }
-
-        unused_wallets.extend(local_wallets.into_keys());
-        eyre::bail!(
-            "{}No associated wallet for addresses: {:?}. Unlocked wallets: {:?}",
-            error_msg,
-            addresses,
-            unused_wallets
-        )
+        addresses.extend(self.froms.iter().flatten().copied());
+        Ok(addresses)
     }
 
     pub fn interactives(&self) -> Result<Option<Vec<LocalWallet>>> {
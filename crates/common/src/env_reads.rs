@@ -0,0 +1,48 @@
+//! Environment variables read by cheatcodes, so that the results cached across runs that depend
+//! on them, e.g. setup fixtures, can be invalidated when they change.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env::VarError,
+    sync::Mutex,
+};
+
+/// The names of the environment variables read so far in this process.
+static READ_VARS: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Reads the environment variable `key` like [std::env::var], recording that it was read.
+pub fn var(key: &str) -> Result<String, VarError> {
+    READ_VARS.lock().unwrap().insert(key.to_string());
+    std::env::var(key)
+}
+
+/// Returns the environment variables read so far in this process with their current values,
+/// `None` if they're unset or not valid unicode.
+pub fn snapshot() -> BTreeMap<String, Option<String>> {
+    READ_VARS.lock().unwrap().iter().map(|key| (key.clone(), std::env::var(key).ok())).collect()
+}
+
+/// Returns whether the environment variables still have the values in `snapshot`.
+pub fn matches(snapshot: &BTreeMap<String, Option<String>>) -> bool {
+    snapshot.iter().all(|(key, value)| std::env::var(key).ok() == *value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_read_vars() {
+        let key = "_foundryEnvReadsTestKey";
+        std::env::set_var(key, "1");
+        assert!(!snapshot().contains_key(key));
+
+        assert_eq!(var(key).unwrap(), "1");
+        let read = snapshot();
+        assert_eq!(read[key], Some("1".to_string()));
+        assert!(matches(&read));
+
+        std::env::set_var(key, "2");
+        assert!(!matches(&read));
+    }
+}
@@ -13,6 +13,7 @@ pub mod clap_helpers;
 pub mod compile;
 pub mod constants;
 pub mod contracts;
+pub mod env_reads;
 pub mod errors;
 pub mod evm;
 pub mod fmt;
@@ -17,8 +17,8 @@
 ///   handles the output.
 ///
 /// - Error and Warning Handling: The compiler output is checked for errors and warnings, and
-///   they are displayed appropriately. If errors are encountered, the process will exit with a
-///   non-zero status code.
+///   they are displayed appropriately. If errors are encountered, the compilation fails with
+///   an error, e.g. so that a mutated source can be restored.
 ///
 /// - JSON Input Generation: The `parse_json_input` method generates the JSON input required by
 ///   the compiler for each contract. It configures the Solidity compiler, saves the input to
@@ -37,7 +37,7 @@ use eyre::{Context, ContextCompat, Result};
 use foundry_compilers::{
     artifacts::{
         output_selection::FileOutputSelection, CompactBytecode, CompactDeployedBytecode, Source,
-        StandardJsonCompilerInput,
+        StandardJsonCompilerInput, StorageLayout,
     },
     ArtifactFile, Artifacts, ConfigurableContractArtifact, Graph, Project, ProjectCompileOutput,
     Solc,
@@ -52,7 +52,7 @@ use std::{
     fs::File,
     io::{Read, Write},
     path::{Path, PathBuf},
-    process::{exit, Command, Stdio},
+    process::{Command, Stdio},
 };
 /// Mapping of bytecode hash (without "0x" prefix) to the respective contract name.
 pub type ContractBytecodes = BTreeMap<String, String>;
@@ -394,7 +394,8 @@ impl ZkSolc {
                     &mut displayed_warnings,
                     &contract_hash,
                     maybe_artifact_paths,
-                );
+                )
+                .wrap_err(format!("Failed to compile {:?}", contract_path))?;
                 data.insert(filename.clone(), artifacts);
                 contract_bytecodes.extend(bytecodes);
             }
@@ -491,7 +492,7 @@ impl ZkSolc {
     ///   accordingly.
     /// - Errors are printed in red color.
     /// - Warnings are printed in yellow color.
-    /// - If an error is encountered, the function exits with a non-zero status code.
+    /// - If an error is encountered, the function returns an error.
     /// - If only warnings are present, a message indicating the presence of warnings is printed.
     ///
     /// # Artifacts Saving
@@ -506,7 +507,7 @@ impl ZkSolc {
     /// let output = std::process::Output { ... };
     /// let source = "/path/to/contract.sol".to_string();
     /// let mut displayed_warnings = HashSet::new();
-    /// ZkSolc::handle_output(output, source, &mut displayed_warnings);
+    /// ZkSolc::handle_output(output, source, &mut displayed_warnings)?;
     /// ```
     ///
     /// In this example, the `handle_output` function is called with the compiler output, contract
@@ -518,8 +519,10 @@ impl ZkSolc {
         displayed_warnings: &mut HashSet<String>,
         contract_hash: &str,
         write_artifacts: Option<ZkSolcArtifactPaths>,
-    ) -> (BTreeMap<String, Vec<ArtifactFile<ConfigurableContractArtifact>>>, ContractBytecodes)
-    {
+    ) -> Result<(
+        BTreeMap<String, Vec<ArtifactFile<ConfigurableContractArtifact>>>,
+        ContractBytecodes,
+    )> {
         // Deserialize the compiler output into a serde_json::Value object
         let compiler_output: ZkSolcCompilerOutput = match serde_json::from_slice(&output) {
             Ok(output) => output,
@@ -531,23 +534,21 @@ impl ZkSolc {
                     Ok(json) if json.get("errors").is_some() => {
                         let errors = json["errors"]
                             .as_array()
-                            .expect("Expected 'errors' to be an array")
-                            .iter()
-                            .map(|e| {
-                                serde_json::from_value(e.clone()).expect("Error parsing error")
-                            })
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|e| serde_json::from_value(e.clone()).ok())
                             .collect::<Vec<CompilerError>>();
                         // Handle errors in the output
-                        ZkSolc::handle_output_errors(errors);
+                        ZkSolc::handle_output_errors(errors)?;
                     }
                     _ => info!("Failed to parse compiler output!"),
                 }
-                exit(1);
+                eyre::bail!("Failed to parse the compiler output of {source}");
             }
         };
 
         // Handle warnings in the output
-        ZkSolc::handle_output_warnings(&compiler_output, displayed_warnings);
+        ZkSolc::handle_output_warnings(&compiler_output, displayed_warnings)?;
 
         // First - let's get all the bytecodes.
         let mut all_bytecodes: HashMap<String, String> = Default::default();
@@ -623,6 +624,8 @@ impl ZkSolc {
                     };
 
                     art.abi = contract.abi.clone();
+                    art.storage_layout = contract.storage_layout.clone();
+                    art.assembly = contract.evm.assembly.clone();
 
                     let artifact = ArtifactFile {
                         artifact: art,
@@ -662,7 +665,7 @@ impl ZkSolc {
                 .unwrap_or_else(|e| panic!("Could not write contract_hash file: {}", e));
         }
 
-        (result, contract_bytecodes)
+        Ok((result, contract_bytecodes))
     }
 
     /// Handles the errors and warnings present in the output JSON from the compiler.
@@ -685,13 +688,12 @@ impl ZkSolc {
     /// If the severity is not "warning", it prints the formatted error message in red and sets
     /// the `has_error` flag to true.
     ///
-    /// If any errors are encountered, the function calls `exit(1)` to terminate the program. If
-    /// only warnings are encountered, it prints a message indicating that the compiler run
-    /// completed with warnings.
+    /// If any errors are encountered, the function returns an error. If only warnings are
+    /// encountered, it prints a message indicating that the compiler run completed with warnings.
     pub fn handle_output_warnings(
         output_json: &ZkSolcCompilerOutput,
         displayed_warnings: &mut HashSet<String>,
-    ) {
+    ) -> Result<()> {
         let errors = &output_json.errors;
 
         let mut has_error = false;
@@ -717,13 +719,16 @@ impl ZkSolc {
         }
 
         if has_error {
-            exit(1);
+            eyre::bail!("Compiler run failed");
         } else if has_warning {
             warn!("Compiler run completed with warnings");
         }
+        Ok(())
     }
     /// Handles and formats the errors present in the output JSON from the zksolc compiler.
-    pub fn handle_output_errors(errors: Vec<CompilerError>) {
+    ///
+    /// Returns an error if any of them is an error and not a warning.
+    pub fn handle_output_errors(errors: Vec<CompilerError>) -> Result<()> {
         let mut has_error = false;
         let mut error_codes = Vec::new();
 
@@ -742,8 +747,9 @@ impl ZkSolc {
             for code in error_codes {
                 error!("{}", Red.paint(format!("Compilation failed with error code: {}", code)));
             }
-            exit(1);
+            eyre::bail!("Compiler run failed");
         }
+        Ok(())
     }
 
     /// Parses the JSON input for a contract and prepares the necessary configuration for the ZkSolc
@@ -801,10 +807,45 @@ impl ZkSolc {
     /// path. It generates the JSON input for the contract, configures the Solidity compiler,
     /// and saves the input to the artifacts directory.
     fn prepare_compiler_input(&mut self, contract_path: &PathBuf) -> Result<()> {
+        // Steps 1-4: Generate the zk-specific Standard JSON Input
+        let std_zk_json = self.standard_json_input(contract_path)?;
+
+        // Store the generated standard JSON input in the ZkSolc instance
+        self.standard_json = Some(std_zk_json.to_owned());
+
+        // Step 5: Build Artifacts Path
+        let artifact_path = &self.build_artifacts_path(contract_path)?;
+
+        // Step 6: Save JSON Input
+        let json_input_path = artifact_path.join("json_input.json");
+        let stdjson =
+            serde_json::to_value(&std_zk_json).wrap_err("Could not serialize JSON input")?;
+
+        std::fs::write(json_input_path, serde_json::to_string_pretty(&stdjson)?)
+            .wrap_err("Could not write JSON input file")?;
+
+        Ok(())
+    }
+
+    /// Returns the zksolc standard JSON input of a contract, as it is passed to the compiler.
+    ///
+    /// This is also the input expected by the zkSync contract verifier.
+    pub fn standard_json_input(
+        &mut self,
+        contract_path: &Path,
+    ) -> Result<ZkStandardJsonCompilerInput> {
         // Step 1: Configure File Output Selection
         let mut file_output_selection: FileOutputSelection = BTreeMap::default();
-        file_output_selection
-            .insert("*".to_string(), vec!["abi".to_string(), "evm.methodIdentifiers".to_string()]);
+        file_output_selection.insert(
+            "*".to_string(),
+            vec![
+                "abi".to_string(),
+                "evm.methodIdentifiers".to_string(),
+                "storageLayout".to_string(),
+                // the EraVM assembly carries the debug info used to locate reverts
+                "evm.assembly".to_string(),
+            ],
+        );
         file_output_selection.insert("".to_string(), vec!["metadata".to_string()]);
 
         // Step 2: Configure Solidity Compiler
@@ -826,23 +867,7 @@ impl ZkSolc {
             .wrap_err("Could not get standard json input")?;
         // Convert the standard JSON input to the zk-specific standard JSON format for further
         // processing
-        let std_zk_json = self.convert_to_zk_standard_json(standard_json);
-
-        // Store the generated standard JSON input in the ZkSolc instance
-        self.standard_json = Some(std_zk_json.to_owned());
-
-        // Step 5: Build Artifacts Path
-        let artifact_path = &self.build_artifacts_path(contract_path)?;
-
-        // Step 6: Save JSON Input
-        let json_input_path = artifact_path.join("json_input.json");
-        let stdjson =
-            serde_json::to_value(&std_zk_json).wrap_err("Could not serialize JSON input")?;
-
-        std::fs::write(json_input_path, serde_json::to_string_pretty(&stdjson)?)
-            .wrap_err("Could not write JSON input file")?;
-
-        Ok(())
+        Ok(self.convert_to_zk_standard_json(standard_json))
     }
 
     fn convert_to_zk_standard_json(
@@ -1018,11 +1043,14 @@ pub struct ZkContract {
     pub factory_dependencies: HashMap<String, String>,
     pub evm: Evm,
     pub abi: Option<JsonAbi>,
+    #[serde(rename = "storageLayout", default)]
+    pub storage_layout: Option<StorageLayout>,
 }
 #[derive(Debug, Deserialize)]
 
 pub struct Evm {
     pub bytecode: Option<ZkSolcBytecode>,
+    pub assembly: Option<String>,
 }
 #[derive(Debug, Deserialize)]
 
@@ -1048,7 +1076,8 @@ mod tests {
             include_str!("../../../testdata/artifacts-counter/artifacts.json").as_bytes().to_vec();
         let mut displayed_warnings = HashSet::new();
         let source = "src/Counter.sol".to_owned();
-        let (result, _) = ZkSolc::handle_output(data, &source, &mut displayed_warnings, "", None);
+        let (result, _) =
+            ZkSolc::handle_output(data, &source, &mut displayed_warnings, "", None).unwrap();
 
         let artifacts = result.get("Counter").unwrap();
         assert_eq!(artifacts.len(), 1);
@@ -1058,6 +1087,51 @@ mod tests {
         assert!(first.artifact.abi.is_some());
         assert_eq!(first.artifact.bytecode.as_ref().unwrap().object.bytes_len(), 3883);
     }
+    /// A mutant that doesn't compile, e.g. `-x` replaced with `+x`, fails the compilation instead
+    /// of exiting, so that `zkforge mutate` can restore the source and mark it invalid.
+    #[test]
+    pub fn test_compiler_errors_fail() {
+        let error = serde_json::json!({
+            "component": "general",
+            "errorCode": "4907",
+            "formattedMessage": "TypeError: Built-in unary operator + cannot be applied to type uint256.",
+            "message": "Built-in unary operator + cannot be applied to type uint256.",
+            "severity": "error",
+            "sourceLocation": { "file": "src/Counter.sol", "start": 142, "end": 144 },
+            "type": "TypeError"
+        });
+        let mut displayed_warnings = HashSet::new();
+
+        // the output zksolc emits when the sources don't compile
+        let output = serde_json::to_vec(&serde_json::json!({ "errors": [error.clone()] })).unwrap();
+        assert!(ZkSolc::handle_output(
+            output,
+            "src/Counter.sol",
+            &mut displayed_warnings,
+            "",
+            None
+        )
+        .is_err());
+
+        let output = serde_json::to_vec(&serde_json::json!({
+            "contracts": {},
+            "sources": {},
+            "errors": [error],
+            "version": "0.8.20",
+            "long_version": "0.8.20+commit.a1b79de6",
+            "zk_version": "1.3.17"
+        }))
+        .unwrap();
+        assert!(ZkSolc::handle_output(
+            output,
+            "src/Counter.sol",
+            &mut displayed_warnings,
+            "",
+            None
+        )
+        .is_err());
+    }
+
     #[test]
     pub fn test_json_parsing() {
         let data =
@@ -0,0 +1,54 @@
+//! Deployments through the `ContractDeployer` system contract, which deploys contracts from the
+//! hash of their bytecode, published as a factory dependency.
+
+use alloy_sol_types::{sol, SolCall};
+pub use zksync_types::CONTRACT_DEPLOYER_ADDRESS;
+
+sol! {
+    /// Deploys a contract with `CREATE`.
+    function create(bytes32 _salt, bytes32 _bytecodeHash, bytes _input);
+
+    /// Deploys a contract with `CREATE2`.
+    function create2(bytes32 _salt, bytes32 _bytecodeHash, bytes _input);
+
+    /// Deploys an account with `CREATE`.
+    function createAccount(bytes32 _salt, bytes32 _bytecodeHash, bytes _input, uint8 _aaVersion);
+
+    /// Deploys an account with `CREATE2`.
+    function create2Account(
+        bytes32 _salt,
+        bytes32 _bytecodeHash,
+        bytes _input,
+        uint8 _aaVersion
+    );
+}
+
+/// Selectors of the `ContractDeployer` functions deploying a contract with `CREATE`.
+pub const CREATE_SELECTORS: [[u8; 4]; 2] = [createCall::SELECTOR, createAccountCall::SELECTOR];
+
+/// Selectors of all the `ContractDeployer` functions deploying a contract, whose second argument
+/// is the hash of the deployed bytecode.
+pub const DEPLOY_SELECTORS: [[u8; 4]; 4] = [
+    createCall::SELECTOR,
+    create2Call::SELECTOR,
+    createAccountCall::SELECTOR,
+    create2AccountCall::SELECTOR,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deploy_selectors() {
+        assert_eq!(
+            DEPLOY_SELECTORS,
+            [
+                [0x9c, 0x4d, 0x53, 0x5b],
+                [0x3c, 0xda, 0x33, 0x51],
+                [0xec, 0xf9, 0x5b, 0x8a],
+                [0x5d, 0x38, 0x27, 0x00]
+            ]
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Finality of zkSync transactions, i.e. whether the L1 batch they were included in has been
+//! committed, proven and executed on L1, as returned by `zks_getL1BatchDetails`.
+
+use crate::RetryProvider;
+use alloy_primitives::B256;
+use ethers_core::types::U64;
+use eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use std::{fmt, time::Duration};
+
+/// How far an L1 batch, and the transactions it includes, has progressed towards finality.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum FinalityStage {
+    /// The transaction isn't included in a sealed batch yet.
+    Pending,
+    /// The batch is sealed on L2, but not committed to L1 yet.
+    Sealed,
+    /// The batch is committed to L1.
+    Committed,
+    /// The proof of the batch is verified on L1.
+    Proven,
+    /// The batch is executed on L1, its transactions are final.
+    Executed,
+}
+
+impl fmt::Display for FinalityStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Pending => "pending",
+            Self::Sealed => "sealed",
+            Self::Committed => "committed",
+            Self::Proven => "proven",
+            Self::Executed => "executed",
+        })
+    }
+}
+
+/// The subset of `zks_getL1BatchDetails` describing the L1 transactions of a batch.
+///
+/// The hashes and timestamps of a stage are set once its L1 transaction is confirmed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BatchDetails {
+    pub number: u32,
+    pub commit_tx_hash: Option<B256>,
+    pub committed_at: Option<String>,
+    pub prove_tx_hash: Option<B256>,
+    pub proven_at: Option<String>,
+    pub execute_tx_hash: Option<B256>,
+    pub executed_at: Option<String>,
+}
+
+impl L1BatchDetails {
+    /// Returns the last stage the batch reached.
+    pub fn stage(&self) -> FinalityStage {
+        if self.execute_tx_hash.is_some() {
+            FinalityStage::Executed
+        } else if self.prove_tx_hash.is_some() {
+            FinalityStage::Proven
+        } else if self.commit_tx_hash.is_some() {
+            FinalityStage::Committed
+        } else {
+            FinalityStage::Sealed
+        }
+    }
+}
+
+/// The finality of an L1 batch, or of a transaction and the batch it's included in.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchStatus {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<B256>,
+    pub l1_batch_number: Option<u32>,
+    pub stage: FinalityStage,
+    pub commit_tx_hash: Option<B256>,
+    pub prove_tx_hash: Option<B256>,
+    pub execute_tx_hash: Option<B256>,
+}
+
+impl BatchStatus {
+    /// Creates the status from the details of the batch, which are missing until it's sealed.
+    fn new(tx_hash: Option<B256>, details: Option<L1BatchDetails>) -> Self {
+        let Some(details) = details else {
+            return Self {
+                tx_hash,
+                l1_batch_number: None,
+                stage: FinalityStage::Pending,
+                commit_tx_hash: None,
+                prove_tx_hash: None,
+                execute_tx_hash: None,
+            }
+        };
+        Self {
+            tx_hash,
+            l1_batch_number: Some(details.number),
+            stage: details.stage(),
+            commit_tx_hash: details.commit_tx_hash,
+            prove_tx_hash: details.prove_tx_hash,
+            execute_tx_hash: details.execute_tx_hash,
+        }
+    }
+}
+
+/// The subset of a zkSync transaction receipt locating its L1 batch.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct L1BatchLocation {
+    l1_batch_number: Option<U64>,
+}
+
+/// Fetches the finality of the L1 batch.
+pub async fn batch_status(provider: &RetryProvider, l1_batch: u32) -> Result<BatchStatus> {
+    let details: Option<L1BatchDetails> = provider
+        .request("zks_getL1BatchDetails", [l1_batch])
+        .await
+        .wrap_err_with(|| format!("Failed to fetch the details of L1 batch {l1_batch}"))?;
+    Ok(BatchStatus::new(None, details))
+}
+
+/// Fetches the finality of the transaction, through the L1 batch it's included in.
+///
+/// Fails if the transaction isn't known, but not if it isn't in a sealed batch yet.
+pub async fn transaction_status(provider: &RetryProvider, tx_hash: B256) -> Result<BatchStatus> {
+    let location: Option<L1BatchLocation> = provider
+        .request("eth_getTransactionReceipt", [tx_hash])
+        .await
+        .wrap_err_with(|| format!("Failed to fetch the receipt of {tx_hash}"))?;
+    let Some(location) = location else {
+        eyre::bail!("Transaction {tx_hash} not found, or not mined yet")
+    };
+    let Some(l1_batch) = location.l1_batch_number else {
+        return Ok(BatchStatus::new(Some(tx_hash), None))
+    };
+
+    let status = batch_status(provider, l1_batch.as_u32()).await?;
+    Ok(BatchStatus { tx_hash: Some(tx_hash), ..status })
+}
+
+/// Polls the status until it reaches the given stage, or the timeout elapses.
+pub async fn wait_for_stage<F, Fut>(
+    stage: FinalityStage,
+    timeout: Duration,
+    interval: Duration,
+    mut status: F,
+) -> Result<BatchStatus>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<BatchStatus>>,
+{
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let current = status().await?;
+        if current.stage >= stage {
+            return Ok(current)
+        }
+        if tokio::time::Instant::now() + interval > deadline {
+            eyre::bail!(
+                "Timed out after {}s waiting for the batch to be {stage}, it's {}",
+                timeout.as_secs(),
+                current.stage
+            )
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_stage_from_details() {
+        let mut details = L1BatchDetails { number: 7, ..Default::default() };
+        assert_eq!(details.stage(), FinalityStage::Sealed);
+        details.commit_tx_hash = Some(B256::repeat_byte(1));
+        assert_eq!(details.stage(), FinalityStage::Committed);
+        details.prove_tx_hash = Some(B256::repeat_byte(2));
+        assert_eq!(details.stage(), FinalityStage::Proven);
+        details.execute_tx_hash = Some(B256::repeat_byte(3));
+        assert_eq!(details.stage(), FinalityStage::Executed);
+
+        let status = BatchStatus::new(None, Some(details));
+        assert_eq!(status.l1_batch_number, Some(7));
+        assert_eq!(BatchStatus::new(None, None).stage, FinalityStage::Pending);
+    }
+
+    #[test]
+    fn deserializes_batch_details() {
+        let details: L1BatchDetails = serde_json::from_value(serde_json::json!({
+            "number": 12,
+            "timestamp": 1700000000,
+            "status": "verified",
+            "commitTxHash": format!("{}", B256::repeat_byte(1)),
+            "committedAt": "2023-11-14T22:13:20Z",
+            "proveTxHash": null,
+            "provenAt": null,
+            "executeTxHash": null,
+            "executedAt": null,
+        }))
+        .unwrap();
+        assert_eq!(details.number, 12);
+        assert_eq!(details.stage(), FinalityStage::Committed);
+    }
+
+    #[tokio::test]
+    async fn waits_for_stage() {
+        let mut polls = 0;
+        let status = wait_for_stage(
+            FinalityStage::Committed,
+            Duration::from_secs(1),
+            Duration::ZERO,
+            || {
+                polls += 1;
+                let stage = if polls < 3 { FinalityStage::Sealed } else { FinalityStage::Proven };
+                async move { Ok(BatchStatus { stage, ..BatchStatus::new(None, None) }) }
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(status.stage, FinalityStage::Proven);
+        assert_eq!(polls, 3);
+
+        assert!(wait_for_stage(
+            FinalityStage::Executed,
+            Duration::ZERO,
+            Duration::from_secs(1),
+            || async { Ok(BatchStatus::new(None, None)) },
+        )
+        .await
+        .is_err());
+    }
+}
@@ -27,18 +27,37 @@
 ///
 /// - `decode_hex`: Decodes a hexadecimal string into a byte vector. Returns `Result<Vec<u8>>`
 ///   with the decoded byte vector if successful, or a `ParseIntError` if the decoding fails.
+use alloy_primitives::{Address, B256};
 use eyre::Result;
-use foundry_config::Chain;
+use foundry_config::{Chain, ZkExecutionMode, MAX_ZK_GAS_LIMIT};
 use multivm::vm_latest::TracerPointer;
-use std::{collections::HashMap, num::ParseIntError};
+use std::{num::ParseIntError, sync::Arc};
 use url::Url;
 use zksync_basic_types::U256;
-use zksync_types::{StorageKey, StorageValue};
+use zksync_types::{AccountTreeId, StorageKey};
 use zksync_web3_rs::types::H256;
 /// Utils for conversion between zksync types and revm types
 pub mod conversion_utils;
+/// Deployments through the `ContractDeployer` system contract
+pub mod deployer;
 /// Tools for working with factory deps
 pub mod factory_deps;
+/// Finality of zkSync transactions and L1 batches
+pub mod finality;
+/// Verification of zkSync storage proofs
+pub mod proof;
+/// Size limits of EraVM bytecodes
+pub mod size;
+/// Source locations of EraVM instructions
+pub mod source_map;
+/// Overrides of the state read by the zkEVM
+pub mod state_overrides;
+pub use state_overrides::ZkStateOverrides;
+/// Storage modifications recorded during test executions
+pub mod storage_modifications;
+pub use storage_modifications::{StorageModificationRecorder, StorageModifications};
+/// Finalization of L2 → L1 withdrawals
+pub mod withdrawal;
 /// Gets the RPC URL for Ethereum.
 ///
 /// If the `eth.rpc_url` is `None`, an error is returned.
@@ -161,46 +180,40 @@ pub fn fix_l2_gas_price(gas_price: U256) -> U256 {
     U256::max(gas_price, U256::from(260_000_000))
 }
 
-/// Fixes the gas limit to be maxmium of 2^31, which is below the VM gas limit of 2^32.
+/// Fixes the gas limit to be maxmium of [MAX_ZK_GAS_LIMIT], 2^31 - 1, which is below the VM gas
+/// limit of 2^32.
 /// This is required so the bootloader does not throw an error for not having enough gas.
 ///
 /// TODO: Remove this later to allow for dynamic gas prices that work in both tests and scripts.
 pub fn fix_l2_gas_limit(gas_limit: U256) -> U256 {
-    U256::min(gas_limit, U256::from(u32::MAX >> 1))
+    U256::min(gas_limit, U256::from(MAX_ZK_GAS_LIMIT))
 }
 
-/// Recorded storage modifications.
-#[derive(Default, Debug, Clone)]
-pub struct StorageModifications {
-    /// Storage key modifications.
-    pub keys: HashMap<StorageKey, StorageValue>,
-    /// Bytecode modifications.
-    pub bytecodes: HashMap<H256, Vec<u8>>,
-    /// Recorded known codes.
-    pub known_codes: HashMap<H256, Vec<u8>>,
-}
-
-impl StorageModifications {
-    /// Updates current modifications with the provided modifications.
-    pub fn extend(&mut self, other: StorageModifications) {
-        self.keys.extend(other.keys);
-        self.bytecodes.extend(other.bytecodes);
-        self.known_codes.extend(other.known_codes);
-    }
-}
-
-/// Keeps track of storage modifications performed during test executions.
-/// This is especially important when forking to re-apply changes.
-pub trait StorageModificationRecorder {
-    /// Merge modified keys and bytecodes into the existing modifications
-    fn record_storage_modifications(&mut self, storage_modifications: StorageModifications);
-
-    /// Return all modified keys
-    fn get_storage_modifications(&self) -> &StorageModifications;
+/// Computes the key under which `slot` of the contract at `address` is stored in the zkSync state
+/// tree.
+///
+/// Unlike on Ethereum, where every account has its own storage trie, zkSync keeps the storage of
+/// all the accounts in a single tree, keyed by the hash of the account and the slot.
+pub fn hashed_storage_key(address: Address, slot: B256) -> B256 {
+    let key = StorageKey::new(
+        AccountTreeId::new(conversion_utils::address_to_h160(address)),
+        zksync_basic_types::H256(slot.0),
+    );
+    conversion_utils::h256_to_b256(key.hashed_key())
 }
 
 /// Converts a reference to self into a tracer pointer.
 pub trait AsTracerPointer<S, H> {
     /// Returns reference to a [TracerPointer]
     fn as_tracer_pointer(&self) -> TracerPointer<S, H>;
+
+    /// Returns the mode the bootloader runs the traced transactions in.
+    fn execution_mode(&self) -> ZkExecutionMode {
+        ZkExecutionMode::default()
+    }
+
+    /// Returns the overrides applied on top of the state read by the traced transactions.
+    fn state_overrides(&self) -> Arc<ZkStateOverrides> {
+        Arc::default()
+    }
 }
@@ -0,0 +1,124 @@
+//! Merkle proofs of zkSync storage, as returned by `zks_getProof`, and their verification
+//! against the root hash of an L1 batch.
+
+use alloy_primitives::{Address, B256};
+use blake2::{Blake2s256, Digest};
+use serde::{Deserialize, Serialize};
+
+use super::hashed_storage_key;
+
+/// Depth of the zkSync state tree.
+const TREE_DEPTH: usize = 256;
+
+/// Proofs of storage slots of an account, as returned by `zks_getProof`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZkAccountProof {
+    /// The account address.
+    pub address: Address,
+    /// Proofs for each of the requested slots.
+    pub storage_proof: Vec<ZkStorageProof>,
+}
+
+/// Proof of a single storage slot.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZkStorageProof {
+    /// The storage slot.
+    pub key: B256,
+    /// The value of the slot.
+    pub value: B256,
+    /// The index of the leaf in the tree, `0` if the slot was never written to.
+    pub index: u64,
+    /// Merkle path to the leaf, starting from the root-adjacent level.
+    ///
+    /// Hashes of empty subtrees at the end of the path are omitted.
+    pub proof: Vec<B256>,
+}
+
+impl ZkStorageProof {
+    /// Computes the root hash of the tree implied by this proof for a slot of `address`.
+    pub fn root_hash(&self, address: Address) -> B256 {
+        let tree_key = hashed_storage_key(address, self.key);
+        let empty_hashes = empty_subtree_hashes();
+
+        let mut hash = hash_leaf(self.index, self.value);
+        for depth in (0..TREE_DEPTH).rev() {
+            let sibling =
+                self.proof.get(depth).copied().unwrap_or(empty_hashes[TREE_DEPTH - 1 - depth]);
+            let is_right = (tree_key[depth / 8] >> (7 - depth % 8)) & 1 == 1;
+            hash = if is_right { hash_branch(sibling, hash) } else { hash_branch(hash, sibling) };
+        }
+        hash
+    }
+
+    /// Returns whether this proof is valid for a slot of `address` in a tree with the given root.
+    pub fn verify(&self, address: Address, root_hash: B256) -> bool {
+        self.proof.len() <= TREE_DEPTH && self.root_hash(address) == root_hash
+    }
+}
+
+fn hash_leaf(index: u64, value: B256) -> B256 {
+    let mut bytes = [0u8; 40];
+    bytes[..8].copy_from_slice(&index.to_be_bytes());
+    bytes[8..].copy_from_slice(value.as_slice());
+    B256::from_slice(&Blake2s256::digest(bytes))
+}
+
+fn hash_branch(left: B256, right: B256) -> B256 {
+    let mut hasher = Blake2s256::new();
+    hasher.update(left);
+    hasher.update(right);
+    B256::from_slice(&hasher.finalize())
+}
+
+/// Returns the hashes of empty subtrees, indexed by their height.
+fn empty_subtree_hashes() -> Vec<B256> {
+    let mut hashes = Vec::with_capacity(TREE_DEPTH);
+    let mut hash = hash_leaf(0, B256::ZERO);
+    for _ in 0..TREE_DEPTH {
+        hashes.push(hash);
+        hash = hash_branch(hash, hash);
+    }
+    hashes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_root() -> B256 {
+        let empty = empty_subtree_hashes();
+        hash_branch(empty[TREE_DEPTH - 1], empty[TREE_DEPTH - 1])
+    }
+
+    #[test]
+    fn verifies_missing_slot_in_empty_tree() {
+        let proof = ZkStorageProof { key: B256::ZERO, value: B256::ZERO, index: 0, proof: vec![] };
+        assert!(proof.verify(Address::ZERO, empty_root()));
+    }
+
+    #[test]
+    fn rejects_tampered_value() {
+        let proof = ZkStorageProof {
+            key: B256::ZERO,
+            value: B256::with_last_byte(1),
+            index: 1,
+            proof: vec![],
+        };
+        assert!(!proof.verify(Address::ZERO, empty_root()));
+    }
+
+    #[test]
+    fn verifies_single_leaf_tree() {
+        let address = Address::with_last_byte(0x42);
+        let key = B256::with_last_byte(7);
+        let mut proof =
+            ZkStorageProof { key, value: B256::with_last_byte(1), index: 1, proof: vec![] };
+        let root = proof.root_hash(address);
+        assert!(proof.verify(address, root));
+
+        proof.value = B256::with_last_byte(2);
+        assert!(!proof.verify(address, root));
+    }
+}
@@ -0,0 +1,184 @@
+//! Size limits of EraVM bytecodes.
+
+use super::factory_deps::PackedEraBytecode;
+use crate::TestFunctionExt;
+use comfy_table::{presets::ASCII_MARKDOWN, *};
+use foundry_compilers::{Artifact, ProjectCompileOutput};
+use std::{collections::BTreeMap, fmt};
+
+/// Size of an EraVM bytecode word, in bytes.
+const WORD_SIZE: usize = 32;
+
+/// Maximum length of a bytecode, in words, since it's encoded in 2 bytes of its hash.
+pub const MAX_BYTECODE_WORDS: usize = (1 << 16) - 1;
+
+/// Maximum number of factory deps a transaction can publish, including the deployed bytecode.
+pub const MAX_FACTORY_DEPS: usize = 32;
+
+/// Size of a compiled contract and of the factory deps published along with it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ZkContractSize {
+    /// Length of the bytecode, in words.
+    pub words: usize,
+    /// Number of factory deps, not counting the bytecode itself.
+    pub factory_deps: usize,
+    /// Length of the longest factory dep, in words.
+    pub max_factory_dep_words: usize,
+    /// Total size of the bytecode and its factory deps, in bytes.
+    pub total_size: usize,
+    /// Whether it's a test or script contract, which is never deployed on-chain.
+    pub is_dev_contract: bool,
+}
+
+impl ZkContractSize {
+    /// Measures a packed era bytecode.
+    pub fn new(packed: &PackedEraBytecode, is_dev_contract: bool) -> Self {
+        let bytecode = packed.bytecode();
+        let deps = packed.factory_deps();
+        // the bytecode itself is the last factory dep
+        let deps = &deps[..deps.len() - 1];
+        Self {
+            words: bytecode.len() / WORD_SIZE,
+            factory_deps: deps.len(),
+            max_factory_dep_words: deps.iter().map(|dep| dep.len() / WORD_SIZE).max().unwrap_or(0),
+            total_size: bytecode.len() + deps.iter().map(Vec::len).sum::<usize>(),
+            is_dev_contract,
+        }
+    }
+
+    /// Returns the limits the contract exceeds.
+    pub fn violations(&self) -> Vec<String> {
+        let mut violations = vec![];
+        if self.words > MAX_BYTECODE_WORDS {
+            violations.push(format!(
+                "bytecode is {} words long, exceeding the limit of {MAX_BYTECODE_WORDS}",
+                self.words
+            ));
+        }
+        if self.max_factory_dep_words > MAX_BYTECODE_WORDS {
+            violations.push(format!(
+                "a factory dep is {} words long, exceeding the limit of {MAX_BYTECODE_WORDS}",
+                self.max_factory_dep_words
+            ));
+        }
+        if self.factory_deps + 1 > MAX_FACTORY_DEPS {
+            violations.push(format!(
+                "deploying it publishes {} factory deps, exceeding the limit of {MAX_FACTORY_DEPS}",
+                self.factory_deps + 1
+            ));
+        }
+        violations
+    }
+}
+
+/// EraVM sizes of the compiled contracts.
+#[derive(Clone, Debug, Default)]
+pub struct ZkSizeReport {
+    /// `<contract name>:size>`
+    pub contracts: BTreeMap<String, ZkContractSize>,
+}
+
+impl ZkSizeReport {
+    /// Measures the contracts compiled with zksolc.
+    pub fn new(output: &ProjectCompileOutput) -> Self {
+        let mut contracts = BTreeMap::new();
+        for (name, artifact) in output.artifacts() {
+            let Some(bytecode) = artifact.get_bytecode_bytes() else { continue };
+            let Ok(packed) = serde_json::from_slice::<PackedEraBytecode>(&bytecode) else {
+                continue
+            };
+            let is_dev_contract = artifact.abi.as_ref().map_or(false, |abi| {
+                abi.functions().any(|func| {
+                    func.name.is_test() || func.name == "IS_TEST" || func.name == "IS_SCRIPT"
+                })
+            });
+            contracts.insert(name, ZkContractSize::new(&packed, is_dev_contract));
+        }
+        Self { contracts }
+    }
+
+    /// Returns the limits exceeded by each contract, excluding test contracts.
+    pub fn violations(&self) -> BTreeMap<&str, Vec<String>> {
+        self.contracts
+            .iter()
+            .filter(|(_, contract)| !contract.is_dev_contract)
+            .map(|(name, contract)| (name.as_str(), contract.violations()))
+            .filter(|(_, violations)| !violations.is_empty())
+            .collect()
+    }
+}
+
+impl fmt::Display for ZkSizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.load_preset(ASCII_MARKDOWN);
+        table.set_header([
+            Cell::new("Contract").add_attribute(Attribute::Bold).fg(Color::Blue),
+            Cell::new("Size (kB)").add_attribute(Attribute::Bold).fg(Color::Blue),
+            Cell::new("Words").add_attribute(Attribute::Bold).fg(Color::Blue),
+            Cell::new("Margin (words)").add_attribute(Attribute::Bold).fg(Color::Blue),
+            Cell::new("Factory deps").add_attribute(Attribute::Bold).fg(Color::Blue),
+            Cell::new("Total size (kB)").add_attribute(Attribute::Bold).fg(Color::Blue),
+        ]);
+
+        let contracts = self.contracts.iter().filter(|(_, c)| !c.is_dev_contract && c.words > 0);
+        for (name, contract) in contracts {
+            let color = if !contract.violations().is_empty() {
+                Color::Red
+            } else if contract.words > MAX_BYTECODE_WORDS * 3 / 4 ||
+                contract.factory_deps + 1 > MAX_FACTORY_DEPS * 3 / 4
+            {
+                Color::Yellow
+            } else {
+                Color::Reset
+            };
+
+            table.add_row([
+                Cell::new(name).fg(color),
+                Cell::new((contract.words * WORD_SIZE) as f64 / 1000.0).fg(color),
+                Cell::new(contract.words).fg(color),
+                Cell::new(MAX_BYTECODE_WORDS as isize - contract.words as isize).fg(color),
+                Cell::new(contract.factory_deps).fg(color),
+                Cell::new(contract.total_size as f64 / 1000.0).fg(color),
+            ]);
+        }
+
+        writeln!(f, "{table}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packed(words: usize, deps: &[usize]) -> PackedEraBytecode {
+        let code = |words: usize| "00".repeat(words * WORD_SIZE);
+        PackedEraBytecode::new(
+            String::new(),
+            code(words),
+            deps.iter().map(|words| code(*words)).collect(),
+        )
+    }
+
+    #[test]
+    fn checks_era_limits() {
+        let size = ZkContractSize::new(&packed(3, &[5, 1]), false);
+        assert_eq!(
+            size,
+            ZkContractSize {
+                words: 3,
+                factory_deps: 2,
+                max_factory_dep_words: 5,
+                total_size: 9 * WORD_SIZE,
+                is_dev_contract: false,
+            }
+        );
+        assert!(size.violations().is_empty());
+
+        let size = ZkContractSize { words: MAX_BYTECODE_WORDS + 1, ..size };
+        assert_eq!(size.violations().len(), 1);
+
+        let size = ZkContractSize { factory_deps: MAX_FACTORY_DEPS, ..size };
+        assert_eq!(size.violations().len(), 2);
+    }
+}
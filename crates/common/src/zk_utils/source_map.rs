@@ -0,0 +1,199 @@
+//! Mapping of EraVM program counters to source locations.
+//!
+//! zksolc doesn't emit solc-style source maps. Instead, the debug info of a contract is carried
+//! by the `.file` and `.loc` directives of its EraVM assembly, which precede the instructions
+//! they describe. Since every instruction is a single code word, the program counter of an
+//! instruction is its index in the `.text` section.
+
+use super::factory_deps::PackedEraBytecode;
+use foundry_compilers::ProjectCompileOutput;
+use std::{collections::HashMap, fmt};
+use zksync_basic_types::H256;
+use zksync_utils::bytecode::hash_bytecode;
+
+/// A location in a source file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// The source locations of the instructions of a contract.
+#[derive(Clone, Debug, Default)]
+pub struct EraSourceMap {
+    files: HashMap<u32, String>,
+    /// The `(file, line, column)` of every instruction, indexed by program counter.
+    instructions: Vec<Option<(u32, u32, u32)>>,
+}
+
+impl EraSourceMap {
+    /// Parses the EraVM assembly of a contract, as emitted by zksolc.
+    pub fn parse(assembly: &str) -> Self {
+        let mut map = Self::default();
+        let mut in_text = false;
+        let mut location = None;
+
+        for line in assembly.lines() {
+            let line = line.split(';').next().unwrap_or_default().trim();
+            if line.is_empty() || line.ends_with(':') {
+                continue
+            }
+
+            let mut tokens = line.split_whitespace();
+            let Some(head) = tokens.next() else { continue };
+            match head {
+                ".text" => in_text = true,
+                ".file" => {
+                    // only numbered entries belong to the file table
+                    if let (Some(Ok(index)), Some(path)) =
+                        (tokens.next().map(str::parse), tokens.next())
+                    {
+                        map.files.insert(index, path.trim_matches('"').to_string());
+                    }
+                }
+                ".loc" => {
+                    let mut numbers = tokens.map_while(|token| token.parse::<u32>().ok());
+                    location = match (numbers.next(), numbers.next(), numbers.next()) {
+                        (Some(file), Some(line), column) if line > 0 => {
+                            Some((file, line, column.unwrap_or_default()))
+                        }
+                        _ => None,
+                    };
+                }
+                directive if directive.starts_with('.') => {
+                    if directive.starts_with(".rodata") ||
+                        directive.starts_with(".data") ||
+                        directive.starts_with(".bss")
+                    {
+                        in_text = false;
+                    }
+                }
+                _ if in_text => map.instructions.push(location),
+                _ => (),
+            }
+        }
+
+        map
+    }
+
+    /// Returns `true` if the assembly carried no debug info.
+    pub fn is_empty(&self) -> bool {
+        self.instructions.iter().all(Option::is_none)
+    }
+
+    /// Returns the source location of the instruction at `pc`, if known.
+    pub fn location(&self, pc: u16) -> Option<SourceLocation> {
+        let (file, line, column) = (*self.instructions.get(pc as usize)?)?;
+        Some(SourceLocation { file: self.files.get(&file)?.clone(), line, column })
+    }
+}
+
+/// The source maps of compiled contracts, keyed by bytecode hash.
+#[derive(Clone, Debug, Default)]
+pub struct ContractSourceMaps(HashMap<H256, (String, EraSourceMap)>);
+
+impl ContractSourceMaps {
+    /// Collects the source maps of the contracts compiled by zksolc.
+    pub fn new(output: &ProjectCompileOutput) -> Self {
+        let mut maps = Self::default();
+        for (name, artifact) in output.artifacts() {
+            let (Some(assembly), Some(bytecode)) = (
+                artifact.assembly.as_deref(),
+                artifact.bytecode.as_ref().and_then(|bytecode| bytecode.object.as_bytes()),
+            ) else {
+                continue
+            };
+            let bytecode_hash = hash_bytecode(&PackedEraBytecode::from_vec(bytecode).bytecode());
+            maps.insert(bytecode_hash, name, EraSourceMap::parse(assembly));
+        }
+        maps
+    }
+
+    /// Adds the source map of a contract.
+    pub fn insert(&mut self, bytecode_hash: H256, name: String, source_map: EraSourceMap) {
+        self.0.insert(bytecode_hash, (name, source_map));
+    }
+
+    /// Describes where the instruction at `pc` of the given code is, if the code is known.
+    ///
+    /// Falls back to the program counter within the contract if it was compiled without debug
+    /// info.
+    pub fn describe(&self, bytecode_hash: &H256, pc: u16) -> Option<String> {
+        let (name, source_map) = self.0.get(bytecode_hash)?;
+        Some(match source_map.location(pc) {
+            Some(location) => format!("{location} ({name})"),
+            None => format!("{name}, pc {pc}"),
+        })
+    }
+
+    /// Names the code executing the instruction at `pc` down to the source line, if known, so
+    /// that samples of the same line can be aggregated.
+    pub fn frame_name(&self, bytecode_hash: &H256, pc: u16) -> Option<String> {
+        let (name, source_map) = self.0.get(bytecode_hash)?;
+        Some(match source_map.location(pc) {
+            Some(location) => format!("{name} ({}:{})", location.file, location.line),
+            None => name.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASSEMBLY: &str = r#"
+	.text
+	.file	"src/Counter.sol:Counter"
+	.file	1 "src/Counter.sol"
+	.globl	__entry
+__entry:
+.func_begin0:
+	add	128, r0, r3
+	.loc	1 12 9 prologue_end
+	st.1	64, r3                          ; store free memory pointer
+	.loc	1 14 13
+	sub.s!	0, r2, r0
+	jump.ne	@.BB0_2
+.BB0_2:
+	.loc	1 0 0
+	ret
+	.rodata.cst32
+CPI0_0:
+	.cell	-1
+	.text
+	.loc	1 20 5
+	revert
+"#;
+
+    #[test]
+    fn maps_instructions_to_locations() {
+        let map = EraSourceMap::parse(ASSEMBLY);
+        assert!(!map.is_empty());
+
+        let location = |line, column| {
+            Some(SourceLocation { file: "src/Counter.sol".to_string(), line, column })
+        };
+        assert_eq!(map.location(0), None);
+        assert_eq!(map.location(1), location(12, 9));
+        assert_eq!(map.location(2), location(14, 13));
+        assert_eq!(map.location(3), location(14, 13));
+        assert_eq!(map.location(4), None);
+        assert_eq!(map.location(5), location(20, 5));
+        assert_eq!(map.location(6), None);
+        assert_eq!(location(20, 5).unwrap().to_string(), "src/Counter.sol:20:5");
+    }
+
+    #[test]
+    fn handles_assembly_without_debug_info() {
+        let map = EraSourceMap::parse("\t.text\n__entry:\n\tadd\tr1, r0, r2\n\tret\n");
+        assert!(map.is_empty());
+        assert_eq!(map.location(0), None);
+    }
+}
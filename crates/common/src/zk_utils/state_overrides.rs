@@ -0,0 +1,148 @@
+//! Overrides of the state read by the zkEVM, applied without modifying the underlying state.
+
+use std::collections::HashMap;
+use zksync_basic_types::{AccountTreeId, H160, H256, U256};
+use zksync_types::{
+    get_code_key, get_nonce_key, storage_key_for_eth_balance,
+    utils::{decompose_full_nonce, nonces_to_full_nonce},
+    StorageKey, KNOWN_CODES_STORAGE_ADDRESS,
+};
+use zksync_utils::{bytecode::hash_bytecode, h256_to_u256, u256_to_h256};
+
+/// Balances, nonces, code and storage of accounts overriding the state read by the zkEVM.
+///
+/// The overrides only apply to the reads, so e.g. a fork cache underneath is left untouched and
+/// the overridden values are not persisted unless written by the transaction itself.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ZkStateOverrides {
+    /// Overridden storage slots, including the balances and the code of the accounts.
+    slots: HashMap<StorageKey, H256>,
+    /// Overridden transaction nonces, keyed by the NonceHolder slot of the account. The deploy
+    /// nonce is kept from the underlying state.
+    nonces: HashMap<StorageKey, U256>,
+    /// Bytecodes of the overridden code, by hash.
+    factory_deps: HashMap<H256, Vec<u8>>,
+}
+
+impl ZkStateOverrides {
+    /// Returns whether there are no overrides.
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty() && self.nonces.is_empty()
+    }
+
+    /// Overrides the balance of `address`.
+    pub fn set_balance(&mut self, address: H160, balance: U256) -> &mut Self {
+        self.slots.insert(storage_key_for_eth_balance(&address), u256_to_h256(balance));
+        self
+    }
+
+    /// Overrides the transaction nonce of `address`.
+    pub fn set_nonce(&mut self, address: H160, nonce: U256) -> &mut Self {
+        self.nonces.insert(get_nonce_key(&address), nonce);
+        self
+    }
+
+    /// Overrides the EraVM bytecode of `address`. The bytecode is also marked as known, so that
+    /// it can be executed.
+    pub fn set_code(&mut self, address: H160, code: Vec<u8>) -> &mut Self {
+        let hash = hash_bytecode(&code);
+        self.slots.insert(get_code_key(&address), hash);
+        self.slots.insert(
+            StorageKey::new(AccountTreeId::new(KNOWN_CODES_STORAGE_ADDRESS), hash),
+            H256::from_low_u64_be(1),
+        );
+        self.factory_deps.insert(hash, code);
+        self
+    }
+
+    /// Overrides the value of `slot` of `address`.
+    pub fn set_storage(&mut self, address: H160, slot: H256, value: H256) -> &mut Self {
+        self.slots.insert(StorageKey::new(AccountTreeId::new(address), slot), value);
+        self
+    }
+
+    /// Returns whether the value of `key` is overridden.
+    pub fn is_overridden(&self, key: &StorageKey) -> bool {
+        self.slots.contains_key(key) || self.nonces.contains_key(key)
+    }
+
+    /// Returns the overridden value of `key`, if any, given a way to read its value in the
+    /// underlying state.
+    pub fn read(&self, key: &StorageKey, underlying: impl FnOnce() -> H256) -> Option<H256> {
+        if let Some(value) = self.slots.get(key) {
+            return Some(*value)
+        }
+        self.nonces.get(key).map(|nonce| {
+            let (_, deploy_nonce) = decompose_full_nonce(h256_to_u256(underlying()));
+            u256_to_h256(nonces_to_full_nonce(*nonce, deploy_nonce))
+        })
+    }
+
+    /// Returns the overridden balance of `address`, if any.
+    pub fn balance(&self, address: H160) -> Option<U256> {
+        self.slots.get(&storage_key_for_eth_balance(&address)).map(|balance| h256_to_u256(*balance))
+    }
+
+    /// Returns the overridden storage keys, including the NonceHolder slots of the overridden
+    /// nonces.
+    pub fn keys(&self) -> impl Iterator<Item = &StorageKey> {
+        self.slots.keys().chain(self.nonces.keys())
+    }
+
+    /// Returns the bytecode of an overridden code with the given hash, if any.
+    pub fn factory_dep(&self, hash: &H256) -> Option<&Vec<u8>> {
+        self.factory_deps.get(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_overridden_slots() {
+        let address = H160::repeat_byte(0x1);
+        let mut overrides = ZkStateOverrides::default();
+        assert!(overrides.is_empty());
+        overrides.set_balance(address, U256::from(100)).set_storage(
+            address,
+            H256::zero(),
+            H256::from_low_u64_be(42),
+        );
+
+        let slot = StorageKey::new(AccountTreeId::new(address), H256::zero());
+        let value = overrides.read(&slot, || unreachable!("overridden slots are not read"));
+        assert_eq!(value, Some(H256::from_low_u64_be(42)));
+        assert_eq!(overrides.balance(address), Some(U256::from(100)));
+        assert_eq!(overrides.balance(H160::repeat_byte(0x2)), None);
+
+        let other = StorageKey::new(AccountTreeId::new(address), H256::from_low_u64_be(1));
+        assert!(!overrides.is_overridden(&other));
+        assert_eq!(overrides.read(&other, H256::zero), None);
+    }
+
+    #[test]
+    fn keeps_the_underlying_deploy_nonce() {
+        let address = H160::repeat_byte(0x1);
+        let mut overrides = ZkStateOverrides::default();
+        overrides.set_nonce(address, U256::from(7));
+
+        let underlying = u256_to_h256(nonces_to_full_nonce(U256::from(1), U256::from(3)));
+        let full_nonce = overrides.read(&get_nonce_key(&address), || underlying).unwrap();
+        assert_eq!(decompose_full_nonce(h256_to_u256(full_nonce)), (U256::from(7), U256::from(3)));
+    }
+
+    #[test]
+    fn marks_overridden_code_as_known() {
+        let address = H160::repeat_byte(0x1);
+        let code = vec![0; 32];
+        let hash = hash_bytecode(&code);
+        let mut overrides = ZkStateOverrides::default();
+        overrides.set_code(address, code.clone());
+
+        assert_eq!(overrides.read(&get_code_key(&address), H256::zero), Some(hash));
+        let known_code_key = StorageKey::new(AccountTreeId::new(KNOWN_CODES_STORAGE_ADDRESS), hash);
+        assert_eq!(overrides.read(&known_code_key, H256::zero), Some(H256::from_low_u64_be(1)));
+        assert_eq!(overrides.factory_dep(&hash), Some(&code));
+    }
+}
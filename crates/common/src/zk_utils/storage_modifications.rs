@@ -0,0 +1,96 @@
+//! Storage modifications recorded during test executions.
+
+use std::collections::HashMap;
+use zksync_types::{StorageKey, StorageValue};
+use zksync_web3_rs::types::H256;
+
+/// Recorded storage modifications.
+#[derive(Default, Debug, Clone)]
+pub struct StorageModifications {
+    /// Storage key modifications.
+    keys: HashMap<StorageKey, StorageValue>,
+    /// Bytecode modifications.
+    bytecodes: HashMap<H256, Vec<u8>>,
+    /// Recorded known codes.
+    known_codes: HashMap<H256, Vec<u8>>,
+}
+
+impl StorageModifications {
+    /// Creates a change-set out of the given modifications.
+    pub fn new(
+        keys: HashMap<StorageKey, StorageValue>,
+        bytecodes: HashMap<H256, Vec<u8>>,
+        known_codes: HashMap<H256, Vec<u8>>,
+    ) -> Self {
+        Self { keys, bytecodes, known_codes }
+    }
+
+    /// Returns the modified storage slots.
+    pub fn keys(&self) -> &HashMap<StorageKey, StorageValue> {
+        &self.keys
+    }
+
+    /// Returns the published bytecodes.
+    pub fn bytecodes(&self) -> &HashMap<H256, Vec<u8>> {
+        &self.bytecodes
+    }
+
+    /// Returns the recorded known codes.
+    pub fn known_codes(&self) -> &HashMap<H256, Vec<u8>> {
+        &self.known_codes
+    }
+
+    /// Records a write to a storage slot.
+    pub fn insert_slot(&mut self, key: StorageKey, value: StorageValue) {
+        self.keys.insert(key, value);
+    }
+
+    /// Records a published bytecode.
+    pub fn insert_bytecode(&mut self, hash: H256, code: Vec<u8>) {
+        self.bytecodes.insert(hash, code);
+    }
+
+    /// Records a known code.
+    pub fn insert_known_code(&mut self, hash: H256, code: Vec<u8>) {
+        self.known_codes.insert(hash, code);
+    }
+
+    /// Updates current modifications with the provided modifications.
+    pub fn extend(&mut self, other: StorageModifications) {
+        self.keys.extend(other.keys);
+        self.bytecodes.extend(other.bytecodes);
+        self.known_codes.extend(other.known_codes);
+    }
+}
+
+/// Keeps track of storage modifications performed during test executions.
+/// This is especially important when forking to re-apply changes.
+pub trait StorageModificationRecorder {
+    /// Merge modified keys and bytecodes into the existing modifications
+    fn record_storage_modifications(&mut self, storage_modifications: StorageModifications);
+
+    /// Return all modified keys
+    fn get_storage_modifications(&self) -> &StorageModifications;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zksync_types::AccountTreeId;
+
+    #[test]
+    fn extends_modifications() {
+        let key = StorageKey::new(AccountTreeId::default(), H256::zero());
+        let mut modifications = StorageModifications::default();
+        modifications.insert_slot(key, H256::from_low_u64_be(1));
+
+        let mut other = StorageModifications::default();
+        other.insert_slot(key, H256::from_low_u64_be(2));
+        other.insert_known_code(H256::from_low_u64_be(3), vec![0; 32]);
+        modifications.extend(other);
+
+        assert_eq!(modifications.keys()[&key], H256::from_low_u64_be(2));
+        assert!(modifications.bytecodes().is_empty());
+        assert_eq!(modifications.known_codes().len(), 1);
+    }
+}
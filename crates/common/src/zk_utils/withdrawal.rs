@@ -0,0 +1,272 @@
+//! Finalization of L2 → L1 withdrawals, i.e. the L1 call releasing the withdrawn funds, built
+//! from the withdrawal transaction and the proof of its message from `zks_getL2ToL1LogProof`.
+
+use crate::RetryProvider;
+use alloy_primitives::{address, Address, Bytes, B256, U256, U64};
+use alloy_sol_types::{sol, sol_data, SolCall, SolEvent, SolType};
+use eyre::{Result, WrapErr};
+use serde::Deserialize;
+
+/// The system contract sending the L2 → L1 messages.
+pub const L1_MESSENGER_ADDRESS: Address = address!("0000000000000000000000000000000000008008");
+
+/// The system contract of the base token, which sends the messages of ETH withdrawals.
+pub const L2_ETH_TOKEN_ADDRESS: Address = address!("000000000000000000000000000000000000800a");
+
+sol! {
+    event L1MessageSent(address indexed _sender, bytes32 indexed _hash, bytes _message);
+
+    /// Finalizes an ETH withdrawal, on the main zkSync contract.
+    function finalizeEthWithdrawal(
+        uint256 _l2BatchNumber,
+        uint256 _l2MessageIndex,
+        uint16 _l2TxNumberInBatch,
+        bytes calldata _message,
+        bytes32[] calldata _merkleProof
+    );
+
+    /// Finalizes an ERC20 withdrawal, on the L1 bridge.
+    function finalizeWithdrawal(
+        uint256 _l2BatchNumber,
+        uint256 _l2MessageIndex,
+        uint16 _l2TxNumberInBatch,
+        bytes calldata _message,
+        bytes32[] calldata _merkleProof
+    );
+}
+
+/// The parameters of the L1 call finalizing a withdrawal.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FinalizeWithdrawal {
+    /// The contract which sent the withdrawal message, i.e. the L2 bridge or the base token.
+    pub sender: Address,
+    pub l1_batch_number: u64,
+    /// The index of the message in the Merkle tree of the messages of the batch.
+    pub l2_message_index: u64,
+    pub l2_tx_number_in_batch: u16,
+    pub message: Bytes,
+    pub merkle_proof: Vec<B256>,
+}
+
+impl FinalizeWithdrawal {
+    /// Returns whether the withdrawal is an ETH one, finalized on the main zkSync contract rather
+    /// than on the L1 bridge.
+    pub fn is_eth(&self) -> bool {
+        self.sender == L2_ETH_TOKEN_ADDRESS
+    }
+
+    /// Returns the calldata of the L1 call finalizing the withdrawal.
+    pub fn calldata(&self) -> Vec<u8> {
+        let (batch, index) = (U256::from(self.l1_batch_number), U256::from(self.l2_message_index));
+        if self.is_eth() {
+            finalizeEthWithdrawalCall {
+                _l2BatchNumber: batch,
+                _l2MessageIndex: index,
+                _l2TxNumberInBatch: self.l2_tx_number_in_batch,
+                _message: self.message.to_vec(),
+                _merkleProof: self.merkle_proof.clone(),
+            }
+            .abi_encode()
+        } else {
+            finalizeWithdrawalCall {
+                _l2BatchNumber: batch,
+                _l2MessageIndex: index,
+                _l2TxNumberInBatch: self.l2_tx_number_in_batch,
+                _message: self.message.to_vec(),
+                _merkleProof: self.merkle_proof.clone(),
+            }
+            .abi_encode()
+        }
+    }
+}
+
+/// The subset of a zkSync transaction receipt locating the withdrawal message.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WithdrawalReceipt {
+    l1_batch_number: Option<U64>,
+    l1_batch_tx_index: Option<U64>,
+    logs: Vec<ReceiptLog>,
+    l2_to_l1_logs: Vec<L2ToL1Log>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReceiptLog {
+    address: Address,
+    topics: Vec<B256>,
+    data: Bytes,
+}
+
+#[derive(Debug, Deserialize)]
+struct L2ToL1Log {
+    sender: Address,
+}
+
+#[derive(Debug, Deserialize)]
+struct L2ToL1LogProof {
+    proof: Vec<B256>,
+    id: u64,
+}
+
+impl WithdrawalReceipt {
+    /// Returns the parameters of the `index`th withdrawal of the transaction, without its proof,
+    /// and the index of its message among the L2 → L1 logs of the transaction.
+    fn withdrawal(&self, index: usize) -> Result<(FinalizeWithdrawal, usize)> {
+        let (Some(l1_batch_number), Some(l1_batch_tx_index)) =
+            (self.l1_batch_number, self.l1_batch_tx_index)
+        else {
+            eyre::bail!("The withdrawal isn't included in a sealed L1 batch yet")
+        };
+
+        let (sender, message) = self
+            .logs
+            .iter()
+            .filter(|log| {
+                log.address == L1_MESSENGER_ADDRESS &&
+                    log.topics.first() == Some(&L1MessageSent::SIGNATURE_HASH)
+            })
+            .nth(index)
+            .and_then(|log| {
+                let sender = Address::from_word(*log.topics.get(1)?);
+                let message = sol_data::Bytes::abi_decode(&log.data, true).ok()?;
+                Some((sender, message.into()))
+            })
+            .ok_or_else(|| eyre::eyre!("The transaction has no withdrawal message {index}"))?;
+
+        let log_index = self
+            .l2_to_l1_logs
+            .iter()
+            .enumerate()
+            .filter(|(_, log)| log.sender == L1_MESSENGER_ADDRESS)
+            .nth(index)
+            .map(|(log_index, _)| log_index)
+            .ok_or_else(|| eyre::eyre!("The transaction has no L2 → L1 log {index}"))?;
+
+        let withdrawal = FinalizeWithdrawal {
+            sender,
+            l1_batch_number: l1_batch_number.to(),
+            l2_tx_number_in_batch: l1_batch_tx_index.to(),
+            message,
+            ..Default::default()
+        };
+        Ok((withdrawal, log_index))
+    }
+}
+
+/// Fetches the parameters finalizing the `index`th withdrawal of the L2 transaction.
+///
+/// Fails if the batch of the withdrawal isn't executed on L1 yet, since it can't be finalized
+/// before.
+pub async fn finalize_withdrawal_params(
+    provider: &RetryProvider,
+    tx_hash: B256,
+    index: usize,
+) -> Result<FinalizeWithdrawal> {
+    let receipt: Option<WithdrawalReceipt> = provider
+        .request("eth_getTransactionReceipt", [tx_hash])
+        .await
+        .wrap_err_with(|| format!("Failed to fetch the receipt of {tx_hash}"))?;
+    let receipt = receipt.ok_or_else(|| eyre::eyre!("Withdrawal {tx_hash} not found"))?;
+    let (mut withdrawal, log_index) = receipt.withdrawal(index)?;
+
+    let proof: Option<L2ToL1LogProof> = provider
+        .request("zks_getL2ToL1LogProof", (tx_hash, log_index))
+        .await
+        .wrap_err("Failed to fetch the proof of the withdrawal message")?;
+    let proof = proof.ok_or_else(|| {
+        eyre::eyre!("No proof available for {tx_hash} yet, its L1 batch must be executed first")
+    })?;
+    withdrawal.l2_message_index = proof.id;
+    withdrawal.merkle_proof = proof.proof;
+    Ok(withdrawal)
+}
+
+/// The subset of `zks_getBridgeContracts` needed to finalize withdrawals.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BridgeContracts {
+    l1_erc20_default_bridge: Address,
+}
+
+/// Returns the L1 contract finalizing the withdrawal: the main zkSync contract for ETH, the
+/// default L1 bridge otherwise.
+pub async fn finalize_withdrawal_target(
+    provider: &RetryProvider,
+    withdrawal: &FinalizeWithdrawal,
+) -> Result<Address> {
+    if withdrawal.is_eth() {
+        provider
+            .request("zks_getMainContract", ())
+            .await
+            .wrap_err("Failed to fetch the main zkSync contract")
+    } else {
+        let bridges: BridgeContracts = provider
+            .request("zks_getBridgeContracts", ())
+            .await
+            .wrap_err("Failed to fetch the bridge contracts")?;
+        Ok(bridges.l1_erc20_default_bridge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt(message: &[u8]) -> WithdrawalReceipt {
+        WithdrawalReceipt {
+            l1_batch_number: Some(U64::from(12)),
+            l1_batch_tx_index: Some(U64::from(3)),
+            logs: vec![
+                ReceiptLog { address: Address::repeat_byte(1), topics: vec![], data: Bytes::new() },
+                ReceiptLog {
+                    address: L1_MESSENGER_ADDRESS,
+                    topics: vec![
+                        L1MessageSent::SIGNATURE_HASH,
+                        L2_ETH_TOKEN_ADDRESS.into_word(),
+                        B256::ZERO,
+                    ],
+                    data: sol_data::Bytes::abi_encode(&message.to_vec()).into(),
+                },
+            ],
+            l2_to_l1_logs: vec![
+                L2ToL1Log { sender: Address::repeat_byte(2) },
+                L2ToL1Log { sender: L1_MESSENGER_ADDRESS },
+            ],
+        }
+    }
+
+    #[test]
+    fn finds_withdrawal_message() {
+        let (withdrawal, log_index) = receipt(&[1, 2, 3]).withdrawal(0).unwrap();
+        assert_eq!(log_index, 1);
+        assert!(withdrawal.is_eth());
+        assert_eq!(withdrawal.l1_batch_number, 12);
+        assert_eq!(withdrawal.l2_tx_number_in_batch, 3);
+        assert_eq!(withdrawal.message, Bytes::from(vec![1, 2, 3]));
+
+        assert!(receipt(&[]).withdrawal(1).is_err());
+        let pending = WithdrawalReceipt { l1_batch_number: None, ..receipt(&[]) };
+        assert!(pending.withdrawal(0).is_err());
+    }
+
+    #[test]
+    fn encodes_finalization_call() {
+        let mut withdrawal = FinalizeWithdrawal {
+            sender: L2_ETH_TOKEN_ADDRESS,
+            l1_batch_number: 12,
+            l2_message_index: 4,
+            l2_tx_number_in_batch: 3,
+            message: vec![1, 2, 3].into(),
+            merkle_proof: vec![B256::repeat_byte(5)],
+        };
+        let calldata = withdrawal.calldata();
+        assert_eq!(calldata[..4], finalizeEthWithdrawalCall::SELECTOR);
+        let call = finalizeEthWithdrawalCall::abi_decode(&calldata, true).unwrap();
+        assert_eq!(call._l2BatchNumber, U256::from(12));
+        assert_eq!(call._l2MessageIndex, U256::from(4));
+        assert_eq!(call._merkleProof, vec![B256::repeat_byte(5)]);
+
+        withdrawal.sender = Address::repeat_byte(9);
+        assert_eq!(withdrawal.calldata()[..4], finalizeWithdrawalCall::SELECTOR);
+    }
+}
@@ -0,0 +1,68 @@
+//! Configuration of the cheatcodes a project can call
+
+use serde::{Deserialize, Serialize};
+
+/// The cheatcodes the tests and scripts of a project can call, e.g. to disable the ones touching
+/// the host in a CI profile.
+///
+/// Cheatcodes are named by their identifier, e.g. `ffi`, `writeFile` or `setEnv`, which covers all
+/// their overloads, or e.g. `writeJson_1` for a single overload.
+///
+/// E.g.
+///
+/// ```toml
+/// [profile.ci.cheatcodes]
+/// disabled = ["ffi", "writeFile", "setEnv"]
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CheatcodeAccess {
+    /// If set, only these cheatcodes can be called.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<Vec<String>>,
+    /// Cheatcodes that can't be called, even if enabled.
+    pub disabled: Vec<String>,
+}
+
+impl CheatcodeAccess {
+    /// Returns whether the cheatcode with the given identifier, e.g. `writeJson_1`, can be called.
+    pub fn is_enabled(&self, id: &str) -> bool {
+        // overloads are identified by a `_<index>` suffix
+        let name = id
+            .rsplit_once('_')
+            .filter(|(_, index)| !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()))
+            .map_or(id, |(name, _)| name);
+        let matches = |listed: &String| listed == id || listed == name;
+
+        self.enabled.as_ref().map_or(true, |enabled| enabled.iter().any(matches)) &&
+            !self.disabled.iter().any(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_cheatcodes_and_their_overloads() {
+        let access = CheatcodeAccess {
+            enabled: None,
+            disabled: vec!["ffi".to_string(), "writeJson_1".to_string()],
+        };
+        assert!(!access.is_enabled("ffi"));
+        assert!(access.is_enabled("tryFfi"));
+        assert!(access.is_enabled("writeJson_0"));
+        assert!(!access.is_enabled("writeJson_1"));
+
+        let access = CheatcodeAccess {
+            enabled: Some(vec!["writeJson".to_string(), "ffi".to_string()]),
+            disabled: vec!["ffi".to_string()],
+        };
+        assert!(access.is_enabled("writeJson_0"));
+        assert!(access.is_enabled("writeJson_1"));
+        assert!(!access.is_enabled("ffi"));
+        assert!(!access.is_enabled("setEnv"));
+
+        assert!(CheatcodeAccess::default().is_enabled("setEnv"));
+    }
+}
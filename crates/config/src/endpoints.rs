@@ -164,3 +164,43 @@ impl DerefMut for ResolvedRpcEndpoints {
         &mut self.endpoints
     }
 }
+
+/// How the RPC URLs are returned by the `rpcUrl` and `rpcUrls` cheatcodes, e.g. to keep the API
+/// keys they embed out of test data and logs.
+///
+/// The returned values can still be passed to cheatcodes like `createFork`, which resolve them to
+/// the configured URL.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcUrlRedaction {
+    /// URLs are returned as configured.
+    #[default]
+    None,
+    /// The credentials, path and query of URLs are replaced with `***`, e.g.
+    /// `https://eth-mainnet.alchemyapi.io/***`.
+    Secrets,
+    /// The alias of the endpoint is returned instead of its URL.
+    Alias,
+}
+
+impl RpcUrlRedaction {
+    /// Returns the value to return for the endpoint `alias` with the given URL.
+    pub fn redact(&self, alias: &str, url: &str) -> String {
+        match self {
+            Self::None => url.to_string(),
+            Self::Secrets => redact_url(url),
+            Self::Alias => alias.to_string(),
+        }
+    }
+}
+
+/// Keeps the scheme, host and port of `url`, replacing anything that may hold a secret.
+fn redact_url(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else { return "***".to_string() };
+    let (authority, rest) = rest.split_at(rest.find(['/', '?', '#']).unwrap_or(rest.len()));
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    if host.len() == authority.len() && rest.trim_start_matches('/').is_empty() {
+        return url.to_string()
+    }
+    format!("{scheme}://{host}/***")
+}
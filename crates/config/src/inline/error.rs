@@ -14,6 +14,9 @@ pub enum InlineConfigParserError {
     /// An error occurred while trying to parse a boolean configuration value
     #[error("Invalid config value for key '{0}'. Unable to parse '{1}' into a boolean value")]
     ParseBool(String, String),
+    /// An integer configuration value exceeds its maximum
+    #[error("Invalid config value for key '{0}'. '{1}' exceeds the maximum of {2}")]
+    OutOfRange(String, String, u64),
 }
 
 /// Wrapper error struct that catches config parsing
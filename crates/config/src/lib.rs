@@ -32,7 +32,7 @@ use semver::Version;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs,
     path::{Path, PathBuf},
     str::FromStr,
@@ -46,7 +46,7 @@ pub mod utils;
 pub use crate::utils::*;
 
 mod endpoints;
-pub use endpoints::{ResolvedRpcEndpoints, RpcEndpoint, RpcEndpoints};
+pub use endpoints::{ResolvedRpcEndpoints, RpcEndpoint, RpcEndpoints, RpcUrlRedaction};
 
 mod etherscan;
 mod resolve;
@@ -61,6 +61,9 @@ pub use fmt::FormatterConfig;
 pub mod fs_permissions;
 pub use crate::fs_permissions::FsPermissions;
 
+pub mod cheatcode_access;
+pub use cheatcode_access::CheatcodeAccess;
+
 pub mod error;
 pub use error::SolidityErrorCode;
 
@@ -97,7 +100,17 @@ use providers::remappings::RemappingsProvider;
 mod inline;
 pub use inline::{validate_profiles, InlineConfig, InlineConfigError, InlineConfigParser, NatSpec};
 // @zkSync - zksolc configuration and settings
+pub mod zk_execution_mode;
+pub mod zk_fork_retries;
+pub mod zk_gas;
+pub mod zk_genesis;
+pub mod zk_tracers;
 pub mod zksolc_config;
+pub use zk_execution_mode::ZkExecutionMode;
+pub use zk_fork_retries::ZkForkRetriesConfig;
+pub use zk_gas::{ZkGasConfig, MAX_ZK_GAS_LIMIT};
+pub use zk_genesis::ZkGenesisAccount;
+pub use zk_tracers::ZkTracer;
 use zksolc_config::{
     Optimizer as OptimizerSettings, Settings as ZkSettings, ZkSolcConfig, ZkSolcConfigBuilder,
 };
@@ -243,6 +256,8 @@ pub struct Config {
     pub invariant: InvariantConfig,
     /// Whether to allow ffi cheatcodes in test
     pub ffi: bool,
+    /// Whether to record logs from the start of every test, as if `vm.recordLogs` was called
+    pub record_logs: bool,
     /// The address which will be executing all tests
     pub sender: Address,
     /// The tx.origin value during EVM execution
@@ -253,6 +268,22 @@ pub struct Config {
     pub block_number: u64,
     /// pins the block number for the state fork
     pub fork_block_number: Option<u64>,
+    /// Whether to record the state fetched by forks, to prefetch it concurrently when forking the
+    /// same chain in the next run
+    pub fork_prefetch: bool,
+    /// Accounts to prefetch concurrently when creating a fork
+    pub fork_prefetch_accounts: Vec<Address>,
+    /// Whether forks fetch storage with `zks_getProof` and verify it against the root hash of the
+    /// L1 batch of the fork block, which must then be the last block of its batch.
+    ///
+    /// Only the storage is proven, the code, balance and nonce of accounts are not.
+    pub fork_verify_storage: bool,
+    /// The L1 endpoint the root hashes storage is verified against are checked on, required with
+    /// `fork_verify_storage`
+    pub fork_verify_l1_url: Option<String>,
+    /// The main zkSync contract on L1 storing the commitments of the batches of the forked chain,
+    /// known for zkSync Era mainnet and Sepolia
+    pub fork_verify_main_contract: Option<Address>,
     /// The chain name or EIP-155 chain ID.
     #[serde(rename = "chain_id", alias = "chain")]
     pub chain: Option<Chain>,
@@ -274,6 +305,9 @@ pub struct Config {
     /// The `block.difficulty` value during EVM execution.
     pub block_difficulty: u64,
     /// Before merge the `block.max_hash`, after merge it is `block.prevrandao`.
+    ///
+    /// In the zkEVM, `block.prevrandao` reads the difficulty of the SystemContext, which is seeded
+    /// with this value unless zero.
     pub block_prevrandao: B256,
     /// the `block.gaslimit` value during EVM execution
     pub block_gas_limit: Option<GasLimit>,
@@ -330,6 +364,8 @@ pub struct Config {
     /// Multiple rpc endpoints and their aliases
     #[serde(default, skip_serializing_if = "RpcEndpoints::is_empty")]
     pub rpc_endpoints: RpcEndpoints,
+    /// How the `rpcUrl` and `rpcUrls` cheatcodes return the URLs of the `rpc_endpoints`
+    pub rpc_url_redaction: RpcUrlRedaction,
     /// Whether to store the referenced sources in the metadata as literal data.
     pub use_literal_content: bool,
     /// Whether to include the metadata hash.
@@ -366,6 +402,12 @@ pub struct Config {
     ///
     /// This includes what operations can be executed (read, write)
     pub fs_permissions: FsPermissions,
+    /// The cheatcodes that can be called, e.g. to disable `ffi` and the file writes in CI.
+    pub cheatcodes: CheatcodeAccess,
+    /// Names of known addresses, used in traces and broadcast artifacts alongside the labels set
+    /// with `vm.label`, which take precedence.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<Address, String>,
 
     /// Temporary config to enable [SpecId::CANCUN]
     ///
@@ -413,6 +455,40 @@ pub struct Config {
     pub force_evmla: bool,
     /// Path to cache missing library dependencies, used for compiling and deploying libraries.
     pub detect_missing_libraries: bool,
+    /// Addresses in the range reserved for system contracts, i.e. below `2^16`, that the zkEVM
+    /// cheatcodes track like user contracts, e.g. precompiles of custom chains.
+    pub zk_tracked_reserved_addresses: Vec<Address>,
+    /// Accounts seeded into the zkEVM before the test contracts are deployed.
+    pub zk_genesis: Vec<ZkGenesisAccount>,
+    /// How many times a test failing with a provider error of its fork is retried before being
+    /// reported as failed. Can be overridden per test with
+    /// `forge-config: default.zk-fork.retries = <n>`.
+    pub zk_fork_test_retries: u32,
+    /// Delay before the first retry of a fork test, in milliseconds, doubled for every further
+    /// retry. Can be overridden per test with `forge-config: default.zk-fork.retry-backoff =
+    /// <ms>`.
+    pub zk_fork_test_retry_backoff: u64,
+    /// Whether fork tests still failing with a provider error after all retries are quarantined,
+    /// i.e. reported without failing the run. Can be overridden per test with
+    /// `forge-config: default.zk-fork.quarantine = <bool>`.
+    pub zk_fork_test_quarantine: bool,
+    /// Whether selectors and events unknown to the compiled ABIs are looked up in the openchain
+    /// signature database to decode zkEVM traces and failure messages. Previously cached
+    /// signatures still decode the custom errors of failing tests either way, but not traces.
+    pub zk_signature_lookup: bool,
+    /// The mode the zkEVM bootloader runs the test transactions in, e.g. `eth_call` to skip the
+    /// account validation.
+    pub zk_execution_mode: ZkExecutionMode,
+    /// The tracers run alongside the zkEVM cheatcodes that are disabled, making the cheatcodes
+    /// relying on them revert, e.g. `forks` to keep tests from forking.
+    pub zk_disabled_tracers: Vec<ZkTracer>,
+    /// The gas limit of the test transactions run in the zkEVM, if it overrides `gas_limit`, at
+    /// most [zk_gas::MAX_ZK_GAS_LIMIT], which is also the default.
+    /// Can be overridden per test with `forge-config: default.zk.gas-limit = <gas>`.
+    pub zk_gas_limit: Option<u64>,
+    /// Whether the counterexamples of failing fuzz tests and the inputs reaching new coverage are
+    /// persisted to `<cache>/zk-fuzz-corpus` and run first on subsequent runs.
+    pub zk_fuzz_corpus: bool,
 }
 
 /// Mapping of fallback standalone sections. See [`FallbackProfileProvider`]
@@ -434,7 +510,7 @@ impl Config {
 
     /// Standalone sections in the config which get integrated into the selected profile
     pub const STANDALONE_SECTIONS: &'static [&'static str] =
-        &["rpc_endpoints", "etherscan", "fmt", "doc", "fuzz", "invariant"];
+        &["rpc_endpoints", "etherscan", "fmt", "doc", "fuzz", "invariant", "labels"];
 
     /// File name of config toml file
     pub const FILE_NAME: &'static str = "foundry.toml";
@@ -1038,6 +1114,12 @@ impl Config {
         Optimizer { enabled: Some(self.optimizer), runs: Some(self.optimizer_runs), details }
     }
 
+    /// Returns whether unknown selectors must not be looked up on openchain, in which case only
+    /// the custom errors of failing tests are decoded, from the cached signatures.
+    pub fn zk_signatures_offline(&self) -> bool {
+        self.offline || !self.zk_signature_lookup
+    }
+
     /// Returns the zksolc `OptimizerSettings` based on the configured settings
     pub fn zk_optimizer(&self) -> OptimizerSettings {
         let mode = self.mode.clone();
@@ -1836,6 +1918,8 @@ impl Default for Config {
         Self {
             profile: Self::DEFAULT_PROFILE,
             fs_permissions: FsPermissions::new([PathPermission::read("out")]),
+            cheatcodes: Default::default(),
+            labels: Default::default(),
             cancun: false,
             __root: Default::default(),
             src: "src".into(),
@@ -1872,11 +1956,17 @@ impl Default for Config {
             fuzz: Default::default(),
             invariant: Default::default(),
             ffi: false,
+            record_logs: false,
             sender: Config::DEFAULT_SENDER,
             tx_origin: Config::DEFAULT_SENDER,
             initial_balance: U256::from(0xffffffffffffffffffffffffu128),
             block_number: 0,
             fork_block_number: None,
+            fork_prefetch: false,
+            fork_verify_storage: false,
+            fork_verify_l1_url: None,
+            fork_verify_main_contract: None,
+            fork_prefetch_accounts: vec![],
             chain: None,
             gas_limit: i64::MAX.into(),
             code_size_limit: None,
@@ -1904,6 +1994,7 @@ impl Default for Config {
             via_ir: false,
             rpc_storage_caching: Default::default(),
             rpc_endpoints: Default::default(),
+            rpc_url_redaction: Default::default(),
             etherscan: Default::default(),
             no_storage_caching: false,
             no_rpc_rate_limit: false,
@@ -1926,6 +2017,16 @@ impl Default for Config {
             force_evmla: false,
             is_system: false,
             detect_missing_libraries: false,
+            zk_tracked_reserved_addresses: vec![],
+            zk_genesis: vec![],
+            zk_fork_test_retries: 0,
+            zk_fork_test_retry_backoff: 1000,
+            zk_fork_test_quarantine: false,
+            zk_signature_lookup: true,
+            zk_execution_mode: ZkExecutionMode::VerifyExecute,
+            zk_disabled_tracers: vec![],
+            zk_gas_limit: None,
+            zk_fuzz_corpus: false,
         }
     }
 }
@@ -4089,6 +4190,226 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_labels() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+
+                [labels]
+                0x1804c8AB1F12E6bbf3894d4083f33e07309d1f38 = "DefaultSender"
+                0xdAC17F958D2ee523a2206206994597C13D831ec7 = "USDT"
+            "#,
+            )?;
+            let loaded = Config::load();
+
+            assert_eq!(
+                loaded.labels,
+                BTreeMap::from([
+                    (Config::DEFAULT_SENDER, "DefaultSender".to_string()),
+                    (address!("dAC17F958D2ee523a2206206994597C13D831ec7"), "USDT".to_string()),
+                ])
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_zk_tracked_reserved_addresses() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                zk_tracked_reserved_addresses = ["0x0000000000000000000000000000000000000100"]
+            "#,
+            )?;
+            let loaded = Config::load();
+
+            assert_eq!(
+                loaded.zk_tracked_reserved_addresses,
+                vec![address!("0000000000000000000000000000000000000100")]
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_zk_genesis() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+
+                [[profile.default.zk_genesis]]
+                address = "0x0000000000000000000000000000000000010000"
+                balance = "1000"
+                artifact = "src/Registry.sol:Registry"
+                storage = { "0x0" = "0x1" }
+            "#,
+            )?;
+            let loaded = Config::load();
+
+            assert_eq!(
+                loaded.zk_genesis,
+                vec![ZkGenesisAccount {
+                    address: address!("0000000000000000000000000000000000010000"),
+                    balance: Some(U256::from(1000)),
+                    artifact: Some("src/Registry.sol:Registry".to_string()),
+                    storage: BTreeMap::from([(U256::ZERO, U256::from(1))]),
+                }]
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_rpc_url_redaction() {
+        figment::Jail::expect_with(|jail| {
+            let loaded = Config::load();
+            assert_eq!(loaded.rpc_url_redaction, RpcUrlRedaction::None);
+
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                rpc_url_redaction = "secrets"
+            "#,
+            )?;
+            let loaded = Config::load();
+            assert_eq!(loaded.rpc_url_redaction, RpcUrlRedaction::Secrets);
+
+            jail.set_env("FOUNDRY_RPC_URL_REDACTION", "alias");
+            let loaded = Config::load();
+            assert_eq!(loaded.rpc_url_redaction, RpcUrlRedaction::Alias);
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_cheatcode_access() {
+        figment::Jail::expect_with(|jail| {
+            let loaded = Config::load();
+            assert_eq!(loaded.cheatcodes, CheatcodeAccess::default());
+
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                ffi = true
+
+                [profile.ci.cheatcodes]
+                disabled = ["ffi", "writeFile", "setEnv"]
+            "#,
+            )?;
+            let loaded = Config::load();
+            assert!(loaded.cheatcodes.is_enabled("ffi"));
+
+            jail.set_env("FOUNDRY_PROFILE", "ci");
+            let loaded = Config::load();
+            assert_eq!(
+                loaded.cheatcodes,
+                CheatcodeAccess {
+                    enabled: None,
+                    disabled: vec!["ffi".into(), "writeFile".into(), "setEnv".into()],
+                }
+            );
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_zk_execution_mode() {
+        figment::Jail::expect_with(|jail| {
+            let loaded = Config::load();
+            assert_eq!(loaded.zk_execution_mode, ZkExecutionMode::VerifyExecute);
+
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                zk_execution_mode = "eth_call"
+            "#,
+            )?;
+            let loaded = Config::load();
+            assert_eq!(loaded.zk_execution_mode, ZkExecutionMode::EthCall);
+
+            jail.set_env("FOUNDRY_ZK_EXECUTION_MODE", "estimate_fee");
+            let loaded = Config::load();
+            assert_eq!(loaded.zk_execution_mode, ZkExecutionMode::EstimateFee);
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_zk_disabled_tracers() {
+        figment::Jail::expect_with(|jail| {
+            let loaded = Config::load();
+            assert!(loaded.zk_disabled_tracers.is_empty());
+
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                zk_disabled_tracers = ["broadcast", "forks"]
+            "#,
+            )?;
+            let loaded = Config::load();
+            assert_eq!(loaded.zk_disabled_tracers, vec![ZkTracer::Broadcast, ZkTracer::Forks]);
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_zk_gas_limit() {
+        figment::Jail::expect_with(|jail| {
+            let loaded = Config::load();
+            assert_eq!(loaded.zk_gas_limit, None);
+
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                zk_gas_limit = 500000000
+            "#,
+            )?;
+            let loaded = Config::load();
+            assert_eq!(loaded.zk_gas_limit, Some(500_000_000));
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_zk_fuzz_corpus() {
+        figment::Jail::expect_with(|jail| {
+            let loaded = Config::load();
+            assert!(!loaded.zk_fuzz_corpus);
+
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                zk_fuzz_corpus = true
+            "#,
+            )?;
+            let loaded = Config::load();
+            assert!(loaded.zk_fuzz_corpus);
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_optimizer_settings_basic() {
         figment::Jail::expect_with(|jail| {
@@ -0,0 +1,24 @@
+//! Mode the zkEVM bootloader runs the test transactions in
+
+use serde::{Deserialize, Serialize};
+
+/// The mode the bootloader runs the test transactions in.
+///
+/// E.g.
+///
+/// ```toml
+/// [profile.default]
+/// zk_execution_mode = "eth_call"
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ZkExecutionMode {
+    /// Transactions are validated, i.e. the account validation and fee payment run, before being
+    /// executed, as on chain.
+    #[default]
+    VerifyExecute,
+    /// Transactions are executed as an `eth_call`, skipping the validation.
+    EthCall,
+    /// Transactions are executed as when estimating their fee.
+    EstimateFee,
+}
@@ -0,0 +1,81 @@
+//! Retries of tests failing because of the provider of their fork
+
+use crate::inline::{
+    parse_config_bool, parse_config_u32, parse_config_u64, InlineConfigParser,
+    InlineConfigParserError, INLINE_CONFIG_ZK_FORK_KEY,
+};
+
+/// How tests failing with provider errors of their fork are retried and quarantined, from the
+/// `zk_fork_test_*` keys and overridable per test, e.g. for tests hitting a rate limited RPC.
+///
+/// E.g.
+///
+/// ```solidity
+/// /// forge-config: default.zk-fork.retries = 3
+/// /// forge-config: default.zk-fork.retry-backoff = 500
+/// /// forge-config: default.zk-fork.quarantine = true
+/// function testFork_Swap() public {...}
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ZkForkRetriesConfig {
+    /// How many times a test failing with a provider error is retried.
+    pub retries: u32,
+    /// Delay before the first retry, in milliseconds, doubled for every further retry.
+    pub retry_backoff: u64,
+    /// Whether tests still failing with a provider error after all retries are quarantined.
+    pub quarantine: bool,
+}
+
+impl InlineConfigParser for ZkForkRetriesConfig {
+    fn config_key() -> String {
+        INLINE_CONFIG_ZK_FORK_KEY.into()
+    }
+
+    fn try_merge(&self, configs: &[String]) -> Result<Option<Self>, InlineConfigParserError> {
+        let overrides: Vec<(String, String)> = Self::get_config_overrides(configs);
+
+        if overrides.is_empty() {
+            return Ok(None)
+        }
+
+        let mut conf_clone = *self;
+
+        for (key, value) in overrides {
+            match key.as_str() {
+                "retries" => conf_clone.retries = parse_config_u32(key, value)?,
+                "retry-backoff" => conf_clone.retry_backoff = parse_config_u64(key, value)?,
+                "quarantine" => conf_clone.quarantine = parse_config_bool(key, value)?,
+                _ => Err(InlineConfigParserError::InvalidConfigProperty(key))?,
+            }
+        }
+        Ok(Some(conf_clone))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_the_inline_retries() {
+        let base = ZkForkRetriesConfig { retries: 1, retry_backoff: 1000, quarantine: false };
+        let configs = &[
+            "forge-config: default.zk-fork.retries = 3".to_string(),
+            "forge-config: default.zk-fork.quarantine = true".to_string(),
+        ];
+        let merged = base.try_merge(configs).expect("valid config").expect("merged");
+        assert_eq!(
+            merged,
+            ZkForkRetriesConfig { retries: 3, retry_backoff: 1000, quarantine: true }
+        );
+
+        let configs = &["forge-config: default.zk.gas-limit = 500000000".to_string()];
+        assert_eq!(base.try_merge(configs), Ok(None));
+
+        let configs = &["forge-config: default.zk-fork.backoff = 1".to_string()];
+        assert_eq!(
+            base.try_merge(configs),
+            Err(InlineConfigParserError::InvalidConfigProperty("backoff".to_string()))
+        );
+    }
+}
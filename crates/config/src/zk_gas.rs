@@ -0,0 +1,92 @@
+//! Gas limit of the test transactions run in the zkEVM
+
+use crate::inline::{
+    parse_config_u64, InlineConfigParser, InlineConfigParserError, INLINE_CONFIG_ZK_KEY,
+};
+
+/// The maximum gas limit of a transaction run in the zkEVM, 2^31 - 1, leaving the rest of the 2^32
+/// gas of the bootloader to the bootloader itself.
+///
+/// The gas limit of the test transactions is capped to it, so it's also their default gas limit.
+pub const MAX_ZK_GAS_LIMIT: u64 = (u32::MAX >> 1) as u64;
+
+/// The gas limit the zkEVM bootloader runs a test transaction with, from `zk_gas_limit` and
+/// overridable per test, e.g. lowered to enforce a stricter budget.
+///
+/// It can't exceed [MAX_ZK_GAS_LIMIT], which the test transactions run with by default, since the
+/// gas of the bootloader itself is fixed.
+///
+/// E.g.
+///
+/// ```solidity
+/// /// forge-config: default.zk.gas-limit = 30000000
+/// function test_LightOperation() public {...}
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ZkGasConfig {
+    /// The gas limit of the test transaction, if it overrides the gas limit of the evm. It's
+    /// capped to the gas the caller can pay for.
+    pub gas_limit: Option<u64>,
+}
+
+impl InlineConfigParser for ZkGasConfig {
+    fn config_key() -> String {
+        INLINE_CONFIG_ZK_KEY.into()
+    }
+
+    fn try_merge(&self, configs: &[String]) -> Result<Option<Self>, InlineConfigParserError> {
+        let overrides: Vec<(String, String)> = Self::get_config_overrides(configs);
+
+        if overrides.is_empty() {
+            return Ok(None)
+        }
+
+        let mut conf_clone = *self;
+
+        for (key, value) in overrides {
+            match key.as_str() {
+                "gas-limit" => {
+                    let gas_limit = parse_config_u64(key.clone(), value.clone())?;
+                    if gas_limit > MAX_ZK_GAS_LIMIT {
+                        Err(InlineConfigParserError::OutOfRange(key, value, MAX_ZK_GAS_LIMIT))?
+                    }
+                    conf_clone.gas_limit = Some(gas_limit)
+                }
+                _ => Err(InlineConfigParserError::InvalidConfigProperty(key))?,
+            }
+        }
+        Ok(Some(conf_clone))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_the_inline_gas_limit() {
+        let base = ZkGasConfig { gas_limit: Some(1_000_000) };
+        let configs = &["forge-config: default.zk.gas-limit = 500000000".to_string()];
+        let merged = base.try_merge(configs).expect("valid config").expect("merged");
+        assert_eq!(merged.gas_limit, Some(500_000_000));
+
+        let configs = &["forge-config: default.fuzz.runs = 100".to_string()];
+        assert_eq!(base.try_merge(configs), Ok(None));
+
+        let configs = &["forge-config: default.zk.gas-limit = 4294967295".to_string()];
+        assert_eq!(
+            base.try_merge(configs),
+            Err(InlineConfigParserError::OutOfRange(
+                "gas-limit".to_string(),
+                "4294967295".to_string(),
+                MAX_ZK_GAS_LIMIT
+            ))
+        );
+
+        let configs = &["forge-config: default.zk.gas = 1".to_string()];
+        assert_eq!(
+            base.try_merge(configs),
+            Err(InlineConfigParserError::InvalidConfigProperty("gas".to_string()))
+        );
+    }
+}
@@ -0,0 +1,31 @@
+//! Initial state of the zkEVM for tests
+
+use alloy_primitives::{Address, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// An account seeded into the zkEVM before the test contracts are deployed and `setUp()` runs.
+///
+/// E.g.
+///
+/// ```toml
+/// [[profile.default.zk_genesis]]
+/// address = "0x0000000000000000000000000000000000010000"
+/// balance = "1000000000000000000"
+/// artifact = "src/Registry.sol:Registry"
+/// storage = { "0x0" = "0x1" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZkGenesisAccount {
+    /// The address of the account.
+    pub address: Address,
+    /// The balance of the account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    /// The contract deployed to the account, as `<path>:<contract>` or its name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<String>,
+    /// Storage slots of the account, set after its code.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<U256, U256>,
+}
@@ -0,0 +1,25 @@
+//! Tracers of the zkEVM cheatcodes that can be disabled
+
+use serde::{Deserialize, Serialize};
+
+/// A tracer run alongside the zkEVM cheatcodes. Disabled tracers no longer observe the
+/// instructions they need, and the cheatcodes relying on them revert.
+///
+/// E.g.
+///
+/// ```toml
+/// [profile.default]
+/// zk_disabled_tracers = ["broadcast", "forks"]
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ZkTracer {
+    /// Mocks calls, for `vm.mockCall` and its variants.
+    Mocks,
+    /// Checks expected calls and events, for `vm.expectCall`, `vm.expectEmit` and `vm.getCalls`.
+    Expectations,
+    /// Records the calls to broadcast, for `vm.startBroadcast` and `vm.stopBroadcast`.
+    Broadcast,
+    /// Creates and selects forks and snapshots, for `vm.createFork`, `vm.snapshot` and the like.
+    Forks,
+}
@@ -0,0 +1,132 @@
+//! Actions queued by the [CheatcodeTracer](crate::cheatcodes::CheatcodeTracer) while executing an
+//! instruction, and applied once the cycle finishes.
+
+use crate::{cheatcodes::PcOrImm, tracers::ForkAction};
+use std::collections::VecDeque;
+use zksync_basic_types::{H160, H256, U256};
+use zksync_types::StorageKey;
+
+#[derive(Debug, Clone)]
+pub(crate) enum FinishCycleOneTimeActions {
+    StorageWrite { key: StorageKey, read_value: H256, write_value: H256 },
+    StoreFactoryDep { hash: U256, bytecode: Vec<U256> },
+    ForceRevert { error: Vec<u8>, exception_handler: PcOrImm },
+    ForceReturn { data: Vec<u8>, continue_pc: PcOrImm },
+    SetOrigin { origin: H160 },
+    SetMsgSender { sender: H160 },
+    Fork(ForkAction),
+    CapCallErgs { ergs: u32 },
+    FailCurrentFrame,
+}
+
+/// The class of an action, which decides when it's applied relative to the other queued actions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum ActionPriority {
+    /// Actions switching the storage the other actions apply to, i.e. forks and snapshots.
+    Fork,
+    /// Writes to the storage and to the decommitter.
+    Storage,
+    /// Changes to the current or previous frames that keep the program counter.
+    Frame,
+    /// Forced returns and reverts, which move the program counter. At most one is queued per
+    /// cycle, and it's applied last.
+    ControlFlow,
+}
+
+impl FinishCycleOneTimeActions {
+    pub(crate) fn priority(&self) -> ActionPriority {
+        match self {
+            Self::Fork(_) => ActionPriority::Fork,
+            Self::StorageWrite { .. } | Self::StoreFactoryDep { .. } => ActionPriority::Storage,
+            Self::SetOrigin { .. } |
+            Self::SetMsgSender { .. } |
+            Self::CapCallErgs { .. } |
+            Self::FailCurrentFrame => ActionPriority::Frame,
+            Self::ForceRevert { .. } | Self::ForceReturn { .. } => ActionPriority::ControlFlow,
+        }
+    }
+}
+
+/// Queue of the actions to apply at the end of the cycle.
+///
+/// Actions are applied by [ActionPriority], and in the order they were queued within the same
+/// priority, so that e.g. a storage write queued along with a fork switch lands on the new fork.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ActionQueue {
+    actions: VecDeque<FinishCycleOneTimeActions>,
+}
+
+impl ActionQueue {
+    /// Queues an action after the queued actions of the same or lower priority.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, if a control flow action is already queued and another one is pushed.
+    pub(crate) fn push(&mut self, action: FinishCycleOneTimeActions) {
+        let priority = action.priority();
+        debug_assert!(
+            priority != ActionPriority::ControlFlow ||
+                self.actions.back().map_or(true, |last| last.priority() != priority),
+            "only one control flow action can be queued per cycle, got {action:?} after {:?}",
+            self.actions.back()
+        );
+        let idx = self.actions.partition_point(|queued| queued.priority() <= priority);
+        self.actions.insert(idx, action);
+    }
+
+    /// Takes the next action to apply.
+    pub(crate) fn pop(&mut self) -> Option<FinishCycleOneTimeActions> {
+        self.actions.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(mut queue: ActionQueue) -> Vec<String> {
+        std::iter::from_fn(|| queue.pop())
+            .map(|action| match action {
+                FinishCycleOneTimeActions::StorageWrite { write_value, .. } => {
+                    format!("write {}", write_value.to_low_u64_be())
+                }
+                FinishCycleOneTimeActions::Fork(_) => "fork".to_string(),
+                FinishCycleOneTimeActions::SetOrigin { .. } => "origin".to_string(),
+                FinishCycleOneTimeActions::CapCallErgs { ergs } => format!("cap {ergs}"),
+                FinishCycleOneTimeActions::ForceRevert { .. } => "revert".to_string(),
+                other => format!("{other:?}"),
+            })
+            .collect()
+    }
+
+    fn write(value: u64) -> FinishCycleOneTimeActions {
+        FinishCycleOneTimeActions::StorageWrite {
+            key: StorageKey::new(Default::default(), H256::zero()),
+            read_value: H256::zero(),
+            write_value: H256::from_low_u64_be(value),
+        }
+    }
+
+    #[test]
+    fn applies_actions_by_priority_then_fifo() {
+        let mut queue = ActionQueue::default();
+        queue.push(FinishCycleOneTimeActions::ForceRevert { error: vec![], exception_handler: 0 });
+        queue.push(write(1));
+        queue.push(FinishCycleOneTimeActions::CapCallErgs { ergs: 10 });
+        queue
+            .push(FinishCycleOneTimeActions::Fork(ForkAction::SelectFork { fork_id: U256::one() }));
+        queue.push(write(2));
+        queue.push(FinishCycleOneTimeActions::SetOrigin { origin: H160::zero() });
+
+        assert_eq!(drain(queue), ["fork", "write 1", "write 2", "cap 10", "origin", "revert"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "only one control flow action")]
+    #[cfg(debug_assertions)]
+    fn rejects_multiple_control_flow_actions() {
+        let mut queue = ActionQueue::default();
+        queue.push(FinishCycleOneTimeActions::ForceReturn { data: vec![], continue_pc: 0 });
+        queue.push(FinishCycleOneTimeActions::ForceRevert { error: vec![], exception_handler: 0 });
+    }
+}
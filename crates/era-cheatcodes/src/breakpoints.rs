@@ -0,0 +1,105 @@
+//! Breakpoints written with `vm.breakpoint`, so that the debugger can jump to them.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use zksync_basic_types::H160;
+
+/// The instruction a breakpoint was written at, i.e. the one following the far call to
+/// `vm.breakpoint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZkBreakpoint {
+    /// The contract the breakpoint was written in.
+    pub address: H160,
+    /// The contract whose code wrote the breakpoint, which differs from `address` in delegate
+    /// calls.
+    pub code_address: H160,
+    /// Program counter to continue from after the call to the cheatcode.
+    pub pc: u16,
+    /// Depth of the callframe that wrote the breakpoint.
+    pub depth: usize,
+}
+
+/// The breakpoints the [CheatcodeTracer](crate::cheatcodes::CheatcodeTracer) writes or removes,
+/// by character. Removals are kept as `None`, so that they also apply to the breakpoints collected
+/// before, e.g. in `setUp`.
+///
+/// Like [Labels](crate::labels::Labels), clones start out empty and are independent. Use
+/// [ZkBreakpoints::share] to hand the breakpoints to a tracer.
+#[derive(Debug, Default)]
+pub struct ZkBreakpoints(Arc<Mutex<HashMap<char, Option<ZkBreakpoint>>>>);
+
+impl Clone for ZkBreakpoints {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl ZkBreakpoints {
+    /// Returns a handle to the same breakpoints.
+    pub fn share(&self) -> Self {
+        Self(self.0.clone())
+    }
+
+    /// Writes the breakpoint, replacing the previous one with the same character.
+    pub fn insert(&self, point: char, breakpoint: ZkBreakpoint) {
+        self.0.lock().unwrap().insert(point, Some(breakpoint));
+    }
+
+    /// Removes the breakpoint with the character, if any.
+    pub fn remove(&self, point: char) {
+        self.0.lock().unwrap().insert(point, None);
+    }
+
+    /// Takes the breakpoints written since the last call, and the removed ones as `None`.
+    pub fn take(&self) -> HashMap<char, Option<ZkBreakpoint>> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+/// Returns the character of the breakpoint, which must be a single alphabetic one, like in the
+/// EVM.
+pub(crate) fn parse_breakpoint(s: &str) -> Result<char, String> {
+    let mut chars = s.chars();
+    let (Some(point), None) = (chars.next(), chars.next()) else {
+        return Err("breakpoints must be exactly one character".to_string())
+    };
+    if !point.is_alphabetic() {
+        return Err("only alphabetic characters are accepted as breakpoints".to_string())
+    }
+    Ok(point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_single_alphabetic_characters() {
+        assert_eq!(parse_breakpoint("a"), Ok('a'));
+        assert_eq!(parse_breakpoint("Z"), Ok('Z'));
+        assert!(parse_breakpoint("").is_err());
+        assert!(parse_breakpoint("ab").is_err());
+        assert!(parse_breakpoint("1").is_err());
+    }
+
+    #[test]
+    fn shares_breakpoints() {
+        let breakpoints = ZkBreakpoints::default();
+        let breakpoint =
+            ZkBreakpoint { address: H160::zero(), code_address: H160::zero(), pc: 7, depth: 1 };
+        breakpoints.share().insert('a', breakpoint);
+        // clones are independent
+        assert!(breakpoints.clone().take().is_empty());
+
+        breakpoints.share().remove('b');
+        assert_eq!(breakpoints.take(), HashMap::from([('a', Some(breakpoint)), ('b', None)]));
+        assert!(breakpoints.take().is_empty());
+
+        // a removal overrides the breakpoint written before
+        breakpoints.insert('a', breakpoint);
+        breakpoints.remove('a');
+        assert_eq!(breakpoints.take(), HashMap::from([('a', None)]));
+    }
+}
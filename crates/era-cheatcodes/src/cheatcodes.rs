@@ -1,25 +1,47 @@
 use crate::{
+    actions::{ActionQueue, FinishCycleOneTimeActions},
+    breakpoints::{parse_breakpoint, ZkBreakpoint, ZkBreakpoints},
+    error::CheatcodeError,
     events::LogEntry,
-    farcall::{FarCallHandler, MockCall, MockedCalls},
+    farcall::{FarCallHandler, MockCall, MockCallReturn},
+    labels::Labels,
+    plugins::{RunningPlugins, TracerPlugins},
+    returns::PendingReturns,
+    revert_site::{RevertSite, RevertSiteSlot},
+    script_wallets::ScriptWallets,
+    snapshot_diff::{known_accounts, SnapshotDiff},
+    support::is_dispatched,
+    tracers::{
+        merge_modified_storage, BroadcastOpts, BroadcastTracer, EmitChecks, EmitOrdering,
+        EnabledTracers, EraEnv, ExpectationTracer, ExpectedCallType, ForkAction, ForkContext,
+        ForkTracer, MockTracer,
+    },
     utils::{ToH160, ToH256, ToU256},
 };
+use alloy_dyn_abi::DynSolType;
 use alloy_primitives::{Address, Bytes, FixedBytes, I256 as rI256};
-use alloy_sol_types::{SolInterface, SolValue};
+use alloy_sol_types::{SolCall, SolInterface, SolValue};
 use era_test_node::utils::bytecode_to_factory_dep;
-use ethers::{signers::Signer, types::TransactionRequest};
+use ethers::signers::Signer;
 use eyre::Context;
-use foundry_cheatcodes::{BroadcastableTransaction, BroadcastableTransactions, CheatsConfig};
+use foundry_cheatcodes::{BroadcastableTransactions, CheatsConfig};
 use foundry_cheatcodes_spec::Vm;
 use foundry_common::{
     conversion_utils::{h160_to_address, revm_u256_to_u256},
-    StorageModifications,
+    get_artifact_path,
+    zk_utils::factory_deps::PackedEraBytecode,
+    StorageModifications, ZkStateOverrides,
 };
+use foundry_config::fs_permissions::FsAccessKind;
 use foundry_evm_core::{
     backend::DatabaseExt,
-    constants::MAGIC_ASSUME,
-    era_revm::{db::RevmDatabaseForEra, storage_view::StorageView, transactions::storage_to_state},
-    fork::CreateFork,
-    opts::EvmOpts,
+    constants::{MAGIC_ASSUME, MAGIC_SKIP},
+    decode::decode_revert,
+    era_revm::{
+        db::RevmDatabaseForEra, env::protocol_version, storage_view::StorageView,
+        transactions::run_era_call,
+    },
+    fork::zksync_provider::finalize_withdrawal_call,
 };
 use itertools::Itertools;
 use multivm::{
@@ -27,26 +49,21 @@ use multivm::{
     vm_latest::{
         BootloaderState, HistoryMode, L1BatchEnv, SimpleMemory, SystemEnv, VmTracer, ZkSyncVmState,
     },
-    zk_evm_1_3_3::zkevm_opcode_defs::CALL_SYSTEM_ABI_REGISTERS,
     zk_evm_1_4_0::{
         tracing::{AfterExecutionData, VmLocalStateData},
         vm_state::{PrimitiveValue, VmLocalState},
         zkevm_opcode_defs::{
             self,
             decoding::{EncodingModeProduction, VmEncodingMode},
-            FatPointer, Opcode, RetOpcode, CALL_IMPLICIT_CALLDATA_FAT_PTR_REGISTER,
+            FatPointer, LogOpcode, Opcode, RetOpcode, CALL_IMPLICIT_CALLDATA_FAT_PTR_REGISTER,
             RET_IMPLICIT_RETURNDATA_PARAMS_REGISTER,
         },
     },
 };
-use revm::{
-    primitives::{ruint::Uint, BlockEnv, CfgEnv, Env, SpecId, U256 as rU256},
-    JournaledState,
-};
-use serde::Serialize;
+use revm::primitives::{TransactTo, TxEnv, U256 as rU256};
 use std::{
     cell::{OnceCell, RefMut},
-    collections::{hash_map::Entry, HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap},
     fmt::Debug,
     fs,
     ops::BitAnd,
@@ -60,24 +77,29 @@ use zksync_types::{
     block::{pack_block_info, unpack_block_info},
     get_code_key, get_nonce_key,
     utils::{decompose_full_nonce, nonces_to_full_nonce, storage_key_for_eth_balance},
-    LogQuery, StorageKey, Timestamp, ACCOUNT_CODE_STORAGE_ADDRESS,
+    vm_version::VmVersion,
+    LogQuery, StorageKey, Timestamp, ACCOUNT_CODE_STORAGE_ADDRESS, KNOWN_CODES_STORAGE_ADDRESS,
 };
-use zksync_utils::{bytecode::CompressedBytecodeInfo, h256_to_u256, u256_to_h256};
+use zksync_utils::{bytecode::hash_bytecode, h256_to_u256, u256_to_h256};
 
 type EraDb<DB> = StorageView<RevmDatabaseForEra<DB>>;
-type PcOrImm = <EncodingModeProduction as VmEncodingMode<8>>::PcOrImm;
+
+/// The data a cheatcode returns, as the words of its ABI encoding.
+pub type ReturnData = Vec<U256>;
+
+pub(crate) type PcOrImm = <EncodingModeProduction as VmEncodingMode<8>>::PcOrImm;
 
 // address(uint160(uint256(keccak256('hevm cheat code'))))
 // 0x7109709ecfa91a80626ff3989d68f67f5b1dd12d
-const CHEATCODE_ADDRESS: H160 = H160([
+pub(crate) const CHEATCODE_ADDRESS: H160 = H160([
     113, 9, 112, 158, 207, 169, 26, 128, 98, 111, 243, 152, 157, 104, 246, 127, 91, 29, 209, 45,
 ]);
 
 // 0x2e1908b13b8b625ed13ecf03c87d45c499d1f325
-const TEST_ADDRESS: H160 =
+pub(crate) const TEST_ADDRESS: H160 =
     H160([46, 25, 8, 177, 59, 139, 98, 94, 209, 62, 207, 3, 200, 125, 69, 196, 153, 209, 243, 37]);
 
-const INTERNAL_CONTRACT_ADDRESSES: [H160; 20] = [
+pub(crate) const INTERNAL_CONTRACT_ADDRESSES: [H160; 20] = [
     zksync_types::BOOTLOADER_ADDRESS,
     zksync_types::ACCOUNT_CODE_STORAGE_ADDRESS,
     zksync_types::NONCE_HOLDER_ADDRESS,
@@ -100,37 +122,13 @@ const INTERNAL_CONTRACT_ADDRESSES: [H160; 20] = [
     H160::zero(),
 ];
 
-//same as above, except without
-// CONTRACT_DEPLOYER_ADDRESS
-// MSG_VALUE_SIMULATOR_ADDRESS
-// and with
-// CHEATCODE_ADDRESS
-const BROADCAST_IGNORED_CONTRACTS: [H160; 19] = [
-    zksync_types::BOOTLOADER_ADDRESS,
-    zksync_types::ACCOUNT_CODE_STORAGE_ADDRESS,
-    zksync_types::NONCE_HOLDER_ADDRESS,
-    zksync_types::KNOWN_CODES_STORAGE_ADDRESS,
-    zksync_types::IMMUTABLE_SIMULATOR_STORAGE_ADDRESS,
-    zksync_types::CONTRACT_FORCE_DEPLOYER_ADDRESS,
-    zksync_types::L1_MESSENGER_ADDRESS,
-    zksync_types::KECCAK256_PRECOMPILE_ADDRESS,
-    zksync_types::L2_ETH_TOKEN_ADDRESS,
-    zksync_types::SYSTEM_CONTEXT_ADDRESS,
-    zksync_types::BOOTLOADER_UTILITIES_ADDRESS,
-    zksync_types::EVENT_WRITER_ADDRESS,
-    zksync_types::COMPRESSOR_ADDRESS,
-    zksync_types::COMPLEX_UPGRADER_ADDRESS,
-    zksync_types::ECRECOVER_PRECOMPILE_ADDRESS,
-    zksync_types::SHA256_PRECOMPILE_ADDRESS,
-    zksync_types::MINT_AND_BURN_ADDRESS,
-    CHEATCODE_ADDRESS,
-    H160::zero(),
-];
-
-#[derive(Debug, Clone)]
-struct EraEnv {
-    l1_batch_env: L1BatchEnv,
-    system_env: SystemEnv,
+// The ERC-20 and ERC-721 getters queried by the `vm.erc20*` and `vm.erc721*` cheatcodes.
+alloy_sol_types::sol! {
+    function balanceOf(address account) external view returns (uint256);
+    function allowance(address owner, address spender) external view returns (uint256);
+    function ownerOf(uint256 tokenId) external view returns (address);
+    function getApproved(uint256 tokenId) external view returns (address);
+    function isApprovedForAll(address owner, address operator) external view returns (bool);
 }
 
 /// Represents the state of a foundry test function, i.e. functions
@@ -149,79 +147,82 @@ enum FoundryTestState {
 #[derive(Debug, Default, Clone)]
 pub struct CheatcodeTracer {
     storage_modifications: StorageModifications,
-    one_time_actions: Vec<FinishCycleOneTimeActions>,
+    one_time_actions: ActionQueue,
     next_return_action: Option<NextReturnAction>,
+    /// `expectRevert` set aside while a cheatcode called under it reverts, restored once the
+    /// cheatcode revert is handled.
+    suspended_return_action: Option<NextReturnAction>,
     permanent_actions: FinishCyclePermanentActions,
-    return_data: Option<Vec<U256>>,
-    return_ptr: Option<FatPointer>,
-    near_calls: usize,
-    serialized_objects: HashMap<String, String>,
+    /// One-shot prank of the next far call, set with `vm.prank`.
+    prank: Option<PrankOpts>,
+    /// Scope of the state overrides set with `vm.withStateOverrides`, which are applied by the
+    /// storage view until the next far call returns.
+    state_overrides: Option<StateOverridesOpts>,
+    returns: PendingReturns,
+    /// JSON objects built with the `vm.serialize*` cheatcodes, by object key.
+    serialized_objects: HashMap<String, serde_json::Map<String, serde_json::Value>>,
     env: OnceCell<EraEnv>,
     config: Arc<CheatsConfig>,
-    recorded_logs: HashSet<LogEntry>,
+    /// Logs recorded after `vm.recordLogs`, by transaction and index, in emission order.
+    recorded_logs: BTreeMap<(u16, usize), LogEntry>,
     recording_logs: bool,
     recording_timestamp: u32,
-    expected_calls: ExpectedCallsTracker,
+    /// Storage accesses recorded after `vm.record`.
+    accesses: Option<RecordedAccesses>,
     test_status: FoundryTestState,
-    emit_config: EmitConfig,
-    saved_snapshots: HashMap<U256, SavedSnapshot>,
-    broadcastable_transactions: Arc<RwLock<BroadcastableTransactions>>,
-    transact_logs: Vec<LogEntry>,
-    mocked_calls: MockedCalls,
     farcall_handler: FarCallHandler,
+    tracing_level: ZkTracingLevel,
+    plugins: RunningPlugins,
+    tracers: EnabledTracers,
+    mocks: MockTracer,
+    expectations: ExpectationTracer,
+    broadcast: BroadcastTracer,
+    forks: ForkTracer,
+    /// Ergs cap of the next far call, set with `vm.zkSetNextCallGas`.
+    next_call_ergs: Option<u32>,
+    /// Storage slot whose next write fails, set with `vm.zkFailNextStorageWrite`.
+    fail_next_storage_write: Option<StorageKey>,
+    revert_site: RevertSiteSlot,
+    /// Labels assigned with `vm.label`, including the ones assigned before the transaction.
+    labels: Labels,
+    /// Wallets remembered with `vm.rememberKey`, to broadcast with.
+    script_wallets: ScriptWallets,
+    /// Breakpoints written with `vm.breakpoint`, for the debugger.
+    breakpoints: ZkBreakpoints,
+    /// Whether the test reverting after running out of gas rejects the fuzz run, set with
+    /// `vm.assumeNoZkOutOfGas`.
+    assume_no_out_of_gas: bool,
+    /// Depth of the frame an out of gas panic has propagated to since `vm.assumeNoZkOutOfGas`,
+    /// unless a frame caught it.
+    out_of_gas_depth: Option<usize>,
+    /// The gas the test had left when calling `vm.assumeZkGasBelow`, and the gas it may use from
+    /// there before the fuzz run is rejected.
+    gas_bound: Option<(u64, u64)>,
+    /// Accounts whose balance was set with `vm.deal`, which `vm.diffSnapshots` can't otherwise
+    /// attribute balance slots to if nothing else reveals them.
+    dealt_accounts: BTreeSet<H160>,
 }
 
-#[derive(Debug, Clone)]
-pub struct SavedSnapshot {
-    modified_storage: HashMap<StorageKey, H256>,
-}
-
-#[derive(Debug, Clone, Default)]
-struct EmitConfig {
-    expected_emit_state: ExpectedEmitState,
-    expect_emits_since: u32,
-    expect_emits_until: u32,
-    call_emits_since: u32,
-    call_emits_until: u32,
-    call_depth: usize,
-    checks: EmitChecks,
-}
-
-#[derive(Debug, Clone, Default)]
-struct EmitChecks {
-    address: Option<H160>,
-    topics: [bool; 3],
-    data: bool,
-}
-
-#[derive(Debug, Clone, Serialize, Eq, Hash, PartialEq, Default)]
-enum ExpectedEmitState {
+/// Verbosity of the zk-internal diagnostics emitted by the tracer, as set by `vm.zkSetTracing`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+enum ZkTracingLevel {
+    /// No additional diagnostics are emitted.
     #[default]
-    NotStarted,
-    ExpectedEmitTriggered,
-    CallTriggered,
-    Assert,
-    Finished,
+    Off,
+    /// Far calls and returns are logged.
+    Calls,
+    /// Every executed opcode is logged, in addition to far calls and returns.
+    Opcodes,
 }
 
-#[derive(Debug, Clone)]
-enum FinishCycleOneTimeActions {
-    StorageWrite { key: StorageKey, read_value: H256, write_value: H256 },
-    StoreFactoryDep { hash: U256, bytecode: Vec<U256> },
-    ForceRevert { error: Vec<u8>, exception_handler: PcOrImm },
-    ForceReturn { data: Vec<u8>, continue_pc: PcOrImm },
-    CreateSelectFork { url_or_alias: String, block_number: Option<u64> },
-    CreateFork { url_or_alias: String, block_number: Option<u64> },
-    RollFork { block_number: Uint<256, 4>, fork_id: Option<Uint<256, 4>> },
-    SelectFork { fork_id: U256 },
-    RevertToSnapshot { snapshot_id: U256 },
-    Snapshot,
-    SetOrigin { origin: H160 },
-    Transact { fork_id: Option<U256>, tx_hash: H256 },
-    MakePersistentAccount { account: H160 },
-    MakePersistentAccounts { accounts: Vec<H160> },
-    RevokePersistentAccount { account: H160 },
-    RevokePersistentAccounts { accounts: Vec<H160> },
+impl From<u8> for ZkTracingLevel {
+    fn from(level: u8) -> Self {
+        match level {
+            0 => Self::Off,
+            1 => Self::Calls,
+            _ => Self::Opcodes,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -240,6 +241,14 @@ enum ActionOnReturn {
         depth: usize,
         prev_continue_pc: Option<PcOrImm>,
         prev_exception_handler_pc: Option<PcOrImm>,
+        /// The address the revert is expected to originate from, if any.
+        reverter: Option<H160>,
+        /// The innermost address that reverted during the current call.
+        reverted_by: Option<H160>,
+        /// The number of consecutive calls expected to revert.
+        count: u64,
+        /// The number of calls that reverted as expected so far.
+        actual_count: u64,
     },
     Revert {
         depth: usize,
@@ -251,7 +260,6 @@ enum ActionOnReturn {
 #[derive(Debug, Default, Clone)]
 struct FinishCyclePermanentActions {
     start_prank: Option<StartPrankOpts>,
-    broadcast: Option<BroadcastOpts>,
 }
 
 #[derive(Debug, Clone)]
@@ -260,45 +268,30 @@ struct StartPrankOpts {
     origin: Option<H160>,
 }
 
-/// Tracks the expected calls per address.
-///
-/// For each address, we track the expected calls per call data. We track it in such manner
-/// so that we don't mix together calldatas that only contain selectors and calldatas that contain
-/// selector and arguments (partial and full matches).
-///
-/// This then allows us to customize the matching behavior for each call data on the
-/// `ExpectedCallData` struct and track how many times we've actually seen the call on the second
-/// element of the tuple.
-type ExpectedCallsTracker = HashMap<H160, HashMap<Vec<u8>, (ExpectedCallData, u64)>>;
-
-#[derive(Debug, Clone)]
-struct ExpectedCallData {
-    /// The expected value sent in the call
-    value: Option<U256>,
-    /// The number of times the call is expected to be made.
-    /// If the type of call is `NonCount`, this is the lower bound for the number of calls
-    /// that must be seen.
-    /// If the type of call is `Count`, this is the exact number of calls that must be seen.
-    count: u64,
-    /// The type of expected call.
-    call_type: ExpectedCallType,
+/// Storage slots read and written by each account, in order. Writes are recorded as reads too.
+#[derive(Debug, Default, Clone)]
+struct RecordedAccesses {
+    reads: HashMap<H160, Vec<H256>>,
+    writes: HashMap<H160, Vec<H256>>,
 }
 
-/// The type of expected call.
-#[derive(Clone, Debug, PartialEq, Eq)]
-enum ExpectedCallType {
-    /// The call is expected to be made at least once.
-    NonCount,
-    /// The exact number of calls expected.
-    Count,
+#[derive(Debug, Clone)]
+struct PrankOpts {
+    sender: H160,
+    /// The `tx.origin` to restore once the pranked call returns, if it's pranked as well.
+    original_origin: Option<H160>,
+    /// Depth of the pranked far call frame.
+    depth: usize,
+    /// Whether the pranked call is in progress.
+    applied: bool,
 }
 
 #[derive(Debug, Clone)]
-struct BroadcastOpts {
-    original_origin: H160,
-    original_caller: H160,
-    new_origin: H160,
+struct StateOverridesOpts {
+    /// Depth of the far call frame the overrides are scoped to.
     depth: usize,
+    /// Whether the call is in progress.
+    applied: bool,
 }
 
 impl<S: DatabaseExt + Send, H: HistoryMode> DynTracer<EraDb<S>, SimpleMemory<H>>
@@ -311,16 +304,92 @@ impl<S: DatabaseExt + Send, H: HistoryMode> DynTracer<EraDb<S>, SimpleMemory<H>>
         memory: &SimpleMemory<H>,
         storage: StoragePtr<EraDb<S>>,
     ) {
-        self.farcall_handler.track_active_far_calls(state, data, memory, storage);
+        if !self.plugins.is_empty() {
+            let storage = &mut *storage.borrow_mut();
+            self.plugins.for_each(|plugin| plugin.before_execution(&state, &data, storage));
+        }
+
+        self.farcall_handler.track_active_far_calls(state, data, memory, storage.clone());
+
+        match data.opcode.variant.opcode {
+            Opcode::Ret(RetOpcode::Revert | RetOpcode::Panic) => {
+                let current = &state.vm_local_state.callstack.current;
+                let bytecode_hash =
+                    executed_bytecode_hash(&mut *storage.borrow_mut(), &current.code_address);
+                self.revert_site.record_revert(
+                    state.vm_local_state.callstack.depth(),
+                    RevertSite { address: current.code_address, bytecode_hash, pc: current.pc },
+                );
+            }
+            Opcode::Ret(RetOpcode::Ok) => {
+                self.revert_site.record_return(state.vm_local_state.callstack.depth())
+            }
+            _ => (),
+        }
+
+        self.reject_missing_reverts(&state, &data);
+        if self.assume_no_out_of_gas {
+            self.reject_out_of_gas(&state, &data);
+        }
+        if self.gas_bound.is_some() {
+            self.reject_gas_above_bound(&state, &data);
+        }
+
+        if let Some(key) = &self.fail_next_storage_write {
+            if matches!(data.opcode.variant.opcode, Opcode::Log(LogOpcode::StorageWrite)) &&
+                *key.address() == state.vm_local_state.callstack.current.this_address &&
+                *key.key() == u256_to_h256(data.src0_value.value)
+            {
+                tracing::info!(?key, "failing storage write");
+                self.fail_next_storage_write = None;
+                self.one_time_actions.push(FinishCycleOneTimeActions::FailCurrentFrame);
+            }
+        }
+
+        if let Some(accesses) = &mut self.accesses {
+            if let Opcode::Log(opcode @ (LogOpcode::StorageRead | LogOpcode::StorageWrite)) =
+                data.opcode.variant.opcode
+            {
+                let address = state.vm_local_state.callstack.current.this_address;
+                let slot = u256_to_h256(data.src0_value.value);
+                accesses.reads.entry(address).or_default().push(slot);
+                if matches!(opcode, LogOpcode::StorageWrite) {
+                    accesses.writes.entry(address).or_default().push(slot);
+                }
+            }
+        }
+
+        if self.tracers.mocks && matches!(data.opcode.variant.opcode, Opcode::FarCall(_)) {
+            self.mocks.before_far_call(&state, &data, memory, &storage);
+        }
+
+        if self.tracing_level >= ZkTracingLevel::Opcodes {
+            tracing::info!(
+                opcode = ?data.opcode.variant.opcode,
+                pc = state.vm_local_state.callstack.current.pc,
+                depth = state.vm_local_state.callstack.depth(),
+                ergs_remaining = state.vm_local_state.callstack.current.ergs_remaining,
+                "zk trace: executing opcode"
+            );
+        }
 
         //store the current exception handler in expect revert
         // to be used to force a revert
         if let Some(ActionOnReturn::ExpectRevert {
             prev_exception_handler_pc,
             prev_continue_pc,
+            reverted_by,
             ..
         }) = self.current_return_action()
         {
+            // the innermost frame reverting is the one the revert originated from
+            let current = &state.vm_local_state.callstack.current;
+            if matches!(data.opcode.variant.opcode, Opcode::Ret(RetOpcode::Revert)) &&
+                !current.is_local_frame
+            {
+                reverted_by.get_or_insert(current.this_address);
+            }
+
             if matches!(data.opcode.variant.opcode, Opcode::Ret(_)) {
                 // Callstack on the desired depth, it has the correct pc for continue
                 let last = state.vm_local_state.callstack.inner.last().unwrap();
@@ -360,57 +429,50 @@ impl<S: DatabaseExt + Send, H: HistoryMode> DynTracer<EraDb<S>, SimpleMemory<H>>
         memory: &SimpleMemory<H>,
         storage: StoragePtr<EraDb<S>>,
     ) {
+        if self.tracing_level >= ZkTracingLevel::Calls {
+            self.trace_call(&state, &data, memory);
+        }
+
+        if !self.plugins.is_empty() {
+            let storage = &mut *storage.borrow_mut();
+            self.plugins.for_each(|plugin| plugin.after_execution(&state, &data, storage));
+        }
+
+        // the code of a mocked function's callee is loaded by now
+        self.mocks.restore_swapped_code(&storage);
+
         let current = state.vm_local_state.callstack.get_current_stack();
-        let is_reserved_addr = current
-            .code_address
-            .bitand(H160::from_str("ffffffffffffffffffffffffffffffffffff0000").unwrap())
-            .is_zero();
+        let is_reserved_addr = self.is_reserved_address(current.code_address);
 
-        if current.code_address != CHEATCODE_ADDRESS &&
+        if self.tracers.expectations &&
+            current.code_address != CHEATCODE_ADDRESS &&
             !INTERNAL_CONTRACT_ADDRESSES.contains(&current.code_address) &&
             !is_reserved_addr
         {
-            if self.emit_config.expected_emit_state == ExpectedEmitState::ExpectedEmitTriggered {
-                //cheatcode triggered, waiting for far call
-                if let Opcode::FarCall(_call) = data.opcode.variant.opcode {
-                    self.emit_config.call_emits_since = state.vm_local_state.timestamp;
-                    self.emit_config.expect_emits_until = state.vm_local_state.timestamp;
-                    self.emit_config.expected_emit_state = ExpectedEmitState::CallTriggered;
-                    self.emit_config.call_depth = state.vm_local_state.callstack.depth();
+            self.expectations.track_emits(&state, &data);
+        }
+
+        if self.update_test_status(&state, &data) == &FoundryTestState::Finished {
+            match self.next_return_action.take() {
+                // fewer calls than expected reverting already reverted the test
+                Some(NextReturnAction { action: ActionOnReturn::ExpectRevert { .. }, .. }) => (),
+                // the test reverting after running out of gas is rejected right after
+                action @ Some(NextReturnAction {
+                    action: ActionOnReturn::Revert { .. }, ..
+                }) => {
+                    self.next_return_action = action;
                 }
+                None => (),
             }
 
-            if self.emit_config.expected_emit_state == ExpectedEmitState::CallTriggered &&
-                state.vm_local_state.callstack.depth() < self.emit_config.call_depth
-            {
-                self.emit_config.call_emits_until = state.vm_local_state.timestamp;
+            // Trigger assert for emit_logs, and check the expected calls
+            if self.tracers.expectations {
+                self.expectations.on_test_finish(&self.labels);
             }
-        }
 
-        if self.update_test_status(&state, &data) == &FoundryTestState::Finished {
-            // Trigger assert for emit_logs
-            self.emit_config.expected_emit_state = ExpectedEmitState::Assert;
-
-            for (address, expected_calls_for_target) in &self.expected_calls {
-                for (expected_calldata, (expected, actual_count)) in expected_calls_for_target {
-                    let failed = match expected.call_type {
-                        // If the cheatcode was called with a `count` argument,
-                        // we must check that the EVM performed a CALL with this calldata exactly
-                        // `count` times.
-                        ExpectedCallType::Count => expected.count != *actual_count,
-                        // If the cheatcode was called without a `count` argument,
-                        // we must check that the EVM performed a CALL with this calldata at least
-                        // `count` times. The amount of times to check was
-                        // the amount of time the cheatcode was called.
-                        ExpectedCallType::NonCount => expected.count > *actual_count,
-                    };
-                    // TODO: change to proper revert
-                    assert!(
-                        !failed,
-                        "Expected call to {:?} with data {:?} was found {} times, expected {}",
-                        address, expected_calldata, actual_count, expected.count
-                    );
-                }
+            if !self.plugins.is_empty() {
+                let storage_modifications = self.get_storage_modifications(&storage);
+                self.plugins.for_each(|plugin| plugin.on_test_finish(&storage_modifications));
             }
 
             // reset the test state to avoid checking again
@@ -420,80 +482,121 @@ impl<S: DatabaseExt + Send, H: HistoryMode> DynTracer<EraDb<S>, SimpleMemory<H>>
         // Checks returns from contracts for expectRevert cheatcode
         self.handle_return(&state, &data, memory);
 
-        // Checks contract calls for expectCall cheatcode
+        // Records contract calls for the expectCall and getCalls cheatcodes
         if let Opcode::FarCall(_call) = data.opcode.variant.opcode {
-            let current = state.vm_local_state.callstack.current;
-            if let Some(expected_calls_for_target) =
-                self.expected_calls.get_mut(&current.code_address)
-            {
-                let calldata = get_calldata(&state, memory);
-                // Match every partial/full calldata
-                for (expected_calldata, (expected, actual_count)) in expected_calls_for_target {
-                    // Increment actual times seen if...
-                    // The calldata is at most, as big as this call's input, and
-                    if expected_calldata.len() <= calldata.len() &&
-                        // Both calldata match, taking the length of the assumed smaller one (which will have at least the selector), and
-                        *expected_calldata == calldata[..expected_calldata.len()] &&
-                        // The value matches, if provided
-                        expected
-                            .value
-                            .map_or(true, |value|{
-                                 value == current.context_u128_value.into()})
-                    {
-                        *actual_count += 1;
-                    }
-                }
+            if self.tracers.expectations {
+                self.expectations.on_far_call(&state, memory);
             }
         }
 
-        if let Opcode::FarCall(_call) = data.opcode.variant.opcode {
-            let current = state.vm_local_state.callstack.current;
-            let calldata = get_calldata(&state, memory);
-            if let Some(return_data) = self.mocked_calls.get_matching_return_data(
-                current.code_address,
-                &calldata,
-                U256::from(current.context_u128_value),
-            ) {
-                tracing::info!(
-                    calldata = hex::encode(&calldata),
-                    return_data = hex::encode(&return_data),
-                    "mock call matched"
-                );
-                self.farcall_handler.set_immediate_return(return_data);
+        if let Some(prank) = &mut self.prank {
+            match data.opcode.variant.opcode {
+                // calls with value go through the simulator and deployments through the deployer,
+                // which call the target or the constructor a frame deeper
+                Opcode::FarCall(_)
+                    if !prank.applied &&
+                        (current.code_address == zksync_types::MSG_VALUE_SIMULATOR_ADDRESS ||
+                            current.code_address ==
+                                zksync_types::CONTRACT_DEPLOYER_ADDRESS) &&
+                        state.vm_local_state.callstack.depth() == prank.depth =>
+                {
+                    prank.depth += 1;
+                }
+                Opcode::FarCall(_)
+                    if !prank.applied &&
+                        current.code_address != CHEATCODE_ADDRESS &&
+                        !INTERNAL_CONTRACT_ADDRESSES.contains(&current.code_address) &&
+                        state.vm_local_state.callstack.depth() == prank.depth =>
+                {
+                    tracing::debug!(sender = ?prank.sender, "pranking far call");
+                    prank.applied = true;
+                    self.one_time_actions
+                        .push(FinishCycleOneTimeActions::SetMsgSender { sender: prank.sender });
+                }
+                Opcode::Ret(_)
+                    if prank.applied && state.vm_local_state.callstack.depth() < prank.depth =>
+                {
+                    let original_origin = prank.original_origin;
+                    self.prank = None;
+                    if let Some(origin) = original_origin {
+                        let key = StorageKey::new(
+                            AccountTreeId::new(zksync_types::SYSTEM_CONTEXT_ADDRESS),
+                            zksync_types::SYSTEM_CONTEXT_TX_ORIGIN_POSITION,
+                        );
+                        self.write_storage(key, origin.into(), &mut storage.borrow_mut());
+                    }
+                }
+                _ => (),
             }
         }
 
-        if self.return_data.is_some() {
-            if let Opcode::Ret(_call) = data.opcode.variant.opcode {
-                if self.near_calls == 0 {
-                    let ptr = state.vm_local_state.registers
-                        [RET_IMPLICIT_RETURNDATA_PARAMS_REGISTER as usize];
-                    let fat_data_pointer = FatPointer::from_u256(ptr.value);
-                    self.return_ptr = Some(fat_data_pointer);
-                } else {
-                    self.near_calls = self.near_calls.saturating_sub(1);
+        if let Some(overrides) = &mut self.state_overrides {
+            match data.opcode.variant.opcode {
+                // calls with value and deployments are made a frame deeper, the overrides stay
+                // applied in the simulator and deployer frames
+                Opcode::FarCall(_)
+                    if !overrides.applied &&
+                        (current.code_address == zksync_types::MSG_VALUE_SIMULATOR_ADDRESS ||
+                            current.code_address ==
+                                zksync_types::CONTRACT_DEPLOYER_ADDRESS) &&
+                        state.vm_local_state.callstack.depth() == overrides.depth =>
+                {
+                    overrides.depth += 1;
+                }
+                Opcode::FarCall(_)
+                    if !overrides.applied &&
+                        current.code_address != CHEATCODE_ADDRESS &&
+                        !INTERNAL_CONTRACT_ADDRESSES.contains(&current.code_address) &&
+                        state.vm_local_state.callstack.depth() == overrides.depth =>
+                {
+                    overrides.applied = true;
+                }
+                Opcode::Ret(op)
+                    if overrides.applied &&
+                        state.vm_local_state.callstack.depth() < overrides.depth =>
+                {
+                    // the writes the call made are kept, unless it reverted
+                    self.state_overrides = None;
+                    storage.borrow_mut().clear_scoped_overrides(!matches!(op, RetOpcode::Ok));
                 }
+                _ => (),
             }
         }
 
-        if let Opcode::Ret(_) = data.opcode.variant.opcode {
-            let current = state.vm_local_state.callstack.current;
-            if current.code_address != CHEATCODE_ADDRESS {
-                if let Some(broadcast) = &self.permanent_actions.broadcast {
-                    if state.vm_local_state.callstack.depth() == broadcast.depth {
-                        // when the test ends, just make sure the tx origin is set to the
-                        // original one (should never get here unless .stopBroadcast wasn't called)
-                        self.one_time_actions.push(FinishCycleOneTimeActions::SetOrigin {
-                            origin: broadcast.original_origin,
-                        });
+        if let Opcode::FarCall(_call) = data.opcode.variant.opcode {
+            if self.tracers.mocks {
+                match self.mocks.on_far_call(&state, memory) {
+                    Some(MockCallReturn::Return(return_data)) => {
+                        self.farcall_handler.set_immediate_return(return_data);
+                    }
+                    // the mocked callee reverts to the exception handler set by the far call
+                    Some(MockCallReturn::Revert(revert_data)) => {
+                        self.farcall_handler.set_immediate_revert(
+                            revert_data,
+                            state.vm_local_state.callstack.current.exception_handler_location,
+                        );
                     }
+                    None => (),
                 }
             }
         }
 
-        if let Opcode::NearCall(_call) = data.opcode.variant.opcode {
-            if self.return_data.is_some() {
-                self.near_calls += 1;
+        if !self.returns.is_empty() {
+            if let Opcode::Ret(_call) = data.opcode.variant.opcode {
+                let ptr = state.vm_local_state.registers
+                    [RET_IMPLICIT_RETURNDATA_PARAMS_REGISTER as usize];
+                self.returns.on_return(
+                    state.vm_local_state.callstack.depth(),
+                    FatPointer::from_u256(ptr.value),
+                );
+            }
+        }
+
+        if let Opcode::Ret(_) = data.opcode.variant.opcode {
+            // when the test ends, just make sure the tx origin is set to the
+            // original one (should never get here unless .stopBroadcast wasn't called)
+            if let Some(origin) = self.broadcast.on_return(&state) {
+                self.one_time_actions.push(FinishCycleOneTimeActions::SetOrigin { origin });
             }
         }
 
@@ -510,119 +613,33 @@ impl<S: DatabaseExt + Send, H: HistoryMode> DynTracer<EraDb<S>, SimpleMemory<H>>
             }
 
             if current.code_address != CHEATCODE_ADDRESS {
-                if let Some(broadcast) = self.permanent_actions.broadcast.as_ref() {
-                    let prev_cs = state
-                        .vm_local_state
-                        .callstack
-                        .inner
-                        .last()
-                        .expect("callstack before the current");
-
-                    if state.vm_local_state.callstack.depth() == broadcast.depth &&
-                        prev_cs.this_address == broadcast.original_caller &&
-                        !BROADCAST_IGNORED_CONTRACTS.contains(&current.code_address)
+                // calls with value are capped at the simulator, which forwards all of its ergs
+                if let Some(ergs) = self.next_call_ergs {
+                    if !is_reserved_addr ||
+                        current.code_address == zksync_types::MSG_VALUE_SIMULATOR_ADDRESS
                     {
-                        self.one_time_actions.push(FinishCycleOneTimeActions::SetOrigin {
-                            origin: broadcast.new_origin,
-                        });
-
-                        let new_origin = broadcast.new_origin;
-                        let handle = &mut storage.borrow_mut();
-                        let revm_db_for_era = &handle.storage_handle;
-                        let rpc = revm_db_for_era.db.lock().unwrap().active_fork_url();
-
-                        let calldata = get_calldata(&state, memory);
-
-                        let is_deployment =
-                            current.code_address == zksync_types::CONTRACT_DEPLOYER_ADDRESS;
-                        let factory_deps =
-                            if is_deployment {
-                                let test_contract_hash = handle.read_value(&StorageKey::new(
-                                    AccountTreeId::new(zksync_types::ACCOUNT_CODE_STORAGE_ADDRESS),
-                                    TEST_ADDRESS.into(),
-                                ));
-
-                                self.get_modified_bytecodes(vec![])
-                                    .iter()
-                                    .chain(self.storage_modifications.known_codes.iter())
-                                    .filter_map(|(k, v)| {
-                                        if k != &test_contract_hash {
-                                            Some(v)
-                                        } else {
-                                            None
-                                        }
-                                    })
-                                    .cloned()
-                                    .collect_vec()
-                            } else {
-                                vec![]
-                            };
-
-                        // used to determine whether the nonce should be decreased, since
-                        // zkevm updates the nonce for the _sender_ already when we run the
-                        // script/test function therefore, we should fix it and obtain the nonce
-                        // that resembles the one to be used on-chain
-                        let is_sender_also_caller =
-                            new_origin == self.config.evm_opts.sender.to_h160();
-                        let nonce_offset = if is_sender_also_caller { 1 } else { 0 };
-
-                        let nonce = Self::get_nonce(new_origin, handle)
-                            .0
-                            .saturating_sub(nonce_offset.into());
-
-                        let _gas_limit = current.ergs_remaining;
-
-                        let (value, to) = if current.code_address ==
-                            zksync_types::MSG_VALUE_SIMULATOR_ADDRESS
-                        {
-                            //when some eth is sent to an address in zkevm the call is replaced
-                            // with a call to MsgValueSimulator, which does a mimic_call later
-                            // to the original destination
-                            // The value is stored in the 1st system abi register, and the
-                            // original address is stored in the 2nd register
-                            // see: https://github.com/matter-labs/era-test-node/blob/6ee7d29e876b75506f58355218e1ea755a315d17/etc/system-contracts/contracts/MsgValueSimulator.sol#L26-L27
-                            let msg_value_simulator_value_reg_idx = CALL_SYSTEM_ABI_REGISTERS.start;
-                            let msg_value_simulator_address_reg_idx =
-                                msg_value_simulator_value_reg_idx + 1;
-                            let value = state.vm_local_state.registers
-                                [msg_value_simulator_value_reg_idx as usize];
-
-                            let address = state.vm_local_state.registers
-                                [msg_value_simulator_address_reg_idx as usize];
-
-                            let mut bytes = [0u8; 32];
-                            address.value.to_big_endian(&mut bytes);
-                            let address = H256::from(bytes);
-
-                            (Some(value.value), address.into())
-                        } else {
-                            (None, current.code_address)
-                        };
-
-                        let tx = BroadcastableTransaction {
-                            rpc,
-                            factory_deps,
-                            transaction:
-                                ethers::types::transaction::eip2718::TypedTransaction::Legacy(
-                                    TransactionRequest {
-                                        from: Some(new_origin),
-                                        to: Some(ethers::types::NameOrAddress::Address(to)),
-                                        //TODO: set only if set manually by user in script
-                                        gas: None,
-                                        value,
-                                        data: Some(calldata.into()),
-                                        nonce: Some(nonce),
-                                        ..Default::default()
-                                    },
-                                ),
-                        };
-                        tracing::debug!(?tx, "storing for broadcast");
-
-                        self.broadcastable_transactions.write().unwrap().push_back(tx);
+                        self.next_call_ergs = None;
+                        self.one_time_actions.push(FinishCycleOneTimeActions::CapCallErgs { ergs });
+                    }
+                }
 
-                        // we increase the nonce so that future calls will have the nonce
-                        // increased, simulating the previous tx being executed
-                        self.set_nonce(new_origin, (Some(nonce + 1 + nonce_offset), None), handle);
+                if self.tracers.broadcast {
+                    let sender = self.config.evm_opts.sender.to_h160();
+                    if let Some(call) = self.broadcast.on_far_call(
+                        &state,
+                        memory,
+                        &storage,
+                        &self.storage_modifications,
+                        sender,
+                        &self.labels,
+                    ) {
+                        self.one_time_actions
+                            .push(FinishCycleOneTimeActions::SetOrigin { origin: call.origin });
+                        self.set_nonce(
+                            call.origin,
+                            (Some(call.next_nonce), None),
+                            &mut storage.borrow_mut(),
+                        );
                     }
                 }
                 return
@@ -636,13 +653,18 @@ impl<S: DatabaseExt + Send, H: HistoryMode> DynTracer<EraDb<S>, SimpleMemory<H>>
             let calldata = get_calldata(&state, memory);
 
             // try to dispatch the cheatcode
-            if let Ok(call) = Vm::VmCalls::abi_decode(&calldata, true) {
-                self.dispatch_cheatcode(state, data, memory, storage, call);
-            } else {
-                tracing::error!(
-                    "Failed to decode cheatcode calldata (far call): {}",
-                    hex::encode(calldata),
-                );
+            match Vm::VmCalls::abi_decode(&calldata, true) {
+                Ok(call) => match self.dispatch_cheatcode(state, data, memory, storage, call) {
+                    Ok(Some(return_data)) => {
+                        self.returns.set(state.vm_local_state.callstack.depth(), return_data)
+                    }
+                    Ok(None) => (),
+                    Err(error) => self.revert_with_error(&state, error),
+                },
+                Err(err) => {
+                    let error = foundry_cheatcodes::decode_error(&calldata, &err);
+                    self.revert_with_error(&state, error);
+                }
             }
         }
     }
@@ -671,63 +693,33 @@ impl<S: DatabaseExt + Send, H: HistoryMode> VmTracer<EraDb<S>, H> for CheatcodeT
                 zksync_types::Timestamp(self.recording_timestamp),
             );
             let logs = crate::events::parse_events(events);
-            //insert logs in the hashset
+            // a log is complete once its last message is written, which happens while still in
+            // the frame of the event writer called by the emitter
+            let depth = state.local_state.callstack.depth().saturating_sub(1);
+            // logs are parsed again on every cycle from the event sink, which drops the ones of
+            // reverted frames, so the recorded logs are rebuilt keeping the depth they completed at
+            let mut recorded_logs = BTreeMap::new();
             for log in logs {
-                self.recorded_logs.insert(log);
-            }
-            //insert transact logs
-            for log in &self.transact_logs {
-                self.recorded_logs.insert(log.to_owned());
+                let key = (log.tx_number_in_block, log.index);
+                let depth = match self.recorded_logs.remove(&key) {
+                    Some(recorded)
+                        if recorded.address == log.address &&
+                            recorded.topics == log.topics &&
+                            recorded.data == log.data =>
+                    {
+                        recorded.depth
+                    }
+                    _ => Some(depth),
+                };
+                recorded_logs.insert(key, LogEntry { depth, ..log });
             }
+            self.recorded_logs = recorded_logs;
         }
 
         // This assert is triggered only once after the test execution finishes
         // And is used to assert that all logs exist
-        if self.emit_config.expected_emit_state == ExpectedEmitState::Assert {
-            self.emit_config.expected_emit_state = ExpectedEmitState::Finished;
-
-            let (expected_events_initial_dimension, _) =
-                state.event_sink.get_events_and_l2_l1_logs_after_timestamp(
-                    zksync_types::Timestamp(self.emit_config.expect_emits_since),
-                );
-            let expected_events_surplus = state
-                .event_sink
-                .get_events_and_l2_l1_logs_after_timestamp(zksync_types::Timestamp(
-                    self.emit_config.expect_emits_until,
-                ))
-                .0
-                .len();
-
-            //remove n surplus events from the end of expected_events_initial_dimension
-            let expected_events = expected_events_initial_dimension
-                .clone()
-                .into_iter()
-                .take(expected_events_initial_dimension.len() - expected_events_surplus)
-                .collect::<Vec<_>>();
-            let expected_logs = crate::events::parse_events(expected_events);
-
-            let (actual_events_initial_dimension, _) =
-                state.event_sink.get_events_and_l2_l1_logs_after_timestamp(
-                    zksync_types::Timestamp(self.emit_config.call_emits_since),
-                );
-            let actual_events_surplus = state
-                .event_sink
-                .get_events_and_l2_l1_logs_after_timestamp(zksync_types::Timestamp(
-                    self.emit_config.call_emits_until,
-                ))
-                .0
-                .len();
-
-            //remove n surplus events from the end of actual_events_initial_dimension
-            let actual_events = actual_events_initial_dimension
-                .clone()
-                .into_iter()
-                .take(actual_events_initial_dimension.len() - actual_events_surplus)
-                .collect::<Vec<_>>();
-            let mut actual_logs = crate::events::parse_events(actual_events);
-            actual_logs.extend(self.transact_logs.clone());
-
-            assert!(compare_logs(&expected_logs, &actual_logs, self.emit_config.checks.clone()));
+        if self.tracers.expectations {
+            self.expectations.assert_emits(state, &self.forks.transact_logs);
         }
 
         while let Some(action) = self.one_time_actions.pop() {
@@ -747,263 +739,46 @@ impl<S: DatabaseExt + Send, H: HistoryMode> VmTracer<EraDb<S>, H> for CheatcodeT
                         is_service: false,
                     });
                 }
-                FinishCycleOneTimeActions::MakePersistentAccount { account } => {
-                    let era_db: &RevmDatabaseForEra<S> = &storage.borrow_mut().storage_handle;
-
-                    let mut db = era_db.db.lock().unwrap();
-                    db.add_persistent_account(revm::primitives::Address::from(
-                        account.to_fixed_bytes(),
-                    ));
-                }
-                FinishCycleOneTimeActions::MakePersistentAccounts { accounts } => {
-                    let era_db: &RevmDatabaseForEra<S> = &storage.borrow_mut().storage_handle;
-
-                    let mut db = era_db.db.lock().unwrap();
-                    db.extend_persistent_accounts(
-                        accounts
-                            .into_iter()
-                            .map(|a: H160| revm::primitives::Address::from(a.to_fixed_bytes()))
-                            .collect::<Vec<revm::primitives::Address>>(),
-                    );
-                }
-                FinishCycleOneTimeActions::RevokePersistentAccount { account } => {
-                    let era_db: &RevmDatabaseForEra<S> = &storage.borrow_mut().storage_handle;
-                    let mut db = era_db.db.lock().unwrap();
-                    db.remove_persistent_account(&revm::primitives::Address::from(
-                        account.to_fixed_bytes(),
-                    ));
-                }
-                FinishCycleOneTimeActions::RevokePersistentAccounts { accounts } => {
-                    let era_db: &RevmDatabaseForEra<S> = &storage.borrow_mut().storage_handle;
-                    let mut db = era_db.db.lock().unwrap();
-                    db.remove_persistent_accounts(
-                        accounts
-                            .into_iter()
-                            .map(|a: H160| revm::primitives::Address::from(a.to_fixed_bytes()))
-                            .collect::<Vec<revm::primitives::Address>>(),
-                    );
-                }
                 FinishCycleOneTimeActions::StoreFactoryDep { hash, bytecode } => state
                     .decommittment_processor
                     .populate(vec![(hash, bytecode)], Timestamp(state.local_state.timestamp)),
-                FinishCycleOneTimeActions::CreateSelectFork { url_or_alias, block_number } => {
-                    let mut storage = storage.borrow_mut();
-                    let modified_storage =
-                        self.get_modified_storage(storage.modified_storage_keys());
-                    let modified_bytecodes = self.get_modified_bytecodes(
-                        bootloader_state.get_last_tx_compressed_bytecodes(),
-                    );
-
-                    storage.clean_cache();
-                    let fork_id = {
-                        let era_db: &RevmDatabaseForEra<S> = &storage.storage_handle;
-                        let bytecodes = into_revm_bytecodes(modified_bytecodes.clone());
-                        state.decommittment_processor.populate(
-                            bytecodes
-                                .clone()
-                                .into_iter()
-                                .filter(|(key, _)| {
-                                    !state
-                                        .decommittment_processor
-                                        .known_bytecodes
-                                        .inner()
-                                        .contains_key(key)
-                                })
-                                .collect(),
-                            Timestamp(state.local_state.timestamp),
-                        );
-
-                        let mut journaled_state = JournaledState::new(SpecId::LATEST, vec![]);
-                        journaled_state.state =
-                            storage_to_state(era_db, &modified_storage, bytecodes);
-
-                        let mut db = era_db.db.lock().unwrap();
-                        let era_env = self.env.get().unwrap();
-                        let mut env = into_revm_env(era_env);
-                        db.create_select_fork(
-                            create_fork_request(
-                                era_env,
-                                self.config.clone(),
-                                block_number,
-                                &url_or_alias,
-                            ),
-                            &mut env,
-                            &mut journaled_state,
-                        )
+                FinishCycleOneTimeActions::Fork(action) => {
+                    let ctx = ForkContext {
+                        env: self.env.get().unwrap(),
+                        config: &self.config,
+                        storage_modifications: &self.storage_modifications,
                     };
-                    storage.modified_storage_keys = modified_storage;
-
-                    self.return_data = Some(fork_id.unwrap().to_return_data());
-                }
-                FinishCycleOneTimeActions::CreateFork { url_or_alias, block_number } => {
-                    let era_db = &storage.borrow_mut().storage_handle;
-                    let mut db = era_db.db.lock().unwrap();
-                    let era_env = self.env.get().unwrap();
-                    let fork_id = db
-                        .create_fork(create_fork_request(
-                            era_env,
-                            self.config.clone(),
-                            block_number,
-                            &url_or_alias,
-                        ))
-                        .unwrap();
-                    self.return_data = Some(fork_id.to_return_data());
-                }
-                FinishCycleOneTimeActions::RollFork { block_number, fork_id } => {
-                    let modified_storage =
-                        self.get_modified_storage(storage.borrow_mut().modified_storage_keys());
-                    let modified_bytecodes = self.get_modified_bytecodes(
-                        bootloader_state.get_last_tx_compressed_bytecodes(),
-                    );
-
-                    let mut storage = storage.borrow_mut();
-
-                    storage.clean_cache();
+                    let depth = state.local_state.callstack.depth();
+                    if let Some(return_data) =
+                        self.forks.apply(action, state, bootloader_state, &storage, ctx)
                     {
-                        let era_db = &storage.storage_handle;
-                        let bytecodes = into_revm_bytecodes(modified_bytecodes.clone());
-                        state.decommittment_processor.populate(
-                            bytecodes
-                                .clone()
-                                .into_iter()
-                                .filter(|(key, _)| {
-                                    !state
-                                        .decommittment_processor
-                                        .known_bytecodes
-                                        .inner()
-                                        .contains_key(key)
-                                })
-                                .collect(),
-                            Timestamp(state.local_state.timestamp),
-                        );
-
-                        let mut journaled_state = JournaledState::new(SpecId::LATEST, vec![]);
-                        journaled_state.state =
-                            storage_to_state(era_db, &modified_storage, bytecodes);
-
-                        let mut db = era_db.db.lock().unwrap();
-                        let era_env = self.env.get().unwrap();
-                        let mut env = into_revm_env(era_env);
-                        db.roll_fork(fork_id, block_number, &mut env, &mut journaled_state)
-                            .unwrap();
-                    };
-                    storage.modified_storage_keys = modified_storage;
-                }
-                FinishCycleOneTimeActions::SelectFork { fork_id } => {
-                    let mut storage = storage.borrow_mut();
-                    let modified_storage =
-                        self.get_modified_storage(storage.modified_storage_keys());
-                    let modified_bytecodes = self.get_modified_bytecodes(
-                        bootloader_state.get_last_tx_compressed_bytecodes(),
-                    );
-                    {
-                        storage.clean_cache();
-                        let era_db = &storage.storage_handle;
-                        let bytecodes = into_revm_bytecodes(modified_bytecodes.clone());
-                        state.decommittment_processor.populate(
-                            bytecodes
-                                .clone()
-                                .into_iter()
-                                .filter(|(key, _)| {
-                                    !state
-                                        .decommittment_processor
-                                        .known_bytecodes
-                                        .inner()
-                                        .contains_key(key)
-                                })
-                                .collect(),
-                            Timestamp(state.local_state.timestamp),
-                        );
-
-                        let mut journaled_state = JournaledState::new(SpecId::LATEST, vec![]);
-                        journaled_state.state =
-                            storage_to_state(era_db, &modified_storage, bytecodes);
-
-                        let mut db = era_db.db.lock().unwrap();
-                        let era_env = self.env.get().unwrap();
-                        let mut env = into_revm_env(era_env);
-                        db.select_fork(
-                            rU256::from(fork_id.as_u128()),
-                            &mut env,
-                            &mut journaled_state,
-                        )
-                        .unwrap();
+                        self.returns.set(depth, return_data);
                     }
-                    storage.modified_storage_keys = modified_storage;
-
-                    self.return_data = Some(vec![fork_id]);
                 }
-                FinishCycleOneTimeActions::RevertToSnapshot { snapshot_id } => {
-                    let mut storage = storage.borrow_mut();
-                    let modified_storage =
-                        self.get_modified_storage(storage.modified_storage_keys());
-                    storage.clean_cache();
-
-                    {
-                        let era_db = &storage.storage_handle;
-                        let bytecodes = bootloader_state
-                            .get_last_tx_compressed_bytecodes()
-                            .iter()
-                            .map(|b| bytecode_to_factory_dep(b.original.clone()))
-                            .collect();
-
-                        let mut journaled_state = JournaledState::new(SpecId::LATEST, vec![]);
-                        journaled_state.state =
-                            storage_to_state(era_db, &modified_storage, bytecodes);
-
-                        let mut db = era_db.db.lock().unwrap();
-                        let era_env = self.env.get().unwrap();
-                        let mut env = into_revm_env(era_env);
-                        db.revert(Uint::from_limbs(snapshot_id.0), &journaled_state, &mut env);
+                FinishCycleOneTimeActions::CapCallErgs { ergs } => {
+                    let callstack = &mut state.local_state.callstack;
+                    let current = callstack.get_current_stack_mut();
+                    let excess = current.ergs_remaining.saturating_sub(ergs);
+                    tracing::debug!(ergs, excess, "Capping ergs of far call");
+                    current.ergs_remaining -= excess;
+                    // the ergs withheld from the call stay with the caller
+                    if let Some(caller) = callstack.inner.last_mut() {
+                        caller.ergs_remaining += excess;
                     }
-
-                    storage.modified_storage_keys =
-                        self.saved_snapshots.remove(&snapshot_id).unwrap().modified_storage;
                 }
-                FinishCycleOneTimeActions::Snapshot => {
-                    let mut storage = storage.borrow_mut();
-                    let modified_storage =
-                        self.get_modified_storage(storage.modified_storage_keys());
-
-                    storage.clean_cache();
-
-                    let snapshot_id = {
-                        let era_db = &storage.storage_handle;
-                        let bytecodes = bootloader_state
-                            .get_last_tx_compressed_bytecodes()
-                            .iter()
-                            .map(|b| bytecode_to_factory_dep(b.original.clone()))
-                            .collect();
-
-                        let mut journaled_state = JournaledState::new(SpecId::LATEST, vec![]);
-                        journaled_state.state =
-                            storage_to_state(era_db, &modified_storage, bytecodes);
-
-                        let mut db = era_db.db.lock().unwrap();
-                        let era_env = self.env.get().unwrap();
-                        let env = into_revm_env(era_env);
-                        let snapshot_id = db.snapshot(&journaled_state, &env);
-
-                        self.saved_snapshots.insert(
-                            snapshot_id.to_u256(),
-                            SavedSnapshot { modified_storage: modified_storage.clone() },
-                        );
-                        snapshot_id
-                    };
-
-                    storage.modified_storage_keys = modified_storage;
-                    self.return_data = Some(snapshot_id.to_return_data());
+                FinishCycleOneTimeActions::FailCurrentFrame => {
+                    // the frame panics on the next cycle, rolling back its changes
+                    state.local_state.pending_exception = true;
                 }
                 FinishCycleOneTimeActions::ForceReturn { data, continue_pc: pc } => {
                     tracing::debug!(?data, pc, "Forcing return");
-                    self.return_data = Some(data.to_return_data());
                     let ptr = state.local_state.registers
                         [RET_IMPLICIT_RETURNDATA_PARAMS_REGISTER as usize];
                     let fat_data_pointer = FatPointer::from_u256(ptr.value);
 
                     Self::set_return(
                         fat_data_pointer,
-                        self.return_data.take().unwrap(),
+                        data.to_return_data(),
                         &mut state.local_state,
                         &mut state.memory,
                     );
@@ -1014,15 +789,13 @@ impl<S: DatabaseExt + Send, H: HistoryMode> VmTracer<EraDb<S>, H> for CheatcodeT
                 FinishCycleOneTimeActions::ForceRevert { error, exception_handler: pc } => {
                     tracing::debug!(?error, pc, "Forcing revert");
 
-                    self.return_data = Some(error.to_return_data());
-
                     let ptr = state.local_state.registers
                         [RET_IMPLICIT_RETURNDATA_PARAMS_REGISTER as usize];
                     let fat_data_pointer = FatPointer::from_u256(ptr.value);
 
                     Self::set_return(
                         fat_data_pointer,
-                        self.return_data.take().unwrap(),
+                        error.to_return_data(),
                         &mut state.local_state,
                         &mut state.memory,
                     );
@@ -1030,6 +803,9 @@ impl<S: DatabaseExt + Send, H: HistoryMode> VmTracer<EraDb<S>, H> for CheatcodeT
                     //change current stack pc to exception handler
                     state.local_state.callstack.get_current_stack_mut().pc = pc;
                 }
+                FinishCycleOneTimeActions::SetMsgSender { sender } => {
+                    state.local_state.callstack.current.msg_sender = sender;
+                }
                 FinishCycleOneTimeActions::SetOrigin { origin } => {
                     let prev = state
                         .local_state
@@ -1046,65 +822,12 @@ impl<S: DatabaseExt + Send, H: HistoryMode> VmTracer<EraDb<S>, H> for CheatcodeT
 
                     storage.borrow_mut().set_value(key, origin.into());
                 }
-                FinishCycleOneTimeActions::Transact { fork_id, tx_hash } => {
-                    let journaled_state = {
-                        let bytecodes = bootloader_state
-                            .get_last_tx_compressed_bytecodes()
-                            .iter()
-                            .map(|b| bytecode_to_factory_dep(b.original.clone()))
-                            .collect();
-
-                        let storage = storage.borrow_mut();
-                        let modified_storage =
-                            self.get_modified_storage(storage.modified_storage_keys());
-
-                        let era_db = &storage.storage_handle;
-
-                        let mut journaled_state = JournaledState::new(SpecId::LATEST, vec![]);
-                        journaled_state.state =
-                            storage_to_state(era_db, &modified_storage, bytecodes);
-
-                        let mut db = era_db.db.lock().unwrap();
-                        let era_env = self.env.get().unwrap();
-                        let mut env = into_revm_env(era_env);
-
-                        db.transact(
-                            fork_id.map(|id| {
-                                let mut arr = [0; 32];
-                                id.to_big_endian(&mut arr);
-                                Uint::from_be_bytes(arr)
-                            }),
-                            tx_hash.to_fixed_bytes().into(),
-                            &mut env,
-                            &mut journaled_state,
-                            &mut revm::inspectors::NoOpInspector,
-                        )
-                        .unwrap();
-
-                        journaled_state
-                    };
-
-                    storage.borrow_mut().read_storage_keys = Default::default();
-
-                    for log in journaled_state.logs {
-                        self.transact_logs.push(LogEntry {
-                            address: log.address.to_h160(),
-                            data: log.data.to_vec(),
-                            topics: log
-                                .topics
-                                .iter()
-                                .map(|b| H256::from_slice(b.as_slice()))
-                                .collect(),
-                        })
-                    }
-                }
             }
         }
 
         self.farcall_handler.maybe_return_early(state, bootloader_state, storage);
 
-        if let Some(fat_pointer) = self.return_ptr.take() {
-            let elements = self.return_data.take().unwrap();
+        for (fat_pointer, elements) in self.returns.take_returned() {
             Self::set_return(fat_pointer, elements, &mut state.local_state, &mut state.memory);
         }
 
@@ -1126,16 +849,216 @@ impl CheatcodeTracer {
         broadcastable_transactions: Arc<RwLock<BroadcastableTransactions>>,
     ) -> Self {
         Self {
+            recording_logs: cheatcodes_config.record_logs,
             config: cheatcodes_config,
             storage_modifications,
-            broadcastable_transactions,
+            broadcast: BroadcastTracer::new(broadcastable_transactions),
             ..Default::default()
         }
     }
 
+    /// Runs clones of the given [TracerPlugins] alongside the cheatcode tracer.
+    pub fn with_plugins(mut self, plugins: TracerPlugins) -> Self {
+        self.plugins = plugins.instantiate();
+        self
+    }
+
+    /// Records the site of the innermost revert of the transaction into the given slot.
+    pub fn with_revert_site(mut self, revert_site: RevertSiteSlot) -> Self {
+        self.revert_site = revert_site;
+        self
+    }
+
+    /// Reads and records the labels of addresses, set with `vm.label`, in the given labels.
+    pub fn with_labels(mut self, labels: Labels) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Records the wallets remembered with `vm.rememberKey` in the given wallets.
+    pub fn with_script_wallets(mut self, script_wallets: ScriptWallets) -> Self {
+        self.script_wallets = script_wallets;
+        self
+    }
+
+    /// Records the breakpoints written with `vm.breakpoint` in the given breakpoints.
+    pub fn with_breakpoints(mut self, breakpoints: ZkBreakpoints) -> Self {
+        self.breakpoints = breakpoints;
+        self
+    }
+
+    /// Only enables the given tracers, the cheatcodes relying on the other ones revert.
+    pub fn with_tracers(mut self, tracers: EnabledTracers) -> Self {
+        self.tracers = tracers;
+        self
+    }
+
     /// Resets the test state to [TestStatus::NotStarted]
     fn reset_test_status(&mut self) {
         self.test_status = FoundryTestState::NotStarted;
+        self.tracing_level = ZkTracingLevel::Off;
+        self.expectations.reset();
+        self.assume_no_out_of_gas = false;
+        self.out_of_gas_depth = None;
+        self.gas_bound = None;
+        self.suspended_return_action = None;
+    }
+
+    /// Turns the revert of the test into an `assume` rejection if it propagates a frame running
+    /// out of gas since `vm.assumeNoZkOutOfGas`.
+    ///
+    /// The zkEVM panics frames running out of gas, so any panic is taken for one. It propagates
+    /// while the callers revert without data too; a caller returning successfully or reverting
+    /// with its own data, e.g. after a `try`/`catch` or a low-level call returning `false`, caught
+    /// it.
+    fn reject_out_of_gas(
+        &mut self,
+        state: &VmLocalStateData<'_>,
+        data: &multivm::zk_evm_1_4_0::tracing::BeforeExecutionData,
+    ) {
+        let FoundryTestState::Running { call_depth } = self.test_status else { return };
+        let Opcode::Ret(op) = data.opcode.variant.opcode else { return };
+        let depth = state.vm_local_state.callstack.depth();
+        if depth < call_depth {
+            return
+        }
+        match op {
+            RetOpcode::Panic => self.out_of_gas_depth = Some(depth),
+            _ if self.out_of_gas_depth == Some(depth + 1) => {
+                let ptr = state.vm_local_state.registers
+                    [RET_IMPLICIT_RETURNDATA_PARAMS_REGISTER as usize];
+                let propagated =
+                    matches!(op, RetOpcode::Revert) && FatPointer::from_u256(ptr.value).length == 0;
+                self.out_of_gas_depth = propagated.then_some(depth);
+            }
+            _ => (),
+        }
+
+        let current = &state.vm_local_state.callstack.current;
+        if self.out_of_gas_depth == Some(call_depth) &&
+            depth == call_depth &&
+            !current.is_local_frame &&
+            self.next_return_action.is_none()
+        {
+            tracing::info!("test reverted after running out of gas, rejecting the fuzz run");
+            self.reject_test(depth);
+        }
+    }
+
+    /// Turns the return of the test into an `assume` rejection if it used more gas than allowed
+    /// with `vm.assumeZkGasBelow`.
+    fn reject_gas_above_bound(
+        &mut self,
+        state: &VmLocalStateData<'_>,
+        data: &multivm::zk_evm_1_4_0::tracing::BeforeExecutionData,
+    ) {
+        let FoundryTestState::Running { call_depth } = self.test_status else { return };
+        let Opcode::Ret(_) = data.opcode.variant.opcode else { return };
+        let Some((gas_left, max_gas)) = self.gas_bound else { return };
+        let current = &state.vm_local_state.callstack.current;
+        let depth = state.vm_local_state.callstack.depth();
+        if depth != call_depth || current.is_local_frame || self.next_return_action.is_some() {
+            return
+        }
+
+        let used = gas_left.saturating_sub(current.ergs_remaining as u64);
+        if used > max_gas {
+            tracing::info!(used, max_gas, "test used more gas than bound, rejecting the fuzz run");
+            self.reject_test(depth);
+        }
+    }
+
+    /// Reverts the test frame returning at `depth` with [MAGIC_ASSUME], rejecting the fuzz run.
+    fn reject_test(&mut self, depth: usize) {
+        // the exception handler to continue at is stored right after, before the return
+        self.next_return_action = Some(NextReturnAction {
+            target_depth: depth - 1,
+            action: ActionOnReturn::Revert {
+                depth: depth - 1,
+                prev_exception_handler_pc: None,
+                reason: MAGIC_ASSUME.to_vec(),
+            },
+            returns_to_skip: 0,
+        });
+    }
+
+    /// Reverts the test when it returns before as many calls as expected with
+    /// `expectRevert(..., count)` reverted.
+    fn reject_missing_reverts(
+        &mut self,
+        state: &VmLocalStateData<'_>,
+        data: &multivm::zk_evm_1_4_0::tracing::BeforeExecutionData,
+    ) {
+        let FoundryTestState::Running { call_depth } = self.test_status else { return };
+        let Opcode::Ret(_) = data.opcode.variant.opcode else { return };
+        let depth = state.vm_local_state.callstack.depth();
+        if depth != call_depth || state.vm_local_state.callstack.current.is_local_frame {
+            return
+        }
+        let Some(ActionOnReturn::ExpectRevert { count, actual_count, .. }) =
+            self.current_return_action()
+        else {
+            return
+        };
+        // a single expected revert without a call is tolerated, like before counts existed
+        if *count <= 1 {
+            return
+        }
+
+        let error = format!("expected {count} reverts, but got {actual_count}");
+        tracing::error!("{error}");
+        // the exception handler to continue at is stored right after, before the return
+        self.next_return_action = Some(NextReturnAction {
+            target_depth: depth - 1,
+            action: ActionOnReturn::Revert {
+                depth: depth - 1,
+                prev_exception_handler_pc: None,
+                reason: foundry_cheatcodes::Error::encode(error).to_vec(),
+            },
+            returns_to_skip: 0,
+        });
+    }
+
+    /// Returns `true` if the address is in the range reserved for system contracts, unless it was
+    /// configured to be tracked like a user contract.
+    fn is_reserved_address(&self, address: H160) -> bool {
+        address
+            .bitand(H160::from_str("ffffffffffffffffffffffffffffffffffff0000").unwrap())
+            .is_zero() &&
+            !self.config.tracked_reserved_addresses.contains(&h160_to_address(address))
+    }
+
+    /// Logs the far calls and returns executed by the VM.
+    fn trace_call<H: HistoryMode>(
+        &self,
+        state: &VmLocalStateData<'_>,
+        data: &AfterExecutionData,
+        memory: &SimpleMemory<H>,
+    ) {
+        let current = state.vm_local_state.callstack.current;
+        match data.opcode.variant.opcode {
+            Opcode::FarCall(call) => {
+                tracing::info!(
+                    ?call,
+                    depth = state.vm_local_state.callstack.depth(),
+                    code_address = %self.labels.describe(current.code_address),
+                    msg_sender = %self.labels.describe(current.msg_sender),
+                    value = current.context_u128_value,
+                    ergs_remaining = current.ergs_remaining,
+                    calldata = hex::encode(get_calldata(state, memory)),
+                    "zk trace: far call"
+                );
+            }
+            Opcode::Ret(ret) => {
+                tracing::info!(
+                    ?ret,
+                    depth = state.vm_local_state.callstack.depth(),
+                    code_address = %self.labels.describe(current.code_address),
+                    "zk trace: return"
+                );
+            }
+            _ => (),
+        }
     }
 
     /// Updates and keeps track of the test status.
@@ -1158,6 +1081,7 @@ impl CheatcodeTracer {
                         call_depth: state.vm_local_state.callstack.depth(),
                     };
                     tracing::info!("Test started depth {}", state.vm_local_state.callstack.depth());
+                    self.plugins.for_each(|plugin| plugin.on_test_start());
                 }
             }
             Opcode::Ret(_) => {
@@ -1183,42 +1107,169 @@ impl CheatcodeTracer {
         _memory: &SimpleMemory<H>,
         storage: StoragePtr<EraDb<S>>,
         call: Vm::VmCalls,
-    ) {
+    ) -> Result<Option<ReturnData>, CheatcodeError> {
         use Vm::{VmCalls::*, *};
 
+        if let Some(cheatcode) =
+            Vm::CHEATCODES.iter().find(|c| c.func.selector_bytes == call.selector())
+        {
+            if !self.config.cheatcode_access.is_enabled(cheatcode.func.id) {
+                return Err(CheatcodeError::DisabledByConfig(cheatcode.func.id))
+            }
+        }
+        if let Some(tracer) = self.tracers.disabled_for(&call) {
+            return Err(CheatcodeError::TracerDisabled(tracer))
+        }
+        if !is_dispatched(&call) {
+            return Err(CheatcodeError::not_dispatched(call.selector()))
+        }
+
+        let mut return_data = None;
         match call {
             activeFork(activeForkCall {}) => {
                 tracing::info!("👷 Getting active fork");
                 let handle = &storage.borrow_mut().storage_handle;
                 let fork_id = handle.db.lock().unwrap().active_fork_id();
-                assert!(fork_id.is_some(), "No active fork found. Please create a fork first.");
-                self.return_data = Some(fork_id.unwrap().to_return_data());
+                let Some(fork_id) = fork_id else {
+                    return Err(CheatcodeError::Revert("No active fork found".to_string()))
+                };
+                return_data = Some(fork_id.to_return_data());
             }
             addr(addrCall { privateKey: private_key }) => {
                 tracing::info!("👷 Getting address for private key");
                 let Ok(address) = zksync_types::PackedEthSignature::address_from_private_key(
                     &private_key.to_h256(),
                 ) else {
-                    tracing::error!("Failed generating address for private key");
-                    return
+                    return Err(CheatcodeError::InvalidPrivateKey)
                 };
-                self.return_data = Some(h160_to_address(address).to_return_data());
+                return_data = Some(h160_to_address(address).to_return_data());
             }
             assume(assumeCall { condition }) => {
                 tracing::info!(condition, "👷 Skipping fuzz test run if condition is not met");
                 if condition {
-                    return
+                    return Ok(None)
                 }
-                self.add_revert_test(MAGIC_ASSUME.to_vec(), state.vm_local_state.callstack.depth());
+                self.add_revert_test(
+                    MAGIC_ASSUME.to_vec(),
+                    state.vm_local_state.callstack.depth(),
+                )?;
+            }
+            assumeNoZkOutOfGas(assumeNoZkOutOfGasCall {}) => {
+                tracing::info!("👷 Rejecting the fuzz run if the test runs out of gas");
+                self.assume_no_out_of_gas = true;
+            }
+            assumeZkGasBelow(assumeZkGasBelowCall { gas }) => {
+                tracing::info!("👷 Rejecting the fuzz run if the test uses more than {gas} gas");
+                let FoundryTestState::Running { call_depth } = self.test_status else {
+                    return Err(CheatcodeError::Revert(
+                        "`assumeZkGasBelow` can only be used in tests".to_string(),
+                    ))
+                };
+                // the gas of the frames of the test, including what was passed to this call
+                let callstack = &state.vm_local_state.callstack;
+                let gas_left = callstack.inner[call_depth.min(callstack.inner.len())..]
+                    .iter()
+                    .chain([&callstack.current])
+                    .map(|frame| frame.ergs_remaining as u64)
+                    .sum();
+                self.gas_bound = Some((gas_left, gas.try_into().unwrap_or(u64::MAX)));
+            }
+            breakpoint_0(breakpoint_0Call { char }) => {
+                tracing::info!("👷 Writing breakpoint {char:?}");
+                self.write_breakpoint(&state, &char, true)?;
+            }
+            breakpoint_1(breakpoint_1Call { char, value }) => {
+                tracing::info!(value, "👷 Writing conditional breakpoint {char:?}");
+                self.write_breakpoint(&state, &char, value)?;
+            }
+            diffSnapshots(diffSnapshotsCall { fromSnapshotId: from, toSnapshotId: to }) => {
+                tracing::info!("👷 Diffing snapshots {from} and {to}");
+                let (Some(before), Some(after)) = (
+                    self.forks.saved_snapshots.get(&from.to_u256()),
+                    self.forks.saved_snapshots.get(&to.to_u256()),
+                ) else {
+                    return Err(CheatcodeError::Revert(format!(
+                        "snapshots {from} and {to} must both exist to be diffed"
+                    )))
+                };
+                let mut storage = storage.borrow_mut();
+                let mut accounts = known_accounts(
+                    before
+                        .modified_storage
+                        .keys()
+                        .chain(after.modified_storage.keys())
+                        .chain(storage.read_storage_keys.keys())
+                        .chain(storage.modified_storage_keys.keys()),
+                );
+                accounts.extend(&self.dealt_accounts);
+                let diff = SnapshotDiff::new(
+                    &before.modified_storage,
+                    &after.modified_storage,
+                    accounts,
+                    |key| storage.storage_handle.read_value(key),
+                );
+                match serde_json::to_string(&diff) {
+                    Ok(json) => return_data = Some(json.to_return_data()),
+                    Err(err) => {
+                        return Err(CheatcodeError::Revert(format!(
+                            "failed to serialize snapshot diff: {err}"
+                        )))
+                    }
+                }
+            }
+            createWallet_0(createWallet_0Call { walletLabel: label }) => {
+                tracing::info!("👷 Creating wallet {label:?}");
+                let private_key =
+                    alloy_primitives::U256::from_be_bytes(alloy_primitives::keccak256(&label).0);
+                return_data = Some(self.create_wallet(private_key, Some(label))?);
+            }
+            createWallet_1(createWallet_1Call { privateKey: private_key }) => {
+                tracing::info!("👷 Creating wallet from private key");
+                return_data = Some(self.create_wallet(private_key, None)?);
+            }
+            createWallet_2(createWallet_2Call { privateKey: private_key, walletLabel: label }) => {
+                tracing::info!("👷 Creating wallet {label:?} from private key");
+                return_data = Some(self.create_wallet(private_key, Some(label))?);
             }
             deal(dealCall { account, newBalance: new_balance }) => {
                 tracing::info!("👷 Setting balance for {account:?} to {new_balance}");
+                self.dealt_accounts.insert(account.to_h160());
                 self.write_storage(
                     storage_key_for_eth_balance(&account.to_h160()),
                     new_balance.to_h256(),
                     &mut storage.borrow_mut(),
                 );
             }
+            deriveKey_0(deriveKey_0Call { mnemonic, index }) => {
+                tracing::info!("👷 Deriving key {index} from mnemonic");
+                let key = crate::utils::derive_key(
+                    &mnemonic,
+                    crate::utils::DEFAULT_DERIVATION_PATH_PREFIX,
+                    index,
+                    "english",
+                )?;
+                return_data = Some(vec![key]);
+            }
+            deriveKey_1(deriveKey_1Call { mnemonic, derivationPath: path, index }) => {
+                tracing::info!("👷 Deriving key {index} at {path:?} from mnemonic");
+                let key = crate::utils::derive_key(&mnemonic, &path, index, "english")?;
+                return_data = Some(vec![key]);
+            }
+            deriveKey_2(deriveKey_2Call { mnemonic, index, language }) => {
+                tracing::info!("👷 Deriving key {index} from {language} mnemonic");
+                let key = crate::utils::derive_key(
+                    &mnemonic,
+                    crate::utils::DEFAULT_DERIVATION_PATH_PREFIX,
+                    index,
+                    &language,
+                )?;
+                return_data = Some(vec![key]);
+            }
+            deriveKey_3(deriveKey_3Call { mnemonic, derivationPath: path, index, language }) => {
+                tracing::info!("👷 Deriving key {index} at {path:?} from {language} mnemonic");
+                let key = crate::utils::derive_key(&mnemonic, &path, index, &language)?;
+                return_data = Some(vec![key]);
+            }
             etch(etchCall { target, newRuntimeBytecode: new_runtime_bytecode }) => {
                 tracing::info!("👷 Setting address code for {target:?}");
                 let code_key = get_code_key(&target.to_h160());
@@ -1228,205 +1279,304 @@ impl CheatcodeTracer {
             }
             envAddress_0(envAddress_0Call { name }) => {
                 tracing::info!("👷 Getting address env variable {name}");
-                let env_var: Address =
-                    parse_env(&name, |var| var.parse()).expect("Env var not address");
-                self.return_data = Some(env_var.to_return_data());
+                let env_var: Address = parse_env(&name, "address", |var| var.parse())?;
+                return_data = Some(env_var.to_return_data());
             }
             envAddress_1(envAddress_1Call { name, delim }) => {
                 tracing::info!("👷 Getting address env variable {name} with delimiter {delim}");
                 let env_vars: Vec<Address> =
-                    parse_env_array(&name, &delim, |var| var.parse()).expect("Env var not address");
-                self.return_data = Some(env_vars.to_return_data());
+                    parse_env_array(&name, &delim, "address[]", |var| var.parse())?;
+                return_data = Some(env_vars.to_return_data());
             }
             envBool_0(envBool_0Call { name }) => {
                 tracing::info!("👷 Getting bool env variable {name}");
-                let env_var: bool = parse_env(&name, |var| var.parse()).expect("Env var not bool");
-                self.return_data = Some(env_var.to_return_data());
+                let env_var: bool = parse_env(&name, "bool", |var| var.parse())?;
+                return_data = Some(env_var.to_return_data());
             }
             envBool_1(envBool_1Call { name, delim }) => {
                 tracing::info!("👷 Getting bool env variable {name} with delimiter {delim}");
                 let env_vars: Vec<bool> =
-                    parse_env_array(&name, &delim, |var| var.parse()).expect("Env var not bool");
-                self.return_data = Some(env_vars.to_return_data());
+                    parse_env_array(&name, &delim, "bool[]", |var| var.parse())?;
+                return_data = Some(env_vars.to_return_data());
             }
             envBytes_0(envBytes_0Call { name }) => {
                 tracing::info!("👷 Getting bytes env variable {name}");
-                let env_var: Bytes =
-                    parse_env(&name, |var| var.parse()).expect("Env var not bytes");
-                self.return_data = Some(env_var.to_return_data());
+                let env_var: Bytes = parse_env(&name, "bytes", |var| var.parse())?;
+                return_data = Some(env_var.to_return_data());
             }
             envBytes_1(envBytes_1Call { name, delim }) => {
                 tracing::info!("👷 Getting bytes env variable {name} with delimiter {delim}");
                 let env_vars: Vec<Bytes> =
-                    parse_env_array(&name, &delim, |var| var.parse()).expect("Env var not bytes");
-                self.return_data = Some(env_vars.to_return_data());
+                    parse_env_array(&name, &delim, "bytes[]", |var| var.parse())?;
+                return_data = Some(env_vars.to_return_data());
             }
             envBytes32_0(envBytes32_0Call { name }) => {
                 tracing::info!("👷 Getting bytes32 env variable {name}");
-                let env_var: FixedBytes<32> =
-                    parse_env(&name, |var| var.parse()).expect("Env var not bytes32");
-                self.return_data = Some(env_var.to_return_data());
+                let env_var: FixedBytes<32> = parse_env(&name, "bytes32", |var| var.parse())?;
+                return_data = Some(env_var.to_return_data());
             }
             envBytes32_1(envBytes32_1Call { name, delim }) => {
                 tracing::info!("👷 Getting bytes32 env variable {name} with delimiter {delim}");
                 let env_vars: Vec<FixedBytes<32>> =
-                    parse_env_array(&name, &delim, |var| var.parse()).expect("Env var not bytes32");
-                self.return_data = Some(env_vars.to_return_data());
+                    parse_env_array(&name, &delim, "bytes32[]", |var| var.parse())?;
+                return_data = Some(env_vars.to_return_data());
             }
             envInt_0(envInt_0Call { name }) => {
                 tracing::info!("👷 Getting int256 env variable {name}");
-                let env_var: rI256 = parse_env(&name, |var| var.parse()).expect("Env var not int");
-                self.return_data = Some(env_var.to_return_data());
+                let env_var: rI256 = parse_env(&name, "int256", |var| var.parse())?;
+                return_data = Some(env_var.to_return_data());
             }
             envInt_1(envInt_1Call { name, delim }) => {
                 tracing::info!("👷 Getting int256 env variable {name} with delimiter {delim}");
                 let env_vars: Vec<rI256> =
-                    parse_env_array(&name, &delim, |var| var.parse()).expect("Env var not int");
-                self.return_data = Some(env_vars.to_return_data());
+                    parse_env_array(&name, &delim, "int256[]", |var| var.parse())?;
+                return_data = Some(env_vars.to_return_data());
             }
             envString_0(envString_0Call { name }) => {
                 tracing::info!("👷 Getting string env variable {name}");
-                let env_var: String =
-                    parse_env(&name, |var| var.parse()).expect("Env var not string");
-                self.return_data = Some(env_var.to_return_data());
+                let env_var: String = parse_env(&name, "string", |var| var.parse())?;
+                return_data = Some(env_var.to_return_data());
             }
             envString_1(envString_1Call { name, delim }) => {
                 tracing::info!("👷 Getting string env variable {name} with delimiter {delim}");
                 let env_vars: Vec<String> =
-                    parse_env_array(&name, &delim, |var| var.parse()).expect("Env var not string");
-                self.return_data = Some(env_vars.to_return_data());
+                    parse_env_array(&name, &delim, "string[]", |var| var.parse())?;
+                return_data = Some(env_vars.to_return_data());
             }
             envUint_0(envUint_0Call { name }) => {
                 tracing::info!("👷 Getting uint256 env variable {name}");
-                let env_var: rU256 = parse_env(&name, |var| var.parse()).expect("Env var not int");
-                self.return_data = Some(env_var.to_return_data());
+                let env_var: rU256 = parse_env(&name, "uint256", |var| var.parse())?;
+                return_data = Some(env_var.to_return_data());
             }
             envUint_1(envUint_1Call { name, delim }) => {
                 tracing::info!("👷 Getting uint256 env variable {name} with delimiter {delim}");
                 let env_vars: Vec<rU256> =
-                    parse_env_array(&name, &delim, |var| var.parse()).expect("Env var not int");
-                self.return_data = Some(env_vars.to_return_data());
+                    parse_env_array(&name, &delim, "uint256[]", |var| var.parse())?;
+                return_data = Some(env_vars.to_return_data());
             }
             envOr_0(envOr_0Call { name, defaultValue }) => {
                 tracing::info!("👷 Getting bool env variable {name} with fallback {defaultValue}");
-                let env_var: bool = parse_env(&name, |var| var.parse()).unwrap_or(defaultValue);
-                self.return_data = Some(env_var.to_return_data());
+                let env_var: bool =
+                    parse_env(&name, "bool", |var| var.parse()).unwrap_or(defaultValue);
+                return_data = Some(env_var.to_return_data());
             }
             envOr_1(envOr_1Call { name, defaultValue }) => {
                 tracing::info!(
                     "👷 Getting uint256 env variable {name} with fallback {defaultValue}"
                 );
-                let env_var: rU256 = parse_env(&name, |var| var.parse()).unwrap_or(defaultValue);
-                self.return_data = Some(env_var.to_return_data());
+                let env_var: rU256 =
+                    parse_env(&name, "uint256", |var| var.parse()).unwrap_or(defaultValue);
+                return_data = Some(env_var.to_return_data());
             }
             envOr_2(envOr_2Call { name, defaultValue }) => {
                 tracing::info!(
                     "👷 Getting int256 env variable {name} with fallback {defaultValue}"
                 );
-                let env_var: rI256 = parse_env(&name, |var| var.parse()).unwrap_or(defaultValue);
-                self.return_data = Some(env_var.to_return_data());
+                let env_var: rI256 =
+                    parse_env(&name, "int256", |var| var.parse()).unwrap_or(defaultValue);
+                return_data = Some(env_var.to_return_data());
             }
             envOr_3(envOr_3Call { name, defaultValue }) => {
                 tracing::info!(
                     "👷 Getting address env variable {name} with fallback {defaultValue}"
                 );
-                let env_var: Address = parse_env(&name, |var| var.parse()).unwrap_or(defaultValue);
-                self.return_data = Some(env_var.to_return_data());
+                let env_var: Address =
+                    parse_env(&name, "address", |var| var.parse()).unwrap_or(defaultValue);
+                return_data = Some(env_var.to_return_data());
             }
             envOr_4(envOr_4Call { name, defaultValue }) => {
                 tracing::info!(
                     "👷 Getting bytes32 env variable {name} with fallback {defaultValue}"
                 );
                 let env_var: FixedBytes<32> =
-                    parse_env(&name, |var| var.parse()).unwrap_or(defaultValue);
-                self.return_data = Some(env_var.to_return_data());
+                    parse_env(&name, "bytes32", |var| var.parse()).unwrap_or(defaultValue);
+                return_data = Some(env_var.to_return_data());
             }
             envOr_5(envOr_5Call { name, defaultValue }) => {
                 tracing::info!(
                     "👷 Getting string env variable {name} with fallback {defaultValue}"
                 );
-                let env_var = parse_env(&name, |var| {
+                let env_var = parse_env(&name, "string", |var| {
                     Ok::<_, &(dyn std::error::Error + Send + Sync)>(var.to_string())
                 })
                 .unwrap_or(defaultValue);
-                self.return_data = Some(env_var.to_return_data());
+                return_data = Some(env_var.to_return_data());
             }
             envOr_6(envOr_6Call { name, defaultValue }) => {
                 tracing::info!(
                     "👷 Getting bytes env variable {name} with fallback {defaultValue:?}"
                 );
                 let env_var: Bytes =
-                    parse_env(&name, |var| var.parse()).unwrap_or(defaultValue.into());
-                self.return_data = Some(env_var.to_return_data());
+                    parse_env(&name, "bytes", |var| var.parse()).unwrap_or(defaultValue.into());
+                return_data = Some(env_var.to_return_data());
             }
             envOr_7(envOr_7Call { name, delim, defaultValue }) => {
                 tracing::info!("👷 Getting bool env variable {name} with delimiter {delim} and fallback {defaultValue:?}");
                 let env_vars: Vec<bool> =
-                    parse_env_array(&name, &delim, |var| var.parse()).unwrap_or(defaultValue);
-                self.return_data = Some(env_vars.to_return_data());
+                    parse_env_array(&name, &delim, "bool[]", |var| var.parse())
+                        .unwrap_or(defaultValue);
+                return_data = Some(env_vars.to_return_data());
             }
             envOr_8(envOr_8Call { name, delim, defaultValue }) => {
                 tracing::info!("👷 Getting uint256 env variable {name} with delimiter {delim} and fallback {defaultValue:?}");
                 let env_vars: Vec<rU256> =
-                    parse_env_array(&name, &delim, |var| var.parse()).unwrap_or(defaultValue);
-                self.return_data = Some(env_vars.to_return_data());
+                    parse_env_array(&name, &delim, "uint256[]", |var| var.parse())
+                        .unwrap_or(defaultValue);
+                return_data = Some(env_vars.to_return_data());
             }
             envOr_9(envOr_9Call { name, delim, defaultValue }) => {
                 tracing::info!("👷 Getting int256 env variable {name} with delimiter {delim} and fallback {defaultValue:?}");
                 let env_vars: Vec<rI256> =
-                    parse_env_array(&name, &delim, |var| var.parse()).unwrap_or(defaultValue);
-                self.return_data = Some(env_vars.to_return_data());
+                    parse_env_array(&name, &delim, "int256[]", |var| var.parse())
+                        .unwrap_or(defaultValue);
+                return_data = Some(env_vars.to_return_data());
             }
             envOr_10(envOr_10Call { name, delim, defaultValue }) => {
                 tracing::info!("👷 Getting address env variable {name} with delimiter {delim} and fallback {defaultValue:?}");
                 let env_vars: Vec<Address> =
-                    parse_env_array(&name, &delim, |var| var.parse()).unwrap_or(defaultValue);
-                self.return_data = Some(env_vars.to_return_data());
+                    parse_env_array(&name, &delim, "address[]", |var| var.parse())
+                        .unwrap_or(defaultValue);
+                return_data = Some(env_vars.to_return_data());
             }
             envOr_11(envOr_11Call { name, delim, defaultValue }) => {
                 tracing::info!("👷 Getting bytes32 env variable {name} with delimiter {delim} and fallback {defaultValue:?}");
                 let env_vars: Vec<FixedBytes<32>> =
-                    parse_env_array(&name, &delim, |var| var.parse()).unwrap_or(defaultValue);
-                self.return_data = Some(env_vars.to_return_data());
+                    parse_env_array(&name, &delim, "bytes32[]", |var| var.parse())
+                        .unwrap_or(defaultValue);
+                return_data = Some(env_vars.to_return_data());
             }
             envOr_12(envOr_12Call { name, delim, defaultValue }) => {
                 tracing::info!("👷 Getting string env variable {name} with delimiter {delim} and fallback {defaultValue:?}");
                 let env_vars: Vec<String> =
-                    parse_env_array(&name, &delim, |var| var.parse()).unwrap_or(defaultValue);
-                self.return_data = Some(env_vars.to_return_data());
+                    parse_env_array(&name, &delim, "string[]", |var| var.parse())
+                        .unwrap_or(defaultValue);
+                return_data = Some(env_vars.to_return_data());
             }
             envOr_13(envOr_13Call { name, delim, defaultValue }) => {
                 tracing::info!("👷 Getting bytes env variable {name} with delimiter {delim} and fallback {defaultValue:?}");
-                let env_vars: Vec<Bytes> = parse_env_array(&name, &delim, |var| var.parse())
-                    .unwrap_or(defaultValue.into_iter().map(|v| v.into()).collect());
-                self.return_data = Some(env_vars.to_return_data());
+                let env_vars: Vec<Bytes> =
+                    parse_env_array(&name, &delim, "bytes[]", |var| var.parse())
+                        .unwrap_or(defaultValue.into_iter().map(|v| v.into()).collect());
+                return_data = Some(env_vars.to_return_data());
+            }
+            erc20Allowance(erc20AllowanceCall { token, owner, spender }) => {
+                tracing::info!(
+                    "👷 Getting the allowance of {spender:?} from {owner:?} in {token:?}"
+                );
+                let calldata = allowanceCall { owner, spender }.abi_encode();
+                let allowance: rU256 = self.call_token(&state, &storage, token, calldata)?;
+                return_data = Some(allowance.to_return_data());
+            }
+            erc20BalanceOf(erc20BalanceOfCall { token, account }) => {
+                tracing::info!("👷 Getting the balance of {account:?} in {token:?}");
+                let calldata = balanceOfCall { account }.abi_encode();
+                let balance: rU256 = self.call_token(&state, &storage, token, calldata)?;
+                return_data = Some(balance.to_return_data());
+            }
+            erc721BalanceOf(erc721BalanceOfCall { token, owner }) => {
+                tracing::info!("👷 Getting the number of tokens of {owner:?} in {token:?}");
+                let calldata = balanceOfCall { account: owner }.abi_encode();
+                let balance: rU256 = self.call_token(&state, &storage, token, calldata)?;
+                return_data = Some(balance.to_return_data());
+            }
+            erc721GetApproved(erc721GetApprovedCall { token, tokenId: token_id }) => {
+                tracing::info!("👷 Getting the account approved for token {token_id} of {token:?}");
+                let calldata = getApprovedCall { tokenId: token_id }.abi_encode();
+                let approved: Address = self.call_token(&state, &storage, token, calldata)?;
+                return_data = Some(approved.to_return_data());
+            }
+            erc721IsApprovedForAll(erc721IsApprovedForAllCall { token, owner, operator }) => {
+                tracing::info!(
+                    "👷 Getting whether {operator:?} is approved for all of {owner:?} in {token:?}"
+                );
+                let calldata = isApprovedForAllCall { owner, operator }.abi_encode();
+                let approved: bool = self.call_token(&state, &storage, token, calldata)?;
+                return_data = Some(approved.to_return_data());
+            }
+            erc721OwnerOf(erc721OwnerOfCall { token, tokenId: token_id }) => {
+                tracing::info!("👷 Getting the owner of token {token_id} of {token:?}");
+                let calldata = ownerOfCall { tokenId: token_id }.abi_encode();
+                let owner: Address = self.call_token(&state, &storage, token, calldata)?;
+                return_data = Some(owner.to_return_data());
             }
             expectRevert_0(expectRevert_0Call {}) => {
                 let depth = state.vm_local_state.callstack.depth();
                 tracing::info!(%depth, "👷 Setting up expectRevert for any reason");
-                self.add_expect_revert(None, depth)
+                self.add_expect_revert(None, None, 1, depth)?
             }
             expectRevert_1(expectRevert_1Call { revertData }) => {
                 let depth = state.vm_local_state.callstack.depth();
                 tracing::info!(%depth, reason = ?revertData, "👷 Setting up expectRevert with bytes4 reason");
-                self.add_expect_revert(Some(revertData.to_vec()), depth)
+                self.add_expect_revert(Some(revertData.to_vec()), None, 1, depth)?
             }
             expectRevert_2(expectRevert_2Call { revertData }) => {
                 let depth = state.vm_local_state.callstack.depth();
                 tracing::info!(%depth, reason = ?revertData, "👷 Setting up expectRevert with reason");
-                self.add_expect_revert(Some(revertData.to_vec()), depth)
+                self.add_expect_revert(Some(revertData.to_vec()), None, 1, depth)?
+            }
+            expectRevert_3(expectRevert_3Call { count }) => {
+                let depth = state.vm_local_state.callstack.depth();
+                tracing::info!(%depth, %count, "👷 Setting up expectRevert for any reason");
+                self.add_expect_revert(None, None, count, depth)?
+            }
+            expectRevert_4(expectRevert_4Call { revertData, count }) => {
+                let depth = state.vm_local_state.callstack.depth();
+                tracing::info!(%depth, %count, reason = ?revertData, "👷 Setting up expectRevert with bytes4 reason");
+                self.add_expect_revert(Some(revertData.to_vec()), None, count, depth)?
+            }
+            expectRevert_5(expectRevert_5Call { revertData, count }) => {
+                let depth = state.vm_local_state.callstack.depth();
+                tracing::info!(%depth, %count, reason = ?revertData, "👷 Setting up expectRevert with reason");
+                self.add_expect_revert(Some(revertData.to_vec()), None, count, depth)?
+            }
+            expectRevert_6(expectRevert_6Call { reverter, count }) => {
+                let depth = state.vm_local_state.callstack.depth();
+                tracing::info!(%depth, %count, ?reverter, "👷 Setting up expectRevert for any reason");
+                self.add_expect_revert(None, Some(reverter.to_h160()), count, depth)?
+            }
+            expectRevert_7(expectRevert_7Call { revertData, reverter, count }) => {
+                let depth = state.vm_local_state.callstack.depth();
+                tracing::info!(%depth, %count, ?reverter, reason = ?revertData, "👷 Setting up expectRevert with bytes4 reason");
+                self.add_expect_revert(
+                    Some(revertData.to_vec()),
+                    Some(reverter.to_h160()),
+                    count,
+                    depth,
+                )?
+            }
+            expectRevert_8(expectRevert_8Call { revertData, reverter, count }) => {
+                let depth = state.vm_local_state.callstack.depth();
+                tracing::info!(%depth, %count, ?reverter, reason = ?revertData, "👷 Setting up expectRevert with reason");
+                self.add_expect_revert(
+                    Some(revertData.to_vec()),
+                    Some(reverter.to_h160()),
+                    count,
+                    depth,
+                )?
             }
             expectCall_0(expectCall_0Call { callee, data }) => {
                 tracing::info!("👷 Setting expected call to {callee:?}");
-                self.expect_call(&callee.to_h160(), &data, None, 1, ExpectedCallType::NonCount);
+                self.expectations.expect_call(
+                    &callee.to_h160(),
+                    &data,
+                    None,
+                    1,
+                    ExpectedCallType::NonCount,
+                );
             }
             expectCall_1(expectCall_1Call { callee, data, count }) => {
                 tracing::info!("👷 Setting expected call to {callee:?} with count {count}");
-                self.expect_call(&callee.to_h160(), &data, None, count, ExpectedCallType::Count);
+                self.expectations.expect_call(
+                    &callee.to_h160(),
+                    &data,
+                    None,
+                    count,
+                    ExpectedCallType::Count,
+                );
             }
             expectCall_2(expectCall_2Call { callee, msgValue, data }) => {
                 tracing::info!("👷 Setting expected call to {callee:?} with value {msgValue}");
-                self.expect_call(
+                self.expectations.expect_call(
                     &callee.to_h160(),
                     &data,
                     Some(msgValue.to_u256()),
@@ -1439,7 +1589,7 @@ impl CheatcodeTracer {
                     "👷 Setting expected call to {callee:?} with value {msgValue} and count
                 {count}"
                 );
-                self.expect_call(
+                self.expectations.expect_call(
                     &callee.to_h160(),
                     &data,
                     Some(msgValue.to_u256()),
@@ -1455,13 +1605,14 @@ impl CheatcodeTracer {
                     checkTopic3,
                     checkData
                 );
-                self.emit_config.expected_emit_state = ExpectedEmitState::ExpectedEmitTriggered;
-                self.emit_config.expect_emits_since = state.vm_local_state.timestamp;
-                self.emit_config.checks = EmitChecks {
-                    address: None,
-                    topics: [checkTopic1, checkTopic2, checkTopic3],
-                    data: checkData,
-                };
+                self.expectations.expect_emit(
+                    EmitChecks {
+                        address: None,
+                        topics: [checkTopic1, checkTopic2, checkTopic3],
+                        data: checkData,
+                    },
+                    state.vm_local_state.timestamp,
+                );
             }
             expectEmit_1(expectEmit_1Call {
                 checkTopic1,
@@ -1478,42 +1629,47 @@ impl CheatcodeTracer {
                     checkData,
                     emitter
                 );
-                self.emit_config.expected_emit_state = ExpectedEmitState::ExpectedEmitTriggered;
-                self.emit_config.expect_emits_since = state.vm_local_state.timestamp;
-                self.emit_config.checks = EmitChecks {
-                    address: Some(emitter.to_h160()),
-                    topics: [checkTopic1, checkTopic2, checkTopic3],
-                    data: checkData,
-                };
-                self.emit_config.call_depth = state.vm_local_state.callstack.depth();
+                self.expectations.expect_emit(
+                    EmitChecks {
+                        address: Some(emitter.to_h160()),
+                        topics: [checkTopic1, checkTopic2, checkTopic3],
+                        data: checkData,
+                    },
+                    state.vm_local_state.timestamp,
+                );
+                self.expectations.emit_config.call_depth = state.vm_local_state.callstack.depth();
             }
             expectEmit_2(expectEmit_2Call {}) => {
                 tracing::info!("👷 Setting expected emit at {}", state.vm_local_state.timestamp);
-                self.emit_config.expected_emit_state = ExpectedEmitState::ExpectedEmitTriggered;
-                self.emit_config.expect_emits_since = state.vm_local_state.timestamp;
-                self.emit_config.checks =
-                    EmitChecks { address: None, topics: [true; 3], data: true };
+                self.expectations.expect_emit(
+                    EmitChecks { address: None, topics: [true; 3], data: true },
+                    state.vm_local_state.timestamp,
+                );
+            }
+            expectEmit_4(expectEmit_4Call { topicWildcards, checkData }) => {
+                tracing::info!(
+                    "👷 Setting expected emit with topic wildcards {topicWildcards:#05b} and data check {checkData}"
+                );
+                self.expectations.expect_emit(
+                    EmitChecks::with_topic_wildcards(topicWildcards, checkData),
+                    state.vm_local_state.timestamp,
+                );
             }
             ffi(ffiCall { commandInput: command_input }) => {
                 tracing::info!("👷 Running ffi: {command_input:?}");
                 let Some(first_arg) = command_input.get(0) else {
-                    tracing::error!("Failed to run ffi: no args");
-                    return
+                    return Err(CheatcodeError::Ffi("no args".to_string()))
                 };
-                let Ok(output) = Command::new(first_arg)
+                let output = Command::new(first_arg)
                     .args(&command_input[1..])
                     .current_dir(&self.config.root)
                     .output()
-                else {
-                    tracing::error!("Failed to run ffi");
-                    return
-                };
+                    .map_err(|err| CheatcodeError::Ffi(err.to_string()))?;
 
                 // The stdout might be encoded on valid hex, or it might just be a string,
                 // so we need to determine which it is to avoid improperly encoding later.
                 let Ok(trimmed_stdout) = String::from_utf8(output.stdout) else {
-                    tracing::error!("Failed to parse ffi output");
-                    return
+                    return Err(CheatcodeError::Ffi("output is not valid UTF-8".to_string()))
                 };
                 let trimmed_stdout = trimmed_stdout.trim();
                 let encoded_stdout =
@@ -1523,7 +1679,60 @@ impl CheatcodeTracer {
                         trimmed_stdout.as_bytes().to_vec()
                     };
 
-                self.return_data = Some(encoded_stdout.to_return_data());
+                return_data = Some(encoded_stdout.to_return_data());
+            }
+            getCalls(getCallsCall { callee, data }) => {
+                tracing::info!("👷 Getting calls to {callee} with calldata {data:?}");
+                let Some(count) = self.expectations.get_calls(&callee.to_h160(), &data) else {
+                    return Err(CheatcodeError::Revert(
+                        "calls aren't recorded, call `recordCalls` first".to_string(),
+                    ))
+                };
+                return_data = Some(vec![U256::from(count)]);
+            }
+            getCode(getCodeCall { artifactPath: artifact_path }) => {
+                tracing::info!("👷 Getting code of artifact {artifact_path}");
+                let code = self.load_artifact_code(&artifact_path, &mut storage.borrow_mut())?;
+                return_data = Some(Bytes::from(code).to_return_data());
+            }
+            getDeployedCode_0(getDeployedCode_0Call { artifactPath: artifact_path }) => {
+                tracing::info!("👷 Getting deployed code of artifact {artifact_path}");
+                // EraVM contracts are deployed with their code as is, the constructor being part
+                // of it
+                let code = self.load_artifact_code(&artifact_path, &mut storage.borrow_mut())?;
+                return_data = Some(Bytes::from(code).to_return_data());
+            }
+            getDeployedCode_1(getDeployedCode_1Call { account }) => {
+                tracing::info!("👷 Getting deployed code of {account:?}");
+                let mut storage = storage.borrow_mut();
+                let hash = storage.read_value(&get_code_key(&account.to_h160()));
+                if hash.is_zero() {
+                    return Ok(Some(Bytes::new().to_return_data()))
+                }
+
+                // forked code is fetched by hash from the fork's RPC
+                let code = self
+                    .storage_modifications
+                    .bytecodes()
+                    .get(&hash)
+                    .or_else(|| self.storage_modifications.known_codes().get(&hash))
+                    .cloned()
+                    .or_else(|| storage.load_factory_dep(hash));
+                match code {
+                    Some(code) => return_data = Some(Bytes::from(code).to_return_data()),
+                    None => {
+                        return Err(CheatcodeError::Revert(format!(
+                            "bytecode {hash:?} of {account} is not available"
+                        )))
+                    }
+                }
+            }
+            getLabel(getLabelCall { account }) => {
+                let label = self
+                    .labels
+                    .get(account.to_h160())
+                    .unwrap_or_else(|| format!("unlabeled:{account}"));
+                return_data = Some(label.to_return_data());
             }
             getNonce_0(getNonce_0Call { account }) => {
                 tracing::info!("👷 Getting nonce for {account:?}");
@@ -1536,32 +1745,47 @@ impl CheatcodeTracer {
                 );
                 tracing::info!("👷 Setting returndata",);
                 tracing::info!("👷 Returndata is {:?}", account_nonce);
-                self.return_data = Some(vec![account_nonce]);
+                return_data = Some(vec![account_nonce]);
+            }
+            getNonce_1(getNonce_1Call { wallet }) => {
+                tracing::info!("👷 Getting nonce for wallet {:?}", wallet.addr);
+                let (account_nonce, _) =
+                    Self::get_nonce(wallet.addr.to_h160(), &mut storage.borrow_mut());
+                return_data = Some(vec![account_nonce]);
+            }
+            getFoundryVersion(getFoundryVersionCall {}) => {
+                tracing::info!("👷 Getting foundry version");
+                return_data = Some(env!("CARGO_PKG_VERSION").to_return_data());
             }
-            getRecordedLogs(getRecordedLogsCall {}) => {
+            getRecordedLogs_0(getRecordedLogs_0Call {}) => {
                 tracing::info!("👷 Getting recorded logs");
-                let logs: Vec<Log> = self
-                    .recorded_logs
-                    .iter()
-                    .filter(|log| !log.data.is_empty())
-                    .filter(|log| !INTERNAL_CONTRACT_ADDRESSES.contains(&log.address))
-                    .map(|log| Log {
-                        topics: log
-                            .topics
-                            .iter()
-                            .map(|topic| topic.to_fixed_bytes().into())
-                            .collect(),
-                        data: log.data.clone(),
-                        emitter: log.address.to_fixed_bytes().into(),
-                    })
-                    .collect_vec();
-
-                self.return_data = Some(logs.to_return_data());
-
-                //clean up logs
-                self.recorded_logs = HashSet::new();
-                //disable flag of recording logs
-                self.recording_logs = false;
+                let logs = self.take_recorded_logs(&state, |_| true);
+                return_data = Some(logs.to_return_data());
+            }
+            getRecordedLogs_1(getRecordedLogs_1Call { emitters, maxDepth: max_depth }) => {
+                tracing::info!("👷 Getting recorded logs of {emitters:?} up to depth {max_depth}");
+                let emitters = emitters.iter().map(|emitter| emitter.to_h160()).collect_vec();
+                // the cheatcode runs in a frame called by the caller
+                let caller_depth = state.vm_local_state.callstack.depth().saturating_sub(1);
+                let max_depth = max_depth.saturating_to::<usize>();
+                // logs of `vm.transact` have no depth, as they weren't emitted by a frame of the
+                // test
+                let logs = self.take_recorded_logs(&state, |log| {
+                    (emitters.is_empty() || emitters.contains(&log.address)) &&
+                        log.depth.map_or(true, |depth| {
+                            depth.saturating_sub(caller_depth) <= max_depth
+                        })
+                });
+                return_data = Some(logs.to_return_data());
+            }
+            getScriptWallets(getScriptWalletsCall {}) => {
+                let wallets = self.script_wallet_addresses();
+                tracing::info!("👷 Getting the script wallets {wallets:?}");
+                return_data = Some(wallets.to_return_data());
+            }
+            isZkVm(isZkVmCall {}) => {
+                tracing::info!("👷 Checking if running in the zkEVM");
+                return_data = Some(true.to_return_data());
             }
             isPersistent(isPersistentCall { account }) => {
                 tracing::info!("👷 Checking if account {:?} is persistent", account);
@@ -1570,7 +1794,15 @@ impl CheatcodeTracer {
                 let is_persistent = db.is_persistent(&revm::primitives::Address::from(
                     account.to_h160().to_fixed_bytes(),
                 ));
-                self.return_data = Some(is_persistent.to_return_data());
+                return_data = Some(is_persistent.to_return_data());
+            }
+            keyExists(keyExistsCall { json, key }) => {
+                tracing::info!("👷 Checking whether JSON key {key:?} exists");
+                return_data = Some(json_return_data(foundry_cheatcodes::key_exists(&json, &key))?);
+            }
+            label(labelCall { account, newLabel: new_label }) => {
+                tracing::info!("👷 Labeling {account:?} as {new_label:?}");
+                self.labels.insert(account.to_h160(), new_label);
             }
             load(loadCall { target, slot }) => {
                 if H160(target.0 .0) != CHEATCODE_ADDRESS {
@@ -1578,20 +1810,18 @@ impl CheatcodeTracer {
                     let key = StorageKey::new(AccountTreeId::new(target.to_h160()), H256(*slot));
                     let mut storage = storage.borrow_mut();
                     let value = storage.read_value(&key);
-                    self.return_data = Some(vec![h256_to_u256(value)]);
+                    return_data = Some(vec![h256_to_u256(value)]);
                 } else {
-                    self.return_data = Some(vec![U256::zero()]);
+                    return_data = Some(vec![U256::zero()]);
                 }
             }
             makePersistent_0(makePersistent_0Call { account }) => {
                 tracing::info!("👷 Making account {:?} persistent", account);
-                self.one_time_actions.push(FinishCycleOneTimeActions::MakePersistentAccount {
-                    account: account.to_h160(),
-                });
+                self.fork_action(ForkAction::MakePersistentAccount { account: account.to_h160() });
             }
             makePersistent_1(makePersistent_1Call { account0, account1 }) => {
                 tracing::info!("👷 Making accounts {:?} and {:?} persistent", account0, account1);
-                self.one_time_actions.push(FinishCycleOneTimeActions::MakePersistentAccounts {
+                self.fork_action(ForkAction::MakePersistentAccounts {
                     accounts: vec![account0.to_h160(), account1.to_h160()],
                 });
             }
@@ -1602,37 +1832,157 @@ impl CheatcodeTracer {
                     account1,
                     account2
                 );
-                self.one_time_actions.push(FinishCycleOneTimeActions::MakePersistentAccounts {
+                self.fork_action(ForkAction::MakePersistentAccounts {
                     accounts: vec![account0.to_h160(), account1.to_h160(), account2.to_h160()],
                 });
             }
             makePersistent_3(makePersistent_3Call { accounts }) => {
                 tracing::info!("👷 Making accounts {:?} persistent", accounts);
-                self.one_time_actions.push(FinishCycleOneTimeActions::MakePersistentAccounts {
+                self.fork_action(ForkAction::MakePersistentAccounts {
                     accounts: accounts.into_iter().map(|a| a.to_h160()).collect(),
                 });
             }
             mockCall_0(mockCall_0Call { callee, data, returnData }) => {
                 tracing::info!("👷 Mocking call to {callee:?}");
-                self.mocked_calls.insert(
+                self.mocks.mock(
                     MockCall { address: callee.to_h160(), value: None, calldata: data },
-                    returnData,
+                    MockCallReturn::Return(returnData),
                 )
             }
             mockCall_1(mockCall_1Call { callee, msgValue, data, returnData }) => {
                 tracing::info!("👷 Mocking call to {callee:?}");
-                self.mocked_calls.insert(
+                self.mocks.mock(
+                    MockCall {
+                        address: callee.to_h160(),
+                        value: Some(revm_u256_to_u256(msgValue)),
+                        calldata: data,
+                    },
+                    MockCallReturn::Return(returnData),
+                )
+            }
+            mockCallRevert_0(mockCallRevert_0Call { callee, data, revertData }) => {
+                tracing::info!("👷 Mocking call to {callee:?} to revert");
+                self.mocks.mock(
+                    MockCall { address: callee.to_h160(), value: None, calldata: data },
+                    MockCallReturn::Revert(revertData),
+                )
+            }
+            mockCallRevert_1(mockCallRevert_1Call { callee, msgValue, data, revertData }) => {
+                tracing::info!("👷 Mocking call to {callee:?} to revert");
+                self.mocks.mock(
                     MockCall {
                         address: callee.to_h160(),
                         value: Some(revm_u256_to_u256(msgValue)),
                         calldata: data,
                     },
-                    returnData,
+                    MockCallReturn::Revert(revertData),
                 )
             }
+            mockFunction(mockFunctionCall { callee, target, data }) => {
+                tracing::info!("👷 Mocking function of {callee:?} with {target:?}");
+                self.mocks.mock_function(callee.to_h160(), target.to_h160(), data);
+            }
             clearMockedCalls(clearMockedCallsCall {}) => {
                 tracing::info!("👷 Clearing all mocked calls");
-                self.mocked_calls.clear();
+                self.mocks.clear();
+            }
+            parseJson_0(parseJson_0Call { json }) => {
+                tracing::info!("👷 Parsing JSON");
+                return_data = Some(json_return_data(foundry_cheatcodes::parse_json(&json, "$"))?);
+            }
+            parseJson_1(parseJson_1Call { json, key }) => {
+                tracing::info!("👷 Parsing JSON at {key:?}");
+                return_data = Some(json_return_data(foundry_cheatcodes::parse_json(&json, &key))?);
+            }
+            parseJsonAddress(parseJsonAddressCall { json, key }) => {
+                tracing::info!("👷 Parsing JSON at {key:?} as address");
+                return_data = Some(parse_json_as(&json, &key, &DynSolType::Address)?);
+            }
+            parseJsonAddressArray(parseJsonAddressArrayCall { json, key }) => {
+                tracing::info!("👷 Parsing JSON at {key:?} as address[]");
+                return_data = Some(parse_json_as(&json, &key, &DynSolType::Address)?);
+            }
+            parseJsonBool(parseJsonBoolCall { json, key }) => {
+                tracing::info!("👷 Parsing JSON at {key:?} as bool");
+                return_data = Some(parse_json_as(&json, &key, &DynSolType::Bool)?);
+            }
+            parseJsonBoolArray(parseJsonBoolArrayCall { json, key }) => {
+                tracing::info!("👷 Parsing JSON at {key:?} as bool[]");
+                return_data = Some(parse_json_as(&json, &key, &DynSolType::Bool)?);
+            }
+            parseJsonBytes(parseJsonBytesCall { json, key }) => {
+                tracing::info!("👷 Parsing JSON at {key:?} as bytes");
+                return_data = Some(parse_json_as(&json, &key, &DynSolType::Bytes)?);
+            }
+            parseJsonBytesArray(parseJsonBytesArrayCall { json, key }) => {
+                tracing::info!("👷 Parsing JSON at {key:?} as bytes[]");
+                return_data = Some(parse_json_as(&json, &key, &DynSolType::Bytes)?);
+            }
+            parseJsonBytes32(parseJsonBytes32Call { json, key }) => {
+                tracing::info!("👷 Parsing JSON at {key:?} as bytes32");
+                return_data = Some(parse_json_as(&json, &key, &DynSolType::FixedBytes(32))?);
+            }
+            parseJsonBytes32Array(parseJsonBytes32ArrayCall { json, key }) => {
+                tracing::info!("👷 Parsing JSON at {key:?} as bytes32[]");
+                return_data = Some(parse_json_as(&json, &key, &DynSolType::FixedBytes(32))?);
+            }
+            parseJsonInt(parseJsonIntCall { json, key }) => {
+                tracing::info!("👷 Parsing JSON at {key:?} as int256");
+                return_data = Some(parse_json_as(&json, &key, &DynSolType::Int(256))?);
+            }
+            parseJsonIntArray(parseJsonIntArrayCall { json, key }) => {
+                tracing::info!("👷 Parsing JSON at {key:?} as int256[]");
+                return_data = Some(parse_json_as(&json, &key, &DynSolType::Int(256))?);
+            }
+            parseJsonKeys(parseJsonKeysCall { json, key }) => {
+                tracing::info!("👷 Parsing the keys of the JSON object at {key:?}");
+                return_data =
+                    Some(json_return_data(foundry_cheatcodes::parse_json_keys(&json, &key))?);
+            }
+            parseJsonString(parseJsonStringCall { json, key }) => {
+                tracing::info!("👷 Parsing JSON at {key:?} as string");
+                return_data = Some(parse_json_as(&json, &key, &DynSolType::String)?);
+            }
+            parseJsonStringArray(parseJsonStringArrayCall { json, key }) => {
+                tracing::info!("👷 Parsing JSON at {key:?} as string[]");
+                return_data = Some(parse_json_as(&json, &key, &DynSolType::String)?);
+            }
+            parseJsonUint(parseJsonUintCall { json, key }) => {
+                tracing::info!("👷 Parsing JSON at {key:?} as uint256");
+                return_data = Some(parse_json_as(&json, &key, &DynSolType::Uint(256))?);
+            }
+            parseJsonUintArray(parseJsonUintArrayCall { json, key }) => {
+                tracing::info!("👷 Parsing JSON at {key:?} as uint256[]");
+                return_data = Some(parse_json_as(&json, &key, &DynSolType::Uint(256))?);
+            }
+            record(recordCall {}) => {
+                tracing::info!("👷 Recording storage accesses");
+                self.accesses = Some(Default::default());
+            }
+            accesses(accessesCall { target }) => {
+                tracing::info!("👷 Getting storage accesses of {target:?}");
+                let target = target.to_h160();
+                let (reads, writes) = self
+                    .accesses
+                    .as_ref()
+                    .map(|accesses| {
+                        let slots = |slots: &HashMap<H160, Vec<H256>>| {
+                            slots
+                                .get(&target)
+                                .into_iter()
+                                .flatten()
+                                .map(|slot| FixedBytes::from(slot.to_fixed_bytes()))
+                                .collect_vec()
+                        };
+                        (slots(&accesses.reads), slots(&accesses.writes))
+                    })
+                    .unwrap_or_default();
+                let data = (reads, writes).abi_encode_params();
+                return_data = Some(data.chunks(32).map(U256::from_big_endian).collect_vec());
+            }
+            recordCalls(recordCallsCall {}) => {
+                tracing::info!("👷 Recording calls");
+                self.expectations.record_calls();
             }
             recordLogs(recordLogsCall {}) => {
                 tracing::info!("👷 Recording logs");
@@ -1644,6 +1994,18 @@ impl CheatcodeTracer {
                 self.recording_timestamp = state.vm_local_state.timestamp;
                 self.recording_logs = true;
             }
+            projectRoot(projectRootCall {}) => {
+                tracing::info!("👷 Getting project root");
+                let root = self.config.root.display().to_string();
+                return_data = Some(root.to_return_data());
+            }
+            publicKeyP256(publicKeyP256Call { privateKey: private_key }) => {
+                tracing::info!("👷 Getting secp256r1 public key for private key");
+                let Some(public_key) = crate::utils::public_key_p256(&private_key) else {
+                    return Err(CheatcodeError::InvalidPrivateKey)
+                };
+                return_data = Some(public_key.to_vec())
+            }
             readCallers(readCallersCall {}) => {
                 tracing::info!("👷 Reading callers");
 
@@ -1659,8 +2021,10 @@ impl CheatcodeTracer {
                 let mut mode = CallerMode::None;
                 let mut new_caller = current_origin;
 
-                if let Some(prank) = &self.permanent_actions.start_prank {
-                    //TODO: vm.prank -> CallerMode::Prank
+                if let Some(prank) = self.prank.as_ref().filter(|prank| !prank.applied) {
+                    mode = CallerMode::Prank;
+                    new_caller = prank.sender.into();
+                } else if let Some(prank) = &self.permanent_actions.start_prank {
                     mode = CallerMode::RecurrentPrank;
                     new_caller = prank.sender.into();
                 }
@@ -1679,32 +2043,53 @@ impl CheatcodeTracer {
                 let message_sender = h256_to_u256(new_caller);
                 let tx_origin = h256_to_u256(current_origin);
 
-                self.return_data = Some(vec![caller_mode, message_sender, tx_origin]);
+                return_data = Some(vec![caller_mode, message_sender, tx_origin]);
             }
             readFile(readFileCall { path }) => {
                 tracing::info!("👷 Reading file in path {}", path);
-                let Ok(data) = fs::read(path) else {
-                    tracing::error!("Failed to read file");
-                    return
+                let data = fs::read(&path).map_err(|err| {
+                    CheatcodeError::Revert(format!("failed to read {path:?}: {err}"))
+                })?;
+                return_data = Some(data.to_return_data());
+            }
+            rememberKey(rememberKeyCall { privateKey: private_key }) => {
+                let chain_id = self.env.get().unwrap().system_env.chain_id.as_u64();
+                let Some(wallet) =
+                    crate::utils::parse_wallet(&private_key).map(|w| w.with_chain_id(chain_id))
+                else {
+                    return Err(CheatcodeError::InvalidPrivateKey)
                 };
-                self.return_data = Some(data.to_return_data());
+
+                let address = wallet.address();
+                tracing::info!("👷 Remembering the key of {address:?}");
+                self.script_wallets.push(wallet);
+                return_data = Some(h160_to_address(address).to_return_data());
             }
             revertTo(revertToCall { snapshotId }) => {
                 tracing::info!("👷 Reverting to snapshot {}", snapshotId);
-                self.one_time_actions.push(FinishCycleOneTimeActions::RevertToSnapshot {
+                self.fork_action(ForkAction::RevertToSnapshot {
                     snapshot_id: snapshotId.to_u256(),
                 });
-                self.return_data = Some(true.to_return_data());
+                return_data = Some(true.to_return_data());
+            }
+            revertToNamed(revertToNamedCall { name }) => {
+                tracing::info!("👷 Reverting to snapshot {name:?}");
+                let Some(&snapshot_id) = self.forks.named_snapshots.get(&name) else {
+                    tracing::error!("No snapshot named {name:?}");
+                    return Ok(Some(false.to_return_data()))
+                };
+                self.fork_action(ForkAction::RevertToSnapshot { snapshot_id });
+                return_data = Some(true.to_return_data());
             }
             revokePersistent_0(revokePersistent_0Call { account }) => {
                 tracing::info!("👷 Revoking persistence for account {:?}", account);
-                self.one_time_actions.push(FinishCycleOneTimeActions::RevokePersistentAccount {
+                self.fork_action(ForkAction::RevokePersistentAccount {
                     account: account.to_h160(),
                 });
             }
             revokePersistent_1(revokePersistent_1Call { accounts }) => {
                 tracing::info!("👷 Revoking persistence for accounts {:?}", accounts);
-                self.one_time_actions.push(FinishCycleOneTimeActions::RevokePersistentAccounts {
+                self.fork_action(ForkAction::RevokePersistentAccounts {
                     accounts: accounts.into_iter().map(|a| a.to_h160()).collect(),
                 });
             }
@@ -1725,14 +2110,11 @@ impl CheatcodeTracer {
             }
             rollFork_0(rollFork_0Call { blockNumber }) => {
                 tracing::info!("👷 Rolling active fork to block number {}", blockNumber);
-                self.one_time_actions.push(FinishCycleOneTimeActions::RollFork {
-                    block_number: blockNumber,
-                    fork_id: None,
-                });
+                self.fork_action(ForkAction::RollFork { block_number: blockNumber, fork_id: None });
             }
             rollFork_2(rollFork_2Call { blockNumber, forkId }) => {
                 tracing::info!("👷 Rolling fork {} to block number {}", forkId, blockNumber);
-                self.one_time_actions.push(FinishCycleOneTimeActions::RollFork {
+                self.fork_action(ForkAction::RollFork {
                     block_number: blockNumber,
                     fork_id: Some(forkId),
                 });
@@ -1741,17 +2123,15 @@ impl CheatcodeTracer {
                 tracing::info!("👷 Getting rpc url of {}", rpcAlias);
                 let rpc_endpoints = &self.config.rpc_endpoints;
                 let rpc_url = match rpc_endpoints.get(&rpcAlias) {
-                    Some(Ok(url)) => url.clone(),
+                    Some(Ok(url)) => self.config.rpc_url_redaction.redact(&rpcAlias, url),
                     _ => {
-                        //this should revert but we don't have reverts yet
-                        panic!(
-                            "Failed to resolve env var `{}`: environment variable not found",
-                            rpcAlias
-                        )
+                        return Err(CheatcodeError::Revert(format!(
+                            "failed to resolve rpc url `{rpcAlias}`: environment variable not found"
+                        )))
                     }
                 };
 
-                self.return_data = Some(rpc_url.to_return_data());
+                return_data = Some(rpc_url.to_return_data());
             }
             rpcUrls(rpcUrlsCall {}) => {
                 tracing::info!("👷 Getting rpc urls");
@@ -1762,12 +2142,12 @@ impl CheatcodeTracer {
                         key: alias.clone(),
                         url: url
                             .as_ref()
-                            .map(|value| value.clone())
+                            .map(|value| self.config.rpc_url_redaction.redact(alias, value))
                             .unwrap_or_else(|_| alias.clone()),
                     })
                     .collect_vec();
 
-                self.return_data = Some(urls.to_return_data());
+                return_data = Some(urls.to_return_data());
             }
             serializeAddress_0(serializeAddress_0Call {
                 objectKey: object_key,
@@ -1780,15 +2160,25 @@ impl CheatcodeTracer {
                     value_key,
                     object_key
                 );
-                let json_value = serde_json::json!({
-                    value_key: value
-                });
-
-                //write to serialized_objects
-                self.serialized_objects.insert(object_key.clone(), json_value.to_string());
-
-                let address_with_checksum = value.to_checksum(None);
-                self.return_data = Some(address_with_checksum.to_return_data());
+                let json =
+                    self.serialize_json(&object_key, Some(&value_key), &value.to_checksum(None))?;
+                return_data = Some(json.to_return_data());
+            }
+            serializeAddress_1(serializeAddress_1Call {
+                objectKey: object_key,
+                valueKey: value_key,
+                values,
+            }) => {
+                tracing::info!(
+                    "👷 Serializing address array with key {value_key:?} to object {object_key:?}"
+                );
+                let values = values.iter().map(|value| value.to_checksum(None));
+                let json = self.serialize_json(
+                    &object_key,
+                    Some(&value_key),
+                    &foundry_cheatcodes::array_str(values, true),
+                )?;
+                return_data = Some(json.to_return_data());
             }
             serializeBool_0(serializeBool_0Call {
                 objectKey: object_key,
@@ -1801,14 +2191,152 @@ impl CheatcodeTracer {
                     value_key,
                     object_key
                 );
-                let json_value = serde_json::json!({
-                    value_key: value
-                });
-
-                self.serialized_objects.insert(object_key.clone(), json_value.to_string());
-
-                let bool_value = value.to_string();
-                self.return_data = Some(bool_value.to_return_data());
+                let json =
+                    self.serialize_json(&object_key, Some(&value_key), &value.to_string())?;
+                return_data = Some(json.to_return_data());
+            }
+            serializeBool_1(serializeBool_1Call {
+                objectKey: object_key,
+                valueKey: value_key,
+                values,
+            }) => {
+                tracing::info!(
+                    "👷 Serializing bool array with key {value_key:?} to object {object_key:?}"
+                );
+                let json = self.serialize_json(
+                    &object_key,
+                    Some(&value_key),
+                    &foundry_cheatcodes::array_str(values, false),
+                )?;
+                return_data = Some(json.to_return_data());
+            }
+            serializeBytes_0(serializeBytes_0Call {
+                objectKey: object_key,
+                valueKey: value_key,
+                value,
+            }) => {
+                tracing::info!(
+                    "👷 Serializing bytes {:?} with key {:?} to object {:?}",
+                    value,
+                    value_key,
+                    object_key
+                );
+                let json = self.serialize_json(
+                    &object_key,
+                    Some(&value_key),
+                    &hex::encode_prefixed(value),
+                )?;
+                return_data = Some(json.to_return_data());
+            }
+            serializeBytes_1(serializeBytes_1Call {
+                objectKey: object_key,
+                valueKey: value_key,
+                values,
+            }) => {
+                tracing::info!(
+                    "👷 Serializing bytes array with key {value_key:?} to object {object_key:?}"
+                );
+                let values = values.iter().map(hex::encode_prefixed);
+                let json = self.serialize_json(
+                    &object_key,
+                    Some(&value_key),
+                    &foundry_cheatcodes::array_str(values, true),
+                )?;
+                return_data = Some(json.to_return_data());
+            }
+            serializeBytes32_0(serializeBytes32_0Call {
+                objectKey: object_key,
+                valueKey: value_key,
+                value,
+            }) => {
+                tracing::info!(
+                    "👷 Serializing bytes32 {:?} with key {:?} to object {:?}",
+                    value,
+                    value_key,
+                    object_key
+                );
+                let json =
+                    self.serialize_json(&object_key, Some(&value_key), &value.to_string())?;
+                return_data = Some(json.to_return_data());
+            }
+            serializeBytes32_1(serializeBytes32_1Call {
+                objectKey: object_key,
+                valueKey: value_key,
+                values,
+            }) => {
+                tracing::info!(
+                    "👷 Serializing bytes32 array with key {value_key:?} to object {object_key:?}"
+                );
+                let json = self.serialize_json(
+                    &object_key,
+                    Some(&value_key),
+                    &foundry_cheatcodes::array_str(values, true),
+                )?;
+                return_data = Some(json.to_return_data());
+            }
+            serializeInt_0(serializeInt_0Call {
+                objectKey: object_key,
+                valueKey: value_key,
+                value,
+            }) => {
+                tracing::info!(
+                    "👷 Serializing int256 {:?} with key {:?} to object {:?}",
+                    value,
+                    value_key,
+                    object_key
+                );
+                let json =
+                    self.serialize_json(&object_key, Some(&value_key), &value.to_string())?;
+                return_data = Some(json.to_return_data());
+            }
+            serializeInt_1(serializeInt_1Call {
+                objectKey: object_key,
+                valueKey: value_key,
+                values,
+            }) => {
+                tracing::info!(
+                    "👷 Serializing int256 array with key {value_key:?} to object {object_key:?}"
+                );
+                let json = self.serialize_json(
+                    &object_key,
+                    Some(&value_key),
+                    &foundry_cheatcodes::array_str(values, false),
+                )?;
+                return_data = Some(json.to_return_data());
+            }
+            serializeJson(serializeJsonCall { objectKey: object_key, value }) => {
+                tracing::info!("👷 Serializing JSON {value:?} to object {object_key:?}");
+                let json = self.serialize_json(&object_key, None, &value)?;
+                return_data = Some(json.to_return_data());
+            }
+            serializeString_0(serializeString_0Call {
+                objectKey: object_key,
+                valueKey: value_key,
+                value,
+            }) => {
+                tracing::info!(
+                    "👷 Serializing string {:?} with key {:?} to object {:?}",
+                    value,
+                    value_key,
+                    object_key
+                );
+                let json = self.serialize_json(&object_key, Some(&value_key), &value)?;
+                return_data = Some(json.to_return_data());
+            }
+            serializeString_1(serializeString_1Call {
+                objectKey: object_key,
+                valueKey: value_key,
+                values,
+            }) => {
+                tracing::info!(
+                    "👷 Serializing string array with key {value_key:?} to object {object_key:?}"
+                );
+                let json = self.serialize_json(
+                    &object_key,
+                    Some(&value_key),
+                    &foundry_cheatcodes::array_str(values, true),
+                )?;
+                return_data = Some(json.to_return_data());
             }
             serializeUint_0(serializeUint_0Call {
                 objectKey: object_key,
@@ -1821,17 +2349,42 @@ impl CheatcodeTracer {
                     value_key,
                     object_key
                 );
-                let json_value = serde_json::json!({
-                    value_key: value
-                });
-
-                self.serialized_objects.insert(object_key.clone(), json_value.to_string());
-
-                let uint_value = value.to_string();
-                self.return_data = Some(uint_value.to_return_data());
+                let json =
+                    self.serialize_json(&object_key, Some(&value_key), &value.to_string())?;
+                return_data = Some(json.to_return_data());
+            }
+            serializeUint_1(serializeUint_1Call {
+                objectKey: object_key,
+                valueKey: value_key,
+                values,
+            }) => {
+                tracing::info!(
+                    "👷 Serializing uint256 array with key {value_key:?} to object {object_key:?}"
+                );
+                let json = self.serialize_json(
+                    &object_key,
+                    Some(&value_key),
+                    &foundry_cheatcodes::array_str(values, false),
+                )?;
+                return_data = Some(json.to_return_data());
             }
             setEnv(setEnvCall { name, value }) => {
                 tracing::info!("👷 Setting env variable {name:?} to {value:?}");
+                // `set_var` panics on these
+                let invalid = if name.is_empty() {
+                    Some("environment variable key can't be empty")
+                } else if name.contains('=') {
+                    Some("environment variable key can't contain equal sign `=`")
+                } else if name.contains('\0') {
+                    Some("environment variable key can't contain NUL character `\\0`")
+                } else if value.contains('\0') {
+                    Some("environment variable value can't contain NUL character `\\0`")
+                } else {
+                    None
+                };
+                if let Some(reason) = invalid {
+                    return Err(CheatcodeError::Revert(reason.to_string()))
+                }
                 std::env::set_var(name, value);
             }
             setNonce(setNonceCall { account, newNonce: new_nonce }) => {
@@ -1849,56 +2402,127 @@ impl CheatcodeTracer {
                         new_nonce
                     );
                 } else {
-                    tracing::error!("👷 Setting nonces failed")
+                    return Err(CheatcodeError::Revert(format!(
+                        "setting nonces of {account} failed"
+                    )))
                 }
             }
+            setTxOrigin(setTxOriginCall { newOrigin: new_origin }) => {
+                tracing::info!("👷 Setting tx origin to {new_origin:?}");
+                let key = StorageKey::new(
+                    AccountTreeId::new(zksync_types::SYSTEM_CONTEXT_ADDRESS),
+                    zksync_types::SYSTEM_CONTEXT_TX_ORIGIN_POSITION,
+                );
+                self.write_storage(key, new_origin.to_h160().into(), &mut storage.borrow_mut());
+            }
             sign_0(sign_0Call { privateKey: private_key, digest }) => {
                 tracing::info!("👷 Signing digest with private key");
-                let Ok(signature) = zksync_types::PackedEthSignature::sign(
-                    &private_key.to_h256(),
-                    digest.as_slice(),
-                ) else {
-                    tracing::error!("Failed to sign digest with private key");
-                    return
+                let Some(signature) = crate::utils::sign(&private_key, &digest.0) else {
+                    return Err(CheatcodeError::InvalidPrivateKey)
                 };
-
-                let r = signature.r();
-                let s = signature.s();
-                // Ethereum signed message produced by most clients contains v where v = 27 +
-                // recovery_id(0,1,2,3), but for some clients v = recovery_id(0,1,2,3). The library
-                // that we use for signature verification (written for bitcoin)
-                // expects v = recovery_id and to able to recover the address from Solidity it
-                // expects v = 27 + recovery_id.
-                let v = signature.v() + 27;
-
-                self.return_data = Some(vec![v.into(), r.into(), s.into()])
+                return_data = Some(signature.to_vec())
+            }
+            sign_1(sign_1Call { wallet, digest }) => {
+                tracing::info!("👷 Signing digest with wallet {:?}", wallet.addr);
+                let Some(signature) = crate::utils::sign(&wallet.privateKey, &digest.0) else {
+                    return Err(CheatcodeError::InvalidPrivateKey)
+                };
+                return_data = Some(signature.to_vec())
+            }
+            signP256(signP256Call { privateKey: private_key, digest }) => {
+                tracing::info!("👷 Signing digest with secp256r1 private key");
+                let Some(signature) = crate::utils::sign_p256(&private_key, &digest.0) else {
+                    return Err(CheatcodeError::InvalidPrivateKey)
+                };
+                return_data = Some(signature.to_vec())
+            }
+            skip(skipCall { skipTest: skip_test }) => {
+                if !skip_test {
+                    return Ok(None)
+                }
+                // skipping from a nested call would only revert that call, failing the test
+                let depth = state.vm_local_state.callstack.depth();
+                let FoundryTestState::Running { call_depth } = self.test_status else {
+                    return Err(CheatcodeError::Revert(
+                        "`skip` can only be used in tests".to_string(),
+                    ))
+                };
+                if depth != call_depth + 1 {
+                    return Err(CheatcodeError::Revert(
+                        "`skip` can only be used at test level".to_string(),
+                    ))
+                }
+                if self.next_return_action.is_some() {
+                    return Err(CheatcodeError::Revert(
+                        "`skip` cannot be used while an `expectRevert` is pending".to_string(),
+                    ))
+                }
+                tracing::info!("👷 Skipping the test");
+                self.add_revert_test(MAGIC_SKIP.to_vec(), depth)?;
+            }
+            broadcastWallet(broadcastWalletCall { senderIndex }) => {
+                let origin = self.script_wallet(senderIndex)?;
+                tracing::info!(
+                    "👷 Broadcasting the next call with script wallet #{senderIndex}: {origin}"
+                );
+                self.start_broadcast(&storage, &state, Some(origin), true)
             }
             snapshot(snapshotCall {}) => {
                 tracing::info!("👷 Creating snapshot");
-                self.one_time_actions.push(FinishCycleOneTimeActions::Snapshot);
+                self.fork_action(ForkAction::Snapshot { name: None });
+            }
+            snapshotNamed(snapshotNamedCall { name }) => {
+                tracing::info!("👷 Creating snapshot {name:?}");
+                self.fork_action(ForkAction::Snapshot { name: Some(name) });
             }
             startBroadcast_0(startBroadcast_0Call {}) => {
                 tracing::info!("👷 Starting broadcast with default origin");
 
-                self.start_broadcast(&storage, &state, None)
+                self.start_broadcast(&storage, &state, None, false)
             }
             startBroadcast_1(startBroadcast_1Call { signer }) => {
                 let origin = signer.to_h160();
                 tracing::info!("👷 Starting broadcast with given origin: {origin}");
-                self.start_broadcast(&storage, &state, Some(origin))
+                self.start_broadcast(&storage, &state, Some(origin), false)
             }
             startBroadcast_2(startBroadcast_2Call { privateKey }) => {
                 let chain_id = self.env.get().unwrap().system_env.chain_id.as_u64();
                 let Some(wallet) =
                     crate::utils::parse_wallet(&privateKey).map(|w| w.with_chain_id(chain_id))
                 else {
-                    tracing::error!(cheatcode = "startBroadcast", "unable to parse private key");
-                    return
+                    return Err(CheatcodeError::InvalidPrivateKey)
                 };
 
                 let origin = wallet.address();
                 tracing::info!("👷 Starting broadcast with origin from private key: {origin}");
-                self.start_broadcast(&storage, &state, Some(origin))
+                self.start_broadcast(&storage, &state, Some(origin), false);
+                if self.broadcast.is_active() {
+                    self.script_wallets.push(wallet);
+                }
+            }
+            startBroadcastWallet(startBroadcastWalletCall { senderIndex }) => {
+                let origin = self.script_wallet(senderIndex)?;
+                tracing::info!("👷 Starting broadcast with script wallet #{senderIndex}: {origin}");
+                self.start_broadcast(&storage, &state, Some(origin), false)
+            }
+            prank_0(prank_0Call { msgSender: msg_sender }) => {
+                tracing::info!("👷 Pranking the next call to {msg_sender:?}");
+                self.prank(&state, &storage, msg_sender.to_h160(), None)?;
+            }
+            prank_1(prank_1Call { msgSender: msg_sender, txOrigin: tx_origin }) => {
+                tracing::info!(
+                    "👷 Pranking the next call to {msg_sender:?} with origin {tx_origin:?}"
+                );
+                self.prank(&state, &storage, msg_sender.to_h160(), Some(tx_origin.to_h160()))?;
+            }
+            prevrandao(prevrandaoCall { newPrevrandao: new_prevrandao }) => {
+                tracing::info!("👷 Setting block prevrandao to {new_prevrandao}");
+                // `block.prevrandao` reads the difficulty of the SystemContext in the zkEVM
+                let key = StorageKey::new(
+                    AccountTreeId::new(zksync_types::SYSTEM_CONTEXT_ADDRESS),
+                    zksync_types::SYSTEM_CONTEXT_DIFFICULTY_POSITION,
+                );
+                self.write_storage(key, H256(*new_prevrandao), &mut storage.borrow_mut());
             }
             startPrank_0(startPrank_0Call { msgSender: msg_sender }) => {
                 tracing::info!("👷 Starting prank to {msg_sender:?}");
@@ -1927,46 +2551,72 @@ impl CheatcodeTracer {
                 let key = StorageKey::new(AccountTreeId::new(target.to_h160()), H256(*slot));
                 self.write_storage(key, H256(*value), &mut storage);
             }
+            storeBatch(storeBatchCall { targets, slots, values }) => {
+                tracing::info!("👷 Setting {} storage slots", targets.len());
+                if targets.len() != slots.len() || slots.len() != values.len() {
+                    return Err(CheatcodeError::Revert(
+                        "targets, slots and values must have the same length".to_string(),
+                    ))
+                }
+
+                // Only the last value of each slot is queued, so that every queued write reads the
+                // value the slot had before the batch.
+                let writes = targets
+                    .iter()
+                    .zip(slots)
+                    .zip(values)
+                    .map(|((target, slot), value)| {
+                        (
+                            StorageKey::new(AccountTreeId::new(target.to_h160()), H256(**slot)),
+                            H256(**value),
+                        )
+                    })
+                    .collect::<HashMap<_, _>>();
+                let mut storage = storage.borrow_mut();
+                for (key, value) in writes {
+                    self.write_storage(key, value, &mut storage);
+                }
+            }
             toString_0(toString_0Call { value }) => {
                 tracing::info!("Converting address into string");
                 let address_with_checksum = value.to_checksum(None);
-                self.return_data = Some(address_with_checksum.to_return_data());
+                return_data = Some(address_with_checksum.to_return_data());
             }
             toString_1(toString_1Call { value }) => {
                 tracing::info!("Converting bytes into string");
                 let bytes_value = hex::encode_prefixed(value);
-                self.return_data = Some(bytes_value.to_return_data());
+                return_data = Some(bytes_value.to_return_data());
             }
             toString_2(toString_2Call { value }) => {
                 tracing::info!("Converting bytes32 into string");
                 let bytes_value = hex::encode_prefixed(value);
-                self.return_data = Some(bytes_value.to_return_data());
+                return_data = Some(bytes_value.to_return_data());
             }
             toString_3(toString_3Call { value }) => {
                 tracing::info!("Converting bool into string");
                 let bool_value = value.to_string();
-                self.return_data = Some(bool_value.to_return_data());
+                return_data = Some(bool_value.to_return_data());
             }
             toString_4(toString_4Call { value }) => {
                 tracing::info!("Converting uint256 into string");
                 let uint_value = value.to_string();
-                self.return_data = Some(uint_value.to_return_data());
+                return_data = Some(uint_value.to_return_data());
             }
             toString_5(toString_5Call { value }) => {
                 tracing::info!("Converting int256 into string");
                 let int_value = value.to_string();
-                self.return_data = Some(int_value.to_return_data());
+                return_data = Some(int_value.to_return_data());
             }
             transact_0(transact_0Call { txHash }) => {
                 tracing::info!("👷 Transacting current fork with: {txHash:x?}");
-                self.one_time_actions.push(FinishCycleOneTimeActions::Transact {
+                self.fork_action(ForkAction::Transact {
                     fork_id: None,
                     tx_hash: H256::from(txHash.0),
                 });
             }
             transact_1(transact_1Call { forkId, txHash }) => {
                 tracing::info!("👷 Transacting fork {forkId} with: {txHash:x?}");
-                self.one_time_actions.push(FinishCycleOneTimeActions::Transact {
+                self.fork_action(ForkAction::Transact {
                     fork_id: Some(forkId.to_u256()),
                     tx_hash: H256::from(txHash.0),
                 });
@@ -1974,23 +2624,18 @@ impl CheatcodeTracer {
             tryFfi(tryFfiCall { commandInput: command_input }) => {
                 tracing::info!("👷 Running try ffi: {command_input:?}");
                 let Some(first_arg) = command_input.get(0) else {
-                    tracing::error!("Failed to run ffi: no args");
-                    return
+                    return Err(CheatcodeError::Ffi("no args".to_string()))
                 };
-                let Ok(output) = Command::new(first_arg)
+                let output = Command::new(first_arg)
                     .args(&command_input[1..])
                     .current_dir(&self.config.root)
                     .output()
-                else {
-                    tracing::error!("Failed to run ffi");
-                    return
-                };
+                    .map_err(|err| CheatcodeError::Ffi(err.to_string()))?;
 
                 // The stdout might be encoded on valid hex, or it might just be a string,
                 // so we need to determine which it is to avoid improperly encoding later.
                 let Ok(trimmed_stdout) = String::from_utf8(output.stdout) else {
-                    tracing::error!("Failed to parse ffi output");
-                    return
+                    return Err(CheatcodeError::Ffi("output is not valid UTF-8".to_string()))
                 };
                 let trimmed_stdout = trimmed_stdout.trim();
                 let encoded_stdout =
@@ -2005,7 +2650,30 @@ impl CheatcodeTracer {
                     stdout: encoded_stdout,
                     stderr: output.stderr,
                 };
-                self.return_data = Some(ffi_result.to_return_data());
+                return_data = Some(ffi_result.to_return_data());
+            }
+            txGasPrice(txGasPriceCall { newGasPrice: new_gas_price }) => {
+                tracing::info!("👷 Setting tx gas price to {new_gas_price}");
+
+                let key = StorageKey::new(
+                    AccountTreeId::new(zksync_types::SYSTEM_CONTEXT_ADDRESS),
+                    zksync_types::SYSTEM_CONTEXT_GAS_PRICE_POSITION,
+                );
+                self.write_storage(
+                    key,
+                    u256_to_h256(new_gas_price.to_u256()),
+                    &mut storage.borrow_mut(),
+                );
+                self.broadcast.fee_overrides.gas_price = Some(new_gas_price.to_u256());
+            }
+            txOrigin(txOriginCall {}) => {
+                tracing::info!("👷 Getting tx origin");
+                let key = StorageKey::new(
+                    AccountTreeId::new(zksync_types::SYSTEM_CONTEXT_ADDRESS),
+                    zksync_types::SYSTEM_CONTEXT_TX_ORIGIN_POSITION,
+                );
+                let origin = storage.borrow_mut().read_value(&key);
+                return_data = Some(vec![h256_to_u256(origin)]);
             }
             warp(warpCall { newTimestamp: new_timestamp }) => {
                 tracing::info!("👷 Setting block timestamp {}", new_timestamp);
@@ -2024,14 +2692,17 @@ impl CheatcodeTracer {
             }
             createSelectFork_0(createSelectFork_0Call { urlOrAlias }) => {
                 tracing::info!("👷 Creating and selecting fork {}", urlOrAlias,);
+                if let Err(err) = self.forks.ensure_zksync_fork(&self.config, &urlOrAlias) {
+                    return Err(CheatcodeError::Revert(format!("{err:#}")))
+                }
 
-                if self.permanent_actions.broadcast.is_none() {
-                    self.one_time_actions.push(FinishCycleOneTimeActions::CreateSelectFork {
+                if !self.broadcast.is_active() {
+                    self.fork_action(ForkAction::CreateSelectFork {
                         url_or_alias: urlOrAlias,
                         block_number: None,
                     });
                 } else {
-                    tracing::error!("cannot select fork during a broadcast")
+                    return Err(CheatcodeError::ForkDuringBroadcast)
                 }
             }
             createSelectFork_1(createSelectFork_1Call { urlOrAlias, blockNumber }) => {
@@ -2041,20 +2712,26 @@ impl CheatcodeTracer {
                     urlOrAlias,
                     block_number
                 );
+                if let Err(err) = self.forks.ensure_zksync_fork(&self.config, &urlOrAlias) {
+                    return Err(CheatcodeError::Revert(format!("{err:#}")))
+                }
 
-                if self.permanent_actions.broadcast.is_none() {
-                    self.one_time_actions.push(FinishCycleOneTimeActions::CreateSelectFork {
+                if !self.broadcast.is_active() {
+                    self.fork_action(ForkAction::CreateSelectFork {
                         url_or_alias: urlOrAlias,
                         block_number: Some(block_number),
                     });
                 } else {
-                    tracing::error!("cannot select fork during a broadcast")
+                    return Err(CheatcodeError::ForkDuringBroadcast)
                 }
             }
             createFork_0(createFork_0Call { urlOrAlias }) => {
                 tracing::info!("👷 Creating fork {}", urlOrAlias,);
+                if let Err(err) = self.forks.ensure_zksync_fork(&self.config, &urlOrAlias) {
+                    return Err(CheatcodeError::Revert(format!("{err:#}")))
+                }
 
-                self.one_time_actions.push(FinishCycleOneTimeActions::CreateFork {
+                self.fork_action(ForkAction::CreateFork {
                     url_or_alias: urlOrAlias,
                     block_number: None,
                 });
@@ -2062,7 +2739,10 @@ impl CheatcodeTracer {
             createFork_1(createFork_1Call { urlOrAlias, blockNumber }) => {
                 let block_number = blockNumber.to_u256().as_u64();
                 tracing::info!("👷 Creating fork {} for block number {}", urlOrAlias, block_number);
-                self.one_time_actions.push(FinishCycleOneTimeActions::CreateFork {
+                if let Err(err) = self.forks.ensure_zksync_fork(&self.config, &urlOrAlias) {
+                    return Err(CheatcodeError::Revert(format!("{err:#}")))
+                }
+                self.fork_action(ForkAction::CreateFork {
                     url_or_alias: urlOrAlias,
                     block_number: Some(block_number),
                 });
@@ -2070,60 +2750,208 @@ impl CheatcodeTracer {
             selectFork(selectForkCall { forkId }) => {
                 tracing::info!("👷 Selecting fork {}", forkId);
 
-                if self.permanent_actions.broadcast.is_none() {
-                    self.one_time_actions
-                        .push(FinishCycleOneTimeActions::SelectFork { fork_id: forkId.to_u256() });
+                if !self.broadcast.is_active() {
+                    self.fork_action(ForkAction::SelectFork { fork_id: forkId.to_u256() });
                 } else {
-                    tracing::error!("cannot select fork during broadcast")
+                    return Err(CheatcodeError::ForkDuringBroadcast)
                 }
             }
+            withStateOverrides_0(withStateOverrides_0Call { overrides }) => {
+                tracing::info!("👷 Overriding {} storage slots for the next call", overrides.len());
+                self.set_state_overrides(&[], &[], &overrides, &state, &storage)?;
+            }
+            withStateOverrides_1(withStateOverrides_1Call { balances, codes, slots }) => {
+                tracing::info!(
+                    "👷 Overriding {} balances, {} codes and {} storage slots for the next call",
+                    balances.len(),
+                    codes.len(),
+                    slots.len()
+                );
+                self.set_state_overrides(&balances, &codes, &slots, &state, &storage)?;
+            }
             writeFile(writeFileCall { path, data }) => {
                 tracing::info!("👷 Writing data to file in path {}", path);
-                if fs::write(path, data).is_err() {
-                    tracing::error!("Failed to write file");
+                if let Err(err) = fs::write(&path, data) {
+                    return Err(CheatcodeError::Revert(format!(
+                        "failed to write to {path:?}: {err}"
+                    )));
                 }
             }
             writeJson_0(writeJson_0Call { json, path }) => {
                 tracing::info!("👷 Writing json data to file in path {}", path);
-                let Ok(json) = serde_json::from_str::<serde_json::Value>(&json) else {
-                    tracing::error!("Failed to parse json");
-                    return
+                let json = match serde_json::from_str::<serde_json::Value>(&json) {
+                    Ok(json) => json,
+                    Err(err) => {
+                        return Err(CheatcodeError::Revert(format!("failed parsing JSON: {err}")))
+                    }
                 };
-                let Ok(formatted_json) = serde_json::to_string_pretty(&json) else {
-                    tracing::error!("Failed to format json");
-                    return
+                let formatted_json = match serde_json::to_string_pretty(&json) {
+                    Ok(formatted_json) => formatted_json,
+                    Err(err) => {
+                        return Err(CheatcodeError::Revert(format!("failed formatting JSON: {err}")))
+                    }
                 };
-                if fs::write(path, formatted_json).is_err() {
-                    tracing::error!("Failed to write file");
+                if let Err(err) = fs::write(&path, formatted_json) {
+                    return Err(CheatcodeError::Revert(format!(
+                        "failed to write to {path:?}: {err}"
+                    )));
                 }
             }
             writeJson_1(writeJson_1Call { json, path, valueKey: value_key }) => {
                 tracing::info!("👷 Writing json data to file in path {path} with key {value_key}");
-                let Ok(file) = fs::read_to_string(&path) else {
-                    tracing::error!("Failed to read file");
-                    return
+                let file = match fs::read_to_string(&path) {
+                    Ok(file) => file,
+                    Err(err) => {
+                        return Err(CheatcodeError::Revert(format!(
+                            "failed to read {path:?}: {err}"
+                        )))
+                    }
                 };
-                let Ok(mut file_json) = serde_json::from_str::<serde_json::Value>(&file) else {
-                    tracing::error!("Failed to parse json");
-                    return
+                let mut file_json = match serde_json::from_str::<serde_json::Value>(&file) {
+                    Ok(file_json) => file_json,
+                    Err(err) => {
+                        return Err(CheatcodeError::Revert(format!(
+                            "failed parsing JSON in {path:?}: {err}"
+                        )))
+                    }
                 };
-                let Ok(json) = serde_json::from_str::<serde_json::Value>(&json) else {
-                    tracing::error!("Failed to parse json");
-                    return
+                let json = match serde_json::from_str::<serde_json::Value>(&json) {
+                    Ok(json) => json,
+                    Err(err) => {
+                        return Err(CheatcodeError::Revert(format!("failed parsing JSON: {err}")))
+                    }
                 };
-                file_json[value_key] = json;
-                let Ok(formatted_json) = serde_json::to_string_pretty(&file_json) else {
-                    tracing::error!("Failed to format json");
-                    return
+                if let Err(err) = set_json_key(&mut file_json, &value_key, json) {
+                    return Err(CheatcodeError::Revert(err.to_string()))
+                }
+                let formatted_json = match serde_json::to_string_pretty(&file_json) {
+                    Ok(formatted_json) => formatted_json,
+                    Err(err) => {
+                        return Err(CheatcodeError::Revert(format!("failed formatting JSON: {err}")))
+                    }
+                };
+                if let Err(err) = fs::write(&path, formatted_json) {
+                    return Err(CheatcodeError::Revert(format!(
+                        "failed to write to {path:?}: {err}"
+                    )));
+                }
+            }
+            zkAssertCodeMatchesArtifact(zkAssertCodeMatchesArtifactCall {
+                account,
+                artifactPath: artifact_path,
+            }) => {
+                tracing::info!("👷 Comparing code of {account} with artifact {artifact_path}");
+                let deployed = storage.borrow_mut().read_value(&get_code_key(&account.to_h160()));
+                let expected = match self.read_zk_artifact(&artifact_path) {
+                    Ok(packed) => hash_bytecode(&packed.bytecode()),
+                    Err(err) => return Err(CheatcodeError::Revert(format!("{err:#}"))),
                 };
-                if fs::write(path, formatted_json).is_err() {
-                    tracing::error!("Failed to write file");
+                if deployed != expected {
+                    return Err(CheatcodeError::Revert(format!(
+                            "code of {account} does not match artifact {artifact_path}\n  deployed: {}\n  artifact: {}",
+                            describe_code_hash(deployed),
+                            describe_code_hash(expected),
+                        )));
                 }
             }
+            zkFailNextStorageWrite(zkFailNextStorageWriteCall { target, slot }) => {
+                tracing::info!("👷 Failing the next write to slot {slot} of {target}");
+                self.fail_next_storage_write =
+                    Some(StorageKey::new(AccountTreeId::new(target.to_h160()), H256(*slot)));
+            }
+            zkFinalizeWithdrawal(zkFinalizeWithdrawalCall {
+                urlOrAlias: url_or_alias,
+                withdrawalHash: withdrawal_hash,
+            }) => {
+                tracing::info!("👷 Building the finalization of withdrawal {withdrawal_hash}");
+                let url = self
+                    .config
+                    .rpc_url(&url_or_alias)
+                    .map_err(|err| CheatcodeError::Revert(err.to_string()))?;
+                let (target, data) = finalize_withdrawal_call(&url, withdrawal_hash, 0)
+                    .map_err(|err| CheatcodeError::Revert(format!("{err:#}")))?;
+                let data = (target, Bytes::from(data)).abi_encode_params();
+                return_data = Some(data.chunks(32).map(U256::from_big_endian).collect_vec());
+            }
+            zkSetEmitOrdering(zkSetEmitOrderingCall { ordering }) => {
+                tracing::info!("👷 Setting expected emit ordering to {ordering}");
+                self.expectations.emit_config.ordering =
+                    EmitOrdering::try_from(ordering).map_err(CheatcodeError::Revert)?;
+            }
+            zkSetGasPerPubdataLimit(zkSetGasPerPubdataLimitCall {
+                gasPerPubdataLimit: gas_per_pubdata_limit,
+            }) => {
+                tracing::info!(
+                    "👷 Setting broadcast gas per pubdata limit to {gas_per_pubdata_limit}"
+                );
+                self.broadcast.fee_overrides.gas_per_pubdata_limit =
+                    Some(gas_per_pubdata_limit.to_u256());
+            }
+            zkSetMaxFeePerGas(zkSetMaxFeePerGasCall { maxFeePerGas: max_fee_per_gas }) => {
+                tracing::info!("👷 Setting broadcast max fee per gas to {max_fee_per_gas}");
+                self.broadcast.fee_overrides.max_fee_per_gas = Some(max_fee_per_gas.to_u256());
+            }
+            zkSetNextCallGas(zkSetNextCallGasCall { gas }) => {
+                tracing::info!("👷 Capping the ergs of the next call to {gas}");
+                self.next_call_ergs = Some(gas.saturating_to());
+            }
+            zkProtocolVersion(zkProtocolVersionCall {}) => {
+                tracing::info!("👷 Getting the zkEVM protocol version");
+                let protocol_version = protocol_version();
+                let vm_version = VmVersion::from(protocol_version);
+                let data = (protocol_version as u16, format!("{vm_version:?}")).abi_encode_params();
+                return_data = Some(data.chunks(32).map(U256::from_big_endian).collect_vec());
+            }
+            zkSetTracing(zkSetTracingCall { level }) => {
+                tracing::info!("👷 Setting zk tracing level to {level}");
+                self.tracing_level = ZkTracingLevel::from(level);
+            }
             code => {
-                tracing::error!("👷 Unrecognized cheatcode {:?}", code);
+                debug_assert!(
+                    !is_dispatched(&code),
+                    "{code:?} is listed as dispatched without an arm"
+                );
+                return Err(CheatcodeError::not_dispatched(code.selector()))
             }
         };
+
+        Ok(return_data)
+    }
+
+    /// Returns the `Wallet` struct of the private key, labeling its address if a label is given.
+    fn create_wallet(
+        &self,
+        private_key: alloy_primitives::U256,
+        label: Option<String>,
+    ) -> Result<Vec<U256>, CheatcodeError> {
+        let Some((address, [x, y])) = crate::utils::wallet_of(&private_key) else {
+            return Err(CheatcodeError::InvalidPrivateKey)
+        };
+        if let Some(label) = label {
+            self.labels.insert(address, label);
+        }
+        Ok(vec![h256_to_u256(address.into()), x, y, private_key.to_u256()])
+    }
+
+    /// Sets the key of the serialized object to the value, which is nested as JSON if it parses as
+    /// such, or replaces the whole object with the value if no key is given. Returns the object.
+    fn serialize_json(
+        &mut self,
+        object_key: &str,
+        value_key: Option<&str>,
+        value: &str,
+    ) -> Result<String, CheatcodeError> {
+        let object = self.serialized_objects.entry(object_key.to_string()).or_default();
+        if let Some(value_key) = value_key {
+            let value = serde_json::from_str(value)
+                .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+            object.insert(value_key.to_string(), value);
+        } else {
+            *object = serde_json::from_str(value).map_err(|err| {
+                CheatcodeError::Revert(format!("failed to parse JSON object: {err}"))
+            })?;
+        }
+        Ok(serde_json::Value::Object(object.clone()).to_string())
     }
 
     fn store_factory_dep(&mut self, hash: U256, bytecode: Vec<U256>) {
@@ -2143,6 +2971,121 @@ impl CheatcodeTracer {
         });
     }
 
+    /// Applies the overrides of `withStateOverrides` on top of the reads of `storage` until the
+    /// next far call returns. The later overrides of the same account or slot take precedence.
+    fn set_state_overrides<S: DatabaseExt + Send>(
+        &mut self,
+        balances: &[Vm::BalanceOverride],
+        codes: &[Vm::CodeOverride],
+        slots: &[Vm::StorageOverride],
+        state: &VmLocalStateData<'_>,
+        storage: &StoragePtr<EraDb<S>>,
+    ) -> Result<(), CheatcodeError> {
+        if self.state_overrides.is_some() {
+            return Err(CheatcodeError::Revert(
+                "state overrides are already set for the next call".to_string(),
+            ))
+        }
+
+        let mut overrides = ZkStateOverrides::default();
+        for Vm::BalanceOverride { account, balance } in balances {
+            overrides.set_balance(account.to_h160(), balance.to_u256());
+        }
+        for Vm::CodeOverride { account, code } in codes {
+            if code.len() % 32 != 0 {
+                return Err(CheatcodeError::Revert(format!(
+                    "the code of {account} is not a sequence of 32 bytes words"
+                )))
+            }
+            overrides.set_code(account.to_h160(), code.to_vec());
+        }
+        for Vm::StorageOverride { account, slot, value } in slots {
+            overrides.set_storage(account.to_h160(), H256(**slot), H256(**value));
+        }
+        storage.borrow_mut().set_scoped_overrides(overrides);
+
+        // the call is made from the test frame, at the depth of the cheatcode frame
+        self.state_overrides = Some(StateOverridesOpts {
+            depth: state.vm_local_state.callstack.depth(),
+            applied: false,
+        });
+        Ok(())
+    }
+
+    /// Returns the bytecode of a zksolc artifact, marking it and the factory dependencies it
+    /// deploys as known so that it can be deployed or etched.
+    fn load_artifact_code<S: WriteStorage>(
+        &mut self,
+        artifact_path: &str,
+        storage: &mut RefMut<S>,
+    ) -> Result<Vec<u8>, CheatcodeError> {
+        let packed = self
+            .read_zk_artifact(artifact_path)
+            .map_err(|err| CheatcodeError::Revert(format!("{err:#}")))?;
+        for dep in packed.factory_deps() {
+            let (hash, code) = bytecode_to_factory_dep(dep);
+            self.store_factory_dep(hash, code);
+            let known_code_key = StorageKey::new(
+                AccountTreeId::new(KNOWN_CODES_STORAGE_ADDRESS),
+                u256_to_h256(hash),
+            );
+            self.write_storage(known_code_key, H256::from_low_u64_be(1), storage);
+        }
+        Ok(packed.bytecode())
+    }
+
+    /// Reads the bytecode and factory dependencies of a zksolc artifact, given either the path to
+    /// its json file or `File.sol:Contract`.
+    fn read_zk_artifact(&self, path: &str) -> eyre::Result<PackedEraBytecode> {
+        let mut paths = self.config.paths.clone();
+        paths.artifacts = self.config.root.join("zkout");
+        let path = self
+            .config
+            .ensure_path_allowed(get_artifact_path(&paths, path), FsAccessKind::Read)
+            .map_err(|err| eyre::eyre!("{err}"))?;
+
+        let data = fs::read_to_string(&path)
+            .wrap_err_with(|| format!("failed to read artifact {}", path.display()))?;
+        let artifact: serde_json::Value = serde_json::from_str(&data)?;
+        let object = artifact["bytecode"]["object"]
+            .as_str()
+            .ok_or_else(|| eyre::eyre!("artifact {} has no bytecode", path.display()))?;
+        let object = hex::decode(object.trim_start_matches("0x"))?;
+        serde_json::from_slice(&object)
+            .wrap_err_with(|| format!("artifact {} is not a zksolc artifact", path.display()))
+    }
+
+    /// Drains the recorded logs, followed by the ones of `vm.transact`, returning those matching
+    /// the filter in emission order.
+    ///
+    /// Recording stops until the next `vm.recordLogs`, unless logs are recorded throughout tests.
+    fn take_recorded_logs(
+        &mut self,
+        state: &VmLocalStateData<'_>,
+        filter: impl Fn(&LogEntry) -> bool,
+    ) -> Vec<Log> {
+        let logs = std::mem::take(&mut self.recorded_logs)
+            .into_values()
+            .chain(self.forks.transact_logs.drain(..))
+            .filter(|log| !log.data.is_empty())
+            .filter(|log| !INTERNAL_CONTRACT_ADDRESSES.contains(&log.address))
+            .filter(&filter)
+            .map(|log| Log {
+                topics: log.topics.iter().map(|topic| topic.to_fixed_bytes().into()).collect(),
+                data: log.data,
+                emitter: log.address.to_fixed_bytes().into(),
+            })
+            .collect_vec();
+
+        if self.config.record_logs {
+            self.recording_timestamp = state.vm_local_state.timestamp;
+        } else {
+            self.recording_logs = false;
+        }
+
+        logs
+    }
+
     /// Returns a given account's nonce
     ///
     /// The first item of the tuple represents the total number of transactions,
@@ -2211,9 +3154,15 @@ impl CheatcodeTracer {
         self.next_return_action.as_mut().map(|action| &mut action.action)
     }
 
-    fn add_expect_revert(&mut self, reason: Option<Vec<u8>>, depth: usize) {
+    fn add_expect_revert(
+        &mut self,
+        reason: Option<Vec<u8>>,
+        reverter: Option<H160>,
+        count: u64,
+        depth: usize,
+    ) -> Result<(), CheatcodeError> {
         if self.current_return_action().is_some() {
-            panic!("expectRevert already set")
+            return Err(CheatcodeError::Revert("expectRevert already set".to_string()))
         }
 
         //-1: Because we are working with return opcode and it pops the stack after execution
@@ -2222,16 +3171,21 @@ impl CheatcodeTracer {
             depth: depth - 1,
             prev_exception_handler_pc: None,
             prev_continue_pc: None,
+            reverter,
+            reverted_by: None,
+            count,
+            actual_count: 0,
         };
 
         // We have to skip at least one return from CHEATCODES contract
         self.next_return_action =
             Some(NextReturnAction { target_depth: depth - 1, action, returns_to_skip: 1 });
+        Ok(())
     }
 
-    fn add_revert_test(&mut self, reason: Vec<u8>, depth: usize) {
+    fn add_revert_test(&mut self, reason: Vec<u8>, depth: usize) -> Result<(), CheatcodeError> {
         if self.current_return_action().is_some() {
-            panic!("revert test already set")
+            return Err(CheatcodeError::Revert("a revert is already pending".to_string()))
         }
 
         //-1: Because we are working with return opcode and it pops the stack after execution
@@ -2240,6 +3194,98 @@ impl CheatcodeTracer {
 
         self.next_return_action =
             Some(NextReturnAction { target_depth: depth - 1, action, returns_to_skip: 0 });
+        Ok(())
+    }
+
+    /// Calls the ERC-20 or ERC-721 `token` from the caller of the cheatcode, with the gas left to
+    /// the cheatcode and without modifying the state, and decodes the value it returns.
+    fn call_token<S: DatabaseExt + Send, T: SolValue>(
+        &self,
+        state: &VmLocalStateData<'_>,
+        storage: &StoragePtr<EraDb<S>>,
+        token: Address,
+        calldata: Vec<u8>,
+    ) -> Result<T, CheatcodeError> {
+        let caller =
+            state.vm_local_state.callstack.inner.last().expect("cheatcode called without a caller");
+        let env = self.env.get().unwrap();
+        let tx_env = TxEnv {
+            caller: h160_to_address(caller.this_address),
+            transact_to: TransactTo::Call(token),
+            data: calldata.into(),
+            gas_limit: state.vm_local_state.callstack.current.ergs_remaining.into(),
+            ..Default::default()
+        };
+        // contracts deployed in the test aren't in the database yet
+        let factory_deps = self
+            .storage_modifications
+            .bytecodes()
+            .iter()
+            .chain(self.storage_modifications.known_codes())
+            .map(|(hash, code)| (*hash, code.clone()));
+
+        let output = run_era_call(
+            &storage.borrow(),
+            factory_deps,
+            tx_env,
+            env.system_env.chain_id,
+            env.l1_batch_env.l1_gas_price,
+        )
+        .map_err(|revert| {
+            CheatcodeError::Revert(format!(
+                "call to {token} reverted: {}",
+                decode_revert(&revert, None, None)
+            ))
+        })?;
+        T::abi_decode(&output, true)
+            .map_err(|_| CheatcodeError::Revert(format!("{token} returned an unexpected value")))
+    }
+
+    /// Writes the breakpoint at the instruction following the call to the cheatcode, or removes it
+    /// if `add` is false.
+    fn write_breakpoint(
+        &self,
+        state: &VmLocalStateData<'_>,
+        s: &str,
+        add: bool,
+    ) -> Result<(), CheatcodeError> {
+        let point = parse_breakpoint(s).map_err(CheatcodeError::Revert)?;
+        if !add {
+            self.breakpoints.remove(point);
+            return Ok(())
+        }
+
+        let callstack = &state.vm_local_state.callstack;
+        let caller = callstack.inner.last().expect("cheatcode called without a caller");
+        self.breakpoints.insert(
+            point,
+            ZkBreakpoint {
+                address: caller.this_address,
+                code_address: caller.code_address,
+                pc: caller.pc,
+                depth: callstack.depth() - 1,
+            },
+        );
+        Ok(())
+    }
+
+    /// Reverts the currently executing cheatcode call with the given error message, encoded as
+    /// `CheatcodeError(string)`.
+    fn revert_with_error(&mut self, state: &VmLocalStateData<'_>, error: impl ToString) {
+        let error = error.to_string();
+        tracing::error!("{error}");
+        // a pending `expectRevert` is resumed after this revert, so it can match it in the caller
+        match self.next_return_action.take() {
+            Some(
+                pending @ NextReturnAction { action: ActionOnReturn::ExpectRevert { .. }, .. },
+            ) => self.suspended_return_action = Some(pending),
+            Some(pending) => tracing::debug!(?pending, "replacing pending return action"),
+            None => (),
+        }
+        let _ = self.add_revert_test(
+            foundry_cheatcodes::Error::encode(error).to_vec(),
+            state.vm_local_state.callstack.depth(),
+        );
     }
 
     fn handle_expect_revert<H: HistoryMode>(
@@ -2312,51 +3358,6 @@ impl CheatcodeTracer {
         }
     }
 
-    /// Adds an expectCall to the tracker.
-    fn expect_call(
-        &mut self,
-        callee: &H160,
-        calldata: &Vec<u8>,
-        value: Option<U256>,
-        count: u64,
-        call_type: ExpectedCallType,
-    ) {
-        let expecteds = self.expected_calls.entry(*callee).or_default();
-
-        match call_type {
-            ExpectedCallType::Count => {
-                // Get the expected calls for this target.
-                // In this case, as we're using counted expectCalls, we should not be able to set
-                // them more than once.
-                assert!(
-                    !expecteds.contains_key(calldata),
-                    "counted expected calls can only bet set once"
-                );
-                expecteds
-                    .insert(calldata.to_vec(), (ExpectedCallData { value, count, call_type }, 0));
-            }
-            ExpectedCallType::NonCount => {
-                // Check if the expected calldata exists.
-                // If it does, increment the count by one as we expect to see it one more time.
-                match expecteds.entry(calldata.clone()) {
-                    Entry::Occupied(mut entry) => {
-                        let (expected, _) = entry.get_mut();
-                        // Ensure we're not overwriting a counted expectCall.
-                        assert!(
-                            expected.call_type == ExpectedCallType::NonCount,
-                            "cannot overwrite a counted expectCall with a non-counted expectCall"
-                        );
-                        expected.count += 1;
-                    }
-                    // If it does not exist, then create it.
-                    Entry::Vacant(entry) => {
-                        entry.insert((ExpectedCallData { value, count, call_type }, 0));
-                    }
-                }
-            }
-        }
-    }
-
     fn handle_return<H: HistoryMode>(
         &mut self,
         state: &VmLocalStateData<'_>,
@@ -2381,6 +3382,10 @@ impl CheatcodeTracer {
                 depth,
                 prev_exception_handler_pc: exception_handler,
                 prev_continue_pc: continue_pc,
+                reverter,
+                reverted_by,
+                count,
+                actual_count,
             } => {
                 // Check how many returns we need to skip before finding the actual one
                 if action.returns_to_skip != 0 {
@@ -2389,6 +3394,32 @@ impl CheatcodeTracer {
                 }
 
                 match op {
+                    // a count of 0 expects the call not to revert with the reason
+                    RetOpcode::Revert if *count == 0 => {
+                        let Some(exception_handler) = *exception_handler else {
+                            tracing::error!("exceptRevert missing stored continuations");
+                            return
+                        };
+                        let matches = Self::handle_expect_revert(
+                            reason.as_ref(),
+                            op,
+                            state,
+                            memory,
+                        )
+                        .is_ok() &&
+                            reverter.map_or(true, |reverter| *reverted_by == Some(reverter));
+                        // any other revert goes through as is
+                        if matches {
+                            self.one_time_actions.push(FinishCycleOneTimeActions::ForceRevert {
+                                error: "call reverted when it was expected not to"
+                                    .to_string()
+                                    .into(),
+                                exception_handler,
+                            });
+                        }
+                        self.next_return_action = None;
+                    }
+                    RetOpcode::Ok if *count == 0 => self.next_return_action = None,
                     RetOpcode::Revert => {
                         tracing::debug!(wanted = %depth, current_depth = %callstack_depth, opcode = ?data.opcode.variant.opcode, "expectRevert");
                         let (Some(exception_handler), Some(continue_pc)) =
@@ -2398,8 +3429,35 @@ impl CheatcodeTracer {
                             return
                         };
 
+                        let result = Self::handle_expect_revert(reason.as_ref(), op, state, memory)
+                            .and_then(|_| match reverter {
+                                Some(reverter) if reverted_by != &Some(*reverter) => Err(format!(
+                                    "Reverter != expected reverter: {reverted_by:?} != {reverter:?}"
+                                )
+                                .into()),
+                                _ => Ok(()),
+                            });
+                        // Expect the next call to revert as well until the count is reached
+                        let next_return_action = match result {
+                            Ok(_) if actual_count + 1 < *count => Some(NextReturnAction {
+                                target_depth: action.target_depth,
+                                action: ActionOnReturn::ExpectRevert {
+                                    reason: reason.clone(),
+                                    depth: *depth,
+                                    prev_exception_handler_pc: None,
+                                    prev_continue_pc: None,
+                                    reverter: *reverter,
+                                    reverted_by: None,
+                                    count: *count,
+                                    actual_count: actual_count + 1,
+                                },
+                                returns_to_skip: 0,
+                            }),
+                            _ => None,
+                        };
+
                         self.one_time_actions.push(
-                            Self::handle_expect_revert(reason.as_ref(), op, state, memory)
+                            result
                                 .map(|_| FinishCycleOneTimeActions::ForceReturn {
                                     //dummy data
                                     data: // vec![0u8; 8192]
@@ -2411,7 +3469,7 @@ impl CheatcodeTracer {
                                     exception_handler,
                                 }),
                         );
-                        self.next_return_action = None;
+                        self.next_return_action = next_return_action;
                     }
                     RetOpcode::Ok => {
                         let Some(exception_handler) = *exception_handler else {
@@ -2419,7 +3477,16 @@ impl CheatcodeTracer {
                             return
                         };
                         if let Err(error) =
-                            Self::handle_expect_revert(reason.as_ref(), op, state, memory)
+                            Self::handle_expect_revert(reason.as_ref(), op, state, memory).map_err(
+                                |error| {
+                                    if *actual_count > 0 {
+                                        format!("expected {count} reverts, but got {actual_count}")
+                                            .into()
+                                    } else {
+                                        error
+                                    }
+                                },
+                            )
                         {
                             self.one_time_actions.push(FinishCycleOneTimeActions::ForceRevert {
                                 error,
@@ -2441,7 +3508,7 @@ impl CheatcodeTracer {
                     error: reason.to_owned(),
                     exception_handler: continue_pc,
                 });
-                self.next_return_action = None;
+                self.next_return_action = self.suspended_return_action.take();
             }
         }
     }
@@ -2452,7 +3519,7 @@ impl CheatcodeTracer {
         sender: H160,
         origin: Option<H160>,
     ) {
-        if self.permanent_actions.broadcast.is_some() {
+        if self.broadcast.is_active() {
             tracing::error!("prank is incompatible with broadcast");
             return
         }
@@ -2477,6 +3544,43 @@ impl CheatcodeTracer {
         }
     }
 
+    fn prank<S: DatabaseExt + Send>(
+        &mut self,
+        state: &VmLocalStateData<'_>,
+        storage: &StoragePtr<EraDb<S>>,
+        sender: H160,
+        origin: Option<H160>,
+    ) -> Result<(), CheatcodeError> {
+        if self.broadcast.is_active() {
+            return Err(CheatcodeError::Revert("prank is incompatible with broadcast".to_string()))
+        }
+        if self.permanent_actions.start_prank.is_some() {
+            return Err(CheatcodeError::Revert(
+                "cannot overwrite a prank set with startPrank".to_string(),
+            ))
+        }
+
+        let original_origin = origin.map(|tx_origin| {
+            let key = StorageKey::new(
+                AccountTreeId::new(zksync_types::SYSTEM_CONTEXT_ADDRESS),
+                zksync_types::SYSTEM_CONTEXT_TX_ORIGIN_POSITION,
+            );
+            let storage = &mut storage.borrow_mut();
+            let original_tx_origin = storage.read_value(&key);
+            self.write_storage(key, tx_origin.into(), storage);
+            original_tx_origin.into()
+        });
+
+        // the pranked call is made from the test frame, at the depth of the cheatcode frame
+        self.prank = Some(PrankOpts {
+            sender,
+            original_origin,
+            depth: state.vm_local_state.callstack.depth(),
+            applied: false,
+        });
+        Ok(())
+    }
+
     fn stop_prank<S: DatabaseExt + Send>(&mut self, storage: &StoragePtr<EraDb<S>>) {
         if let Some(original_tx_origin) =
             self.permanent_actions.start_prank.take().and_then(|v| v.origin)
@@ -2494,6 +3598,7 @@ impl CheatcodeTracer {
         storage: &StoragePtr<EraDb<S>>,
         state: &VmLocalStateData<'_>,
         new_origin: Option<H160>,
+        single_call: bool,
     ) {
         if self.permanent_actions.start_prank.is_some() {
             tracing::error!("broadcast is incompatible with prank");
@@ -2512,68 +3617,71 @@ impl CheatcodeTracer {
         let original_tx_origin = storage.read_value(&key);
         let new_origin = new_origin.unwrap_or(original_tx_origin.into());
 
-        self.permanent_actions.broadcast = Some(BroadcastOpts {
+        self.broadcast.start(BroadcastOpts {
             new_origin,
             original_origin: original_tx_origin.into(),
             original_caller: state.vm_local_state.callstack.current.msg_sender,
             depth,
+            single_call,
         })
     }
 
+    /// Returns the wallets the script can broadcast with: the ones it was run with, followed by
+    /// the remembered ones.
+    fn script_wallet_addresses(&self) -> Vec<Address> {
+        self.config
+            .script_wallets
+            .iter()
+            .copied()
+            .chain(self.script_wallets.addresses().into_iter().map(h160_to_address))
+            .unique()
+            .collect_vec()
+    }
+
+    /// Returns the script wallet at `index` in [Self::script_wallet_addresses].
+    fn script_wallet(&self, index: rU256) -> Result<H160, CheatcodeError> {
+        let wallets = self.script_wallet_addresses();
+        usize::try_from(index)
+            .ok()
+            .and_then(|i| wallets.get(i))
+            .map(|wallet| wallet.to_h160())
+            .ok_or_else(|| {
+                CheatcodeError::Revert(format!(
+                    "sender index {index} is out of range, the script has {} wallets",
+                    wallets.len()
+                ))
+            })
+    }
+
     fn stop_broadcast(&mut self) {
-        if let Some(broadcast) = self.permanent_actions.broadcast.take() {
-            self.one_time_actions
-                .push(FinishCycleOneTimeActions::SetOrigin { origin: broadcast.original_origin });
+        if let Some(origin) = self.broadcast.stop() {
+            self.one_time_actions.push(FinishCycleOneTimeActions::SetOrigin { origin });
         }
     }
 
-    /// Merge current modified storage with the entire storage modifications made so far in the test
-    fn get_modified_storage(
+    /// Returns the entire storage modifications made so far in the test, including the ones made
+    /// by the current transaction.
+    fn get_storage_modifications<S: DatabaseExt + Send>(
         &self,
-        storage: &HashMap<StorageKey, H256>,
-    ) -> HashMap<StorageKey, H256> {
-        let mut modified_storage = self
-            .storage_modifications
-            .keys
-            .clone()
-            .into_iter()
-            .filter(|(key, _)| key.address() != &zksync_types::SYSTEM_CONTEXT_ADDRESS)
-            .collect::<HashMap<_, _>>();
-        modified_storage.extend(
-            storage
-                .iter()
-                .filter(|(key, _)| key.address() != &zksync_types::SYSTEM_CONTEXT_ADDRESS),
-        );
-        modified_storage
+        storage: &StoragePtr<EraDb<S>>,
+    ) -> StorageModifications {
+        StorageModifications::new(
+            merge_modified_storage(
+                &self.storage_modifications,
+                storage.borrow().modified_storage_keys(),
+            ),
+            self.storage_modifications.bytecodes().clone(),
+            self.storage_modifications.known_codes().clone(),
+        )
     }
 
-    /// Merge current modified bytecodes with the entire storage modifications made so far in the
-    /// test
-    fn get_modified_bytecodes(
-        &self,
-        bootloader_bytecodes: Vec<CompressedBytecodeInfo>,
-    ) -> HashMap<H256, Vec<u8>> {
-        let mut modified_bytecodes = self.storage_modifications.bytecodes.clone();
-        modified_bytecodes.extend(
-            bootloader_bytecodes
-                .iter()
-                .map(|b| {
-                    let (bytecode_key, bytecode_value) =
-                        bytecode_to_factory_dep(b.original.clone());
-                    let key = u256_to_h256(bytecode_key);
-                    let value = bytecode_value
-                        .into_iter()
-                        .flat_map(|v| u256_to_h256(v).as_bytes().to_owned())
-                        .collect_vec();
-                    (key, value)
-                })
-                .collect::<HashMap<_, _>>(),
-        );
-        modified_bytecodes
+    /// Queues an action on the forks, applied at the end of the cycle.
+    fn fork_action(&mut self, action: ForkAction) {
+        self.one_time_actions.push(FinishCycleOneTimeActions::Fork(action));
     }
 }
 
-trait ToZkEvmResult {
+pub(crate) trait ToZkEvmResult {
     /// Converts a [SolcValue] to return data for zkevm
     fn to_return_data(&self) -> Vec<U256>;
 }
@@ -2590,69 +3698,32 @@ where
     }
 }
 
-fn into_revm_bytecodes(zk_bytecodes: HashMap<H256, Vec<u8>>) -> HashMap<U256, Vec<U256>> {
-    zk_bytecodes
-        .into_iter()
-        .map(|(key, value)| {
-            let key = h256_to_u256(key);
-            let value = value.chunks(32).map(U256::from).collect_vec();
-            (key, value)
-        })
-        .collect()
-}
-
-fn into_revm_env(env: &EraEnv) -> Env {
-    use revm::primitives::U256;
-    let block = BlockEnv {
-        number: U256::from(env.l1_batch_env.first_l2_block.number),
-        coinbase: h160_to_address(env.l1_batch_env.fee_account),
-        timestamp: U256::from(env.l1_batch_env.first_l2_block.timestamp),
-        gas_limit: U256::from(env.system_env.gas_limit),
-        basefee: U256::from(env.l1_batch_env.base_fee()),
-        ..Default::default()
-    };
-
-    let mut cfg = CfgEnv::default();
-    cfg.chain_id = env.system_env.chain_id.as_u64();
-
-    Env { block, cfg, ..Default::default() }
+/// Describes a code hash from the account code storage, whose second byte marks contracts under
+/// construction and whose next two bytes are the length of the code in words.
+fn describe_code_hash(hash: H256) -> String {
+    if hash.is_zero() {
+        return "no code".to_string()
+    }
+    let words = u16::from_be_bytes([hash[2], hash[3]]);
+    let constructing = if hash[1] == 1 { ", under construction" } else { "" };
+    format!("{hash:?} ({words} words{constructing})")
 }
 
-fn create_fork_request(
-    env: &EraEnv,
-    config: Arc<CheatsConfig>,
-    block_number: Option<u64>,
-    url_or_alias: &str,
-) -> CreateFork {
-    use foundry_evm_core::opts::Env;
-    use revm::primitives::Address as revmAddress;
-
-    let url = config.rpc_url(url_or_alias).unwrap();
-    let env = into_revm_env(env);
-    let opts_env = Env {
-        gas_limit: u64::MAX,
-        chain_id: None,
-        tx_origin: revmAddress::ZERO,
-        block_number: 0,
-        block_timestamp: 0,
-        ..Default::default()
-    };
-    let evm_opts = EvmOpts {
-        env: opts_env,
-        fork_url: Some(url.clone()),
-        fork_block_number: block_number,
-        ..Default::default()
-    };
-
-    CreateFork {
-        enable_caching: config.rpc_storage_caching.enable_for_endpoint(&url),
-        url,
-        env,
-        evm_opts,
-    }
+/// Returns the hash of the bytecode executed by the far call frames of `address`.
+pub(crate) fn executed_bytecode_hash<S: ReadStorage + ?Sized>(
+    storage: &mut S,
+    address: &H160,
+) -> H256 {
+    let mut hash = storage.read_value(&get_code_key(address));
+    // constructor frames run the code marked as being constructed
+    hash.0[1] = 0;
+    hash
 }
 
-fn get_calldata<H: HistoryMode>(state: &VmLocalStateData<'_>, memory: &SimpleMemory<H>) -> Vec<u8> {
+pub(crate) fn get_calldata<H: HistoryMode>(
+    state: &VmLocalStateData<'_>,
+    memory: &SimpleMemory<H>,
+) -> Vec<u8> {
     let ptr = state.vm_local_state.registers[CALL_IMPLICIT_CALLDATA_FAT_PTR_REGISTER as usize];
     assert!(ptr.is_pointer);
     let fat_data_pointer = FatPointer::from_u256(ptr.value);
@@ -2663,71 +3734,97 @@ fn get_calldata<H: HistoryMode>(state: &VmLocalStateData<'_>, memory: &SimpleMem
     )
 }
 
-fn compare_logs(expected_logs: &[LogEntry], actual_logs: &[LogEntry], checks: EmitChecks) -> bool {
-    let mut expected_iter = expected_logs.iter().peekable();
-    let mut actual_iter = actual_logs.iter();
-
-    while let Some(expected_log) = expected_iter.peek() {
-        let mut found = false;
-
-        for actual_log in actual_iter.by_ref() {
-            if are_logs_equal(expected_log, actual_log, &checks) {
-                found = true;
-                break
-            }
-        }
-
-        if found {
-            expected_iter.next(); // Move to the next expected log
-        } else {
-            // Expected log not found in the remaining actual logs
-            return false
+/// Reads the environment variable `name`, as upstream foundry does.
+fn get_env(name: &str) -> Result<String, CheatcodeError> {
+    match foundry_common::env_reads::var(name) {
+        Ok(value) => Ok(value),
+        Err(std::env::VarError::NotPresent) => {
+            Err(CheatcodeError::Revert(format!("environment variable {name:?} not found")))
         }
+        Err(std::env::VarError::NotUnicode(value)) => Err(CheatcodeError::Revert(format!(
+            "environment variable {name:?} was not valid unicode: {value:?}"
+        ))),
     }
-
-    true
-}
-
-fn are_logs_equal(a: &LogEntry, b: &LogEntry, emit_checks: &EmitChecks) -> bool {
-    let address_match = match emit_checks.address {
-        Some(address) => b.address == address,
-        None => true,
-    };
-
-    let topics_match = emit_checks.topics.iter().enumerate().all(|(i, &check)| {
-        if check {
-            a.topics.get(i) == b.topics.get(i)
-        } else {
-            true
-        }
-    });
-
-    let data_match = if emit_checks.data { a.data == b.data } else { true };
-
-    address_match && topics_match && data_match
 }
 
-fn parse_env<F, T, E>(name: &str, parser_fn: F) -> eyre::Result<T>
+/// Reads and parses the environment variable `name` as a value of the solidity type `ty`.
+fn parse_env<F, T, E>(name: &str, ty: &str, parser_fn: F) -> Result<T, CheatcodeError>
 where
     F: Fn(&str) -> Result<T, E>,
-    E: std::error::Error + Send + Sync + 'static,
+    E: std::fmt::Display,
 {
-    let env_var = std::env::var(name).context("Env var not found")?;
-    let env_var = parser_fn(&env_var)?;
-    Ok(env_var)
+    let env_var = get_env(name)?;
+    parser_fn(&env_var).map_err(|err| {
+        CheatcodeError::Revert(format!("failed parsing ${name} as type `{ty}`: {err}"))
+    })
 }
 
-fn parse_env_array<F, T, E>(name: &str, delim: &str, parser_fn: F) -> eyre::Result<Vec<T>>
+/// Reads and parses the environment variable `name` as an array of the solidity type `ty`, split
+/// by `delim`.
+fn parse_env_array<F, T, E>(
+    name: &str,
+    delim: &str,
+    ty: &str,
+    parser_fn: F,
+) -> Result<Vec<T>, CheatcodeError>
 where
     F: Fn(&str) -> Result<T, E>,
-    E: std::error::Error + Send + Sync + 'static,
+    E: std::fmt::Display,
 {
-    let env_var = std::env::var(name).context("Env var not found")?;
-    let env_vars = env_var
-        .split(&delim)
+    let env_var = get_env(name)?;
+    env_var
+        .split(delim)
         .map(|var| var.trim())
         .filter(|var| !var.is_empty())
-        .map(parser_fn)
-        .collect::<Result<Vec<_>, E>>()?;
-    Ok(env_vars)
+        .map(&parser_fn)
+        .collect::<Result<Vec<_>, E>>()
+        .map_err(|err| {
+            CheatcodeError::Revert(format!("failed parsing ${name} as type `{ty}`: {err}"))
+        })
+}
+
+/// Returns the ABI-encoded values parsed from JSON, or reverts with the error.
+fn json_return_data(result: foundry_cheatcodes::Result) -> Result<ReturnData, CheatcodeError> {
+    let data = result.map_err(|err| CheatcodeError::Revert(err.to_string()))?;
+    Ok(data.chunks(32).map(U256::from_big_endian).collect_vec())
+}
+
+/// Parses the value at `key` of `json` as `ty`, with the same coercions as upstream foundry, e.g.
+/// numbers may be decimal or hex strings, and bytes must be hex strings.
+fn parse_json_as(json: &str, key: &str, ty: &DynSolType) -> Result<ReturnData, CheatcodeError> {
+    json_return_data(foundry_cheatcodes::parse_json_coerce(json, key, ty))
+}
+
+/// Sets `value` at the (possibly nested) `key` of `json`, creating any missing intermediate
+/// objects.
+///
+/// The key follows the same notation as upstream foundry's `writeJson`, i.e. `a`, `.a.b` or
+/// `$.a.b` are all accepted.
+fn set_json_key(
+    json: &mut serde_json::Value,
+    key: &str,
+    value: serde_json::Value,
+) -> eyre::Result<()> {
+    let path = key.trim_start_matches('$').trim_start_matches('.');
+    if path.is_empty() {
+        *json = value;
+        return Ok(())
+    }
+
+    let mut current = json;
+    for segment in path.split('.') {
+        if segment.is_empty() {
+            eyre::bail!("invalid JSON key {key:?}: empty path segment")
+        }
+        if current.is_null() {
+            *current = serde_json::Value::Object(Default::default());
+        }
+        let Some(object) = current.as_object_mut() else {
+            eyre::bail!("invalid JSON key {key:?}: {segment:?} is not inside an object")
+        };
+        current = object.entry(segment).or_insert(serde_json::Value::Null);
+    }
+    *current = value;
+
+    Ok(())
 }
@@ -0,0 +1,64 @@
+//! Errors of the cheatcodes, which revert the cheatcode call.
+
+use alloy_primitives::FixedBytes;
+use foundry_cheatcodes_spec::Vm;
+
+/// The error a cheatcode failed with.
+///
+/// The [CheatcodeTracer](crate::cheatcodes::CheatcodeTracer) reverts the cheatcode call with the
+/// error's message.
+#[derive(Debug, thiserror::Error)]
+pub enum CheatcodeError {
+    /// The tracer the cheatcode relies on is disabled.
+    #[error("the {0} tracer is disabled")]
+    TracerDisabled(&'static str),
+    /// The cheatcode is disabled in the `cheatcodes` section of the config.
+    #[error("`{0}` is disabled by config")]
+    DisabledByConfig(&'static str),
+    /// The given private key is invalid.
+    #[error("invalid private key")]
+    InvalidPrivateKey,
+    /// An `ffi` command couldn't be run.
+    #[error("failed to run ffi: {0}")]
+    Ffi(String),
+    /// Forks can't be selected while broadcasting.
+    #[error("cannot select a fork during a broadcast")]
+    ForkDuringBroadcast,
+    /// The cheatcode is part of the `Vm` interface, but isn't supported in the zkEVM yet.
+    #[error("cheatcode `{0}` is not supported in the zkEVM yet")]
+    Unsupported(&'static str),
+    /// The selector doesn't match any cheatcode.
+    #[error("unknown cheatcode with selector {0}")]
+    Unknown(FixedBytes<4>),
+    /// The cheatcode failed with the given message.
+    #[error("{0}")]
+    Revert(String),
+}
+
+impl CheatcodeError {
+    /// Returns the error of a cheatcode the dispatcher doesn't handle.
+    pub(crate) fn not_dispatched(selector: [u8; 4]) -> Self {
+        match Vm::CHEATCODES.iter().find(|c| c.func.selector_bytes == selector) {
+            Some(cheatcode) => Self::Unsupported(cheatcode.func.declaration),
+            None => Self::Unknown(selector.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_cheatcodes_not_dispatched() {
+        let cheatcode = &Vm::CHEATCODES[0];
+        assert!(matches!(
+            CheatcodeError::not_dispatched(cheatcode.func.selector_bytes),
+            CheatcodeError::Unsupported(declaration) if declaration == cheatcode.func.declaration
+        ));
+
+        let error = CheatcodeError::not_dispatched([0; 4]);
+        assert!(matches!(error, CheatcodeError::Unknown(_)));
+        assert_eq!(error.to_string(), "unknown cheatcode with selector 0x00000000");
+    }
+}
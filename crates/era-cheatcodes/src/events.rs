@@ -2,11 +2,27 @@ use foundry_common::conversion_utils::h256_to_h160;
 use multivm::zk_evm_1_4_0::reference_impls::event_sink::EventMessage;
 use zksync_basic_types::{H160, H256};
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
 pub struct LogEntry {
     pub address: H160,
     pub topics: Vec<H256>,
     pub data: Vec<u8>,
+    /// The position of the transaction that emitted the log in its block.
+    pub tx_number_in_block: u16,
+    /// The position of the log among the ones it was parsed with, in emission order.
+    pub index: usize,
+    /// The call depth of the frame that emitted the log, if known.
+    pub depth: Option<usize>,
+}
+
+impl std::fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "log #{} of tx {} from {:?}", self.index, self.tx_number_in_block, self.address)?;
+        if let Some(depth) = self.depth {
+            write!(f, " at depth {depth}")?;
+        }
+        write!(f, ", topics {:?}, data 0x{}", self.topics, hex::encode(&self.data))
+    }
 }
 
 #[derive(Clone)]
@@ -81,8 +97,10 @@ fn merge_events_inner(events: Vec<EventMessage>) -> Vec<SolidityLikeEvent> {
             let (topics, data) = if num_topics == 0 && data_length == 0 {
                 (vec![], vec![])
             } else if num_topics == 0 {
-                data_length -= 32;
-                (vec![], buffer.to_vec())
+                // the first word may be partially used by short data
+                let length = data_length.min(32);
+                data_length -= length;
+                (vec![], buffer[..length].to_vec())
             } else {
                 num_topics -= 1;
                 (vec![buffer], vec![])
@@ -106,16 +124,24 @@ fn merge_events_inner(events: Vec<EventMessage>) -> Vec<SolidityLikeEvent> {
 }
 
 /// Parse a list of [EventMessage]s to [LogEntry]s.
+///
+/// All the topics of an event are kept, i.e. none for anonymous events without indexed fields and
+/// more than 4 for the events of system contracts.
 pub fn parse_events(events: Vec<EventMessage>) -> Vec<LogEntry> {
     let raw_events = merge_events_inner(events);
 
     raw_events
         .into_iter()
-        .map(|event| {
+        .enumerate()
+        .filter_map(|(index, event)| {
             // The events writer events where the first topic is the actual address of the event and
             // the rest of the topics are real topics
-            LogEntry {
-                address: h256_to_h160(&H256::from_slice(&event.topics[0])),
+            let Some(address) = event.topics.first() else {
+                tracing::warn!(?event.address, "skipping event without emitter");
+                return None
+            };
+            Some(LogEntry {
+                address: h256_to_h160(&H256::from_slice(address)),
                 topics: event
                     .topics
                     .into_iter()
@@ -123,7 +149,80 @@ pub fn parse_events(events: Vec<EventMessage>) -> Vec<LogEntry> {
                     .map(|topic| H256::from_slice(&topic))
                     .collect(),
                 data: event.data,
-            }
+                tx_number_in_block: event.tx_number_in_block,
+                index,
+                depth: None,
+            })
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zksync_basic_types::U256;
+
+    /// Returns the messages the event writer emits for an event: a first message with the number
+    /// of topics and the data length, then two words per message.
+    fn event(address: H160, topics: &[u64], data: &[u8]) -> Vec<EventMessage> {
+        let mut words = vec![U256::from_big_endian(H256::from(address).as_bytes())];
+        words.extend(topics.iter().map(|&topic| U256::from(topic)));
+        words.extend(data.chunks(32).map(|chunk| {
+            let mut word = [0u8; 32];
+            word[..chunk.len()].copy_from_slice(chunk);
+            U256::from_big_endian(&word)
+        }));
+        let message = |is_first, key, value| EventMessage {
+            shard_id: 0,
+            is_first,
+            tx_number_in_block: 1,
+            address: zksync_types::EVENT_WRITER_ADDRESS,
+            key,
+            value,
+        };
+
+        let header = U256::from(topics.len() as u64 + 1) | (U256::from(data.len()) << 32);
+        let mut messages = vec![message(true, header, words[0])];
+        for pair in words[1..].chunks(2) {
+            messages.push(message(false, pair[0], pair.get(1).copied().unwrap_or_default()));
+        }
+        messages
+    }
+
+    #[test]
+    fn tells_identical_events_apart() {
+        let emitter = H160::repeat_byte(1);
+        let events = [event(emitter, &[7], &[]), event(emitter, &[7], &[])].concat();
+
+        let logs = parse_events(events);
+        assert_eq!(logs.len(), 2);
+        for (index, log) in logs.iter().enumerate() {
+            assert_eq!(log.address, emitter);
+            assert_eq!(log.topics, [H256::from_low_u64_be(7)]);
+            assert_eq!((log.tx_number_in_block, log.index, log.depth), (1, index, None));
+        }
+        assert_ne!(logs[0], logs[1]);
+    }
+
+    #[test]
+    fn keeps_all_topics() {
+        let emitter = H160::repeat_byte(1);
+        let data = [0xab; 40];
+        let events = [
+            // anonymous, without indexed fields
+            event(emitter, &[], &data),
+            event(emitter, &[], &[0xcd; 4]),
+            // more topics than a solidity event has
+            event(emitter, &[1, 2, 3, 4, 5], &data),
+        ]
+        .concat();
+
+        let logs = parse_events(events);
+        assert_eq!(logs.len(), 3);
+        assert!(logs[0].topics.is_empty());
+        assert_eq!(logs[0].data, data);
+        assert_eq!(logs[1].data, [0xcd; 4]);
+        assert_eq!(logs[2].topics, (1..=5).map(H256::from_low_u64_be).collect::<Vec<_>>());
+        assert_eq!(logs[2].data, data);
+    }
+}
@@ -9,11 +9,12 @@ use multivm::{
     vm_latest::{BootloaderState, HistoryMode, SimpleMemory, ZkSyncVmState},
     zk_evm_1_3_3::aux_structures::MemoryPage,
     zk_evm_1_4_0::{
-        tracing::VmLocalStateData,
+        tracing::{BeforeExecutionData, VmLocalStateData},
         vm_state::{self, PrimitiveValue},
         zkevm_opcode_defs::{
             decoding::{EncodingModeProduction, VmEncodingMode},
-            FatPointer, Opcode, RET_IMPLICIT_RETURNDATA_PARAMS_REGISTER,
+            FarCallABI, FarCallForwardPageType, FatPointer, Opcode, CALL_SYSTEM_ABI_REGISTERS,
+            RET_IMPLICIT_RETURNDATA_PARAMS_REGISTER,
         },
     },
 };
@@ -60,12 +61,26 @@ impl FarCallHandler {
         }
     }
 
+    /// Marks the current FarCall opcode to revert immediately during `finish_cycle`, continuing
+    /// at the caller's `exception_handler`.
+    /// Must be called during `after_execution`.
+    pub(crate) fn set_immediate_revert(
+        &mut self,
+        revert_data: Vec<u8>,
+        exception_handler: PcOrImm,
+    ) {
+        self.set_immediate_return(revert_data);
+        if let Some(immediate_return) = &mut self.immediate_return {
+            immediate_return.continue_pc = exception_handler;
+        }
+    }
+
     /// Tracks the call stack for the currently active FarCall.
     /// Must be called during `before_execution`.
     pub(crate) fn track_active_far_calls<S, H: HistoryMode>(
         &mut self,
         state: VmLocalStateData<'_>,
-        data: multivm::zk_evm_1_4_0::tracing::BeforeExecutionData,
+        data: BeforeExecutionData,
         _memory: &SimpleMemory<H>,
         _storage: StoragePtr<EraDb<S>>,
     ) {
@@ -115,8 +130,72 @@ impl FarCallHandler {
     }
 }
 
+/// Returns the callee and the value of the call simulated by the current frame, if it's the
+/// MsgValueSimulator's.
+///
+/// When some eth is sent to an address in the zkEVM, the call is replaced with a call to the
+/// MsgValueSimulator, which transfers the value and then does a mimic call to the original callee.
+/// The value is stored in the 1st system ABI register, and the callee in the 2nd one.
+/// See: https://github.com/matter-labs/era-test-node/blob/6ee7d29e876b75506f58355218e1ea755a315d17/etc/system-contracts/contracts/MsgValueSimulator.sol#L26-L27
+pub(crate) fn msg_value_call(state: &VmLocalStateData<'_>) -> Option<(H160, U256)> {
+    let current = state.vm_local_state.callstack.current;
+    if current.code_address != zksync_types::MSG_VALUE_SIMULATOR_ADDRESS {
+        return None
+    }
+
+    let value_reg_idx = CALL_SYSTEM_ABI_REGISTERS.start as usize;
+    let value = state.vm_local_state.registers[value_reg_idx].value;
+    let address = state.vm_local_state.registers[value_reg_idx + 1].value;
+    let mut bytes = [0u8; 32];
+    address.to_big_endian(&mut bytes);
+    Some((H160::from_slice(&bytes[12..]), value))
+}
+
+/// Returns the callee and the calldata of the far call about to be executed.
+/// Must be called during `before_execution`, with the far call opcode.
+///
+/// The callee is in the 2nd source register, and the calldata is pointed to by the far call ABI in
+/// the 1st one, either in the heap of the caller or as a forwarded fat pointer.
+pub(crate) fn pending_far_call<H: HistoryMode>(
+    state: &VmLocalStateData<'_>,
+    data: &BeforeExecutionData,
+    memory: &SimpleMemory<H>,
+) -> (H160, Vec<u8>) {
+    let mut bytes = [0u8; 32];
+    data.src1_value.value.to_big_endian(&mut bytes);
+    let callee = H160::from_slice(&bytes[12..]);
+
+    let abi = FarCallABI::from_u256(data.src0_value.value);
+    let mut ptr = abi.memory_quasi_fat_pointer;
+    let base_page = state.vm_local_state.callstack.current.base_memory_page;
+    match abi.forwarding_mode {
+        FarCallForwardPageType::UseHeap => {
+            ptr.memory_page = CallStackEntry::heap_page_from_base(base_page).0;
+        }
+        FarCallForwardPageType::UseAuxHeap => {
+            ptr.memory_page = CallStackEntry::aux_heap_page_from_base(base_page).0;
+        }
+        FarCallForwardPageType::ForwardFatPointer => {
+            ptr.start = ptr.start.saturating_add(ptr.offset);
+            ptr.length = ptr.length.saturating_sub(ptr.offset);
+        }
+    }
+    let calldata = memory.read_unaligned_bytes(
+        ptr.memory_page as usize,
+        ptr.start as usize,
+        ptr.length as usize,
+    );
+    (callee, calldata)
+}
+
 /// Defines the [MockCall]s return type.
-type MockCallReturn = Vec<u8>;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockCallReturn {
+    /// The call returns the data.
+    Return(Vec<u8>),
+    /// The call reverts with the data.
+    Revert(Vec<u8>),
+}
 
 /// Defines the match criteria of a mocked call.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
@@ -138,7 +217,8 @@ pub struct MockedCalls {
 }
 
 impl MockedCalls {
-    /// Insert a mocked call with its return data.
+    /// Insert a mocked call with its return or revert data, replacing the earlier mock of the same
+    /// call, if any.
     pub(crate) fn insert(&mut self, call: MockCall, return_data: MockCallReturn) {
         if call.value.is_some() {
             self.with_value.insert(call, return_data);
@@ -156,13 +236,14 @@ impl MockedCalls {
     /// Matches the mocked calls based on foundry rules. The matching is in the precedence order of:
     /// * Calls with value parameter and exact calldata match
     /// * Exact calldata matches
-    /// * Partial calldata matches
+    /// * Partial calldata matches, i.e. mocks whose calldata is a prefix of the actual calldata,
+    ///   such as a selector only. The longest prefix wins, and calls with value parameter win ties.
     pub(crate) fn get_matching_return_data(
         &self,
         code_address: H160,
         actual_calldata: &[u8],
         actual_value: U256,
-    ) -> Option<Vec<u8>> {
+    ) -> Option<MockCallReturn> {
         let mut best_match = None;
 
         for (call, call_return_data) in self.with_value.iter().chain(self.without_value.iter()) {
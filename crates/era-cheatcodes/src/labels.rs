@@ -0,0 +1,77 @@
+//! Labels assigned to addresses with `vm.label`, so that logs and failure messages can refer to
+//! the labeled contracts by name.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use zksync_basic_types::H160;
+
+/// The labels the [CheatcodeTracer](crate::cheatcodes::CheatcodeTracer) reads and records.
+///
+/// Like [RevertSiteSlot](crate::revert_site::RevertSiteSlot), clones of the labels start out empty
+/// and are independent, since executors are cloned for every call and calls may run concurrently.
+/// Use [Labels::share] to hand the labels to a tracer.
+#[derive(Debug, Default)]
+pub struct Labels(Arc<Mutex<HashMap<H160, String>>>);
+
+impl Clone for Labels {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl Labels {
+    /// Returns a handle to the same labels.
+    pub fn share(&self) -> Self {
+        Self(self.0.clone())
+    }
+
+    /// Returns the label of `address`, if any.
+    pub fn get(&self, address: H160) -> Option<String> {
+        self.0.lock().unwrap().get(&address).cloned()
+    }
+
+    /// Labels `address`, replacing its previous label.
+    pub fn insert(&self, address: H160, label: String) {
+        self.0.lock().unwrap().insert(address, label);
+    }
+
+    /// Adds the given labels, e.g. the ones assigned in the EVM.
+    pub fn extend(&self, labels: impl IntoIterator<Item = (H160, String)>) {
+        self.0.lock().unwrap().extend(labels);
+    }
+
+    /// Takes all the labels, leaving none.
+    pub fn take(&self) -> HashMap<H160, String> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+
+    /// Describes `address` for logs and failure messages, along with its label if any.
+    pub fn describe(&self, address: H160) -> String {
+        match self.get(address) {
+            Some(label) => format!("{label} ({address:?})"),
+            None => format!("{address:?}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_labels_but_not_clones() {
+        let labels = Labels::default();
+        let address = H160::repeat_byte(1);
+        labels.share().insert(address, "alice".to_string());
+        assert_eq!(labels.get(address).as_deref(), Some("alice"));
+        assert_eq!(labels.clone().get(address), None);
+
+        assert_eq!(labels.describe(address), "alice (0x0101010101010101010101010101010101010101)");
+        assert_eq!(labels.describe(H160::zero()), "0x0000000000000000000000000000000000000000");
+
+        assert_eq!(labels.take().len(), 1);
+        assert_eq!(labels.get(address), None);
+    }
+}
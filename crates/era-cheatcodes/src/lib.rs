@@ -1,4 +1,16 @@
+mod actions;
+pub mod breakpoints;
 pub mod cheatcodes;
+pub mod error;
 mod events;
 mod farcall;
+pub mod labels;
+pub mod plugins;
+pub mod profiler;
+mod returns;
+pub mod revert_site;
+pub mod script_wallets;
+mod snapshot_diff;
+pub mod support;
+pub mod tracers;
 mod utils;
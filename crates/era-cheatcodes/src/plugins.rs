@@ -0,0 +1,101 @@
+use std::{
+    fmt::{self, Debug},
+    sync::{Arc, Mutex},
+};
+
+use foundry_common::StorageModifications;
+use multivm::zk_evm_1_4_0::tracing::{AfterExecutionData, BeforeExecutionData, VmLocalStateData};
+use zksync_state::ReadStorage;
+
+/// An additional tracer that runs alongside the
+/// [CheatcodeTracer](crate::cheatcodes::CheatcodeTracer).
+///
+/// Plugins are notified of every executed opcode, as well as when a foundry test function starts
+/// and finishes executing. All hooks are no-ops by default, so implementors only need to override
+/// the ones they are interested in.
+pub trait TracerPlugin: Debug + Send {
+    /// Called when a foundry test function starts executing.
+    fn on_test_start(&mut self) {}
+
+    /// Called when a foundry test function has finished executing, with all the storage
+    /// modifications made so far, including the ones made by the test itself.
+    fn on_test_finish(&mut self, _storage_modifications: &StorageModifications) {}
+
+    /// Called before an opcode is executed.
+    fn before_execution(
+        &mut self,
+        _state: &VmLocalStateData<'_>,
+        _data: &BeforeExecutionData,
+        _storage: &mut dyn ReadStorage,
+    ) {
+    }
+
+    /// Called after an opcode is executed.
+    fn after_execution(
+        &mut self,
+        _state: &VmLocalStateData<'_>,
+        _data: &AfterExecutionData,
+        _storage: &mut dyn ReadStorage,
+    ) {
+    }
+}
+
+type PluginFactory = Arc<dyn Fn() -> Box<dyn TracerPlugin> + Send + Sync>;
+
+/// A shareable registry of [TracerPlugin]s.
+///
+/// The registry is cheap to clone, and all clones refer to the same set of plugins. Every tracer
+/// runs its own clones of the registered plugins, so that the tracers of tests running in parallel
+/// don't contend for them: plugins keeping state across transactions, or collecting results, must
+/// share it between their clones.
+#[derive(Default, Clone)]
+pub struct TracerPlugins(Arc<Mutex<Vec<PluginFactory>>>);
+
+impl Debug for TracerPlugins {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TracerPlugins").field(&self.0.lock().unwrap().len()).finish()
+    }
+}
+
+impl TracerPlugins {
+    /// Registers a new plugin, cloned for every tracer.
+    pub fn register(&self, plugin: impl TracerPlugin + Clone + Sync + 'static) {
+        self.0.lock().unwrap().push(Arc::new(move || Box::new(plugin.clone())));
+    }
+
+    /// Returns true if no plugins are registered.
+    pub fn is_empty(&self) -> bool {
+        self.0.lock().unwrap().is_empty()
+    }
+
+    /// Returns clones of the registered plugins for a tracer to run.
+    pub(crate) fn instantiate(&self) -> RunningPlugins {
+        RunningPlugins(self.0.lock().unwrap().iter().map(|factory| factory()).collect())
+    }
+}
+
+/// The clones of the registered [TracerPlugins] run by a tracer.
+///
+/// Clones start out without plugins, as the plugins run by a tracer aren't cloneable.
+#[derive(Debug, Default)]
+pub(crate) struct RunningPlugins(Vec<Box<dyn TracerPlugin>>);
+
+impl Clone for RunningPlugins {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl RunningPlugins {
+    /// Returns true if the tracer runs no plugins.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Calls `f` for every plugin, in registration order.
+    pub(crate) fn for_each(&mut self, mut f: impl FnMut(&mut dyn TracerPlugin)) {
+        for plugin in self.0.iter_mut() {
+            f(plugin.as_mut());
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! A [TracerPlugin] sampling the executed instructions, to find the hot spots of zkEVM execution.
+
+use crate::{cheatcodes::executed_bytecode_hash, plugins::TracerPlugin};
+use multivm::zk_evm_1_4_0::tracing::{BeforeExecutionData, VmLocalStateData};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use zksync_basic_types::{H160, H256};
+use zksync_state::ReadStorage;
+
+/// A far call frame of a sampled stack: the code being executed and its program counter, which is
+/// the call site for the frames below the innermost one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProfiledFrame {
+    pub bytecode_hash: H256,
+    pub pc: u16,
+}
+
+type Samples = HashMap<Vec<ProfiledFrame>, u64>;
+
+/// Samples every executed instruction together with the far call stack it was executed in.
+///
+/// Every clone samples on its own, e.g. the one run by the tracer of a test, and merges its
+/// samples into the ones shared by all clones once dropped. A clone can thus be registered as a
+/// plugin while another one is kept to read the merged samples.
+#[derive(Debug, Default)]
+pub struct ZkProfiler {
+    merged_samples: Arc<Mutex<Samples>>,
+    /// Samples of this clone, not merged yet.
+    samples: Samples,
+    /// Bytecode hashes of the executed contracts.
+    code_hashes: HashMap<H160, H256>,
+}
+
+impl ZkProfiler {
+    /// Returns the number of instructions executed in every sampled stack, outermost frame first,
+    /// by the clones dropped so far.
+    pub fn samples(&self) -> Samples {
+        self.merged_samples.lock().unwrap().clone()
+    }
+}
+
+impl Clone for ZkProfiler {
+    fn clone(&self) -> Self {
+        Self { merged_samples: self.merged_samples.clone(), ..Default::default() }
+    }
+}
+
+impl Drop for ZkProfiler {
+    fn drop(&mut self) {
+        if self.samples.is_empty() {
+            return
+        }
+        let mut merged_samples = self.merged_samples.lock().unwrap();
+        for (stack, count) in self.samples.drain() {
+            *merged_samples.entry(stack).or_default() += count;
+        }
+    }
+}
+
+impl TracerPlugin for ZkProfiler {
+    fn on_test_start(&mut self) {
+        // code may have been etched in between tests
+        self.code_hashes.clear();
+    }
+
+    fn before_execution(
+        &mut self,
+        state: &VmLocalStateData<'_>,
+        _data: &BeforeExecutionData,
+        storage: &mut dyn ReadStorage,
+    ) {
+        let callstack = &state.vm_local_state.callstack;
+        let mut stack: Vec<ProfiledFrame> = vec![];
+        for frame in callstack.inner.iter().chain([&callstack.current]) {
+            match stack.last_mut() {
+                // near calls stay within the code of the enclosing far call
+                Some(last) if frame.is_local_frame => last.pc = frame.pc,
+                _ => {
+                    let bytecode_hash = *self
+                        .code_hashes
+                        .entry(frame.code_address)
+                        .or_insert_with(|| executed_bytecode_hash(storage, &frame.code_address));
+                    stack.push(ProfiledFrame { bytecode_hash, pc: frame.pc });
+                }
+            }
+        }
+
+        *self.samples.entry(stack).or_default() += 1;
+    }
+}
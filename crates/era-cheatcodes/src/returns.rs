@@ -0,0 +1,87 @@
+//! Return data of cheatcode calls, pending until the cheatcode frame returns.
+
+use multivm::zk_evm_1_4_0::zkevm_opcode_defs::FatPointer;
+use std::collections::BTreeMap;
+use zksync_basic_types::U256;
+
+/// Return data of the cheatcode calls in progress, keyed by the callstack depth of their frame,
+/// so that the return of a cheatcode can't be clobbered by another one.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct PendingReturns {
+    frames: BTreeMap<usize, PendingReturn>,
+}
+
+#[derive(Debug, Clone)]
+struct PendingReturn {
+    data: Vec<U256>,
+    /// The returndata pointer of the frame, once it returned.
+    ptr: Option<FatPointer>,
+}
+
+impl PendingReturns {
+    /// Sets the data returned by the cheatcode frame at `depth`.
+    pub(crate) fn set(&mut self, depth: usize, data: Vec<U256>) {
+        self.frames.insert(depth, PendingReturn { data, ptr: None });
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Records that the execution returned to `depth`, with the given returndata pointer.
+    ///
+    /// Near returns within a cheatcode frame don't leave it, so only the frames deeper than `depth`
+    /// returned.
+    pub(crate) fn on_return(&mut self, depth: usize, ptr: FatPointer) {
+        for pending in self.frames.range_mut(depth + 1..).map(|(_, pending)| pending) {
+            pending.ptr.get_or_insert(ptr);
+        }
+    }
+
+    /// Takes the data of the frames that returned, along with their returndata pointer.
+    pub(crate) fn take_returned(&mut self) -> Vec<(FatPointer, Vec<U256>)> {
+        let returned = self
+            .frames
+            .iter()
+            .filter_map(|(depth, pending)| pending.ptr.is_some().then_some(*depth))
+            .collect::<Vec<_>>();
+        returned
+            .into_iter()
+            .filter_map(|depth| self.frames.remove(&depth))
+            .filter_map(|pending| Some((pending.ptr?, pending.data)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ptr(memory_page: u32) -> FatPointer {
+        FatPointer::from_u256(U256::from(memory_page) << 32)
+    }
+
+    #[test]
+    fn keeps_returns_of_frames_apart() {
+        let mut returns = PendingReturns::default();
+        returns.set(2, vec![U256::from(2)]);
+        returns.set(4, vec![U256::from(4)]);
+
+        // a near return within the frame at depth 4
+        returns.on_return(4, ptr(1));
+        assert!(returns.take_returned().is_empty());
+
+        returns.on_return(3, ptr(2));
+        let returned = returns.take_returned();
+        assert_eq!(returned.len(), 1);
+        assert_eq!(returned[0].0.memory_page, 2);
+        assert_eq!(returned[0].1, vec![U256::from(4)]);
+        assert!(!returns.is_empty());
+
+        returns.on_return(1, ptr(3));
+        let returned = returns.take_returned();
+        assert_eq!(returned[0].0.memory_page, 3);
+        assert_eq!(returned[0].1, vec![U256::from(2)]);
+        assert!(returns.is_empty());
+    }
+}
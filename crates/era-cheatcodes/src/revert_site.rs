@@ -0,0 +1,93 @@
+//! Location of the revert a transaction failed with, so that test failures can point at the
+//! reverting source line.
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use zksync_basic_types::{H160, H256};
+
+/// The instruction a transaction reverted at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertSite {
+    /// The contract whose code reverted.
+    pub address: H160,
+    /// Hash of the reverting code, identifying the compiled contract.
+    pub bytecode_hash: H256,
+    /// Program counter of the reverting instruction.
+    pub pc: u16,
+}
+
+/// The slot the [CheatcodeTracer](crate::cheatcodes::CheatcodeTracer) records the revert site of a
+/// transaction into.
+///
+/// Unlike [TracerPlugins](crate::plugins::TracerPlugins), clones of the slot start out empty and
+/// are independent, since executors are cloned for every call and calls may run concurrently. Use
+/// [RevertSiteSlot::share] to hand the slot to a tracer.
+#[derive(Debug, Default)]
+pub struct RevertSiteSlot(Arc<Mutex<Option<(usize, RevertSite)>>>);
+
+impl Clone for RevertSiteSlot {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl RevertSiteSlot {
+    /// Returns a handle to the same slot.
+    pub fn share(&self) -> Self {
+        Self(self.0.clone())
+    }
+
+    /// Takes the recorded revert site, if any.
+    pub fn take(&self) -> Option<RevertSite> {
+        self.0.lock().unwrap().take().map(|(_, site)| site)
+    }
+
+    /// Records a revert at the given call depth.
+    ///
+    /// Reverts bubbling up from a deeper frame keep the site of the innermost one, which is the
+    /// revert the transaction originated from.
+    pub(crate) fn record_revert(&self, depth: usize, site: RevertSite) {
+        let mut slot = self.0.lock().unwrap();
+        if !slot.is_some_and(|(recorded_depth, _)| recorded_depth > depth) {
+            *slot = Some((depth, site));
+        }
+    }
+
+    /// Records a successful return at the given call depth, dropping the reverts of deeper frames
+    /// since they were handled.
+    pub(crate) fn record_return(&self, depth: usize) {
+        let mut slot = self.0.lock().unwrap();
+        if slot.is_some_and(|(recorded_depth, _)| recorded_depth > depth) {
+            *slot = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn site(pc: u16) -> RevertSite {
+        RevertSite { address: H160::zero(), bytecode_hash: H256::zero(), pc }
+    }
+
+    #[test]
+    fn keeps_innermost_revert() {
+        let slot = RevertSiteSlot::default();
+        slot.record_revert(3, site(1));
+        slot.record_revert(2, site(2));
+        slot.record_revert(1, site(3));
+        assert_eq!(slot.share().take(), Some(site(1)));
+        assert_eq!(slot.take(), None);
+    }
+
+    #[test]
+    fn forgets_handled_reverts() {
+        let slot = RevertSiteSlot::default();
+        slot.record_revert(3, site(1));
+        slot.record_return(2);
+        slot.record_revert(2, site(2));
+        assert_eq!(slot.take(), Some(site(2)));
+    }
+}
@@ -0,0 +1,43 @@
+//! Wallets remembered with `vm.rememberKey`, so that scripts can broadcast with the keys they
+//! derive or read.
+
+use ethers::{
+    signers::{LocalWallet, Signer},
+    types::H160,
+};
+use std::sync::{Arc, Mutex};
+
+/// The wallets the [CheatcodeTracer](crate::cheatcodes::CheatcodeTracer) records.
+///
+/// Like [Labels](crate::labels::Labels), clones start out empty and are independent. Use
+/// [ScriptWallets::share] to hand the wallets to a tracer.
+#[derive(Debug, Default)]
+pub struct ScriptWallets(Arc<Mutex<Vec<LocalWallet>>>);
+
+impl Clone for ScriptWallets {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl ScriptWallets {
+    /// Returns a handle to the same wallets.
+    pub fn share(&self) -> Self {
+        Self(self.0.clone())
+    }
+
+    /// Remembers the wallet.
+    pub fn push(&self, wallet: LocalWallet) {
+        self.0.lock().unwrap().push(wallet);
+    }
+
+    /// Returns the addresses of the wallets.
+    pub fn addresses(&self) -> Vec<H160> {
+        self.0.lock().unwrap().iter().map(|wallet| wallet.address()).collect()
+    }
+
+    /// Takes all the wallets, leaving none.
+    pub fn take(&self) -> Vec<LocalWallet> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
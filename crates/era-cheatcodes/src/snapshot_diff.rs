@@ -0,0 +1,235 @@
+//! Structured diffs between the states saved by two snapshots.
+//!
+//! Balances and nonces are regular storage in the zkEVM, so the diff is computed over the
+//! modified storage of both snapshots, and the slots of the `L2EthToken` and `NonceHolder`
+//! system contracts are reported separately for the accounts they belong to.
+//!
+//! Balance slots are hashes of the accounts they belong to, so they're attributed by hashing the
+//! accounts known to the tracer, see [known_accounts]. The slots of unknown accounts are reported
+//! as storage changes of the `L2EthToken`.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use zksync_basic_types::{H160, H256, U256};
+use zksync_types::{
+    get_nonce_key, storage_key_for_eth_balance, utils::decompose_full_nonce, StorageKey,
+    ACCOUNT_CODE_STORAGE_ADDRESS, L2_ETH_TOKEN_ADDRESS, NONCE_HOLDER_ADDRESS,
+};
+use zksync_utils::h256_to_u256;
+
+/// The changes between two snapshots.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct SnapshotDiff {
+    pub storage: Vec<StorageChange>,
+    pub balances: Vec<BalanceChange>,
+    pub nonces: Vec<NonceChange>,
+}
+
+/// A changed storage slot.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct StorageChange {
+    pub address: H160,
+    pub slot: H256,
+    pub before: H256,
+    pub after: H256,
+}
+
+/// A changed base token balance.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct BalanceChange {
+    pub address: H160,
+    pub before: U256,
+    pub after: U256,
+}
+
+/// A changed nonce.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct NonceChange {
+    pub address: H160,
+    pub before: Nonce,
+    pub after: Nonce,
+}
+
+/// The nonces of an account, as kept by the `NonceHolder`.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Nonce {
+    pub tx_nonce: U256,
+    pub deployment_nonce: U256,
+}
+
+impl From<H256> for Nonce {
+    fn from(value: H256) -> Self {
+        let (tx_nonce, deployment_nonce) = decompose_full_nonce(h256_to_u256(value));
+        Self { tx_nonce, deployment_nonce }
+    }
+}
+
+/// Returns the accounts `keys` reveal: the contracts whose storage they're in, and the accounts
+/// whose code or nonce they're the slots of.
+pub fn known_accounts<'a>(keys: impl IntoIterator<Item = &'a StorageKey>) -> BTreeSet<H160> {
+    let mut accounts = BTreeSet::new();
+    for key in keys {
+        accounts.insert(*key.address());
+        if [ACCOUNT_CODE_STORAGE_ADDRESS, NONCE_HOLDER_ADDRESS].contains(key.address()) {
+            accounts.insert(H160::from_slice(&key.key()[12..]));
+        }
+    }
+    accounts
+}
+
+impl SnapshotDiff {
+    /// Computes the diff from the snapshot with modified storage `before` to the one with
+    /// `after`, reading the values of slots modified in only one of them with `read_original`.
+    /// Balance slots are attributed to the `accounts`, and to the accounts seen in the diff.
+    pub fn new(
+        before: &HashMap<StorageKey, H256>,
+        after: &HashMap<StorageKey, H256>,
+        mut accounts: BTreeSet<H160>,
+        mut read_original: impl FnMut(&StorageKey) -> H256,
+    ) -> Self {
+        let changes = before
+            .keys()
+            .chain(after.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter_map(|key| {
+                let before = before.get(key).copied().unwrap_or_else(|| read_original(key));
+                let after = after.get(key).copied().unwrap_or_else(|| read_original(key));
+                (before != after).then_some((*key, (before, after)))
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let mut diff = Self::default();
+        for (key, (before, after)) in &changes {
+            if *key.address() == NONCE_HOLDER_ADDRESS {
+                let address = H160::from_slice(&key.key()[12..]);
+                if get_nonce_key(&address) == *key {
+                    accounts.insert(address);
+                    diff.nonces.push(NonceChange {
+                        address,
+                        before: (*before).into(),
+                        after: (*after).into(),
+                    });
+                    continue
+                }
+            }
+            if *key.address() != L2_ETH_TOKEN_ADDRESS {
+                accounts.insert(*key.address());
+                diff.storage.push(StorageChange {
+                    address: *key.address(),
+                    slot: *key.key(),
+                    before: *before,
+                    after: *after,
+                });
+            }
+        }
+
+        let balance_keys = accounts
+            .into_iter()
+            .map(|address| (storage_key_for_eth_balance(&address), address))
+            .collect::<HashMap<_, _>>();
+        for (key, (before, after)) in &changes {
+            if *key.address() != L2_ETH_TOKEN_ADDRESS {
+                continue
+            }
+            match balance_keys.get(key) {
+                Some(address) => diff.balances.push(BalanceChange {
+                    address: *address,
+                    before: h256_to_u256(*before),
+                    after: h256_to_u256(*after),
+                }),
+                None => diff.storage.push(StorageChange {
+                    address: *key.address(),
+                    slot: *key.key(),
+                    before: *before,
+                    after: *after,
+                }),
+            }
+        }
+
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zksync_types::{utils::nonces_to_full_nonce, AccountTreeId};
+    use zksync_utils::u256_to_h256;
+
+    #[test]
+    fn diffs_storage_balances_and_nonces() {
+        let account = H160::repeat_byte(0x11);
+        let slot = StorageKey::new(AccountTreeId::new(account), H256::zero());
+        let untouched = StorageKey::new(AccountTreeId::new(account), H256::repeat_byte(1));
+
+        let before = HashMap::from([
+            (slot, H256::zero()),
+            (untouched, H256::repeat_byte(2)),
+            (storage_key_for_eth_balance(&account), u256_to_h256(U256::from(100))),
+        ]);
+        let after = HashMap::from([
+            (slot, H256::repeat_byte(3)),
+            (untouched, H256::repeat_byte(2)),
+            (storage_key_for_eth_balance(&account), u256_to_h256(U256::from(50))),
+            (
+                get_nonce_key(&account),
+                u256_to_h256(nonces_to_full_nonce(U256::from(1), U256::zero())),
+            ),
+        ]);
+
+        let diff = SnapshotDiff::new(&before, &after, BTreeSet::new(), |_| H256::zero());
+        assert_eq!(
+            diff,
+            SnapshotDiff {
+                storage: vec![StorageChange {
+                    address: account,
+                    slot: H256::zero(),
+                    before: H256::zero(),
+                    after: H256::repeat_byte(3),
+                }],
+                balances: vec![BalanceChange {
+                    address: account,
+                    before: U256::from(100),
+                    after: U256::from(50),
+                }],
+                nonces: vec![NonceChange {
+                    address: account,
+                    before: Nonce { tx_nonce: U256::zero(), deployment_nonce: U256::zero() },
+                    after: Nonce { tx_nonce: U256::from(1), deployment_nonce: U256::zero() },
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn attributes_balances_to_known_accounts() {
+        let account = H160::repeat_byte(0x22);
+        let balance = storage_key_for_eth_balance(&account);
+        let before = HashMap::from([(balance, H256::zero())]);
+        let after = HashMap::from([(balance, u256_to_h256(U256::from(1)))]);
+
+        // only the balance of the account changed, so the diff alone doesn't reveal it
+        let diff = SnapshotDiff::new(&before, &after, BTreeSet::new(), |_| H256::zero());
+        assert!(diff.balances.is_empty());
+        assert_eq!(diff.storage.len(), 1);
+
+        let code_key =
+            StorageKey::new(AccountTreeId::new(ACCOUNT_CODE_STORAGE_ADDRESS), H256::from(account));
+        let accounts = known_accounts([&code_key]);
+        assert!(accounts.contains(&account));
+        let diff = SnapshotDiff::new(&before, &after, accounts, |_| H256::zero());
+        assert_eq!(
+            diff,
+            SnapshotDiff {
+                balances: vec![BalanceChange {
+                    address: account,
+                    before: U256::zero(),
+                    after: U256::from(1),
+                }],
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Support of the `Vm` cheatcodes in the zkEVM.
+//!
+//! The report is derived from the cheatcodes `CheatcodeTracer::dispatch_cheatcode` dispatches,
+//! listed with `dispatched_cheatcodes!`. The dispatcher rejects the cheatcodes missing from the
+//! list before matching them, so that a new arm can't be reached without being listed.
+
+use foundry_cheatcodes_spec::Vm;
+use serde::Serialize;
+
+/// Lists the cheatcodes with an arm in the dispatcher, generating both [DISPATCHED_CHEATCODES] and
+/// [is_dispatched] from the `Vm::VmCalls` variants.
+macro_rules! dispatched_cheatcodes {
+    ($($id:ident),* $(,)?) => {
+        /// The identifiers of the cheatcodes dispatched in the zkEVM.
+        pub const DISPATCHED_CHEATCODES: &[&str] = &[$(stringify!($id)),*];
+
+        /// Returns `true` if the cheatcode is dispatched in the zkEVM.
+        pub(crate) fn is_dispatched(call: &Vm::VmCalls) -> bool {
+            matches!(call, $(Vm::VmCalls::$id(_))|*)
+        }
+    };
+}
+
+dispatched_cheatcodes! {
+    accesses,
+    activeFork,
+    addr,
+    assume,
+    assumeNoZkOutOfGas,
+    assumeZkGasBelow,
+    breakpoint_0,
+    breakpoint_1,
+    broadcastWallet,
+    clearMockedCalls,
+    createFork_0,
+    createFork_1,
+    createSelectFork_0,
+    createSelectFork_1,
+    createWallet_0,
+    createWallet_1,
+    createWallet_2,
+    deal,
+    deriveKey_0,
+    deriveKey_1,
+    deriveKey_2,
+    deriveKey_3,
+    diffSnapshots,
+    envAddress_0,
+    envAddress_1,
+    envBool_0,
+    envBool_1,
+    envBytes32_0,
+    envBytes32_1,
+    envBytes_0,
+    envBytes_1,
+    envInt_0,
+    envInt_1,
+    envOr_0,
+    envOr_1,
+    envOr_10,
+    envOr_11,
+    envOr_12,
+    envOr_13,
+    envOr_2,
+    envOr_3,
+    envOr_4,
+    envOr_5,
+    envOr_6,
+    envOr_7,
+    envOr_8,
+    envOr_9,
+    envString_0,
+    envString_1,
+    envUint_0,
+    envUint_1,
+    erc20Allowance,
+    erc20BalanceOf,
+    erc721BalanceOf,
+    erc721GetApproved,
+    erc721IsApprovedForAll,
+    erc721OwnerOf,
+    etch,
+    expectCall_0,
+    expectCall_1,
+    expectCall_2,
+    expectCall_3,
+    expectEmit_0,
+    expectEmit_1,
+    expectEmit_2,
+    expectEmit_4,
+    expectRevert_0,
+    expectRevert_1,
+    expectRevert_2,
+    expectRevert_3,
+    expectRevert_4,
+    expectRevert_5,
+    expectRevert_6,
+    expectRevert_7,
+    expectRevert_8,
+    ffi,
+    getCalls,
+    getCode,
+    getDeployedCode_0,
+    getDeployedCode_1,
+    getFoundryVersion,
+    getLabel,
+    getNonce_0,
+    getNonce_1,
+    getRecordedLogs_0,
+    getRecordedLogs_1,
+    getScriptWallets,
+    isPersistent,
+    isZkVm,
+    keyExists,
+    label,
+    load,
+    makePersistent_0,
+    makePersistent_1,
+    makePersistent_2,
+    makePersistent_3,
+    mockCall_0,
+    mockCall_1,
+    mockCallRevert_0,
+    mockCallRevert_1,
+    mockFunction,
+    parseJson_0,
+    parseJson_1,
+    parseJsonAddress,
+    parseJsonAddressArray,
+    parseJsonBool,
+    parseJsonBoolArray,
+    parseJsonBytes,
+    parseJsonBytes32,
+    parseJsonBytes32Array,
+    parseJsonBytesArray,
+    parseJsonInt,
+    parseJsonIntArray,
+    parseJsonKeys,
+    parseJsonString,
+    parseJsonStringArray,
+    parseJsonUint,
+    parseJsonUintArray,
+    prank_0,
+    prank_1,
+    prevrandao,
+    projectRoot,
+    publicKeyP256,
+    readCallers,
+    readFile,
+    record,
+    recordCalls,
+    recordLogs,
+    rememberKey,
+    revertTo,
+    revertToNamed,
+    revokePersistent_0,
+    revokePersistent_1,
+    roll,
+    rollFork_0,
+    rollFork_2,
+    rpcUrl,
+    rpcUrls,
+    selectFork,
+    serializeAddress_0,
+    serializeAddress_1,
+    serializeBool_0,
+    serializeBool_1,
+    serializeBytes32_0,
+    serializeBytes32_1,
+    serializeBytes_0,
+    serializeBytes_1,
+    serializeInt_0,
+    serializeInt_1,
+    serializeJson,
+    serializeString_0,
+    serializeString_1,
+    serializeUint_0,
+    serializeUint_1,
+    setEnv,
+    setNonce,
+    setTxOrigin,
+    sign_0,
+    sign_1,
+    signP256,
+    skip,
+    snapshot,
+    snapshotNamed,
+    startBroadcast_0,
+    startBroadcast_1,
+    startBroadcast_2,
+    startBroadcastWallet,
+    startPrank_0,
+    startPrank_1,
+    stopBroadcast,
+    stopPrank,
+    store,
+    storeBatch,
+    toString_0,
+    toString_1,
+    toString_2,
+    toString_3,
+    toString_4,
+    toString_5,
+    transact_0,
+    transact_1,
+    tryFfi,
+    txGasPrice,
+    txOrigin,
+    warp,
+    withStateOverrides_0,
+    withStateOverrides_1,
+    writeFile,
+    writeJson_0,
+    writeJson_1,
+    zkAssertCodeMatchesArtifact,
+    zkFailNextStorageWrite,
+    zkFinalizeWithdrawal,
+    zkProtocolVersion,
+    zkSetEmitOrdering,
+    zkSetGasPerPubdataLimit,
+    zkSetMaxFeePerGas,
+    zkSetNextCallGas,
+    zkSetTracing,
+}
+
+/// The dispatched cheatcodes that only implement part of their behavior.
+const PARTIAL_CHEATCODES: &[&str] = &["readCallers"];
+
+/// How much of a cheatcode is implemented in the zkEVM.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SupportStatus {
+    Supported,
+    Partial,
+    Unsupported,
+}
+
+impl std::fmt::Display for SupportStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Supported => "supported",
+            Self::Partial => "partial",
+            Self::Unsupported => "unsupported",
+        })
+    }
+}
+
+/// The support of a single cheatcode.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheatcodeSupport {
+    /// The cheatcode's identifier, e.g. `expectRevert_0`.
+    pub id: &'static str,
+    /// The Solidity signature of the cheatcode.
+    pub signature: &'static str,
+    /// The Solidity declaration of the cheatcode.
+    pub declaration: &'static str,
+    pub status: SupportStatus,
+}
+
+/// Returns the support status of every cheatcode of the `Vm` interface.
+pub fn cheatcode_support() -> Vec<CheatcodeSupport> {
+    Vm::CHEATCODES
+        .iter()
+        .map(|cheatcode| {
+            let id = cheatcode.func.id;
+            let status = if PARTIAL_CHEATCODES.contains(&id) {
+                SupportStatus::Partial
+            } else if DISPATCHED_CHEATCODES.contains(&id) {
+                SupportStatus::Supported
+            } else {
+                SupportStatus::Unsupported
+            };
+            CheatcodeSupport {
+                id,
+                signature: cheatcode.func.signature,
+                declaration: cheatcode.func.declaration,
+                status,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_partial_cheatcodes_as_dispatched() {
+        for id in PARTIAL_CHEATCODES {
+            assert!(DISPATCHED_CHEATCODES.contains(id), "{id} isn't dispatched");
+        }
+        assert!(is_dispatched(&Vm::VmCalls::warp(Vm::warpCall {
+            newTimestamp: Default::default()
+        })));
+    }
+
+    #[test]
+    fn reports_dispatched_cheatcodes() {
+        let support = cheatcode_support();
+        let status = |id| support.iter().find(|c| c.id == id).unwrap().status;
+        assert_eq!(status("warp"), SupportStatus::Supported);
+        assert_eq!(status("readCallers"), SupportStatus::Partial);
+        assert_eq!(support.len(), Vm::CHEATCODES.len());
+    }
+}
@@ -0,0 +1,228 @@
+//! Recording of the calls to broadcast, set with `vm.startBroadcast`.
+
+use super::merge_modified_bytecodes;
+use crate::{
+    cheatcodes::{get_calldata, CHEATCODE_ADDRESS, TEST_ADDRESS},
+    farcall::msg_value_call,
+    labels::Labels,
+};
+use ethers::types::TransactionRequest;
+use foundry_cheatcodes::{BroadcastableTransaction, BroadcastableTransactions, FeeOverrides};
+use foundry_common::StorageModifications;
+use foundry_evm_core::{
+    backend::DatabaseExt,
+    era_revm::{db::RevmDatabaseForEra, storage_view::StorageView},
+};
+use itertools::Itertools;
+use multivm::{
+    vm_latest::{HistoryMode, SimpleMemory},
+    zk_evm_1_4_0::tracing::VmLocalStateData,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+use zksync_basic_types::{AccountTreeId, H160, U256};
+use zksync_state::{ReadStorage, StoragePtr};
+use zksync_types::{get_nonce_key, utils::decompose_full_nonce, StorageKey};
+use zksync_utils::h256_to_u256;
+
+type EraDb<DB> = StorageView<RevmDatabaseForEra<DB>>;
+
+//same as `INTERNAL_CONTRACT_ADDRESSES`, except without
+// CONTRACT_DEPLOYER_ADDRESS
+// MSG_VALUE_SIMULATOR_ADDRESS
+// and with
+// CHEATCODE_ADDRESS
+const BROADCAST_IGNORED_CONTRACTS: [H160; 19] = [
+    zksync_types::BOOTLOADER_ADDRESS,
+    zksync_types::ACCOUNT_CODE_STORAGE_ADDRESS,
+    zksync_types::NONCE_HOLDER_ADDRESS,
+    zksync_types::KNOWN_CODES_STORAGE_ADDRESS,
+    zksync_types::IMMUTABLE_SIMULATOR_STORAGE_ADDRESS,
+    zksync_types::CONTRACT_FORCE_DEPLOYER_ADDRESS,
+    zksync_types::L1_MESSENGER_ADDRESS,
+    zksync_types::KECCAK256_PRECOMPILE_ADDRESS,
+    zksync_types::L2_ETH_TOKEN_ADDRESS,
+    zksync_types::SYSTEM_CONTEXT_ADDRESS,
+    zksync_types::BOOTLOADER_UTILITIES_ADDRESS,
+    zksync_types::EVENT_WRITER_ADDRESS,
+    zksync_types::COMPRESSOR_ADDRESS,
+    zksync_types::COMPLEX_UPGRADER_ADDRESS,
+    zksync_types::ECRECOVER_PRECOMPILE_ADDRESS,
+    zksync_types::SHA256_PRECOMPILE_ADDRESS,
+    zksync_types::MINT_AND_BURN_ADDRESS,
+    CHEATCODE_ADDRESS,
+    H160::zero(),
+];
+
+/// Records the calls made by the broadcasting contract as transactions to broadcast.
+#[derive(Debug, Default, Clone)]
+pub struct BroadcastTracer {
+    opts: Option<BroadcastOpts>,
+    transactions: Arc<RwLock<BroadcastableTransactions>>,
+    pub(crate) fee_overrides: FeeOverrides,
+    /// The nonce of the next transaction of each sender, by the RPC it's broadcast to, so that
+    /// switching between senders continues from their last broadcast transaction.
+    nonces: HashMap<(Option<String>, H160), U256>,
+    /// The depth of the call a single-call broadcast recorded, and the origin to restore once it
+    /// returns.
+    single_call_return: Option<(usize, H160)>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct BroadcastOpts {
+    pub(crate) original_origin: H160,
+    pub(crate) original_caller: H160,
+    pub(crate) new_origin: H160,
+    pub(crate) depth: usize,
+    /// Whether the broadcast stops by itself after the next call.
+    pub(crate) single_call: bool,
+}
+
+/// A call recorded for broadcast, which the dispatcher makes originate from the broadcaster.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BroadcastedCall {
+    /// The origin of the call.
+    pub(crate) origin: H160,
+    /// The nonce of the origin after the call, as if it was executed on-chain.
+    pub(crate) next_nonce: U256,
+}
+
+impl BroadcastTracer {
+    pub(crate) fn new(transactions: Arc<RwLock<BroadcastableTransactions>>) -> Self {
+        Self { transactions, ..Default::default() }
+    }
+
+    /// Returns whether a broadcast is ongoing.
+    pub(crate) fn is_active(&self) -> bool {
+        self.opts.is_some()
+    }
+
+    pub(crate) fn start(&mut self, opts: BroadcastOpts) {
+        self.opts = Some(opts);
+    }
+
+    /// Stops the ongoing broadcast, returning the origin to restore.
+    pub(crate) fn stop(&mut self) -> Option<H160> {
+        self.opts.take().map(|broadcast| broadcast.original_origin)
+    }
+
+    /// Returns the origin to restore once the call of a single-call broadcast returns, or once the
+    /// frame of the broadcasting contract returns, which only happens if `vm.stopBroadcast` wasn't
+    /// called.
+    pub(crate) fn on_return(&mut self, state: &VmLocalStateData<'_>) -> Option<H160> {
+        if let Some((depth, origin)) = self.single_call_return {
+            if state.vm_local_state.callstack.depth() < depth {
+                self.single_call_return = None;
+                return Some(origin)
+            }
+            return None
+        }
+
+        let broadcast = self.opts.as_ref()?;
+        (state.vm_local_state.callstack.current.code_address != CHEATCODE_ADDRESS &&
+            state.vm_local_state.callstack.depth() == broadcast.depth)
+            .then_some(broadcast.original_origin)
+    }
+
+    /// Records the far call that was just executed for broadcast, if it's made by the
+    /// broadcasting contract.
+    pub(crate) fn on_far_call<S: DatabaseExt + Send, H: HistoryMode>(
+        &mut self,
+        state: &VmLocalStateData<'_>,
+        memory: &SimpleMemory<H>,
+        storage: &StoragePtr<EraDb<S>>,
+        storage_modifications: &StorageModifications,
+        sender: H160,
+        labels: &Labels,
+    ) -> Option<BroadcastedCall> {
+        let broadcast = self.opts.as_ref()?;
+        let current = state.vm_local_state.callstack.current;
+        let prev_cs =
+            state.vm_local_state.callstack.inner.last().expect("callstack before the current");
+
+        if state.vm_local_state.callstack.depth() != broadcast.depth ||
+            prev_cs.this_address != broadcast.original_caller ||
+            BROADCAST_IGNORED_CONTRACTS.contains(&current.code_address)
+        {
+            return None
+        }
+
+        let new_origin = broadcast.new_origin;
+        let handle = &mut storage.borrow_mut();
+        let revm_db_for_era = &handle.storage_handle;
+        let rpc = revm_db_for_era.db.lock().unwrap().active_fork_url();
+
+        let calldata = get_calldata(state, memory);
+
+        let is_deployment = current.code_address == zksync_types::CONTRACT_DEPLOYER_ADDRESS;
+        let factory_deps = if is_deployment {
+            let test_contract_hash = handle.read_value(&StorageKey::new(
+                AccountTreeId::new(zksync_types::ACCOUNT_CODE_STORAGE_ADDRESS),
+                TEST_ADDRESS.into(),
+            ));
+
+            merge_modified_bytecodes(storage_modifications, vec![])
+                .iter()
+                .chain(storage_modifications.known_codes().iter())
+                .filter_map(|(k, v)| if k != &test_contract_hash { Some(v) } else { None })
+                .cloned()
+                .collect_vec()
+        } else {
+            vec![]
+        };
+
+        // used to determine whether the nonce should be decreased, since
+        // zkevm updates the nonce for the _sender_ already when we run the
+        // script/test function therefore, we should fix it and obtain the nonce
+        // that resembles the one to be used on-chain
+        let is_sender_also_caller = new_origin == sender;
+        let nonce_offset = if is_sender_also_caller { 1 } else { 0 };
+
+        let (account_nonce, _) =
+            decompose_full_nonce(h256_to_u256(handle.read_value(&get_nonce_key(&new_origin))));
+        let nonce = account_nonce.saturating_sub(nonce_offset.into());
+        // the nonce of the sender may have been reset in the meantime, e.g. by reverting to a
+        // snapshot or switching forks
+        let sender_key = (rpc.clone(), new_origin);
+        let nonce = self.nonces.get(&sender_key).map_or(nonce, |next| nonce.max(*next));
+        self.nonces.insert(sender_key, nonce + 1);
+
+        let (value, to) = match msg_value_call(state) {
+            Some((to, value)) => (Some(value), to),
+            None => (None, current.code_address),
+        };
+
+        let tx = BroadcastableTransaction {
+            rpc,
+            factory_deps,
+            fee_overrides: self.fee_overrides.clone(),
+            transaction: ethers::types::transaction::eip2718::TypedTransaction::Legacy(
+                TransactionRequest {
+                    from: Some(new_origin),
+                    to: Some(ethers::types::NameOrAddress::Address(to)),
+                    //TODO: set only if set manually by user in script
+                    gas: None,
+                    value,
+                    data: Some(calldata.into()),
+                    nonce: Some(nonce),
+                    ..Default::default()
+                },
+            ),
+        };
+        tracing::debug!(?tx, to = %labels.describe(to), "storing for broadcast");
+
+        self.transactions.write().unwrap().push_back(tx);
+
+        // the origin is restored once the call returns, the next ones aren't broadcast
+        if broadcast.single_call {
+            self.single_call_return = Some((broadcast.depth, broadcast.original_origin));
+            self.opts = None;
+        }
+
+        // we increase the nonce so that future calls will have the nonce
+        // increased, simulating the previous tx being executed
+        Some(BroadcastedCall { origin: new_origin, next_nonce: nonce + 1 + nonce_offset })
+    }
+}
@@ -0,0 +1,551 @@
+//! Expectations on the calls made and events emitted by a test, set with `vm.expectCall` and
+//! `vm.expectEmit`.
+
+use crate::{
+    cheatcodes::{get_calldata, INTERNAL_CONTRACT_ADDRESSES},
+    events::{parse_events, LogEntry},
+    labels::Labels,
+};
+use foundry_evm_core::{
+    backend::DatabaseExt,
+    era_revm::{db::RevmDatabaseForEra, storage_view::StorageView},
+};
+use itertools::Itertools;
+use multivm::{
+    vm_latest::{HistoryMode, SimpleMemory, ZkSyncVmState},
+    zk_evm_1_4_0::{
+        tracing::{AfterExecutionData, VmLocalStateData},
+        zkevm_opcode_defs::Opcode,
+    },
+};
+use serde::Serialize;
+use std::collections::{hash_map::Entry, HashMap};
+use zksync_basic_types::{H160, U256};
+
+type EraDb<DB> = StorageView<RevmDatabaseForEra<DB>>;
+
+/// Checks the calls and events expected by a test when it finishes.
+#[derive(Debug, Default, Clone)]
+pub struct ExpectationTracer {
+    expected_calls: ExpectedCallsTracker,
+    /// Calldata of the calls made since `vm.recordCalls`, per callee, queried with `vm.getCalls`.
+    /// `None` until the calls are recorded.
+    calls: Option<HashMap<H160, Vec<Vec<u8>>>>,
+    pub(crate) emit_config: EmitConfig,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EmitConfig {
+    pub(crate) expected_emit_state: ExpectedEmitState,
+    pub(crate) expect_emits_since: u32,
+    pub(crate) expect_emits_until: u32,
+    pub(crate) call_emits_since: u32,
+    pub(crate) call_emits_until: u32,
+    pub(crate) call_depth: usize,
+    pub(crate) checks: EmitChecks,
+    pub(crate) ordering: EmitOrdering,
+}
+
+/// How the expected events are matched against the emitted ones, as set by `vm.zkSetEmitOrdering`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum EmitOrdering {
+    /// The events must be emitted in order, other events may be emitted in between.
+    #[default]
+    Ordered,
+    /// The events must be emitted in order, without other events in between.
+    Consecutive,
+    /// The events may be emitted in any order.
+    Unordered,
+}
+
+impl TryFrom<u8> for EmitOrdering {
+    type Error = String;
+
+    fn try_from(ordering: u8) -> Result<Self, Self::Error> {
+        match ordering {
+            0 => Ok(Self::Ordered),
+            1 => Ok(Self::Consecutive),
+            2 => Ok(Self::Unordered),
+            _ => Err(format!("unknown emit ordering {ordering}, expected 0, 1 or 2")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EmitChecks {
+    pub(crate) address: Option<H160>,
+    /// Whether topics 1 to 3 are checked, the event signature always is.
+    pub(crate) topics: [bool; 3],
+    pub(crate) data: bool,
+}
+
+impl EmitChecks {
+    /// Returns the checks of an `expectEmit` whose topics marked in `wildcards` are left
+    /// unchecked, bit 0 standing for topic 1.
+    pub(crate) fn with_topic_wildcards(wildcards: u8, data: bool) -> Self {
+        Self { address: None, topics: [0, 1, 2].map(|bit| wildcards & (1 << bit) == 0), data }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Eq, Hash, PartialEq, Default)]
+pub(crate) enum ExpectedEmitState {
+    #[default]
+    NotStarted,
+    ExpectedEmitTriggered,
+    CallTriggered,
+    Assert,
+    Finished,
+}
+
+/// Tracks the expected calls per address.
+///
+/// For each address, we track the expected calls per call data. We track it in such manner
+/// so that we don't mix together calldatas that only contain selectors and calldatas that contain
+/// selector and arguments (partial and full matches).
+///
+/// This then allows us to customize the matching behavior for each call data on the
+/// `ExpectedCallData` struct and track how many times we've actually seen the call on the second
+/// element of the tuple.
+type ExpectedCallsTracker = HashMap<H160, HashMap<Vec<u8>, (ExpectedCallData, u64)>>;
+
+#[derive(Debug, Clone)]
+struct ExpectedCallData {
+    /// The expected value sent in the call
+    value: Option<U256>,
+    /// The number of times the call is expected to be made.
+    /// If the type of call is `NonCount`, this is the lower bound for the number of calls
+    /// that must be seen.
+    /// If the type of call is `Count`, this is the exact number of calls that must be seen.
+    count: u64,
+    /// The type of expected call.
+    call_type: ExpectedCallType,
+}
+
+/// The type of expected call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ExpectedCallType {
+    /// The call is expected to be made at least once.
+    NonCount,
+    /// The exact number of calls expected.
+    Count,
+}
+
+impl ExpectationTracer {
+    /// Expects the events emitted from now on, until the next far call returns, to be emitted by
+    /// that call.
+    pub(crate) fn expect_emit(&mut self, checks: EmitChecks, timestamp: u32) {
+        self.emit_config.expected_emit_state = ExpectedEmitState::ExpectedEmitTriggered;
+        self.emit_config.expect_emits_since = timestamp;
+        self.emit_config.checks = checks;
+    }
+
+    /// Tracks the far call the expected events are checked against, given an instruction
+    /// executed by a user contract.
+    pub(crate) fn track_emits(&mut self, state: &VmLocalStateData<'_>, data: &AfterExecutionData) {
+        if self.emit_config.expected_emit_state == ExpectedEmitState::ExpectedEmitTriggered {
+            //cheatcode triggered, waiting for far call
+            if let Opcode::FarCall(_call) = data.opcode.variant.opcode {
+                self.emit_config.call_emits_since = state.vm_local_state.timestamp;
+                self.emit_config.expect_emits_until = state.vm_local_state.timestamp;
+                self.emit_config.expected_emit_state = ExpectedEmitState::CallTriggered;
+                self.emit_config.call_depth = state.vm_local_state.callstack.depth();
+            }
+        }
+
+        if self.emit_config.expected_emit_state == ExpectedEmitState::CallTriggered &&
+            state.vm_local_state.callstack.depth() < self.emit_config.call_depth
+        {
+            self.emit_config.call_emits_until = state.vm_local_state.timestamp;
+        }
+    }
+
+    /// Asserts the expected calls were made, and triggers the assertion of the expected events on
+    /// the next cycle.
+    pub(crate) fn on_test_finish(&mut self, labels: &Labels) {
+        self.emit_config.expected_emit_state = ExpectedEmitState::Assert;
+
+        // TODO: change to proper revert
+        if let Some(failure) = self.unmet_expected_calls(labels).into_iter().next() {
+            panic!("{failure}");
+        }
+    }
+
+    /// Resets the settings that only last for a single test.
+    pub(crate) fn reset(&mut self) {
+        self.emit_config.ordering = EmitOrdering::default();
+        self.calls = None;
+    }
+
+    /// Asserts the expected events were emitted, once the test finished.
+    pub(crate) fn assert_emits<S: DatabaseExt + Send, H: HistoryMode>(
+        &mut self,
+        state: &mut ZkSyncVmState<EraDb<S>, H>,
+        transact_logs: &[LogEntry],
+    ) {
+        if self.emit_config.expected_emit_state != ExpectedEmitState::Assert {
+            return
+        }
+        self.emit_config.expected_emit_state = ExpectedEmitState::Finished;
+
+        let (expected_events_initial_dimension, _) =
+            state.event_sink.get_events_and_l2_l1_logs_after_timestamp(zksync_types::Timestamp(
+                self.emit_config.expect_emits_since,
+            ));
+        let expected_events_surplus = state
+            .event_sink
+            .get_events_and_l2_l1_logs_after_timestamp(zksync_types::Timestamp(
+                self.emit_config.expect_emits_until,
+            ))
+            .0
+            .len();
+
+        //remove n surplus events from the end of expected_events_initial_dimension
+        let expected_events = expected_events_initial_dimension
+            .clone()
+            .into_iter()
+            .take(expected_events_initial_dimension.len() - expected_events_surplus)
+            .collect::<Vec<_>>();
+        let expected_logs = parse_events(expected_events);
+
+        let (actual_events_initial_dimension, _) =
+            state.event_sink.get_events_and_l2_l1_logs_after_timestamp(zksync_types::Timestamp(
+                self.emit_config.call_emits_since,
+            ));
+        let actual_events_surplus = state
+            .event_sink
+            .get_events_and_l2_l1_logs_after_timestamp(zksync_types::Timestamp(
+                self.emit_config.call_emits_until,
+            ))
+            .0
+            .len();
+
+        //remove n surplus events from the end of actual_events_initial_dimension
+        let actual_events = actual_events_initial_dimension
+            .clone()
+            .into_iter()
+            .take(actual_events_initial_dimension.len() - actual_events_surplus)
+            .collect::<Vec<_>>();
+        let mut actual_logs = parse_events(actual_events);
+        actual_logs.extend(transact_logs.iter().cloned());
+
+        assert!(
+            compare_logs(
+                &expected_logs,
+                &actual_logs,
+                self.emit_config.checks.clone(),
+                self.emit_config.ordering,
+            ),
+            "expected logs were not emitted\nexpected:\n  {}\nactual:\n  {}",
+            expected_logs.iter().join("\n  "),
+            actual_logs.iter().join("\n  "),
+        );
+    }
+
+    /// Counts the far call that was just made towards the matching `expectCall`s, and records it
+    /// for `getCalls` once the calls are recorded.
+    ///
+    /// Every far call frame is considered regardless of its depth or caller, which includes
+    /// calls the test contract makes to itself and the constructor frames entered by the
+    /// `ContractDeployer` on deployments, whose calldata is the constructor input. Calls to
+    /// system contracts aren't recorded.
+    pub(crate) fn on_far_call<H: HistoryMode>(
+        &mut self,
+        state: &VmLocalStateData<'_>,
+        memory: &SimpleMemory<H>,
+    ) {
+        let current = state.vm_local_state.callstack.current;
+        let expected = self.expected_calls.contains_key(&current.code_address);
+        let recorded =
+            self.calls.is_some() && !INTERNAL_CONTRACT_ADDRESSES.contains(&current.code_address);
+        if !expected && !recorded {
+            return
+        }
+
+        let calldata = get_calldata(state, memory);
+        if expected {
+            self.record_call(current.code_address, &calldata, current.context_u128_value.into());
+        }
+        if let Some(calls) = self.calls.as_mut() {
+            if recorded {
+                calls.entry(current.code_address).or_default().push(calldata);
+            }
+        }
+    }
+
+    /// Starts recording the calls made, dropping the ones recorded so far.
+    pub(crate) fn record_calls(&mut self) {
+        self.calls = Some(HashMap::new());
+    }
+
+    /// Returns the number of calls made to `callee` since `vm.recordCalls` whose calldata starts
+    /// with `calldata`, or `None` if the calls aren't recorded.
+    pub(crate) fn get_calls(&self, callee: &H160, calldata: &[u8]) -> Option<usize> {
+        let calls = self.calls.as_ref()?;
+        Some(
+            calls
+                .get(callee)
+                .map_or(0, |calls| calls.iter().filter(|call| call.starts_with(calldata)).count()),
+        )
+    }
+
+    fn record_call(&mut self, callee: H160, calldata: &[u8], value: U256) {
+        let Some(expected_calls_for_target) = self.expected_calls.get_mut(&callee) else { return };
+
+        // Match every partial/full calldata
+        for (expected_calldata, (expected, actual_count)) in expected_calls_for_target {
+            // Increment actual times seen if...
+            // The calldata is at most, as big as this call's input, and
+            if expected_calldata.len() <= calldata.len() &&
+                // Both calldata match, taking the length of the assumed smaller one (which will have at least the selector), and
+                *expected_calldata == calldata[..expected_calldata.len()] &&
+                // The value matches, if provided
+                expected.value.map_or(true, |expected| expected == value)
+            {
+                *actual_count += 1;
+            }
+        }
+    }
+
+    /// Adds an expectCall to the tracker.
+    pub(crate) fn expect_call(
+        &mut self,
+        callee: &H160,
+        calldata: &Vec<u8>,
+        value: Option<U256>,
+        count: u64,
+        call_type: ExpectedCallType,
+    ) {
+        let expecteds = self.expected_calls.entry(*callee).or_default();
+
+        match call_type {
+            ExpectedCallType::Count => {
+                // Get the expected calls for this target.
+                // In this case, as we're using counted expectCalls, we should not be able to set
+                // them more than once.
+                assert!(
+                    !expecteds.contains_key(calldata),
+                    "counted expected calls can only bet set once"
+                );
+                expecteds
+                    .insert(calldata.to_vec(), (ExpectedCallData { value, count, call_type }, 0));
+            }
+            ExpectedCallType::NonCount => {
+                // Check if the expected calldata exists.
+                // If it does, increment the count by one as we expect to see it one more time.
+                match expecteds.entry(calldata.clone()) {
+                    Entry::Occupied(mut entry) => {
+                        let (expected, _) = entry.get_mut();
+                        // Ensure we're not overwriting a counted expectCall.
+                        assert!(
+                            expected.call_type == ExpectedCallType::NonCount,
+                            "cannot overwrite a counted expectCall with a non-counted expectCall"
+                        );
+                        expected.count += 1;
+                    }
+                    // If it does not exist, then create it.
+                    Entry::Vacant(entry) => {
+                        entry.insert((ExpectedCallData { value, count, call_type }, 0));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns a description of every expected call that wasn't made as expected.
+    fn unmet_expected_calls(&self, labels: &Labels) -> Vec<String> {
+        let mut failures = vec![];
+        for (address, expected_calls_for_target) in &self.expected_calls {
+            for (expected_calldata, (expected, actual_count)) in expected_calls_for_target {
+                let failed = match expected.call_type {
+                    // If the cheatcode was called with a `count` argument,
+                    // we must check that the EVM performed a CALL with this calldata exactly
+                    // `count` times.
+                    ExpectedCallType::Count => expected.count != *actual_count,
+                    // If the cheatcode was called without a `count` argument,
+                    // we must check that the EVM performed a CALL with this calldata at least
+                    // `count` times. The amount of times to check was
+                    // the amount of time the cheatcode was called.
+                    ExpectedCallType::NonCount => expected.count > *actual_count,
+                };
+                if failed {
+                    failures.push(format!(
+                        "Expected call to {} with data {:?} was found {} times, expected {}",
+                        labels.describe(*address),
+                        expected_calldata,
+                        actual_count,
+                        expected.count
+                    ));
+                }
+            }
+        }
+        failures
+    }
+}
+
+fn compare_logs(
+    expected_logs: &[LogEntry],
+    actual_logs: &[LogEntry],
+    checks: EmitChecks,
+    ordering: EmitOrdering,
+) -> bool {
+    // with an emitter given, the logs of other contracts can't break the sequence
+    let actual_logs: Vec<_> = match checks.address {
+        Some(emitter) => actual_logs.iter().filter(|log| log.address == emitter).cloned().collect(),
+        None => actual_logs.to_vec(),
+    };
+    let matches =
+        |expected: &LogEntry, actual: &LogEntry| are_logs_equal(expected, actual, &checks);
+    match ordering {
+        EmitOrdering::Ordered => {
+            let mut actual_iter = actual_logs.iter();
+            expected_logs
+                .iter()
+                .all(|expected| actual_iter.by_ref().any(|actual| matches(expected, actual)))
+        }
+        EmitOrdering::Consecutive => {
+            expected_logs.is_empty() ||
+                actual_logs.windows(expected_logs.len()).any(|window| {
+                    expected_logs
+                        .iter()
+                        .zip(window)
+                        .all(|(expected, actual)| matches(expected, actual))
+                })
+        }
+        EmitOrdering::Unordered => {
+            let mut used = vec![false; actual_logs.len()];
+            expected_logs.iter().all(|expected| {
+                let Some(index) = actual_logs
+                    .iter()
+                    .enumerate()
+                    .position(|(i, actual)| !used[i] && matches(expected, actual))
+                else {
+                    return false
+                };
+                used[index] = true;
+                true
+            })
+        }
+    }
+}
+
+fn are_logs_equal(a: &LogEntry, b: &LogEntry, emit_checks: &EmitChecks) -> bool {
+    // topics beyond the ones the checks apply to, e.g. of system contracts' events, are compared
+    let topics_match = a.topics.len() == b.topics.len() &&
+        a.topics.iter().zip(&b.topics).enumerate().all(|(i, (a, b))| {
+            let check = i == 0 || emit_checks.topics.get(i - 1).copied().unwrap_or(true);
+            !check || a == b
+        });
+
+    let data_match = if emit_checks.data { a.data == b.data } else { true };
+
+    topics_match && data_match
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_expected_calls() {
+        let mut expectations = ExpectationTracer::default();
+        let labels = Labels::default();
+        let callee = H160::repeat_byte(1);
+        expectations.expect_call(&callee, &vec![1], None, 1, ExpectedCallType::NonCount);
+        expectations.expect_call(
+            &callee,
+            &vec![1, 2],
+            Some(U256::one()),
+            2,
+            ExpectedCallType::Count,
+        );
+        assert_eq!(expectations.unmet_expected_calls(&labels).len(), 2);
+
+        expectations.record_call(callee, &[1, 2, 3], U256::zero());
+        assert_eq!(expectations.unmet_expected_calls(&labels).len(), 1);
+
+        expectations.record_call(callee, &[1, 2], U256::one());
+        expectations.record_call(callee, &[1, 2], U256::one());
+        assert!(expectations.unmet_expected_calls(&labels).is_empty());
+
+        expectations.record_call(callee, &[1, 2], U256::one());
+        assert_eq!(expectations.unmet_expected_calls(&labels).len(), 1);
+
+        labels.insert(callee, "callee".to_string());
+        assert!(expectations.unmet_expected_calls(&labels)[0].starts_with(
+            "Expected call to callee (0x0101010101010101010101010101010101010101) with data"
+        ));
+    }
+
+    #[test]
+    fn counts_calls_made() {
+        let mut expectations = ExpectationTracer::default();
+        let callee = H160::repeat_byte(1);
+        assert_eq!(expectations.get_calls(&callee, &[]), None);
+
+        expectations.record_calls();
+        assert_eq!(expectations.get_calls(&callee, &[]), Some(0));
+
+        expectations.calls.as_mut().unwrap().insert(callee, vec![vec![1, 2], vec![1, 3], vec![2]]);
+        assert_eq!(expectations.get_calls(&callee, &[]), Some(3));
+        assert_eq!(expectations.get_calls(&callee, &[1]), Some(2));
+        assert_eq!(expectations.get_calls(&callee, &[1, 2]), Some(1));
+        assert_eq!(expectations.get_calls(&callee, &[1, 2, 3]), Some(0));
+        assert_eq!(expectations.get_calls(&H160::zero(), &[]), Some(0));
+
+        expectations.reset();
+        assert_eq!(expectations.get_calls(&callee, &[]), None);
+    }
+
+    #[test]
+    fn parses_emit_orderings() {
+        assert_eq!(EmitOrdering::try_from(0), Ok(EmitOrdering::Ordered));
+        assert_eq!(EmitOrdering::try_from(1), Ok(EmitOrdering::Consecutive));
+        assert_eq!(EmitOrdering::try_from(2), Ok(EmitOrdering::Unordered));
+        assert!(EmitOrdering::try_from(3).is_err());
+    }
+
+    #[test]
+    fn compares_logs() {
+        let log = |topic: u8| LogEntry {
+            address: H160::zero(),
+            topics: vec![zksync_basic_types::H256::repeat_byte(topic)],
+            ..Default::default()
+        };
+        let checks = EmitChecks { address: None, topics: [true; 3], data: true };
+        let expected = [log(1), log(2)];
+        let actual = [log(1), log(3), log(2)];
+
+        assert!(compare_logs(&expected, &actual, checks.clone(), EmitOrdering::Ordered));
+        assert!(!compare_logs(&expected, &actual, checks.clone(), EmitOrdering::Consecutive));
+        assert!(compare_logs(&[log(2), log(1)], &actual, checks.clone(), EmitOrdering::Unordered));
+        assert!(!compare_logs(&[log(2), log(1)], &actual, checks, EmitOrdering::Ordered));
+    }
+
+    #[test]
+    fn skips_wildcard_topics() {
+        let log = |topics: [u8; 4]| LogEntry {
+            address: H160::zero(),
+            topics: topics.map(zksync_basic_types::H256::repeat_byte).to_vec(),
+            ..Default::default()
+        };
+        let expected = log([1, 2, 3, 4]);
+
+        let checks = EmitChecks::with_topic_wildcards(0b010, true);
+        assert_eq!(checks.topics, [true, false, true]);
+        assert!(are_logs_equal(&expected, &log([1, 2, 9, 4]), &checks));
+        assert!(!are_logs_equal(&expected, &log([1, 9, 3, 4]), &checks));
+        assert!(!are_logs_equal(&expected, &log([9, 2, 3, 4]), &checks));
+
+        let checks = EmitChecks::with_topic_wildcards(0b111, true);
+        assert!(are_logs_equal(&expected, &log([1, 7, 8, 9]), &checks));
+
+        let log = |topics: &[u8]| LogEntry {
+            topics: topics.iter().copied().map(zksync_basic_types::H256::repeat_byte).collect(),
+            ..Default::default()
+        };
+        let expected = log(&[1, 2, 3, 4, 5]);
+        assert!(are_logs_equal(&expected, &log(&[1, 9, 9, 9, 5]), &checks));
+        assert!(!are_logs_equal(&expected, &log(&[1, 2, 3, 4, 9]), &checks));
+        assert!(!are_logs_equal(&expected, &log(&[1, 2, 3, 4]), &checks));
+    }
+}
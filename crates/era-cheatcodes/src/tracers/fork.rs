@@ -0,0 +1,481 @@
+//! Forks and snapshots, created with `vm.createFork` and `vm.snapshot`.
+
+use super::{merge_modified_bytecodes, merge_modified_storage};
+use crate::{
+    cheatcodes::ToZkEvmResult,
+    events::LogEntry,
+    utils::{ToH160, ToU256},
+};
+use era_test_node::utils::bytecode_to_factory_dep;
+use foundry_cheatcodes::CheatsConfig;
+use foundry_common::{conversion_utils::h160_to_address, StorageModifications};
+use foundry_evm_core::{
+    backend::DatabaseExt,
+    era_revm::{db::RevmDatabaseForEra, storage_view::StorageView, transactions::storage_to_state},
+    fork::{zksync_provider::ensure_zksync_endpoint, CreateFork},
+    opts::EvmOpts,
+};
+use itertools::Itertools;
+use multivm::vm_latest::{BootloaderState, HistoryMode, L1BatchEnv, SystemEnv, ZkSyncVmState};
+use revm::{
+    primitives::{ruint::Uint, BlockEnv, CfgEnv, Env, SpecId, U256 as rU256},
+    JournaledState,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+use zksync_basic_types::{H160, H256, U256};
+use zksync_state::StoragePtr;
+use zksync_types::{StorageKey, Timestamp};
+use zksync_utils::h256_to_u256;
+
+type EraDb<DB> = StorageView<RevmDatabaseForEra<DB>>;
+
+#[derive(Debug, Clone)]
+pub(crate) struct EraEnv {
+    pub(crate) l1_batch_env: L1BatchEnv,
+    pub(crate) system_env: SystemEnv,
+}
+
+/// Creates, selects and rolls forks, and takes and reverts to snapshots, at the end of the cycle
+/// of the cheatcode requesting it.
+#[derive(Debug, Default, Clone)]
+pub struct ForkTracer {
+    pub(crate) saved_snapshots: HashMap<U256, SavedSnapshot>,
+    pub(crate) named_snapshots: HashMap<String, U256>,
+    /// Fork endpoints known to be zkSync nodes.
+    zksync_endpoints: HashSet<String>,
+    /// Logs emitted by the transactions executed with `vm.transact`.
+    pub(crate) transact_logs: Vec<LogEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SavedSnapshot {
+    pub(crate) modified_storage: HashMap<StorageKey, H256>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum ForkAction {
+    CreateSelectFork { url_or_alias: String, block_number: Option<u64> },
+    CreateFork { url_or_alias: String, block_number: Option<u64> },
+    RollFork { block_number: Uint<256, 4>, fork_id: Option<Uint<256, 4>> },
+    SelectFork { fork_id: U256 },
+    RevertToSnapshot { snapshot_id: U256 },
+    Snapshot { name: Option<String> },
+    Transact { fork_id: Option<U256>, tx_hash: H256 },
+    MakePersistentAccount { account: H160 },
+    MakePersistentAccounts { accounts: Vec<H160> },
+    RevokePersistentAccount { account: H160 },
+    RevokePersistentAccounts { accounts: Vec<H160> },
+}
+
+/// What the [ForkTracer] needs from the dispatcher to apply a [ForkAction].
+pub(crate) struct ForkContext<'a> {
+    pub(crate) env: &'a EraEnv,
+    pub(crate) config: &'a Arc<CheatsConfig>,
+    pub(crate) storage_modifications: &'a StorageModifications,
+}
+
+impl ForkTracer {
+    /// Checks that the fork endpoint is a zkSync node before forking it, since the zkEVM can only
+    /// run on the state of zkSync chains.
+    pub(crate) fn ensure_zksync_fork(
+        &mut self,
+        config: &CheatsConfig,
+        url_or_alias: &str,
+    ) -> eyre::Result<()> {
+        let url = config.rpc_url(url_or_alias).map_err(|err| eyre::eyre!("{err}"))?;
+        if !self.zksync_endpoints.contains(&url) {
+            ensure_zksync_endpoint(&url)?;
+            self.zksync_endpoints.insert(url);
+        }
+        Ok(())
+    }
+
+    /// Applies the action, carrying the storage modified so far over to the new fork, and returns
+    /// the data the cheatcode that requested it returns, if any.
+    pub(crate) fn apply<S: DatabaseExt + Send, H: HistoryMode>(
+        &mut self,
+        action: ForkAction,
+        state: &mut ZkSyncVmState<EraDb<S>, H>,
+        bootloader_state: &mut BootloaderState,
+        storage: &StoragePtr<EraDb<S>>,
+        ctx: ForkContext<'_>,
+    ) -> Option<Vec<U256>> {
+        let mut return_data = None;
+        match action {
+            ForkAction::MakePersistentAccount { account } => {
+                let era_db: &RevmDatabaseForEra<S> = &storage.borrow_mut().storage_handle;
+
+                let mut db = era_db.db.lock().unwrap();
+                db.add_persistent_account(revm::primitives::Address::from(
+                    account.to_fixed_bytes(),
+                ));
+            }
+            ForkAction::MakePersistentAccounts { accounts } => {
+                let era_db: &RevmDatabaseForEra<S> = &storage.borrow_mut().storage_handle;
+
+                let mut db = era_db.db.lock().unwrap();
+                db.extend_persistent_accounts(
+                    accounts
+                        .into_iter()
+                        .map(|a: H160| revm::primitives::Address::from(a.to_fixed_bytes()))
+                        .collect::<Vec<revm::primitives::Address>>(),
+                );
+            }
+            ForkAction::RevokePersistentAccount { account } => {
+                let era_db: &RevmDatabaseForEra<S> = &storage.borrow_mut().storage_handle;
+                let mut db = era_db.db.lock().unwrap();
+                db.remove_persistent_account(&revm::primitives::Address::from(
+                    account.to_fixed_bytes(),
+                ));
+            }
+            ForkAction::RevokePersistentAccounts { accounts } => {
+                let era_db: &RevmDatabaseForEra<S> = &storage.borrow_mut().storage_handle;
+                let mut db = era_db.db.lock().unwrap();
+                db.remove_persistent_accounts(
+                    accounts
+                        .into_iter()
+                        .map(|a: H160| revm::primitives::Address::from(a.to_fixed_bytes()))
+                        .collect::<Vec<revm::primitives::Address>>(),
+                );
+            }
+            ForkAction::CreateSelectFork { url_or_alias, block_number } => {
+                let mut storage = storage.borrow_mut();
+                let modified_storage = merge_modified_storage(
+                    ctx.storage_modifications,
+                    storage.modified_storage_keys(),
+                );
+                let modified_bytecodes = merge_modified_bytecodes(
+                    ctx.storage_modifications,
+                    bootloader_state.get_last_tx_compressed_bytecodes(),
+                );
+
+                storage.clean_cache();
+                let fork_id = {
+                    let era_db: &RevmDatabaseForEra<S> = &storage.storage_handle;
+                    let bytecodes = into_revm_bytecodes(modified_bytecodes.clone());
+                    state.decommittment_processor.populate(
+                        bytecodes
+                            .clone()
+                            .into_iter()
+                            .filter(|(key, _)| {
+                                !state
+                                    .decommittment_processor
+                                    .known_bytecodes
+                                    .inner()
+                                    .contains_key(key)
+                            })
+                            .collect(),
+                        Timestamp(state.local_state.timestamp),
+                    );
+
+                    let mut journaled_state = JournaledState::new(SpecId::LATEST, vec![]);
+                    journaled_state.state = storage_to_state(era_db, &modified_storage, bytecodes);
+
+                    let mut db = era_db.db.lock().unwrap();
+                    let era_env = ctx.env;
+                    let mut env = into_revm_env(era_env);
+                    db.create_select_fork(
+                        create_fork_request(
+                            era_env,
+                            ctx.config.clone(),
+                            block_number,
+                            &url_or_alias,
+                        ),
+                        &mut env,
+                        &mut journaled_state,
+                    )
+                };
+                storage.modified_storage_keys = modified_storage;
+
+                return_data = Some(fork_id.unwrap().to_return_data());
+            }
+            ForkAction::CreateFork { url_or_alias, block_number } => {
+                let era_db = &storage.borrow_mut().storage_handle;
+                let mut db = era_db.db.lock().unwrap();
+                let era_env = ctx.env;
+                let fork_id = db
+                    .create_fork(create_fork_request(
+                        era_env,
+                        ctx.config.clone(),
+                        block_number,
+                        &url_or_alias,
+                    ))
+                    .unwrap();
+                return_data = Some(fork_id.to_return_data());
+            }
+            ForkAction::RollFork { block_number, fork_id } => {
+                let modified_storage = merge_modified_storage(
+                    ctx.storage_modifications,
+                    storage.borrow_mut().modified_storage_keys(),
+                );
+                let modified_bytecodes = merge_modified_bytecodes(
+                    ctx.storage_modifications,
+                    bootloader_state.get_last_tx_compressed_bytecodes(),
+                );
+
+                let mut storage = storage.borrow_mut();
+
+                storage.clean_cache();
+                {
+                    let era_db = &storage.storage_handle;
+                    let bytecodes = into_revm_bytecodes(modified_bytecodes.clone());
+                    state.decommittment_processor.populate(
+                        bytecodes
+                            .clone()
+                            .into_iter()
+                            .filter(|(key, _)| {
+                                !state
+                                    .decommittment_processor
+                                    .known_bytecodes
+                                    .inner()
+                                    .contains_key(key)
+                            })
+                            .collect(),
+                        Timestamp(state.local_state.timestamp),
+                    );
+
+                    let mut journaled_state = JournaledState::new(SpecId::LATEST, vec![]);
+                    journaled_state.state = storage_to_state(era_db, &modified_storage, bytecodes);
+
+                    let mut db = era_db.db.lock().unwrap();
+                    let era_env = ctx.env;
+                    let mut env = into_revm_env(era_env);
+                    db.roll_fork(fork_id, block_number, &mut env, &mut journaled_state).unwrap();
+                };
+                storage.modified_storage_keys = modified_storage;
+            }
+            ForkAction::SelectFork { fork_id } => {
+                let mut storage = storage.borrow_mut();
+                let modified_storage = merge_modified_storage(
+                    ctx.storage_modifications,
+                    storage.modified_storage_keys(),
+                );
+                let modified_bytecodes = merge_modified_bytecodes(
+                    ctx.storage_modifications,
+                    bootloader_state.get_last_tx_compressed_bytecodes(),
+                );
+                {
+                    storage.clean_cache();
+                    let era_db = &storage.storage_handle;
+                    let bytecodes = into_revm_bytecodes(modified_bytecodes.clone());
+                    state.decommittment_processor.populate(
+                        bytecodes
+                            .clone()
+                            .into_iter()
+                            .filter(|(key, _)| {
+                                !state
+                                    .decommittment_processor
+                                    .known_bytecodes
+                                    .inner()
+                                    .contains_key(key)
+                            })
+                            .collect(),
+                        Timestamp(state.local_state.timestamp),
+                    );
+
+                    let mut journaled_state = JournaledState::new(SpecId::LATEST, vec![]);
+                    journaled_state.state = storage_to_state(era_db, &modified_storage, bytecodes);
+
+                    let mut db = era_db.db.lock().unwrap();
+                    let era_env = ctx.env;
+                    let mut env = into_revm_env(era_env);
+                    db.select_fork(rU256::from(fork_id.as_u128()), &mut env, &mut journaled_state)
+                        .unwrap();
+                }
+                storage.modified_storage_keys = modified_storage;
+
+                return_data = Some(vec![fork_id]);
+            }
+            ForkAction::RevertToSnapshot { snapshot_id } => {
+                let mut storage = storage.borrow_mut();
+                let modified_storage = merge_modified_storage(
+                    ctx.storage_modifications,
+                    storage.modified_storage_keys(),
+                );
+                storage.clean_cache();
+
+                {
+                    let era_db = &storage.storage_handle;
+                    let bytecodes = bootloader_state
+                        .get_last_tx_compressed_bytecodes()
+                        .iter()
+                        .map(|b| bytecode_to_factory_dep(b.original.clone()))
+                        .collect();
+
+                    let mut journaled_state = JournaledState::new(SpecId::LATEST, vec![]);
+                    journaled_state.state = storage_to_state(era_db, &modified_storage, bytecodes);
+
+                    let mut db = era_db.db.lock().unwrap();
+                    let era_env = ctx.env;
+                    let mut env = into_revm_env(era_env);
+                    db.revert(Uint::from_limbs(snapshot_id.0), &journaled_state, &mut env);
+                }
+
+                storage.modified_storage_keys =
+                    self.saved_snapshots.remove(&snapshot_id).unwrap().modified_storage;
+                // reverting deletes the snapshot and all snapshots taken after it
+                self.named_snapshots.retain(|_, id| *id < snapshot_id);
+            }
+            ForkAction::Snapshot { name } => {
+                let mut storage = storage.borrow_mut();
+                let modified_storage = merge_modified_storage(
+                    ctx.storage_modifications,
+                    storage.modified_storage_keys(),
+                );
+
+                storage.clean_cache();
+
+                let snapshot_id = {
+                    let era_db = &storage.storage_handle;
+                    let bytecodes = bootloader_state
+                        .get_last_tx_compressed_bytecodes()
+                        .iter()
+                        .map(|b| bytecode_to_factory_dep(b.original.clone()))
+                        .collect();
+
+                    let mut journaled_state = JournaledState::new(SpecId::LATEST, vec![]);
+                    journaled_state.state = storage_to_state(era_db, &modified_storage, bytecodes);
+
+                    let mut db = era_db.db.lock().unwrap();
+                    let era_env = ctx.env;
+                    let env = into_revm_env(era_env);
+                    let snapshot_id = db.snapshot(&journaled_state, &env);
+
+                    self.saved_snapshots.insert(
+                        snapshot_id.to_u256(),
+                        SavedSnapshot { modified_storage: modified_storage.clone() },
+                    );
+                    snapshot_id
+                };
+
+                if let Some(name) = name {
+                    self.named_snapshots.insert(name, snapshot_id.to_u256());
+                }
+                storage.modified_storage_keys = modified_storage;
+                return_data = Some(snapshot_id.to_return_data());
+            }
+            ForkAction::Transact { fork_id, tx_hash } => {
+                let journaled_state = {
+                    let bytecodes = bootloader_state
+                        .get_last_tx_compressed_bytecodes()
+                        .iter()
+                        .map(|b| bytecode_to_factory_dep(b.original.clone()))
+                        .collect();
+
+                    let storage = storage.borrow_mut();
+                    let modified_storage = merge_modified_storage(
+                        ctx.storage_modifications,
+                        storage.modified_storage_keys(),
+                    );
+
+                    let era_db = &storage.storage_handle;
+
+                    let mut journaled_state = JournaledState::new(SpecId::LATEST, vec![]);
+                    journaled_state.state = storage_to_state(era_db, &modified_storage, bytecodes);
+
+                    let mut db = era_db.db.lock().unwrap();
+                    let era_env = ctx.env;
+                    let mut env = into_revm_env(era_env);
+
+                    db.transact(
+                        fork_id.map(|id| {
+                            let mut arr = [0; 32];
+                            id.to_big_endian(&mut arr);
+                            Uint::from_be_bytes(arr)
+                        }),
+                        tx_hash.to_fixed_bytes().into(),
+                        &mut env,
+                        &mut journaled_state,
+                        &mut revm::inspectors::NoOpInspector,
+                    )
+                    .unwrap();
+
+                    journaled_state
+                };
+
+                storage.borrow_mut().read_storage_keys = Default::default();
+
+                for (index, log) in journaled_state.logs.into_iter().enumerate() {
+                    self.transact_logs.push(LogEntry {
+                        address: log.address.to_h160(),
+                        data: log.data.to_vec(),
+                        topics: log.topics.iter().map(|b| H256::from_slice(b.as_slice())).collect(),
+                        index,
+                        ..Default::default()
+                    })
+                }
+            }
+        }
+        return_data
+    }
+}
+
+fn into_revm_bytecodes(zk_bytecodes: HashMap<H256, Vec<u8>>) -> HashMap<U256, Vec<U256>> {
+    zk_bytecodes
+        .into_iter()
+        .map(|(key, value)| {
+            let key = h256_to_u256(key);
+            let value = value.chunks(32).map(U256::from).collect_vec();
+            (key, value)
+        })
+        .collect()
+}
+
+fn into_revm_env(env: &EraEnv) -> Env {
+    use revm::primitives::U256;
+    let block = BlockEnv {
+        number: U256::from(env.l1_batch_env.first_l2_block.number),
+        coinbase: h160_to_address(env.l1_batch_env.fee_account),
+        timestamp: U256::from(env.l1_batch_env.first_l2_block.timestamp),
+        gas_limit: U256::from(env.system_env.gas_limit),
+        basefee: U256::from(env.l1_batch_env.base_fee()),
+        ..Default::default()
+    };
+
+    let mut cfg = CfgEnv::default();
+    cfg.chain_id = env.system_env.chain_id.as_u64();
+
+    Env { block, cfg, ..Default::default() }
+}
+
+fn create_fork_request(
+    env: &EraEnv,
+    config: Arc<CheatsConfig>,
+    block_number: Option<u64>,
+    url_or_alias: &str,
+) -> CreateFork {
+    use foundry_evm_core::opts::Env;
+    use revm::primitives::Address as revmAddress;
+
+    let url = config.rpc_url(url_or_alias).unwrap();
+    let env = into_revm_env(env);
+    let opts_env = Env {
+        gas_limit: u64::MAX,
+        chain_id: None,
+        tx_origin: revmAddress::ZERO,
+        block_number: 0,
+        block_timestamp: 0,
+        ..Default::default()
+    };
+    let evm_opts = EvmOpts {
+        env: opts_env,
+        fork_url: Some(url.clone()),
+        fork_block_number: block_number,
+        fork_prefetch: config.evm_opts.fork_prefetch,
+        fork_prefetch_accounts: config.evm_opts.fork_prefetch_accounts.clone(),
+        fork_verify_storage: config.evm_opts.fork_verify_storage,
+        fork_verify_l1_url: config.evm_opts.fork_verify_l1_url.clone(),
+        fork_verify_main_contract: config.evm_opts.fork_verify_main_contract,
+        ..Default::default()
+    };
+
+    CreateFork {
+        enable_caching: config.rpc_storage_caching.enable_for_endpoint(&url),
+        url,
+        env,
+        evm_opts,
+    }
+}
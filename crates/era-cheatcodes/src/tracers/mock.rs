@@ -0,0 +1,217 @@
+//! Mocking of far calls, set with `vm.mockCall` and `vm.mockFunction`.
+
+use crate::{
+    cheatcodes::get_calldata,
+    farcall::{msg_value_call, pending_far_call, MockCall, MockCallReturn, MockedCalls},
+};
+use foundry_evm_core::{
+    backend::DatabaseExt,
+    era_revm::{db::RevmDatabaseForEra, storage_view::StorageView},
+};
+use multivm::{
+    vm_latest::{HistoryMode, SimpleMemory},
+    zk_evm_1_4_0::tracing::{BeforeExecutionData, VmLocalStateData},
+};
+use std::collections::HashMap;
+use zksync_basic_types::{H160, H256, U256};
+use zksync_state::{ReadStorage, StoragePtr, WriteStorage};
+use zksync_types::{get_code_key, StorageKey};
+
+type EraDb<DB> = StorageView<RevmDatabaseForEra<DB>>;
+
+/// Returns the mocked data of the far calls matching a mock, and runs the code of the mock targets
+/// of the calls matching a mocked function.
+#[derive(Debug, Default, Clone)]
+pub struct MockTracer {
+    mocked_calls: MockedCalls,
+    /// Mock targets of the mocked functions, by callee and calldata.
+    mocked_functions: HashMap<H160, HashMap<Vec<u8>, H160>>,
+    /// Code hash of the callee swapped for the one of a mock target for the far call being
+    /// executed, to restore once the code is loaded.
+    swapped_code: Option<(StorageKey, H256)>,
+}
+
+impl MockTracer {
+    /// Mocks the calls matching `call` to return or revert with the given data.
+    pub(crate) fn mock(&mut self, call: MockCall, mock_return: MockCallReturn) {
+        self.mocked_calls.insert(call, mock_return)
+    }
+
+    /// Clears all the mocked calls. The mocked functions are kept.
+    pub(crate) fn clear(&mut self) {
+        self.mocked_calls.clear()
+    }
+
+    /// Runs the code of `target` instead of the one of `callee` on calls to `callee` with `data`,
+    /// in the context of `callee`, as if `callee` delegated the call to `target`.
+    pub(crate) fn mock_function(&mut self, callee: H160, target: H160, data: Vec<u8>) {
+        self.mocked_functions.entry(callee).or_default().insert(data, target);
+    }
+
+    /// Returns the target the call is delegated to, matching the calldata exactly or else by
+    /// selector.
+    fn function_mock_target(&self, callee: H160, calldata: &[u8]) -> Option<H160> {
+        let mocks = self.mocked_functions.get(&callee)?;
+        mocks
+            .get(calldata)
+            .or_else(|| calldata.get(..4).and_then(|selector| mocks.get(selector)))
+            .copied()
+    }
+
+    /// Swaps the code of the callee of the far call about to be executed for the one of its mock
+    /// target, if the call matches a mocked function.
+    /// Must be called during `before_execution`.
+    pub(crate) fn before_far_call<S: DatabaseExt + Send, H: HistoryMode>(
+        &mut self,
+        state: &VmLocalStateData<'_>,
+        data: &BeforeExecutionData,
+        memory: &SimpleMemory<H>,
+        storage: &StoragePtr<EraDb<S>>,
+    ) {
+        if self.mocked_functions.is_empty() {
+            return
+        }
+        let (callee, calldata) = pending_far_call(state, data, memory);
+        let Some(target) = self.function_mock_target(callee, &calldata) else { return };
+
+        let mut storage = storage.borrow_mut();
+        let target_code_hash = storage.read_value(&get_code_key(&target));
+        if target_code_hash.is_zero() {
+            tracing::warn!(?callee, ?target, "mock function target has no code, ignoring");
+            return
+        }
+        tracing::info!(
+            ?callee,
+            ?target,
+            calldata = hex::encode(&calldata),
+            "mock function matched"
+        );
+        let key = get_code_key(&callee);
+        self.swapped_code = Some((key, storage.read_value(&key)));
+        storage.set_value(key, target_code_hash);
+    }
+
+    /// Restores the code of the callee swapped in `before_far_call`, once the far call loaded it.
+    /// Must be called during `after_execution`.
+    pub(crate) fn restore_swapped_code<S: DatabaseExt + Send>(
+        &mut self,
+        storage: &StoragePtr<EraDb<S>>,
+    ) {
+        if let Some((key, code_hash)) = self.swapped_code.take() {
+            storage.borrow_mut().set_value(key, code_hash);
+        }
+    }
+
+    /// Returns the data the far call that was just executed is mocked to return or revert with, if
+    /// any.
+    ///
+    /// Calls with value are matched when calling the MsgValueSimulator, so that the value of mocked
+    /// calls isn't transferred.
+    pub(crate) fn on_far_call<H: HistoryMode>(
+        &self,
+        state: &VmLocalStateData<'_>,
+        memory: &SimpleMemory<H>,
+    ) -> Option<MockCallReturn> {
+        let current = state.vm_local_state.callstack.current;
+        let (address, value) = msg_value_call(state)
+            .unwrap_or((current.code_address, U256::from(current.context_u128_value)));
+        let calldata = get_calldata(state, memory);
+        let mock_return = self.mocked_calls.get_matching_return_data(address, &calldata, value)?;
+        tracing::info!(calldata = hex::encode(&calldata), ?mock_return, "mock call matched");
+        Some(mock_return)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_mocked_calls() {
+        let mut mocks = MockTracer::default();
+        let address = H160::repeat_byte(1);
+        let call = |value: Option<U256>, calldata: Vec<u8>| MockCall { address, value, calldata };
+        mocks.mock(call(None, vec![1]), MockCallReturn::Return(vec![2]));
+        mocks.mock(call(Some(U256::one()), vec![1]), MockCallReturn::Return(vec![3]));
+        mocks.mock(call(None, vec![1, 2]), MockCallReturn::Revert(vec![4]));
+
+        let matching = |calldata: &[u8], value: u64| {
+            mocks.mocked_calls.get_matching_return_data(address, calldata, U256::from(value))
+        };
+        assert_eq!(matching(&[1, 5], 0), Some(MockCallReturn::Return(vec![2])));
+        assert_eq!(matching(&[1, 5], 1), Some(MockCallReturn::Return(vec![3])));
+        assert_eq!(matching(&[1, 2], 0), Some(MockCallReturn::Revert(vec![4])));
+        assert_eq!(matching(&[5], 0), None);
+
+        mocks.clear();
+        assert_eq!(mocks.mocked_calls.get_matching_return_data(address, &[1], U256::zero()), None);
+    }
+
+    #[test]
+    fn prefers_the_longest_calldata_prefix() {
+        let mut mocks = MockTracer::default();
+        let address = H160::repeat_byte(1);
+        let selector = vec![0xa, 0xb, 0xc, 0xd];
+        let call = |value: Option<U256>, args: &[u8]| MockCall {
+            address,
+            value,
+            calldata: [selector.as_slice(), args].concat(),
+        };
+        mocks.mock(call(None, &[]), MockCallReturn::Return(vec![1]));
+        mocks.mock(call(None, &[2]), MockCallReturn::Return(vec![2]));
+        mocks.mock(call(Some(U256::one()), &[2]), MockCallReturn::Return(vec![3]));
+
+        let matching = |args: &[u8], value: u64| {
+            let calldata = [selector.as_slice(), args].concat();
+            mocks.mocked_calls.get_matching_return_data(address, &calldata, U256::from(value))
+        };
+        assert_eq!(matching(&[1, 1], 0), Some(MockCallReturn::Return(vec![1])));
+        assert_eq!(matching(&[2, 1], 0), Some(MockCallReturn::Return(vec![2])));
+        assert_eq!(matching(&[2, 1], 1), Some(MockCallReturn::Return(vec![3])));
+        assert_eq!(matching(&[], 0), Some(MockCallReturn::Return(vec![1])));
+        assert_eq!(
+            mocks.mocked_calls.get_matching_return_data(address, &[0xa], U256::zero()),
+            None
+        );
+    }
+
+    #[test]
+    fn matches_mocked_functions_exactly_or_by_selector() {
+        let mut mocks = MockTracer::default();
+        let callee = H160::repeat_byte(1);
+        mocks.mock_function(callee, H160::repeat_byte(2), vec![1, 2, 3, 4]);
+        mocks.mock_function(callee, H160::repeat_byte(3), vec![1, 2, 3, 4, 5]);
+
+        assert_eq!(
+            mocks.function_mock_target(callee, &[1, 2, 3, 4, 5]),
+            Some(H160::repeat_byte(3))
+        );
+        assert_eq!(
+            mocks.function_mock_target(callee, &[1, 2, 3, 4, 6]),
+            Some(H160::repeat_byte(2))
+        );
+        assert_eq!(mocks.function_mock_target(callee, &[1, 2, 3, 5]), None);
+        assert_eq!(mocks.function_mock_target(H160::repeat_byte(2), &[1, 2, 3, 4]), None);
+
+        // clearing the mocked calls keeps the mocked functions
+        mocks.clear();
+        assert_eq!(mocks.function_mock_target(callee, &[1, 2, 3, 4]), Some(H160::repeat_byte(2)));
+    }
+
+    #[test]
+    fn later_mocks_replace_earlier_ones() {
+        let mut mocks = MockTracer::default();
+        let address = H160::repeat_byte(1);
+        let call = |value: Option<U256>| MockCall { address, value, calldata: vec![1] };
+        mocks.mock(call(None), MockCallReturn::Return(vec![2]));
+        mocks.mock(call(None), MockCallReturn::Revert(vec![3]));
+        mocks.mock(call(Some(U256::one())), MockCallReturn::Return(vec![4]));
+        mocks.mock(call(Some(U256::one())), MockCallReturn::Return(vec![5]));
+
+        let matching = |value: u64| {
+            mocks.mocked_calls.get_matching_return_data(address, &[1], U256::from(value))
+        };
+        assert_eq!(matching(0), Some(MockCallReturn::Revert(vec![3])));
+        assert_eq!(matching(1), Some(MockCallReturn::Return(vec![5])));
+    }
+}
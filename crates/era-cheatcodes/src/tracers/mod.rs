@@ -0,0 +1,180 @@
+//! Focused tracers the [CheatcodeTracer](crate::cheatcodes::CheatcodeTracer) dispatches to.
+//!
+//! The cheatcode tracer decodes and dispatches the cheatcodes, and keeps track of the test and of
+//! the returns it has to rewrite, while each of these tracers owns the state of one feature and
+//! observes the instructions it needs. Tracers can be disabled with [EnabledTracers], configured
+//! with `zk_disabled_tracers`, in which case the cheatcodes relying on them revert.
+
+mod broadcast;
+mod expectation;
+mod fork;
+mod mock;
+
+pub(crate) use broadcast::BroadcastOpts;
+pub use broadcast::BroadcastTracer;
+pub use expectation::ExpectationTracer;
+pub(crate) use expectation::{EmitChecks, EmitOrdering, ExpectedCallType};
+pub(crate) use fork::{EraEnv, ForkAction, ForkContext};
+pub use fork::{ForkTracer, SavedSnapshot};
+pub use mock::MockTracer;
+
+use era_test_node::utils::bytecode_to_factory_dep;
+use foundry_cheatcodes_spec::Vm;
+use foundry_common::StorageModifications;
+use foundry_config::ZkTracer;
+use itertools::Itertools;
+use std::collections::HashMap;
+use zksync_basic_types::H256;
+use zksync_types::StorageKey;
+use zksync_utils::{bytecode::CompressedBytecodeInfo, u256_to_h256};
+
+/// The tracers enabled alongside the cheatcodes, all of them by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnabledTracers {
+    /// Whether calls can be mocked, see [MockTracer].
+    pub mocks: bool,
+    /// Whether calls and events can be expected, see [ExpectationTracer].
+    pub expectations: bool,
+    /// Whether calls can be broadcast, see [BroadcastTracer].
+    pub broadcast: bool,
+    /// Whether forks and snapshots can be used, see [ForkTracer].
+    pub forks: bool,
+}
+
+impl Default for EnabledTracers {
+    fn default() -> Self {
+        Self { mocks: true, expectations: true, broadcast: true, forks: true }
+    }
+}
+
+impl EnabledTracers {
+    /// Disables all the tracers.
+    pub fn none() -> Self {
+        Self { mocks: false, expectations: false, broadcast: false, forks: false }
+    }
+
+    /// Enables all the tracers but the given ones, as configured with `zk_disabled_tracers`.
+    pub fn without(disabled: &[ZkTracer]) -> Self {
+        let mut enabled = Self::default();
+        for tracer in disabled {
+            match tracer {
+                ZkTracer::Mocks => enabled.mocks = false,
+                ZkTracer::Expectations => enabled.expectations = false,
+                ZkTracer::Broadcast => enabled.broadcast = false,
+                ZkTracer::Forks => enabled.forks = false,
+            }
+        }
+        enabled
+    }
+
+    /// Returns the name of the tracer the cheatcode relies on, if it's disabled.
+    pub(crate) fn disabled_for(&self, call: &Vm::VmCalls) -> Option<&'static str> {
+        use Vm::VmCalls::*;
+
+        let (tracer, enabled) = match call {
+            mockCall_0(_) | mockCall_1(_) | mockCallRevert_0(_) | mockCallRevert_1(_) |
+            mockFunction(_) | clearMockedCalls(_) => ("mock", self.mocks),
+            expectCall_0(_) | expectCall_1(_) | expectCall_2(_) | expectCall_3(_) |
+            expectEmit_0(_) | expectEmit_1(_) | expectEmit_2(_) | zkSetEmitOrdering(_) |
+            recordCalls(_) | getCalls(_) => ("expectation", self.expectations),
+            startBroadcast_0(_) |
+            startBroadcast_1(_) |
+            startBroadcast_2(_) |
+            startBroadcastWallet(_) |
+            stopBroadcast(_) |
+            zkSetGasPerPubdataLimit(_) |
+            zkSetMaxFeePerGas(_) => ("broadcast", self.broadcast),
+            createFork_0(_) |
+            createFork_1(_) |
+            createSelectFork_0(_) |
+            createSelectFork_1(_) |
+            selectFork(_) |
+            rollFork_0(_) |
+            rollFork_2(_) |
+            transact_0(_) |
+            transact_1(_) |
+            makePersistent_0(_) |
+            makePersistent_1(_) |
+            makePersistent_2(_) |
+            makePersistent_3(_) |
+            revokePersistent_0(_) |
+            revokePersistent_1(_) |
+            snapshot(_) |
+            snapshotNamed(_) |
+            revertTo(_) |
+            revertToNamed(_) |
+            diffSnapshots(_) => ("fork", self.forks),
+            _ => return None,
+        };
+        (!enabled).then_some(tracer)
+    }
+}
+
+/// Merges the storage modified by the current transaction into the storage modified so far in the
+/// test.
+pub(crate) fn merge_modified_storage(
+    storage_modifications: &StorageModifications,
+    storage: &HashMap<StorageKey, H256>,
+) -> HashMap<StorageKey, H256> {
+    let mut modified_storage = storage_modifications
+        .keys()
+        .iter()
+        .filter(|(key, _)| key.address() != &zksync_types::SYSTEM_CONTEXT_ADDRESS)
+        .map(|(key, value)| (*key, *value))
+        .collect::<HashMap<_, _>>();
+    modified_storage.extend(
+        storage.iter().filter(|(key, _)| key.address() != &zksync_types::SYSTEM_CONTEXT_ADDRESS),
+    );
+    modified_storage
+}
+
+/// Merges the bytecodes published by the current transaction into the bytecodes published so far
+/// in the test.
+pub(crate) fn merge_modified_bytecodes(
+    storage_modifications: &StorageModifications,
+    bootloader_bytecodes: Vec<CompressedBytecodeInfo>,
+) -> HashMap<H256, Vec<u8>> {
+    let mut modified_bytecodes = storage_modifications.bytecodes().clone();
+    modified_bytecodes.extend(
+        bootloader_bytecodes
+            .iter()
+            .map(|b| {
+                let (bytecode_key, bytecode_value) = bytecode_to_factory_dep(b.original.clone());
+                let key = u256_to_h256(bytecode_key);
+                let value = bytecode_value
+                    .into_iter()
+                    .flat_map(|v| u256_to_h256(v).as_bytes().to_owned())
+                    .collect_vec();
+                (key, value)
+            })
+            .collect::<HashMap<_, _>>(),
+    );
+    modified_bytecodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::Address;
+
+    #[test]
+    fn rejects_cheatcodes_of_disabled_tracers() {
+        let mock = Vm::VmCalls::clearMockedCalls(Vm::clearMockedCallsCall {});
+        let fork =
+            Vm::VmCalls::makePersistent_0(Vm::makePersistent_0Call { account: Address::ZERO });
+        let other = Vm::VmCalls::isZkVm(Vm::isZkVmCall {});
+
+        let enabled = EnabledTracers::default();
+        assert_eq!(enabled.disabled_for(&mock), None);
+        assert_eq!(enabled.disabled_for(&fork), None);
+
+        let enabled = EnabledTracers::without(&[ZkTracer::Forks]);
+        assert_eq!(enabled, EnabledTracers { forks: false, ..Default::default() });
+        assert_eq!(enabled.disabled_for(&mock), None);
+        assert_eq!(enabled.disabled_for(&fork), Some("fork"));
+
+        let enabled = EnabledTracers::none();
+        assert_eq!(enabled.disabled_for(&mock), Some("mock"));
+        assert_eq!(enabled.disabled_for(&other), None);
+    }
+}
@@ -1,4 +1,11 @@
-use ethers::signers::LocalWallet;
+use crate::error::CheatcodeError;
+use ethers::signers::{
+    coins_bip39::{
+        ChineseSimplified, ChineseTraditional, Czech, English, French, Italian, Japanese, Korean,
+        Portuguese, Spanish, Wordlist,
+    },
+    LocalWallet, MnemonicBuilder, Signer,
+};
 use revm::primitives::{ruint::Uint, Address};
 use zksync_basic_types::{H160, H256, U256};
 use zksync_types::zkevm_test_harness::k256::{elliptic_curve::Curve, Secp256k1};
@@ -53,3 +60,146 @@ pub(super) fn parse_private_key(private_key: &Uint<256, 4>) -> Option<[u8; 32]>
 pub(super) fn parse_wallet(private_key: &Uint<256, 4>) -> Option<LocalWallet> {
     parse_private_key(private_key).and_then(|b| LocalWallet::from_bytes(&b).ok())
 }
+
+/// Returns the address of the wallet of the private key, and the `(x, y)` coordinates of its
+/// public key.
+pub(super) fn wallet_of(private_key: &Uint<256, 4>) -> Option<(H160, [U256; 2])> {
+    use ethers::core::k256::elliptic_curve::sec1::ToEncodedPoint;
+
+    let wallet = parse_wallet(private_key)?;
+    let point = wallet.signer().verifying_key().as_affine().to_encoded_point(false);
+    Some((wallet.address(), [U256::from_big_endian(point.x()?), U256::from_big_endian(point.y()?)]))
+}
+
+/// The BIP32 default derivation path prefix.
+pub(super) const DEFAULT_DERIVATION_PATH_PREFIX: &str = "m/44'/60'/0'/0/";
+
+/// Derives the private key at `{path}{index}` from a mnemonic, or the path of a file with one, in
+/// the given language.
+pub(super) fn derive_key(
+    mnemonic: &str,
+    path: &str,
+    index: u32,
+    language: &str,
+) -> Result<U256, CheatcodeError> {
+    fn derive<W: Wordlist>(mnemonic: &str, path: &str) -> Result<U256, CheatcodeError> {
+        let wallet = MnemonicBuilder::<W>::default()
+            .phrase(mnemonic)
+            .derivation_path(path)
+            .and_then(|builder| builder.build())
+            .map_err(|err| CheatcodeError::Revert(format!("failed to derive key: {err}")))?;
+        Ok(U256::from_big_endian(&wallet.signer().to_bytes()))
+    }
+
+    let mut path = path.to_string();
+    if !path.ends_with('/') {
+        path.push('/');
+    }
+    path.push_str(&index.to_string());
+
+    match language {
+        "chinese_simplified" => derive::<ChineseSimplified>(mnemonic, &path),
+        "chinese_traditional" => derive::<ChineseTraditional>(mnemonic, &path),
+        "czech" => derive::<Czech>(mnemonic, &path),
+        "english" => derive::<English>(mnemonic, &path),
+        "french" => derive::<French>(mnemonic, &path),
+        "italian" => derive::<Italian>(mnemonic, &path),
+        "japanese" => derive::<Japanese>(mnemonic, &path),
+        "korean" => derive::<Korean>(mnemonic, &path),
+        "portuguese" => derive::<Portuguese>(mnemonic, &path),
+        "spanish" => derive::<Spanish>(mnemonic, &path),
+        _ => Err(CheatcodeError::Revert(format!("unsupported mnemonic language: {language:?}"))),
+    }
+}
+
+/// Signs the digest as is with the private key, returning `(v, r, s)` with `v = 27 + recovery_id`
+/// so the signer can be recovered with `ecrecover`.
+pub(super) fn sign(private_key: &Uint<256, 4>, digest: &[u8; 32]) -> Option<[U256; 3]> {
+    let signature = parse_wallet(private_key)?.sign_hash(ethers::types::H256(*digest)).ok()?;
+    let word = |value: ethers::types::U256| {
+        let mut bytes = [0u8; 32];
+        value.to_big_endian(&mut bytes);
+        U256::from_big_endian(&bytes)
+    };
+    Some([U256::from(signature.v), word(signature.r), word(signature.s)])
+}
+
+pub(super) fn parse_private_key_p256(
+    private_key: &Uint<256, 4>,
+) -> Option<p256::ecdsa::SigningKey> {
+    let order = <p256::NistP256 as p256::elliptic_curve::Curve>::ORDER;
+    if *private_key == Uint::ZERO || *private_key >= Uint::<256, 4>::from_limbs(*order.as_words()) {
+        return None
+    }
+    let bytes = private_key.to_be_bytes::<32>();
+    p256::ecdsa::SigningKey::from_bytes((&bytes).into()).ok()
+}
+
+/// Signs the digest as is with the private key on the secp256r1 curve, returning `(r, s)`.
+pub(super) fn sign_p256(private_key: &Uint<256, 4>, digest: &[u8; 32]) -> Option<[U256; 2]> {
+    use p256::ecdsa::signature::hazmat::PrehashSigner;
+
+    let signature: p256::ecdsa::Signature =
+        parse_private_key_p256(private_key)?.sign_prehash(digest).ok()?;
+    Some([
+        U256::from_big_endian(&signature.r().to_bytes()),
+        U256::from_big_endian(&signature.s().to_bytes()),
+    ])
+}
+
+/// Returns the `(x, y)` coordinates of the secp256r1 public key of the private key.
+pub(super) fn public_key_p256(private_key: &Uint<256, 4>) -> Option<[U256; 2]> {
+    use p256::elliptic_curve::sec1::ToEncodedPoint;
+
+    let key = parse_private_key_p256(private_key)?;
+    let point = key.verifying_key().as_affine().to_encoded_point(false);
+    Some([U256::from_big_endian(point.x()?), U256::from_big_endian(point.y()?)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p256::ecdsa::{signature::hazmat::PrehashVerifier, Signature, VerifyingKey};
+
+    #[test]
+    fn signs_p256_digests() {
+        let private_key = Uint::from(1u64);
+        let digest = [7u8; 32];
+
+        let [x, y] = public_key_p256(&private_key).unwrap();
+        // the generator of the curve
+        assert_eq!(
+            format!("{x:x}"),
+            "6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296"
+        );
+        assert_eq!(
+            format!("{y:x}"),
+            "4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5"
+        );
+
+        let [r, s] = sign_p256(&private_key, &digest).unwrap();
+        let mut bytes = [0u8; 64];
+        r.to_big_endian(&mut bytes[..32]);
+        s.to_big_endian(&mut bytes[32..]);
+        let signature = Signature::from_slice(&bytes).unwrap();
+        let key = VerifyingKey::from(parse_private_key_p256(&private_key).unwrap());
+        assert!(key.verify_prehash(&digest, &signature).is_ok());
+
+        assert!(sign_p256(&Uint::ZERO, &digest).is_none());
+        assert!(public_key_p256(&Uint::MAX).is_none());
+    }
+
+    #[test]
+    fn derives_keys_from_mnemonics() {
+        let mnemonic = "test test test test test test test test test test test junk";
+        let key = derive_key(mnemonic, DEFAULT_DERIVATION_PATH_PREFIX, 0, "english").unwrap();
+        assert_eq!(
+            format!("{key:x}"),
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+        );
+        // the trailing slash is optional
+        assert_eq!(derive_key(mnemonic, "m/44'/60'/0'/0", 0, "english").unwrap(), key);
+
+        assert!(derive_key(mnemonic, DEFAULT_DERIVATION_PATH_PREFIX, 0, "klingon").is_err());
+    }
+}
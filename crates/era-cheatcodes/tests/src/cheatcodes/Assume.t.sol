@@ -1,11 +1,27 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 pragma solidity ^0.8.13;
 
-import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Test, console2 as console, Vm} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+contract Assumer {
+    Vm constant vm = Vm(Constants.CHEATCODE_ADDRESS);
+
+    function assumeEven(uint256 x) external pure returns (uint256) {
+        vm.assume(x % 2 == 0);
+        return x / 2;
+    }
+}
 
 contract AssumeTest is Test {
     function testAssume(uint8 x) public {
         vm.assume(x < 2 ** 7);
         assertTrue(x < 2 ** 7, "did not discard inputs");
     }
+
+    function testAssumeInNestedCall(uint256 x) public {
+        Assumer assumer = new Assumer();
+        uint256 half = assumer.assumeEven(x);
+        assertEq(half * 2, x, "did not discard inputs");
+    }
 }
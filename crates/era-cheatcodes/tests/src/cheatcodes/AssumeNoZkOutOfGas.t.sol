@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+interface ZkVm {
+    function assumeNoZkOutOfGas() external;
+
+    function assumeZkGasBelow(uint256 gas) external;
+
+    function zkSetNextCallGas(uint256 gas) external;
+}
+
+contract Burner {
+    uint256 public counter;
+
+    function burn(uint256 iterations) public {
+        for (uint256 i = 0; i < iterations; i++) {
+            counter += 1;
+        }
+    }
+}
+
+contract AssumeNoZkOutOfGasTest is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+
+    function testFuzzAssumeNoZkOutOfGas(uint8 iterations) public {
+        Burner burner = new Burner();
+        zkVm.assumeNoZkOutOfGas();
+
+        // large inputs run out of gas and are rejected instead of failing the test
+        zkVm.zkSetNextCallGas(100_000);
+        burner.burn(uint256(iterations) * 10);
+
+        assertEq(burner.counter(), uint256(iterations) * 10);
+    }
+
+    function testFailAssumeNoZkOutOfGasCaught() public {
+        Burner burner = new Burner();
+        zkVm.assumeNoZkOutOfGas();
+
+        // running out of gas is caught, so the revert below fails the test instead of rejecting it
+        zkVm.zkSetNextCallGas(100_000);
+        try burner.burn(100_000) {} catch {}
+        revert("caught out of gas");
+    }
+
+    function testFuzzAssumeZkGasBelow(uint8 iterations) public {
+        Burner burner = new Burner();
+        zkVm.assumeZkGasBelow(2_000_000);
+
+        // expensive inputs are rejected instead of failing the test
+        uint256 gasBefore = gasleft();
+        burner.burn(uint256(iterations) * 10);
+        assertLe(gasBefore - gasleft(), 2_000_000);
+    }
+}
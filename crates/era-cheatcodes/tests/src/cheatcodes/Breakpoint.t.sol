@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+interface ZkVm {
+    function breakpoint(string calldata char) external;
+    function breakpoint(string calldata char, bool value) external;
+}
+
+contract BreakpointTest is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+
+    function testBreakpoint() public {
+        zkVm.breakpoint("a");
+        zkVm.breakpoint("b", true);
+        zkVm.breakpoint("b", false);
+    }
+
+    function testBreakpointInvalidCharReverts() public {
+        (bool success,) = Constants.CHEATCODE_ADDRESS.call(abi.encodeWithSignature("breakpoint(string)", "ab"));
+        require(!success, "breakpoints of more than one character should revert");
+
+        (success,) = Constants.CHEATCODE_ADDRESS.call(abi.encodeWithSignature("breakpoint(string,bool)", "1", true));
+        require(!success, "non-alphabetic breakpoints should revert");
+    }
+}
@@ -4,6 +4,12 @@ pragma solidity ^0.8.13;
 import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
 import {Constants} from "./Constants.sol";
 
+interface ZkVm {
+    function getScriptWallets() external returns (address[] memory wallets);
+    function broadcastWallet(uint256 senderIndex) external;
+    function startBroadcastWallet(uint256 senderIndex) external;
+}
+
 contract ATest is Test {
     uint256 public changed = 0;
 
@@ -33,10 +39,14 @@ contract ATest is Test {
 }
 
 contract BroadcastTest is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+
     // 1st anvil account
     address public ACCOUNT_A = 0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266;
+    uint256 public KEY_A = 0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80;
     // 2nd anvil account
     address public ACCOUNT_B = 0x70997970C51812dc3A010C7d01b50e0d17dc79C8;
+    uint256 public KEY_B = 0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d;
 
     function test_SimpleBroadcastDeploy() public {
         vm.startBroadcast(ACCOUNT_A);
@@ -85,4 +95,69 @@ contract BroadcastTest is Test {
 
        vm.stopBroadcast(); 
     }
+
+    function test_GetScriptWallets() public {
+        vm.rememberKey(KEY_A);
+        vm.startBroadcast(KEY_B);
+        vm.stopBroadcast();
+        // remembering a key twice doesn't list it twice
+        vm.rememberKey(KEY_A);
+
+        address[] memory wallets = zkVm.getScriptWallets();
+        require(wallets.length == 2, "wrong number of wallets");
+        require(wallets[0] == ACCOUNT_A, "remembered wallet is missing");
+        require(wallets[1] == ACCOUNT_B, "broadcasting wallet is missing");
+    }
+
+    function test_SwitchBroadcastSenders() public {
+        vm.rememberKey(KEY_A);
+        vm.rememberKey(KEY_B);
+        address[] memory wallets = zkVm.getScriptWallets();
+        uint64 nonceA = vm.getNonce(wallets[0]);
+        uint64 nonceB = vm.getNonce(wallets[1]);
+
+        vm.startBroadcast(wallets[0]);
+        ATest test = new ATest();
+        vm.stopBroadcast();
+
+        vm.startBroadcast(wallets[1]);
+        test.inc();
+        vm.stopBroadcast();
+
+        vm.startBroadcast(wallets[0]);
+        test.inc();
+        vm.stopBroadcast();
+
+        require(vm.getNonce(wallets[0]) == nonceA + 2, "nonce of the first sender is wrong");
+        require(vm.getNonce(wallets[1]) == nonceB + 1, "nonce of the second sender is wrong");
+        require(test.changed() == 2, "calls were not executed");
+    }
+
+    function test_BroadcastWallet() public {
+        vm.rememberKey(KEY_A);
+        vm.rememberKey(KEY_B);
+        uint64 nonceA = vm.getNonce(ACCOUNT_A);
+        uint64 nonceB = vm.getNonce(ACCOUNT_B);
+
+        zkVm.startBroadcastWallet(0);
+        ATest test = new ATest();
+        vm.stopBroadcast();
+
+        // only the next call is broadcast
+        zkVm.broadcastWallet(1);
+        test.inc();
+        test.inc();
+
+        require(vm.getNonce(ACCOUNT_A) == nonceA + 1, "nonce of the first sender is wrong");
+        require(vm.getNonce(ACCOUNT_B) == nonceB + 1, "nonce of the second sender is wrong");
+        require(test.changed() == 2, "calls were not executed");
+    }
+
+    function test_BroadcastWalletOutOfRange() public {
+        vm.rememberKey(KEY_A);
+
+        (bool success,) =
+            address(zkVm).call(abi.encodeWithSelector(ZkVm.startBroadcastWallet.selector, uint256(1)));
+        require(!success, "broadcast with a missing wallet");
+    }
 }
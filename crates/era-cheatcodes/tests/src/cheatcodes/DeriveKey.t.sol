@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+contract CheatcodeDeriveKeyTest is Test {
+    string constant MNEMONIC = "test test test test test test test test test test test junk";
+    uint256 constant FIRST_KEY = 0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80;
+
+    function testDeriveKey() external {
+        require(vm.deriveKey(MNEMONIC, 0) == FIRST_KEY, "key 0 mismatch");
+        require(vm.deriveKey(MNEMONIC, "m/44'/60'/0'/0/", 0) == FIRST_KEY, "key 0 at path mismatch");
+        require(vm.deriveKey(MNEMONIC, 0, "english") == FIRST_KEY, "english key 0 mismatch");
+        require(
+            vm.deriveKey(MNEMONIC, "m/44'/60'/0'/0/", 1, "english") == vm.deriveKey(MNEMONIC, 1),
+            "key 1 mismatch"
+        );
+        require(vm.deriveKey(MNEMONIC, 1) != FIRST_KEY, "keys are not derived by index");
+    }
+
+    function testDeriveKeyInvalidMnemonicReverts() external {
+        (bool success,) = Constants.CHEATCODE_ADDRESS.call(
+            abi.encodeWithSignature("deriveKey(string,uint32)", "not a mnemonic", uint32(0))
+        );
+        require(!success, "deriving from an invalid mnemonic should revert");
+    }
+
+    function testRememberKey() external {
+        address remembered = vm.rememberKey(FIRST_KEY);
+        require(remembered == vm.addr(FIRST_KEY), "remembered address mismatch");
+    }
+}
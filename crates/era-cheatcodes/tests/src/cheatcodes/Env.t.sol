@@ -8,6 +8,15 @@ contract EnvTest is Test {
         string memory key = "_foundryCheatcodeSetEnvTestKey";
         string memory val = "_foundryCheatcodeSetEnvTestVal";
         vm.setEnv(key, val);
+        require(keccak256(bytes(vm.envString(key))) == keccak256(bytes(val)), "env was not set");
+    }
+
+    function testSetEnvRejectsInvalidKeys() public {
+        (bool success, ) = address(vm).call(abi.encodeWithSignature("setEnv(string,string)", "", "value"));
+        require(!success, "setEnv with an empty key did not revert");
+
+        (success, ) = address(vm).call(abi.encodeWithSignature("setEnv(string,string)", "A=B", "value"));
+        require(!success, "setEnv with `=` in the key did not revert");
     }
 
     uint256 constant numEnvBoolTests = 2;
@@ -1173,4 +1182,50 @@ contract EnvTest is Test {
             );
         }
     }
+
+    function testEnvMissingReverts() public {
+        (bool success, bytes memory data) = address(vm).call(
+            abi.encodeWithSignature("envUint(string)", "_foundryCheatcodeEnvMissingTestKey")
+        );
+        require(!success, "envUint of a missing variable did not revert");
+        require(
+            keccak256(data) ==
+                keccak256(
+                    abi.encodeWithSignature(
+                        "CheatcodeError(string)",
+                        'environment variable "_foundryCheatcodeEnvMissingTestKey" not found'
+                    )
+                ),
+            "envUint of a missing variable reverted with the wrong message"
+        );
+    }
+
+    function testEnvOrMissingFallsBackToDefault() public {
+        string memory key = "_foundryCheatcodeEnvOrMissingTestKey";
+        (bool success, ) = address(vm).call(abi.encodeWithSignature("envUint(string)", key));
+        require(!success, "envUint of a missing variable did not revert");
+
+        require(vm.envOr(key, uint256(42)) == 42, "envOr of a missing variable did not fall back");
+
+        vm.setEnv(key, "7");
+        require(vm.envOr(key, uint256(42)) == 7, "envOr of a set variable fell back");
+    }
+
+    function testEnvOrMalformedFallsBackToDefault() public {
+        string memory key = "_foundryCheatcodeEnvOrMalformedTestKey";
+        vm.setEnv(key, "not a number");
+
+        require(vm.envOr(key, uint256(42)) == 42, "envOr of a malformed variable did not fall back");
+    }
+
+    function testEnvMalformedReverts() public {
+        string memory key = "_foundryCheatcodeEnvMalformedTestKey";
+        vm.setEnv(key, "not an address");
+
+        (bool success, ) = address(vm).call(abi.encodeWithSignature("envAddress(string)", key));
+        require(!success, "envAddress of a malformed variable did not revert");
+
+        (success, ) = address(vm).call(abi.encodeWithSignature("envAddress(string,string)", key, ","));
+        require(!success, "envAddress array of a malformed variable did not revert");
+    }
 }
@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+interface ZkVm {
+    function erc20BalanceOf(address token, address account) external view returns (uint256 balance);
+    function erc20Allowance(address token, address owner, address spender)
+        external
+        view
+        returns (uint256 allowance);
+}
+
+contract Token {
+    mapping(address => uint256) public balanceOf;
+    mapping(address => mapping(address => uint256)) public allowance;
+
+    function mint(address account, uint256 amount) external {
+        balanceOf[account] += amount;
+    }
+
+    function approve(address spender, uint256 amount) external returns (bool) {
+        allowance[msg.sender][spender] = amount;
+        return true;
+    }
+}
+
+contract Erc20Test is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+    address constant ALICE = address(0xa11ce);
+
+    Token token;
+
+    function setUp() public {
+        token = new Token();
+        token.mint(ALICE, 100);
+    }
+
+    function testErc20BalanceOf() public {
+        require(zkVm.erc20BalanceOf(address(token), ALICE) == 100, "balance from setUp mismatch");
+        require(zkVm.erc20BalanceOf(address(token), address(this)) == 0, "balance should be zero");
+
+        // balances changed in the test are seen
+        token.mint(ALICE, 23);
+        require(zkVm.erc20BalanceOf(address(token), ALICE) == 123, "balance from test mismatch");
+    }
+
+    function testErc20Allowance() public {
+        token.approve(ALICE, 42);
+        require(zkVm.erc20Allowance(address(token), address(this), ALICE) == 42, "allowance mismatch");
+        require(zkVm.erc20Allowance(address(token), ALICE, address(this)) == 0, "allowance should be zero");
+    }
+
+    function testErc20BalanceOfNonTokenReverts() public {
+        (bool success,) = Constants.CHEATCODE_ADDRESS.call(
+            abi.encodeWithSelector(ZkVm.erc20BalanceOf.selector, address(0xdead), ALICE)
+        );
+        require(!success, "querying an account without code should revert");
+    }
+}
@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+interface ZkVm {
+    function erc721BalanceOf(address token, address owner) external view returns (uint256 balance);
+    function erc721OwnerOf(address token, uint256 tokenId) external view returns (address owner);
+    function erc721GetApproved(address token, uint256 tokenId) external view returns (address approved);
+    function erc721IsApprovedForAll(address token, address owner, address operator)
+        external
+        view
+        returns (bool approved);
+}
+
+contract Collection {
+    mapping(address => uint256) public balanceOf;
+    mapping(uint256 => address) public ownerOf;
+    mapping(uint256 => address) public getApproved;
+    mapping(address => mapping(address => bool)) public isApprovedForAll;
+
+    function mint(address owner, uint256 tokenId) external {
+        balanceOf[owner] += 1;
+        ownerOf[tokenId] = owner;
+    }
+
+    function approve(address approved, uint256 tokenId) external {
+        getApproved[tokenId] = approved;
+    }
+
+    function setApprovalForAll(address operator, bool approved) external {
+        isApprovedForAll[msg.sender][operator] = approved;
+    }
+}
+
+contract Erc721Test is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+    address constant ALICE = address(0xa11ce);
+
+    Collection collection;
+
+    function setUp() public {
+        collection = new Collection();
+        collection.mint(ALICE, 1);
+        collection.mint(ALICE, 2);
+    }
+
+    function testErc721BalanceOf() public {
+        require(zkVm.erc721BalanceOf(address(collection), ALICE) == 2, "balance mismatch");
+        require(zkVm.erc721BalanceOf(address(collection), address(this)) == 0, "balance should be zero");
+    }
+
+    function testErc721OwnerOf() public {
+        require(zkVm.erc721OwnerOf(address(collection), 1) == ALICE, "owner mismatch");
+
+        // owners changed in the test are seen
+        collection.mint(address(this), 3);
+        require(zkVm.erc721OwnerOf(address(collection), 3) == address(this), "owner from test mismatch");
+    }
+
+    function testErc721Approvals() public {
+        collection.approve(ALICE, 1);
+        collection.setApprovalForAll(ALICE, true);
+
+        require(zkVm.erc721GetApproved(address(collection), 1) == ALICE, "approved mismatch");
+        require(zkVm.erc721GetApproved(address(collection), 2) == address(0), "approved should be zero");
+        require(zkVm.erc721IsApprovedForAll(address(collection), address(this), ALICE), "operator not approved");
+        require(!zkVm.erc721IsApprovedForAll(address(collection), ALICE, address(this)), "operator approved");
+    }
+}
@@ -50,6 +50,14 @@ contract NestedContract {
     }
 }
 
+contract ConstructorCaller {
+    uint256 public number;
+
+    constructor(Contract target) {
+        number = target.numberA();
+    }
+}
+
 contract ExpectCallTest is Test {
     function exposed_callTargetNTimes(
         Contract target,
@@ -353,6 +361,24 @@ contract ExpectCallTest is Test {
     //     vm.expectCall(address(target), abi.encodeWithSelector(target.add.selector));
     //     this.exposed_callTargetNTimes(target, 5, 5, 1);
     // }
+
+    function testExpectCallInConstructor() public {
+        Contract target = new Contract();
+
+        vm.expectCall(address(target), abi.encodeWithSelector(target.numberA.selector));
+
+        new ConstructorCaller(target);
+    }
+
+    function testExpectSelfCall() public {
+        vm.expectCall(address(this), abi.encodeWithSelector(this.exposed_selfCall.selector, 7), 1);
+
+        this.exposed_selfCall(7);
+    }
+
+    function exposed_selfCall(uint256 a) public pure returns (uint256) {
+        return a;
+    }
 }
 
 contract ExpectCallCountTest is Test {
@@ -4,6 +4,12 @@ pragma solidity ^0.8.13;
 import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
 import {Constants} from "./Constants.sol";
 
+interface ZkVm {
+    function zkSetEmitOrdering(uint8 ordering) external;
+
+    function expectEmit(uint8 topicWildcards, bool checkData) external;
+}
+
 struct Log {
     bytes32[] topics;
     bytes data;
@@ -40,6 +46,69 @@ contract ExpectEmitTest is Test {
         emitter.emitEvent(2, 2, 1, testData1);
     }
 
+    function testExpectEmitConsecutive() public {
+        bytes memory testData1 = "test";
+
+        Emitter emitter = new Emitter();
+        ZkVm(Constants.CHEATCODE_ADDRESS).zkSetEmitOrdering(1);
+
+        vm.expectEmit();
+
+        emit LogTopic1(1, 2, 3, testData1);
+        emit LogTopic1(4, 5, 6, testData1);
+
+        emitter.emitEvents(1, 4, testData1);
+    }
+
+    function testExpectEmitUnordered() public {
+        bytes memory testData1 = "test";
+
+        Emitter emitter = new Emitter();
+        ZkVm(Constants.CHEATCODE_ADDRESS).zkSetEmitOrdering(2);
+
+        vm.expectEmit();
+
+        emit LogTopic1(4, 5, 6, testData1);
+        emit LogTopic1(1, 2, 3, testData1);
+
+        emitter.emitEvents(1, 4, testData1);
+    }
+
+    function testSetEmitOrderingRejectsUnknownOrderings() public {
+        (bool success,) =
+            Constants.CHEATCODE_ADDRESS.call(abi.encodeWithSelector(ZkVm.zkSetEmitOrdering.selector, uint8(3)));
+        require(!success, "unknown emit ordering was accepted");
+    }
+
+    function testExpectEmitWithAddressIgnoresOtherEmitters() public {
+        bytes memory testData1 = "test";
+
+        Emitter emitter = new Emitter();
+        Emitter other = new Emitter();
+        Forwarder forwarder = new Forwarder();
+        ZkVm(Constants.CHEATCODE_ADDRESS).zkSetEmitOrdering(1);
+
+        vm.expectEmit(true, true, true, true, address(emitter));
+
+        emit LogTopic1(1, 2, 3, testData1);
+        emit LogTopic1(4, 5, 6, testData1);
+
+        forwarder.emitInterleaved(emitter, other, testData1);
+    }
+
+    function testExpectEmitTopicWildcards() public {
+        bytes memory testData1 = "test";
+
+        Emitter emitter = new Emitter();
+
+        // the second topic is unpredictable, e.g. a timestamp
+        ZkVm(Constants.CHEATCODE_ADDRESS).expectEmit(0x2, true);
+
+        emit LogTopic1(2, 0, 1, testData1);
+
+        emitter.emitEvent(2, 42, 1, testData1);
+    }
+
     function trimReturnBytes(
         bytes memory rawData
     ) internal pure returns (bytes memory) {
@@ -80,4 +149,22 @@ contract Emitter {
         emit LogTopic1(1, topic2, topic3, data); // Event not expected
         emit LogTopic1(topic1, topic2, topic3, data);
     }
+
+    function emitEvents(uint256 first, uint256 second, bytes memory data) public {
+        emit LogTopic1(0, 0, 0, data); // Event not expected
+        emit LogTopic1(first, first + 1, first + 2, data);
+        emit LogTopic1(second, second + 1, second + 2, data);
+    }
+
+    function emitEvent(uint256 topic1, bytes memory data) public {
+        emit LogTopic1(topic1, topic1 + 1, topic1 + 2, data);
+    }
+}
+
+contract Forwarder {
+    function emitInterleaved(Emitter emitter, Emitter other, bytes memory data) public {
+        emitter.emitEvent(1, data);
+        other.emitEvent(7, data);
+        emitter.emitEvent(4, data);
+    }
 }
@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: UNLICENSED
 pragma solidity ^0.8.13;
 
-import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Test, Vm, console2 as console} from "../../lib/forge-std/src/Test.sol";
 import {Constants} from "./Constants.sol";
 
 contract Reverter {
@@ -35,6 +35,20 @@ contract Reverter {
     }
 }
 
+interface CountedExpectRevertVm {
+    function expectRevert(bytes calldata revertData, uint64 count) external;
+
+    function expectRevert(bytes calldata revertData, address reverter, uint64 count) external;
+}
+
+contract CheatcodeCaller {
+    Vm constant vm = Vm(Constants.CHEATCODE_ADDRESS);
+
+    function readMissingEnv() public view returns (uint256) {
+        return vm.envUint("ZK_EXPECT_REVERT_MISSING_ENV_VAR");
+    }
+}
+
 contract ConstructorReverter {
     constructor(string memory message) {
         revert(message);
@@ -68,6 +82,8 @@ contract Dummy {
 }
 
 contract ExpectRevertTest is Test {
+    CountedExpectRevertVm constant countedVm = CountedExpectRevertVm(Constants.CHEATCODE_ADDRESS);
+
     function shouldRevert() internal pure {
         revert();
     }
@@ -226,6 +242,83 @@ contract ExpectRevertTest is Test {
         reverter.doNotRevert();
     }
 
+    function testExpectRevertCount() public {
+        Reverter reverter = new Reverter();
+
+        countedVm.expectRevert(bytes("revert"), 2);
+
+        reverter.revertWithMessage("revert");
+        reverter.revertWithMessage("revert");
+        reverter.doNotRevert();
+    }
+
+    function testFailExpectRevertCountNotReached() public {
+        Reverter reverter = new Reverter();
+
+        countedVm.expectRevert(bytes("revert"), 2);
+
+        reverter.revertWithMessage("revert");
+        reverter.doNotRevert();
+    }
+
+    function testExpectRevertCountWithReverter() public {
+        Reverter reverter = new Reverter();
+        Reverter inner = new Reverter();
+
+        countedVm.expectRevert(bytes("nested revert"), address(inner), 2);
+
+        reverter.nestedRevert(inner, "nested revert");
+        reverter.nestedRevert(inner, "nested revert");
+    }
+
+    function testFailExpectRevertCountWrongReverter() public {
+        Reverter reverter = new Reverter();
+        Reverter inner = new Reverter();
+
+        countedVm.expectRevert(bytes("nested revert"), address(reverter), 1);
+
+        reverter.nestedRevert(inner, "nested revert");
+    }
+
+    function testFailExpectRevertCountOnlyPartlyReached() public {
+        Reverter reverter = new Reverter();
+
+        countedVm.expectRevert(bytes("revert"), 3);
+
+        reverter.revertWithMessage("revert");
+        reverter.revertWithMessage("revert");
+    }
+
+    function testExpectRevertCountZero() public {
+        Reverter reverter = new Reverter();
+
+        countedVm.expectRevert(bytes("revert"), 0);
+        reverter.doNotRevert();
+    }
+
+    function testExpectRevertCountZeroOtherReason() public {
+        Reverter reverter = new Reverter();
+
+        countedVm.expectRevert(bytes("revert"), 0);
+        (bool success,) =
+            address(reverter).call(abi.encodeWithSelector(Reverter.revertWithMessage.selector, "other"));
+        require(!success, "the call should revert with its own reason");
+    }
+
+    function testFailExpectRevertCountZeroReverted() public {
+        Reverter reverter = new Reverter();
+
+        countedVm.expectRevert(bytes("revert"), 0);
+        reverter.revertWithMessage("revert");
+    }
+
+    function testExpectRevertFailedCheatcode() public {
+        CheatcodeCaller caller = new CheatcodeCaller();
+
+        vm.expectRevert();
+        caller.readMissingEnv();
+    }
+
     // function testFailExpectRevertDangling() public {
     //     cheatcodes.expectRevert("dangling");
     // }
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+import {Counter} from "./Counter.sol";
+
+interface ZkVm {
+    function zkSetMaxFeePerGas(uint256 maxFeePerGas) external;
+
+    function zkSetGasPerPubdataLimit(uint256 gasPerPubdataLimit) external;
+}
+
+contract FeeOverridesTest is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+
+    // 1st anvil account
+    address public ACCOUNT_A = 0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266;
+
+    function testTxGasPrice() public {
+        vm.txGasPrice(1 gwei);
+        require(tx.gasprice == 1 gwei, "gas price was not set");
+    }
+
+    function testFeeOverridesDuringBroadcast() public {
+        vm.startBroadcast(ACCOUNT_A);
+
+        vm.txGasPrice(1 gwei);
+        zkVm.zkSetMaxFeePerGas(2 gwei);
+        zkVm.zkSetGasPerPubdataLimit(50000);
+
+        Counter counter = new Counter();
+        counter.increment();
+
+        vm.stopBroadcast();
+
+        require(counter.number() == 1, "fee overrides altered execution");
+    }
+}
@@ -4,6 +4,10 @@ pragma solidity ^0.8.13;
 import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
 import {Constants} from "./Constants.sol";
 
+interface DeployedCodeVm {
+    function getDeployedCode(address account) external view returns (bytes memory runtimeBytecode);
+}
+
 contract ForkTest is Test {
     /// USDC TOKEN
     address constant TOKEN_ADDRESS = 0x3355df6D4c9C3035724Fd0e3914dE96A5a83aaf4;
@@ -78,4 +82,20 @@ contract ForkTest is Test {
 
         require(activeFork == forkId, "Active fork is not correct");
     }
+
+    function testGetDeployedCodeOfForkedContract() public {
+        DeployedCodeVm codeVm = DeployedCodeVm(Constants.CHEATCODE_ADDRESS);
+
+        bytes memory code = codeVm.getDeployedCode(TOKEN_ADDRESS);
+        require(code.length > 0, "forked code was not fetched");
+        require(code.length % 32 == 0, "code is not an EraVM bytecode");
+        require(
+            keccak256(codeVm.getDeployedCode(TOKEN_ADDRESS)) == keccak256(code),
+            "code differs between calls"
+        );
+        require(
+            codeVm.getDeployedCode(address(0xC0FFEE)).length == 0,
+            "account without code has code"
+        );
+    }
 }
@@ -31,4 +31,19 @@ contract FsTest is Test {
             "read data did not match write data"
         );
     }
+
+    function testWriteFileRevertsOnFailure() public {
+        string memory path = "src/fixtures/File/missing_dir/write_file.txt";
+
+        (bool success, bytes memory data) = Constants.CHEATCODE_ADDRESS.call(
+            abi.encodeWithSignature(
+                "writeFile(string,string)",
+                path,
+                "hello writable world"
+            )
+        );
+
+        require(!success, "writeFile should have reverted");
+        require(data.length > 0, "writeFile reverted without a reason");
+    }
 }
@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test} from "../../lib/forge-std/src/Test.sol";
+
+contract GenesisTest is Test {
+    // seeded with `zk_genesis` in foundry.toml
+    address constant GENESIS_ACCOUNT = 0x000000000000000000000000000000000000bEEF;
+    uint256 constant GENESIS_BALANCE = 1234;
+
+    function testGenesisBalance() public view {
+        require(GENESIS_ACCOUNT.balance == GENESIS_BALANCE, "genesis balance was not seeded");
+    }
+}
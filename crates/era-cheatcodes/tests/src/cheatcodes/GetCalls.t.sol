@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+interface ZkVm {
+    function recordCalls() external;
+    function getCalls(address callee, bytes calldata data) external view returns (uint256 count);
+}
+
+contract Target {
+    function add(uint256 a, uint256 b) public pure returns (uint256) {
+        return a + b;
+    }
+
+    function number() public pure returns (uint256) {
+        return 1;
+    }
+}
+
+contract GetCallsTest is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+
+    function testGetCallsCountsMatchingCalls() public {
+        Target target = new Target();
+        target.add(7, 8);
+
+        zkVm.recordCalls();
+        assertEq(zkVm.getCalls(address(target), abi.encodeWithSelector(Target.add.selector)), 0);
+
+        target.add(1, 2);
+        target.add(3, 4);
+        target.number();
+
+        assertEq(zkVm.getCalls(address(target), abi.encodeWithSelector(Target.add.selector)), 2);
+        assertEq(zkVm.getCalls(address(target), abi.encodeCall(Target.add, (1, 2))), 1);
+        assertEq(zkVm.getCalls(address(target), abi.encodeCall(Target.add, (5, 6))), 0);
+        assertEq(zkVm.getCalls(address(target), abi.encodeCall(Target.number, ())), 1);
+    }
+
+    function testGetCallsRevertsWithoutRecording() public {
+        Target target = new Target();
+
+        (bool success,) =
+            address(zkVm).call(abi.encodeWithSelector(ZkVm.getCalls.selector, address(target), ""));
+        require(!success, "got calls without recording them");
+    }
+}
@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+import {Counter} from "./Counter.sol";
+
+interface ZkVm {
+    function getDeployedCode(address account) external view returns (bytes memory runtimeBytecode);
+}
+
+contract CounterFactory {
+    function deploy() public returns (Counter) {
+        return new Counter();
+    }
+}
+
+contract CheatcodeGetCodeTest is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+
+    function testGetCodeReturnsTheZkBytecode() public {
+        Counter counter = new Counter();
+
+        bytes memory code = vm.getCode("Counter.sol:Counter");
+        require(code.length > 0, "code is empty");
+        require(
+            keccak256(code) == keccak256(zkVm.getDeployedCode(address(counter))),
+            "code does not match the deployed code"
+        );
+        require(
+            keccak256(vm.getCode("zkout/Counter.sol/Counter.json")) == keccak256(code),
+            "code of the artifact path does not match"
+        );
+    }
+
+    function testGetCodeCanBeDeployed() public {
+        address target = address(0x1234);
+        vm.etch(target, vm.getCode("Counter.sol:Counter"));
+
+        Counter(target).setNumber(42);
+        require(Counter(target).number() == 42, "code was not deployed");
+    }
+
+    function testGetDeployedCodeCanBeEtched() public {
+        address target = address(0x1234);
+        vm.etch(target, vm.getDeployedCode("GetCode.t.sol:CounterFactory"));
+
+        // the factory dependencies of the artifact are known, so the etched code can deploy them
+        Counter counter = CounterFactory(target).deploy();
+        counter.setNumber(42);
+        require(counter.number() == 42, "factory dependency was not deployed");
+    }
+
+    function testGetCodeOfMissingArtifactReverts() public {
+        (bool success,) = address(vm).call(abi.encodeWithSelector(vm.getCode.selector, "Missing.sol:Missing"));
+        require(!success, "missing artifact did not revert");
+    }
+}
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+interface ZkVm {
+    function getFoundryVersion() external view returns (string memory version);
+
+    function isZkVm() external view returns (bool isZk);
+}
+
+contract IntrospectionTest is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+
+    function testProjectRoot() public {
+        string memory root = vm.projectRoot();
+
+        require(bytes(root).length > 0, "project root was empty");
+
+        // The fixtures are resolved relative to the project root
+        string memory data = vm.readFile(
+            string.concat(root, "/src/fixtures/File/read.txt")
+        );
+        require(
+            keccak256(bytes(data)) ==
+                keccak256("hello readable world\nthis is the second line!\n"),
+            "project root did not point to the project"
+        );
+    }
+
+    function testGetFoundryVersion() public view {
+        string memory version = zkVm.getFoundryVersion();
+
+        console.log("foundry version:", version);
+        require(bytes(version).length > 0, "foundry version was empty");
+    }
+
+    function testIsZkVm() public view {
+        require(zkVm.isZkVm(), "expected to be running in the zkEVM");
+    }
+}
@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+contract LabeledTarget {
+    function ping() public pure returns (uint256) {
+        return 1;
+    }
+}
+
+contract CheatcodeLabelTest is Test {
+    address constant TEST_ADDRESS = 0x6Eb28604685b1F182dAB800A1Bfa4BaFdBA8a79a;
+
+    LabeledTarget target;
+
+    function setUp() public {
+        target = new LabeledTarget();
+        vm.label(address(target), "target");
+    }
+
+    function testLabel() public {
+        vm.label(TEST_ADDRESS, "alice");
+
+        string memory label = vm.getLabel(TEST_ADDRESS);
+        console.log("label:", label);
+        require(keccak256(bytes(label)) == keccak256("alice"), "label was not set");
+    }
+
+    function testLabelFromSetUp() public view {
+        string memory label = vm.getLabel(address(target));
+        require(keccak256(bytes(label)) == keccak256("target"), "label of setUp was not kept");
+    }
+
+    function testRelabel() public {
+        vm.label(address(target), "renamed");
+
+        string memory label = vm.getLabel(address(target));
+        require(keccak256(bytes(label)) == keccak256("renamed"), "label was not replaced");
+    }
+
+    function testGetLabelUnlabeled() public view {
+        string memory label = vm.getLabel(address(0x1234));
+        require(
+            keccak256(bytes(label)) == keccak256("unlabeled:0x0000000000000000000000000000000000001234"),
+            "unlabeled address has a label"
+        );
+    }
+}
@@ -4,6 +4,35 @@ pragma solidity ^0.8.13;
 import {Test, Vm, console2 as console} from "../../lib/forge-std/src/Test.sol";
 import {Constants} from "./Constants.sol";
 
+interface RecordedLogsVm {
+    function getRecordedLogs(address[] calldata emitters, uint256 maxDepth) external returns (Vm.Log[] memory logs);
+}
+
+contract Emitter {
+    event LogTopic1(uint256 indexed topic1, bytes data);
+
+    function emitEvent(uint256 topic1, bytes memory data) public {
+        emit LogTopic1(topic1, data);
+    }
+}
+
+contract RevertingEmitter {
+    event LogTopic1(uint256 indexed topic1, bytes data);
+
+    function emitEventAndRevert(uint256 topic1, bytes memory data) public {
+        emit LogTopic1(topic1, data);
+        revert("reverted");
+    }
+}
+
+contract NestedEmitter {
+    Emitter public emitter = new Emitter();
+
+    function emitEvent(uint256 topic1, bytes memory data) public {
+        emitter.emitEvent(topic1, data);
+    }
+}
+
 contract LogsTest is Test {
     event LogTopic1(uint256 indexed topic1, bytes data);
 
@@ -21,4 +50,67 @@ contract LogsTest is Test {
         console.log("logs length: %d", entries.length);
         require(entries.length == 1, "wrong number of logs");
     }
-}
\ No newline at end of file
+
+    function testGetRecordedLogsFiltered() public {
+        RecordedLogsVm logsVm = RecordedLogsVm(Constants.CHEATCODE_ADDRESS);
+        NestedEmitter nested = new NestedEmitter();
+        Emitter emitter = new Emitter();
+
+        vm.recordLogs();
+        emit LogTopic1(1, "test");
+        emitter.emitEvent(2, "test");
+        nested.emitEvent(3, "test");
+
+        Vm.Log[] memory entries = logsVm.getRecordedLogs(new address[](0), 1);
+        require(entries.length == 2, "logs below depth 1 were not filtered out");
+
+        vm.recordLogs();
+        emit LogTopic1(1, "test");
+        emitter.emitEvent(2, "test");
+        nested.emitEvent(3, "test");
+
+        address[] memory emitters = new address[](1);
+        emitters[0] = address(nested.emitter());
+        entries = logsVm.getRecordedLogs(emitters, 2);
+        require(entries.length == 1, "logs of other emitters were not filtered out");
+        require(entries[0].topics[1] == bytes32(uint256(3)), "wrong log");
+    }
+
+    function testRecordsIdenticalLogsInOrder() public {
+        RecordedLogsVm logsVm = RecordedLogsVm(Constants.CHEATCODE_ADDRESS);
+        NestedEmitter nested = new NestedEmitter();
+        Emitter emitter = nested.emitter();
+
+        vm.recordLogs();
+        emitter.emitEvent(1, "same");
+        nested.emitEvent(1, "same");
+        emitter.emitEvent(2, "last");
+
+        Vm.Log[] memory entries = vm.getRecordedLogs();
+        require(entries.length == 3, "identical logs were merged");
+        require(entries[2].topics[1] == bytes32(uint256(2)), "logs are not in emission order");
+
+        vm.recordLogs();
+        emitter.emitEvent(1, "same");
+        nested.emitEvent(1, "same");
+
+        // only the log emitted by the frame called by the test
+        entries = logsVm.getRecordedLogs(new address[](0), 1);
+        require(entries.length == 1, "logs of different frames were not told apart");
+    }
+
+    function testDropsLogsOfRevertedCalls() public {
+        Emitter emitter = new Emitter();
+        RevertingEmitter reverting = new RevertingEmitter();
+
+        vm.recordLogs();
+        emitter.emitEvent(1, "before");
+        try reverting.emitEventAndRevert(2, "reverted") {} catch {}
+        emitter.emitEvent(3, "after");
+
+        Vm.Log[] memory entries = vm.getRecordedLogs();
+        require(entries.length == 2, "logs of the reverted call were recorded");
+        require(entries[0].topics[1] == bytes32(uint256(1)), "wrong first log");
+        require(entries[1].topics[1] == bytes32(uint256(3)), "log after the reverted call was dropped");
+    }
+}
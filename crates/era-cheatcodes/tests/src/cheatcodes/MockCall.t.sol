@@ -41,6 +41,12 @@ contract NestedMock {
     }
 }
 
+contract Payer {
+    function pay(Mock mock, uint256 value, uint256 a) public returns (uint256) {
+        return mock.pay{value: value}(a);
+    }
+}
+
 contract MockCallTest is Test {
     function testMockGetters() public {
         Mock target = new Mock();
@@ -103,6 +109,35 @@ contract MockCallTest is Test {
         assertEq(target.numberB(), 2);
     }
 
+    function testClearMockedCallsThenMockAgain() public {
+        Mock target = new Mock();
+
+        vm.mockCall(address(target), abi.encodeWithSelector(target.numberB.selector), abi.encode(10));
+        vm.clearMockedCalls();
+        vm.mockCall(address(target), abi.encodeWithSelector(target.numberA.selector), abi.encode(20));
+
+        assertEq(target.numberA(), 20);
+        assertEq(target.numberB(), 2);
+    }
+
+    function testMockCallReplacesEarlierMock() public {
+        Mock target = new Mock();
+        bytes memory data = abi.encodeWithSelector(target.numberB.selector);
+
+        vm.mockCall(address(target), data, abi.encode(10));
+        assertEq(target.numberB(), 10);
+
+        vm.mockCall(address(target), data, abi.encode(20));
+        assertEq(target.numberB(), 20);
+
+        vm.mockCallRevert(address(target), data, "");
+        (bool success,) = address(target).call(data);
+        assertFalse(success);
+
+        vm.mockCall(address(target), data, abi.encode(30));
+        assertEq(target.numberB(), 30);
+    }
+
     function testMockCallMultiplePartialMatch() public {
         Mock mock = new Mock();
 
@@ -132,6 +167,19 @@ contract MockCallTest is Test {
         assertEq(mock.pay{value: 50}(1), 100);
     }
 
+    function testMockCallWithValueFromNestedCall() public {
+        Mock mock = new Mock();
+        Payer payer = new Payer();
+        vm.deal(address(payer), 100);
+
+        vm.mockCall(address(mock), 10, abi.encodeWithSelector(mock.pay.selector), abi.encode(20));
+
+        assertEq(payer.pay(mock, 10, 1), 20);
+        assertEq(payer.pay(mock, 5, 1), 1);
+        // the value of mocked calls is not transferred
+        assertEq(address(mock).balance, 5);
+    }
+
     function testMockCallWithValueCalldataPrecedence() public {
         Mock mock = new Mock();
 
@@ -143,6 +191,29 @@ contract MockCallTest is Test {
         assertEq(mock.pay(2), 2);
     }
 
+    function testMockCallRevert() public {
+        Mock target = new Mock();
+        bytes memory revertData = abi.encodeWithSignature("Error(string)", "mocked revert");
+
+        vm.mockCallRevert(address(target), abi.encodeWithSelector(target.numberA.selector), revertData);
+
+        vm.expectRevert("mocked revert");
+        target.numberA();
+
+        // other calls are not mocked
+        assertEq(target.numberB(), 2);
+    }
+
+    function testMockCallRevertWithValue() public {
+        Mock mock = new Mock();
+
+        vm.mockCallRevert(address(mock), 10, abi.encodeWithSelector(mock.pay.selector), "");
+
+        assertEq(mock.pay(1), 1);
+        (bool success,) = address(mock).call{value: 10}(abi.encodeWithSelector(mock.pay.selector, 1));
+        assertFalse(success);
+    }
+
     // This fails as calls to empty account cause panic in the VM
     // function testMockCallEmptyAccount() public {
     //     Mock mock = Mock(address(100));
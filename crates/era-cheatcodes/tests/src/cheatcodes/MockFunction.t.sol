@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+interface ZkVm {
+    function mockFunction(address callee, address target, bytes calldata data) external;
+}
+
+contract MockFunctionContract {
+    uint256 a = 10;
+
+    function mocked_function() public view returns (uint256) {
+        return a;
+    }
+
+    function mocked_args_function(uint256 x) public view returns (uint256) {
+        return x + a;
+    }
+}
+
+contract ModelMockFunctionContract {
+    uint256 b = 100;
+
+    function mocked_function() public view returns (uint256) {
+        return b + 1;
+    }
+
+    function mocked_args_function(uint256 x) public view returns (uint256) {
+        return x + b + 1;
+    }
+}
+
+contract CheatcodeMockFunctionTest is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+
+    MockFunctionContract my_contract;
+    ModelMockFunctionContract model_contract;
+
+    function setUp() public {
+        my_contract = new MockFunctionContract();
+        model_contract = new ModelMockFunctionContract();
+    }
+
+    function testMockFunctionRunsInTheContextOfTheCallee() public {
+        zkVm.mockFunction(
+            address(my_contract),
+            address(model_contract),
+            abi.encodeWithSelector(MockFunctionContract.mocked_function.selector)
+        );
+        // the code of the mock target reads the storage of the callee
+        assertEq(my_contract.mocked_function(), 11);
+        assertEq(model_contract.mocked_function(), 101);
+    }
+
+    function testMockFunctionMatchesBySelector() public {
+        zkVm.mockFunction(
+            address(my_contract),
+            address(model_contract),
+            abi.encodeWithSelector(MockFunctionContract.mocked_args_function.selector)
+        );
+        assertEq(my_contract.mocked_args_function(1), 12);
+        assertEq(my_contract.mocked_function(), 10);
+    }
+
+    function testMockFunctionMatchesExactCalldata() public {
+        zkVm.mockFunction(
+            address(my_contract),
+            address(model_contract),
+            abi.encodeWithSelector(MockFunctionContract.mocked_args_function.selector, 1)
+        );
+        assertEq(my_contract.mocked_args_function(1), 12);
+        assertEq(my_contract.mocked_args_function(3), 13);
+    }
+}
@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+interface ZkVm {
+    function signP256(uint256 privateKey, bytes32 digest) external pure returns (bytes32 r, bytes32 s);
+
+    function publicKeyP256(uint256 privateKey) external pure returns (uint256 publicKeyX, uint256 publicKeyY);
+}
+
+contract CheatcodeP256Test is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+
+    // the secp256r1 curve order
+    uint256 constant ORDER = 0xFFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551;
+
+    function testPublicKeyP256() external {
+        (uint256 x, uint256 y) = zkVm.publicKeyP256(1);
+        require(x == 0x6B17D1F2E12C4247F8BCE6E563A440F277037D812DEB33A0F4A13945D898C296, "x mismatch");
+        require(y == 0x4FE342E2FE1A7F9B8EE7EB4A7C0F9E162BCE33576B315ECECBB6406837BF51F5, "y mismatch");
+    }
+
+    function testSignP256() external {
+        bytes32 digest = keccak256("hello world");
+        (bytes32 r, bytes32 s) = zkVm.signP256(1, digest);
+        require(uint256(r) != 0 && uint256(r) < ORDER, "r out of range");
+        require(uint256(s) != 0 && uint256(s) < ORDER, "s out of range");
+
+        (bytes32 r2, bytes32 s2) = zkVm.signP256(1, digest);
+        require(r == r2 && s == s2, "signatures are not deterministic");
+    }
+
+    function testSignP256InvalidKeyReverts() external {
+        (bool success,) =
+            Constants.CHEATCODE_ADDRESS.call(abi.encodeWithSelector(ZkVm.signP256.selector, ORDER, bytes32(0)));
+        require(!success, "signing with a key out of range should revert");
+    }
+}
@@ -0,0 +1,125 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+interface JsonVm {
+    function keyExists(string calldata json, string calldata key) external view returns (bool);
+    function parseJsonKeys(string calldata json, string calldata key) external pure returns (string[] memory keys);
+}
+
+contract ParseJsonTest is Test {
+    JsonVm constant jsonVm = JsonVm(Constants.CHEATCODE_ADDRESS);
+
+    // the fields are sorted alphabetically, like the keys of the parsed objects
+    struct Deployment {
+        address owner;
+        string token;
+        uint256 totalSupply;
+    }
+
+    string constant JSON =
+        '{"deployment": {"owner": "0x36615Cf349d7F6344891B1e7CA7C72883F5dc049", "token": "USDC", "totalSupply": 1000}, "chains": [270, 324]}';
+
+    function testParseJsonKey() public {
+        uint256 totalSupply = abi.decode(vm.parseJson(JSON, ".deployment.totalSupply"), (uint256));
+        require(totalSupply == 1000, "total supply mismatch");
+
+        string memory token = abi.decode(vm.parseJson(JSON, "$.deployment.token"), (string));
+        require(keccak256(bytes(token)) == keccak256("USDC"), "token mismatch");
+
+        uint256[] memory chains = abi.decode(vm.parseJson(JSON, ".chains"), (uint256[]));
+        require(chains.length == 2 && chains[0] == 270 && chains[1] == 324, "chains mismatch");
+    }
+
+    function testParseJsonObject() public {
+        Deployment memory deployment = abi.decode(vm.parseJson(JSON, ".deployment"), (Deployment));
+        require(deployment.owner == 0x36615Cf349d7F6344891B1e7CA7C72883F5dc049, "owner mismatch");
+        require(keccak256(bytes(deployment.token)) == keccak256("USDC"), "token mismatch");
+        require(deployment.totalSupply == 1000, "total supply mismatch");
+    }
+
+    function testParseJsonRoot() public {
+        bytes memory data = vm.parseJson('{"a": 1, "b": 2}');
+        (uint256 a, uint256 b) = abi.decode(data, (uint256, uint256));
+        require(a == 1 && b == 2, "root mismatch");
+    }
+
+    function testParseJsonTyped() public {
+        string memory json =
+            '{"uint": "0x2a", "decimal": "42", "int": -7, "bool": true, "addr": "0x36615Cf349d7F6344891B1e7CA7C72883F5dc049", "str": "zk", "data": "0x0102", "hash": "0x0000000000000000000000000000000000000000000000000000000000000001"}';
+
+        require(vm.parseJsonUint(json, ".uint") == 42, "hex uint mismatch");
+        require(vm.parseJsonUint(json, ".decimal") == 42, "decimal uint mismatch");
+        require(vm.parseJsonInt(json, ".int") == -7, "int mismatch");
+        require(vm.parseJsonBool(json, ".bool"), "bool mismatch");
+        require(
+            vm.parseJsonAddress(json, ".addr") == 0x36615Cf349d7F6344891B1e7CA7C72883F5dc049, "address mismatch"
+        );
+        require(keccak256(bytes(vm.parseJsonString(json, ".str"))) == keccak256("zk"), "string mismatch");
+        require(keccak256(vm.parseJsonBytes(json, ".data")) == keccak256(hex"0102"), "bytes mismatch");
+        require(vm.parseJsonBytes32(json, ".hash") == bytes32(uint256(1)), "bytes32 mismatch");
+    }
+
+    function testParseJsonTypedArrays() public {
+        string memory json =
+            '{"uints": [1, "0x2"], "ints": [-1, 2], "bools": [true, false], "addrs": ["0x36615Cf349d7F6344891B1e7CA7C72883F5dc049"], "strs": ["a", "b"], "datas": ["0x01", "0x0203"], "hashes": ["0x0000000000000000000000000000000000000000000000000000000000000002"]}';
+
+        uint256[] memory uints = vm.parseJsonUintArray(json, ".uints");
+        require(uints.length == 2 && uints[0] == 1 && uints[1] == 2, "uint array mismatch");
+        int256[] memory ints = vm.parseJsonIntArray(json, ".ints");
+        require(ints.length == 2 && ints[0] == -1 && ints[1] == 2, "int array mismatch");
+        bool[] memory bools = vm.parseJsonBoolArray(json, ".bools");
+        require(bools.length == 2 && bools[0] && !bools[1], "bool array mismatch");
+        address[] memory addrs = vm.parseJsonAddressArray(json, ".addrs");
+        require(addrs.length == 1 && addrs[0] == 0x36615Cf349d7F6344891B1e7CA7C72883F5dc049, "address array mismatch");
+        string[] memory strs = vm.parseJsonStringArray(json, ".strs");
+        require(strs.length == 2 && keccak256(bytes(strs[1])) == keccak256("b"), "string array mismatch");
+        bytes[] memory datas = vm.parseJsonBytesArray(json, ".datas");
+        require(datas.length == 2 && keccak256(datas[1]) == keccak256(hex"0203"), "bytes array mismatch");
+        bytes32[] memory hashes = vm.parseJsonBytes32Array(json, ".hashes");
+        require(hashes.length == 1 && hashes[0] == bytes32(uint256(2)), "bytes32 array mismatch");
+    }
+
+    function testParseJsonTypedMismatchReverts() public {
+        (bool success,) = Constants.CHEATCODE_ADDRESS.call(
+            abi.encodeWithSignature("parseJsonUint(string,string)", '{"a": "not a number"}', ".a")
+        );
+        require(!success, "parsing a string as uint should revert");
+
+        (success,) = Constants.CHEATCODE_ADDRESS.call(
+            abi.encodeWithSignature("parseJsonBytes(string,string)", '{"a": "0102"}', ".a")
+        );
+        require(!success, "parsing bytes without a hex prefix should revert");
+    }
+
+    function testKeyExists() public view {
+        require(jsonVm.keyExists(JSON, ".deployment.owner"), "owner should exist");
+        require(jsonVm.keyExists(JSON, "$.chains"), "chains should exist");
+        require(!jsonVm.keyExists(JSON, ".deployment.admin"), "admin should not exist");
+    }
+
+    function testParseJsonKeys() public view {
+        string[] memory keys = jsonVm.parseJsonKeys(JSON, ".deployment");
+        require(keys.length == 3, "keys length mismatch");
+        require(keccak256(bytes(keys[0])) == keccak256("owner"), "first key mismatch");
+        require(keccak256(bytes(keys[2])) == keccak256("totalSupply"), "last key mismatch");
+
+        keys = jsonVm.parseJsonKeys(JSON, "$");
+        require(keys.length == 2, "root keys length mismatch");
+    }
+
+    function testParseJsonKeysOfNonObjectReverts() public {
+        (bool success,) = Constants.CHEATCODE_ADDRESS.call(
+            abi.encodeWithSelector(JsonVm.parseJsonKeys.selector, JSON, ".chains")
+        );
+        require(!success, "parsing the keys of an array should revert");
+    }
+
+    function testParseJsonInvalidReverts() public {
+        (bool success,) =
+            Constants.CHEATCODE_ADDRESS.call(abi.encodeWithSignature("parseJson(string)", "not json"));
+        require(!success, "parsing invalid JSON should revert");
+    }
+}
@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+contract PrankVictim {
+    function assertCallerAndOrigin(address expectedSender, address expectedOrigin) public view {
+        require(msg.sender == expectedSender, "unexpected msg.sender");
+        require(tx.origin == expectedOrigin, "unexpected tx.origin");
+    }
+}
+
+contract PrankedDeployment {
+    address public deployer;
+
+    constructor() {
+        deployer = msg.sender;
+    }
+}
+
+contract CheatcodePrankTest is Test {
+    address constant TEST_ADDRESS = 0x6Eb28604685b1F182dAB800A1Bfa4BaFdBA8a79a;
+    address constant TEST_ORIGIN = 0xdEBe90b7BFD87Af696B1966082F6515a6E72F3d8;
+
+    function testPrankOnlyTheNextCall() public {
+        address originalOrigin = tx.origin;
+        PrankVictim victim = new PrankVictim();
+
+        vm.prank(TEST_ADDRESS);
+        victim.assertCallerAndOrigin(TEST_ADDRESS, originalOrigin);
+
+        // the prank was reset after the call
+        victim.assertCallerAndOrigin(address(this), originalOrigin);
+    }
+
+    function testPrankWithOrigin() public {
+        address originalOrigin = tx.origin;
+        PrankVictim victim = new PrankVictim();
+
+        vm.prank(TEST_ADDRESS, TEST_ORIGIN);
+        victim.assertCallerAndOrigin(TEST_ADDRESS, TEST_ORIGIN);
+
+        victim.assertCallerAndOrigin(address(this), originalOrigin);
+        require(tx.origin == originalOrigin, "tx.origin was not restored");
+    }
+
+    function testPrankDeployment() public {
+        vm.prank(TEST_ADDRESS);
+        PrankedDeployment deployment = new PrankedDeployment();
+        require(deployment.deployer() == TEST_ADDRESS, "constructor was not pranked");
+
+        // the prank was reset after the deployment
+        deployment = new PrankedDeployment();
+        require(deployment.deployer() == address(this), "prank was not reset");
+    }
+
+    function testPrankIsReadByReadCallers() public {
+        vm.prank(TEST_ADDRESS);
+        (Vm.CallerMode mode, address sender,) = vm.readCallers();
+        assertEq(uint256(mode), uint256(Vm.CallerMode.Prank));
+        assertEq(sender, TEST_ADDRESS);
+    }
+}
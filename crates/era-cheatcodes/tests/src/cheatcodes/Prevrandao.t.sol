@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+contract RandomnessConsumer {
+    function prevrandao() public view returns (uint256) {
+        return block.prevrandao;
+    }
+}
+
+contract CheatcodePrevrandaoTest is Test {
+    uint256 constant SETUP_PREVRANDAO = 0x42;
+    uint256 constant NEW_PREVRANDAO = 0x1234;
+
+    function setUp() public {
+        vm.prevrandao(bytes32(SETUP_PREVRANDAO));
+    }
+
+    function testPrevrandaoIsKeptFromSetUp() public {
+        require(block.prevrandao == SETUP_PREVRANDAO, "prevrandao was not kept from setUp");
+    }
+
+    function testPrevrandao() public {
+        RandomnessConsumer consumer = new RandomnessConsumer();
+
+        vm.prevrandao(bytes32(NEW_PREVRANDAO));
+
+        require(block.prevrandao == NEW_PREVRANDAO, "prevrandao was not changed");
+        require(consumer.prevrandao() == NEW_PREVRANDAO, "prevrandao was not changed for calls");
+    }
+}
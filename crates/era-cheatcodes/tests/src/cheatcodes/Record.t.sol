@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console, stdStorage, StdStorage} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+contract RecordAccess {
+    uint256 public value = 1;
+
+    function record() public returns (NestedRecordAccess) {
+        assembly {
+            sstore(1, add(sload(1), 1))
+        }
+
+        NestedRecordAccess inner = new NestedRecordAccess();
+        inner.record();
+
+        return inner;
+    }
+}
+
+contract NestedRecordAccess {
+    function record() public {
+        assembly {
+            sstore(2, add(sload(2), 1))
+        }
+    }
+}
+
+contract CheatcodeRecordTest is Test {
+    using stdStorage for StdStorage;
+
+    function testRecordAccess() public {
+        RecordAccess target = new RecordAccess();
+
+        vm.record();
+        NestedRecordAccess inner = target.record();
+
+        (bytes32[] memory reads, bytes32[] memory writes) = vm.accesses(address(target));
+        (bytes32[] memory innerReads, bytes32[] memory innerWrites) = vm.accesses(address(inner));
+
+        require(reads.length == 2, "number of reads is incorrect");
+        require(reads[0] == bytes32(uint256(1)), "key for read 0 is incorrect");
+        require(reads[1] == bytes32(uint256(1)), "key for read 1 is incorrect");
+        require(writes.length == 1, "number of writes is incorrect");
+        require(writes[0] == bytes32(uint256(1)), "key for write is incorrect");
+
+        require(innerReads.length == 2, "number of nested reads is incorrect");
+        require(innerReads[0] == bytes32(uint256(2)), "key for nested read 0 is incorrect");
+        require(innerWrites.length == 1, "number of nested writes is incorrect");
+        require(innerWrites[0] == bytes32(uint256(2)), "key for nested write is incorrect");
+    }
+
+    function testNoAccessesWithoutRecording() public {
+        RecordAccess target = new RecordAccess();
+        target.record();
+
+        (bytes32[] memory reads, bytes32[] memory writes) = vm.accesses(address(target));
+        require(reads.length == 0 && writes.length == 0, "accesses were recorded");
+    }
+
+    function testStdStorageFindsSlots() public {
+        RecordAccess target = new RecordAccess();
+
+        uint256 slot = stdstore.target(address(target)).sig(RecordAccess.value.selector).find();
+        require(slot == 0, "slot of value was not found");
+
+        stdstore.target(address(target)).sig(RecordAccess.value.selector).checked_write(42);
+        require(target.value() == 42, "value was not written");
+    }
+}
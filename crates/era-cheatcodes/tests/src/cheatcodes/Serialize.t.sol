@@ -12,7 +12,7 @@ contract CheatcodeSerializeTest is Test {
         string memory testString = vm.serializeAddress("obj1", "address", TEST_ADDRESS);
         require(
             keccak256(bytes(testString)) ==
-                keccak256(bytes("0x6Eb28604685b1F182dAB800A1Bfa4BaFdBA8a79a")),
+                keccak256(bytes('{"address":"0x6Eb28604685b1F182dAB800A1Bfa4BaFdBA8a79a"}')),
             "serializeAddress mismatch"
         );
     }
@@ -20,7 +20,7 @@ contract CheatcodeSerializeTest is Test {
     function testSerializeBool() external {
         string memory testString = vm.serializeBool("obj1", "boolean", true);
         require(
-            keccak256(bytes(testString)) == keccak256(bytes("true")),
+            keccak256(bytes(testString)) == keccak256(bytes('{"boolean":true}')),
             "serializeBool mismatch"
         );
     }
@@ -28,8 +28,92 @@ contract CheatcodeSerializeTest is Test {
     function testSerializeUint() external {
         string memory testString = vm.serializeUint("obj1", "uint", 99);
         require(
-            keccak256(bytes(testString)) == keccak256(bytes("99")),
+            keccak256(bytes(testString)) == keccak256(bytes('{"uint":99}')),
             "serializeUint mismatch"
         );
     }
+
+    function testSerializeScalars() external {
+        vm.serializeInt("scalars", "int", -7);
+        vm.serializeBytes32("scalars", "bytes32", bytes32(uint256(1)));
+        string memory json = vm.serializeBytes("scalars", "bytes", hex"0102");
+        require(
+            keccak256(bytes(json)) ==
+                keccak256(
+                    bytes(
+                        '{"int":-7,"bytes32":"0x0000000000000000000000000000000000000000000000000000000000000001","bytes":"0x0102"}'
+                    )
+                ),
+            "scalars mismatch"
+        );
+    }
+
+    function testSerializeArrays() external {
+        uint256[] memory uints = new uint256[](2);
+        uints[0] = 1;
+        uints[1] = 2;
+        int256[] memory ints = new int256[](1);
+        ints[0] = -1;
+        bool[] memory bools = new bool[](1);
+        bools[0] = true;
+        address[] memory addresses = new address[](1);
+        addresses[0] = TEST_ADDRESS;
+        string[] memory strings = new string[](2);
+        strings[0] = "a";
+        strings[1] = "b";
+        bytes[] memory datas = new bytes[](1);
+        datas[0] = hex"01";
+
+        vm.serializeUint("arrays", "uints", uints);
+        vm.serializeInt("arrays", "ints", ints);
+        vm.serializeBool("arrays", "bools", bools);
+        vm.serializeAddress("arrays", "addresses", addresses);
+        vm.serializeString("arrays", "strings", strings);
+        string memory json = vm.serializeBytes("arrays", "datas", datas);
+        require(
+            keccak256(bytes(json)) ==
+                keccak256(
+                    bytes(
+                        '{"uints":[1,2],"ints":[-1],"bools":[true],"addresses":["0x6Eb28604685b1F182dAB800A1Bfa4BaFdBA8a79a"],"strings":["a","b"],"datas":["0x01"]}'
+                    )
+                ),
+            "arrays mismatch"
+        );
+
+        bytes32[] memory hashes = new bytes32[](0);
+        json = vm.serializeBytes32("empty", "hashes", hashes);
+        require(keccak256(bytes(json)) == keccak256(bytes('{"hashes":[]}')), "empty array mismatch");
+    }
+
+    function testSerializeOverwritesKey() external {
+        vm.serializeUint("overwrite", "a", 1);
+        vm.serializeUint("overwrite", "b", 2);
+        string memory json = vm.serializeUint("overwrite", "a", 3);
+        require(keccak256(bytes(json)) == keccak256(bytes('{"a":3,"b":2}')), "overwritten key mismatch");
+    }
+
+    function testSerializeJsonComposesObjects() external {
+        vm.serializeUint("inner", "uint", 99);
+        string memory inner = vm.serializeBool("inner", "boolean", true);
+        require(
+            keccak256(bytes(inner)) == keccak256(bytes('{"uint":99,"boolean":true}')),
+            "serializeBool mismatch"
+        );
+        inner = vm.serializeString("inner", "string", "zk");
+
+        vm.serializeJson("outer", '{"version":1}');
+        string memory outer = vm.serializeString("outer", "inner", inner);
+        require(
+            keccak256(bytes(outer)) ==
+                keccak256(bytes('{"version":1,"inner":{"uint":99,"boolean":true,"string":"zk"}}')),
+            "composed object mismatch"
+        );
+    }
+
+    function testSerializeJsonRejectsInvalidObjects() external {
+        (bool success,) = Constants.CHEATCODE_ADDRESS.call(
+            abi.encodeWithSignature("serializeJson(string,string)", "obj1", "not json")
+        );
+        require(!success, "invalid JSON should revert");
+    }
 }
@@ -4,13 +4,41 @@ pragma solidity ^0.8.13;
 import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
 import {Constants} from "./Constants.sol";
 
+struct Wallet {
+    address addr;
+    uint256 publicKeyX;
+    uint256 publicKeyY;
+    uint256 privateKey;
+}
+
+interface ZkVm {
+    function sign(Wallet calldata wallet, bytes32 digest) external returns (uint8 v, bytes32 r, bytes32 s);
+}
+
 contract SignTest is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+
     function test_Sign() public {
         (address alice, uint256 alicePk) = makeAddrAndKey("alice");
         bytes32 message = "hello world";
         (uint8 v, bytes32 r, bytes32 s) = vm.sign(alicePk, message);
 
-        address signer = ecrecover(keccak256(abi.encodePacked(message)), v, r, s);
+        address signer = ecrecover(message, v, r, s);
         assertEq(alice, signer); // [PASS]
     }
+
+    function test_SignWithWallet() public {
+        (address alice, uint256 alicePk) = makeAddrAndKey("alice");
+        bytes32 digest = keccak256("hello world");
+        (uint8 v, bytes32 r, bytes32 s) = zkVm.sign(Wallet(alice, 0, 0, alicePk), digest);
+
+        assertEq(alice, ecrecover(digest, v, r, s));
+    }
+
+    function test_SignInvalidKeyReverts() public {
+        (bool success,) = Constants.CHEATCODE_ADDRESS.call(
+            abi.encodeWithSelector(ZkVm.sign.selector, Wallet(address(0), 0, 0, 0), bytes32(0))
+        );
+        require(!success, "signing with an invalid key should revert");
+    }
 }
@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+interface ZkVm {
+    function skip(bool skipTest) external;
+}
+
+contract SkipTest is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+
+    function testSkip() public {
+        zkVm.skip(true);
+        revert("the test should have been skipped");
+    }
+
+    function testDontSkip() public {
+        zkVm.skip(false);
+        assertTrue(true);
+    }
+
+    function testFailSkipWithPendingExpectRevert() public {
+        vm.expectRevert();
+        zkVm.skip(true);
+    }
+
+    function testFuzzSkip(uint256 x) public {
+        zkVm.skip(true);
+        revert("the fuzz test should have been skipped");
+    }
+}
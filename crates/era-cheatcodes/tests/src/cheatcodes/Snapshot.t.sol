@@ -5,12 +5,21 @@ import {Test, Vm, console2 as console} from "../../lib/forge-std/src/Test.sol";
 import {Constants} from "./Constants.sol";
 import {Utils} from "./Utils.sol";
 
+interface NamedSnapshotVm {
+    function snapshotNamed(string calldata name) external returns (uint256 snapshotId);
+
+    function revertToNamed(string calldata name) external returns (bool success);
+
+    function diffSnapshots(uint256 fromSnapshotId, uint256 toSnapshotId) external view returns (string memory diff);
+}
+
 struct Storage {
     uint256 slot0;
     uint256 slot1;
 }
 
 contract SnapshotTest is Test {
+    NamedSnapshotVm constant namedVm = NamedSnapshotVm(Constants.CHEATCODE_ADDRESS);
     Storage store;
 
     function setUp() public {
@@ -74,4 +83,46 @@ contract SnapshotTest is Test {
             "snapshot revert for block.timestamp unsuccessful"
         );
     }
+
+    function testNamedSnapshot() public {
+        namedVm.snapshotNamed("beforeUpdate");
+
+        store.slot0 = 300;
+        namedVm.snapshotNamed("afterUpdate");
+
+        store.slot1 = 400;
+
+        require(namedVm.revertToNamed("afterUpdate"), "revert to afterUpdate failed");
+        assertEq(store.slot0, 300, "snapshot revert for slot 0 unsuccessful");
+        assertEq(store.slot1, 20, "snapshot revert for slot 1 unsuccessful");
+
+        require(namedVm.revertToNamed("beforeUpdate"), "revert to beforeUpdate failed");
+        assertEq(store.slot0, 10, "snapshot revert for slot 0 unsuccessful");
+        assertEq(store.slot1, 20, "snapshot revert for slot 1 unsuccessful");
+    }
+
+    function testRevertToUnknownNamedSnapshot() public {
+        namedVm.snapshotNamed("known");
+        require(namedVm.revertToNamed("known"), "revert to known snapshot failed");
+
+        require(!namedVm.revertToNamed("known"), "snapshot was not deleted on revert");
+        require(!namedVm.revertToNamed("unknown"), "reverted to unknown snapshot");
+    }
+
+    function testDiffSnapshots() public {
+        string memory empty = '{"storage":[],"balances":[],"nonces":[]}';
+
+        uint256 before = vm.snapshot();
+        uint256 unchanged = vm.snapshot();
+        assertEq(namedVm.diffSnapshots(before, unchanged), empty, "unexpected changes");
+
+        store.slot0 = 300;
+        vm.deal(address(0xBEEF), 1 ether);
+        uint256 changed = vm.snapshot();
+
+        string memory diff = namedVm.diffSnapshots(before, changed);
+        assertTrue(keccak256(bytes(diff)) != keccak256(bytes(empty)), "changes not reported");
+        assertEq(vm.parseJsonAddress(diff, ".balances[0].address"), address(0xBEEF), "balance not attributed");
+        assertEq(namedVm.diffSnapshots(changed, changed), empty, "snapshot differs from itself");
+    }
 }
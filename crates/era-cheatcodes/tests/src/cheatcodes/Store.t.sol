@@ -4,6 +4,10 @@ pragma solidity ^0.8.13;
 import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
 import {Constants} from "./Constants.sol";
 
+interface StoreBatchVm {
+    function storeBatch(address[] calldata targets, bytes32[] calldata slots, bytes32[] calldata values) external;
+}
+
 contract Storage {
     uint256 public slot0 = 10;
     uint256 public slot1 = 20;
@@ -26,4 +30,33 @@ contract StoreTest is Test {
         assertEq(store.slot0(), 1, "store failed");
         assertEq(store.slot1(), 20, "store failed");
     }
+
+    function testStoreBatch() public {
+        Storage other = new Storage();
+        address[] memory targets = new address[](3);
+        bytes32[] memory slots = new bytes32[](3);
+        bytes32[] memory values = new bytes32[](3);
+        (targets[0], slots[0], values[0]) = (address(store), bytes32(0), bytes32(uint256(1)));
+        (targets[1], slots[1], values[1]) = (address(other), bytes32(uint256(1)), bytes32(uint256(2)));
+        (targets[2], slots[2], values[2]) = (address(store), bytes32(0), bytes32(uint256(3)));
+
+        StoreBatchVm(Constants.CHEATCODE_ADDRESS).storeBatch(targets, slots, values);
+
+        assertEq(store.slot0(), 3, "last write to a slot should win");
+        assertEq(store.slot1(), 20, "store batch failed");
+        assertEq(other.slot0(), 10, "store batch failed");
+        assertEq(other.slot1(), 2, "store batch failed");
+    }
+
+    function testStoreBatchRevertsOnLengthMismatch() public {
+        (bool success, ) = Constants.CHEATCODE_ADDRESS.call(
+            abi.encodeWithSelector(
+                StoreBatchVm.storeBatch.selector,
+                new address[](1),
+                new bytes32[](2),
+                new bytes32[](1)
+            )
+        );
+        require(!success, "storeBatch should revert on length mismatch");
+    }
 }
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+interface TxOriginVm {
+    function setTxOrigin(address newOrigin) external;
+
+    function txOrigin() external view returns (address origin);
+}
+
+contract OriginReader {
+    function origin() public view returns (address) {
+        return tx.origin;
+    }
+}
+
+contract CheatcodeTxOriginTest is Test {
+    address constant TEST_ORIGIN = 0xdEBe90b7BFD87Af696B1966082F6515a6E72F3d8;
+
+    TxOriginVm constant originVm = TxOriginVm(Constants.CHEATCODE_ADDRESS);
+
+    function testTxOriginReadsCurrentOrigin() public view {
+        require(originVm.txOrigin() == tx.origin, "origin mismatch");
+    }
+
+    function testSetTxOrigin() public {
+        OriginReader reader = new OriginReader();
+        address sender = msg.sender;
+
+        originVm.setTxOrigin(TEST_ORIGIN);
+
+        require(tx.origin == TEST_ORIGIN, "tx.origin not set");
+        require(originVm.txOrigin() == TEST_ORIGIN, "txOrigin not set");
+        require(reader.origin() == TEST_ORIGIN, "origin of nested call not set");
+        require(msg.sender == sender, "sender changed");
+    }
+}
@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+struct Wallet {
+    address addr;
+    uint256 publicKeyX;
+    uint256 publicKeyY;
+    uint256 privateKey;
+}
+
+interface ZkVm {
+    function createWallet(string calldata walletLabel) external returns (Wallet memory wallet);
+
+    function createWallet(uint256 privateKey) external returns (Wallet memory wallet);
+
+    function createWallet(uint256 privateKey, string calldata walletLabel) external returns (Wallet memory wallet);
+
+    function getNonce(Wallet calldata wallet) external returns (uint64 nonce);
+
+    function sign(Wallet calldata wallet, bytes32 digest) external returns (uint8 v, bytes32 r, bytes32 s);
+
+    function getLabel(address account) external returns (string memory currentLabel);
+}
+
+contract CheatcodeWalletTest is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+
+    function testCreateWalletFromLabel() external {
+        Wallet memory wallet = zkVm.createWallet("alice");
+
+        require(wallet.privateKey == uint256(keccak256("alice")), "private key mismatch");
+        require(wallet.addr == vm.addr(wallet.privateKey), "address mismatch");
+        require(wallet.publicKeyX != 0 && wallet.publicKeyY != 0, "public key is not set");
+        // the address is the hash of the public key
+        require(
+            wallet.addr == address(uint160(uint256(keccak256(abi.encode(wallet.publicKeyX, wallet.publicKeyY))))),
+            "public key mismatch"
+        );
+        require(keccak256(bytes(zkVm.getLabel(wallet.addr))) == keccak256("alice"), "wallet is not labeled");
+    }
+
+    function testCreateWalletFromKey() external {
+        Wallet memory wallet = zkVm.createWallet(1);
+        require(wallet.addr == vm.addr(1), "address mismatch");
+        require(
+            wallet.publicKeyX == 0x79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798,
+            "public key is not the generator"
+        );
+
+        Wallet memory labeled = zkVm.createWallet(1, "one");
+        require(labeled.addr == wallet.addr, "labeled address mismatch");
+        require(keccak256(bytes(zkVm.getLabel(wallet.addr))) == keccak256("one"), "wallet is not labeled");
+    }
+
+    function testSignAndGetNonceWithWallet() external {
+        Wallet memory wallet = zkVm.createWallet("bob");
+
+        bytes32 digest = keccak256("hello world");
+        (uint8 v, bytes32 r, bytes32 s) = zkVm.sign(wallet, digest);
+        require(ecrecover(digest, v, r, s) == wallet.addr, "signer mismatch");
+
+        require(zkVm.getNonce(wallet) == vm.getNonce(wallet.addr), "nonce mismatch");
+    }
+}
@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+struct StorageOverride {
+    address account;
+    bytes32 slot;
+    bytes32 value;
+}
+
+struct BalanceOverride {
+    address account;
+    uint256 balance;
+}
+
+struct CodeOverride {
+    address account;
+    bytes code;
+}
+
+interface ZkVm {
+    function withStateOverrides(StorageOverride[] calldata overrides) external;
+    function withStateOverrides(
+        BalanceOverride[] calldata balances,
+        CodeOverride[] calldata codes,
+        StorageOverride[] calldata slots
+    ) external;
+}
+
+contract Counter {
+    uint256 public count = 1;
+    uint256 public limit = 10;
+
+    function increment() public returns (uint256) {
+        require(count < limit, "limit reached");
+        count += 1;
+        return count;
+    }
+
+    function set(uint256 value) public {
+        count = value;
+    }
+
+    function setAndRevert(uint256 value) public {
+        count = value;
+        revert("reverted");
+    }
+}
+
+contract LimitReader {
+    uint256 public limit;
+
+    constructor(Counter counter) {
+        limit = counter.limit();
+    }
+}
+
+contract BalanceReader {
+    function balanceOf(address account) public view returns (uint256) {
+        return account.balance;
+    }
+}
+
+contract Answer {
+    function answer() public pure returns (uint256) {
+        return 42;
+    }
+}
+
+contract CheatcodeWithStateOverridesTest is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+
+    function overrides(address account, bytes32 slot, bytes32 value)
+        internal
+        pure
+        returns (StorageOverride[] memory result)
+    {
+        result = new StorageOverride[](1);
+        result[0] = StorageOverride(account, slot, value);
+    }
+
+    function testOverridesAreScopedToTheNextCall() public {
+        Counter counter = new Counter();
+
+        zkVm.withStateOverrides(overrides(address(counter), bytes32(uint256(1)), bytes32(uint256(100))));
+        require(counter.limit() == 100, "slot was not overridden");
+
+        require(counter.limit() == 10, "slot was not restored");
+    }
+
+    function testWritesOfTheCallAreKept() public {
+        Counter counter = new Counter();
+
+        zkVm.withStateOverrides(overrides(address(counter), bytes32(uint256(0)), bytes32(uint256(5))));
+        require(counter.increment() == 6, "slot was not overridden");
+
+        require(counter.count() == 6, "write of the call was not kept");
+    }
+
+    function testWritesOfTheOverriddenValueAreKept() public {
+        Counter counter = new Counter();
+
+        zkVm.withStateOverrides(overrides(address(counter), bytes32(uint256(0)), bytes32(uint256(5))));
+        counter.set(5);
+
+        require(counter.count() == 5, "write of the call was not kept");
+    }
+
+    function testWritesOfRevertedCallsAreDropped() public {
+        Counter counter = new Counter();
+
+        zkVm.withStateOverrides(overrides(address(counter), bytes32(uint256(0)), bytes32(uint256(5))));
+        (bool success,) = address(counter).call(abi.encodeWithSelector(Counter.setAndRevert.selector, 7));
+        require(!success, "call did not revert");
+
+        require(counter.count() == 1, "slot was not restored");
+    }
+
+    function testOverridesApplyToConstructors() public {
+        Counter counter = new Counter();
+
+        zkVm.withStateOverrides(overrides(address(counter), bytes32(uint256(1)), bytes32(uint256(100))));
+        LimitReader reader = new LimitReader(counter);
+
+        require(reader.limit() == 100, "slot was not overridden in the constructor");
+        require(counter.limit() == 10, "slot was not restored");
+    }
+
+    function testOverridesBalances() public {
+        BalanceReader reader = new BalanceReader();
+        address account = address(0xba1a);
+
+        BalanceOverride[] memory balances = new BalanceOverride[](1);
+        balances[0] = BalanceOverride(account, 1234);
+        zkVm.withStateOverrides(balances, new CodeOverride[](0), new StorageOverride[](0));
+        require(reader.balanceOf(account) == 1234, "balance was not overridden");
+
+        require(reader.balanceOf(account) != 1234, "balance was not restored");
+    }
+
+    function testOverridesCode() public {
+        address account = address(0xc0de);
+        bytes memory code = vm.getCode("WithStateOverrides.t.sol:Answer");
+
+        CodeOverride[] memory codes = new CodeOverride[](1);
+        codes[0] = CodeOverride(account, code);
+        zkVm.withStateOverrides(new BalanceOverride[](0), codes, new StorageOverride[](0));
+        require(Answer(account).answer() == 42, "code was not overridden");
+
+        require(account.code.length == 0, "code was not restored");
+    }
+
+    function testCannotOverrideTwice() public {
+        Counter counter = new Counter();
+        zkVm.withStateOverrides(overrides(address(counter), bytes32(uint256(1)), bytes32(uint256(100))));
+
+        (bool success,) = address(zkVm).call(
+            abi.encodeWithSignature(
+                "withStateOverrides((address,bytes32,bytes32)[])",
+                overrides(address(counter), bytes32(uint256(1)), bytes32(uint256(50)))
+            )
+        );
+        require(!success, "overrides were set twice");
+    }
+}
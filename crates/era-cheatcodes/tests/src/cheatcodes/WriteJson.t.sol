@@ -56,4 +56,41 @@ contract FsTest is Test {
             "read data did not match write data"
         );
     }
+
+    function testWriteJsonNestedKey() public {
+        string memory path = "src/fixtures/Json/write_test_nested.json";
+
+        vm.writeJson('{"a": {"b": 1}}', path);
+
+        // Overwrite an existing nested key
+        vm.writeJson("2", path, ".a.b");
+        // Create a new nested key, along with its missing parents
+        vm.writeJson('"test"', path, "$.c.d");
+
+        string memory readData = vm.readFile(path);
+
+        require(
+            keccak256(bytes(readData)) ==
+                keccak256(
+                    bytes(
+                        '{\n  "a": {\n    "b": 2\n  },\n  "c": {\n    "d": "test"\n  }\n}'
+                    )
+                ),
+            "read data did not match write data"
+        );
+    }
+
+    function testWriteJsonRevertsOnInvalidJson() public {
+        string memory path = "src/fixtures/Json/write_test_invalid.json";
+
+        (bool success, ) = Constants.CHEATCODE_ADDRESS.call(
+            abi.encodeWithSignature(
+                "writeJson(string,string)",
+                "{not json",
+                path
+            )
+        );
+
+        require(!success, "writeJson should have reverted");
+    }
 }
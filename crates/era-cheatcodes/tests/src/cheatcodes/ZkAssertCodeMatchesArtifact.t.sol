@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+import {Counter} from "./Counter.sol";
+
+interface ZkVm {
+    function zkAssertCodeMatchesArtifact(address account, string calldata artifactPath) external view;
+}
+
+contract ZkAssertCodeMatchesArtifactTest is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+
+    function testZkAssertCodeMatchesArtifact() public {
+        Counter counter = new Counter();
+
+        zkVm.zkAssertCodeMatchesArtifact(address(counter), "Counter.sol:Counter");
+        zkVm.zkAssertCodeMatchesArtifact(address(counter), "zkout/Counter.sol/Counter.json");
+    }
+
+    function testZkAssertCodeMatchesArtifactMismatch() public {
+        Counter counter = new Counter();
+
+        (bool success, ) = Constants.CHEATCODE_ADDRESS.call(
+            abi.encodeWithSelector(
+                ZkVm.zkAssertCodeMatchesArtifact.selector,
+                address(counter),
+                "ZkAssertCodeMatchesArtifact.t.sol:ZkAssertCodeMatchesArtifactTest"
+            )
+        );
+        require(!success, "mismatching code should have reverted");
+
+        (success, ) = Constants.CHEATCODE_ADDRESS.call(
+            abi.encodeWithSelector(
+                ZkVm.zkAssertCodeMatchesArtifact.selector,
+                address(0xC0FFEE),
+                "Counter.sol:Counter"
+            )
+        );
+        require(!success, "account without code should have reverted");
+    }
+}
@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+import {Counter} from "./Counter.sol";
+
+interface ZkVm {
+    function zkFailNextStorageWrite(address target, bytes32 slot) external;
+}
+
+contract ZkFailNextStorageWriteTest is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+
+    function testZkFailNextStorageWrite() public {
+        Counter counter = new Counter();
+
+        zkVm.zkFailNextStorageWrite(address(counter), bytes32(uint256(0)));
+        (bool success, ) = address(counter).call(abi.encodeWithSelector(Counter.increment.selector));
+        require(!success, "storage write should have failed");
+        require(counter.number() == 0, "failed write should have been rolled back");
+
+        counter.increment();
+        require(counter.number() == 1, "only the next write should fail");
+    }
+
+    function testZkFailNextStorageWriteOtherSlot() public {
+        Counter counter = new Counter();
+
+        zkVm.zkFailNextStorageWrite(address(counter), bytes32(uint256(1)));
+        counter.increment();
+        require(counter.number() == 1, "write to another slot should not fail");
+    }
+}
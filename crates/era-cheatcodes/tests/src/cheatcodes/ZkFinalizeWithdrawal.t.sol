@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+interface ZkVm {
+    function zkFinalizeWithdrawal(string calldata urlOrAlias, bytes32 withdrawalHash)
+        external
+        returns (address l1Target, bytes memory data);
+}
+
+contract ZkFinalizeWithdrawalTest is Test {
+    function testFinalizeUnknownWithdrawalReverts() public {
+        (bool success,) = Constants.CHEATCODE_ADDRESS.call(
+            abi.encodeWithSelector(ZkVm.zkFinalizeWithdrawal.selector, "local", bytes32(0))
+        );
+        require(!success, "finalizing an unknown withdrawal should revert");
+    }
+}
@@ -0,0 +1,22 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+interface ZkVm {
+    function zkProtocolVersion() external view returns (uint16 protocolVersion, string memory vmVersion);
+}
+
+contract CheatcodeZkProtocolVersionTest is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+
+    function testZkProtocolVersion() public view {
+        (uint16 protocolVersion, string memory vmVersion) = zkVm.zkProtocolVersion();
+        console.log("protocol version", protocolVersion, vmVersion);
+
+        require(protocolVersion > 0, "protocol version is not set");
+        bytes memory name = bytes(vmVersion);
+        require(name.length > 2 && name[0] == "V" && name[1] == "m", "vm version is not named");
+    }
+}
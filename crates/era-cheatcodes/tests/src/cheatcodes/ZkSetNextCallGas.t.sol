@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+
+interface ZkVm {
+    function zkSetNextCallGas(uint256 gas) external;
+}
+
+contract Burner {
+    uint256 public counter;
+
+    function burn(uint256 iterations) public {
+        for (uint256 i = 0; i < iterations; i++) {
+            counter += 1;
+        }
+    }
+}
+
+contract ZkSetNextCallGasTest is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+
+    function testZkSetNextCallGasRunsOutOfGas() public {
+        Burner burner = new Burner();
+
+        zkVm.zkSetNextCallGas(10_000);
+        (bool success, ) = address(burner).call(abi.encodeWithSelector(Burner.burn.selector, 1000));
+        require(!success, "call should have run out of gas");
+        require(burner.counter() == 0, "state changes should have been reverted");
+    }
+
+    function testZkSetNextCallGasOnlyAffectsNextCall() public {
+        Burner burner = new Burner();
+
+        zkVm.zkSetNextCallGas(10_000);
+        (bool success, ) = address(burner).call(abi.encodeWithSelector(Burner.burn.selector, 1000));
+        require(!success, "call should have run out of gas");
+
+        (success, ) = address(burner).call(abi.encodeWithSelector(Burner.burn.selector, 1000));
+        require(success, "call should not be capped");
+        require(burner.counter() == 1000, "burn was not executed");
+    }
+
+    function testZkSetNextCallGasSufficientErgs() public {
+        Burner burner = new Burner();
+
+        zkVm.zkSetNextCallGas(10_000_000);
+        (bool success, ) = address(burner).call(abi.encodeWithSelector(Burner.burn.selector, 10));
+        require(success, "call should have enough ergs");
+        require(burner.counter() == 10, "burn was not executed");
+    }
+}
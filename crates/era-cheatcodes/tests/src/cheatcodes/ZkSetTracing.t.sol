@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import {Test, console2 as console} from "../../lib/forge-std/src/Test.sol";
+import {Constants} from "./Constants.sol";
+import {Counter} from "./Counter.sol";
+
+interface ZkVm {
+    function zkSetTracing(uint8 level) external;
+}
+
+contract ZkSetTracingTest is Test {
+    ZkVm constant zkVm = ZkVm(Constants.CHEATCODE_ADDRESS);
+
+    function testZkSetTracing() public {
+        Counter counter = new Counter();
+
+        zkVm.zkSetTracing(2);
+        counter.increment();
+        zkVm.zkSetTracing(0);
+
+        require(counter.number() == 1, "tracing altered execution");
+    }
+}
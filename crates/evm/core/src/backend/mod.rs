@@ -496,6 +496,15 @@ impl Backend {
         ret
     }
 
+    /// Inserts a bytecode that can be looked up by its hash, e.g. a zkEVM factory dependency.
+    pub fn insert_bytecode(&mut self, hash: B256, code: Bytecode) {
+        if let Some(db) = self.active_fork_db_mut() {
+            db.contracts.insert(hash, code);
+        } else {
+            self.mem_db.contracts.insert(hash, code);
+        }
+    }
+
     /// Completely replace an account's storage without overriding account info.
     ///
     /// When forking, this causes the backend to assume a `0` value for all
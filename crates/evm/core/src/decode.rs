@@ -131,6 +131,36 @@ pub fn maybe_decode_revert(
     ))
 }
 
+/// Splits `err` into the selector and data of a custom error, unless it's one
+/// [maybe_decode_revert] decodes without the signatures database: `Error(string)`,
+/// `Panic(uint256)`, the cheatcode errors, or an error of `maybe_abi`.
+pub fn unknown_custom_error<'a>(
+    err: &'a [u8],
+    maybe_abi: Option<&JsonAbi>,
+) -> Option<([u8; SELECTOR_LEN], &'a [u8])> {
+    if err.len() < SELECTOR_LEN || err == crate::constants::MAGIC_SKIP {
+        return None
+    }
+    if alloy_sol_types::GenericContractError::abi_decode(err, false).is_ok() {
+        return None
+    }
+
+    let (selector, data) = err.split_at(SELECTOR_LEN);
+    let selector: &[u8; 4] = selector.try_into().unwrap();
+    if matches!(
+        *selector,
+        Vm::CheatcodeError::SELECTOR |
+            Vm::expectRevert_1Call::SELECTOR |
+            Vm::expectRevert_2Call::SELECTOR
+    ) {
+        return None
+    }
+    if maybe_abi.is_some_and(|abi| abi.errors().any(|e| selector == e.selector())) {
+        return None
+    }
+    Some((*selector, data))
+}
+
 fn trimmed_hex(s: &[u8]) -> String {
     let s = hex::encode(s);
     let n = 32 * 2;
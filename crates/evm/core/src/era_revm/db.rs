@@ -10,8 +10,11 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use foundry_common::zk_utils::conversion_utils::{
-    h160_to_address, h256_to_b256, h256_to_h160, revm_u256_to_h256, u256_to_revm_u256,
+use foundry_common::zk_utils::{
+    conversion_utils::{
+        h160_to_address, h256_to_b256, h256_to_h160, revm_u256_to_h256, u256_to_revm_u256,
+    },
+    ZkStateOverrides,
 };
 use revm::{
     primitives::{Bytecode, Bytes},
@@ -24,7 +27,7 @@ use zksync_types::{
     utils::decompose_full_nonce, StorageKey, StorageLog, StorageLogKind,
     ACCOUNT_CODE_STORAGE_ADDRESS, L2_ETH_TOKEN_ADDRESS, NONCE_HOLDER_ADDRESS,
     SYSTEM_CONTEXT_ADDRESS, SYSTEM_CONTEXT_BLOCK_INFO_POSITION,
-    SYSTEM_CONTEXT_CURRENT_L2_BLOCK_INFO_POSITION,
+    SYSTEM_CONTEXT_CURRENT_L2_BLOCK_INFO_POSITION, SYSTEM_CONTEXT_DIFFICULTY_POSITION,
 };
 
 use super::storage_view::StorageView;
@@ -35,6 +38,8 @@ pub struct RevmDatabaseForEra<DB> {
     pub db: Arc<Mutex<Box<DB>>>,
     pub current_block: u64,
     pub factory_deps: HashMap<H256, Vec<u8>>,
+    /// Overrides applied on top of the reads from the database.
+    pub overrides: Arc<ZkStateOverrides>,
 }
 
 impl<Db> Clone for RevmDatabaseForEra<Db> {
@@ -43,6 +48,7 @@ impl<Db> Clone for RevmDatabaseForEra<Db> {
             db: self.db.clone(),
             current_block: self.current_block,
             factory_deps: self.factory_deps.clone(),
+            overrides: self.overrides.clone(),
         }
     }
 }
@@ -72,10 +78,31 @@ where
             let (num, _) = unpack_block_info(h256_to_u256(num_and_ts));
             num
         };
-        Self { db, current_block: current_block as u64, factory_deps: HashMap::new() }
+        Self {
+            db,
+            current_block: current_block as u64,
+            factory_deps: HashMap::new(),
+            overrides: Default::default(),
+        }
+    }
+
+    /// Applies `overrides` on top of the reads from the database, without writing them to it.
+    pub fn with_overrides(mut self, overrides: Arc<ZkStateOverrides>) -> Self {
+        self.overrides = overrides;
+        self
     }
 
-    pub fn into_storage_view_with_system_contracts(mut self, chain_id: u32) -> StorageView<Self> {
+    /// Returns a [StorageView] with the system contracts deployed and the SystemContext
+    /// initialized for `chain_id`.
+    ///
+    /// zkSync has no prevrandao, `block.prevrandao` reads the difficulty of the SystemContext
+    /// instead. It keeps the value set by the previous transactions, and is otherwise seeded with
+    /// `prevrandao`, unless zero.
+    pub fn into_storage_view_with_system_contracts(
+        mut self,
+        chain_id: u32,
+        prevrandao: H256,
+    ) -> StorageView<Self> {
         let mut modified_keys = HashMap::new();
         let contracts = era_test_node::system_contracts::get_deployed_contracts(
             &era_test_node::system_contracts::Options::BuiltInWithoutSecurity,
@@ -94,6 +121,17 @@ where
                 (log.kind == StorageLogKind::Write)
                     .then_some(modified_keys.insert(log.key, log.value));
             });
+        if let Some(prevrandao) =
+            self.get_prevrandao().or_else(|| (!prevrandao.is_zero()).then_some(prevrandao))
+        {
+            modified_keys.insert(
+                StorageKey::new(
+                    AccountTreeId::new(SYSTEM_CONTEXT_ADDRESS),
+                    SYSTEM_CONTEXT_DIFFICULTY_POSITION,
+                ),
+                prevrandao,
+            );
+        }
 
         let factory_deps = contracts
             .into_iter()
@@ -128,6 +166,15 @@ where
         (num, ts)
     }
 
+    /// Returns the prevrandao, i.e. the difficulty of the SYSTEM_CONTEXT, if set in the database.
+    pub fn get_prevrandao(&self) -> Option<H256> {
+        let prevrandao = self.read_storage_internal(
+            SYSTEM_CONTEXT_ADDRESS,
+            h256_to_u256(SYSTEM_CONTEXT_DIFFICULTY_POSITION),
+        );
+        (!prevrandao.is_zero()).then_some(prevrandao)
+    }
+
     /// Returns the nonce for a given account from NonceHolder storage.
     pub fn get_nonce_for_address(&self, address: H160) -> u64 {
         // Nonce is stored in the first mapping of the Nonce contract.
@@ -141,9 +188,13 @@ where
     }
 
     fn read_storage_internal(&self, address: H160, idx: U256) -> H256 {
-        let mut db = self.db.lock().unwrap();
-        let result = db.storage(h160_to_address(address), u256_to_revm_u256(idx)).unwrap();
-        revm_u256_to_h256(result)
+        let read = || {
+            let mut db = self.db.lock().unwrap();
+            let result = db.storage(h160_to_address(address), u256_to_revm_u256(idx)).unwrap();
+            revm_u256_to_h256(result)
+        };
+        let key = StorageKey::new(AccountTreeId::new(address), u256_to_h256(idx));
+        self.overrides.read(&key, read).unwrap_or_else(read)
     }
 
     /// Tries to fetch the bytecode that belongs to a given account.
@@ -218,7 +269,10 @@ where
 {
     fn read_value(&mut self, key: &StorageKey) -> zksync_types::StorageValue {
         let mut result = self.read_storage_internal(*key.address(), h256_to_u256(*key.key()));
-        if L2_ETH_TOKEN_ADDRESS == *key.address() && result.is_zero() {
+        if L2_ETH_TOKEN_ADDRESS == *key.address() &&
+            result.is_zero() &&
+            !self.overrides.is_overridden(key)
+        {
             // TODO: here we should read the account information from the Database trait
             // and lookup how many token it holds.
             // Unfortunately the 'idx' here is a hash of the account and Database doesn't
@@ -234,6 +288,9 @@ where
     }
 
     fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
+        if let Some(bytecode) = self.overrides.factory_dep(&hash) {
+            return Some(bytecode.clone())
+        }
         let mut db = self.db.lock().unwrap();
         let result = db.code_by_hash(h256_to_b256(hash));
         let res = match result {
@@ -262,6 +319,55 @@ mod tests {
     use super::*;
     use crate::era_revm::testing::MockDatabase;
 
+    #[test]
+    fn test_storage_view_seeds_prevrandao_unless_zero() {
+        let db = || RevmDatabaseForEra {
+            current_block: 0,
+            db: Arc::new(Mutex::new(Box::new(MockDatabase::default()))),
+            factory_deps: Default::default(),
+            overrides: Default::default(),
+        };
+        let key = StorageKey::new(
+            AccountTreeId::new(SYSTEM_CONTEXT_ADDRESS),
+            SYSTEM_CONTEXT_DIFFICULTY_POSITION,
+        );
+        let default_difficulty = *get_system_context_init_logs(L2ChainId::from(260))
+            .iter()
+            .find(|log| log.key == key)
+            .map(|log| &log.value)
+            .unwrap();
+
+        let storage = db().into_storage_view_with_system_contracts(260, H256::repeat_byte(0x1));
+        assert_eq!(storage.modified_storage_keys[&key], H256::repeat_byte(0x1));
+
+        let storage = db().into_storage_view_with_system_contracts(260, H256::zero());
+        assert_eq!(storage.modified_storage_keys[&key], default_difficulty);
+    }
+
+    #[test]
+    fn test_read_value_applies_overrides() {
+        let account = H160::repeat_byte(0xa);
+        let mut overrides = ZkStateOverrides::default();
+        overrides
+            .set_storage(account, H256::zero(), H256::repeat_byte(0x1))
+            .set_balance(account, U256::zero());
+        let mut db = RevmDatabaseForEra {
+            current_block: 0,
+            db: Arc::new(Mutex::new(Box::new(MockDatabase::default()))),
+            factory_deps: Default::default(),
+            overrides: Default::default(),
+        }
+        .with_overrides(Arc::new(overrides));
+
+        let slot = StorageKey::new(AccountTreeId::new(account), H256::zero());
+        assert_eq!(db.read_value(&slot), H256::repeat_byte(0x1));
+        // an overridden zero balance isn't replaced by the default one
+        let balance = zksync_types::storage_key_for_eth_balance(&account);
+        assert_eq!(db.read_value(&balance), H256::zero());
+        let other = StorageKey::new(AccountTreeId::new(account), H256::repeat_byte(0x2));
+        assert_eq!(db.read_value(&other), H256::zero());
+    }
+
     #[test]
     fn test_fetch_account_code_returns_hash_and_code_if_present_in_modified_keys_and_bytecodes() {
         let bytecode_hash = H256::repeat_byte(0x3);
@@ -280,6 +386,7 @@ mod tests {
             current_block: 0,
             db: Arc::new(Mutex::new(Box::new(MockDatabase::default()))),
             factory_deps: Default::default(),
+            overrides: Default::default(),
         };
 
         let actual = db.fetch_account_code(account, &modified_keys, &bytecodes);
@@ -310,6 +417,7 @@ mod tests {
             current_block: 0,
             db: Arc::new(Mutex::new(Box::new(MockDatabase::default()))),
             factory_deps: Default::default(),
+            overrides: Default::default(),
         };
 
         let actual = db.fetch_account_code(account, &modified_keys, &bytecodes);
@@ -338,6 +446,7 @@ mod tests {
         let db = RevmDatabaseForEra {
             current_block: 0,
             factory_deps: Default::default(),
+            overrides: Default::default(),
             db: Arc::new(Mutex::new(Box::new(MockDatabase {
                 basic: hashmap! {
                     h160_to_address(account) => AccountInfo {
@@ -386,6 +495,7 @@ mod tests {
         let db = RevmDatabaseForEra {
             current_block: 0,
             factory_deps: Default::default(),
+            overrides: Default::default(),
             db: Arc::new(Mutex::new(Box::new(MockDatabase {
                 basic: hashmap! {
                     h160_to_address(account) => AccountInfo {
@@ -1,4 +1,5 @@
 use era_test_node::node::L2_GAS_PRICE;
+use foundry_config::ZkExecutionMode;
 use multivm::{
     interface::{L1BatchEnv, L2BlockEnv, SystemEnv},
     vm_latest::{
@@ -55,18 +56,28 @@ pub fn create_l1_batch_env<ST: ReadStorage>(
     }
 }
 
+/// Returns the protocol version the zkEVM runs the transactions with.
+pub fn protocol_version() -> zksync_types::ProtocolVersionId {
+    zksync_types::ProtocolVersionId::latest()
+}
+
 pub fn create_system_env(
     base_system_contracts: BaseSystemContracts,
     chain_id: L2ChainId,
+    execution_mode: ZkExecutionMode,
 ) -> SystemEnv {
     SystemEnv {
         zk_porter_available: false,
         // TODO: when forking, we could consider taking the protocol version id from the fork
         // itself.
-        version: zksync_types::ProtocolVersionId::latest(),
+        version: protocol_version(),
         base_system_smart_contracts: base_system_contracts,
         gas_limit: BLOCK_GAS_LIMIT,
-        execution_mode: TxExecutionMode::VerifyExecute,
+        execution_mode: match execution_mode {
+            ZkExecutionMode::VerifyExecute => TxExecutionMode::VerifyExecute,
+            ZkExecutionMode::EthCall => TxExecutionMode::EthCall,
+            ZkExecutionMode::EstimateFee => TxExecutionMode::EstimateFee,
+        },
         default_validation_computational_gas_limit: BLOCK_GAS_LIMIT,
         chain_id,
     }
@@ -7,6 +7,7 @@ use era_test_node::{
     system_contracts::{Options, SystemContracts},
     utils::bytecode_to_factory_dep,
 };
+use foundry_config::ZkExecutionMode;
 use multivm::{
     interface::{VmExecutionResultAndLogs, VmInterface},
     tracers::CallTracer,
@@ -22,17 +23,26 @@ use crate::era_revm::env::{create_l1_batch_env, create_system_env};
 use super::storage_view::StorageView;
 
 /// Executes the given L2 transaction and returns all the VM logs.
+///
+/// The bootloader is picked for `execution_mode`: only the playground one runs `eth_call`s without
+/// validating them, and the fee estimation one accounts for the overhead of the estimation.
 pub fn run_l2_tx_raw<S: ReadStorage>(
     l2_tx: L2Tx,
     storage: StoragePtr<StorageView<S>>,
     chain_id: L2ChainId,
     l1_gas_price: u64,
+    execution_mode: ZkExecutionMode,
     mut tracers: Vec<TracerPointer<StorageView<S>, multivm::vm_latest::HistoryDisabled>>,
 ) -> (VmExecutionResultAndLogs, HashMap<U256, Vec<U256>>, HashMap<StorageKey, H256>) {
     let batch_env = create_l1_batch_env(storage.clone(), l1_gas_price);
 
     let system_contracts = SystemContracts::from_options(&Options::BuiltInWithoutSecurity);
-    let system_env = create_system_env(system_contracts.baseline_contracts, chain_id);
+    let base_system_contracts = match execution_mode {
+        ZkExecutionMode::VerifyExecute => system_contracts.baseline_contracts,
+        ZkExecutionMode::EthCall => system_contracts.playground_contracts,
+        ZkExecutionMode::EstimateFee => system_contracts.fee_estimate_contracts,
+    };
+    let system_env = create_system_env(base_system_contracts, chain_id, execution_mode);
 
     let mut vm: Vm<_, HistoryDisabled> = Vm::new(batch_env.clone(), system_env, storage.clone());
 
@@ -85,3 +95,52 @@ where
         })
         .unwrap_or_default()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::era_revm::{db::RevmDatabaseForEra, testing::MockDatabase};
+    use std::sync::Mutex;
+    use zksync_basic_types::H160;
+    use zksync_types::{fee::Fee, transaction_request::PaymasterParams};
+
+    fn run_with_nonce(nonce: u32, execution_mode: ZkExecutionMode) -> VmExecutionResultAndLogs {
+        let storage =
+            RevmDatabaseForEra::new(Arc::new(Mutex::new(Box::new(MockDatabase::default()))))
+                .into_storage_view_with_system_contracts(260, H256::zero())
+                .into_rc_ptr();
+        let tx = L2Tx::new(
+            H160::repeat_byte(0x2),
+            vec![],
+            nonce.into(),
+            Fee {
+                gas_limit: U256::from(10_000_000),
+                max_fee_per_gas: U256::from(260_000_000),
+                max_priority_fee_per_gas: U256::zero(),
+                gas_per_pubdata_limit: U256::from(800),
+            },
+            H160::repeat_byte(0x1),
+            U256::zero(),
+            None,
+            PaymasterParams::default(),
+        );
+
+        let (result, _, _) = run_l2_tx_raw(
+            tx,
+            storage,
+            L2ChainId::from(260),
+            50_000_000_000,
+            execution_mode,
+            vec![],
+        );
+        result
+    }
+
+    #[test]
+    fn test_eth_call_skips_validation() {
+        // the nonce of the sender is 0, so validating the transaction fails
+        assert!(run_with_nonce(5, ZkExecutionMode::VerifyExecute).result.is_failed());
+        assert!(!run_with_nonce(5, ZkExecutionMode::EthCall).result.is_failed());
+        assert!(!run_with_nonce(0, ZkExecutionMode::VerifyExecute).result.is_failed());
+    }
+}
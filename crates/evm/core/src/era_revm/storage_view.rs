@@ -1,5 +1,11 @@
-use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fmt,
+    rc::Rc,
+};
 
+use foundry_common::zk_utils::ZkStateOverrides;
 use zksync_state::{ReadStorage, WriteStorage};
 use zksync_types::{StorageKey, StorageValue, H256};
 
@@ -23,6 +29,19 @@ pub struct StorageView<S> {
     pub read_storage_keys: HashMap<StorageKey, StorageValue>,
     // Cache for `contains_key()` checks. The cache is only valid within one L1 batch execution.
     initial_writes_cache: HashMap<StorageKey, bool>,
+    // Overrides applied on top of the reads until they are cleared, e.g. by `withStateOverrides`
+    scoped_overrides: Option<ScopedOverrides>,
+}
+
+/// Overrides applied on top of the reads of a [StorageView] for a while, unlike the ones of the
+/// underlying storage.
+#[derive(Debug)]
+struct ScopedOverrides {
+    overrides: ZkStateOverrides,
+    /// The keys written since the overrides were set, whose written values shadow the overrides.
+    written: HashSet<StorageKey>,
+    /// The values of the overridden keys in `modified_storage_keys` when the overrides were set.
+    modified: HashMap<StorageKey, Option<StorageValue>>,
 }
 
 impl<S: ReadStorage + fmt::Debug> StorageView<S> {
@@ -33,6 +52,35 @@ impl<S: ReadStorage + fmt::Debug> StorageView<S> {
             modified_storage_keys: HashMap::new(),
             read_storage_keys: HashMap::new(),
             initial_writes_cache: HashMap::new(),
+            scoped_overrides: None,
+        }
+    }
+
+    /// Applies `overrides` on top of the reads until [Self::clear_scoped_overrides] is called.
+    /// The overridden values are never written, the keys written in the meantime read their
+    /// written values instead.
+    pub fn set_scoped_overrides(&mut self, overrides: ZkStateOverrides) {
+        let modified = overrides
+            .keys()
+            .map(|key| (*key, self.modified_storage_keys.get(key).copied()))
+            .collect();
+        self.scoped_overrides =
+            Some(ScopedOverrides { overrides, written: HashSet::new(), modified });
+    }
+
+    /// Removes the overrides set with [Self::set_scoped_overrides], keeping the writes made in the
+    /// meantime unless they were `reverted`. Reverted writes of the overridden keys are rolled back
+    /// to the overridden values they read, so these keys are reset to their values from before the
+    /// overrides.
+    pub fn clear_scoped_overrides(&mut self, reverted: bool) {
+        let Some(scoped) = self.scoped_overrides.take() else { return };
+        if reverted {
+            for (key, value) in scoped.modified {
+                match value {
+                    Some(value) => self.modified_storage_keys.insert(key, value),
+                    None => self.modified_storage_keys.remove(&key),
+                };
+            }
         }
     }
 
@@ -46,11 +94,17 @@ impl<S: ReadStorage + fmt::Debug> StorageView<S> {
     fn get_value_no_log(&mut self, key: &StorageKey) -> StorageValue {
         let cached_value =
             self.modified_storage_keys.get(key).or_else(|| self.read_storage_keys.get(key));
-        cached_value.copied().unwrap_or_else(|| {
+        let value = cached_value.copied().unwrap_or_else(|| {
             let value = self.storage_handle.read_value(key);
             self.read_storage_keys.insert(*key, value);
             value
-        })
+        });
+        match &self.scoped_overrides {
+            Some(scoped) if !scoped.written.contains(key) => {
+                scoped.overrides.read(key, || value).unwrap_or(value)
+            }
+            _ => value,
+        }
     }
     /// Make a Rc RefCell ptr to the storage
     pub fn into_rc_ptr(self) -> Rc<RefCell<Self>> {
@@ -86,6 +140,11 @@ impl<S: ReadStorage + fmt::Debug> ReadStorage for StorageView<S> {
     }
 
     fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
+        if let Some(bytecode) =
+            self.scoped_overrides.as_ref().and_then(|scoped| scoped.overrides.factory_dep(&hash))
+        {
+            return Some(bytecode.clone())
+        }
         self.storage_handle.load_factory_dep(hash)
     }
 
@@ -107,6 +166,9 @@ impl<S: ReadStorage + fmt::Debug> WriteStorage for StorageView<S> {
             key.key()
         );
         self.modified_storage_keys.insert(key, value);
+        if let Some(scoped) = &mut self.scoped_overrides {
+            scoped.written.insert(key);
+        }
 
         original
     }
@@ -159,4 +221,38 @@ mod test {
         assert_eq!(storage_view.read_value(&new_key), new_value);
         assert!(storage_view.is_write_initial(&new_key));
     }
+
+    #[test]
+    fn test_scoped_overrides() {
+        let account = Address::from([0xfe; 20]);
+        let key = StorageKey::new(AccountTreeId::new(account), H256::from_low_u64_be(61));
+        let written_key = StorageKey::new(AccountTreeId::new(account), H256::from_low_u64_be(62));
+        let value = H256::from_low_u64_be(73);
+        let overridden = H256::from_low_u64_be(74);
+
+        let raw_storage = InMemoryStorage::default();
+        let mut storage_view = StorageView::new(&raw_storage);
+        storage_view.set_value(key, value);
+
+        let mut overrides = ZkStateOverrides::default();
+        overrides.set_storage(account, *key.key(), overridden).set_storage(
+            account,
+            *written_key.key(),
+            overridden,
+        );
+        storage_view.set_scoped_overrides(overrides.clone());
+        assert_eq!(storage_view.read_value(&key), overridden);
+        // writing the overridden value keeps it
+        assert_eq!(storage_view.set_value(written_key, overridden), overridden);
+        storage_view.clear_scoped_overrides(false);
+        assert_eq!(storage_view.read_value(&key), value);
+        assert_eq!(storage_view.read_value(&written_key), overridden);
+
+        // reverted writes are dropped
+        storage_view.set_scoped_overrides(overrides);
+        storage_view.set_value(key, H256::from_low_u64_be(75));
+        storage_view.set_value(key, overridden);
+        storage_view.clear_scoped_overrides(true);
+        assert_eq!(storage_view.read_value(&key), value);
+    }
 }
@@ -24,7 +24,7 @@ impl revm::Database for MockDatabase {
     }
 
     fn code_by_hash(&mut self, _code_hash: B256) -> Result<Bytecode, Self::Error> {
-        todo!()
+        Ok(Bytecode::new())
     }
 
     fn storage(&mut self, _address: Address, _index: U256) -> Result<U256, Self::Error> {
@@ -18,8 +18,9 @@ use std::{
 use zksync_basic_types::{web3::signing::keccak256, L2ChainId, H160, H256, U256};
 use zksync_state::{ReadStorage, WriteStorage};
 use zksync_types::{
-    fee::Fee, l2::L2Tx, transaction_request::PaymasterParams, PackedEthSignature, StorageKey,
-    StorageValue, ACCOUNT_CODE_STORAGE_ADDRESS, KNOWN_CODES_STORAGE_ADDRESS,
+    fee::Fee, get_nonce_key, l2::L2Tx, transaction_request::PaymasterParams,
+    utils::decompose_full_nonce, PackedEthSignature, StorageKey, StorageValue,
+    ACCOUNT_CODE_STORAGE_ADDRESS, KNOWN_CODES_STORAGE_ADDRESS,
 };
 use zksync_utils::{h256_to_account_address, h256_to_u256, u256_to_h256};
 
@@ -31,6 +32,7 @@ use foundry_common::{
     },
     AsTracerPointer, StorageModificationRecorder, StorageModifications,
 };
+use foundry_config::ZkExecutionMode;
 
 use super::db::RevmDatabaseForEra;
 use crate::{
@@ -127,7 +129,8 @@ where
     INSP: AsTracerPointer<StorageView<RevmDatabaseForEra<DB>>, HistoryDisabled>
         + StorageModificationRecorder,
 {
-    let mut era_db = RevmDatabaseForEra::new(Arc::new(Mutex::new(Box::new(db))));
+    let mut era_db = RevmDatabaseForEra::new(Arc::new(Mutex::new(Box::new(db))))
+        .with_overrides(inspector.state_overrides());
     let (num, ts) = era_db.get_l2_block_number_and_timestamp();
     let l1_num = num;
     let nonce = era_db.get_nonce_for_address(H160::from_slice(env.tx.caller.as_slice()));
@@ -151,7 +154,7 @@ where
     };
 
     let mut l2_tx =
-        tx_env_to_era_tx(env.tx.clone(), nonce, &inspector.get_storage_modifications().bytecodes);
+        tx_env_to_era_tx(env.tx.clone(), nonce, inspector.get_storage_modifications().bytecodes());
 
     if l2_tx.common_data.signature.is_empty() {
         // FIXME: This is a hack to make sure that the signature is not empty.
@@ -159,7 +162,8 @@ where
         l2_tx.common_data.signature = PackedEthSignature::default().serialize_packed().into();
     }
     let tracer = inspector.as_tracer_pointer();
-    let storage = era_db.clone().into_storage_view_with_system_contracts(chain_id_u32);
+    let prevrandao = env.block.prevrandao.map(|prevrandao| H256(prevrandao.0)).unwrap_or_default();
+    let storage = era_db.clone().into_storage_view_with_system_contracts(chain_id_u32, prevrandao);
 
     let storage_ptr = storage.into_rc_ptr();
     let (tx_result, bytecodes, modified_storage) = run_l2_tx_raw(
@@ -167,14 +171,15 @@ where
         storage_ptr.clone(),
         L2ChainId::from(chain_id_u32),
         u64::max(env.block.basefee.to::<u64>(), 1000),
+        inspector.execution_mode(),
         vec![tracer],
     );
 
     // Record storage modifications in the inspector.
     // We record known_codes only if they aren't already in the bytecodes changeset.
-    inspector.record_storage_modifications(StorageModifications {
-        keys: modified_storage.clone(),
-        bytecodes: bytecodes
+    inspector.record_storage_modifications(StorageModifications::new(
+        modified_storage.clone(),
+        bytecodes
             .clone()
             .into_iter()
             .map(|(key, value)| {
@@ -186,7 +191,7 @@ where
                 (key, value)
             })
             .collect(),
-        known_codes: storage_ptr
+        storage_ptr
             .borrow()
             .read_storage_keys
             .iter()
@@ -202,7 +207,7 @@ where
                 }
             })
             .collect(),
-    });
+    ));
 
     let execution_result = match tx_result.result {
         multivm::interface::ExecutionResult::Success { output, .. } => {
@@ -268,6 +273,59 @@ where
     })
 }
 
+/// Executes a read-only call on top of `storage` with the `eth_call` bootloader, which skips the
+/// account validation and the fee payment, and returns its output, or its revert data if it
+/// fails. The call is sent with the current nonce of the caller and the gas limit of `tx_env`.
+///
+/// `storage` is left untouched, the call runs on a copy of its modifications. `factory_deps` are
+/// the bytecodes not in the database yet, such as the ones of the contracts deployed in the
+/// current transaction.
+pub fn run_era_call<DB>(
+    storage: &StorageView<RevmDatabaseForEra<DB>>,
+    factory_deps: impl IntoIterator<Item = (H256, Vec<u8>)>,
+    tx_env: TxEnv,
+    chain_id: L2ChainId,
+    l1_gas_price: u64,
+) -> Result<Vec<u8>, Vec<u8>>
+where
+    DB: DatabaseExt + Send,
+    <DB as revm::Database>::Error: Debug,
+{
+    let mut era_db = storage.storage_handle.clone();
+    era_db.factory_deps.extend(factory_deps);
+    let mut call_storage = StorageView::new(era_db);
+    call_storage.modified_storage_keys = storage.modified_storage_keys.clone();
+
+    let caller = H160::from_slice(tx_env.caller.as_slice());
+    let full_nonce = call_storage.read_value(&get_nonce_key(&caller));
+    let (nonce, _) = decompose_full_nonce(h256_to_u256(full_nonce));
+    let mut l2_tx = tx_env_to_era_tx(tx_env, nonce.as_u64(), &HashMap::new());
+    l2_tx.common_data.signature = PackedEthSignature::default().serialize_packed().into();
+
+    let (tx_result, _, _) = run_l2_tx_raw(
+        l2_tx,
+        call_storage.into_rc_ptr(),
+        chain_id,
+        l1_gas_price,
+        ZkExecutionMode::EthCall,
+        vec![],
+    );
+    match tx_result.result {
+        multivm::interface::ExecutionResult::Success { output, .. } => {
+            Ok(decode_l2_tx_result(output))
+        }
+        multivm::interface::ExecutionResult::Revert { output } => Err(match output {
+            multivm::interface::VmRevertReason::General { data, .. } => data,
+            multivm::interface::VmRevertReason::Unknown { data, .. } => data,
+            _ => Vec::new(),
+        }),
+        multivm::interface::ExecutionResult::Halt { reason } => {
+            tracing::error!("call execution halted: {}", reason);
+            Err(Vec::new())
+        }
+    }
+}
+
 fn decode_l2_tx_result(output: Vec<u8>) -> Vec<u8> {
     ethabi::decode(&[ParamType::Bytes], &output)
         .ok()
@@ -1,7 +1,7 @@
 //! Smart caching and deduplication of requests when using a forking provider
 use crate::{
     backend::{DatabaseError, DatabaseResult},
-    fork::{cache::FlushJsonBlockCacheDB, BlockchainDb},
+    fork::{cache::FlushJsonBlockCacheDB, BlockchainDb, MemDb},
 };
 use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
 use ethers_core::{
@@ -32,7 +32,7 @@ use std::{
     },
 };
 
-use super::zksync_provider::ZkSyncMiddleware;
+use super::zksync_provider::{VerifiedBatch, ZkSyncMiddleware};
 
 // Various future/request type aliases
 
@@ -113,6 +113,8 @@ pub struct BackendHandler<M: ZkSyncMiddleware> {
     /// The block to fetch data from.
     // This is an `Option` so that we can have less code churn in the functions below
     block_id: Option<BlockId>,
+    /// The L1 batch storage is fetched from and verified against, if storage is verified.
+    verified_batch: Option<VerifiedBatch>,
 }
 
 impl<M> BackendHandler<M>
@@ -135,9 +137,20 @@ where
             queued_requests: Default::default(),
             incoming: rx,
             block_id,
+            verified_batch: None,
         }
     }
 
+    /// Fetches storage with `zks_getProof` from the given L1 batch, if any, and verifies it
+    /// against the root hash of the batch before caching it.
+    ///
+    /// Only the storage is proven, the code, balance and nonce of accounts are still fetched
+    /// unverified from the pinned block, which must be the last block of the batch.
+    pub fn with_verified_storage(mut self, batch: Option<VerifiedBatch>) -> Self {
+        self.verified_batch = batch;
+        self
+    }
+
     /// handle the request in queue in the future.
     ///
     /// We always check:
@@ -184,7 +197,12 @@ where
                 self.block_id = Some(block_id);
             }
             BackendRequest::ByteCodeHash(code_hash, sender) => {
-                self.request_bytecode_by_hash(code_hash, sender);
+                let bytecode = self.db.bytecodes().read().get(&code_hash).cloned();
+                if let Some(bytecode) = bytecode {
+                    let _ = sender.send(Ok(bytecode));
+                } else {
+                    self.request_bytecode_by_hash(code_hash, sender);
+                }
             }
         }
     }
@@ -200,6 +218,14 @@ where
                 entry.insert(vec![listener]);
                 let provider = self.provider.clone();
                 let block_id = self.block_id;
+                if let Some(batch) = self.verified_batch {
+                    let fut = Box::pin(async move {
+                        let storage = provider.get_verified_storage_at(address, idx, batch).await;
+                        (storage, address, idx)
+                    });
+                    self.pending_requests.push(ProviderRequest::Storage(fut));
+                    return
+                }
                 let fut = Box::pin(async move {
                     // serialize & deserialize back to U256
                     let idx_req = B256::from(idx);
@@ -490,7 +516,10 @@ where
                     ProviderRequest::ByteCodeHash(fut) => {
                         if let Poll::Ready((sender, bytecode, code_hash)) = fut.poll_unpin(cx) {
                             let msg = match bytecode {
-                                Ok(Some(bytecode)) => Ok(bytecode),
+                                Ok(Some(bytecode)) => {
+                                    pin.db.bytecodes().write().insert(code_hash, bytecode.clone());
+                                    Ok(bytecode)
+                                }
                                 Ok(None) => Err(DatabaseError::MissingCode(code_hash)),
                                 Err(err) => {
                                     let err = Arc::new(eyre::Error::new(err));
@@ -680,6 +709,11 @@ impl SharedBackend {
         })
     }
 
+    /// Returns the database holding the state fetched by the backend
+    pub(crate) fn fetched_db(&self) -> &MemDb {
+        self.cache.0.db()
+    }
+
     /// Flushes the DB to disk if caching is enabled
     pub(crate) fn flush_cache(&self) {
         self.cache.0.flush();
@@ -753,8 +787,11 @@ mod tests {
         fork::{BlockchainDbMeta, CreateFork, JsonBlockCacheDB},
         opts::EvmOpts,
     };
-    use ethers_providers::Middleware;
-    use foundry_common::get_http_provider;
+    use ethers_providers::{Middleware, Provider};
+    use foundry_common::{
+        get_http_provider,
+        zk_utils::proof::{ZkAccountProof, ZkStorageProof},
+    };
     use foundry_config::{Config, NamedChain};
     use std::{collections::BTreeSet, path::PathBuf, sync::Arc};
     const ENDPOINT: &str = "https://mainnet.infura.io/v3/40bee2d557ed4b52908c3e62345a3d8b";
@@ -802,6 +839,53 @@ mod tests {
         assert_eq!(slots.len() as u64, max_slots);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn verifies_storage_proofs() {
+        let address = Address::with_last_byte(0x42);
+        let proof = ZkStorageProof {
+            key: B256::with_last_byte(7),
+            value: B256::with_last_byte(1),
+            index: 1,
+            proof: vec![],
+        };
+        let batch = VerifiedBatch {
+            number: 1,
+            root_hash: proof.root_hash(address),
+            last_block: 1,
+            execute_tx_hash: None,
+        };
+
+        let (provider, mock) = Provider::mocked();
+        let db = BlockchainDb::new(
+            BlockchainDbMeta {
+                cfg_env: Default::default(),
+                block_env: Default::default(),
+                hosts: Default::default(),
+            },
+            None,
+        );
+        let (backend, handler) = SharedBackend::new(Arc::new(provider), db.clone(), None);
+        tokio::spawn(handler.with_verified_storage(Some(batch)));
+
+        mock.push(ZkAccountProof { address, storage_proof: vec![proof] }).unwrap();
+        assert_eq!(backend.storage_ref(address, U256::from(7)).unwrap(), U256::from(1));
+        assert_eq!(
+            db.storage().read().get(&address).unwrap().get(&U256::from(7)),
+            Some(&U256::from(1))
+        );
+
+        // a value the root hash doesn't prove is rejected, and not cached
+        let forged = ZkStorageProof {
+            key: B256::with_last_byte(8),
+            value: B256::with_last_byte(2),
+            index: 2,
+            proof: vec![],
+        };
+        mock.push(ZkAccountProof { address, storage_proof: vec![forged] }).unwrap();
+        assert!(backend.storage_ref(address, U256::from(8)).is_err());
+        assert!(!db.storage().read().get(&address).unwrap().contains_key(&U256::from(8)));
+    }
+
     #[test]
     fn can_read_cache() {
         let cache_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test-data/storage.json");
@@ -3,7 +3,7 @@ use crate::backend::StateSnapshot;
 use alloy_primitives::{Address, B256, U256};
 use parking_lot::RwLock;
 use revm::{
-    primitives::{Account, AccountInfo, AccountStatus, HashMap as Map, KECCAK_EMPTY},
+    primitives::{Account, AccountInfo, AccountStatus, Bytecode, HashMap as Map, KECCAK_EMPTY},
     DatabaseCommit,
 };
 use serde::{ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
@@ -99,6 +99,11 @@ impl BlockchainDb {
         &self.db.block_hashes
     }
 
+    /// Returns the map that holds the bytecodes fetched by hash
+    pub fn bytecodes(&self) -> &RwLock<Map<B256, Bytecode>> {
+        &self.db.bytecodes
+    }
+
     /// Returns the [revm::Env] related metadata
     pub fn meta(&self) -> &Arc<RwLock<BlockchainDbMeta>> {
         &self.meta
@@ -258,6 +263,8 @@ pub struct MemDb {
     pub storage: RwLock<Map<Address, StorageInfo>>,
     /// All retrieved block hashes
     pub block_hashes: RwLock<Map<U256, B256>>,
+    /// All bytecodes retrieved by hash
+    pub bytecodes: RwLock<Map<B256, Bytecode>>,
 }
 
 impl MemDb {
@@ -266,6 +273,7 @@ impl MemDb {
         self.accounts.write().clear();
         self.storage.write().clear();
         self.block_hashes.write().clear();
+        self.bytecodes.write().clear();
     }
 
     // Inserts the account, replacing it if it exists already
@@ -321,6 +329,7 @@ impl Clone for MemDb {
             storage: RwLock::new(self.storage.read().clone()),
             accounts: RwLock::new(self.accounts.read().clone()),
             block_hashes: RwLock::new(self.block_hashes.read().clone()),
+            bytecodes: RwLock::new(self.bytecodes.read().clone()),
         }
     }
 }
@@ -457,6 +466,7 @@ impl<'de> Deserialize<'de> for JsonBlockCacheData {
                 accounts: RwLock::new(accounts),
                 storage: RwLock::new(storage),
                 block_hashes: RwLock::new(block_hashes),
+                bytecodes: Default::default(),
             }),
         })
     }
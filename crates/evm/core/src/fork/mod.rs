@@ -16,6 +16,12 @@ pub mod zksync_provider;
 mod multi;
 pub use multi::{ForkId, MultiFork, MultiForkHandler};
 
+mod prefetch;
+pub use prefetch::PrefetchHints;
+
+mod replay;
+pub use replay::FetchedState;
+
 /// Represents a _fork_ of a remote chain whose data is available only via the `url` endpoint.
 #[derive(Debug, Clone)]
 pub struct CreateFork {
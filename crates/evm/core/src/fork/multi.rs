@@ -3,11 +3,16 @@
 //! The design is similar to the single `SharedBackend`, `BackendHandler` but supports multiple
 //! concurrently active pairs at once.
 
-use crate::fork::{BackendHandler, BlockchainDb, BlockchainDbMeta, CreateFork, SharedBackend};
-use alloy_primitives::Bytes;
-use ethers_core::types::{BlockId, BlockNumber};
-use ethers_providers::{JsonRpcClient, Provider};
-use foundry_common::{runtime_client::RuntimeClient, types::ToEthers, ProviderBuilder};
+use crate::fork::{
+    BackendHandler, BlockchainDb, BlockchainDbMeta, CreateFork, PrefetchHints, SharedBackend,
+};
+use alloy_primitives::{Address, Bytes, B256, U256};
+use ethers_core::types::{BlockId, BlockNumber, U64};
+use ethers_providers::{JsonRpcClient, Provider, ProviderError};
+use foundry_common::{
+    runtime_client::RuntimeClient, types::ToEthers, zk_utils::proof::ZkAccountProof,
+    ProviderBuilder,
+};
 use foundry_config::Config;
 use futures::{
     channel::mpsc::{channel, Receiver, Sender},
@@ -27,7 +32,10 @@ use std::{
     time::Duration,
 };
 
-use super::zksync_provider::ZkSyncMiddleware;
+use super::zksync_provider::{
+    known_main_contract, verify_batch_on_l1, BlockDetails, L1BatchDetails, VerifiedBatch,
+    ZkSyncMiddleware,
+};
 
 /// The identifier for a specific fork, this could be the name of the network a custom descriptive
 /// name.
@@ -255,6 +263,21 @@ impl MultiForkHandler {
         }
     }
 
+    /// Records the state fetched by the forks with prefetching enabled, so that the next run
+    /// against the same chain prefetches it.
+    fn record_prefetch_hints(&self) {
+        let mut chains: HashMap<u64, PrefetchHints> = HashMap::new();
+        for fork in self.forks.values().filter(|fork| fork.opts.evm_opts.fork_prefetch) {
+            chains
+                .entry(fork.opts.env.cfg.chain_id)
+                .or_default()
+                .extend(PrefetchHints::from_db(fork.backend.fetched_db()));
+        }
+        for (chain, hints) in chains {
+            hints.save(chain);
+        }
+    }
+
     fn on_request(&mut self, req: Request) {
         match req {
             Request::CreateFork(fork, sender) => self.create_fork(*fork, sender),
@@ -277,6 +300,7 @@ impl MultiForkHandler {
             }
             Request::ShutDown(sender) => {
                 trace!(target: "fork::multi", "received shutdown signal");
+                self.record_prefetch_hints();
                 // we're emptying all fork backends, this way we ensure all caches get flushed
                 self.forks.clear();
                 self.handlers.clear();
@@ -469,16 +493,62 @@ async fn create_fork(mut fork: CreateFork) -> eyre::Result<(CreatedFork, Handler
         .map(|num| num.as_u64())
         .unwrap_or_else(|| meta.block_env.number.to_ethers().as_u64());
 
-    // determine the cache path if caching is enabled
-    let cache_path = if fork.enable_caching {
+    // determine the cache path if caching is enabled, verified storage isn't mixed with the cache
+    let cache_path = if fork.enable_caching && !fork.evm_opts.fork_verify_storage {
         Config::foundry_block_cache_dir(meta.cfg_env.chain_id, number)
     } else {
         None
     };
 
+    let chain_id = meta.cfg_env.chain_id;
     let db = BlockchainDb::new(meta, cache_path);
+    let verified_batch = if fork.evm_opts.fork_verify_storage {
+        let batch = provider.get_verified_batch(Some(number)).await.map_err(|err| {
+            eyre::eyre!(
+                "Could not find the L1 batch to verify the storage of the fork against: {err}"
+            )
+        })?;
+        // only the storage is proven, and it can only be proven at the end of a batch, so the code,
+        // balance and nonce are only consistent with it if they're read at that same block
+        if batch.last_block != number {
+            eyre::bail!(
+                "Cannot verify the storage of a fork at block {number}, storage can only be \
+                 verified at the end of L1 batch {}, fork at block {} instead",
+                batch.number,
+                batch.last_block
+            )
+        }
+        // the root hash comes from the forked node, so it's only trusted once checked on L1
+        let l1_url = fork.evm_opts.fork_verify_l1_url.as_deref().ok_or_else(|| {
+            eyre::eyre!("Verifying the storage of a fork requires `fork_verify_l1_url`")
+        })?;
+        let main_contract = fork
+            .evm_opts
+            .fork_verify_main_contract
+            .or_else(|| known_main_contract(chain_id))
+            .ok_or_else(|| {
+                eyre::eyre!(
+                    "The main zkSync contract of chain {chain_id} is unknown, set \
+                     `fork_verify_main_contract` to verify the storage of the fork"
+                )
+            })?;
+        verify_batch_on_l1(l1_url, main_contract, &batch).await.map_err(|err| {
+            eyre::eyre!("Could not check the root hash of L1 batch {} on L1: {err}", batch.number)
+        })?;
+        trace!(target: "fork::multi", ?batch, %main_contract, "verifying fork storage");
+        Some(batch)
+    } else {
+        None
+    };
     let (backend, handler) =
         SharedBackend::new(provider, db, Some(BlockId::Number(BlockNumber::Number(number.into()))));
+    let handler = handler.with_verified_storage(verified_batch);
+
+    let mut hints = PrefetchHints::for_accounts(fork.evm_opts.fork_prefetch_accounts.clone());
+    if fork.evm_opts.fork_prefetch {
+        hints.extend(PrefetchHints::load(chain_id).unwrap_or_default());
+    }
+    hints.spawn_prefetch(backend.clone());
     let fork = CreatedFork::new(fork, backend);
     Ok((fork, handler))
 }
@@ -492,6 +562,61 @@ impl<P: JsonRpcClient> ZkSyncMiddleware for Provider<P> {
         let bytecode: Option<Bytes> = self.request("zks_getBytecodeByHash", vec![hash]).await?;
         Ok(bytecode.map(revm::primitives::Bytecode::new_raw))
     }
+
+    async fn get_verified_batch(&self, block: Option<u64>) -> Result<VerifiedBatch, Self::Error> {
+        let number = match block {
+            Some(block) => {
+                let details: Option<BlockDetails> =
+                    self.request("zks_getBlockDetails", [block]).await?;
+                details
+                    .ok_or_else(|| ProviderError::CustomError(format!("block {block} not found")))?
+                    .l1_batch_number
+            }
+            None => self.request::<_, U64>("zks_L1BatchNumber", ()).await?.as_u32(),
+        };
+
+        let details: Option<L1BatchDetails> =
+            self.request("zks_getL1BatchDetails", [number]).await?;
+        let execute_tx_hash = details.as_ref().and_then(|details| details.execute_tx_hash);
+        let root_hash = details.and_then(|details| details.root_hash).ok_or_else(|| {
+            ProviderError::CustomError(format!(
+                "the root hash of L1 batch {number} is not computed yet"
+            ))
+        })?;
+
+        let range: Option<(U64, U64)> = self.request("zks_getL1BatchBlockRange", [number]).await?;
+        let (_, last_block) = range.ok_or_else(|| {
+            ProviderError::CustomError(format!("the blocks of L1 batch {number} are unknown"))
+        })?;
+        Ok(VerifiedBatch { number, root_hash, last_block: last_block.as_u64(), execute_tx_hash })
+    }
+
+    async fn get_verified_storage_at(
+        &self,
+        address: Address,
+        idx: U256,
+        batch: VerifiedBatch,
+    ) -> Result<U256, Self::Error> {
+        let key = B256::from(idx);
+        let proof: Option<ZkAccountProof> =
+            self.request("zks_getProof", (address, [key], batch.number)).await?;
+        let proof = proof
+            .and_then(|proof| proof.storage_proof.into_iter().find(|proof| proof.key == key))
+            .ok_or_else(|| {
+                ProviderError::CustomError(format!(
+                    "no proof of slot {key} of {address} in L1 batch {}",
+                    batch.number
+                ))
+            })?;
+
+        if !proof.verify(address, batch.root_hash) {
+            return Err(ProviderError::CustomError(format!(
+                "invalid proof of slot {key} of {address} against the root hash {} of L1 batch {}",
+                batch.root_hash, batch.number
+            )))
+        }
+        Ok(U256::from_be_bytes(proof.value.0))
+    }
 }
 
 #[async_trait::async_trait]
@@ -502,4 +627,17 @@ impl<P: JsonRpcClient> ZkSyncMiddleware for Arc<Provider<P>> {
     ) -> Result<Option<revm::primitives::Bytecode>, Self::Error> {
         self.as_ref().get_bytecode_by_hash(hash).await
     }
+
+    async fn get_verified_batch(&self, block: Option<u64>) -> Result<VerifiedBatch, Self::Error> {
+        self.as_ref().get_verified_batch(block).await
+    }
+
+    async fn get_verified_storage_at(
+        &self,
+        address: Address,
+        idx: U256,
+        batch: VerifiedBatch,
+    ) -> Result<U256, Self::Error> {
+        self.as_ref().get_verified_storage_at(address, idx, batch).await
+    }
 }
@@ -0,0 +1,160 @@
+//! Concurrent prefetching of the state a fork is expected to touch.
+//!
+//! A fork fetches every account and storage slot one request at a time, when the execution first
+//! reaches it. If the touched state is known in advance, from a list of hinted accounts or from the
+//! state fetched by the previous run against the same chain, it is requested concurrently as soon
+//! as the fork is created instead, so that it's already cached when the execution needs it.
+
+use super::{MemDb, SharedBackend};
+use alloy_primitives::{Address, U256};
+use foundry_common::zk_utils::conversion_utils::{address_to_h160, h256_to_revm_u256};
+use foundry_config::{Chain, Config};
+use revm::DatabaseRef;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread,
+};
+use zksync_types::{get_code_key, get_nonce_key};
+
+/// Number of threads requesting the prefetched state from a fork backend.
+const PREFETCH_THREADS: usize = 16;
+
+/// The accounts and storage slots to prefetch when creating a fork.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrefetchHints {
+    /// Accounts whose balance, nonce and code are prefetched.
+    pub accounts: BTreeSet<Address>,
+    /// Storage slots prefetched per account.
+    pub storage: BTreeMap<Address, BTreeSet<U256>>,
+}
+
+impl PrefetchHints {
+    /// Returns the hints for the given accounts.
+    ///
+    /// Since the code hash and nonce of an account are kept in the storage of the zkSync system
+    /// contracts, the slots holding them are prefetched as well.
+    pub fn for_accounts(accounts: impl IntoIterator<Item = Address>) -> Self {
+        let mut hints = Self::default();
+        for account in accounts {
+            hints.accounts.insert(account);
+            for key in
+                [get_code_key(&address_to_h160(account)), get_nonce_key(&address_to_h160(account))]
+            {
+                hints
+                    .storage
+                    .entry(Address::from_slice(key.address().as_bytes()))
+                    .or_default()
+                    .insert(h256_to_revm_u256(*key.key()));
+            }
+        }
+        hints
+    }
+
+    /// Collects the accounts and storage slots fetched into the given database.
+    pub fn from_db(db: &MemDb) -> Self {
+        Self {
+            accounts: db.accounts.read().keys().copied().collect(),
+            storage: db
+                .storage
+                .read()
+                .iter()
+                .map(|(address, slots)| (*address, slots.keys().copied().collect()))
+                .collect(),
+        }
+    }
+
+    /// Returns the path of the hints recorded by the previous run against the chain,
+    /// `~/.foundry/cache/rpc/<chain>/prefetch.json`.
+    pub fn cache_file(chain: impl Into<Chain>) -> Option<PathBuf> {
+        Some(Config::foundry_chain_cache_dir(chain)?.join("prefetch.json"))
+    }
+
+    /// Loads the hints recorded by the previous run against the chain, if any.
+    pub fn load(chain: impl Into<Chain>) -> Option<Self> {
+        let path = Self::cache_file(chain)?;
+        foundry_common::fs::read_json_file(&path).ok()
+    }
+
+    /// Records the hints for the next run against the chain.
+    pub fn save(&self, chain: impl Into<Chain>) {
+        let Some(path) = Self::cache_file(chain) else { return };
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Err(err) = foundry_common::fs::write_json_file(&path, self) {
+            warn!(target: "fork::prefetch", %err, "failed to record prefetch hints");
+        }
+    }
+
+    /// Adds the accounts and storage slots of `other`.
+    pub fn extend(&mut self, other: Self) {
+        self.accounts.extend(other.accounts);
+        for (address, slots) in other.storage {
+            self.storage.entry(address).or_default().extend(slots);
+        }
+    }
+
+    /// Returns `true` if there is nothing to prefetch.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty() && self.storage.values().all(BTreeSet::is_empty)
+    }
+
+    /// Requests the hinted state from the backend on a pool of threads.
+    ///
+    /// Failed requests are ignored, the state is requested again once the execution reaches it.
+    pub fn spawn_prefetch(self, backend: SharedBackend) {
+        let requests = self
+            .accounts
+            .into_iter()
+            .map(|account| (account, None))
+            .chain(self.storage.into_iter().flat_map(|(address, slots)| {
+                slots.into_iter().map(move |slot| (address, Some(slot)))
+            }))
+            .collect::<Vec<_>>();
+        if requests.is_empty() {
+            return
+        }
+        trace!(target: "fork::prefetch", requests = requests.len(), "prefetching fork state");
+
+        let threads = PREFETCH_THREADS.min(requests.len());
+        let requests = Arc::new(Mutex::new(requests.into_iter()));
+        for _ in 0..threads {
+            let (backend, requests) = (backend.clone(), requests.clone());
+            thread::spawn(move || loop {
+                let Some(request) = requests.lock().unwrap().next() else { break };
+                let _ = match request {
+                    (address, None) => backend.basic_ref(address).map(drop),
+                    (address, Some(slot)) => backend.storage_ref(address, slot).map(drop),
+                };
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::AccountInfo;
+
+    #[test]
+    fn collects_fetched_state() {
+        let db = MemDb::default();
+        let account = Address::with_last_byte(1);
+        db.do_insert_account(account, AccountInfo::default());
+        db.storage.write().entry(account).or_default().insert(U256::from(7), U256::from(1));
+
+        let hints = PrefetchHints::from_db(&db);
+        assert_eq!(hints.accounts, BTreeSet::from([account]));
+        assert_eq!(hints.storage, BTreeMap::from([(account, BTreeSet::from([U256::from(7)]))]));
+
+        let mut merged = PrefetchHints::for_accounts([Address::with_last_byte(2)]);
+        assert_eq!(merged.accounts.len(), 1);
+        assert_eq!(merged.storage.values().map(BTreeSet::len).sum::<usize>(), 2);
+        merged.extend(hints);
+        assert_eq!(merged.accounts.len(), 2);
+        assert!(!merged.is_empty());
+    }
+}
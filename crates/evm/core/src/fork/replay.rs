@@ -0,0 +1,108 @@
+//! Recording of the state fetched by a fork, to re-execute against it offline.
+//!
+//! A fork only fetches the accounts, storage slots and bytecodes the execution touches, so the
+//! state it fetched while running a test is enough to run that same test again without its RPC,
+//! as long as the execution doesn't diverge.
+
+use super::MemDb;
+use crate::backend::{Backend, DatabaseResult};
+use alloy_primitives::{Address, Bytes, B256, U256};
+use revm::primitives::{AccountInfo, BlockEnv, Bytecode, Env};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The state a fork fetched from its RPC, along with the block it is pinned to.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FetchedState {
+    /// The chain id of the fork.
+    pub chain_id: u64,
+    /// The block the fork is pinned to.
+    pub block: BlockEnv,
+    /// Fetched accounts.
+    pub accounts: BTreeMap<Address, AccountInfo>,
+    /// Fetched storage slots per account.
+    pub storage: BTreeMap<Address, BTreeMap<U256, U256>>,
+    /// Bytecodes fetched by hash, e.g. zkEVM factory dependencies.
+    pub bytecodes: BTreeMap<B256, Bytes>,
+}
+
+impl FetchedState {
+    /// Collects the state fetched into the given database.
+    pub fn from_db(db: &MemDb, env: &Env) -> Self {
+        Self {
+            chain_id: env.cfg.chain_id,
+            block: env.block.clone(),
+            accounts: db.accounts.read().iter().map(|(k, v)| (*k, v.clone())).collect(),
+            storage: db
+                .storage
+                .read()
+                .iter()
+                .map(|(address, slots)| (*address, slots.iter().map(|(k, v)| (*k, *v)).collect()))
+                .collect(),
+            bytecodes: db
+                .bytecodes
+                .read()
+                .iter()
+                .map(|(hash, code)| (*hash, code.original_bytes()))
+                .collect(),
+        }
+    }
+
+    /// Collects the state fetched by the active fork of `backend`, if any.
+    pub fn from_active_fork(backend: &Backend, env: &Env) -> Option<Self> {
+        backend.active_fork_db().map(|db| Self::from_db(db.db.fetched_db(), env))
+    }
+
+    /// Sets the chain id and block of the fork in `env`.
+    pub fn apply_env(&self, env: &mut Env) {
+        env.cfg.chain_id = self.chain_id;
+        env.block = self.block.clone();
+    }
+
+    /// Writes the fetched state into `backend`, in place of the fork.
+    pub fn apply(&self, backend: &mut Backend) -> DatabaseResult<()> {
+        for (address, info) in &self.accounts {
+            backend.insert_account_info(*address, info.clone());
+        }
+        for (address, slots) in &self.storage {
+            backend
+                .replace_account_storage(*address, slots.iter().map(|(k, v)| (*k, *v)).collect())?;
+        }
+        for (hash, code) in &self.bytecodes {
+            backend.insert_bytecode(*hash, Bytecode::new_raw(code.clone()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_fetched_state() {
+        let db = MemDb::default();
+        let address = Address::with_last_byte(1);
+        db.accounts.write().insert(address, AccountInfo { nonce: 1, ..Default::default() });
+        db.storage.write().entry(address).or_default().insert(U256::ZERO, U256::from(2));
+        db.bytecodes
+            .write()
+            .insert(B256::with_last_byte(3), Bytecode::new_raw(Bytes::from(vec![4])));
+
+        let mut env = Env::default();
+        env.cfg.chain_id = 324;
+        env.block.number = U256::from(5);
+
+        let state = FetchedState::from_db(&db, &env);
+        assert_eq!(state.accounts[&address].nonce, 1);
+        assert_eq!(state.storage[&address][&U256::ZERO], U256::from(2));
+        assert_eq!(state.bytecodes[&B256::with_last_byte(3)], Bytes::from(vec![4]));
+
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(serde_json::from_str::<FetchedState>(&json).unwrap(), state);
+
+        let mut replayed = Env::default();
+        state.apply_env(&mut replayed);
+        assert_eq!((replayed.cfg.chain_id, replayed.block.number), (324, U256::from(5)));
+    }
+}
@@ -1,8 +1,249 @@
-use alloy_primitives::B256;
+use alloy_primitives::{address, keccak256, Address, B256, U256};
+use alloy_sol_types::{sol, SolCall, SolType};
+use ethers_core::types::{TransactionRequest, U64};
 use ethers_providers::Middleware;
+use eyre::WrapErr;
+use foundry_common::{
+    types::ToEthers,
+    zk_utils::withdrawal::{finalize_withdrawal_params, finalize_withdrawal_target},
+    ProviderBuilder, RetryProvider,
+};
+use foundry_compilers::utils::RuntimeOrHandle;
 use revm::primitives::Bytecode;
+use serde::Deserialize;
 
 #[async_trait::async_trait]
 pub trait ZkSyncMiddleware: Middleware {
     async fn get_bytecode_by_hash(&self, hash: B256) -> Result<Option<Bytecode>, Self::Error>;
+
+    /// Returns the L1 batch containing the given block, or the latest sealed one, along with the
+    /// root hash of its state tree and its last L2 block.
+    async fn get_verified_batch(&self, block: Option<u64>) -> Result<VerifiedBatch, Self::Error>;
+
+    /// Fetches a storage slot with `zks_getProof`, and verifies its proof against the root hash
+    /// of the batch.
+    ///
+    /// Fails if the proof doesn't match the root hash.
+    async fn get_verified_storage_at(
+        &self,
+        address: Address,
+        idx: U256,
+        batch: VerifiedBatch,
+    ) -> Result<U256, Self::Error>;
+}
+
+/// An L1 batch whose root hash the storage of a fork is verified against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifiedBatch {
+    /// The number of the L1 batch.
+    pub number: u32,
+    /// The root hash of the state tree after the batch.
+    pub root_hash: B256,
+    /// The last L2 block of the batch, the only block whose storage the root hash proves.
+    pub last_block: u64,
+    /// The L1 transaction executing the batch, whose calldata has the commitment of the batch.
+    pub execute_tx_hash: Option<B256>,
+}
+
+/// The subset of `zks_getBlockDetails` needed to find the L1 batch of a block.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BlockDetails {
+    pub(crate) l1_batch_number: u32,
+}
+
+/// The subset of `zks_getL1BatchDetails` needed to verify proofs.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct L1BatchDetails {
+    pub(crate) root_hash: Option<B256>,
+    pub(crate) execute_tx_hash: Option<B256>,
+}
+
+sol! {
+    /// The commitment of an L1 batch, whose hash the main zkSync contract stores.
+    struct StoredBatchInfo {
+        uint64 batchNumber;
+        bytes32 batchHash;
+        uint64 indexRepeatedStorageChanges;
+        uint256 numberOfLayer1Txs;
+        bytes32 priorityOperationsHash;
+        bytes32 l2LogsTreeRoot;
+        uint256 timestamp;
+        bytes32 commitment;
+    }
+
+    function storedBatchHash(uint256 _batchNumber) external view returns (bytes32);
+
+    function getTotalBatchesExecuted() external view returns (uint256);
+
+    function executeBatches(StoredBatchInfo[] calldata _batchesData);
+}
+
+/// Returns the main zkSync contract on L1 of the chains it's known for, by L2 chain ID.
+pub fn known_main_contract(chain_id: u64) -> Option<Address> {
+    match chain_id {
+        // zkSync Era mainnet
+        324 => Some(address!("32400084c286cf3e17e7b677ea9583e60a000324")),
+        // zkSync Era Sepolia testnet
+        300 => Some(address!("9a6de0f62aa270a8bcb1e2610078650d539b1ef9")),
+        _ => None,
+    }
+}
+
+/// Checks on L1 that the root hash of the batch is the one the main zkSync contract executed, so
+/// that it isn't only trusted from the forked node.
+///
+/// The commitment of the batch is read from the calldata of its execution transaction, and checked
+/// against the hash the main contract stores for the batch.
+pub async fn verify_batch_on_l1(
+    l1_url: &str,
+    main_contract: Address,
+    batch: &VerifiedBatch,
+) -> eyre::Result<()> {
+    let provider = ProviderBuilder::new(l1_url).build()?;
+
+    let executed = l1_call(&provider, main_contract, getTotalBatchesExecutedCall {}).await?._0;
+    if executed < U256::from(batch.number) {
+        eyre::bail!(
+            "L1 batch {} is not executed on L1 yet, only {executed} batches are",
+            batch.number
+        )
+    }
+    let stored_hash = l1_call(
+        &provider,
+        main_contract,
+        storedBatchHashCall { _batchNumber: U256::from(batch.number) },
+    )
+    .await?
+    ._0;
+
+    let tx_hash = batch
+        .execute_tx_hash
+        .ok_or_else(|| eyre::eyre!("the execution of L1 batch {} is unknown", batch.number))?;
+    let tx = provider
+        .get_transaction(tx_hash.to_ethers())
+        .await
+        .wrap_err_with(|| format!("Failed to fetch the L1 transaction {tx_hash}"))?
+        .ok_or_else(|| eyre::eyre!("the L1 transaction {tx_hash} was not found"))?;
+    check_stored_batch(batch, &tx.input, stored_hash)
+}
+
+/// Calls the main zkSync contract on L1, at the latest block.
+async fn l1_call<C: SolCall>(
+    provider: &RetryProvider,
+    main_contract: Address,
+    call: C,
+) -> eyre::Result<C::Return> {
+    let tx = TransactionRequest::new().to(main_contract.to_ethers()).data(call.abi_encode());
+    let output = provider
+        .call(&tx.into(), None)
+        .await
+        .wrap_err_with(|| format!("Failed to call the main zkSync contract {main_contract}"))?;
+    Ok(C::abi_decode_returns(&output, true)?)
+}
+
+/// Checks that the `executeBatches` calldata has the commitment of the batch, and that it hashes
+/// to the hash the main zkSync contract stores for the batch.
+fn check_stored_batch(
+    batch: &VerifiedBatch,
+    calldata: &[u8],
+    stored_hash: B256,
+) -> eyre::Result<()> {
+    let execute = executeBatchesCall::abi_decode(calldata, true).wrap_err_with(|| {
+        format!("the execution of L1 batch {} is not an `executeBatches` call", batch.number)
+    })?;
+    let info = execute
+        ._batchesData
+        .into_iter()
+        .find(|info| info.batchNumber == u64::from(batch.number))
+        .ok_or_else(|| eyre::eyre!("L1 batch {} is not in its execution", batch.number))?;
+
+    if keccak256(StoredBatchInfo::abi_encode(&info)) != stored_hash {
+        eyre::bail!(
+            "the commitment of L1 batch {} doesn't match the hash {stored_hash} stored on L1",
+            batch.number
+        )
+    }
+    if info.batchHash != batch.root_hash {
+        eyre::bail!(
+            "the root hash {} of L1 batch {} doesn't match the root hash {} committed on L1",
+            batch.root_hash,
+            batch.number,
+            info.batchHash
+        )
+    }
+    Ok(())
+}
+
+/// Builds the L1 call finalizing the `index`th withdrawal of the L2 transaction, fetching the
+/// proof of its message from the zkSync node at `url`. Returns the L1 contract to call, and the
+/// calldata.
+pub fn finalize_withdrawal_call(
+    url: &str,
+    tx_hash: B256,
+    index: usize,
+) -> eyre::Result<(Address, Vec<u8>)> {
+    let provider = ProviderBuilder::new(url).build()?;
+    RuntimeOrHandle::new().block_on(async {
+        let withdrawal = finalize_withdrawal_params(&provider, tx_hash, index).await?;
+        let target = finalize_withdrawal_target(&provider, &withdrawal).await?;
+        Ok((target, withdrawal.calldata()))
+    })
+}
+
+/// Checks that the endpoint is a zkSync node, i.e. that it serves the `zks_` namespace, so that
+/// forking another chain fails right away instead of with decoding errors in the middle of a test.
+pub fn ensure_zksync_endpoint(url: &str) -> eyre::Result<()> {
+    let provider = ProviderBuilder::new(url).build()?;
+    RuntimeOrHandle::new().block_on(provider.request::<_, U64>("zks_L1ChainId", ())).map_err(
+        |err| {
+            eyre::eyre!(
+                "{url} is not a zkSync endpoint, only zkSync chains can be forked in the zkEVM \
+                 (`zks_L1ChainId` failed: {err})"
+            )
+        },
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored_batch(number: u64, batch_hash: B256) -> StoredBatchInfo {
+        StoredBatchInfo {
+            batchNumber: number,
+            batchHash: batch_hash,
+            indexRepeatedStorageChanges: 0,
+            numberOfLayer1Txs: U256::ZERO,
+            priorityOperationsHash: B256::ZERO,
+            l2LogsTreeRoot: B256::ZERO,
+            timestamp: U256::ZERO,
+            commitment: B256::with_last_byte(number as u8),
+        }
+    }
+
+    #[test]
+    fn checks_stored_batch() {
+        let root_hash = B256::with_last_byte(1);
+        let batch = VerifiedBatch { number: 2, root_hash, last_block: 10, execute_tx_hash: None };
+        let info = stored_batch(2, root_hash);
+        let stored_hash = keccak256(StoredBatchInfo::abi_encode(&info));
+        let calldata = executeBatchesCall { _batchesData: vec![stored_batch(1, B256::ZERO), info] }
+            .abi_encode();
+        assert!(check_stored_batch(&batch, &calldata, stored_hash).is_ok());
+
+        // the commitment must be the one stored on L1
+        assert!(check_stored_batch(&batch, &calldata, B256::ZERO).is_err());
+
+        // the root hash of the forked node must be the committed one
+        let forged = VerifiedBatch { root_hash: B256::with_last_byte(2), ..batch };
+        assert!(check_stored_batch(&forged, &calldata, stored_hash).is_err());
+
+        // the batch must be among the executed ones
+        let calldata =
+            executeBatchesCall { _batchesData: vec![stored_batch(1, B256::ZERO)] }.abi_encode();
+        assert!(check_stored_batch(&batch, &calldata, stored_hash).is_err());
+    }
 }
@@ -40,6 +40,25 @@ pub struct EvmOpts {
     /// Disables storage caching entirely.
     pub no_storage_caching: bool,
 
+    /// Records the state fetched by forks, to prefetch it concurrently when forking the same
+    /// chain in the next run.
+    pub fork_prefetch: bool,
+
+    /// Accounts to prefetch concurrently when creating a fork.
+    pub fork_prefetch_accounts: Vec<Address>,
+
+    /// Fetches the storage of forks with `zks_getProof`, verified against the root hash of the L1
+    /// batch of the fork block, which must be the last block of its batch.
+    ///
+    /// Only the storage is proven, the code, balance and nonce of accounts are not.
+    pub fork_verify_storage: bool,
+
+    /// The L1 endpoint the root hashes storage is verified against are checked on.
+    pub fork_verify_l1_url: Option<String>,
+
+    /// The main zkSync contract on L1, storing the commitments of the batches.
+    pub fork_verify_main_contract: Option<Address>,
+
     /// The initial balance of each deployed test contract.
     pub initial_balance: U256,
 
@@ -38,6 +38,8 @@ pub struct FuzzedExecutor {
     sender: Address,
     /// The fuzz configuration
     config: FuzzConfig,
+    /// Calldata run before fuzzing, e.g. counterexamples of previous runs
+    corpus: Vec<Bytes>,
 }
 
 impl FuzzedExecutor {
@@ -48,7 +50,14 @@ impl FuzzedExecutor {
         sender: Address,
         config: FuzzConfig,
     ) -> Self {
-        Self { executor, runner, sender, config }
+        Self { executor, runner, sender, config, corpus: vec![] }
+    }
+
+    /// Sets the calldata to run before fuzzing. The first one failing is reported as the
+    /// counterexample, without fuzzing.
+    pub fn with_corpus(mut self, corpus: Vec<Bytes>) -> Self {
+        self.corpus = corpus;
+        self
     }
 
     /// Fuzzes the provided function, assuming it is available at the contract at `address`
@@ -78,6 +87,9 @@ impl FuzzedExecutor {
         // Stores coverage information for all fuzz cases
         let coverage: RefCell<Option<HitMaps>> = RefCell::default();
 
+        // Stores the calldata of the cases that increased the coverage
+        let new_coverage_inputs: RefCell<Vec<Bytes>> = RefCell::default();
+
         let state = self.build_fuzz_state();
 
         let mut weights = vec![];
@@ -94,8 +106,8 @@ impl FuzzedExecutor {
 
         let strat = proptest::strategy::Union::new_weighted(weights);
         debug!(func=?func.name, should_fail, "fuzzing");
-        let run_result = self.runner.clone().run(&strat, |calldata| {
-            let fuzz_res = self.single_fuzz(&state, address, should_fail, calldata)?;
+        let test_case = |calldata: Bytes| {
+            let fuzz_res = self.single_fuzz(&state, address, should_fail, calldata.clone())?;
 
             match fuzz_res {
                 FuzzOutcome::Case(case) => {
@@ -107,6 +119,14 @@ impl FuzzedExecutor {
 
                     traces.replace(case.traces);
 
+                    if case
+                        .coverage
+                        .as_ref()
+                        .is_some_and(|hits| has_new_hits(hits, coverage.borrow().as_ref()))
+                    {
+                        new_coverage_inputs.borrow_mut().push(calldata);
+                    }
+
                     if let Some(prev) = coverage.take() {
                         // Safety: If `Option::or` evaluates to `Some`, then `call.coverage` must
                         // necessarily also be `Some`
@@ -135,7 +155,20 @@ impl FuzzedExecutor {
                     Err(TestCaseError::fail(reason.unwrap_or_default()))
                 }
             }
-        });
+        };
+
+        let corpus_failure =
+            self.corpus.iter().find_map(|calldata| match test_case(calldata.clone()) {
+                Err(TestCaseError::Fail(reason)) => Some(TestError::Fail(reason, calldata.clone())),
+                _ => None,
+            });
+        let run_result = match corpus_failure {
+            Some(failure) => {
+                debug!(func=?func.name, "corpus input failed");
+                Err(failure)
+            }
+            None => self.runner.clone().run(&strat, test_case),
+        };
 
         let (calldata, call) = counterexample.into_inner();
         let mut result = FuzzTestResult {
@@ -149,6 +182,7 @@ impl FuzzedExecutor {
             labeled_addresses: call.labels,
             traces: if run_result.is_ok() { traces.into_inner() } else { call.traces.clone() },
             coverage: coverage.into_inner(),
+            new_coverage_inputs: new_coverage_inputs.into_inner(),
         };
 
         match run_result {
@@ -215,11 +249,8 @@ impl FuzzedExecutor {
         );
 
         // When the `assume` cheatcode is called it returns a special string
-        if let Ok(call_result) = String::abi_decode(&call.result, false) {
-            let call_result: Vec<u8> = call_result.into();
-            if call_result == MAGIC_ASSUME {
-                return Err(TestCaseError::reject(FuzzError::AssumeReject))
-            }
+        if is_assume_rejection(&call.result) {
+            return Err(TestCaseError::reject(FuzzError::AssumeReject))
         }
 
         let breakpoints = call
@@ -257,3 +288,20 @@ impl FuzzedExecutor {
         }
     }
 }
+
+/// Returns `true` if the call was rejected by `vm.assume`.
+///
+/// The cheatcode reverts with [`MAGIC_ASSUME`] as is, both in the EVM and in the zkEVM, and the
+/// test bubbles it up unchanged. It's also recognized when encoded as a string.
+fn is_assume_rejection(result: &[u8]) -> bool {
+    result == MAGIC_ASSUME ||
+        String::abi_decode(result, false).is_ok_and(|reason| reason.as_bytes() == MAGIC_ASSUME)
+}
+
+/// Returns `true` if `hits` contains an instruction that isn't hit in `seen`.
+fn has_new_hits(hits: &HitMaps, seen: Option<&HitMaps>) -> bool {
+    hits.iter().any(|(code_hash, hit_map)| {
+        let seen = seen.and_then(|seen| seen.get(code_hash));
+        hit_map.hits.keys().any(|pc| seen.map_or(true, |seen| !seen.hits.contains_key(pc)))
+    })
+}
@@ -12,6 +12,7 @@ use crate::inspectors::{
 use alloy_dyn_abi::{DynSolValue, FunctionExt, JsonAbiExt};
 use alloy_json_abi::{Function, JsonAbi as Abi};
 use alloy_primitives::{Address, Bytes, FixedBytes, B256, U256};
+use era_cheatcodes::revert_site::RevertSite;
 use ethers_core::types::{Log, H256};
 use ethers_signers::LocalWallet;
 use foundry_common::{
@@ -20,7 +21,7 @@ use foundry_common::{
         address_to_h160, h160_to_address, h256_to_revm_u256, revm_u256_to_u256, u256_to_revm_u256,
     },
     evm::Breakpoints,
-    fix_l2_gas_limit, fix_l2_gas_price,
+    fix_l2_gas_limit, fix_l2_gas_price, ZkStateOverrides,
 };
 use foundry_evm_core::{
     backend::{Backend, DatabaseError, DatabaseExt, DatabaseResult, FuzzBackendWrapper},
@@ -38,7 +39,7 @@ use revm::{
         BlockEnv, Bytecode, Env, ExecutionResult, Output, ResultAndState, SpecId, TransactTo, TxEnv,
     },
 };
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, sync::Arc};
 use zksync_types::{
     get_nonce_key,
     utils::{decompose_full_nonce, nonces_to_full_nonce},
@@ -123,7 +124,7 @@ impl Executor {
             .expect("failed writing account storage for known codes storage address");
 
         // setup modified keys in inspector to persist cheatcode address setup across forks
-        inspector.storage_modifications.keys.insert(
+        inspector.storage_modifications.insert_slot(
             StorageKey::new(
                 AccountTreeId::new(ACCOUNT_CODE_STORAGE_ADDRESS),
                 H256::from_slice(
@@ -132,7 +133,7 @@ impl Executor {
             ),
             H256::from_slice(&empty_contract_code_hash.0),
         );
-        inspector.storage_modifications.keys.insert(
+        inspector.storage_modifications.insert_slot(
             StorageKey::new(
                 AccountTreeId::new(zksync_types::H160(CHEATCODE_ADDRESS.0 .0)),
                 H256::from_slice(&empty_contract_code_hash.0),
@@ -190,6 +191,56 @@ impl Executor {
         Ok(self.backend.basic_ref(address)?.map(|acc| acc.nonce).unwrap_or_default())
     }
 
+    /// Set the EraVM bytecode of an account. The bytecode is also marked as known, so that it can
+    /// be executed by the zksync VM.
+    pub fn set_code(&mut self, address: Address, code: Bytes) -> DatabaseResult<&mut Self> {
+        let code_hash = hash_bytecode(&code);
+
+        let mut account = self.backend.basic_ref(address)?.unwrap_or_default();
+        account.code_hash = B256::from_slice(&code_hash.0);
+        account.code = Some(Bytecode::new_raw(code));
+        self.backend.insert_account_info(address, account);
+
+        self.set_storage(
+            h160_to_address(ACCOUNT_CODE_STORAGE_ADDRESS),
+            U256::from_be_bytes(FixedBytes::<32>::left_padding_from(address.as_slice()).0),
+            U256::from_be_slice(&code_hash.0),
+        )?;
+        self.set_storage(
+            h160_to_address(KNOWN_CODES_STORAGE_ADDRESS),
+            U256::from_be_slice(&code_hash.0),
+            U256::from(1u64),
+        )?;
+        Ok(self)
+    }
+
+    /// Set the value of a storage slot of an account.
+    pub fn set_storage(
+        &mut self,
+        address: Address,
+        slot: U256,
+        value: U256,
+    ) -> DatabaseResult<&mut Self> {
+        self.backend.insert_account_storage(address, slot, value)?;
+
+        // record the modification so it persists across forks
+        self.inspector.storage_modifications.insert_slot(
+            StorageKey::new(
+                AccountTreeId::new(address_to_h160(address)),
+                H256::from(slot.to_be_bytes::<32>()),
+            ),
+            H256::from(value.to_be_bytes::<32>()),
+        );
+        Ok(self)
+    }
+
+    /// Sets the overrides applied on top of the state read by the zkEVM. Unlike the setters above,
+    /// the overrides don't modify the backend.
+    pub fn set_zk_state_overrides(&mut self, overrides: ZkStateOverrides) -> &mut Self {
+        self.inspector.zk_state_overrides = Arc::new(overrides);
+        self
+    }
+
     #[inline]
     pub fn set_tracing(&mut self, tracing: bool) -> &mut Self {
         self.inspector.tracing(tracing);
@@ -243,6 +294,7 @@ impl Executor {
                     Err(EvmError::Execution(Box::new(ExecutionErr {
                         reverted: res.reverted,
                         reason: "execution error".to_owned(),
+                        result: res.result,
                         traces: res.traces,
                         gas_used: res.gas_used,
                         gas_refunded: res.gas_refunded,
@@ -253,6 +305,7 @@ impl Executor {
                         state_changeset: None,
                         transactions: None,
                         script_wallets: res.script_wallets,
+                        revert_site: res.revert_site,
                     })))
                 }
             }
@@ -428,6 +481,7 @@ impl Executor {
             script_wallets,
             env,
             coverage,
+            revert_site,
             ..
         } = result;
 
@@ -444,6 +498,7 @@ impl Executor {
                     return Err(EvmError::Execution(Box::new(ExecutionErr {
                         reverted: true,
                         reason: "Deployment succeeded, but no address was returned. This is a bug, please report it".to_string(),
+                        result: Bytes::new(),
                         traces,
                         gas_used,
                         gas_refunded: 0,
@@ -453,7 +508,8 @@ impl Executor {
                         labels,
                         state_changeset: None,
                         transactions: None,
-                        script_wallets
+                        script_wallets,
+                        revert_site: None,
                     })));
                 }
             }
@@ -462,6 +518,7 @@ impl Executor {
                 return Err(EvmError::Execution(Box::new(ExecutionErr {
                     reverted: true,
                     reason,
+                    result,
                     traces,
                     gas_used,
                     gas_refunded,
@@ -472,6 +529,7 @@ impl Executor {
                     state_changeset: None,
                     transactions: None,
                     script_wallets,
+                    revert_site,
                 })))
             }
         };
@@ -637,7 +695,11 @@ impl Executor {
     /// to pay for the gas + value. Hence we cap the gas limit what the caller can actually pay.
     pub fn adjust_zksync_gas_parameters(&mut self) {
         let tx_env = &self.env.tx;
-        let caller_balance = self.get_balance(tx_env.caller).unwrap_or_default();
+        let caller_balance =
+            match self.inspector.zk_state_overrides.balance(address_to_h160(tx_env.caller)) {
+                Some(balance) => u256_to_revm_u256(balance),
+                None => self.get_balance(tx_env.caller).unwrap_or_default(),
+            };
         let min_gas_price =
             u256_to_revm_u256(fix_l2_gas_price(revm_u256_to_u256(tx_env.gas_price)));
         let max_allowed_gas_limit = caller_balance
@@ -662,6 +724,8 @@ impl Executor {
 pub struct ExecutionErr {
     pub reverted: bool,
     pub reason: String,
+    /// The data the call reverted with, which `reason` was decoded from
+    pub result: Bytes,
     pub gas_used: u64,
     pub gas_refunded: u64,
     pub stipend: u64,
@@ -672,6 +736,8 @@ pub struct ExecutionErr {
     pub transactions: Option<BroadcastableTransactions>,
     pub state_changeset: Option<StateChangeset>,
     pub script_wallets: Vec<LocalWallet>,
+    /// The instruction the zkEVM transaction reverted at
+    pub revert_site: Option<RevertSite>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -797,6 +863,8 @@ pub struct RawCallResult {
     pub out: Option<Output>,
     /// The chisel state
     pub chisel_state: Option<(Stack, Vec<u8>, InstructionResult)>,
+    /// The instruction the zkEVM transaction reverted at
+    pub revert_site: Option<RevertSite>,
 }
 
 impl Default for RawCallResult {
@@ -821,6 +889,7 @@ impl Default for RawCallResult {
             cheatcodes: Default::default(),
             out: None,
             chisel_state: None,
+            revert_site: None,
         }
     }
 }
@@ -880,6 +949,7 @@ fn convert_executed_result(
         cheatcodes,
         script_wallets,
         chisel_state,
+        revert_site,
     } = inspector.collect();
 
     let transactions = match cheatcodes.as_ref() {
@@ -911,6 +981,7 @@ fn convert_executed_result(
         cheatcodes,
         out,
         chisel_state,
+        revert_site,
     })
 }
 
@@ -935,6 +1006,7 @@ fn convert_call_result(
         state_changeset,
         script_wallets,
         env,
+        revert_site,
         ..
     } = call_result;
 
@@ -980,6 +1052,7 @@ fn convert_call_result(
             Err(EvmError::Execution(Box::new(ExecutionErr {
                 reverted,
                 reason,
+                result,
                 gas_used,
                 gas_refunded,
                 stipend,
@@ -990,6 +1063,7 @@ fn convert_call_result(
                 transactions,
                 state_changeset,
                 script_wallets,
+                revert_site,
             })))
         }
     }
@@ -3,9 +3,20 @@ use super::{
     TracePrinter, Tracer,
 };
 use alloy_primitives::{Address, Bytes, B256, U256};
+use era_cheatcodes::{
+    breakpoints::ZkBreakpoints,
+    labels::Labels,
+    plugins::TracerPlugins,
+    revert_site::{RevertSite, RevertSiteSlot},
+    script_wallets::ScriptWallets,
+    tracers::EnabledTracers,
+};
 use ethers_core::types::Log;
 use ethers_signers::LocalWallet;
-use foundry_common::{AsTracerPointer, StorageModificationRecorder, StorageModifications};
+use foundry_common::{
+    conversion_utils::{address_to_h160, h160_to_address},
+    AsTracerPointer, StorageModificationRecorder, StorageModifications, ZkStateOverrides,
+};
 use foundry_evm_core::{
     backend::DatabaseExt, debug::DebugArena, era_revm::storage_view::StorageView,
 };
@@ -49,6 +60,8 @@ pub struct InspectorStackBuilder {
     pub print: Option<bool>,
     /// The chisel state inspector.
     pub chisel_state: Option<usize>,
+    /// Additional tracers to run alongside the cheatcode tracer in the zkEVM.
+    pub tracer_plugins: Option<TracerPlugins>,
 }
 
 impl InspectorStackBuilder {
@@ -128,6 +141,13 @@ impl InspectorStackBuilder {
         self
     }
 
+    /// Set the additional tracers to run in the zkEVM.
+    #[inline]
+    pub fn tracer_plugins(mut self, plugins: TracerPlugins) -> Self {
+        self.tracer_plugins = Some(plugins);
+        self
+    }
+
     /// Builds the stack of inspectors to use when transacting/committing on the EVM.
     ///
     /// See also [`revm::Evm::inspect_ref`] and [`revm::Evm::commit_ref`].
@@ -143,6 +163,7 @@ impl InspectorStackBuilder {
             coverage,
             print,
             chisel_state,
+            tracer_plugins,
         } = self;
         let mut stack = InspectorStack::new();
 
@@ -161,6 +182,9 @@ impl InspectorStackBuilder {
         stack.enable_debugger(debug.unwrap_or(false));
         stack.print(print.unwrap_or(false));
         stack.tracing(trace.unwrap_or(false));
+        if let Some(tracer_plugins) = tracer_plugins {
+            stack.set_tracer_plugins(tracer_plugins);
+        }
 
         // environment, must come after all of the inspectors
         if let Some(block) = block {
@@ -195,6 +219,7 @@ pub struct InspectorData {
     pub cheatcodes: Option<Cheatcodes>,
     pub script_wallets: Vec<LocalWallet>,
     pub chisel_state: Option<(Stack, Vec<u8>, InstructionResult)>,
+    pub revert_site: Option<RevertSite>,
 }
 /// An inspector that calls multiple inspectors in sequence.
 ///
@@ -211,6 +236,17 @@ pub struct InspectorStack {
     pub printer: Option<TracePrinter>,
     pub tracer: Option<Tracer>,
     pub storage_modifications: StorageModifications,
+    pub tracer_plugins: TracerPlugins,
+    /// The site of the innermost revert of the zkEVM transaction.
+    pub revert_site: RevertSiteSlot,
+    /// The labels assigned in the zkEVM transaction.
+    pub zk_labels: Labels,
+    /// The wallets remembered in the zkEVM transaction.
+    pub zk_script_wallets: ScriptWallets,
+    /// The breakpoints written in the zkEVM transaction.
+    pub zk_breakpoints: ZkBreakpoints,
+    /// Overrides of the state read by the zkEVM transactions, e.g. the ones of `cast call`.
+    pub zk_state_overrides: Arc<ZkStateOverrides>,
 }
 
 impl InspectorStack {
@@ -295,9 +331,36 @@ impl InspectorStack {
         self.tracer = yes.then(Default::default);
     }
 
+    /// Set the additional tracers to run in the zkEVM.
+    #[inline]
+    pub fn set_tracer_plugins(&mut self, plugins: TracerPlugins) {
+        self.tracer_plugins = plugins;
+    }
+
     /// Collects all the data gathered during inspection into a single struct.
     #[inline]
-    pub fn collect(self) -> InspectorData {
+    pub fn collect(mut self) -> InspectorData {
+        let zk_labels = self.zk_labels.take();
+        if let Some(cheatcodes) = self.cheatcodes.as_mut() {
+            cheatcodes.labels.extend(
+                zk_labels.into_iter().map(|(address, label)| (h160_to_address(address), label)),
+            );
+            cheatcodes.script_wallets.extend(self.zk_script_wallets.take());
+            for (point, breakpoint) in self.zk_breakpoints.take() {
+                match breakpoint {
+                    Some(breakpoint) => {
+                        cheatcodes.breakpoints.insert(
+                            point,
+                            (h160_to_address(breakpoint.address), breakpoint.pc as usize),
+                        );
+                    }
+                    None => {
+                        cheatcodes.breakpoints.remove(&point);
+                    }
+                }
+            }
+        }
+
         InspectorData {
             logs: self.log_collector.map(|logs| logs.logs).unwrap_or_default(),
             labels: self
@@ -317,6 +380,7 @@ impl InspectorStack {
                 .unwrap_or_default(),
             cheatcodes: self.cheatcodes,
             chisel_state: self.chisel_state.and_then(|state| state.state),
+            revert_site: self.revert_site.take(),
         }
     }
 
@@ -575,6 +639,7 @@ impl<DB: DatabaseExt> Inspector<DB> for InspectorStack {
 }
 
 use era_cheatcodes::cheatcodes::CheatcodeTracer;
+use foundry_config::ZkExecutionMode;
 use foundry_evm_core::era_revm::db::RevmDatabaseForEra;
 use multivm::vm_latest::{HistoryDisabled, ToTracerPointer};
 
@@ -585,8 +650,20 @@ impl<DB: DatabaseExt + Send> AsTracerPointer<StorageView<RevmDatabaseForEra<DB>>
         &self,
     ) -> multivm::vm_latest::TracerPointer<StorageView<RevmDatabaseForEra<DB>>, HistoryDisabled>
     {
+        // the labels assigned in the EVM, or in previous transactions, are readable
+        if let Some(cheatcodes) = &self.cheatcodes {
+            self.zk_labels.extend(
+                cheatcodes
+                    .labels
+                    .iter()
+                    .map(|(address, label)| (address_to_h160(*address), label.clone())),
+            );
+        }
+
+        let config = self.cheatcodes.as_ref().map(|c| c.config.clone()).unwrap_or_default();
+        let tracers = EnabledTracers::without(&config.disabled_tracers);
         CheatcodeTracer::new(
-            self.cheatcodes.as_ref().map(|c| c.config.clone()).unwrap_or_default(),
+            config,
             self.storage_modifications.clone(),
             //TODO: dedicated InspectorStack field
             self.cheatcodes
@@ -594,8 +671,22 @@ impl<DB: DatabaseExt + Send> AsTracerPointer<StorageView<RevmDatabaseForEra<DB>>
                 .map(|c| c.broadcastable_transactions.clone())
                 .unwrap_or_default(),
         )
+        .with_plugins(self.tracer_plugins.clone())
+        .with_revert_site(self.revert_site.share())
+        .with_labels(self.zk_labels.share())
+        .with_script_wallets(self.zk_script_wallets.share())
+        .with_breakpoints(self.zk_breakpoints.share())
+        .with_tracers(tracers)
         .into_tracer_pointer()
     }
+
+    fn execution_mode(&self) -> ZkExecutionMode {
+        self.cheatcodes.as_ref().map(|c| c.config.execution_mode).unwrap_or_default()
+    }
+
+    fn state_overrides(&self) -> Arc<ZkStateOverrides> {
+        self.zk_state_overrides.clone()
+    }
 }
 
 impl StorageModificationRecorder for &mut InspectorStack {
@@ -152,6 +152,10 @@ pub struct FuzzTestResult {
 
     /// Raw coverage info
     pub coverage: Option<HitMaps>,
+
+    /// The calldata of the cases that hit instructions no previous case hit, in the order they
+    /// were run. Only found when coverage is collected.
+    pub new_coverage_inputs: Vec<Bytes>,
 }
 
 impl FuzzTestResult {
@@ -149,6 +149,14 @@ impl SignaturesIdentifier {
         self.ensure_not_offline()?;
         self.identify(SelectorType::Event, identifier, get_event).await
     }
+
+    /// Identifies `Function` from its cache or, unless offline, `https://api.openchain.xyz`
+    ///
+    /// Unlike [SignaturesIdentifier::identify_function], cached signatures are still used in
+    /// offline mode, e.g. to decode the custom errors of failing zkEVM tests.
+    pub async fn identify_cached_function(&mut self, identifier: &[u8]) -> Option<Function> {
+        self.identify(SelectorType::Function, identifier, get_func).await
+    }
 }
 
 impl Drop for SignaturesIdentifier {
@@ -191,4 +199,23 @@ mod tests {
         assert_eq!(sigs.read().await.cached.events.len(), 1);
         assert_eq!(sigs.read().await.cached.functions.len(), 1);
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn identifies_cached_functions_offline() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cached = CachedSignatures {
+            events: Default::default(),
+            functions: BTreeMap::from([(
+                "0x23b872dd".to_string(),
+                "transferFrom(address,address,uint256)".to_string(),
+            )]),
+        };
+        fs::write_json_file(&tmp.path().join("signatures"), &cached).unwrap();
+
+        let sigs = SignaturesIdentifier::new(Some(tmp.path().into()), true).unwrap();
+        assert_eq!(sigs.write().await.identify_function(&[35, 184, 114, 221]).await, None);
+
+        let func = sigs.write().await.identify_cached_function(&[35, 184, 114, 221]).await.unwrap();
+        assert_eq!(func, get_func("transferFrom(address,address,uint256)").unwrap());
+    }
 }
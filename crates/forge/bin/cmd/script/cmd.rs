@@ -169,6 +169,7 @@ impl ScriptArgs {
             for tx in txs.iter() {
                 lib_deploy.push_back(BroadcastableTransaction {
                     factory_deps: vec![],
+                    fee_overrides: Default::default(),
                     rpc: tx.rpc.clone(),
                     transaction: TypedTransaction::Legacy(tx.transaction.clone().into()),
                 });
@@ -335,6 +336,7 @@ impl ScriptArgs {
             for new_tx in new_txs.iter() {
                 txs.push_back(BroadcastableTransaction {
                     factory_deps: vec![],
+                    fee_overrides: Default::default(),
                     rpc: new_tx.rpc.clone(),
                     transaction: TypedTransaction::Legacy(new_tx.transaction.clone().into()),
                 });
@@ -5,7 +5,7 @@ use foundry_cli::utils as forge_utils;
 use foundry_compilers::artifacts::{OptimizerDetails, RevertStrings, YulDetails};
 use foundry_config::{
     cache::{CachedChains, CachedEndpoints, StorageCachingConfig},
-    Config, FuzzConfig, InvariantConfig, SolcReq,
+    Config, FuzzConfig, InvariantConfig, SolcReq, ZkExecutionMode,
 };
 use foundry_evm::opts::EvmOpts;
 use foundry_test_utils::{
@@ -64,11 +64,17 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         },
         invariant: InvariantConfig { runs: 256, ..Default::default() },
         ffi: true,
+        record_logs: false,
         sender: "00a329c0648769A73afAc7F9381D08FB43dBEA72".parse().unwrap(),
         tx_origin: "00a329c0648769A73afAc7F9F81E08FB43dBEA72".parse().unwrap(),
         initial_balance: U256::from(0xffffffffffffffffffffffffu128),
         block_number: 10,
         fork_block_number: Some(200),
+        fork_prefetch: false,
+        fork_prefetch_accounts: vec![],
+        fork_verify_storage: false,
+        fork_verify_l1_url: None,
+        fork_verify_main_contract: None,
         chain: Some(9999.into()),
         gas_limit: 99_000_000u64.into(),
         code_size_limit: Some(100000),
@@ -106,11 +112,14 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         allow_paths: vec![],
         include_paths: vec![],
         rpc_endpoints: Default::default(),
+        rpc_url_redaction: Default::default(),
         build_info: false,
         build_info_path: None,
         fmt: Default::default(),
         doc: Default::default(),
         fs_permissions: Default::default(),
+        cheatcodes: Default::default(),
+        labels: Default::default(),
         cancun: true,
         __non_exhaustive: (),
         __warnings: vec![],
@@ -126,6 +135,16 @@ forgetest!(can_extract_config_values, |prj, cmd| {
         force_evmla: false,
         is_system: false,
         detect_missing_libraries: false,
+        zk_tracked_reserved_addresses: vec![],
+        zk_genesis: vec![],
+        zk_fork_test_retries: 0,
+        zk_fork_test_retry_backoff: 1000,
+        zk_fork_test_quarantine: false,
+        zk_signature_lookup: true,
+        zk_execution_mode: ZkExecutionMode::VerifyExecute,
+        zk_disabled_tracers: vec![],
+        zk_gas_limit: None,
+        zk_fuzz_corpus: false,
     };
     prj.write_config(input.clone());
     let config = cmd.config();
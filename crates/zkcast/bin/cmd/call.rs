@@ -1,3 +1,6 @@
+use super::zk_fork::ZkForkArgs;
+use alloy_dyn_abi::FunctionExt;
+use alloy_json_abi::Function;
 use alloy_primitives::U256;
 use cast::{Cast, TxBuilder};
 use clap::Parser;
@@ -8,6 +11,7 @@ use foundry_cli::{
     utils::{self, handle_traces, parse_ether_value, TraceResult},
 };
 use foundry_common::{
+    fmt::format_token,
     runtime_client::RuntimeClient,
     types::{ToAlloy, ToEthers},
 };
@@ -82,6 +86,9 @@ pub struct CallArgs {
 
     #[clap(flatten)]
     eth: EthereumOpts,
+
+    #[clap(flatten)]
+    zk: ZkForkArgs,
 }
 
 #[derive(Debug, Parser)]
@@ -124,6 +131,7 @@ impl CallArgs {
             debug,
             verbose,
             labels,
+            zk,
         } = self;
 
         let config = Config::from(&eth);
@@ -143,6 +151,10 @@ impl CallArgs {
 
         match command {
             Some(CallSubcommands::Create { code, sig, args, value }) => {
+                if zk.zksync {
+                    eyre::bail!("`--create` is not supported with `--zksync`")
+                }
+
                 if trace {
                     let figment = Config::figment_with_root(find_project_root_path(None).unwrap())
                         .merge(eth.rpc);
@@ -204,6 +216,22 @@ impl CallArgs {
 
                     return Ok(())
                 }
+
+                if zk.zksync {
+                    let (tx, func) = builder.build();
+                    let result = zk
+                        .call(
+                            &config,
+                            sender,
+                            tx.to_addr().copied().expect("an address to be here").to_alloy(),
+                            tx.data().cloned().unwrap_or_default().to_vec().into(),
+                            tx.value().copied().unwrap_or_default().to_alloy(),
+                        )
+                        .await?;
+
+                    println!("{}", format_output(func.as_ref(), &result.result)?);
+                    return Ok(())
+                }
             }
         };
 
@@ -214,6 +242,22 @@ impl CallArgs {
     }
 }
 
+/// Formats the output of a call, decoding it if the function is known
+fn format_output(func: Option<&Function>, output: &[u8]) -> Result<String> {
+    let decoded = match func {
+        Some(func) => func.abi_decode_output(output, false).wrap_err(
+            "could not decode output; did you specify the wrong function return data type?",
+        )?,
+        None => vec![],
+    };
+
+    Ok(if decoded.is_empty() {
+        hex::encode_prefixed(output)
+    } else {
+        decoded.iter().map(format_token).collect::<Vec<_>>().join("\n")
+    })
+}
+
 /// fills the builder from create arg
 #[allow(clippy::needless_pass_by_ref_mut)]
 async fn fill_create(
@@ -1,3 +1,4 @@
+use super::zk_fork::ZkForkArgs;
 use alloy_primitives::U256;
 use cast::{Cast, TxBuilder};
 use clap::Parser;
@@ -7,6 +8,7 @@ use foundry_cli::{
     opts::{EtherscanOpts, RpcOpts},
     utils::{self, parse_ether_value},
 };
+use foundry_common::types::ToAlloy;
 use foundry_config::{figment::Figment, Config};
 use std::str::FromStr;
 
@@ -49,6 +51,9 @@ pub struct EstimateArgs {
 
     #[clap(subcommand)]
     command: Option<EstimateSubcommands>,
+
+    #[clap(flatten)]
+    zk: ZkForkArgs,
 }
 
 #[derive(Debug, Parser)]
@@ -77,7 +82,7 @@ pub enum EstimateSubcommands {
 
 impl EstimateArgs {
     pub async fn run(self) -> Result<()> {
-        let EstimateArgs { from, to, sig, args, value, rpc, etherscan, command } = self;
+        let EstimateArgs { from, to, sig, args, value, rpc, etherscan, command, zk } = self;
 
         let figment = Figment::from(Config::figment()).merge(etherscan).merge(rpc);
         let config = Config::from_provider(figment);
@@ -91,6 +96,10 @@ impl EstimateArgs {
 
         match command {
             Some(EstimateSubcommands::Create { code, sig, args, value }) => {
+                if zk.zksync {
+                    eyre::bail!("`--create` is not supported with `--zksync`")
+                }
+
                 builder.value(value);
 
                 let mut data = hex::decode(code)?;
@@ -109,6 +118,23 @@ impl EstimateArgs {
         };
 
         let builder_output = builder.peek();
+
+        if zk.zksync {
+            let (tx, _) = builder_output;
+            let result = zk
+                .call(
+                    &config,
+                    tx.from().copied().unwrap_or_default().to_alloy(),
+                    tx.to_addr().copied().expect("an address to be here").to_alloy(),
+                    tx.data().cloned().unwrap_or_default().to_vec().into(),
+                    tx.value().copied().unwrap_or_default().to_alloy(),
+                )
+                .await?;
+
+            println!("{}", result.gas_used);
+            return Ok(())
+        }
+
         let gas = Cast::new(&provider).estimate(builder_output).await?;
         println!("{gas}");
         Ok(())
@@ -18,5 +18,8 @@ pub mod run;
 pub mod send;
 pub mod storage;
 pub mod wallet;
+pub mod zk_batch_status;
 pub mod zk_deposit;
+pub mod zk_fork;
+pub mod zk_proof;
 pub mod zk_send;
@@ -1,5 +1,6 @@
 use alloy_primitives::U256;
 use clap::Parser;
+use ethers_core::types::{Transaction, H256};
 use ethers_providers::Middleware;
 use eyre::{Result, WrapErr};
 use foundry_cli::{
@@ -8,15 +9,24 @@ use foundry_cli::{
     update_progress, utils,
     utils::{handle_traces, TraceResult},
 };
-use foundry_common::{is_known_system_sender, types::ToAlloy, SYSTEM_TRANSACTION_TYPE};
+use foundry_common::{
+    is_known_system_sender,
+    types::ToAlloy,
+    zk_utils::deployer::{CONTRACT_DEPLOYER_ADDRESS, DEPLOY_SELECTORS},
+    RetryProvider, SYSTEM_TRANSACTION_TYPE,
+};
 use foundry_compilers::EvmVersion;
 use foundry_config::{find_project_root_path, Config};
 use foundry_evm::{
     executors::{EvmError, TracingExecutor},
     opts::EvmOpts,
+    revm::{primitives::State, DatabaseRef},
     utils::configure_tx_env,
 };
 
+/// Transaction type of zkSync L1 -> L2 priority transactions.
+const L1_PRIORITY_TRANSACTION_TYPE: u64 = 0xff;
+
 /// CLI arguments for `cast run`.
 #[derive(Debug, Clone, Parser)]
 pub struct RunArgs {
@@ -106,6 +116,12 @@ impl RunArgs {
                 tx.hash
             ))
         }
+        if is_l1_priority_tx(&tx) {
+            return Err(eyre::eyre!(
+                "{:?} is an L1 -> L2 priority transaction.\nReplaying priority transactions is currently not supported.",
+                tx.hash
+            ))
+        }
 
         let tx_block_number = tx
             .block_number
@@ -142,10 +158,12 @@ impl RunArgs {
 
                 for (index, tx) in block.transactions.into_iter().enumerate() {
                     // System transactions such as on L2s don't contain any pricing info so we skip
-                    // them otherwise this would cause reverts
+                    // them otherwise this would cause reverts. The same goes for zkSync L1 -> L2
+                    // priority transactions, which are paid for on L1.
                     if is_known_system_sender(tx.from.to_alloy()) ||
                         tx.transaction_type.map(|ty| ty.as_u64()) ==
-                            Some(SYSTEM_TRANSACTION_TYPE)
+                            Some(SYSTEM_TRANSACTION_TYPE) ||
+                        is_l1_priority_tx(&tx)
                     {
                         update_progress!(pb, index);
                         continue
@@ -154,6 +172,7 @@ impl RunArgs {
                         break
                     }
 
+                    load_factory_deps(&provider, &mut executor, &tx).await?;
                     configure_tx_env(&mut env, &tx);
 
                     if let Some(to) = tx.to {
@@ -188,14 +207,20 @@ impl RunArgs {
         }
 
         // Execute our transaction
+        let mut state_diff = None;
         let result = {
             executor.set_trace_printer(self.trace_printer);
 
+            load_factory_deps(&provider, &mut executor, &tx).await?;
             configure_tx_env(&mut env, &tx);
 
             if let Some(to) = tx.to {
                 trace!(tx=?tx.hash, to=?to, "executing call transaction");
-                TraceResult::from(executor.commit_tx_with_env(env)?)
+                // The target transaction is the last one executed, so its changes are not
+                // committed: this keeps the pre-transaction values around for the state diff.
+                let result = executor.call_raw_with_env(env)?;
+                state_diff = result.state_changeset.clone();
+                TraceResult::from(result)
             } else {
                 trace!(tx=?tx.hash, "executing create transaction");
                 match executor.deploy_with_env(env, None) {
@@ -207,6 +232,90 @@ impl RunArgs {
 
         handle_traces(result, &config, chain, self.label, self.verbose, self.debug).await?;
 
+        if let Some(state_diff) = state_diff {
+            print_state_diff(&executor, &state_diff)?;
+        }
+
         Ok(())
     }
 }
+
+/// Returns true if the transaction is a zkSync L1 -> L2 priority transaction.
+fn is_l1_priority_tx(tx: &Transaction) -> bool {
+    tx.transaction_type.map(|ty| ty.as_u64()) == Some(L1_PRIORITY_TRANSACTION_TYPE)
+}
+
+/// Makes the bytecode deployed by a zkSync transaction available to the executor.
+///
+/// Deployments on zkSync go through the `ContractDeployer` and only reference the bytecode by its
+/// hash, the bytecode itself being published as a factory dependency which is not part of the
+/// transaction returned by the RPC. It is fetched with `zks_getBytecodeByHash` instead and
+/// recorded in the executor, which passes it along as a factory dependency.
+async fn load_factory_deps(
+    provider: &RetryProvider,
+    executor: &mut TracingExecutor,
+    tx: &Transaction,
+) -> Result<()> {
+    let input = &tx.input;
+    if !tx.to.is_some_and(|to| to.as_bytes() == CONTRACT_DEPLOYER_ADDRESS.as_bytes()) ||
+        input.len() < 68 ||
+        !DEPLOY_SELECTORS.iter().any(|selector| input[..4] == *selector)
+    {
+        return Ok(())
+    }
+
+    // `bytecodeHash` is the second argument of all the deploy functions.
+    let hash = H256::from_slice(&input[36..68]);
+    if executor.inspector.storage_modifications.bytecodes().contains_key(&hash) {
+        return Ok(())
+    }
+
+    let bytecode: Option<Vec<u8>> = provider
+        .request("zks_getBytecodeByHash", [hash])
+        .await
+        .wrap_err_with(|| format!("Failed to fetch the bytecode of {:?}", tx.hash))?;
+    match bytecode {
+        Some(bytecode) => {
+            executor.inspector.storage_modifications.insert_bytecode(hash, bytecode);
+        }
+        None => trace!(tx=?tx.hash, ?hash, "deployed bytecode not found"),
+    }
+    Ok(())
+}
+
+/// Prints the storage slots changed by the replayed transaction, along with their value before
+/// the transaction.
+fn print_state_diff(executor: &TracingExecutor, state: &State) -> Result<()> {
+    let mut accounts = state
+        .iter()
+        .filter_map(|(address, account)| {
+            let mut slots = account
+                .storage
+                .iter()
+                .filter_map(|(slot, value)| {
+                    let previous = executor.backend.storage_ref(*address, *slot).ok()?;
+                    (previous != value.present_value).then_some((
+                        *slot,
+                        previous,
+                        value.present_value,
+                    ))
+                })
+                .collect::<Vec<_>>();
+            slots.sort_unstable_by_key(|(slot, ..)| *slot);
+            (!slots.is_empty()).then_some((*address, slots))
+        })
+        .collect::<Vec<_>>();
+    accounts.sort_unstable_by_key(|(address, _)| *address);
+
+    println!("\nState diff:");
+    if accounts.is_empty() {
+        println!("  No storage changes.");
+    }
+    for (address, slots) in accounts {
+        println!("  {address}:");
+        for (slot, previous, present) in slots {
+            println!("    @ {slot:#x}: {previous:#x} -> {present:#x}");
+        }
+    }
+    Ok(())
+}
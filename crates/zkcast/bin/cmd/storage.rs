@@ -1,5 +1,5 @@
 use crate::opts::parse_slot;
-use alloy_primitives::{B256, U256};
+use alloy_primitives::{Address, B256, U256};
 use cast::Cast;
 use clap::Parser;
 use comfy_table::{presets::ASCII_MARKDOWN, Table};
@@ -15,6 +15,9 @@ use foundry_common::{
     abi::find_source,
     compile::{compile, etherscan_project, suppress_compile},
     types::{ToAlloy, ToEthers},
+    zk_compile::ZkSolc,
+    zk_utils::{factory_deps::PackedEraBytecode, hashed_storage_key},
+    zksolc_manager::{setup_zksolc_manager, DEFAULT_ZKSOLC_VERSION},
     RetryProvider,
 };
 use foundry_compilers::{artifacts::StorageLayout, ConfigurableContractArtifact, Project, Solc};
@@ -48,6 +51,11 @@ pub struct StorageArgs {
     #[clap(long, short)]
     block: Option<BlockId>,
 
+    /// Look up the contract in the zksolc artifacts of the project, and print the zkSync storage
+    /// keys of the slots.
+    #[clap(long)]
+    zksync: bool,
+
     #[clap(flatten)]
     rpc: RpcOpts,
 
@@ -78,12 +86,19 @@ impl StorageArgs {
     pub async fn run(self) -> Result<()> {
         let config = Config::from(&self);
 
-        let Self { address, slot, block, build, .. } = self;
+        let Self { address, slot, block, zksync, build, .. } = self;
 
         let provider = utils::get_provider(&config)?;
 
         // Slot was provided, perform a simple RPC call
         if let Some(slot) = slot {
+            if zksync {
+                let addr = address
+                    .as_address()
+                    .ok_or_else(|| eyre::eyre!("Could not resolve address"))?
+                    .to_alloy();
+                eprintln!("zkSync storage key: {}", hashed_storage_key(addr, slot));
+            }
             let cast = Cast::new(provider);
             println!("{}", cast.storage(address, slot.to_ethers(), block).await?);
             return Ok(())
@@ -98,6 +113,29 @@ impl StorageArgs {
 
         // Check if we're in a forge project and if we can find the address' code
         let mut project = build.project()?;
+        if zksync && project.paths.has_input_files() {
+            let address = address.clone();
+            // Deployed EraVM code is matched against the bytecode packed in the zksolc artifacts
+            let mut zksolc_cfg = config.zk_solc_config().map_err(|e| eyre::eyre!(e))?;
+            zksolc_cfg.compiler_path =
+                setup_zksolc_manager(DEFAULT_ZKSOLC_VERSION.to_owned()).await?;
+            project.paths.artifacts = project.paths.root.join("zkout");
+            let mut zksolc = ZkSolc::new(zksolc_cfg, project);
+            let (out, _) = zksolc
+                .compile()
+                .map_err(|e| eyre::eyre!("Failed to compile with zksolc: {}", e))?;
+            let match_code = |artifact: &ConfigurableContractArtifact| -> Option<bool> {
+                let bytes =
+                    artifact.deployed_bytecode.as_ref()?.bytecode.as_ref()?.object.as_bytes()?;
+                Some(PackedEraBytecode::from_vec(bytes).bytecode() == address_code.as_ref())
+            };
+            let artifact =
+                out.artifacts().find(|(_, artifact)| match_code(artifact).unwrap_or_default());
+            if let Some((_, artifact)) = artifact {
+                return fetch_and_print_storage(provider, address, artifact, true, zksync).await
+            }
+            eyre::bail!("No zksolc artifact matches the code deployed at the provided address");
+        }
         if project.paths.has_input_files() {
             // Find in artifacts and pretty print
             add_storage_layout_output(&mut project);
@@ -110,7 +148,8 @@ impl StorageArgs {
             let artifact =
                 out.artifacts().find(|(_, artifact)| match_code(artifact).unwrap_or_default());
             if let Some((_, artifact)) = artifact {
-                return fetch_and_print_storage(provider, address.clone(), artifact, true).await
+                return fetch_and_print_storage(provider, address.clone(), artifact, true, zksync)
+                    .await
             }
         }
 
@@ -176,7 +215,7 @@ impl StorageArgs {
         // Clear temp directory
         root.close()?;
 
-        fetch_and_print_storage(provider, address, artifact, true).await
+        fetch_and_print_storage(provider, address, artifact, true, zksync).await
     }
 }
 
@@ -185,14 +224,25 @@ async fn fetch_and_print_storage(
     address: NameOrAddress,
     artifact: &ConfigurableContractArtifact,
     pretty: bool,
+    zksync: bool,
 ) -> Result<()> {
     if is_storage_layout_empty(&artifact.storage_layout) {
         eprintln!("Storage layout is empty.");
         Ok(())
     } else {
         let layout = artifact.storage_layout.as_ref().unwrap().clone();
-        let values = fetch_storage_slots(provider, address, &layout).await?;
-        print_storage(layout, values, pretty)
+        let values = fetch_storage_slots(provider, address.clone(), &layout).await?;
+        let zk_address = if zksync {
+            Some(
+                address
+                    .as_address()
+                    .ok_or_else(|| eyre::eyre!("Could not resolve address"))?
+                    .to_alloy(),
+            )
+        } else {
+            None
+        };
+        print_storage(layout, values, pretty, zk_address)
     }
 }
 
@@ -214,7 +264,16 @@ async fn fetch_storage_slots(
     join_all(futures).await.into_iter().map(|r| Ok(r?.to_alloy())).collect()
 }
 
-fn print_storage(layout: StorageLayout, values: Vec<B256>, pretty: bool) -> Result<()> {
+/// Prints the storage layout along with the values of the slots.
+///
+/// If `zk_address` is set, the zkSync storage key of each slot, derived for that address, is
+/// printed as well.
+fn print_storage(
+    layout: StorageLayout,
+    values: Vec<B256>,
+    pretty: bool,
+    zk_address: Option<Address>,
+) -> Result<()> {
     if !pretty {
         println!("{}", serde_json::to_string_pretty(&serde_json::to_value(layout)?)?);
         return Ok(())
@@ -222,23 +281,33 @@ fn print_storage(layout: StorageLayout, values: Vec<B256>, pretty: bool) -> Resu
 
     let mut table = Table::new();
     table.load_preset(ASCII_MARKDOWN);
-    table.set_header(["Name", "Type", "Slot", "Offset", "Bytes", "Value", "Hex Value", "Contract"]);
+    let mut header =
+        vec!["Name", "Type", "Slot", "Offset", "Bytes", "Value", "Hex Value", "Contract"];
+    if zk_address.is_some() {
+        header.push("zkSync Key");
+    }
+    table.set_header(header);
 
     for (slot, value) in layout.storage.into_iter().zip(values) {
         let storage_type = layout.types.get(&slot.storage_type);
         let raw_value_bytes = value.0;
         let converted_value = U256::from_be_bytes(raw_value_bytes);
 
-        table.add_row([
-            slot.label.as_str(),
-            storage_type.map_or("?", |t| &t.label),
-            &slot.slot,
-            &slot.offset.to_string(),
-            &storage_type.map_or("?", |t| &t.number_of_bytes),
-            &converted_value.to_string(),
-            &value.to_string(),
-            &slot.contract,
-        ]);
+        let mut row = vec![
+            slot.label.clone(),
+            storage_type.map_or("?", |t| &t.label).to_string(),
+            slot.slot.clone(),
+            slot.offset.to_string(),
+            storage_type.map_or("?", |t| &t.number_of_bytes).to_string(),
+            converted_value.to_string(),
+            value.to_string(),
+            slot.contract.clone(),
+        ];
+        if let Some(address) = zk_address {
+            let slot = B256::from(U256::from_str(&slot.slot)?);
+            row.push(hashed_storage_key(address, slot).to_string());
+        }
+        table.add_row(row);
     }
 
     println!("{table}");
@@ -18,6 +18,10 @@ use foundry_config::Config;
 use serde_json::json;
 use std::path::Path;
 use yansi::Paint;
+use zksync_web3_rs::{
+    eip712::{Eip712Transaction, Eip712TransactionRequest},
+    zks_utils::EIP712_TX_TYPE,
+};
 
 pub mod vanity;
 use vanity::VanityArgs;
@@ -97,15 +101,25 @@ pub enum WalletSubcommands {
         /// Use --data --from-file to denote the message is a file name containing typed data.
         /// The data will be combined and hashed using the EIP712 specification before signing.
         /// The data should be formatted as JSON.
+        ///
+        /// A zkSync transaction can be provided as a json string or a file name, in the format
+        /// accepted by `zks_estimateFee`. Use --zk-tx flag to denote the message is a zkSync
+        /// transaction. Missing fields are left to their defaults, and `from` defaults to the
+        /// signer's address. The signed transaction is printed RLP encoded, ready to be sent
+        /// with `cast publish`.
         message: String,
 
         /// If provided, the message will be treated as typed data.
-        #[clap(long)]
+        #[clap(long, group = "typed")]
         data: bool,
 
-        /// If provided, the message will be treated as a file name containing typed data. Requires
-        /// --data.
-        #[clap(long, requires = "data")]
+        /// If provided, the message will be treated as a zkSync EIP-712 transaction.
+        #[clap(long, group = "typed")]
+        zk_tx: bool,
+
+        /// If provided, the message will be treated as a file name containing typed data or a
+        /// zkSync transaction. Requires --data or --zk-tx.
+        #[clap(long, requires = "typed")]
         from_file: bool,
 
         #[clap(flatten)]
@@ -246,8 +260,23 @@ impl WalletSubcommands {
                 let addr = wallet.address();
                 println!("{}", addr.to_alloy().to_checksum(None));
             }
-            WalletSubcommands::Sign { message, data, from_file, wallet } => {
+            WalletSubcommands::Sign { message, data, zk_tx, from_file, wallet } => {
                 let wallet = wallet.signer(0).await?;
+                if zk_tx {
+                    let tx: serde_json::Value = if from_file {
+                        foundry_common::fs::read_json_file(message.as_ref())?
+                    } else {
+                        serde_json::from_str(&message)?
+                    };
+                    let tx = Self::zk_tx_request(tx, wallet.address())?;
+                    let signable: Eip712Transaction =
+                        tx.clone().try_into().wrap_err("Invalid zkSync transaction")?;
+                    let sig = wallet.sign_typed_data(&signable).await?;
+                    let encoded =
+                        tx.rlp_signed(sig).wrap_err("Failed to RLP encode transaction")?;
+                    println!("0x{}", hex::encode([&[EIP712_TX_TYPE], &*encoded].concat()));
+                    return Ok(())
+                }
                 let sig = if data {
                     let typed_data: TypedData = if from_file {
                         // data is a file name, read json from file
@@ -361,6 +390,20 @@ flag to set your key via:
         Ok(())
     }
 
+    /// Builds a zkSync EIP-712 transaction from its JSON representation, filling the missing
+    /// fields with their defaults and `from` with the signer's address.
+    fn zk_tx_request(
+        tx: serde_json::Value,
+        from: ethers_core::types::Address,
+    ) -> Result<Eip712TransactionRequest> {
+        let serde_json::Value::Object(fields) = tx else {
+            eyre::bail!("zkSync transaction must be a JSON object")
+        };
+        let mut request = serde_json::to_value(Eip712TransactionRequest::new().from(from))?;
+        request.as_object_mut().expect("transaction request is an object").extend(fields);
+        serde_json::from_value(request).wrap_err("Invalid zkSync transaction")
+    }
+
     fn hex_str_to_bytes(s: &str) -> Result<Vec<u8>> {
         Ok(match s.strip_prefix("0x") {
             Some(data) => hex::decode(data).wrap_err("Could not decode 0x-prefixed string.")?,
@@ -430,4 +473,37 @@ mod tests {
             _ => panic!("expected WalletSubcommands::Sign"),
         }
     }
+
+    #[test]
+    fn can_parse_wallet_sign_zk_tx() {
+        let args = WalletSubcommands::parse_from(["foundry-cli", "sign", "--zk-tx", "{ ... }"]);
+        match args {
+            WalletSubcommands::Sign { message, data, zk_tx, from_file, .. } => {
+                assert_eq!(message, "{ ... }".to_string());
+                assert!(!data);
+                assert!(zk_tx);
+                assert!(!from_file);
+            }
+            _ => panic!("expected WalletSubcommands::Sign"),
+        }
+    }
+
+    #[test]
+    fn zk_tx_conflicts_with_data() {
+        let args =
+            WalletSubcommands::try_parse_from(["foundry-cli", "sign", "--data", "--zk-tx", "{}"]);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn zk_tx_request_defaults_sender() {
+        let from = ethers_core::types::Address::from_low_u64_be(1);
+        let tx = WalletSubcommands::zk_tx_request(
+            json!({ "to": "0x0000000000000000000000000000000000000002" }),
+            from,
+        )
+        .unwrap();
+        assert_eq!(tx.from, from);
+        assert_eq!(tx.to, ethers_core::types::Address::from_low_u64_be(2));
+    }
 }
@@ -0,0 +1,116 @@
+use alloy_primitives::B256;
+use clap::Parser;
+use eyre::Result;
+use foundry_cli::{opts::RpcOpts, utils};
+use foundry_common::zk_utils::finality::{self, BatchStatus, FinalityStage};
+use foundry_config::Config;
+use std::time::Duration;
+
+/// CLI arguments for `cast zk-batch-status`.
+#[derive(Debug, Clone, Parser)]
+pub struct ZkBatchStatusArgs {
+    /// The hash of the L2 transaction.
+    #[clap(value_name = "TX_HASH", required_unless_present = "l1_batch")]
+    tx_hash: Option<B256>,
+
+    /// Query the L1 batch with this number instead of the batch of a transaction.
+    #[clap(long, conflicts_with = "tx_hash")]
+    l1_batch: Option<u32>,
+
+    /// Wait until the batch reaches this stage, failing if it doesn't before the timeout.
+    #[clap(long, value_enum, value_name = "STAGE")]
+    wait_for: Option<FinalityStage>,
+
+    /// How long to wait for the stage, in seconds.
+    #[clap(long, default_value = "3600", requires = "wait_for")]
+    timeout: u64,
+
+    /// How often to poll the status while waiting, in seconds.
+    #[clap(long, default_value = "10", requires = "wait_for")]
+    interval: u64,
+
+    /// Print as JSON.
+    #[clap(long, short, help_heading = "Display options")]
+    json: bool,
+
+    #[clap(flatten)]
+    rpc: RpcOpts,
+}
+
+impl ZkBatchStatusArgs {
+    pub async fn run(self) -> Result<()> {
+        let ZkBatchStatusArgs { tx_hash, l1_batch, wait_for, timeout, interval, json, rpc } = self;
+
+        let config = Config::from(&rpc);
+        let provider = &utils::get_provider(&config)?;
+        let status = move || async move {
+            match (tx_hash, l1_batch) {
+                (Some(tx_hash), _) => finality::transaction_status(provider, tx_hash).await,
+                (None, Some(l1_batch)) => finality::batch_status(provider, l1_batch).await,
+                (None, None) => unreachable!("clap requires a transaction or a batch"),
+            }
+        };
+
+        let status = match wait_for {
+            Some(stage) => {
+                finality::wait_for_stage(
+                    stage,
+                    Duration::from_secs(timeout),
+                    Duration::from_secs(interval),
+                    status,
+                )
+                .await?
+            }
+            None => status().await?,
+        };
+
+        if json {
+            println!("{}", serde_json::to_string_pretty(&status)?);
+        } else {
+            print_status(&status);
+        }
+        Ok(())
+    }
+}
+
+fn print_status(status: &BatchStatus) {
+    let hash = |hash: Option<B256>| hash.map_or_else(|| "-".to_string(), |hash| hash.to_string());
+    if let Some(tx_hash) = status.tx_hash {
+        println!("transaction      {tx_hash}");
+    }
+    println!(
+        "l1 batch         {}",
+        status.l1_batch_number.map_or_else(|| "-".to_string(), |number| number.to_string())
+    );
+    println!("stage            {}", status.stage);
+    println!("commit tx        {}", hash(status.commit_tx_hash));
+    println!("prove tx         {}", hash(status.prove_tx_hash));
+    println!("execute tx       {}", hash(status.execute_tx_hash));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requires_transaction_or_batch() {
+        assert!(ZkBatchStatusArgs::try_parse_from(["foundry-cli"]).is_err());
+        assert!(ZkBatchStatusArgs::try_parse_from(["foundry-cli", "--l1-batch", "1"]).is_ok());
+        assert!(ZkBatchStatusArgs::try_parse_from([
+            "foundry-cli",
+            &B256::repeat_byte(1).to_string(),
+            "--l1-batch",
+            "1"
+        ])
+        .is_err());
+
+        let args = ZkBatchStatusArgs::parse_from([
+            "foundry-cli",
+            &B256::repeat_byte(1).to_string(),
+            "--wait-for",
+            "proven",
+        ]);
+        assert_eq!(args.wait_for, Some(FinalityStage::Proven));
+        assert_eq!(args.timeout, 3600);
+    }
+}
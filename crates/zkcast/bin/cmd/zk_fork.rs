@@ -0,0 +1,251 @@
+//! Local execution of calls in an in-process zkSync Era VM running over a fork of the RPC, used by
+//! `cast call --zksync` and `cast estimate --zksync`.
+
+use alloy_primitives::{Address, Bytes, U256};
+use clap::Parser;
+use eyre::{Context, Result};
+use foundry_cli::utils::parse_ether_value;
+use foundry_common::{
+    conversion_utils::{address_to_h160, revm_u256_to_h256, revm_u256_to_u256},
+    ZkStateOverrides,
+};
+use foundry_config::{find_project_root_path, Config};
+use foundry_evm::{
+    executors::{RawCallResult, TracingExecutor},
+    opts::EvmOpts,
+};
+use std::str::FromStr;
+
+/// Arguments to execute a call in a local zkSync Era VM over a fork, optionally overriding the
+/// forked state before the call.
+#[derive(Clone, Debug, Default, Parser)]
+#[clap(next_help_heading = "zkSync fork options")]
+pub struct ZkForkArgs {
+    /// Execute the call in a local zkSync Era VM running over a fork of the RPC.
+    #[clap(long)]
+    pub zksync: bool,
+
+    /// Override the balance of an account before the call.
+    ///
+    /// Format: `address:balance`, where the balance is either in wei or a string with a unit
+    /// type, e.g. `1ether`.
+    #[clap(long, requires = "zksync", value_name = "ADDRESS:BALANCE")]
+    pub override_balance: Vec<BalanceOverride>,
+
+    /// Override the nonce of an account before the call.
+    ///
+    /// Format: `address:nonce`
+    #[clap(long, requires = "zksync", value_name = "ADDRESS:NONCE")]
+    pub override_nonce: Vec<NonceOverride>,
+
+    /// Override the EraVM bytecode of an account before the call.
+    ///
+    /// Format: `address:bytecode`
+    #[clap(long, requires = "zksync", value_name = "ADDRESS:CODE")]
+    pub override_code: Vec<CodeOverride>,
+
+    /// Override a storage slot of an account before the call.
+    ///
+    /// Format: `address:slot:value`
+    #[clap(long, requires = "zksync", value_name = "ADDRESS:SLOT:VALUE")]
+    pub override_state: Vec<StateOverride>,
+}
+
+impl ZkForkArgs {
+    /// Spawns an executor over a fork of the configured RPC, with all the overrides applied.
+    ///
+    /// The overrides are layered on top of the state read by the zkEVM, so they don't end up in
+    /// the fork cache.
+    pub async fn executor(&self, config: &Config) -> Result<TracingExecutor> {
+        let evm_opts =
+            Config::figment_with_root(find_project_root_path(None)?).extract::<EvmOpts>()?;
+
+        let (env, fork, _chain) = TracingExecutor::get_fork_material(config, evm_opts).await?;
+        let mut executor = TracingExecutor::new(env, fork, None, false).await;
+        executor.set_zk_state_overrides(self.state_overrides());
+
+        Ok(executor)
+    }
+
+    /// Collects the overrides into the layer applied on top of the forked state.
+    pub fn state_overrides(&self) -> ZkStateOverrides {
+        let mut overrides = ZkStateOverrides::default();
+        for BalanceOverride { address, balance } in &self.override_balance {
+            overrides.set_balance(address_to_h160(*address), revm_u256_to_u256(*balance));
+        }
+        for NonceOverride { address, nonce } in &self.override_nonce {
+            overrides.set_nonce(address_to_h160(*address), (*nonce).into());
+        }
+        for CodeOverride { address, code } in &self.override_code {
+            overrides.set_code(address_to_h160(*address), code.to_vec());
+        }
+        for StateOverride { address, slot, value } in &self.override_state {
+            overrides.set_storage(
+                address_to_h160(*address),
+                revm_u256_to_h256(*slot),
+                revm_u256_to_h256(*value),
+            );
+        }
+        overrides
+    }
+
+    /// Executes the call in the forked VM, without committing its changes.
+    ///
+    /// The gas limit is capped to what `from` can pay for, so it needs a balance, which can be
+    /// set with `--override-balance`.
+    pub async fn call(
+        &self,
+        config: &Config,
+        from: Address,
+        to: Address,
+        data: Bytes,
+        value: U256,
+    ) -> Result<RawCallResult> {
+        let mut executor = self.executor(config).await?;
+        executor.env.tx.caller = from;
+        executor.env.tx.value = value;
+        executor.adjust_zksync_gas_parameters();
+
+        let result = executor.call_raw(from, to, data, value)?;
+        if result.reverted {
+            eyre::bail!("execution reverted: {}", result.result);
+        }
+        Ok(result)
+    }
+}
+
+/// Splits an override into its address and the remaining value.
+fn split_override(s: &str) -> Result<(Address, &str)> {
+    let (address, value) =
+        s.split_once(':').ok_or_else(|| eyre::eyre!("expected `address:value`, got `{s}`"))?;
+    let address = Address::from_str(address).wrap_err("invalid address")?;
+    Ok((address, value))
+}
+
+/// A balance override, in the form `address:balance`.
+#[derive(Clone, Debug)]
+pub struct BalanceOverride {
+    pub address: Address,
+    pub balance: U256,
+}
+
+impl FromStr for BalanceOverride {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (address, balance) = split_override(s)?;
+        Ok(Self { address, balance: parse_ether_value(balance)? })
+    }
+}
+
+/// A nonce override, in the form `address:nonce`.
+#[derive(Clone, Debug)]
+pub struct NonceOverride {
+    pub address: Address,
+    pub nonce: u64,
+}
+
+impl FromStr for NonceOverride {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (address, nonce) = split_override(s)?;
+        Ok(Self { address, nonce: nonce.parse().wrap_err("invalid nonce")? })
+    }
+}
+
+/// A bytecode override, in the form `address:bytecode`.
+#[derive(Clone, Debug)]
+pub struct CodeOverride {
+    pub address: Address,
+    pub code: Bytes,
+}
+
+impl FromStr for CodeOverride {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (address, code) = split_override(s)?;
+        let code = Bytes::from_str(code).wrap_err("invalid bytecode")?;
+        // EraVM bytecode is made of 32 byte words, and must have an odd number of them.
+        if code.len() % 32 != 0 || (code.len() / 32) % 2 == 0 {
+            eyre::bail!("invalid EraVM bytecode length: {}", code.len());
+        }
+        Ok(Self { address, code })
+    }
+}
+
+/// A storage override, in the form `address:slot:value`.
+#[derive(Clone, Debug)]
+pub struct StateOverride {
+    pub address: Address,
+    pub slot: U256,
+    pub value: U256,
+}
+
+impl FromStr for StateOverride {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (address, rest) = split_override(s)?;
+        let (slot, value) = rest
+            .split_once(':')
+            .ok_or_else(|| eyre::eyre!("expected `address:slot:value`, got `{s}`"))?;
+        Ok(Self {
+            address,
+            slot: U256::from_str(slot).wrap_err("invalid slot")?,
+            value: U256::from_str(value).wrap_err("invalid value")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_common::conversion_utils::u256_to_revm_u256;
+
+    #[test]
+    fn can_parse_overrides() {
+        let args = ZkForkArgs::parse_from([
+            "foundry-cli",
+            "--zksync",
+            "--override-balance",
+            "0x0000000000000000000000000000000000000001:1ether",
+            "--override-nonce",
+            "0x0000000000000000000000000000000000000001:7",
+            "--override-state",
+            "0x0000000000000000000000000000000000000002:0x1:42",
+        ]);
+        assert!(args.zksync);
+        assert_eq!(args.override_balance[0].balance, U256::from(10).pow(U256::from(18)));
+        assert_eq!(args.override_nonce[0].nonce, 7);
+        assert_eq!(args.override_state[0].slot, U256::from(1));
+        assert_eq!(args.override_state[0].value, U256::from(42));
+    }
+
+    #[test]
+    fn collects_state_overrides() {
+        let args = ZkForkArgs::parse_from(["foundry-cli", "--zksync"]);
+        assert!(args.state_overrides().is_empty());
+
+        let args = ZkForkArgs::parse_from([
+            "foundry-cli",
+            "--zksync",
+            "--override-balance",
+            "0x0000000000000000000000000000000000000001:1ether",
+        ]);
+        let account = address_to_h160(Address::with_last_byte(1));
+        let balance = args.state_overrides().balance(account).map(u256_to_revm_u256);
+        assert_eq!(balance, Some(U256::from(10).pow(U256::from(18))));
+    }
+
+    #[test]
+    fn overrides_require_zksync() {
+        let args = ZkForkArgs::try_parse_from([
+            "foundry-cli",
+            "--override-nonce",
+            "0x0000000000000000000000000000000000000001:7",
+        ]);
+        assert!(args.is_err());
+    }
+}
@@ -0,0 +1,67 @@
+//! Storage proofs of zkSync accounts, fetched with `zks_getProof` and verified against the root
+//! hash of the L1 batch they were generated for.
+
+use alloy_primitives::{Address, B256};
+use ethers_core::types::U64;
+use eyre::{Context, Result};
+use foundry_common::{zk_utils::proof::ZkAccountProof, RetryProvider};
+use serde::Deserialize;
+
+/// The subset of `zks_getL1BatchDetails` needed to verify proofs.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct L1BatchDetails {
+    root_hash: Option<B256>,
+}
+
+/// Fetches the proofs of `slots` of `address` in the given L1 batch, or the latest sealed one,
+/// and verifies them against the root hash of the batch.
+///
+/// Fails if any of the proofs doesn't match the root hash.
+pub async fn fetch_and_verify(
+    provider: &RetryProvider,
+    address: Address,
+    slots: Vec<B256>,
+    l1_batch: Option<u32>,
+) -> Result<ZkAccountProof> {
+    let l1_batch = match l1_batch {
+        Some(l1_batch) => l1_batch,
+        None => {
+            let latest: U64 = provider
+                .request("zks_L1BatchNumber", ())
+                .await
+                .wrap_err("Failed to fetch the latest L1 batch number")?;
+            latest.as_u32()
+        }
+    };
+
+    let details: Option<L1BatchDetails> = provider
+        .request("zks_getL1BatchDetails", [l1_batch])
+        .await
+        .wrap_err_with(|| format!("Failed to fetch the details of L1 batch {l1_batch}"))?;
+    let root_hash = details
+        .ok_or_else(|| eyre::eyre!("L1 batch {l1_batch} not found"))?
+        .root_hash
+        .ok_or_else(|| eyre::eyre!("Root hash of L1 batch {l1_batch} is not computed yet"))?;
+
+    let proof: Option<ZkAccountProof> = provider
+        .request("zks_getProof", (address, slots, l1_batch))
+        .await
+        .wrap_err("Failed to fetch the storage proof")?;
+    let proof = proof.ok_or_else(|| eyre::eyre!("No proof available for L1 batch {l1_batch}"))?;
+
+    for storage_proof in &proof.storage_proof {
+        if !storage_proof.verify(address, root_hash) {
+            eyre::bail!(
+                "Invalid proof for slot {} against the root hash {root_hash} of L1 batch {l1_batch}",
+                storage_proof.key
+            );
+        }
+    }
+    eprintln!(
+        "Verified {} slot(s) against the root hash {root_hash} of L1 batch {l1_batch}",
+        proof.storage_proof.len()
+    );
+
+    Ok(proof)
+}
@@ -293,9 +293,19 @@ async fn main() -> Result<()> {
             let provider = utils::get_provider(&config)?;
             println!("{}", Cast::new(provider).nonce(who, block).await?);
         }
-        Subcommands::Proof { address, slots, rpc, block } => {
+        Subcommands::Proof { address, slots, rpc, block, zksync, l1_batch } => {
             let config = Config::from(&rpc);
             let provider = utils::get_provider(&config)?;
+            if zksync {
+                let address = address
+                    .as_address()
+                    .ok_or_else(|| eyre::eyre!("Could not resolve address"))?
+                    .to_alloy();
+                let proof =
+                    cmd::zk_proof::fetch_and_verify(&provider, address, slots, l1_batch).await?;
+                println!("{}", serde_json::to_string(&proof)?);
+                return Ok(())
+            }
             let value = provider
                 .get_proof(address, slots.into_iter().map(|s| s.to_ethers()).collect(), block)
                 .await?;
@@ -346,6 +356,7 @@ async fn main() -> Result<()> {
 
         Subcommands::ZkSendTx(cmd) => cmd.run().await?,
         Subcommands::ZkDepositTx(cmd) => cmd.run().await?,
+        Subcommands::ZkBatchStatus(cmd) => cmd.run().await?,
 
         // 4Byte
         Subcommands::FourByte { selector } => {
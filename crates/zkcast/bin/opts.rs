@@ -2,7 +2,7 @@ use crate::cmd::{
     access_list::AccessListArgs, bind::BindArgs, call::CallArgs, create2::Create2Args,
     estimate::EstimateArgs, find_block::FindBlockArgs, interface::InterfaceArgs, logs::LogsArgs,
     rpc::RpcArgs, run::RunArgs, send::SendTxArgs, storage::StorageArgs, wallet::WalletSubcommands,
-    zk_deposit::ZkDepositTxArgs, zk_send::ZkSendTxArgs,
+    zk_batch_status::ZkBatchStatusArgs, zk_deposit::ZkDepositTxArgs, zk_send::ZkSendTxArgs,
 };
 use alloy_primitives::{Address, B256, U256};
 use clap::{Parser, Subcommand, ValueHint};
@@ -448,6 +448,11 @@ pub enum Subcommands {
     #[clap(about = "Bridge Assets from L1 to L2.")]
     ZkDepositTx(ZkDepositTxArgs),
 
+    /// Get whether a transaction, or an L1 batch, is committed, proven and executed on L1.
+    #[clap(name = "zk-batch-status")]
+    #[clap(visible_aliases = ["zkbs", "zkbatchstatus"])]
+    ZkBatchStatus(ZkBatchStatusArgs),
+
     /// Publish a raw transaction to the network.
     #[clap(name = "publish", visible_alias = "p")]
     PublishTx {
@@ -756,9 +761,20 @@ pub enum Subcommands {
         /// The block height to query at.
         ///
         /// Can also be the tags earliest, finalized, safe, latest, or pending.
-        #[clap(long, short = 'B')]
+        #[clap(long, short = 'B', conflicts_with = "zksync")]
         block: Option<BlockId>,
 
+        /// Fetch a zkSync Merkle proof with `zks_getProof`, and verify it against the root hash
+        /// of the L1 batch.
+        #[clap(long)]
+        zksync: bool,
+
+        /// The L1 batch to generate the zkSync proof for.
+        ///
+        /// Defaults to the latest sealed batch.
+        #[clap(long, requires = "zksync")]
+        l1_batch: Option<u32>,
+
         #[clap(flatten)]
         rpc: RpcOpts,
     },
@@ -1,10 +1,23 @@
+use alloy_json_abi::JsonAbi;
 use clap::{Parser, ValueHint};
 use ethers_contract::{Abigen, ContractFilter, ExcludeContracts, MultiAbigen, SelectContracts};
 use eyre::{Result, WrapErr};
-use foundry_cli::{opts::CoreBuildArgs, utils::LoadConfig};
-use foundry_common::{compile, fs::json_files};
+use foundry_cli::{
+    opts::CoreBuildArgs,
+    utils::{self, LoadConfig},
+};
+use foundry_common::{
+    compile,
+    fs::json_files,
+    zk_compile::ZkSolc,
+    zk_utils::factory_deps::PackedEraBytecode,
+    zksolc_manager::{setup_zksolc_manager, DEFAULT_ZKSOLC_VERSION},
+};
+use foundry_compilers::Artifact;
 use foundry_config::impl_figment_convert;
 use std::{
+    collections::BTreeMap,
+    fmt::Write,
     fs,
     path::{Path, PathBuf},
 };
@@ -77,12 +90,25 @@ pub struct BindArgs {
     #[clap(long)]
     skip_build: bool,
 
+    /// Compile the contracts with zksolc, and generate their zkSync deployment metadata along
+    /// with the bindings.
+    ///
+    /// The bytecode hash, bytecode and factory dependencies of each contract are exported in a
+    /// `zksync` Rust module and a `zksync.ts` file, so that deploy transactions can be built
+    /// without reading the artifacts.
+    #[clap(long, conflicts_with = "skip_build")]
+    zksync: bool,
+
     #[clap(flatten)]
     build_args: CoreBuildArgs,
 }
 
 impl BindArgs {
     pub fn run(self) -> Result<()> {
+        if self.zksync {
+            return self.run_zksync()
+        }
+
         if !self.skip_build {
             // run `forge build`
             let project = self.build_args.project()?;
@@ -207,20 +233,237 @@ No contract artifacts found. Hint: Have you built your contracts yet? `forge bin
 
     /// Generate the bindings
     fn generate_bindings(&self, artifacts: impl AsRef<Path>) -> Result<()> {
-        let bindings = self.get_multi(&artifacts)?.build()?;
+        self.write_bindings(self.get_multi(&artifacts)?, &self.bindings_root(&artifacts))
+    }
+
+    /// Write the bindings of the multi-abigen to `root`
+    fn write_bindings(&self, multi: MultiAbigen, root: &Path) -> Result<()> {
+        let bindings = multi.build()?;
         println!("Generating bindings for {} contracts", bindings.len());
         if !self.module {
             trace!(single_file = self.single_file, "generating crate");
             bindings.dependencies([r#"serde = "1""#]).write_to_crate(
                 &self.crate_name,
                 &self.crate_version,
-                self.bindings_root(&artifacts),
+                root,
                 self.single_file,
             )?;
         } else {
             trace!(single_file = self.single_file, "generating module");
-            bindings.write_to_module(self.bindings_root(&artifacts), self.single_file)?;
+            bindings.write_to_module(root, self.single_file)?;
+        }
+        Ok(())
+    }
+
+    /// Compile the contracts with zksolc and generate their bindings, along with their zkSync
+    /// deployment metadata
+    fn run_zksync(self) -> Result<()> {
+        let config = self.try_load_config_emit_warnings()?;
+        let mut project = config.project()?;
+        let artifacts = project.paths.root.join("zkout");
+        project.paths.artifacts = artifacts.clone();
+
+        let mut zksolc_cfg = config.zk_solc_config().map_err(|e| eyre::eyre!(e))?;
+        zksolc_cfg.compiler_path =
+            utils::block_on(setup_zksolc_manager(DEFAULT_ZKSOLC_VERSION.to_owned()))?;
+        let (output, _) = ZkSolc::new(zksolc_cfg, project)
+            .compile()
+            .map_err(|e| eyre::eyre!("Failed to compile with zksolc: {}", e))?;
+
+        let filter = self.get_filter();
+        let mut contracts = BTreeMap::new();
+        for (name, artifact) in output.artifacts() {
+            if !filter.is_match(&name) {
+                continue
+            }
+            let (Some(abi), Some(bytecode)) = (&artifact.abi, artifact.get_bytecode_bytes()) else {
+                continue
+            };
+            contracts.insert(
+                name.clone(),
+                ZkBindingContract::new(name, abi.clone(), &PackedEraBytecode::from_vec(&bytecode)),
+            );
         }
+        eyre::ensure!(!contracts.is_empty(), "No contract artifacts found.");
+        let contracts = contracts.into_values().collect::<Vec<_>>();
+
+        let root = self.bindings_root(&artifacts);
+        if !self.overwrite && root.is_dir() {
+            println!("Bindings found. Checking for consistency.");
+            let expected = tempfile::tempdir()?;
+            self.write_zk_bindings(&contracts, expected.path())?;
+            ensure_consistent_dir(expected.path(), &root)?;
+            println!("OK.");
+            return Ok(())
+        }
+
+        if self.overwrite && root.is_dir() {
+            trace!(?root, "Removing existing bindings");
+            fs::remove_dir_all(&root)?;
+        }
+
+        self.write_zk_bindings(&contracts, &root)?;
+
+        println!("Bindings have been output to {}", root.to_str().unwrap());
         Ok(())
     }
+
+    /// Write the bindings of the zksolc compiled contracts to `root`, along with their zkSync
+    /// deployment metadata
+    fn write_zk_bindings(&self, contracts: &[ZkBindingContract], root: &Path) -> Result<()> {
+        let abigens = contracts
+            .iter()
+            .map(|contract| {
+                Abigen::new(&contract.name, serde_json::to_string(&contract.abi)?)?
+                    .add_derive("serde::Serialize")?
+                    .add_derive("serde::Deserialize")
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.write_bindings(MultiAbigen::from_abigens(abigens), root)?;
+
+        // Register the metadata module next to the generated ones
+        let (src, parent_module) =
+            if self.module { (root.to_path_buf(), "mod.rs") } else { (root.join("src"), "lib.rs") };
+        fs::write(src.join("zksync.rs"), zk_rust_module(contracts)?)?;
+        let mut parent = fs::read_to_string(src.join(parent_module))?;
+        parent.push_str("pub mod zksync;\n");
+        fs::write(src.join(parent_module), parent)?;
+
+        fs::write(root.join("zksync.ts"), zk_ts_module(contracts)?)?;
+        Ok(())
+    }
+}
+
+/// The zkSync deployment metadata of a contract, as embedded in its bindings.
+struct ZkBindingContract {
+    name: String,
+    abi: JsonAbi,
+    bytecode_hash: String,
+    bytecode: String,
+    factory_deps: Vec<String>,
+}
+
+impl ZkBindingContract {
+    fn new(name: String, abi: JsonAbi, packed: &PackedEraBytecode) -> Self {
+        let mut factory_deps = packed.factory_deps();
+        // The contract's own bytecode is the last of its factory dependencies
+        factory_deps.pop();
+        Self {
+            name,
+            abi,
+            bytecode_hash: format!("{:?}", packed.bytecode_hash()),
+            bytecode: hex::encode_prefixed(packed.bytecode()),
+            factory_deps: factory_deps.into_iter().map(hex::encode_prefixed).collect(),
+        }
+    }
+}
+
+/// Generates the Rust module exporting the zkSync deployment metadata of the contracts.
+fn zk_rust_module(contracts: &[ZkBindingContract]) -> Result<String> {
+    let mut out = String::from(
+        r#"//! zkSync deployment metadata of the contracts, generated by `forge bind --zksync`.
+
+/// The zkSync deployment metadata of a contract.
+#[derive(Clone, Copy, Debug)]
+pub struct ZkContract {
+    /// The hash of the EraVM bytecode, which identifies the contract when deploying it.
+    pub bytecode_hash: &'static str,
+    /// The EraVM bytecode, published as a factory dependency when deploying the contract.
+    pub bytecode: &'static str,
+    /// The bytecodes of the contracts deployed by this contract, which must be published along
+    /// with it.
+    pub factory_deps: &'static [&'static str],
+}
+"#,
+    );
+    for contract in contracts {
+        writeln!(out)?;
+        writeln!(out, "/// The zkSync deployment metadata of `{}`.", contract.name)?;
+        writeln!(
+            out,
+            "pub const {}: ZkContract = ZkContract {{",
+            screaming_snake_case(&contract.name)
+        )?;
+        writeln!(out, "    bytecode_hash: \"{}\",", contract.bytecode_hash)?;
+        writeln!(out, "    bytecode: \"{}\",", contract.bytecode)?;
+        writeln!(out, "    factory_deps: &[")?;
+        for dep in &contract.factory_deps {
+            writeln!(out, "        \"{dep}\",")?;
+        }
+        writeln!(out, "    ],")?;
+        writeln!(out, "}};")?;
+    }
+    Ok(out)
+}
+
+/// Generates the TypeScript module exporting the ABI and zkSync deployment metadata of the
+/// contracts.
+fn zk_ts_module(contracts: &[ZkBindingContract]) -> Result<String> {
+    let mut out = String::from(
+        "// zkSync deployment metadata of the contracts, generated by `forge bind --zksync`.\n",
+    );
+    for contract in contracts {
+        writeln!(out)?;
+        writeln!(out, "export const {} = {{", contract.name)?;
+        writeln!(out, "  abi: {},", serde_json::to_string(&contract.abi)?)?;
+        writeln!(out, "  bytecodeHash: \"{}\",", contract.bytecode_hash)?;
+        writeln!(out, "  bytecode: \"{}\",", contract.bytecode)?;
+        writeln!(out, "  factoryDeps: {},", serde_json::to_string(&contract.factory_deps)?)?;
+        writeln!(out, "}} as const;")?;
+    }
+    Ok(out)
+}
+
+/// Converts a contract name to the name of a Rust constant, e.g. `ERC20Token` to `ERC20_TOKEN`.
+fn screaming_snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut out = String::with_capacity(name.len());
+    for (i, c) in chars.iter().enumerate() {
+        if i > 0 && c.is_uppercase() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).map_or(false, |next| next.is_lowercase());
+            if prev.is_lowercase() ||
+                prev.is_ascii_digit() ||
+                (prev.is_uppercase() && next_is_lower)
+            {
+                out.push('_');
+            }
+        }
+        out.extend(c.to_uppercase());
+    }
+    out
+}
+
+/// Ensures that all the files in `expected` exist in `actual` with the same content.
+fn ensure_consistent_dir(expected: &Path, actual: &Path) -> Result<()> {
+    for entry in fs::read_dir(expected)? {
+        let entry = entry?;
+        let actual = actual.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            ensure_consistent_dir(&entry.path(), &actual)?;
+        } else {
+            let expected_content = fs::read_to_string(entry.path())?;
+            let actual_content = fs::read_to_string(&actual)
+                .wrap_err_with(|| format!("Missing binding file: {}", actual.display()))?;
+            eyre::ensure!(
+                expected_content == actual_content,
+                "Binding file {} is out of date, run with --overwrite to regenerate it",
+                actual.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_contract_names_to_constants() {
+        assert_eq!(screaming_snake_case("Counter"), "COUNTER");
+        assert_eq!(screaming_snake_case("ERC20Token"), "ERC20_TOKEN");
+        assert_eq!(screaming_snake_case("MyERC721"), "MY_ERC721");
+        assert_eq!(screaming_snake_case("IUniswapV2Pair"), "I_UNISWAP_V2_PAIR");
+    }
 }
@@ -54,6 +54,7 @@ pub mod generate;
 pub mod init;
 pub mod inspect;
 pub mod install;
+pub mod mutate;
 pub mod remappings;
 pub mod remove;
 pub mod retry;
@@ -65,5 +66,6 @@ pub mod tree;
 pub mod update;
 pub mod verify;
 pub mod watch;
+pub mod zk;
 pub mod zk_build;
 pub mod zk_create;
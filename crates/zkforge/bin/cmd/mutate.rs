@@ -0,0 +1,128 @@
+use super::test;
+use clap::{Parser, ValueHint};
+use eyre::Result;
+use foundry_cli::utils::LoadConfig;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use yansi::Paint;
+use zkforge::mutate::{Mutant, MutantStatus};
+
+/// CLI arguments for `forge mutate`.
+#[derive(Debug, Clone, Parser)]
+pub struct MutateArgs {
+    /// The source files or directories to mutate.
+    ///
+    /// By default the sources of the project are mutated.
+    #[clap(value_hint = ValueHint::FilePath, value_name = "PATH", num_args(1..))]
+    paths: Vec<PathBuf>,
+
+    /// Exit with a non-zero code if any mutant survives.
+    #[clap(long)]
+    check: bool,
+
+    #[clap(flatten)]
+    test: test::TestArgs,
+}
+
+impl MutateArgs {
+    /// Runs the zk test suite once per mutant of the sources, stopping at the first failing test.
+    ///
+    /// The suite must pass before mutating. The state after `setUp()` cached by this first run is
+    /// reused by the runs of the mutants which don't change it.
+    pub async fn run(mut self) -> Result<()> {
+        let config = self.test.load_config();
+        let paths = if self.paths.is_empty() { vec![config.src.clone()] } else { self.paths };
+        let sources = paths
+            .iter()
+            .flat_map(|path| {
+                if path.is_dir() {
+                    foundry_compilers::utils::source_files_iter(path).collect()
+                } else {
+                    vec![path.clone()]
+                }
+            })
+            .collect::<Vec<_>>();
+
+        self.test.fail_fast = true;
+        self.test.setup_fixtures = true;
+
+        let baseline = self.test.clone().execute_tests().await?;
+        if baseline.failures().count() > 0 {
+            eyre::bail!("the tests must pass before mutating the sources");
+        }
+
+        let mut survived = vec![];
+        let (mut killed, mut invalid) = (0, 0);
+        for path in sources {
+            let source = fs::read_to_string(&path)?;
+            let _restore = RestoreSource { path: &path, source: &source };
+            for mutant in Mutant::generate(&path, &source) {
+                fs::write(&path, mutant.apply(&source))?;
+                let status = match self.test.clone().execute_tests().await {
+                    Ok(outcome) if outcome.failures().count() > 0 => MutantStatus::Killed,
+                    Ok(_) => MutantStatus::Survived,
+                    Err(err) => {
+                        debug!(%mutant, %err, "mutant failed to compile");
+                        MutantStatus::Invalid
+                    }
+                };
+                match status {
+                    MutantStatus::Killed => killed += 1,
+                    MutantStatus::Survived => survived.push(mutant.clone()),
+                    MutantStatus::Invalid => invalid += 1,
+                }
+                println!("{mutant}: {status}");
+            }
+        }
+
+        println!();
+        for mutant in &survived {
+            println!("{} {mutant}", Paint::red("Survived:").bold());
+        }
+        let valid = killed + survived.len();
+        println!(
+            "{killed} killed, {} survived, {invalid} invalid, mutation score: {:.2}%",
+            survived.len(),
+            if valid == 0 { 100.0 } else { killed as f64 * 100.0 / valid as f64 }
+        );
+
+        if self.check && !survived.is_empty() {
+            std::process::exit(1);
+        }
+        Ok(())
+    }
+}
+
+/// Restores a mutated source file once its mutants ran, even if running them failed.
+struct RestoreSource<'a> {
+    path: &'a Path,
+    source: &'a str,
+}
+
+impl Drop for RestoreSource<'_> {
+    fn drop(&mut self) {
+        if let Err(err) = fs::write(self.path, self.source) {
+            error!(path = ?self.path, %err, "failed to restore the mutated source");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_paths_and_test_args() {
+        let args = MutateArgs::parse_from([
+            "foundry-cli",
+            "src/Counter.sol",
+            "--check",
+            "--mt",
+            "testIncrement",
+        ]);
+        assert_eq!(args.paths, [PathBuf::from("src/Counter.sol")]);
+        assert!(args.check);
+    }
+}
@@ -1,9 +1,20 @@
 use super::{
-    multi::MultiChainSequence, providers::ProvidersManager, receipts::clear_pendings,
-    sequence::ScriptSequence, transaction::TransactionWithMetadata, verify::VerifyBundle, *,
+    chain::BroadcastChains,
+    fees,
+    multi::MultiChainSequence,
+    nonces::{self, NonceIssue},
+    plan::ScriptPlan,
+    prediction,
+    providers::ProvidersManager,
+    receipts::clear_pendings,
+    relayer::{Relayer, RelayerTransaction},
+    sequence::ScriptSequence,
+    transaction::TransactionWithMetadata,
+    verify::VerifyBundle,
+    *,
 };
 use ethers_core::{types::TxHash, utils::format_units};
-use ethers_providers::{JsonRpcClient, Middleware, Provider};
+use ethers_providers::{is_local_endpoint, JsonRpcClient, Middleware, Provider};
 use ethers_signers::Signer;
 use eyre::{bail, ContextCompat, Result, WrapErr};
 use foundry_cli::{
@@ -12,13 +23,19 @@ use foundry_cli::{
     update_progress,
     utils::{has_batch_support, has_different_gas_calc},
 };
-use foundry_common::{estimate_eip1559_fees, shell, try_get_http_provider, RetryProvider};
+use foundry_common::{estimate_eip1559_fees, fs, shell, try_get_http_provider, RetryProvider};
+use foundry_evm::inspectors::cheatcodes::FeeOverrides;
 use futures::StreamExt;
-use std::{cmp::min, collections::HashSet, ops::Mul, sync::Arc};
-use zksync_web3_rs::{
-    eip712::{Eip712Meta, Eip712Transaction, Eip712TransactionRequest},
-    zks_utils::EIP712_TX_TYPE,
+use indicatif::ProgressBar;
+use std::{
+    cmp::min,
+    collections::{BTreeMap, HashSet},
+    ops::{Mul, Range},
+    path::Path,
+    sync::Arc,
+    time::Duration,
 };
+use zksync_web3_rs::{eip712::Eip712Transaction, zks_utils::EIP712_TX_TYPE};
 
 impl ScriptArgs {
     /// Sends the transactions which haven't been broadcasted yet.
@@ -32,6 +49,8 @@ impl ScriptArgs {
         let already_broadcasted = deployment_sequence.receipts.len();
 
         if already_broadcasted < deployment_sequence.transactions.len() {
+            self.check_nonces(deployment_sequence, already_broadcasted, &provider).await?;
+
             let required_addresses: HashSet<Address> = deployment_sequence
                 .typed_transactions()
                 .into_iter()
@@ -53,21 +72,51 @@ impl ScriptArgs {
                         .filter_map(|(_, tx)| tx.from().copied().map(|addr| addr.to_alloy())),
                 );
                 (SendTransactionsKind::Unlocked(senders), chain.as_u64())
+            } else if let Some(url) = &self.relayer_url {
+                let chain = provider.get_chainid().await?.as_u64();
+                let relayer = Relayer::new(url.clone(), self.relayer_api_key.clone());
+                (SendTransactionsKind::Relayer { relayer, senders: required_addresses }, chain)
+            } else if is_local_endpoint(fork_url) {
+                // A local node can send the transactions of senders without a known key by
+                // impersonating them.
+                let (local_wallets, impersonated) = self
+                    .wallets
+                    .find_available(provider.clone(), required_addresses, script_wallets)
+                    .await?;
+                impersonate_accounts(&provider, &impersonated).await?;
+                let chain = provider.get_chainid().await?.as_u64();
+                (SendTransactionsKind::Raw { wallets: local_wallets, impersonated }, chain)
             } else {
                 let local_wallets = self
                     .wallets
                     .find_all(provider.clone(), required_addresses, script_wallets)
                     .await?;
                 let chain = local_wallets.values().last().wrap_err("Error accessing local wallet when trying to send onchain transaction, did you set a private key, mnemonic or keystore?")?.chain_id();
-                (SendTransactionsKind::Raw(local_wallets), chain)
+                (
+                    SendTransactionsKind::Raw {
+                        wallets: local_wallets,
+                        impersonated: HashSet::new(),
+                    },
+                    chain,
+                )
             };
 
+            self.check_chains(deployment_sequence, chain, &provider).await?;
+
             // We only wait for a transaction receipt before sending the next transaction, if there
             // is more than one signer. There would be no way of assuring their order
             // otherwise. Or if the chain does not support batched transactions (eg. Arbitrum).
             let sequential_broadcast =
                 send_kind.signers_count() != 1 || self.slow || !has_batch_support(chain);
 
+            // With multiple signers, transactions of independent senders can still be sent
+            // concurrently, as long as we wait for the confirmation of any deployment another
+            // sender depends on.
+            let parallel_senders = self.parallel_senders &&
+                send_kind.signers_count() > 1 &&
+                !self.slow &&
+                has_batch_support(chain);
+
             // Make a one-time gas price estimation
             let (gas_price, eip1559_fees) = {
                 match deployment_sequence.transactions.front().unwrap().typed_tx() {
@@ -91,6 +140,7 @@ impl ScriptArgs {
                 .map(|tx_with_metadata| {
                     let tx = tx_with_metadata.typed_tx();
                     let factory_deps = tx_with_metadata.factory_deps.clone();
+                    let fee_overrides = tx_with_metadata.fee_overrides.clone();
                     let from = (*tx.from().expect("No sender for onchain transaction!")).to_alloy();
 
                     let kind = send_kind.for_sender(&from)?;
@@ -122,76 +172,103 @@ impl ScriptArgs {
                         }
                     }
 
-                    Ok(((tx, factory_deps), kind, is_fixed_gas_limit))
+                    // Fees pinned by the script take precedence over the estimated ones.
+                    if let Some(gas_price) = fee_overrides.gas_price {
+                        tx.set_gas_price(gas_price);
+                    }
+                    if let (TypedTransaction::Eip1559(inner), Some(max_fee_per_gas)) =
+                        (&mut tx, fee_overrides.max_fee_per_gas)
+                    {
+                        inner.max_fee_per_gas = Some(max_fee_per_gas);
+                    }
+
+                    Ok(((tx, factory_deps, fee_overrides), kind, is_fixed_gas_limit))
                 })
                 .collect::<Result<Vec<_>>>()?;
 
             let pb = init_progress!(deployment_sequence.transactions, "txes");
 
-            // We send transactions and wait for receipts in batches of 100, since some networks
-            // cannot handle more than that.
-            let batch_size = 100;
-            let mut index = 0;
-
-            for (batch_number, batch) in sequence.chunks(batch_size).map(|f| f.to_vec()).enumerate()
-            {
-                let mut pending_transactions = vec![];
+            if parallel_senders {
+                let waves = dependency_waves(
+                    deployment_sequence.transactions.iter().skip(already_broadcasted),
+                );
+                self.send_in_waves(
+                    provider.clone(),
+                    deployment_sequence,
+                    &sequence,
+                    waves,
+                    already_broadcasted,
+                    fork_url,
+                    &pb,
+                )
+                .await?;
+            } else {
+                // We send transactions and wait for receipts in batches of 100, since some networks
+                // cannot handle more than that.
+                let batch_size = 100;
+                let mut index = 0;
 
-                shell::println(format!(
-                    "##\nSending transactions [{} - {}].",
-                    batch_number * batch_size,
-                    batch_number * batch_size + min(batch_size, batch.len()) - 1
-                ))?;
-                for (tx, kind, is_fixed_gas_limit) in batch.into_iter() {
-                    let tx_hash = self.send_transaction(
-                        provider.clone(),
-                        tx,
-                        kind,
-                        sequential_broadcast,
-                        fork_url,
-                        is_fixed_gas_limit,
-                    );
-
-                    if sequential_broadcast {
-                        let tx_hash = tx_hash.await?;
-                        deployment_sequence.add_pending(index, tx_hash.to_alloy());
-
-                        update_progress!(pb, (index + already_broadcasted));
-                        index += 1;
-
-                        clear_pendings(
+                for (batch_number, batch) in
+                    sequence.chunks(batch_size).map(|f| f.to_vec()).enumerate()
+                {
+                    let mut pending_transactions = vec![];
+
+                    shell::println(format!(
+                        "##\nSending transactions [{} - {}].",
+                        batch_number * batch_size,
+                        batch_number * batch_size + min(batch_size, batch.len()) - 1
+                    ))?;
+                    for (tx, kind, is_fixed_gas_limit) in batch.into_iter() {
+                        let tx_hash = self.send_transaction(
                             provider.clone(),
-                            deployment_sequence,
-                            Some(vec![tx_hash.to_alloy()]),
-                        )
-                        .await?;
-                    } else {
-                        pending_transactions.push(tx_hash);
+                            tx,
+                            kind,
+                            sequential_broadcast,
+                            fork_url,
+                            is_fixed_gas_limit,
+                        );
+
+                        if sequential_broadcast {
+                            let tx_hash = tx_hash.await?;
+                            deployment_sequence.add_pending(index, tx_hash.to_alloy());
+
+                            update_progress!(pb, (index + already_broadcasted));
+                            index += 1;
+
+                            clear_pendings(
+                                provider.clone(),
+                                deployment_sequence,
+                                Some(vec![tx_hash.to_alloy()]),
+                            )
+                            .await?;
+                        } else {
+                            pending_transactions.push(tx_hash);
+                        }
                     }
-                }
 
-                if !pending_transactions.is_empty() {
-                    let mut buffer = futures::stream::iter(pending_transactions).buffered(7);
+                    if !pending_transactions.is_empty() {
+                        let mut buffer = futures::stream::iter(pending_transactions).buffered(7);
+
+                        while let Some(tx_hash) = buffer.next().await {
+                            let tx_hash = tx_hash?;
+                            deployment_sequence.add_pending(index, tx_hash.to_alloy());
+
+                            update_progress!(pb, (index + already_broadcasted));
+                            index += 1;
+                        }
 
-                    while let Some(tx_hash) = buffer.next().await {
-                        let tx_hash = tx_hash?;
-                        deployment_sequence.add_pending(index, tx_hash.to_alloy());
+                        // Checkpoint save
+                        deployment_sequence.save()?;
 
-                        update_progress!(pb, (index + already_broadcasted));
-                        index += 1;
+                        if !sequential_broadcast {
+                            shell::println("##\nWaiting for receipts.")?;
+                            clear_pendings(provider.clone(), deployment_sequence, None).await?;
+                        }
                     }
 
                     // Checkpoint save
                     deployment_sequence.save()?;
-
-                    if !sequential_broadcast {
-                        shell::println("##\nWaiting for receipts.")?;
-                        clear_pendings(provider.clone(), deployment_sequence, None).await?;
-                    }
                 }
-
-                // Checkpoint save
-                deployment_sequence.save()?;
             }
         }
 
@@ -220,30 +297,225 @@ impl ScriptArgs {
         Ok(())
     }
 
+    /// Sends the transactions wave by wave. Within a wave, every sender submits its transactions
+    /// in order while all senders run concurrently. The receipts of a wave are awaited before
+    /// moving on to the next one.
+    #[allow(clippy::too_many_arguments)]
+    async fn send_in_waves(
+        &self,
+        provider: Arc<RetryProvider>,
+        deployment_sequence: &mut ScriptSequence,
+        sequence: &[(ZkTransaction, SendTransactionKind<'_>, bool)],
+        waves: Vec<Range<usize>>,
+        already_broadcasted: usize,
+        fork_url: &str,
+        pb: &ProgressBar,
+    ) -> Result<()> {
+        let mut sent = 0;
+
+        for wave in waves {
+            let mut by_sender: BTreeMap<Address, Vec<usize>> = BTreeMap::new();
+            for index in wave.clone() {
+                let from = sequence[index].0 .0.from().expect("No sender for onchain transaction!");
+                by_sender.entry((*from).to_alloy()).or_default().push(index);
+            }
+
+            shell::println(format!(
+                "##\nSending transactions [{} - {}] from {} senders in parallel.",
+                wave.start,
+                wave.end - 1,
+                by_sender.len()
+            ))?;
+
+            let futs = by_sender.into_values().map(|indexes| {
+                let provider = provider.clone();
+                async move {
+                    let mut hashes = vec![];
+                    for index in indexes {
+                        let (tx, kind, is_fixed_gas_limit) = sequence[index].clone();
+                        match self
+                            .send_transaction(
+                                provider.clone(),
+                                tx,
+                                kind,
+                                false,
+                                fork_url,
+                                is_fixed_gas_limit,
+                            )
+                            .await
+                        {
+                            Ok(tx_hash) => hashes.push((index, tx_hash)),
+                            // Later transactions of this sender would fail with a nonce gap.
+                            Err(err) => return (hashes, Some(err)),
+                        }
+                    }
+                    (hashes, None)
+                }
+            });
+
+            let mut error = None;
+            for (hashes, err) in futures::future::join_all(futs).await {
+                for (index, tx_hash) in hashes {
+                    deployment_sequence
+                        .add_pending(already_broadcasted + index, tx_hash.to_alloy());
+                    update_progress!(pb, (sent + already_broadcasted));
+                    sent += 1;
+                }
+                error = error.or(err);
+            }
+
+            // Checkpoint save
+            deployment_sequence.save()?;
+
+            if let Some(err) = error {
+                return Err(err)
+            }
+
+            shell::println("##\nWaiting for receipts.")?;
+            clear_pendings(provider.clone(), deployment_sequence, None).await?;
+
+            // Checkpoint save
+            deployment_sequence.save()?;
+        }
+
+        Ok(())
+    }
+
+    /// Makes sure the nonces of the transactions left to broadcast follow the on-chain nonces of
+    /// their senders, renumbering them if `--fix-nonces` is set.
+    async fn check_nonces(
+        &self,
+        deployment_sequence: &mut ScriptSequence,
+        from_index: usize,
+        provider: &RetryProvider,
+    ) -> Result<()> {
+        let issues = nonces::check_nonces(deployment_sequence, from_index, provider).await?;
+        if issues.is_empty() {
+            return Ok(())
+        }
+
+        let report = issues.iter().map(|issue| format!("\n  - {issue}")).collect::<String>();
+        let repairable = issues.iter().all(NonceIssue::is_repairable);
+        if !(self.fix_nonces && repairable) {
+            let hint = if repairable {
+                "Add --fix-nonces to renumber them, or --resume if they were partially broadcasted."
+            } else {
+                "Simulate the script again to update the deployment addresses."
+            };
+            bail!("The nonces of the transactions to broadcast don't follow the on-chain nonces of their senders:{report}\n{hint}")
+        }
+
+        shell::println(format!(
+            "##\nRenumbering the nonces of the transactions to broadcast:{report}"
+        ))?;
+        nonces::repair_nonces(deployment_sequence, from_index, provider).await?;
+        deployment_sequence.save()
+    }
+
+    /// Makes sure the RPC the transactions are broadcasted to is on the chain the sequence was
+    /// configured and simulated for, and the transactions are signed for.
+    ///
+    /// Aborts on any mismatch, unless `--allow-chain-mismatch` is set.
+    async fn check_chains(
+        &self,
+        deployment_sequence: &ScriptSequence,
+        signer_chain: u64,
+        provider: &RetryProvider,
+    ) -> Result<()> {
+        let chains = BroadcastChains {
+            // The sequences of a multi chain deployment don't share the configured chain.
+            config: self.evm_opts.env.chain_id.filter(|_| !deployment_sequence.multi),
+            simulated: deployment_sequence.chain,
+            rpc: provider.get_chainid().await?.as_u64(),
+            signer: signer_chain,
+        };
+        let mismatches = chains.mismatches();
+        if mismatches.is_empty() {
+            return Ok(())
+        }
+
+        let report =
+            mismatches.iter().map(|mismatch| format!("\n  - {mismatch}")).collect::<String>();
+        if !self.allow_chain_mismatch {
+            bail!("Refusing to broadcast to chain {}:{report}\nAdd --allow-chain-mismatch to broadcast anyway.", chains.rpc)
+        }
+
+        shell::println(
+            Paint::red(format!(
+                "##\nWARNING: Broadcasting to chain {} despite:{report}",
+                chains.rpc
+            ))
+            .bold(),
+        )?;
+        Ok(())
+    }
+
     async fn send_transaction(
         &self,
         provider: Arc<RetryProvider>,
-        (mut tx, factory_deps): (TypedTransaction, Vec<Vec<u8>>),
+        (tx, factory_deps, fee_overrides): ZkTransaction,
         kind: SendTransactionKind<'_>,
         sequential_broadcast: bool,
         fork_url: &str,
         is_fixed_gas_limit: bool,
     ) -> Result<TxHash> {
-        let from = tx.from().expect("no sender");
+        let from = (*tx.from().expect("no sender")).to_alloy();
+        let tx_nonce = u64::try_from(tx.nonce().expect("no nonce").to_alloy()).ok();
 
         if sequential_broadcast {
-            let nonce = zkforge::next_nonce((*from).to_alloy(), fork_url, None)
+            let nonce = zkforge::next_nonce(from, fork_url, None)
                 .await
                 .map_err(|_| eyre::eyre!("Not able to query the EOA nonce."))?;
 
-            let tx_nonce = tx.nonce().expect("no nonce");
-            if let Ok(tx_nonce) = u64::try_from(tx_nonce.to_alloy()) {
+            if let Some(tx_nonce) = tx_nonce {
                 if nonce != tx_nonce {
                     bail!("EOA nonce changed unexpectedly while sending transactions. Expected {tx_nonce} got {nonce} from provider.")
                 }
             }
         }
 
+        let mut retries = 0;
+        loop {
+            let result = self
+                .submit_transaction(
+                    provider.clone(),
+                    (tx.clone(), factory_deps.clone(), fee_overrides.clone()),
+                    kind.clone(),
+                    is_fixed_gas_limit,
+                )
+                .await;
+
+            match (result, tx_nonce) {
+                (Err(err), Some(tx_nonce))
+                    if retries < NONCE_TOO_LOW_RETRIES && is_nonce_too_low(&err) =>
+                {
+                    let nonce = zkforge::next_nonce(from, fork_url, None)
+                        .await
+                        .map_err(|_| eyre::eyre!("Not able to query the EOA nonce."))?;
+
+                    // The nonce was consumed by another transaction, retrying won't help.
+                    if nonce > tx_nonce {
+                        bail!("Nonce {tx_nonce} of {from} has already been used on chain (current nonce {nonce}). Add `--resume` to your command to continue broadcasting the remaining transactions.")
+                    }
+
+                    // The node hasn't caught up with the latest nonce yet.
+                    retries += 1;
+                    trace!(?from, tx_nonce, nonce, retries, "nonce too low, retrying");
+                    tokio::time::sleep(Duration::from_secs(retries as u64)).await;
+                }
+                (result, _) => return result,
+            }
+        }
+    }
+
+    /// Submits a single transaction, without any nonce checks.
+    async fn submit_transaction(
+        &self,
+        provider: Arc<RetryProvider>,
+        (mut tx, factory_deps, fee_overrides): ZkTransaction,
+        kind: SendTransactionKind<'_>,
+        is_fixed_gas_limit: bool,
+    ) -> Result<TxHash> {
         match kind {
             SendTransactionKind::Unlocked(addr) => {
                 debug!("sending transaction from unlocked account {:?}: {:?}", addr, tx);
@@ -258,6 +530,9 @@ impl ScriptArgs {
                 }
 
                 //TODO: convert to EIP712 if !factory_deps.is_empty()
+                if !factory_deps.is_empty() {
+                    bail!("Deploying contracts from {addr:?} requires its key: transactions with factory dependencies can't be sent from unlocked or impersonated accounts yet.")
+                }
 
                 // Submit the transaction
                 let pending = provider.send_transaction(tx, None).await?;
@@ -265,7 +540,19 @@ impl ScriptArgs {
                 Ok(pending.tx_hash())
             }
             SendTransactionKind::Raw(signer) => {
-                self.broadcast(provider, signer, (tx, factory_deps)).await
+                self.broadcast(provider, signer, (tx, factory_deps, fee_overrides)).await
+            }
+            SendTransactionKind::Relayer(relayer) => {
+                if !is_fixed_gas_limit &&
+                    (has_different_gas_calc(provider.get_chainid().await?.as_u64()) ||
+                        self.skip_simulation)
+                {
+                    self.estimate_gas(&mut tx, &provider).await?;
+                }
+
+                let request = RelayerTransaction::new(&tx, factory_deps, &fee_overrides)?;
+                debug!("sending transaction through the relayer: {:?}", request);
+                relayer.send(&request).await
             }
         }
     }
@@ -298,7 +585,29 @@ impl ScriptArgs {
                     )
                     .await?;
 
-                if script_config.has_multiple_rpcs() {
+                let predictions = self.predict_deployment_addresses(&deployments).await?;
+
+                if let Some(path) = &self.json_plan {
+                    let plans = deployments
+                        .iter()
+                        .zip(&predictions)
+                        .map(|(sequence, predictions)| ScriptPlan::new(sequence, predictions))
+                        .collect::<Vec<_>>();
+                    let json = serde_json::to_string_pretty(&plans)?;
+                    if path == Path::new("-") {
+                        shell::println(json)?;
+                    } else {
+                        fs::write(path, json)?;
+                        shell::println(format!("\nScript plan written to {}", path.display()))?;
+                    }
+                }
+
+                if self.safe {
+                    let name = script_config.target_contract().name.clone();
+                    for sequence in &deployments {
+                        self.export_safe_batch(sequence, name.clone())?;
+                    }
+                } else if script_config.has_multiple_rpcs() {
                     trace!(target: "script", "broadcasting multi chain deployment");
 
                     let multi = MultiChainSequence::new(
@@ -330,7 +639,7 @@ impl ScriptArgs {
                     .await?;
                 }
 
-                if !self.broadcast {
+                if !self.broadcast && !self.safe {
                     shell::println("\nSIMULATION COMPLETE. To broadcast these transactions, add --broadcast and wallet configuration(s) to the previous command. See forge script --help for more.")?;
                 }
             } else {
@@ -340,6 +649,26 @@ impl ScriptArgs {
         Ok(())
     }
 
+    /// Prints the addresses contracts will be deployed to when broadcasting, given the current
+    /// on-chain nonces of their senders, and warns if those nonces diverge from the simulated ones.
+    async fn predict_deployment_addresses(
+        &self,
+        deployments: &[ScriptSequence],
+    ) -> Result<Vec<Vec<prediction::PredictedDeployment>>> {
+        let mut predictions = Vec::with_capacity(deployments.len());
+        for sequence in deployments {
+            let Some(rpc) = sequence.transactions.front().and_then(|tx| tx.rpc.clone()) else {
+                predictions.push(vec![]);
+                continue
+            };
+
+            let (predicted, divergences) = prediction::predict_deployments(sequence, &rpc).await?;
+            prediction::print_predictions(&predicted, &divergences)?;
+            predictions.push(predicted);
+        }
+        Ok(predictions)
+    }
+
     /// Broadcasts a single chain script.
     async fn single_deployment(
         &self,
@@ -360,6 +689,12 @@ impl ScriptArgs {
         deployment_sequence.add_libraries(libraries);
 
         self.send_transactions(deployment_sequence, &rpc, &result.script_wallets).await?;
+        self.report_divergences(deployment_sequence, &rpc).await;
+        self.write_deployments(
+            deployment_sequence,
+            &script_config.config,
+            &verify.known_contracts,
+        )?;
 
         if self.verify {
             return deployment_sequence.verify_contracts(&script_config.config, verify).await
@@ -419,6 +754,7 @@ impl ScriptArgs {
                     let mut tx = TransactionWithMetadata::from_typed_transaction(btx.transaction);
                     tx.rpc = btx.rpc;
                     tx.factory_deps = btx.factory_deps;
+                    tx.fee_overrides = btx.fee_overrides;
                     tx
                 })
                 .collect()
@@ -573,6 +909,22 @@ impl ScriptArgs {
                 ))?;
                 shell::println("\n==========================")?;
             }
+
+            // Itemize the costs of a dry run, so they can be reviewed before broadcasting.
+            if !self.broadcast {
+                for sequence in &deployments {
+                    let Some(rpc) = sequence.transactions.front().and_then(|tx| tx.rpc.as_ref())
+                    else {
+                        continue
+                    };
+                    let provider_info = manager.get(rpc).expect("provider is set.");
+                    let per_gas = match self.with_gas_price {
+                        Some(gas_price) => gas_price,
+                        None => provider_info.gas_price()?,
+                    };
+                    fees::print_cost_summary(sequence, &provider_info.provider, per_gas).await?;
+                }
+            }
         }
         Ok(deployments)
     }
@@ -583,7 +935,7 @@ impl ScriptArgs {
         &self,
         provider: Arc<RetryProvider>,
         signer: &WalletSigner,
-        (mut legacy_or_1559, factory_deps): (TypedTransaction, Vec<Vec<u8>>),
+        (mut legacy_or_1559, factory_deps, fee_overrides): ZkTransaction,
     ) -> Result<TxHash> {
         // Chains which use `eth_estimateGas` are being sent sequentially and require their gas
         // to be re-estimated right before broadcasting.
@@ -596,7 +948,9 @@ impl ScriptArgs {
             self.estimate_gas(&mut legacy_or_1559, &provider).await?;
         }
 
-        let rlp_signed = if factory_deps.is_empty() {
+        // Only EIP-712 transactions can carry a gas per pubdata limit.
+        let rlp_signed = if factory_deps.is_empty() && fee_overrides.gas_per_pubdata_limit.is_none()
+        {
             // Signing manually so we skip `fill_transaction` and its `eth_createAccessList`
             // request.
             let signature = signer
@@ -607,28 +961,21 @@ impl ScriptArgs {
 
             legacy_or_1559.rlp_signed(&signature)
         } else {
-            let custom_data = Eip712Meta::new().factory_deps(factory_deps);
-
-            let mut deploy_request = Eip712TransactionRequest::new()
-                .r#type(EIP712_TX_TYPE)
-                .from(*legacy_or_1559.from().unwrap())
-                .to(*legacy_or_1559.to().and_then(|to| to.as_address()).unwrap())
-                .chain_id(legacy_or_1559.chain_id().unwrap().as_u64())
-                .nonce(legacy_or_1559.nonce().unwrap())
-                .gas_price(legacy_or_1559.gas_price().unwrap())
-                .max_fee_per_gas(legacy_or_1559.max_cost().unwrap())
-                .data(legacy_or_1559.data().cloned().unwrap())
-                .custom_data(custom_data);
+            let mut deploy_request =
+                fees::eip712_request(&legacy_or_1559, factory_deps, &fee_overrides)?
+                    .chain_id(legacy_or_1559.chain_id().unwrap().as_u64())
+                    .nonce(legacy_or_1559.nonce().unwrap())
+                    .gas_price(legacy_or_1559.gas_price().unwrap())
+                    .max_fee_per_gas(legacy_or_1559.max_cost().unwrap());
 
             let gas_price = provider.get_gas_price().await?;
-            let fee: zksync_web3_rs::zks_provider::types::Fee =
-                provider.request("zks_estimateFee", [deploy_request.clone()]).await.unwrap();
+            let fee = fees::estimate_request_fee(&provider, &deploy_request).await?;
 
             deploy_request = deploy_request
                 .gas_limit(fee.gas_limit)
-                .max_fee_per_gas(fee.max_fee_per_gas)
+                .max_fee_per_gas(fee_overrides.max_fee_per_gas.unwrap_or(fee.max_fee_per_gas))
                 .max_priority_fee_per_gas(fee.max_priority_fee_per_gas)
-                .gas_price(gas_price);
+                .gas_price(fee_overrides.gas_price.unwrap_or(gas_price));
 
             let signable: Eip712Transaction =
                 deploy_request.clone().try_into().expect("converting deploy request");
@@ -668,19 +1015,81 @@ impl ScriptArgs {
     }
 }
 
+/// How many times a transaction rejected with `nonce too low` is resubmitted.
+const NONCE_TOO_LOW_RETRIES: usize = 3;
+
+/// Returns true if the error is a `nonce too low` rejection from the node.
+fn is_nonce_too_low(err: &eyre::Report) -> bool {
+    err.chain().any(|cause| cause.to_string().to_lowercase().contains("nonce too low"))
+}
+
+/// Splits the transactions into consecutive waves that can be sent concurrently across senders.
+///
+/// A new wave is started whenever a transaction touches a contract deployed in the current wave
+/// by a different sender, so that deployments are always confirmed before they are called.
+/// Transactions of the same sender never start a new wave, since their nonces already enforce the
+/// right ordering. The contracts a transaction touches are its `to` address and the contracts it
+/// called in the simulation, through internal calls or its constructor.
+pub(super) fn dependency_waves<'a>(
+    transactions: impl IntoIterator<Item = &'a TransactionWithMetadata>,
+) -> Vec<Range<usize>> {
+    let mut waves = vec![];
+    let mut start = 0;
+    let mut deployed: HashMap<Address, Address> = HashMap::new();
+
+    let mut end = 0;
+    for (index, tx) in transactions.into_iter().enumerate() {
+        end = index + 1;
+        let from = tx.typed_tx().from().map(|from| (*from).to_alloy());
+        let to = tx.typed_tx().to().and_then(|to| to.as_address()).map(|to| (*to).to_alloy());
+
+        let depends_on_other_sender = to
+            .iter()
+            .chain(&tx.touched_contracts)
+            .filter_map(|address| deployed.get(address))
+            .any(|deployer| Some(*deployer) != from);
+        if depends_on_other_sender {
+            waves.push(start..index);
+            start = index;
+            deployed.clear();
+        }
+
+        if let Some(from) = from {
+            let created = tx
+                .additional_contracts
+                .iter()
+                .map(|contract| contract.address)
+                .chain(tx.contract_address.filter(|_| tx.opcode != CallKind::Call));
+            deployed.extend(created.map(|address| (address, from)));
+        }
+    }
+    if start < end {
+        waves.push(start..end);
+    }
+
+    waves
+}
+
+/// A transaction to broadcast, along with its factory deps and the fees pinned by the script.
+type ZkTransaction = (TypedTransaction, Vec<Vec<u8>>, FeeOverrides);
+
 /// How to send a single transaction
 #[derive(Clone)]
 enum SendTransactionKind<'a> {
     Unlocked(Address),
     Raw(&'a WalletSigner),
+    Relayer(&'a Relayer),
 }
 
 /// Represents how to send _all_ transactions
 enum SendTransactionsKind {
     /// Send via `eth_sendTransaction` and rely on the  `from` address being unlocked.
     Unlocked(HashSet<Address>),
-    /// Send a signed transaction via `eth_sendRawTransaction`
-    Raw(HashMap<Address, WalletSigner>),
+    /// Send a signed transaction via `eth_sendRawTransaction`, or via `eth_sendTransaction` for
+    /// the senders impersonated on a local node.
+    Raw { wallets: HashMap<Address, WalletSigner>, impersonated: HashSet<Address> },
+    /// Hand the transactions of the senders to an external relayer, which signs and sends them.
+    Relayer { relayer: Relayer, senders: HashSet<Address> },
 }
 
 impl SendTransactionsKind {
@@ -695,13 +1104,18 @@ impl SendTransactionsKind {
                 }
                 Ok(SendTransactionKind::Unlocked(*addr))
             }
-            SendTransactionsKind::Raw(wallets) => {
+            SendTransactionsKind::Raw { wallets, impersonated } => {
                 if let Some(wallet) = wallets.get(addr) {
                     Ok(SendTransactionKind::Raw(wallet))
+                } else if impersonated.contains(addr) {
+                    Ok(SendTransactionKind::Unlocked(*addr))
                 } else {
                     bail!("No matching signer for {:?} found", addr)
                 }
             }
+            SendTransactionsKind::Relayer { relayer, .. } => {
+                Ok(SendTransactionKind::Relayer(relayer))
+            }
         }
     }
 
@@ -709,7 +1123,84 @@ impl SendTransactionsKind {
     fn signers_count(&self) -> usize {
         match self {
             SendTransactionsKind::Unlocked(addr) => addr.len(),
-            SendTransactionsKind::Raw(signers) => signers.len(),
+            SendTransactionsKind::Raw { wallets, impersonated } => {
+                wallets.len() + impersonated.len()
+            }
+            SendTransactionsKind::Relayer { senders, .. } => senders.len(),
         }
     }
 }
+
+/// Impersonates the given accounts on a local node with `hardhat_impersonateAccount`, so that
+/// their transactions can be sent without their keys.
+async fn impersonate_accounts(provider: &RetryProvider, accounts: &HashSet<Address>) -> Result<()> {
+    for account in accounts {
+        provider
+            .request::<_, serde_json::Value>("hardhat_impersonateAccount", [account.to_ethers()])
+            .await
+            .wrap_err_with(|| {
+                format!("No associated wallet for {account:?}, and the node failed to impersonate it. Set a private key, mnemonic or keystore for it, or use --unlocked if the node already unlocked it.")
+            })?;
+        shell::println(format!(
+            "No wallet found for {account:?}, impersonating it on the local node."
+        ))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx(from: Address, to: Option<Address>, created: Option<Address>) -> TransactionWithMetadata {
+        let mut request = TransactionRequest::new().from(from.to_ethers());
+        if let Some(to) = to {
+            request = request.to(to.to_ethers());
+        }
+        TransactionWithMetadata {
+            opcode: if created.is_some() { CallKind::Create } else { CallKind::Call },
+            contract_address: created,
+            ..TransactionWithMetadata::from_typed_transaction(TypedTransaction::Legacy(request))
+        }
+    }
+
+    #[test]
+    fn splits_waves_on_calls_to_contracts_of_other_senders() {
+        let (alice, bob) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let (token, vault) = (Address::repeat_byte(0x10), Address::repeat_byte(0x20));
+        let transactions = [
+            tx(alice, None, Some(token)),
+            tx(bob, None, Some(vault)),
+            // the nonce of the deployer already orders its own calls
+            tx(alice, Some(token), None),
+            // waits for the deployment of another sender
+            tx(bob, Some(token), None),
+            // the vault was confirmed in the previous wave
+            tx(alice, Some(vault), None),
+        ];
+        assert_eq!(dependency_waves(&transactions), vec![0..3, 3..5]);
+
+        assert!(dependency_waves(std::iter::empty()).is_empty());
+    }
+
+    #[test]
+    fn splits_waves_on_internal_calls_to_contracts_of_other_senders() {
+        let (alice, bob) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let (token, router, pool) =
+            (Address::repeat_byte(0x10), Address::repeat_byte(0x30), Address::repeat_byte(0x40));
+        let transactions = [
+            tx(alice, None, Some(token)),
+            // the router calls the token internally
+            TransactionWithMetadata {
+                touched_contracts: vec![router, token],
+                ..tx(bob, Some(router), None)
+            },
+            // the constructor of the pool calls the router, which wasn't deployed in this wave
+            TransactionWithMetadata {
+                touched_contracts: vec![router],
+                ..tx(alice, None, Some(pool))
+            },
+        ];
+        assert_eq!(dependency_waves(&transactions), vec![0..1, 1..3]);
+    }
+}
@@ -0,0 +1,81 @@
+//! Cross-checking of the chain ids a sequence is configured, simulated and broadcasted with.
+
+use std::fmt;
+
+/// The chain ids involved in broadcasting a sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BroadcastChains {
+    /// The chain id set in the config or with `--chain`, if any.
+    pub config: Option<u64>,
+    /// The chain id of the fork the sequence was simulated against.
+    pub simulated: u64,
+    /// The chain id reported by the RPC the transactions are sent to.
+    pub rpc: u64,
+    /// The chain id the transactions are signed for.
+    pub signer: u64,
+}
+
+/// A chain id differing from the one of the RPC the transactions are sent to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainMismatch {
+    /// Where the differing chain id comes from.
+    pub source: &'static str,
+    pub chain: u64,
+    pub rpc: u64,
+}
+
+impl fmt::Display for ChainMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the {} chain is {}, but the RPC is on chain {}",
+            self.source, self.chain, self.rpc
+        )
+    }
+}
+
+impl BroadcastChains {
+    /// Returns the chain ids that differ from the one of the RPC.
+    pub fn mismatches(&self) -> Vec<ChainMismatch> {
+        [
+            ("configured", self.config),
+            ("simulated", Some(self.simulated)),
+            ("signer", Some(self.signer)),
+        ]
+        .into_iter()
+        .filter_map(|(source, chain)| {
+            chain.filter(|chain| *chain != self.rpc).map(|chain| ChainMismatch {
+                source,
+                chain,
+                rpc: self.rpc,
+            })
+        })
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_mismatching_chains() {
+        let chains = BroadcastChains { config: None, simulated: 300, rpc: 300, signer: 300 };
+        assert!(chains.mismatches().is_empty());
+
+        let chains = BroadcastChains { config: Some(324), ..chains };
+        assert_eq!(
+            chains.mismatches(),
+            vec![ChainMismatch { source: "configured", chain: 324, rpc: 300 }]
+        );
+
+        let chains = BroadcastChains { config: Some(300), simulated: 324, rpc: 300, signer: 1 };
+        assert_eq!(
+            chains.mismatches().iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "the simulated chain is 324, but the RPC is on chain 300",
+                "the signer chain is 1, but the RPC is on chain 300",
+            ]
+        );
+    }
+}
@@ -282,6 +282,12 @@ impl ScriptArgs {
         if self.resume {
             self.send_transactions(&mut deployment_sequence, fork_url, &result.script_wallets)
                 .await?;
+            self.report_divergences(&deployment_sequence, fork_url).await;
+            self.write_deployments(
+                &deployment_sequence,
+                &script_config.config,
+                &verify.known_contracts,
+            )?;
         }
 
         if self.verify {
@@ -351,6 +357,7 @@ impl ScriptArgs {
             for new_tx in new_txs.iter() {
                 txs.push_back(BroadcastableTransaction {
                     factory_deps: vec![],
+                    fee_overrides: new_tx.fee_overrides.clone(),
                     rpc: new_tx.rpc.clone(),
                     transaction: TypedTransaction::Legacy(new_tx.transaction.clone().into()),
                 });
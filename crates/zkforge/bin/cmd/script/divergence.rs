@@ -0,0 +1,255 @@
+use super::{sequence::ScriptSequence, transaction::TransactionWithMetadata, ScriptArgs};
+use alloy_primitives::{Address, Bytes, B256};
+use ethers_core::types::{Log, TransactionReceipt};
+use ethers_providers::Middleware;
+use foundry_common::{
+    shell, try_get_http_provider,
+    types::{ToAlloy, ToEthers},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use yansi::Paint;
+
+/// How much the gas used on chain may differ from the simulated one, in percent, before it's
+/// reported. The fees of the sequencer make the gas used vary slightly between runs.
+const GAS_TOLERANCE_PERCENT: u64 = 10;
+
+/// The outcome of a transaction in the on-chain simulation, compared with its receipt once it's
+/// broadcasted.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedOutcome {
+    pub gas_used: u64,
+    /// The logs emitted by user contracts, in order.
+    pub logs: Vec<SimulatedLog>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulatedLog {
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+}
+
+impl SimulatedOutcome {
+    pub fn new(gas_used: u64, logs: &[Log]) -> Self {
+        Self { gas_used, logs: user_logs(logs) }
+    }
+}
+
+/// How a mined transaction differs from its simulation.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Divergence {
+    /// The transaction reverted on chain, but succeeded in the simulation.
+    Reverted,
+    Gas {
+        simulated: u64,
+        onchain: u64,
+    },
+    LogCount {
+        simulated: usize,
+        onchain: usize,
+    },
+    /// The first log that differs, the previous ones are the same.
+    Log {
+        index: usize,
+        simulated: SimulatedLog,
+        onchain: SimulatedLog,
+    },
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reverted => f.write_str("reverted on chain, but succeeded in the simulation"),
+            Self::Gas { simulated, onchain } => {
+                let change = (*onchain as f64 - *simulated as f64) / *simulated as f64 * 100.0;
+                write!(
+                    f,
+                    "used {onchain} gas on chain, {simulated} in the simulation ({change:+.1}%)"
+                )
+            }
+            Self::LogCount { simulated, onchain } => {
+                write!(f, "emitted {onchain} logs on chain, {simulated} in the simulation")
+            }
+            Self::Log { index, simulated, onchain } => {
+                write!(f, "log {index} differs: ")?;
+                if simulated.address != onchain.address {
+                    write!(
+                        f,
+                        "emitted by {} on chain, by {} in the simulation",
+                        onchain.address, simulated.address
+                    )
+                } else if simulated.topics.first() != onchain.topics.first() {
+                    write!(
+                        f,
+                        "event {:?} on chain, {:?} in the simulation",
+                        onchain.topics.first(),
+                        simulated.topics.first()
+                    )
+                } else {
+                    f.write_str("its topics or data differ")
+                }
+            }
+        }
+    }
+}
+
+/// Compares the receipt of a transaction with its simulation.
+pub fn divergences(simulated: &SimulatedOutcome, receipt: &TransactionReceipt) -> Vec<Divergence> {
+    if receipt.status.map_or(false, |status| status.is_zero()) {
+        return vec![Divergence::Reverted]
+    }
+
+    let mut divergences = vec![];
+    if let Some(onchain) = receipt.gas_used.map(|gas| gas.as_u64()) {
+        let simulated = simulated.gas_used;
+        if onchain.abs_diff(simulated) * 100 > simulated * GAS_TOLERANCE_PERCENT {
+            divergences.push(Divergence::Gas { simulated, onchain });
+        }
+    }
+
+    let onchain = user_logs(&receipt.logs);
+    if onchain.len() != simulated.logs.len() {
+        divergences
+            .push(Divergence::LogCount { simulated: simulated.logs.len(), onchain: onchain.len() });
+    } else if let Some((index, (simulated, onchain))) = simulated
+        .logs
+        .iter()
+        .zip(onchain)
+        .enumerate()
+        .find(|(_, (simulated, onchain))| *simulated != onchain)
+    {
+        divergences.push(Divergence::Log { index, simulated: simulated.clone(), onchain });
+    }
+    divergences
+}
+
+/// Returns the logs not emitted by system contracts, such as the fee transfers of the bootloader,
+/// which the simulation doesn't reproduce exactly.
+fn user_logs(logs: &[Log]) -> Vec<SimulatedLog> {
+    logs.iter()
+        .filter(|log| !is_system_contract(log.address.to_alloy()))
+        .map(|log| SimulatedLog {
+            address: log.address.to_alloy(),
+            topics: log.topics.iter().map(|topic| topic.to_alloy()).collect(),
+            data: log.data.0.clone().into(),
+        })
+        .collect()
+}
+
+/// Returns `true` if the address is in the range reserved for the system contracts.
+fn is_system_contract(address: Address) -> bool {
+    address.0[..18] == [0; 18]
+}
+
+impl ScriptArgs {
+    /// Fetches the receipts of the broadcasted transactions of the sequence again, and reports
+    /// how they differ from their simulation.
+    ///
+    /// The transactions are broadcasted already, so the receipts that can't be fetched are only
+    /// warned about.
+    pub(super) async fn report_divergences(&self, sequence: &ScriptSequence, rpc: &str) {
+        let provider = match try_get_http_provider(rpc) {
+            Ok(provider) => provider,
+            Err(err) => {
+                warn_unchecked(&format!("{err}"));
+                return
+            }
+        };
+
+        let mut report = String::new();
+        let mut unchecked = String::new();
+        for tx in &sequence.transactions {
+            let (Some(simulated), Some(hash)) = (&tx.simulation, tx.hash) else { continue };
+            let receipt = match provider.get_transaction_receipt(hash.to_ethers()).await {
+                Ok(Some(receipt)) => receipt,
+                Ok(None) => continue,
+                Err(err) => {
+                    unchecked += &format!("\n  {hash} ({}): {err}", describe(tx));
+                    continue
+                }
+            };
+
+            let divergences = divergences(simulated, &receipt);
+            if !divergences.is_empty() {
+                report += &format!("\n  {hash} ({}):", describe(tx));
+                for divergence in divergences {
+                    report += &format!("\n    - {divergence}");
+                }
+            }
+        }
+
+        if !report.is_empty() {
+            let _ = shell::println(Paint::yellow(format!(
+                "##\nThe transactions behaved differently on chain than in the simulation:{report}"
+            )));
+        }
+        if !unchecked.is_empty() {
+            warn_unchecked(&format!(
+                "the receipts of these transactions couldn't be fetched:{unchecked}"
+            ));
+        }
+    }
+}
+
+/// Warns that the broadcasted transactions couldn't be compared with their simulation.
+fn warn_unchecked(reason: &str) {
+    let _ = shell::println(Paint::yellow(format!(
+        "Warning: could not compare the broadcasted transactions with their simulation, {reason}"
+    )));
+}
+
+/// Describes the transaction by its contract and function, as far as they're known.
+fn describe(tx: &TransactionWithMetadata) -> String {
+    let contract = tx.contract_name.as_deref().filter(|name| !name.is_empty());
+    match (contract, tx.function.as_deref()) {
+        (Some(contract), Some(function)) => format!("{contract}::{function}"),
+        (Some(contract), None) => format!("deployment of {contract}"),
+        (None, Some(function)) => function.to_string(),
+        (None, None) => "unknown".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{H160, H256, U256, U64};
+
+    fn log(address: H160, topic: u64) -> Log {
+        Log { address, topics: vec![H256::from_low_u64_be(topic)], ..Default::default() }
+    }
+
+    fn receipt(status: u64, gas_used: u64, logs: Vec<Log>) -> TransactionReceipt {
+        TransactionReceipt {
+            status: Some(U64::from(status)),
+            gas_used: Some(U256::from(gas_used)),
+            logs,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn detects_divergences() {
+        let token = H160::repeat_byte(1);
+        // the fee transfers of the bootloader are ignored
+        let fee = log(H160::from_low_u64_be(0x800a), 1);
+        let simulated = SimulatedOutcome::new(1000, &[log(token, 1), fee.clone()]);
+        assert_eq!(simulated.logs.len(), 1);
+
+        assert!(divergences(&simulated, &receipt(1, 1050, vec![log(token, 1), fee])).is_empty());
+        assert_eq!(divergences(&simulated, &receipt(0, 1000, vec![])), vec![Divergence::Reverted]);
+        assert_eq!(
+            divergences(&simulated, &receipt(1, 2000, vec![log(token, 1)])),
+            vec![Divergence::Gas { simulated: 1000, onchain: 2000 }]
+        );
+        assert_eq!(
+            divergences(&simulated, &receipt(1, 1000, vec![])),
+            vec![Divergence::LogCount { simulated: 1, onchain: 0 }]
+        );
+        assert!(matches!(
+            divergences(&simulated, &receipt(1, 1000, vec![log(token, 2)]))[..],
+            [Divergence::Log { index: 0, .. }]
+        ));
+    }
+}
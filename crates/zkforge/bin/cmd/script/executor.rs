@@ -1,5 +1,6 @@
 use super::{
     artifacts::ArtifactInfo,
+    divergence::SimulatedOutcome,
     runner::SimulationStage,
     transaction::{AdditionalContract, TransactionWithMetadata},
     *,
@@ -11,6 +12,7 @@ use foundry_cli::utils::{ensure_clean_constructor, needs_setup};
 use foundry_common::{shell, types::ToEthers, RpcUrl};
 use foundry_compilers::artifacts::CompactContractBytecode;
 use futures::future::join_all;
+use itertools::Itertools;
 use parking_lot::RwLock;
 use std::{collections::VecDeque, sync::Arc};
 use zkforge::{
@@ -147,6 +149,7 @@ impl ScriptArgs {
                 runner.executor.adjust_zksync_gas_parameters();
 
                 let deps = transaction.factory_deps;
+                let fee_overrides = transaction.fee_overrides;
                 if let TypedTransaction::Legacy(mut tx) = transaction.transaction {
                     let result = runner
                         .simulate(
@@ -183,6 +186,21 @@ impl ScriptArgs {
                         })
                         .collect();
 
+                    let touched_contracts = result
+                        .traces
+                        .iter()
+                        .flat_map(|(_, traces)| {
+                            traces
+                                .arena
+                                .iter()
+                                .filter(|node| {
+                                    !matches!(node.kind(), CallKind::Create | CallKind::Create2)
+                                })
+                                .map(|node| node.trace.address)
+                        })
+                        .unique()
+                        .collect();
+
                     // Simulate mining the transaction if the user passes `--slow`.
                     if self.slow {
                         runner.executor.env.block.number += U256::from(1);
@@ -200,7 +218,7 @@ impl ScriptArgs {
                         println!("Gas limit was set in script to {:}", tx.gas.unwrap());
                     }
 
-                    let tx = TransactionWithMetadata::new(
+                    let mut tx = TransactionWithMetadata::new(
                         tx.into(),
                         transaction.rpc,
                         &result,
@@ -209,7 +227,10 @@ impl ScriptArgs {
                         created_contracts,
                         is_fixed_gas_limit,
                         deps,
+                        fee_overrides,
                     )?;
+                    tx.simulation = Some(SimulatedOutcome::new(result.gas_used, &result.logs));
+                    tx.touched_contracts = touched_contracts;
 
                     Ok((Some(tx), result.traces))
                 } else {
@@ -321,9 +342,17 @@ impl ScriptArgs {
             .gas_limit(script_config.evm_opts.gas_limit());
 
         if let SimulationStage::Local = stage {
+            let script_wallets = self.wallets.known_addresses().unwrap_or_else(|err| {
+                warn!(%err, "failed to read the wallets of the script");
+                vec![]
+            });
             builder = builder.inspectors(|stack| {
                 stack.debug(self.debug).cheatcodes(
-                    CheatsConfig::new(&script_config.config, script_config.evm_opts.clone()).into(),
+                    CheatsConfig {
+                        script_wallets,
+                        ..CheatsConfig::new(&script_config.config, script_config.evm_opts.clone())
+                    }
+                    .into(),
                 )
             });
         }
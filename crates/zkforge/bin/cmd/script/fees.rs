@@ -0,0 +1,159 @@
+//! Fee estimates of script transactions, as computed by the node with `zks_estimateFee`.
+
+use super::{sequence::ScriptSequence, transaction::TransactionWithMetadata};
+use alloy_primitives::U256;
+use comfy_table::Table;
+use ethers_core::{types::transaction::eip2718::TypedTransaction, utils::format_units};
+use eyre::{ContextCompat, Result, WrapErr};
+use foundry_common::{
+    shell,
+    types::{ToAlloy, ToEthers},
+    RetryProvider,
+};
+use foundry_evm::{inspectors::cheatcodes::FeeOverrides, utils::CallKind};
+use zksync_web3_rs::{
+    eip712::{Eip712Meta, Eip712TransactionRequest},
+    zks_provider::types::Fee,
+    zks_utils::EIP712_TX_TYPE,
+};
+
+/// Estimates the fee of a transaction, including the factory dependencies and gas per pubdata
+/// limit it will be broadcasted with.
+pub async fn estimate_fee(provider: &RetryProvider, tx: &TransactionWithMetadata) -> Result<Fee> {
+    let request = eip712_request(&tx.transaction, tx.factory_deps.clone(), &tx.fee_overrides)?;
+    estimate_request_fee(provider, &request).await
+}
+
+/// Estimates the fee of an EIP-712 transaction request.
+pub async fn estimate_request_fee(
+    provider: &RetryProvider,
+    request: &Eip712TransactionRequest,
+) -> Result<Fee> {
+    provider.request("zks_estimateFee", [request]).await.wrap_err("Failed to estimate fee")
+}
+
+/// Converts a transaction into an EIP-712 request carrying its factory dependencies and gas per
+/// pubdata limit.
+pub fn eip712_request(
+    typed_tx: &TypedTransaction,
+    factory_deps: Vec<Vec<u8>>,
+    fee_overrides: &FeeOverrides,
+) -> Result<Eip712TransactionRequest> {
+    let mut custom_data = Eip712Meta::new().factory_deps(factory_deps);
+    if let Some(gas_per_pubdata_limit) = fee_overrides.gas_per_pubdata_limit {
+        custom_data = custom_data.gas_per_pubdata(gas_per_pubdata_limit);
+    }
+
+    let mut request = Eip712TransactionRequest::new()
+        .r#type(EIP712_TX_TYPE)
+        .from(*typed_tx.from().wrap_err("transaction has no sender")?)
+        .to(*typed_tx
+            .to()
+            .and_then(|to| to.as_address())
+            .wrap_err("transaction has no recipient")?)
+        .data(typed_tx.data().cloned().unwrap_or_default())
+        .custom_data(custom_data);
+    if let Some(value) = typed_tx.value() {
+        request = request.value(*value);
+    }
+    Ok(request)
+}
+
+/// Prints the estimated cost of every transaction of a sequence at the given gas price.
+///
+/// Transactions whose fee can't be estimated, e.g. because they depend on contracts deployed by
+/// previous transactions of the sequence, fall back to the gas used during simulation.
+///
+/// The pubdata of a transaction is estimated from the gas the node charges on top of the gas the
+/// simulation used, which pays for the published data at the gas per pubdata limit.
+pub async fn print_cost_summary(
+    sequence: &ScriptSequence,
+    provider: &RetryProvider,
+    gas_price: U256,
+) -> Result<()> {
+    let mut table = Table::new();
+    table.set_header(["#", "Target", "Method", "L2 gas", "Pubdata (bytes)", "Fee (ETH)"]);
+
+    let mut total = U256::ZERO;
+    for (index, tx) in sequence.transactions.iter().enumerate() {
+        let (gas, pubdata) = match estimate_fee(provider, tx).await {
+            Ok(fee) => {
+                let pubdata = tx
+                    .simulation
+                    .as_ref()
+                    .and_then(|simulation| {
+                        estimate_pubdata(
+                            fee.gas_limit.to_alloy(),
+                            U256::from(simulation.gas_used),
+                            fee.gas_per_pubdata_limit.to_alloy(),
+                        )
+                    })
+                    .map(|bytes| bytes.to_string())
+                    .unwrap_or_else(|| "-".to_string());
+                (fee.gas_limit.to_alloy(), pubdata)
+            }
+            Err(err) => {
+                trace!("fee estimation failed: {err}");
+                let gas = tx.transaction.gas().map(|gas| gas.to_alloy()).unwrap_or_default();
+                (gas, "-".to_string())
+            }
+        };
+        let fee = gas.saturating_mul(gas_price);
+        total = total.saturating_add(fee);
+
+        let target = match (&tx.contract_name, tx.contract_address) {
+            (Some(name), _) if !name.is_empty() => name.clone(),
+            (_, Some(address)) => address.to_string(),
+            _ => "-".to_string(),
+        };
+        let method = match tx.opcode {
+            CallKind::Create | CallKind::Create2 => "constructor".to_string(),
+            _ => tx.function.clone().filter(|f| !f.is_empty()).unwrap_or_else(|| "-".into()),
+        };
+        table.add_row([
+            (index + 1).to_string(),
+            target,
+            method,
+            gas.to_string(),
+            pubdata,
+            format_eth(fee),
+        ]);
+    }
+
+    shell::println("\nEstimated transaction costs:\n")?;
+    shell::println(table.to_string())?;
+    shell::println(format!("\nEstimated total cost: {} ETH", format_eth(total)))?;
+    Ok(())
+}
+
+/// Estimates how many bytes of pubdata a transaction publishes, from the gas it's charged above its
+/// execution gas.
+fn estimate_pubdata(gas_limit: U256, execution_gas: U256, gas_per_pubdata: U256) -> Option<U256> {
+    if gas_per_pubdata.is_zero() {
+        return None
+    }
+    Some(gas_limit.saturating_sub(execution_gas) / gas_per_pubdata)
+}
+
+fn format_eth(wei: U256) -> String {
+    format_units(wei.to_ethers(), 18)
+        .map(|eth| eth.trim_end_matches('0').trim_end_matches('.').to_string())
+        .unwrap_or_else(|_| "[Could not calculate]".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_pubdata_from_the_gas_above_execution() {
+        let pubdata = estimate_pubdata(U256::from(1_800_000), U256::from(200_000), U256::from(800));
+        assert_eq!(pubdata, Some(U256::from(2_000)));
+
+        // the node charged less than the simulation used
+        let pubdata = estimate_pubdata(U256::from(100_000), U256::from(200_000), U256::from(800));
+        assert_eq!(pubdata, Some(U256::ZERO));
+
+        assert_eq!(estimate_pubdata(U256::from(100_000), U256::ZERO, U256::ZERO), None);
+    }
+}
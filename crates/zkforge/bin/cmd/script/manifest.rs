@@ -0,0 +1,261 @@
+use super::{
+    prediction::is_zk_create, sequence::ScriptSequence, transaction::TransactionWithMetadata,
+    ScriptArgs,
+};
+use alloy_dyn_abi::{DynSolType, JsonAbiExt};
+use alloy_json_abi::{Function, JsonAbi};
+use alloy_primitives::{Address, B256};
+use ethers_core::types::TransactionReceipt;
+use eyre::{Result, WrapErr};
+use foundry_common::{fmt::format_token_raw, fs, shell, types::ToAlloy, ContractsByArtifact};
+use foundry_config::Config;
+use foundry_evm::utils::CallKind;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+/// Directory of the project root the deployment manifests are written to, one file per chain.
+pub const DEPLOYMENTS_DIR: &str = "deployments";
+
+/// The contracts deployed to a chain by the broadcasted scripts.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentManifest {
+    pub chain: u64,
+    /// The deployed contracts by name. Contracts deployed more than once by the same script are
+    /// suffixed with the number of previous deployments, e.g. `Token_1`.
+    pub contracts: BTreeMap<String, DeployedContract>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployedContract {
+    pub address: Address,
+    pub transaction_hash: B256,
+    pub block_number: Option<u64>,
+    /// The constructor arguments, decoded if the ABI of the contract is known.
+    pub constructor_args: Vec<String>,
+    /// The zkSync bytecode hash of the contract, if it was deployed through the
+    /// `ContractDeployer`.
+    pub bytecode_hash: Option<B256>,
+    /// When the script that deployed the contract was run.
+    pub timestamp: u64,
+}
+
+impl DeploymentManifest {
+    /// Returns the path of the manifest of the chain.
+    pub fn path(root: &Path, chain: u64) -> PathBuf {
+        root.join(DEPLOYMENTS_DIR).join(format!("{chain}.json"))
+    }
+
+    /// Loads the manifest of the chain, or an empty one if there's none yet.
+    pub fn load(root: &Path, chain: u64) -> Result<Self> {
+        let path = Self::path(root, chain);
+        if !path.exists() {
+            return Ok(Self { chain, ..Default::default() })
+        }
+        fs::read_json_file(&path).wrap_err("Failed to read the deployment manifest")
+    }
+
+    /// Writes the manifest, returning its path.
+    pub fn save(&self, root: &Path) -> Result<PathBuf> {
+        let path = Self::path(root, self.chain);
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+
+    /// Adds the contracts deployed by the mined transactions of the sequence, replacing the ones
+    /// with the same name.
+    pub fn extend(&mut self, sequence: &ScriptSequence, known_contracts: &ContractsByArtifact) {
+        let artifacts = zk_artifacts(known_contracts);
+        let mut deployed: HashMap<String, usize> = HashMap::new();
+
+        for tx in &sequence.transactions {
+            let Some(receipt) = tx.hash.and_then(|hash| {
+                sequence.receipts.iter().find(|receipt| receipt.transaction_hash.to_alloy() == hash)
+            }) else {
+                continue
+            };
+            if receipt.status.map_or(true, |status| status.is_zero()) {
+                continue
+            }
+            let Some((name, mut contract)) = deployment(tx, receipt, &artifacts) else { continue };
+            contract.timestamp = sequence.timestamp;
+
+            let count = deployed.entry(name.clone()).or_default();
+            let name = if *count == 0 { name } else { format!("{name}_{count}") };
+            *count += 1;
+            self.contracts.insert(name, contract);
+        }
+    }
+}
+
+impl ScriptArgs {
+    /// Records the contracts deployed by the broadcasted sequence in the manifest of its chain, if
+    /// `--write-deployments` is set.
+    pub(super) fn write_deployments(
+        &self,
+        sequence: &ScriptSequence,
+        config: &Config,
+        known_contracts: &ContractsByArtifact,
+    ) -> Result<()> {
+        if !self.write_deployments {
+            return Ok(())
+        }
+
+        let mut manifest = DeploymentManifest::load(&config.__root.0, sequence.chain)?;
+        manifest.extend(sequence, known_contracts);
+        let path = manifest.save(&config.__root.0)?;
+        shell::println(format!("\nDeployments written to: {}", path.display()))?;
+        Ok(())
+    }
+}
+
+/// Returns the name and ABI of the known contracts by their zkSync bytecode hash.
+fn zk_artifacts(known_contracts: &ContractsByArtifact) -> HashMap<B256, (&str, &JsonAbi)> {
+    known_contracts
+        .iter()
+        .filter_map(|(id, (abi, code))| {
+            zk_bytecode_hash(code).map(|hash| (hash, (id.name.as_str(), abi)))
+        })
+        .collect()
+}
+
+/// Returns the zkSync bytecode hash of the code, if it's a valid zkEVM bytecode.
+fn zk_bytecode_hash(code: &[u8]) -> Option<B256> {
+    let words = code.len() / 32;
+    let valid = !code.is_empty() && code.len() % 32 == 0 && words % 2 == 1 && words < 1 << 16;
+    valid.then(|| B256::from(zksync_utils::bytecode::hash_bytecode(code).0))
+}
+
+/// Returns the contract deployed by the transaction, and its name.
+fn deployment(
+    tx: &TransactionWithMetadata,
+    receipt: &TransactionReceipt,
+    artifacts: &HashMap<B256, (&str, &JsonAbi)>,
+) -> Option<(String, DeployedContract)> {
+    let transaction_hash = receipt.transaction_hash.to_alloy();
+    let block_number = receipt.block_number.map(|number| number.as_u64());
+    let name = tx.contract_name.clone().filter(|name| !name.is_empty());
+
+    if matches!(tx.opcode, CallKind::Create | CallKind::Create2) {
+        return Some((
+            name?,
+            DeployedContract {
+                address: tx.contract_address?,
+                transaction_hash,
+                block_number,
+                constructor_args: tx.arguments.clone().unwrap_or_default(),
+                bytecode_hash: None,
+                timestamp: 0,
+            },
+        ))
+    }
+    if !is_zk_create(tx) {
+        return None
+    }
+
+    // `create(bytes32 salt, bytes32 bytecodeHash, bytes input)`, `createAccount` has an extra
+    // trailing argument
+    let data = tx.typed_tx().data()?;
+    let params = DynSolType::Tuple(vec![
+        DynSolType::FixedBytes(32),
+        DynSolType::FixedBytes(32),
+        DynSolType::Bytes,
+    ])
+    .abi_decode_params(data.get(4..)?)
+    .ok()?;
+    let params = params.as_tuple()?;
+    let bytecode_hash = B256::from_slice(params[1].as_fixed_bytes()?.0);
+    let input = params[2].as_bytes()?;
+
+    let address = receipt.contract_address.map(|address| address.to_alloy()).or_else(|| {
+        tx.additional_contracts
+            .iter()
+            .find(|contract| contract.opcode == CallKind::Create)
+            .map(|contract| contract.address)
+    })?;
+
+    let artifact = artifacts.get(&bytecode_hash);
+    let constructor_args =
+        artifact
+            .and_then(|(_, abi)| abi.constructor())
+            .and_then(|constructor| {
+                Function {
+                    name: "constructor".to_string(),
+                    inputs: constructor.inputs.clone(),
+                    outputs: vec![],
+                    state_mutability: constructor.state_mutability,
+                }
+                .abi_decode_input(input, false)
+                .ok()
+            })
+            .map(|args| args.iter().map(format_token_raw).collect())
+            .unwrap_or_else(|| {
+                if input.is_empty() {
+                    vec![]
+                } else {
+                    vec![hex::encode_prefixed(input)]
+                }
+            });
+
+    let name = artifact
+        .map(|(name, _)| name.to_string())
+        .or(name)
+        .unwrap_or_else(|| format!("Unknown{address}"));
+
+    Some((
+        name,
+        DeployedContract {
+            address,
+            transaction_hash,
+            block_number,
+            constructor_args,
+            bytecode_hash: Some(bytecode_hash),
+            timestamp: 0,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_hashes_zk_bytecodes() {
+        assert!(zk_bytecode_hash(&[]).is_none());
+        assert!(zk_bytecode_hash(&[0; 31]).is_none());
+        // an even number of words
+        assert!(zk_bytecode_hash(&[0; 64]).is_none());
+
+        let hash = zk_bytecode_hash(&[0; 32]).unwrap();
+        // version 1, followed by the length in words
+        assert_eq!(hash[..4], [1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn roundtrips_manifest() {
+        let root = tempfile::tempdir().unwrap();
+        let mut manifest = DeploymentManifest::load(root.path(), 324).unwrap();
+        assert!(manifest.contracts.is_empty());
+
+        let contract = DeployedContract {
+            address: Address::repeat_byte(1),
+            transaction_hash: B256::repeat_byte(2),
+            block_number: Some(3),
+            constructor_args: vec!["42".to_string()],
+            bytecode_hash: Some(B256::repeat_byte(4)),
+            timestamp: 5,
+        };
+        manifest.contracts.insert("Counter".to_string(), contract.clone());
+        let path = manifest.save(root.path()).unwrap();
+        assert_eq!(path, root.path().join("deployments").join("324.json"));
+
+        let manifest = DeploymentManifest::load(root.path(), 324).unwrap();
+        assert_eq!(manifest.chain, 324);
+        assert_eq!(manifest.contracts["Counter"], contract);
+    }
+}
@@ -43,7 +43,10 @@ use foundry_evm::{
 use futures::future;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    path::PathBuf,
+};
 use yansi::Paint;
 use zkforge::{
     backend::Backend,
@@ -60,12 +63,21 @@ use zkforge::{
 mod artifacts;
 mod broadcast;
 mod build;
+mod chain;
 mod cmd;
+mod divergence;
 mod executor;
+mod fees;
+mod manifest;
 mod multi;
+mod nonces;
+mod plan;
+mod prediction;
 mod providers;
 mod receipts;
+mod relayer;
 mod runner;
+mod safe;
 mod sequence;
 pub mod transaction;
 mod verify;
@@ -118,6 +130,11 @@ pub struct ScriptArgs {
     #[clap(long)]
     pub broadcast: bool,
 
+    /// Writes the transactions as a Safe Transaction Builder batch instead of broadcasting them,
+    /// for scripts whose transactions are sent by a Safe.
+    #[clap(long, conflicts_with_all = ["broadcast", "resume"])]
+    pub safe: bool,
+
     /// Skips on-chain simulation.
     #[clap(long)]
     pub skip_simulation: bool,
@@ -134,6 +151,17 @@ pub struct ScriptArgs {
     )]
     pub unlocked: bool,
 
+    /// Hands the transactions to the relayer API at this URL to be signed and sent, instead of
+    /// signing them locally.
+    ///
+    /// Each transaction is `POST`ed as JSON, and the relayer responds with its hash.
+    #[clap(long, value_name = "URL", conflicts_with = "unlocked")]
+    pub relayer_url: Option<String>,
+
+    /// The API key sent to the relayer as a bearer token.
+    #[clap(long, env = "RELAYER_API_KEY", value_name = "KEY", requires = "relayer_url")]
+    pub relayer_api_key: Option<String>,
+
     /// Resumes submitting transactions that failed or timed-out previously.
     ///
     /// It DOES NOT simulate the script again and it expects nonces to have remained the same.
@@ -143,6 +171,18 @@ pub struct ScriptArgs {
     #[clap(long)]
     pub resume: bool,
 
+    /// Renumbers the nonces of the transactions to broadcast if they don't follow the on-chain
+    /// nonces of their senders, instead of aborting.
+    ///
+    /// Transactions whose deployment addresses would change are never renumbered.
+    #[clap(long)]
+    pub fix_nonces: bool,
+
+    /// Only warns, instead of aborting, if the chain of the RPC the transactions are broadcasted
+    /// to differs from the configured chain, the simulated chain or the chain of the signers.
+    #[clap(long)]
+    pub allow_chain_mismatch: bool,
+
     /// If present, --resume or --verify will be assumed to be a multi chain deployment.
     #[clap(long)]
     pub multi: bool,
@@ -158,6 +198,15 @@ pub struct ScriptArgs {
     #[clap(long)]
     pub slow: bool,
 
+    /// Sends the transactions of different senders concurrently.
+    ///
+    /// Transactions calling a contract deployed by another sender are only sent once that
+    /// deployment has been confirmed. Only the direct target of a transaction is checked, so
+    /// contracts of other senders reached through internal calls or from a constructor must be
+    /// deployed in an earlier broadcast.
+    #[clap(long, conflicts_with = "slow")]
+    pub parallel_senders: bool,
+
     /// Disables interactive prompts that might appear when deploying big contracts.
     ///
     /// For more info on the contract size limit, see EIP-170: <https://eips.ethereum.org/EIPS/eip-170>
@@ -172,10 +221,30 @@ pub struct ScriptArgs {
     #[clap(long)]
     pub verify: bool,
 
+    /// Writes the contracts deployed by the broadcasted transactions, with their transaction,
+    /// constructor arguments and bytecode hash, to `deployments/<CHAIN_ID>.json`.
+    #[clap(long)]
+    pub write_deployments: bool,
+
     /// Output results in JSON format.
     #[clap(long)]
     pub json: bool,
 
+    /// Writes the planned transactions, with their decoded calls, predicted deployment addresses
+    /// and dependencies, as JSON to the given file, or to stdout if no file is given.
+    ///
+    /// The file must be given as `--json-plan=<PATH>`, so that the script path can follow the
+    /// flag.
+    #[clap(
+        long,
+        value_hint = ValueHint::FilePath,
+        value_name = "PATH",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "-"
+    )]
+    pub json_plan: Option<PathBuf>,
+
     /// Gas price for legacy transactions, or max fee per gas for EIP1559 transactions.
     #[clap(
         long,
@@ -218,11 +287,12 @@ impl ScriptArgs {
 
         let mut local_identifier = LocalTraceIdentifier::new(known_contracts);
         let mut decoder = CallTraceDecoderBuilder::new()
+            .with_labels(script_config.config.labels.clone())
             .with_labels(result.labeled_addresses.clone())
             .with_verbosity(verbosity)
             .with_signature_identifier(SignaturesIdentifier::new(
                 Config::foundry_cache_dir(),
-                script_config.config.offline,
+                script_config.config.zk_signatures_offline(),
             )?)
             .build();
 
@@ -427,6 +497,7 @@ impl ScriptArgs {
             .enumerate()
             .map(|(i, bytes)| BroadcastableTransaction {
                 factory_deps: vec![],
+                fee_overrides: Default::default(),
                 rpc: fork_url.clone(),
                 transaction: TypedTransaction::Legacy(TransactionRequest {
                     from: Some(from.to_ethers()),
@@ -737,6 +808,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_parse_json_plan() {
+        let args = ScriptArgs::parse_from(["foundry-cli", "--json-plan", "Contract.sol"]);
+        assert_eq!(args.path, "Contract.sol");
+        assert_eq!(args.json_plan, Some(PathBuf::from("-")));
+
+        let args = ScriptArgs::parse_from(["foundry-cli", "--json-plan=plan.json", "Contract.sol"]);
+        assert_eq!(args.path, "Contract.sol");
+        assert_eq!(args.json_plan, Some(PathBuf::from("plan.json")));
+    }
+
     #[test]
     fn can_parse_unlocked() {
         let args: ScriptArgs = ScriptArgs::parse_from([
@@ -761,6 +843,21 @@ mod tests {
         assert!(args.is_err());
     }
 
+    #[test]
+    fn can_parse_parallel_senders() {
+        let args: ScriptArgs =
+            ScriptArgs::parse_from(["foundry-cli", "Contract.sol", "--parallel-senders"]);
+        assert!(args.parallel_senders);
+
+        let args = ScriptArgs::try_parse_from([
+            "foundry-cli",
+            "Contract.sol",
+            "--parallel-senders",
+            "--slow",
+        ]);
+        assert!(args.is_err());
+    }
+
     #[test]
     fn can_merge_script_config() {
         let args: ScriptArgs = ScriptArgs::parse_from([
@@ -153,16 +153,17 @@ impl ScriptArgs {
         let mut results: Vec<Result<(), Report>> = Vec::new();
 
         for sequence in deployments.deployments.iter_mut() {
-            let result = match self
-                .send_transactions(
-                    sequence,
-                    &sequence.typed_transactions().first().unwrap().0.clone(),
-                    &script_wallets,
-                )
-                .await
-            {
-                Ok(_) if self.verify => sequence.verify_contracts(config, verify.clone()).await,
-                Ok(_) => Ok(()),
+            let rpc = sequence.typed_transactions().first().unwrap().0.clone();
+            let result = match self.send_transactions(sequence, &rpc, &script_wallets).await {
+                Ok(_) => {
+                    self.report_divergences(sequence, &rpc).await;
+                    match self.write_deployments(sequence, config, &verify.known_contracts) {
+                        Ok(_) if self.verify => {
+                            sequence.verify_contracts(config, verify.clone()).await
+                        }
+                        result => result,
+                    }
+                }
                 Err(err) => Err(err),
             };
             results.push(result);
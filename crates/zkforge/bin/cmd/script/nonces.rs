@@ -0,0 +1,166 @@
+//! Detection and repair of nonce gaps and collisions before broadcasting a sequence.
+
+use super::{
+    prediction::{fetch_chain_nonces, is_zk_create, ChainNonces},
+    sequence::ScriptSequence,
+    transaction::TransactionWithMetadata,
+};
+use alloy_primitives::Address;
+use foundry_common::{types::ToAlloy, RetryProvider};
+use foundry_evm::utils::CallKind;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt,
+};
+use zksync_types::utils::deployed_address_create;
+
+/// A queued transaction whose nonces don't line up with the on-chain state of its sender.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NonceIssue {
+    /// The transaction skips nonces, so it would be stuck until they are used.
+    Gap { tx_index: usize, sender: Address, nonce: u64, expected: u64 },
+    /// The nonce of the transaction was already used.
+    Collision { tx_index: usize, sender: Address, nonce: u64, expected: u64 },
+    /// The deployment nonce of the sender moved since the simulation, so the contract would be
+    /// deployed to another address than the one the later transactions refer to.
+    DeploymentNonce { tx_index: usize, sender: Address, simulated: Address, predicted: Address },
+}
+
+impl NonceIssue {
+    /// Returns `true` if renumbering the transaction nonces fixes the issue.
+    pub fn is_repairable(&self) -> bool {
+        !matches!(self, Self::DeploymentNonce { .. })
+    }
+}
+
+impl fmt::Display for NonceIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Gap { tx_index, sender, nonce, expected } => write!(
+                f,
+                "transaction {tx_index} of {sender} has nonce {nonce}, skipping the next free nonce {expected}"
+            ),
+            Self::Collision { tx_index, sender, nonce, expected } => write!(
+                f,
+                "transaction {tx_index} of {sender} has nonce {nonce}, which is already used (next free nonce is {expected})"
+            ),
+            Self::DeploymentNonce { tx_index, sender, simulated, predicted } => write!(
+                f,
+                "transaction {tx_index} of {sender} deploys to {predicted} instead of the simulated {simulated}, since the deployment nonce of the sender changed"
+            ),
+        }
+    }
+}
+
+/// Compares the nonces of the transactions of a sequence, starting at `from_index`, with the
+/// on-chain nonces of their senders.
+pub async fn check_nonces(
+    sequence: &ScriptSequence,
+    from_index: usize,
+    provider: &RetryProvider,
+) -> eyre::Result<Vec<NonceIssue>> {
+    let mut nonces: HashMap<Address, ChainNonces> = HashMap::new();
+    let mut issues = vec![];
+
+    for (tx_index, tx) in sequence.transactions.iter().enumerate().skip(from_index) {
+        let Some(from) = tx.typed_tx().from().copied() else { continue };
+        let next = match nonces.entry(from.to_alloy()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(fetch_chain_nonces(provider, from).await?),
+        };
+        issues.extend(check_transaction(tx_index, tx, next));
+    }
+
+    Ok(issues)
+}
+
+/// Checks the nonces of a transaction against the next free nonces of its sender, and advances
+/// them past the transaction.
+fn check_transaction(
+    tx_index: usize,
+    tx: &TransactionWithMetadata,
+    next: &mut ChainNonces,
+) -> Vec<NonceIssue> {
+    let mut issues = vec![];
+    let Some(sender) = tx.typed_tx().from().map(|from| from.to_alloy()) else { return issues };
+
+    if let Some(nonce) = tx.typed_tx().nonce().and_then(|nonce| u64::try_from(*nonce).ok()) {
+        let expected = next.tx;
+        if nonce > expected {
+            issues.push(NonceIssue::Gap { tx_index, sender, nonce, expected });
+        } else if nonce < expected {
+            issues.push(NonceIssue::Collision { tx_index, sender, nonce, expected });
+        }
+    }
+    next.tx += 1;
+
+    if is_zk_create(tx) {
+        let predicted = deployed_address_create(
+            zksync_types::Address::from_slice(sender.as_slice()),
+            next.deploy.into(),
+        );
+        let predicted = Address::from_slice(predicted.as_bytes());
+        next.deploy += 1;
+
+        let simulated = tx
+            .additional_contracts
+            .iter()
+            .find(|contract| contract.opcode == CallKind::Create)
+            .map(|contract| contract.address);
+        if let Some(simulated) = simulated.filter(|simulated| *simulated != predicted) {
+            issues.push(NonceIssue::DeploymentNonce { tx_index, sender, simulated, predicted });
+        }
+    }
+
+    issues
+}
+
+/// Renumbers the nonces of the transactions of a sequence, starting at `from_index`, to follow
+/// the on-chain nonces of their senders.
+pub async fn repair_nonces(
+    sequence: &mut ScriptSequence,
+    from_index: usize,
+    provider: &RetryProvider,
+) -> eyre::Result<()> {
+    let mut nonces: HashMap<Address, u64> = HashMap::new();
+    for tx in sequence.transactions.iter_mut().skip(from_index) {
+        let Some(from) = tx.typed_tx().from().copied() else { continue };
+        let next = match nonces.entry(from.to_alloy()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(fetch_chain_nonces(provider, from).await?.tx),
+        };
+        tx.typed_tx_mut().set_nonce(*next);
+        *next += 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::TransactionRequest;
+    use foundry_common::types::ToEthers;
+
+    fn tx(sender: Address, nonce: u64) -> TransactionWithMetadata {
+        TransactionWithMetadata::from_typed_transaction(
+            TransactionRequest::new().from(sender.to_ethers()).nonce(nonce).into(),
+        )
+    }
+
+    #[test]
+    fn detects_gaps_and_collisions() {
+        let sender = Address::with_last_byte(1);
+        let mut next = ChainNonces { tx: 5, deploy: 0 };
+
+        assert_eq!(check_transaction(0, &tx(sender, 5), &mut next), vec![]);
+        assert_eq!(
+            check_transaction(1, &tx(sender, 8), &mut next),
+            vec![NonceIssue::Gap { tx_index: 1, sender, nonce: 8, expected: 6 }]
+        );
+        assert_eq!(
+            check_transaction(2, &tx(sender, 3), &mut next),
+            vec![NonceIssue::Collision { tx_index: 2, sender, nonce: 3, expected: 7 }]
+        );
+        assert_eq!(next.tx, 8);
+    }
+}
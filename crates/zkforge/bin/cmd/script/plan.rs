@@ -0,0 +1,96 @@
+//! Machine-readable plan of the transactions a script will broadcast, so that deployment
+//! pipelines can review it before broadcasting.
+
+use super::{
+    broadcast::dependency_waves, prediction::PredictedDeployment, sequence::ScriptSequence,
+    transaction::TransactionWithMetadata,
+};
+use alloy_primitives::{Address, U256};
+use foundry_common::types::ToAlloy;
+use foundry_evm::utils::CallKind;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// The planned transactions of a single chain.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptPlan {
+    pub chain: u64,
+    pub transactions: Vec<PlannedTransaction>,
+}
+
+/// A transaction that will be broadcasted.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedTransaction {
+    /// Position of the transaction in the sequence.
+    pub index: usize,
+    /// The wave the transaction is sent in, transactions of a wave are sent concurrently.
+    pub wave: usize,
+    pub kind: CallKind,
+    pub from: Option<Address>,
+    pub to: Option<Address>,
+    pub value: U256,
+    pub nonce: Option<U256>,
+    pub contract_name: Option<String>,
+    /// The decoded function, if the transaction calls a known contract.
+    pub function: Option<String>,
+    pub arguments: Vec<String>,
+    /// Address of the contract deployed by the transaction, given the current on-chain nonce of
+    /// its sender.
+    pub predicted_address: Option<Address>,
+    /// Number of factory dependencies published along with the transaction.
+    pub factory_deps: usize,
+    /// Indices of the earlier transactions deploying the contract this transaction calls.
+    pub depends_on: Vec<usize>,
+}
+
+impl ScriptPlan {
+    /// Builds the plan of a sequence, given the deployment addresses predicted for it.
+    pub fn new(sequence: &ScriptSequence, predictions: &[PredictedDeployment]) -> Self {
+        let waves = dependency_waves(&sequence.transactions);
+        let predicted = predictions
+            .iter()
+            .map(|prediction| (prediction.tx_index, prediction.predicted))
+            .collect::<HashMap<_, _>>();
+
+        let mut deployed_by: HashMap<Address, usize> = HashMap::new();
+        let transactions = sequence
+            .transactions
+            .iter()
+            .enumerate()
+            .map(|(index, tx)| {
+                let typed_tx = tx.typed_tx();
+                let to = typed_tx.to().and_then(|to| to.as_address()).map(|to| (*to).to_alloy());
+                let depends_on = to.and_then(|to| deployed_by.get(&to)).copied().into_iter();
+                deployed_by.extend(deployed_addresses(tx).map(|address| (address, index)));
+
+                PlannedTransaction {
+                    index,
+                    wave: waves.iter().position(|wave| wave.contains(&index)).unwrap_or_default(),
+                    kind: tx.opcode,
+                    from: typed_tx.from().map(|from| (*from).to_alloy()),
+                    to,
+                    value: typed_tx.value().map(|value| value.to_alloy()).unwrap_or_default(),
+                    nonce: typed_tx.nonce().map(|nonce| nonce.to_alloy()),
+                    contract_name: tx.contract_name.clone().filter(|name| !name.is_empty()),
+                    function: tx.function.clone().filter(|function| !function.is_empty()),
+                    arguments: tx.arguments.clone().unwrap_or_default(),
+                    predicted_address: predicted.get(&index).copied(),
+                    factory_deps: tx.factory_deps.len(),
+                    depends_on: depends_on.collect(),
+                }
+            })
+            .collect();
+
+        Self { chain: sequence.chain, transactions }
+    }
+}
+
+/// Returns the addresses of the contracts deployed by a transaction during simulation.
+fn deployed_addresses(tx: &TransactionWithMetadata) -> impl Iterator<Item = Address> + '_ {
+    tx.additional_contracts
+        .iter()
+        .map(|contract| contract.address)
+        .chain(tx.contract_address.filter(|_| tx.opcode != CallKind::Call))
+}
@@ -0,0 +1,172 @@
+use super::{sequence::ScriptSequence, transaction::TransactionWithMetadata};
+use alloy_primitives::Address;
+use ethers_core::types::{H160, H256};
+use ethers_providers::Middleware;
+use eyre::{Result, WrapErr};
+use foundry_common::{
+    shell, try_get_http_provider,
+    types::ToAlloy,
+    zk_utils::deployer::{CONTRACT_DEPLOYER_ADDRESS, CREATE_SELECTORS},
+    RetryProvider,
+};
+use foundry_evm::utils::CallKind;
+use std::collections::{hash_map::Entry, HashMap};
+use yansi::Paint;
+use zksync_types::{
+    get_nonce_key,
+    utils::{decompose_full_nonce, deployed_address_create},
+    NONCE_HOLDER_ADDRESS,
+};
+use zksync_utils::h256_to_u256;
+
+/// The on-chain nonces of a sender, as stored in the `NonceHolder` system contract.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct ChainNonces {
+    /// Transaction nonce.
+    pub tx: u64,
+    /// Deployment nonce, used to derive `CREATE` addresses.
+    pub deploy: u64,
+}
+
+/// A contract address that will be assigned once the sequence is broadcasted.
+#[derive(Debug)]
+pub struct PredictedDeployment {
+    /// Index of the deploying transaction in the sequence.
+    pub tx_index: usize,
+    pub sender: Address,
+    pub contract_name: Option<String>,
+    /// Address the contract was deployed to during simulation, if known.
+    pub simulated: Option<Address>,
+    /// Address the contract will be deployed to when broadcasted against the current chain state.
+    pub predicted: Address,
+}
+
+/// A sender whose nonce during simulation differs from its on-chain nonce.
+#[derive(Debug)]
+pub struct NonceDivergence {
+    pub sender: Address,
+    pub simulated: u64,
+    pub on_chain: u64,
+}
+
+/// Predicts the addresses the `CREATE` deployments of a [ScriptSequence] will receive when
+/// broadcasted, based on the live on-chain nonces of their senders.
+///
+/// `CREATE2` deployments are skipped, since their address does not depend on any nonce.
+pub async fn predict_deployments(
+    sequence: &ScriptSequence,
+    rpc: &str,
+) -> Result<(Vec<PredictedDeployment>, Vec<NonceDivergence>)> {
+    let provider = try_get_http_provider(rpc)?;
+
+    let mut nonces: HashMap<Address, ChainNonces> = HashMap::new();
+    let mut deployments = vec![];
+    let mut divergences: Vec<NonceDivergence> = vec![];
+
+    for (tx_index, tx) in sequence.transactions.iter().enumerate() {
+        let Some(from) = tx.typed_tx().from().copied() else { continue };
+        let sender = from.to_alloy();
+
+        let next = match nonces.entry(sender) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(fetch_chain_nonces(&provider, from).await?),
+        };
+
+        if let Some(simulated) = tx.typed_tx().nonce().and_then(|n| u64::try_from(*n).ok()) {
+            if simulated != next.tx && !divergences.iter().any(|d| d.sender == sender) {
+                divergences.push(NonceDivergence { sender, simulated, on_chain: next.tx });
+            }
+        }
+        next.tx += 1;
+
+        if is_zk_create(tx) {
+            let predicted = deployed_address_create(
+                zksync_types::Address::from_slice(sender.as_slice()),
+                next.deploy.into(),
+            );
+            next.deploy += 1;
+
+            deployments.push(PredictedDeployment {
+                tx_index,
+                sender,
+                contract_name: tx.contract_name.clone().filter(|name| !name.is_empty()),
+                simulated: tx
+                    .additional_contracts
+                    .iter()
+                    .find(|contract| contract.opcode == CallKind::Create)
+                    .map(|contract| contract.address),
+                predicted: Address::from_slice(predicted.as_bytes()),
+            });
+        }
+    }
+
+    Ok((deployments, divergences))
+}
+
+/// Prints the predicted deployment addresses and warns about any nonce divergences.
+pub fn print_predictions(
+    deployments: &[PredictedDeployment],
+    divergences: &[NonceDivergence],
+) -> Result<()> {
+    for divergence in divergences {
+        shell::println(Paint::yellow(format!(
+            "Warning: simulation used nonce {} for {}, but its on-chain nonce is {}. \
+             Deployed addresses will differ from the simulated ones.",
+            divergence.simulated, divergence.sender, divergence.on_chain
+        )))?;
+    }
+
+    if deployments.is_empty() {
+        return Ok(())
+    }
+
+    shell::println("\n==========================")?;
+    shell::println("\nPredicted deployment addresses:")?;
+    for deployment in deployments {
+        let name = deployment.contract_name.as_deref().unwrap_or("<unknown>");
+        shell::println(format!("  {name}: {}", deployment.predicted))?;
+        if let Some(simulated) = deployment.simulated.filter(|s| *s != deployment.predicted) {
+            shell::println(Paint::yellow(format!(
+                "    Warning: deployed to {simulated} during simulation (sender {}).",
+                deployment.sender
+            )))?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns true if the transaction deploys a contract through the `ContractDeployer` using
+/// `CREATE`.
+pub(super) fn is_zk_create(tx: &TransactionWithMetadata) -> bool {
+    let is_deployer = tx
+        .typed_tx()
+        .to()
+        .and_then(|to| to.as_address())
+        .is_some_and(|to| to.as_bytes() == CONTRACT_DEPLOYER_ADDRESS.as_bytes());
+
+    is_deployer &&
+        tx.typed_tx().data().is_some_and(|data| {
+            data.len() >= 4 && CREATE_SELECTORS.iter().any(|selector| data[..4] == *selector)
+        })
+}
+
+/// Reads the transaction and deployment nonces of `address` from the `NonceHolder` system
+/// contract.
+pub(super) async fn fetch_chain_nonces(
+    provider: &RetryProvider,
+    address: H160,
+) -> Result<ChainNonces> {
+    let key = get_nonce_key(&zksync_types::Address::from_slice(address.as_bytes()));
+    let full_nonce = provider
+        .get_storage_at(
+            H160::from_slice(NONCE_HOLDER_ADDRESS.as_bytes()),
+            H256::from_slice(key.key().as_bytes()),
+            None,
+        )
+        .await
+        .wrap_err_with(|| format!("Failed to fetch the on-chain nonce of {address:?}"))?;
+
+    let (tx, deploy) =
+        decompose_full_nonce(h256_to_u256(zksync_types::H256::from_slice(full_nonce.as_bytes())));
+    Ok(ChainNonces { tx: tx.as_u64(), deploy: deploy.as_u64() })
+}
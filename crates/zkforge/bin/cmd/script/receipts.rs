@@ -9,7 +9,8 @@ use foundry_common::{
     RetryProvider,
 };
 use futures::StreamExt;
-use std::sync::Arc;
+use serde::Deserialize;
+use std::{sync::Arc, time::Duration};
 
 /// Convenience enum for internal signalling of transaction status
 enum TxStatus {
@@ -29,6 +30,36 @@ impl From<TransactionReceipt> for TxStatus {
     }
 }
 
+/// Status of a transaction as reported by `zks_getTransactionDetails`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ZkTransactionStatus {
+    /// Received by the node but not yet part of a block.
+    Pending,
+    /// Included in an L2 block.
+    Included,
+    /// Included in an L1 batch that has been verified.
+    Verified,
+    /// Rejected by the node.
+    Failed,
+}
+
+/// The subset of `zks_getTransactionDetails` we use to track confirmations.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ZkTransactionDetails {
+    status: ZkTransactionStatus,
+    eth_commit_tx_hash: Option<TxHash>,
+    eth_execute_tx_hash: Option<TxHash>,
+}
+
+/// How often a transaction known to the node, but without receipt, is polled.
+const DETAILS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long a transaction known to the node is polled for a receipt before being considered
+/// dropped.
+const DETAILS_POLL_TIMEOUT: Duration = Duration::from_secs(120);
+
 /// Gets the receipts of previously pending transactions, or removes them from
 /// the deploy sequence's pending vector
 pub async fn wait_for_pending(
@@ -110,6 +141,11 @@ pub async fn clear_pendings(
     // print all receipts
     for receipt in receipts {
         print_receipt(deployment_sequence.chain.into(), &receipt);
+        if let Ok(Some(details)) =
+            get_transaction_details(&provider, receipt.transaction_hash.to_alloy()).await
+        {
+            print_l1_status(&details);
+        }
         deployment_sequence.add_receipt(receipt);
     }
 
@@ -144,12 +180,68 @@ async fn check_tx_status(
         // If the tx is present in the mempool, run the pending tx future, and
         // assume the next drop is really really real
         let pending_res = PendingTransaction::new(hash.to_ethers(), provider).await?;
-        match pending_res {
-            Some(receipt) => Ok(receipt.into()),
-            None => Ok(TxStatus::Dropped),
+        if let Some(receipt) = pending_res {
+            return Ok(receipt.into())
+        }
+
+        // The transaction might not be visible through `eth_getTransactionByHash` yet, while
+        // zkSync already knows about it. Only consider it dropped if zkSync doesn't either, or
+        // still has no receipt for it after a while.
+        match tokio::time::timeout(DETAILS_POLL_TIMEOUT, poll_transaction_details(provider, hash))
+            .await
+        {
+            Ok(status) => status,
+            Err(_) => {
+                debug!(?hash, "timed out waiting for the receipt of a transaction known to zkSync");
+                Ok(TxStatus::Dropped)
+            }
         }
     }
     .await;
 
     (hash, result)
 }
+
+/// Polls `zks_getTransactionDetails` until the transaction has a receipt, or the node doesn't know
+/// about it.
+///
+/// Nodes without the `zks_` namespace, or failing to serve it, are taken to not know about it.
+async fn poll_transaction_details(provider: &RetryProvider, hash: TxHash) -> Result<TxStatus> {
+    loop {
+        match get_transaction_details(provider, hash).await {
+            Ok(Some(details)) if details.status != ZkTransactionStatus::Failed => {
+                if let Some(receipt) = provider.get_transaction_receipt(hash.to_ethers()).await? {
+                    return Ok(receipt.into())
+                }
+                tokio::time::sleep(DETAILS_POLL_INTERVAL).await;
+            }
+            Ok(_) => return Ok(TxStatus::Dropped),
+            Err(err) => {
+                debug!(?hash, %err, "failed to get the zkSync details of a transaction");
+                return Ok(TxStatus::Dropped)
+            }
+        }
+    }
+}
+
+/// Queries `zks_getTransactionDetails` for the given transaction.
+///
+/// Returns `None` if the node doesn't know about the transaction.
+async fn get_transaction_details(
+    provider: &RetryProvider,
+    hash: TxHash,
+) -> Result<Option<ZkTransactionDetails>> {
+    Ok(provider.request("zks_getTransactionDetails", [hash]).await?)
+}
+
+/// Prints how far the transaction has progressed towards L1 finality.
+fn print_l1_status(details: &ZkTransactionDetails) {
+    let status = match details.status {
+        ZkTransactionStatus::Verified => "verified on L1",
+        _ if details.eth_execute_tx_hash.is_some() => "executed on L1",
+        _ if details.eth_commit_tx_hash.is_some() => "committed to L1",
+        ZkTransactionStatus::Failed => "failed",
+        ZkTransactionStatus::Pending | ZkTransactionStatus::Included => "awaiting L1 commitment",
+    };
+    println!("L1 status: {status}");
+}
@@ -0,0 +1,171 @@
+use ethers_core::types::{transaction::eip2718::TypedTransaction, Address, Bytes, TxHash, U256};
+use eyre::{Result, WrapErr};
+use foundry_evm::inspectors::cheatcodes::FeeOverrides;
+use serde::{Deserialize, Serialize};
+
+/// An external relayer which signs and sends the transactions of the senders it manages the keys
+/// of, for setups where the keys can't be used locally.
+///
+/// Transactions are `POST`ed as [`RelayerTransaction`]s to the endpoint, which responds with the
+/// hash of the sent transaction.
+#[derive(Clone, Debug)]
+pub struct Relayer {
+    client: reqwest::Client,
+    url: String,
+    api_key: Option<String>,
+}
+
+/// A transaction to be signed and sent by the relayer.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayerTransaction {
+    pub from: Address,
+    pub to: Option<Address>,
+    pub data: Bytes,
+    pub value: U256,
+    pub chain_id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<U256>,
+    /// Set if the transaction must be sent as an EIP-712 transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_per_pubdata_limit: Option<U256>,
+    /// The bytecodes to publish along with the transaction, which make it an EIP-712 transaction.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub factory_deps: Vec<Bytes>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RelayerResponse {
+    #[serde(alias = "transactionHash")]
+    hash: TxHash,
+}
+
+impl RelayerTransaction {
+    /// Creates the request for the transaction, its factory deps and the fees pinned by the
+    /// script.
+    pub fn new(
+        tx: &TypedTransaction,
+        factory_deps: Vec<Vec<u8>>,
+        fee_overrides: &FeeOverrides,
+    ) -> Result<Self> {
+        let (max_fee_per_gas, max_priority_fee_per_gas) = match tx {
+            TypedTransaction::Eip1559(inner) => {
+                (inner.max_fee_per_gas, inner.max_priority_fee_per_gas)
+            }
+            _ => (None, None),
+        };
+        Ok(Self {
+            from: *tx.from().wrap_err("Transaction has no sender")?,
+            to: tx.to().and_then(|to| to.as_address()).copied(),
+            data: tx.data().cloned().unwrap_or_default(),
+            value: tx.value().copied().unwrap_or_default(),
+            chain_id: tx.chain_id().wrap_err("Transaction has no chain id")?.as_u64(),
+            nonce: tx.nonce().copied(),
+            gas_limit: tx.gas().copied(),
+            gas_price: fee_overrides.gas_price.or_else(|| match tx {
+                TypedTransaction::Eip1559(_) => None,
+                _ => tx.gas_price(),
+            }),
+            max_fee_per_gas: fee_overrides.max_fee_per_gas.or(max_fee_per_gas),
+            max_priority_fee_per_gas,
+            gas_per_pubdata_limit: fee_overrides.gas_per_pubdata_limit,
+            factory_deps: factory_deps.into_iter().map(Into::into).collect(),
+        })
+    }
+}
+
+impl Relayer {
+    pub fn new(url: String, api_key: Option<String>) -> Self {
+        Self { client: reqwest::Client::new(), url, api_key }
+    }
+
+    /// Hands the transaction to the relayer, returning the hash it was sent with.
+    pub async fn send(&self, tx: &RelayerTransaction) -> Result<TxHash> {
+        let mut request = self.client.post(&self.url).json(tx);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request.send().await.wrap_err("Failed to reach the relayer")?;
+
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            eyre::bail!(
+                "The relayer rejected the transaction of {:?} with status code {status}\nDetails: {text}",
+                tx.from
+            );
+        }
+
+        let response: RelayerResponse = serde_json::from_str(&text)
+            .wrap_err_with(|| format!("Unexpected response from the relayer: {text}"))?;
+        Ok(response.hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Eip1559TransactionRequest, TransactionRequest};
+    use serde_json::json;
+
+    #[test]
+    fn serializes_transactions() {
+        let tx = TypedTransaction::Legacy(
+            TransactionRequest::new()
+                .from(Address::repeat_byte(1))
+                .to(Address::repeat_byte(2))
+                .data(vec![0xab])
+                .value(7)
+                .chain_id(324)
+                .nonce(3)
+                .gas(100)
+                .gas_price(10),
+        );
+        let request = RelayerTransaction::new(&tx, vec![], &FeeOverrides::default()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "from": "0x0101010101010101010101010101010101010101",
+                "to": "0x0202020202020202020202020202020202020202",
+                "data": "0xab",
+                "value": "0x7",
+                "chainId": 324,
+                "nonce": "0x3",
+                "gasLimit": "0x64",
+                "gasPrice": "0xa",
+            })
+        );
+    }
+
+    #[test]
+    fn applies_fee_overrides() {
+        let tx = TypedTransaction::Eip1559(
+            Eip1559TransactionRequest::new()
+                .from(Address::repeat_byte(1))
+                .chain_id(324)
+                .max_fee_per_gas(10)
+                .max_priority_fee_per_gas(1),
+        );
+        let fee_overrides = FeeOverrides {
+            max_fee_per_gas: Some(20.into()),
+            gas_per_pubdata_limit: Some(800.into()),
+            ..Default::default()
+        };
+        let request = RelayerTransaction::new(&tx, vec![vec![0; 32]], &fee_overrides).unwrap();
+        assert_eq!(request.to, None);
+        assert_eq!(request.gas_price, None);
+        assert_eq!(request.max_fee_per_gas, Some(20.into()));
+        assert_eq!(request.max_priority_fee_per_gas, Some(1.into()));
+        assert_eq!(request.gas_per_pubdata_limit, Some(800.into()));
+        assert_eq!(request.factory_deps, vec![Bytes::from(vec![0; 32])]);
+    }
+}
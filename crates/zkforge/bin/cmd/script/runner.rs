@@ -209,7 +209,7 @@ impl ScriptRunner {
             let hash = zksync_utils::bytecode::hash_bytecode(dep);
             info!(?hash, "adding factory dep to storage modifications");
 
-            self.executor.inspector.storage_modifications.bytecodes.insert(hash, dep.clone());
+            self.executor.inspector.storage_modifications.insert_bytecode(hash, dep.clone());
         }
 
         if let Some(NameOrAddress::Address(to)) = to {
@@ -0,0 +1,180 @@
+use super::{sequence::ScriptSequence, transaction::TransactionWithMetadata, ScriptArgs};
+use alloy_primitives::{Address, Bytes, B256};
+use eyre::{Result, WrapErr};
+use foundry_cli::utils::now;
+use foundry_common::{fs, shell, types::ToAlloy};
+use serde::Serialize;
+use std::path::PathBuf;
+use yansi::Paint;
+
+/// File the Safe batch of a sequence is written to, next to the sequence.
+const SAFE_BATCH_FILE: &str = "safe-batch-latest.json";
+
+/// A batch of transactions, as imported by the Safe Transaction Builder.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SafeBatch {
+    pub version: String,
+    pub chain_id: String,
+    /// Milliseconds since the epoch.
+    pub created_at: u128,
+    pub meta: SafeBatchMeta,
+    pub transactions: Vec<SafeTransaction>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SafeBatchMeta {
+    pub name: String,
+    pub description: String,
+    pub created_from_safe_address: Address,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct SafeTransaction {
+    pub to: Address,
+    /// The value in wei, as a decimal string.
+    pub value: String,
+    pub data: Bytes,
+}
+
+impl SafeBatch {
+    /// Creates the batch of the transactions, which must all be sent by the same Safe.
+    ///
+    /// Also returns the hashes of the factory dependencies of the transactions, which the Safe
+    /// can't publish and so must already be known on chain.
+    pub fn new<'a>(
+        name: String,
+        chain: u64,
+        txs: impl IntoIterator<Item = &'a TransactionWithMetadata>,
+    ) -> Result<(Self, Vec<B256>)> {
+        let mut safe = None;
+        let mut transactions = vec![];
+        let mut factory_deps = vec![];
+
+        for tx in txs {
+            let tx_data = tx.typed_tx();
+            let from = tx_data.from().wrap_err("Transaction has no sender")?.to_alloy();
+            match safe {
+                None => safe = Some(from),
+                Some(safe) if safe != from => eyre::bail!(
+                    "All the transactions must be sent by the Safe, but they are sent by both \
+                     {safe} and {from}."
+                ),
+                _ => {}
+            }
+
+            let Some(to) = tx_data.to().and_then(|to| to.as_address()) else {
+                eyre::bail!(
+                    "A Safe can't send contract creations, deploy the contracts through the \
+                     ContractDeployer or a factory instead."
+                )
+            };
+            transactions.push(SafeTransaction {
+                to: to.to_alloy(),
+                value: tx_data.value().copied().unwrap_or_default().to_string(),
+                data: tx_data.data().cloned().unwrap_or_default().0.into(),
+            });
+
+            factory_deps.extend(
+                tx.factory_deps
+                    .iter()
+                    .map(|dep| B256::from(zksync_utils::bytecode::hash_bytecode(dep).0)),
+            );
+        }
+
+        let safe = safe.wrap_err("There are no transactions to export")?;
+        factory_deps.sort();
+        factory_deps.dedup();
+
+        let batch = Self {
+            version: "1.0".to_string(),
+            chain_id: chain.to_string(),
+            created_at: now().as_millis(),
+            meta: SafeBatchMeta {
+                name,
+                description: String::new(),
+                created_from_safe_address: safe,
+            },
+            transactions,
+        };
+        Ok((batch, factory_deps))
+    }
+}
+
+impl ScriptArgs {
+    /// Writes the transactions of the sequence as a Safe batch instead of sending them, returning
+    /// the path of the batch.
+    pub(super) fn export_safe_batch(
+        &self,
+        sequence: &ScriptSequence,
+        name: String,
+    ) -> Result<PathBuf> {
+        let (batch, factory_deps) = SafeBatch::new(name, sequence.chain, &sequence.transactions)?;
+
+        let path = sequence.path.with_file_name(SAFE_BATCH_FILE);
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, serde_json::to_string_pretty(&batch)?)?;
+        shell::println(format!(
+            "\nSafe batch of {} transactions for {} written to: {}",
+            batch.transactions.len(),
+            batch.meta.created_from_safe_address,
+            path.display()
+        ))?;
+
+        if !factory_deps.is_empty() {
+            shell::println(Paint::yellow(format!(
+                "Warning: a Safe can't publish factory dependencies, the bytecodes with these \
+                 hashes must already be known on chain:\n  {}",
+                factory_deps.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n  ")
+            )))?;
+        }
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{transaction::eip2718::TypedTransaction, TransactionRequest};
+    use foundry_common::types::ToEthers;
+
+    fn tx(from: Address, to: Option<Address>) -> TransactionWithMetadata {
+        let mut request = TransactionRequest::new().from(from.to_ethers()).value(7).data(vec![1]);
+        if let Some(to) = to {
+            request = request.to(to.to_ethers());
+        }
+        TransactionWithMetadata {
+            transaction: TypedTransaction::Legacy(request),
+            factory_deps: vec![vec![0; 32]],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn exports_transactions_of_the_safe() {
+        let safe = Address::repeat_byte(1);
+        let target = Address::repeat_byte(2);
+        let txs = [tx(safe, Some(target)), tx(safe, Some(target))];
+
+        let (batch, factory_deps) = SafeBatch::new("Deploy".to_string(), 324, &txs).unwrap();
+        assert_eq!(batch.chain_id, "324");
+        assert_eq!(batch.meta.created_from_safe_address, safe);
+        assert_eq!(batch.transactions.len(), 2);
+        assert_eq!(
+            batch.transactions[0],
+            SafeTransaction { to: target, value: "7".to_string(), data: vec![1].into() }
+        );
+        assert_eq!(factory_deps.len(), 1);
+
+        let other = Address::repeat_byte(3);
+        assert!(SafeBatch::new(
+            String::new(),
+            324,
+            &[tx(safe, Some(target)), tx(other, Some(target))]
+        )
+        .is_err());
+        assert!(SafeBatch::new(String::new(), 324, &[tx(safe, None)]).is_err());
+        assert!(SafeBatch::new(String::new(), 324, std::iter::empty()).is_err());
+    }
+}
@@ -5,7 +5,6 @@ use crate::cmd::{
         transaction::{wrapper, AdditionalContract, TransactionWithMetadata},
         verify::VerifyBundle,
     },
-    verify::provider::VerificationProviderType,
 };
 use alloy_primitives::{Address, TxHash};
 use ethers_core::types::{transaction::eip2718::TypedTransaction, TransactionReceipt};
@@ -252,7 +251,7 @@ impl ScriptSequence {
     /// Checks that there is an Etherscan key for the chain id of this sequence.
     pub fn verify_preflight_check(&self, config: &Config, verify: &VerifyBundle) -> Result<()> {
         if config.get_etherscan_api_key(Some(self.chain.into())).is_none() &&
-            verify.verifier.verifier == VerificationProviderType::Etherscan
+            verify.verifier.requires_etherscan_key()
         {
             eyre::bail!(
                 "Etherscan API key wasn't found for chain id {}. On-chain execution aborted",
@@ -274,9 +273,7 @@ impl ScriptSequence {
 
         verify.set_chain(config, self.chain.into());
 
-        if verify.etherscan.key.is_some() ||
-            verify.verifier.verifier != VerificationProviderType::Etherscan
-        {
+        if verify.etherscan.key.is_some() || !verify.verifier.requires_etherscan_key() {
             trace!(target: "script", "prepare future verifications");
 
             let mut future_verifications = Vec::with_capacity(self.receipts.len());
@@ -1,4 +1,4 @@
-use super::{artifacts::ArtifactInfo, ScriptResult};
+use super::{artifacts::ArtifactInfo, divergence::SimulatedOutcome, ScriptResult};
 use alloy_dyn_abi::JsonAbiExt;
 use alloy_json_abi::Function;
 use alloy_primitives::{Address, Bytes, B256};
@@ -9,7 +9,10 @@ use foundry_common::{
     types::{ToAlloy, ToEthers},
     RpcUrl, SELECTOR_LEN,
 };
-use foundry_evm::{constants::DEFAULT_CREATE2_DEPLOYER, traces::CallTraceDecoder, utils::CallKind};
+use foundry_evm::{
+    constants::DEFAULT_CREATE2_DEPLOYER, inspectors::cheatcodes::FeeOverrides,
+    traces::CallTraceDecoder, utils::CallKind,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
@@ -43,6 +46,15 @@ pub struct TransactionWithMetadata {
     pub additional_contracts: Vec<AdditionalContract>,
     pub is_fixed_gas_limit: bool,
     pub factory_deps: Vec<Vec<u8>>,
+    #[serde(default)]
+    pub fee_overrides: FeeOverrides,
+    /// The outcome of the on-chain simulation, unless it was skipped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub simulation: Option<SimulatedOutcome>,
+    /// The contracts the transaction called in the simulation, including through internal calls
+    /// and constructors.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub touched_contracts: Vec<Address>,
 }
 
 fn default_string() -> Option<String> {
@@ -72,9 +84,16 @@ impl TransactionWithMetadata {
         additional_contracts: Vec<AdditionalContract>,
         is_fixed_gas_limit: bool,
         factory_deps: Vec<Vec<u8>>,
+        fee_overrides: FeeOverrides,
     ) -> Result<Self> {
-        let mut metadata =
-            Self { transaction, rpc, is_fixed_gas_limit, factory_deps, ..Default::default() };
+        let mut metadata = Self {
+            transaction,
+            rpc,
+            is_fixed_gas_limit,
+            factory_deps,
+            fee_overrides,
+            ..Default::default()
+        };
 
         // Specify if any contract was directly created with this transaction
         if let Some(NameOrAddress::Address(to)) = metadata.transaction.to().cloned() {
@@ -219,7 +238,11 @@ impl TransactionWithMetadata {
                     if let Some(function) =
                         decoder.functions.get(&data.0[..SELECTOR_LEN]).and_then(|v| v.first())
                     {
-                        self.contract_name = decoder.contracts.get(&target).cloned();
+                        self.contract_name = decoder
+                            .contracts
+                            .get(&target)
+                            .or_else(|| decoder.labels.get(&target))
+                            .cloned();
 
                         self.function = Some(function.signature());
                         self.arguments = Some(
@@ -1,5 +1,7 @@
 use super::{install, test::filter::ProjectPathsAwareFilter, watch::WatchArgs};
-use alloy_primitives::U256;
+use alloy_dyn_abi::JsonAbiExt;
+use alloy_json_abi::{Function, JsonAbi as Abi};
+use alloy_primitives::{B256, U256};
 use clap::Parser;
 use eyre::Result;
 use foundry_cli::{
@@ -7,8 +9,8 @@ use foundry_cli::{
     utils::{self, LoadConfig},
 };
 use foundry_common::{
-    compact_to_contract, compile::ContractSources, evm::EvmArgs, get_contract_name, get_file_name,
-    shell,
+    compact_to_contract, compile::ContractSources, evm::EvmArgs, fmt::format_token,
+    get_contract_name, get_file_name, shell,
 };
 use foundry_config::{
     figment,
@@ -16,32 +18,40 @@ use foundry_config::{
         value::{Dict, Map},
         Metadata, Profile, Provider,
     },
-    get_available_profiles, Config,
+    get_available_profiles, Config, ZkForkRetriesConfig, ZkGasConfig, MAX_ZK_GAS_LIMIT,
 };
 use foundry_debugger::Debugger;
+use itertools::Itertools;
 use regex::Regex;
-use std::{collections::BTreeMap, fs, sync::mpsc::channel, time::Duration};
+use std::{collections::BTreeMap, fs, path::PathBuf, sync::mpsc::channel, time::Duration};
 use watchexec::config::{InitConfig, RuntimeConfig};
 use yansi::Paint;
 use zkforge::{
-    decode::decode_console_logs,
+    decode::{decode_console_logs, unknown_custom_error},
     gas_report::GasReport,
     inspectors::CheatsConfig,
+    replay::ReplayBundle,
     result::{SuiteResult, TestResult, TestStatus},
     traces::{
-        identifier::{EtherscanIdentifier, LocalTraceIdentifier, SignaturesIdentifier},
+        identifier::{
+            EtherscanIdentifier, LocalTraceIdentifier, SignaturesIdentifier,
+            SingleSignaturesIdentifier,
+        },
         CallTraceDecoderBuilder, TraceKind,
     },
     MultiContractRunner, MultiContractRunnerBuilder, TestOptions, TestOptionsBuilder,
 };
 
 mod filter;
+mod profile;
 mod summary;
 use summary::TestSummaryReporter;
 
+use era_cheatcodes::profiler::ZkProfiler;
 pub use filter::FilterArgs;
 use foundry_common::{
     zk_compile::ZkSolc,
+    zk_utils::source_map::ContractSourceMaps,
     zksolc_manager::{setup_zksolc_manager, DEFAULT_ZKSOLC_VERSION},
 };
 
@@ -86,6 +96,36 @@ pub struct TestArgs {
     #[clap(long)]
     pub fail_fast: bool,
 
+    /// Cache the state after `setUp()` of each test contract and reuse it on subsequent runs.
+    ///
+    /// The cached state is discarded whenever the test contract, its libraries, the
+    /// environment it is deployed in, the fork, the cheatcode settings or the environment
+    /// variables change. Setups aren't cached when `ffi` is enabled.
+    #[clap(long)]
+    pub setup_fixtures: bool,
+
+    /// Profile the zkEVM execution of the tests and write the sampled stacks to the given file.
+    ///
+    /// Every executed instruction is attributed to the source line it was compiled from, and the
+    /// report uses the collapsed stack format understood by flamegraph tools.
+    #[clap(long, value_name = "PATH")]
+    pub zk_profile: Option<PathBuf>,
+
+    /// Write a replay bundle of each failing test to the given directory.
+    ///
+    /// A bundle records the fuzz seed, the state fetched by the fork selected at launch or in
+    /// `setUp()` and the cheatcodes called by the test, so that `--replay` can re-execute it
+    /// exactly and offline.
+    #[clap(long, value_name = "DIR")]
+    pub write_replay: Option<PathBuf>,
+
+    /// Re-execute the failing test recorded in the given replay bundle.
+    ///
+    /// The test runs against the recorded fork state instead of the RPC, with the recorded fuzz
+    /// seed, and is reported if its cheatcode calls diverge from the recorded ones.
+    #[clap(long, value_name = "BUNDLE")]
+    pub replay: Option<PathBuf>,
+
     /// The Etherscan (or equivalent) API key.
     #[clap(long, env = "ETHERSCAN_API_KEY", value_name = "KEY")]
     etherscan_api_key: Option<String>,
@@ -181,20 +221,55 @@ impl TestArgs {
             Err(e) => return Err(eyre::eyre!("Failed to compile with zksolc: {}", e)),
         };
 
+        let replay = self.replay.as_deref().map(ReplayBundle::load).transpose()?;
+        if let Some(bundle) = &replay {
+            // only run the recorded test, with its seed and against its recorded fork state
+            let args = filter.args_mut();
+            args.contract_pattern =
+                Some(Regex::new(&format!("^{}$", regex::escape(bundle.contract_name())))?);
+            args.test_pattern =
+                Some(Regex::new(&format!("^{}(\\(.*\\))?$", regex::escape(bundle.test_name())))?);
+            config.fuzz.seed = bundle.fuzz_seed;
+            if bundle.fork.is_some() {
+                evm_opts.fork_url = None;
+            }
+        } else if self.write_replay.is_some() && config.fuzz.seed.is_none() {
+            // pin the seed so that it can be recorded
+            config.fuzz.seed = Some(U256::from_be_bytes(B256::random().0));
+        }
+
+        if let Some(gas_limit) = config.zk_gas_limit.filter(|gas| *gas > MAX_ZK_GAS_LIMIT) {
+            eyre::bail!(
+                "`zk_gas_limit` of {gas_limit} exceeds the maximum gas limit of zkEVM transactions, \
+                 {MAX_ZK_GAS_LIMIT}"
+            )
+        }
+
         let project = config.project()?;
         let test_options: TestOptions = TestOptionsBuilder::default()
             .fuzz(config.fuzz)
             .invariant(config.invariant)
+            .zk_gas(ZkGasConfig { gas_limit: config.zk_gas_limit })
+            .zk_fork_retries(ZkForkRetriesConfig {
+                retries: config.zk_fork_test_retries,
+                retry_backoff: config.zk_fork_test_retry_backoff,
+                quarantine: config.zk_fork_test_quarantine,
+            })
             .profiles(profiles)
             .build(&output, project_root)?;
 
         // Determine print verbosity and executor verbosity
         let verbosity = evm_opts.verbosity;
-        if self.gas_report && evm_opts.verbosity < 3 {
+        if (self.gas_report || self.write_replay.is_some() || replay.is_some()) &&
+            evm_opts.verbosity < 3
+        {
             evm_opts.verbosity = 3;
         }
 
-        let env = evm_opts.evm_env().await?;
+        let mut env = evm_opts.evm_env().await?;
+        if let Some(state) = replay.as_ref().and_then(|bundle| bundle.fork.as_ref()) {
+            state.apply_env(&mut env);
+        }
 
         // Prepare the test builder
         let should_debug = self.debug.is_some();
@@ -206,7 +281,18 @@ impl TestArgs {
             .sender(evm_opts.sender)
             .with_fork(evm_opts.get_fork(&config, env.clone()))
             .with_cheats_config(CheatsConfig::new(&config, evm_opts.clone()))
-            .with_test_options(test_options.clone());
+            .with_test_options(test_options.clone())
+            .with_setup_fixtures(
+                (self.setup_fixtures && replay.is_none())
+                    .then(|| config.cache_path.join("zk-setup-fixtures")),
+            )
+            .with_genesis(config.zk_genesis.clone())
+            .with_replay_dir(self.write_replay.clone())
+            .with_corpus_dir(
+                (config.zk_fuzz_corpus && replay.is_none())
+                    .then(|| config.cache_path.join("zk-fuzz-corpus")),
+            )
+            .with_fetched_state(replay.as_ref().and_then(|bundle| bundle.fork.clone()));
 
         let mut runner = runner_builder.clone().build(
             project_root,
@@ -215,6 +301,13 @@ impl TestArgs {
             evm_opts.clone(),
         )?;
         runner.contract_bytecodes = contract_bytecodes;
+        runner.source_maps = ContractSourceMaps::new(&output);
+
+        let profiler = self.zk_profile.as_ref().map(|path| {
+            let profiler = ZkProfiler::default();
+            runner.tracer_plugins.register(profiler.clone());
+            (path, profiler, runner.source_maps.clone())
+        });
 
         if should_debug {
             filter.args_mut().test_pattern = self.debug.clone();
@@ -235,6 +328,16 @@ impl TestArgs {
             .run_tests(runner, config.clone(), verbosity, &filter, test_options.clone())
             .await?;
 
+        if let Some(bundle) = &replay {
+            check_replay(bundle, &outcome);
+        }
+
+        if let Some((path, profiler, source_maps)) = profiler {
+            let stacks = profile::folded_stacks(&profiler.samples(), &source_maps);
+            fs::write(path, stacks.join("\n"))?;
+            println!("Wrote {} sampled stacks to {}", stacks.len(), path.display());
+        }
+
         if should_debug {
             let tests = outcome.clone().into_tests();
             let mut decoders = Vec::new();
@@ -242,14 +345,17 @@ impl TestArgs {
                 let mut result = test.result;
                 // Identify addresses in each trace
                 let mut builder = CallTraceDecoderBuilder::new()
+                    .with_labels(config.labels.clone())
                     .with_labels(result.labeled_addresses.clone())
                     .with_events(local_identifier.events().cloned())
                     .with_verbosity(verbosity);
 
                 // Signatures are of no value for gas reports
                 if !self.gas_report {
-                    let sig_identifier =
-                        SignaturesIdentifier::new(Config::foundry_cache_dir(), config.offline)?;
+                    let sig_identifier = SignaturesIdentifier::new(
+                        Config::foundry_cache_dir(),
+                        config.zk_signatures_offline(),
+                    )?;
                     builder = builder.with_signature_identifier(sig_identifier.clone());
                 }
 
@@ -389,6 +495,7 @@ impl TestArgs {
         let (tx, rx) = channel::<(String, SuiteResult)>();
 
         // Run tests
+        let known_errors = runner.errors.clone();
         let handle = tokio::task::spawn({
             let filter = filter.clone();
             async move { runner.test(&filter, tx, test_options).await }
@@ -397,14 +504,15 @@ impl TestArgs {
         let mut results = BTreeMap::new();
         let mut gas_report = GasReport::new(config.gas_reports, config.gas_reports_ignore);
         let sig_identifier =
-            SignaturesIdentifier::new(Config::foundry_cache_dir(), config.offline)?;
+            SignaturesIdentifier::new(Config::foundry_cache_dir(), config.zk_signatures_offline())?;
 
         let mut total_passed = 0;
         let mut total_failed = 0;
         let mut total_skipped = 0;
         let mut suite_results: Vec<TestOutcome> = Vec::new();
 
-        'outer: for (contract_name, suite_result) in rx {
+        'outer: for (contract_name, mut suite_result) in rx {
+            decode_custom_errors(&mut suite_result, &sig_identifier, known_errors.as_ref()).await;
             results.insert(contract_name.clone(), suite_result.clone());
 
             let mut tests = suite_result.test_results.clone();
@@ -420,7 +528,7 @@ impl TestArgs {
                 short_test_result(name, result);
 
                 // If the test failed, we want to stop processing the rest of the tests
-                if self.fail_fast && result.status == TestStatus::Failure {
+                if self.fail_fast && result.status == TestStatus::Failure && !result.quarantined {
                     break 'outer
                 }
 
@@ -443,6 +551,7 @@ impl TestArgs {
 
                 // Identify addresses in each trace
                 let mut builder = CallTraceDecoderBuilder::new()
+                    .with_labels(config.labels.clone())
                     .with_labels(result.labeled_addresses.iter().map(|(a, s)| (*a, s.clone())))
                     .with_events(local_identifier.events().cloned())
                     .with_verbosity(verbosity);
@@ -527,6 +636,7 @@ impl TestArgs {
                     total_skipped
                 )
             );
+            print_fork_retries(&results);
 
             if self.summary {
                 let mut summary_table = TestSummaryReporter::new(self.detailed);
@@ -640,9 +750,19 @@ impl TestOutcome {
         self.tests().filter(|(_, t)| t.status == TestStatus::Success)
     }
 
-    /// Iterator over all failing tests and their names
+    /// Iterator over all failing tests and their names, excluding quarantined ones
     pub fn failures(&self) -> impl Iterator<Item = (&String, &TestResult)> {
-        self.tests().filter(|(_, t)| t.status == TestStatus::Failure)
+        self.results.values().flat_map(|suite| suite.failures())
+    }
+
+    /// Iterator over all retried fork tests and their names
+    pub fn retried(&self) -> impl Iterator<Item = (&String, &TestResult)> {
+        self.results.values().flat_map(|suite| suite.retried())
+    }
+
+    /// Iterator over all quarantined fork tests and their names
+    pub fn quarantined(&self) -> impl Iterator<Item = (&String, &TestResult)> {
+        self.results.values().flat_map(|suite| suite.quarantined())
     }
 
     pub fn skips(&self) -> impl Iterator<Item = (&String, &TestResult)> {
@@ -723,6 +843,67 @@ impl TestOutcome {
 
 fn short_test_result(name: &str, result: &TestResult) {
     println!("{result} {name} {}", result.kind.report());
+    if let Some(location) = &result.revert_location {
+        println!("    {} {location}", Paint::red("reverted at"));
+    }
+    if let Some(path) = &result.replay_bundle {
+        println!("    {} {}", Paint::yellow("replay bundle"), path.display());
+    }
+}
+
+/// Reports whether the replayed test reproduced the recorded failure, and whether its execution
+/// diverged from the recorded one.
+fn check_replay(bundle: &ReplayBundle, outcome: &TestOutcome) {
+    let Some((_, result)) = outcome.tests().find(|(name, _)| **name == bundle.test) else {
+        eprintln!(
+            "{} the recorded test {}:{} was not found",
+            Paint::yellow("Warning:").bold(),
+            bundle.contract,
+            bundle.test
+        );
+        return
+    };
+    if result.status != TestStatus::Failure {
+        eprintln!("{} the recorded failure did not reproduce", Paint::yellow("Warning:").bold());
+    }
+    if let Some(call) = bundle.divergence(result) {
+        eprintln!(
+            "{} the replay diverged from the recording at cheatcode call #{call}",
+            Paint::yellow("Warning:").bold()
+        );
+    }
+}
+
+/// Decodes the custom errors failing tests reverted with, if they're missing from the compiled
+/// ABIs, using the signatures database, or only its cache when offline.
+///
+/// Only the failure reasons are decoded, the custom errors in the traces aren't.
+async fn decode_custom_errors(
+    suite: &mut SuiteResult,
+    identifier: &SingleSignaturesIdentifier,
+    known_errors: Option<&Abi>,
+) {
+    for result in suite.test_results.values_mut().filter(|result| result.status.is_failure()) {
+        let Some((selector, data)) = result
+            .revert_data
+            .as_deref()
+            .and_then(|revert_data| unknown_custom_error(revert_data, known_errors))
+        else {
+            continue
+        };
+        if let Some(func) = identifier.write().await.identify_cached_function(&selector).await {
+            result.reason = Some(format_custom_error(&func, data));
+        }
+    }
+}
+
+/// Formats a custom error identified as `func`, decoding its arguments if they match its
+/// signature.
+fn format_custom_error(func: &Function, data: &[u8]) -> String {
+    match func.abi_decode_input(data, false) {
+        Ok(args) => format!("{}({})", func.name, args.iter().map(format_token).format(", ")),
+        Err(_) => format!("{}:{}", func.signature(), hex::encode(data)),
+    }
 }
 
 /// Formats the aggregated summary of all test suites into a string (for printing).
@@ -743,6 +924,39 @@ fn format_aggregated_summary(
     )
 }
 
+/// Prints the fork tests that were retried or quarantined, if any.
+fn print_fork_retries(results: &BTreeMap<String, SuiteResult>) {
+    let retried: Vec<_> = results
+        .iter()
+        .flat_map(|(suite, result)| result.retried().map(move |(name, test)| (suite, name, test)))
+        .collect();
+    if !retried.is_empty() {
+        println!("\nRetried fork tests:");
+        for (suite, name, test) in retried {
+            let status = if test.quarantined {
+                Paint::yellow("quarantined")
+            } else if test.status == TestStatus::Failure {
+                Paint::red("failed")
+            } else {
+                Paint::green("passed")
+            };
+            let term = if test.retries > 1 { "retries" } else { "retry" };
+            println!("  {suite}:{name} {status} after {} {term}", test.retries);
+        }
+    }
+
+    let quarantined: Vec<_> = results
+        .iter()
+        .flat_map(|(suite, result)| result.quarantined().map(move |(name, _)| (suite, name)))
+        .collect();
+    if !quarantined.is_empty() {
+        println!("\n{}", Paint::yellow("Quarantined fork tests, not failing the run:"));
+        for (suite, name) in quarantined {
+            println!("  {suite}:{name}");
+        }
+    }
+}
+
 /// Lists all matching tests
 fn list(
     runner: MultiContractRunner,
@@ -768,8 +982,52 @@ fn list(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloy_primitives::Address;
     use foundry_config::Chain;
 
+    #[test]
+    fn decodes_custom_errors() {
+        let func = foundry_common::abi::get_func("Insufficient(uint256,address)").unwrap();
+        let revert_data = [
+            func.selector().as_slice(),
+            &func
+                .abi_encode_input(&[U256::from(1).into(), Address::with_last_byte(2).into()])
+                .unwrap(),
+        ]
+        .concat();
+
+        let (selector, data) = unknown_custom_error(&revert_data, None).unwrap();
+        assert_eq!(selector, func.selector().0);
+        assert_eq!(
+            format_custom_error(&func, data),
+            "Insufficient(1, 0x0000000000000000000000000000000000000002)"
+        );
+        assert_eq!(
+            format_custom_error(&func, &data[..32]),
+            format!("Insufficient(uint256,address):{}", hex::encode(&data[..32]))
+        );
+
+        // errors of the compiled contracts are already decoded
+        let known: Abi = serde_json::from_str(
+            r#"[{"type":"error","name":"Insufficient","inputs":[{"name":"","type":"uint256"},{"name":"","type":"address"}]}]"#,
+        )
+        .unwrap();
+        assert_eq!(unknown_custom_error(&revert_data, Some(&known)), None);
+
+        // a custom error without arguments is valid UTF-8, but still isn't a reason string
+        let unauthorized = [0x52, 0x41, 0x55, 0x54];
+        assert_eq!(unknown_custom_error(&unauthorized, None), Some((unauthorized, &[][..])));
+
+        // `Error(string)` is decoded without the signatures database
+        let error = foundry_common::abi::get_func("Error(string)").unwrap();
+        let revert = [
+            error.selector().as_slice(),
+            &error.abi_encode_input(&["assertion failed".to_string().into()]).unwrap(),
+        ]
+        .concat();
+        assert_eq!(unknown_custom_error(&revert, None), None);
+    }
+
     #[test]
     fn watch_parse() {
         let args: TestArgs = TestArgs::parse_from(["foundry-cli", "-vw"]);
@@ -0,0 +1,67 @@
+//! Folding of zkEVM execution samples into flamegraph stacks.
+
+use era_cheatcodes::profiler::ProfiledFrame;
+use foundry_common::zk_utils::source_map::ContractSourceMaps;
+use std::collections::{BTreeMap, HashMap};
+
+/// Name of the frames running code that wasn't compiled from the project, e.g. system contracts.
+const UNKNOWN_FRAME: &str = "[unknown]";
+
+/// Folds the sampled stacks into the `frame;frame count` lines of the collapsed stack format
+/// understood by flamegraph tools.
+///
+/// Frames are named after their contract and source line. The unknown frames the stacks start
+/// with, i.e. the bootloader and the system contracts calling into the tests, are left out.
+pub fn folded_stacks(
+    samples: &HashMap<Vec<ProfiledFrame>, u64>,
+    source_maps: &ContractSourceMaps,
+) -> Vec<String> {
+    let mut folded = BTreeMap::<String, u64>::new();
+    for (stack, count) in samples {
+        let names = stack
+            .iter()
+            .map(|frame| source_maps.frame_name(&frame.bytecode_hash, frame.pc))
+            .skip_while(Option::is_none)
+            .map(|name| name.unwrap_or_else(|| UNKNOWN_FRAME.to_string()).replace(';', ":"))
+            .collect::<Vec<_>>();
+        let key = if names.is_empty() { UNKNOWN_FRAME.to_string() } else { names.join(";") };
+        *folded.entry(key).or_default() += count;
+    }
+    folded.into_iter().map(|(stack, count)| format!("{stack} {count}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_common::zk_utils::source_map::EraSourceMap;
+    use zksync_types::H256;
+
+    #[test]
+    fn folds_samples_by_source_line() {
+        let assembly = "\t.file\t1 \"src/Counter.sol\"\n\t.text\n\t.loc\t1 7 5\n\tadd\tr1, r0, r2\n\t.loc\t1 7 9\n\tret\n";
+        let mut source_maps = ContractSourceMaps::default();
+        source_maps.insert(
+            H256::repeat_byte(1),
+            "Counter".to_string(),
+            EraSourceMap::parse(assembly),
+        );
+
+        let bootloader = ProfiledFrame { bytecode_hash: H256::repeat_byte(9), pc: 3 };
+        let frame = |pc| ProfiledFrame { bytecode_hash: H256::repeat_byte(1), pc };
+        let samples = HashMap::from([
+            (vec![bootloader, frame(0)], 2),
+            (vec![bootloader, frame(1)], 3),
+            (vec![bootloader, frame(1), bootloader], 1),
+            (vec![bootloader], 4),
+        ]);
+
+        assert_eq!(
+            folded_stacks(&samples, &source_maps),
+            vec![
+                "Counter (src/Counter.sol:7) 5",
+                "Counter (src/Counter.sol:7);[unknown] 1",
+                "[unknown] 4",
+            ]
+        );
+    }
+}
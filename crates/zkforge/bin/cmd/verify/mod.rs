@@ -3,6 +3,7 @@ use alloy_primitives::Address;
 use clap::{Parser, ValueHint};
 use eyre::Result;
 use foundry_cli::{opts::EtherscanOpts, utils::LoadConfig};
+use foundry_common::zksolc_manager::DEFAULT_ZKSOLC_VERSION;
 use foundry_compilers::info::ContractInfo;
 use foundry_config::{figment, impl_figment_convert, impl_figment_convert_cast, Config};
 use provider::VerificationProviderType;
@@ -17,6 +18,9 @@ use provider::VerificationProvider;
 
 mod sourcify;
 
+mod zksync;
+use zksync::ZkSyncVerificationProvider;
+
 /// Verification provider arguments
 #[derive(Debug, Clone, Parser)]
 pub struct VerifierArgs {
@@ -27,11 +31,47 @@ pub struct VerifierArgs {
     /// The verifier URL, if using a custom provider
     #[clap(long, help_heading = "Verifier options", env = "VERIFIER_URL")]
     pub verifier_url: Option<String>,
+
+    /// Verify with the zkSync explorer.
+    ///
+    /// The contract is recompiled with zksolc and only submitted if its bytecode hash matches the
+    /// code deployed at the address, which is fetched from the configured RPC endpoint.
+    #[clap(long, help_heading = "Verifier options")]
+    pub zksync: bool,
+
+    /// The zksolc version the contract was compiled with, when verifying with `--zksync`.
+    #[clap(
+        long,
+        help_heading = "Verifier options",
+        value_name = "VERSION",
+        default_value = DEFAULT_ZKSOLC_VERSION
+    )]
+    pub zksolc_version: String,
 }
 
 impl Default for VerifierArgs {
     fn default() -> Self {
-        VerifierArgs { verifier: VerificationProviderType::Etherscan, verifier_url: None }
+        VerifierArgs {
+            verifier: VerificationProviderType::Etherscan,
+            verifier_url: None,
+            zksync: false,
+            zksolc_version: DEFAULT_ZKSOLC_VERSION.to_string(),
+        }
+    }
+}
+
+impl VerifierArgs {
+    /// Returns the `VerificationProvider` to use, `--zksync` taking precedence over `--verifier`.
+    pub fn client(&self, key: &Option<String>) -> Result<Box<dyn VerificationProvider>> {
+        if self.zksync {
+            return Ok(Box::<ZkSyncVerificationProvider>::default())
+        }
+        self.verifier.client(key)
+    }
+
+    /// Whether the verification provider requires an Etherscan API key.
+    pub fn requires_etherscan_key(&self) -> bool {
+        !self.zksync && self.verifier == VerificationProviderType::Etherscan
     }
 }
 
@@ -145,7 +185,7 @@ impl VerifyArgs {
 
         let verifier_url = self.verifier.verifier_url.clone();
         println!("Start verifying contract `{}` deployed on {chain}", self.address);
-        self.verifier.verifier.client(&self.etherscan.key)?.verify(self).await.map_err(|err| {
+        self.verifier.client(&self.etherscan.key)?.verify(self).await.map_err(|err| {
             if let Some(verifier_url) = verifier_url {
                  match Url::parse(&verifier_url) {
                     Ok(url) => {
@@ -169,7 +209,7 @@ impl VerifyArgs {
 
     /// Returns the configured verification provider
     pub fn verification_provider(&self) -> Result<Box<dyn VerificationProvider>> {
-        self.verifier.verifier.client(&self.etherscan.key)
+        self.verifier.client(&self.etherscan.key)
     }
 }
 
@@ -181,6 +221,8 @@ pub struct VerifyCheckArgs {
     /// For Etherscan - Submission GUID.
     ///
     /// For Sourcify - Contract Address.
+    ///
+    /// For zkSync - Verification ID.
     id: String,
 
     #[clap(flatten)]
@@ -199,7 +241,7 @@ impl VerifyCheckArgs {
     /// Run the verify command to submit the contract's source code for verification on etherscan
     pub async fn run(self) -> Result<()> {
         println!("Checking verification status on {}", self.etherscan.chain.unwrap_or_default());
-        self.verifier.verifier.client(&self.etherscan.key)?.check(self).await
+        self.verifier.client(&self.etherscan.key)?.check(self).await
     }
 }
 
@@ -0,0 +1,331 @@
+use super::{provider::VerificationProvider, VerifyArgs, VerifyCheckArgs};
+use crate::cmd::retry::RETRY_CHECK_ON_VERIFY;
+use alloy_json_abi::Function;
+use async_trait::async_trait;
+use eyre::{eyre, Context, Result};
+use foundry_cli::utils::{read_constructor_args_file, LoadConfig};
+use foundry_common::{
+    abi::encode_function_args, retry::Retry, try_get_http_provider, types::ToEthers,
+    zk_compile::ZkSolc, zk_utils::factory_deps::PackedEraBytecode,
+    zksolc_manager::setup_zksolc_manager,
+};
+use foundry_compilers::{ArtifactId, ConfigurableContractArtifact, Project, Solc};
+use foundry_config::{Chain, Config, SolcReq};
+use futures::FutureExt;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use zksync_types::H256;
+use zksync_utils::bytecode::hash_bytecode;
+
+/// zkSync Era mainnet contract verification API
+pub static ZKSYNC_MAINNET_VERIFIER_URL: &str =
+    "https://zksync2-mainnet-explorer.zksync.io/contract_verification";
+
+/// zkSync Era sepolia testnet contract verification API
+pub static ZKSYNC_SEPOLIA_VERIFIER_URL: &str =
+    "https://explorer.sepolia.era.zksync.dev/contract_verification";
+
+/// The type that can verify a contract with the zkSync explorer.
+///
+/// The contract is recompiled locally with zksolc, and only submitted if its bytecode hash matches
+/// the one of the code deployed at the given address.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ZkSyncVerificationProvider;
+
+#[async_trait]
+impl VerificationProvider for ZkSyncVerificationProvider {
+    async fn preflight_check(&mut self, args: VerifyArgs) -> Result<()> {
+        verifier_url(&args.verifier.verifier_url, args.etherscan.chain.unwrap_or_default())?;
+        let _ = self.prepare_request(&args, false).await?;
+        Ok(())
+    }
+
+    async fn verify(&mut self, args: VerifyArgs) -> Result<()> {
+        let url =
+            verifier_url(&args.verifier.verifier_url, args.etherscan.chain.unwrap_or_default())?;
+        let body = self.prepare_request(&args, true).await?;
+
+        trace!(target: "forge::verify", contract = %body.contract_name, "submitting zkSync verification request");
+
+        let client = reqwest::Client::new();
+        let retry: Retry = args.retry.into();
+        let id = retry
+            .run_async(|| {
+                async {
+                    println!(
+                        "\nSubmitting verification for [{}] {}.",
+                        body.contract_name, body.contract_address
+                    );
+                    let response = client.post(&url).json(&body).send().await?;
+
+                    let status = response.status();
+                    let text = response.text().await?;
+                    if !status.is_success() {
+                        eyre::bail!(
+                            "zkSync verification request for address ({}) failed with status code {status}\nDetails: {text}",
+                            body.contract_address
+                        );
+                    }
+
+                    text.trim().parse::<u64>().wrap_err_with(|| {
+                        format!("Unexpected response from the zkSync verifier: {text}")
+                    })
+                }
+                .boxed()
+            })
+            .await?;
+
+        println!("Submitted contract for verification:\n\tVerification ID: `{id}`");
+
+        if args.watch {
+            let check_args = VerifyCheckArgs {
+                id: id.to_string(),
+                etherscan: args.etherscan,
+                retry: RETRY_CHECK_ON_VERIFY,
+                verifier: args.verifier,
+            };
+            return self.check(check_args).await
+        }
+
+        Ok(())
+    }
+
+    async fn check(&self, args: VerifyCheckArgs) -> Result<()> {
+        let url =
+            verifier_url(&args.verifier.verifier_url, args.etherscan.chain.unwrap_or_default())?;
+        let url = format!("{}/{}", url.trim_end_matches('/'), args.id);
+
+        let retry: Retry = args.retry.into();
+        retry
+            .run_async(|| {
+                async {
+                    let response = reqwest::get(&url).await?;
+                    if !response.status().is_success() {
+                        eyre::bail!(
+                            "Failed to request verification status with status code {}",
+                            response.status()
+                        );
+                    }
+                    let resp = response.json::<ZkSyncVerificationStatus>().await?;
+
+                    trace!(target: "forge::verify", ?resp, "Received verification response");
+
+                    match resp.status.as_str() {
+                        "queued" | "in_progress" => {
+                            Err(eyre!("Verification is still pending ({})...", resp.status))
+                        }
+                        "successful" => {
+                            println!("Contract successfully verified");
+                            Ok(())
+                        }
+                        "failed" => {
+                            println!(
+                                "Contract failed to verify.\nDetails: `{}`",
+                                resp.error.unwrap_or_default()
+                            );
+                            std::process::exit(1);
+                        }
+                        status => {
+                            eprintln!("Unknown status from the zkSync verifier. Status: {status}");
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                .boxed()
+            })
+            .await
+            .wrap_err("Checking verification result failed:")
+    }
+}
+
+impl ZkSyncVerificationProvider {
+    /// Recompiles the target contract with zksolc and builds the verification request.
+    ///
+    /// If `check_bytecode` is set, the bytecode hash of the compiled contract must match the hash
+    /// of the code deployed at the target address.
+    async fn prepare_request(
+        &self,
+        args: &VerifyArgs,
+        check_bytecode: bool,
+    ) -> Result<ZkSyncVerifyRequest> {
+        let mut config = args.try_load_config_emit_warnings()?;
+        config.libraries.extend(args.libraries.clone());
+
+        let mut project = config.project()?;
+        project.paths.artifacts = project.paths.root.join("zkout");
+        let solc_version = compiler_version(args, &config, &project)?;
+        let root = project.root().clone();
+
+        let mut zksolc_cfg = config.zk_solc_config().map_err(|e| eyre!(e))?;
+        zksolc_cfg.compiler_path =
+            setup_zksolc_manager(args.verifier.zksolc_version.clone()).await?;
+        let optimization_used = zksolc_cfg.settings.optimizer.enabled.unwrap_or_default();
+        let is_system = zksolc_cfg.settings.is_system;
+        let mut zksolc = ZkSolc::new(zksolc_cfg, project);
+        let (output, _) =
+            zksolc.compile().map_err(|e| eyre!("Failed to compile with zksolc: {}", e))?;
+
+        let (id, artifact) = output
+            .into_artifacts()
+            .find(|(id, _)| {
+                id.name == args.contract.name &&
+                    args.contract.path.as_ref().map_or(true, |path| {
+                        id.source.ends_with(path) || root.join(path) == id.source
+                    })
+            })
+            .ok_or_else(|| eyre!("Could not find zksolc artifact for `{}`", args.contract.name))?;
+
+        if check_bytecode {
+            let bytecode = artifact
+                .deployed_bytecode
+                .as_ref()
+                .and_then(|code| code.bytecode.as_ref())
+                .and_then(|code| code.object.as_bytes())
+                .ok_or_else(|| eyre!("No bytecode found for `{}`", args.contract.name))?;
+            let compiled = hash_bytecode(&PackedEraBytecode::from_vec(bytecode).bytecode());
+            let deployed = deployed_bytecode_hash(args, &config).await?;
+            if compiled != deployed {
+                eyre::bail!(
+                    "The bytecode of `{}` doesn't match the code deployed at {}.\nCompiled bytecode hash: {compiled:?}\nDeployed bytecode hash: {deployed:?}\nMake sure the contract is compiled with the same zksolc version and settings it was deployed with.",
+                    args.contract.name,
+                    args.address
+                )
+            }
+        }
+
+        let source = root.join(&id.source);
+        let source_code = serde_json::to_value(zksolc.standard_json_input(&source)?)?;
+        let relative = source.strip_prefix(&root).unwrap_or(&source).to_path_buf();
+
+        Ok(ZkSyncVerifyRequest {
+            contract_address: args.address.to_string(),
+            source_code,
+            code_format: "solidity-standard-json-input".to_string(),
+            contract_name: format!("{}:{}", relative.display(), id.name),
+            compiler_solc_version: solc_version.to_string(),
+            compiler_zksolc_version: args.verifier.zksolc_version.clone(),
+            optimization_used,
+            constructor_arguments: format!(
+                "0x{}",
+                constructor_args(args, &id, &artifact)?
+                    .unwrap_or_default()
+                    .trim_start_matches("0x")
+            ),
+            is_system,
+        })
+    }
+}
+
+/// Returns the verification API of the chain, unless overridden with `--verifier-url`.
+fn verifier_url(verifier_url: &Option<String>, chain: Chain) -> Result<String> {
+    if let Some(url) = verifier_url {
+        return Ok(url.clone())
+    }
+    match chain.id() {
+        324 => Ok(ZKSYNC_MAINNET_VERIFIER_URL.to_string()),
+        300 => Ok(ZKSYNC_SEPOLIA_VERIFIER_URL.to_string()),
+        id => eyre::bail!(
+            "No zkSync verifier known for chain {id}, specify one with `--verifier-url`"
+        ),
+    }
+}
+
+/// Fetches the bytecode hash of the code deployed at the target address.
+async fn deployed_bytecode_hash(args: &VerifyArgs, config: &Config) -> Result<H256> {
+    let provider = try_get_http_provider(config.get_rpc_url_or_localhost_http()?)?;
+    let code = provider.get_code(args.address.to_ethers(), None).await?;
+    if code.is_empty() {
+        eyre::bail!("No contract deployed at {}", args.address)
+    }
+    Ok(hash_bytecode(&code))
+}
+
+/// Parse the solc version zksolc compiled with.
+/// The priority desc:
+///     1. Through CLI arg `--compiler-version`
+///     2. `solc` defined in foundry.toml
+///     3. The version resolved for the project.
+fn compiler_version(args: &VerifyArgs, config: &Config, project: &Project) -> Result<Version> {
+    if let Some(ref version) = args.compiler_version {
+        return Ok(version.trim_start_matches('v').parse()?)
+    }
+
+    if let Some(SolcReq::Local(solc)) = &config.solc {
+        if solc.is_file() {
+            return Ok(Solc::new(solc).version()?)
+        }
+    }
+    if let Some(SolcReq::Version(version)) = &config.solc {
+        return Ok(version.to_owned())
+    }
+
+    let version = project.solc.version()?;
+    Ok(Version::new(version.major, version.minor, version.patch))
+}
+
+/// Return the optional encoded constructor arguments. If the path to constructor arguments was
+/// provided, read them and encode them with the constructor of the compiled contract.
+fn constructor_args(
+    args: &VerifyArgs,
+    id: &ArtifactId,
+    artifact: &ConfigurableContractArtifact,
+) -> Result<Option<String>> {
+    let Some(ref constructor_args_path) = args.constructor_args_path else {
+        return Ok(args.constructor_args.clone())
+    };
+
+    let constructor =
+        artifact.abi.as_ref().and_then(|abi| abi.constructor()).ok_or_else(|| {
+            eyre!("Can't retrieve constructor info from the ABI of `{}`.", id.name)
+        })?;
+    #[allow(deprecated)]
+    let func = Function {
+        name: "constructor".to_string(),
+        inputs: constructor.inputs.clone(),
+        outputs: vec![],
+        state_mutability: alloy_json_abi::StateMutability::NonPayable,
+    };
+    let encoded_args = encode_function_args(
+        &func,
+        read_constructor_args_file(constructor_args_path.to_path_buf())?,
+    )?;
+    let encoded_args = hex::encode(encoded_args);
+    Ok(Some(encoded_args[8..].into()))
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ZkSyncVerifyRequest {
+    contract_address: String,
+    source_code: serde_json::Value,
+    code_format: String,
+    contract_name: String,
+    compiler_solc_version: String,
+    compiler_zksolc_version: String,
+    optimization_used: bool,
+    constructor_arguments: String,
+    is_system: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ZkSyncVerificationStatus {
+    status: String,
+    error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_verifier_url() {
+        assert_eq!(verifier_url(&None, 324u64.into()).unwrap(), ZKSYNC_MAINNET_VERIFIER_URL);
+        assert_eq!(verifier_url(&None, 300u64.into()).unwrap(), ZKSYNC_SEPOLIA_VERIFIER_URL);
+        assert!(verifier_url(&None, 1u64.into()).is_err());
+        assert_eq!(
+            verifier_url(&Some("http://localhost:3070".to_string()), 1u64.into()).unwrap(),
+            "http://localhost:3070"
+        );
+    }
+}
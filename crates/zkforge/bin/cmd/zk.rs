@@ -0,0 +1,62 @@
+use clap::Parser;
+use comfy_table::Table;
+use era_cheatcodes::support::{cheatcode_support, SupportStatus};
+use eyre::Result;
+
+/// CLI arguments for `forge zk`.
+#[derive(Debug, Clone, Parser)]
+pub enum ZkSubcommands {
+    /// List the cheatcodes of the `Vm` interface and whether they are supported in the zkEVM
+    #[clap(visible_alias = "cc")]
+    Cheatcodes {
+        /// Only list cheatcodes with the given status.
+        #[clap(long, value_parser = ["supported", "partial", "unsupported"])]
+        status: Option<String>,
+
+        /// Print the report as JSON.
+        #[clap(long)]
+        json: bool,
+    },
+}
+
+impl ZkSubcommands {
+    pub fn run(self) -> Result<()> {
+        match self {
+            ZkSubcommands::Cheatcodes { status, json } => {
+                let support = cheatcode_support();
+                let count = |status| support.iter().filter(|c| c.status == status).count();
+                let (supported, partial, unsupported) = (
+                    count(SupportStatus::Supported),
+                    count(SupportStatus::Partial),
+                    count(SupportStatus::Unsupported),
+                );
+
+                let listed = support
+                    .iter()
+                    .filter(|c| status.as_ref().map_or(true, |s| c.status.to_string() == *s))
+                    .collect::<Vec<_>>();
+
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&listed)?);
+                    return Ok(())
+                }
+
+                let mut table = Table::new();
+                table.set_header(["Cheatcode", "Signature", "Status"]);
+                for cheatcode in listed {
+                    table.add_row([
+                        cheatcode.id,
+                        cheatcode.signature,
+                        &cheatcode.status.to_string(),
+                    ]);
+                }
+                println!("{table}");
+                println!(
+                    "{} cheatcodes: {supported} supported, {partial} partially supported, {unsupported} unsupported",
+                    support.len()
+                );
+                Ok(())
+            }
+        }
+    }
+}
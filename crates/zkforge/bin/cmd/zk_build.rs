@@ -28,7 +28,7 @@
 use super::{install, watch::WatchArgs};
 use clap::Parser;
 use foundry_cli::{opts::CoreBuildArgs, utils::LoadConfig};
-use foundry_common::zksolc_manager::setup_zksolc_manager;
+use foundry_common::{zk_utils::size::ZkSizeReport, zksolc_manager::setup_zksolc_manager};
 use foundry_compilers::Project;
 use foundry_config::{
     figment::{
@@ -81,7 +81,7 @@ pub struct ZkBuildArgs {
     #[serde(skip)]
     pub names: bool,
 
-    /// Print compiled contract sizes.
+    /// Print compiled contract sizes, failing if any exceeds the EraVM bytecode limits.
     #[clap(long)]
     #[serde(skip)]
     pub sizes: bool,
@@ -144,7 +144,24 @@ impl ZkBuildArgs {
         zksolc_cfg.compiler_path = compiler_path;
 
         // TODO: add filter support
-        let _ = foundry_common::zk_compile::compile_smart_contracts(zksolc_cfg, project);
+        let compiled = foundry_common::zk_compile::compile_smart_contracts(zksolc_cfg, project);
+        if self.sizes {
+            let (output, _) = compiled?;
+            let size_report = ZkSizeReport::new(&output);
+            println!("{size_report}");
+
+            // fail if any contract exceeds the EraVM limits, excluding test contracts.
+            let violations = size_report.violations();
+            if !violations.is_empty() {
+                let report = violations
+                    .iter()
+                    .flat_map(|(name, violations)| {
+                        violations.iter().map(move |violation| format!("\n  - {name}: {violation}"))
+                    })
+                    .collect::<String>();
+                eyre::bail!("Some contracts exceed the EraVM limits:{report}")
+            }
+        }
         Ok(())
     }
     /// Returns the `Project` for the current workspace
@@ -76,6 +76,7 @@ fn main() -> Result<()> {
             config.project()?.cleanup()?;
             Ok(())
         }
+        Subcommands::Mutate(cmd) => utils::block_on(cmd.run()),
         Subcommands::Snapshot(cmd) => {
             if cmd.is_watch() {
                 utils::block_on(watch::watch_snapshot(cmd))
@@ -101,5 +102,6 @@ fn main() -> Result<()> {
         Subcommands::Generate(cmd) => match cmd.sub {
             GenerateSubcommands::Test(cmd) => cmd.run(),
         },
+        Subcommands::Zk { command } => command.run(),
     }
 }
@@ -10,12 +10,14 @@ use crate::cmd::{
     init::InitArgs,
     inspect,
     install::InstallArgs,
+    mutate,
     remappings::RemappingArgs,
     remove::RemoveArgs,
     script::ScriptArgs,
     selectors::SelectorsSubcommands,
     snapshot, test, tree, update,
     verify::{VerifyArgs, VerifyCheckArgs},
+    zk::ZkSubcommands,
     zk_build::ZkBuildArgs,
     zk_create::ZkCreateArgs,
 };
@@ -120,6 +122,9 @@ pub enum Subcommands {
     /// Manage the Foundry cache.
     Cache(CacheArgs),
 
+    /// Run the zk test suite against mutants of the sources, to find the changes it misses.
+    Mutate(mutate::MutateArgs),
+
     /// Create a snapshot of each test's gas usage.
     #[clap(visible_alias = "s")]
     Snapshot(snapshot::SnapshotArgs),
@@ -164,4 +169,10 @@ pub enum Subcommands {
 
     /// Generate scaffold files.
     Generate(generate::GenerateArgs),
+
+    /// zkSync utilities
+    Zk {
+        #[clap(subcommand)]
+        command: ZkSubcommands,
+    },
 }
@@ -0,0 +1,133 @@
+//! Fuzz corpora: inputs of fuzz tests worth running again, i.e. the counterexamples of previous
+//! failures and the inputs that reached new coverage, persisted to disk and run before fuzzing on
+//! subsequent runs.
+//!
+//! A corpus is stored like the failures upstream persists for invariant tests, as a JSON list of
+//! [BaseCounterExample]s in a file named after the test, in a directory named after the contract.
+
+use alloy_dyn_abi::JsonAbiExt;
+use alloy_json_abi::Function;
+use alloy_primitives::Bytes;
+use eyre::Result;
+use foundry_evm::fuzz::BaseCounterExample;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// The VM the corpora are recorded with, as inputs reproducing a failure in one VM may not in
+/// another.
+const VM_TYPE: &str = "zkevm";
+
+/// The inputs recorded for a fuzz test.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FuzzCorpus {
+    /// The recorded inputs, in the order they were found.
+    inputs: Vec<BaseCounterExample>,
+}
+
+impl FuzzCorpus {
+    /// Returns the path of the corpus of a fuzz test in `dir`.
+    pub fn path(dir: &Path, contract: &str, test: &str) -> PathBuf {
+        dir.join(VM_TYPE).join(contract.replace(['/', '\\', ':'], "_")).join(test)
+    }
+
+    /// Loads the corpus stored at `path`, or an empty one if there is none or it can't be read.
+    pub fn load(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default()
+        }
+        foundry_common::fs::read_json_file(path).unwrap_or_else(|err| {
+            warn!(?path, %err, "failed to read fuzz corpus");
+            Self::default()
+        })
+    }
+
+    /// Returns the calldata of the recorded inputs.
+    pub fn calldata(&self) -> Vec<Bytes> {
+        self.inputs.iter().map(|input| input.calldata.clone()).collect()
+    }
+
+    /// Drops the inputs that can't be decoded as a call to `func` anymore, e.g. because its
+    /// parameters changed since they were recorded. Returns whether any input was dropped.
+    pub fn retain_valid(&mut self, func: &Function) -> bool {
+        let len = self.inputs.len();
+        self.inputs.retain(|input| is_valid_input(func, &input.calldata));
+        self.inputs.len() != len
+    }
+
+    /// Adds an input of `func` to the corpus, returns whether it wasn't already recorded.
+    pub fn add(&mut self, func: &Function, calldata: Bytes) -> bool {
+        if self.inputs.iter().any(|input| input.calldata == calldata) {
+            return false
+        }
+        self.inputs.push(BaseCounterExample {
+            sender: None,
+            addr: None,
+            calldata,
+            signature: Some(func.signature()),
+            contract_name: None,
+            traces: None,
+            args: vec![],
+        });
+        true
+    }
+
+    /// Writes the corpus to `path`, creating its directory as needed.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        foundry_common::fs::write_json_file(path, self)?;
+        Ok(())
+    }
+}
+
+/// Returns whether `calldata` calls `func` with arguments matching its parameters.
+fn is_valid_input(func: &Function, calldata: &[u8]) -> bool {
+    calldata.len() >= 4 &&
+        calldata[..4] == func.selector()[..] &&
+        func.abi_decode_input(&calldata[4..], false).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_dyn_abi::DynSolValue;
+
+    #[test]
+    fn roundtrips_corpus() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = FuzzCorpus::path(dir.path(), "test/Swap.t.sol:SwapTest", "testFuzzSwap");
+        assert!(path.ends_with("zkevm/test_Swap.t.sol_SwapTest/testFuzzSwap"));
+        assert!(FuzzCorpus::load(&path).calldata().is_empty());
+
+        let func = Function::parse("testFuzzSwap(uint256)").unwrap();
+        let calldata = Bytes::from(func.abi_encode_input(&[DynSolValue::from(1u64)]).unwrap());
+        let mut corpus = FuzzCorpus::default();
+        assert!(corpus.add(&func, calldata.clone()));
+        assert!(!corpus.add(&func, calldata.clone()));
+        corpus.save(&path).unwrap();
+        assert_eq!(FuzzCorpus::load(&path).calldata(), [calldata]);
+    }
+
+    #[test]
+    fn drops_stale_inputs() {
+        let func = Function::parse("testFuzzSwap(uint256)").unwrap();
+        let valid = Bytes::from(func.abi_encode_input(&[DynSolValue::from(1u64)]).unwrap());
+
+        let mut corpus = FuzzCorpus::default();
+        corpus.add(&func, valid.clone());
+        assert!(!corpus.retain_valid(&func));
+
+        // the parameters of the test changed
+        let old = Function::parse("testFuzzSwap(uint8,uint8)").unwrap();
+        corpus.add(&old, Bytes::from(old.abi_encode_input(&[1u8.into(), 2u8.into()]).unwrap()));
+        // the parameters didn't change, but the arguments are too short
+        let mut truncated = valid.to_vec();
+        truncated.truncate(20);
+        corpus.add(&func, truncated.into());
+
+        assert!(corpus.retain_valid(&func));
+        assert_eq!(corpus.calldata(), [valid]);
+    }
+}
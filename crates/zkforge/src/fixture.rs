@@ -0,0 +1,278 @@
+//! Setup fixtures: snapshots of the state produced by deploying a test contract and running its
+//! `setUp()` function, persisted to disk so that subsequent runs can skip both when nothing
+//! changed.
+
+use crate::genesis::GenesisAccount;
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use eyre::Result;
+use foundry_common::conversion_utils::{h160_to_address, h256_to_b256};
+use foundry_evm::{executors::Executor, fork::CreateFork, inspectors::CheatsConfig};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+use zksync_types::H256;
+
+/// A modified storage slot.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureSlot {
+    pub address: Address,
+    pub key: B256,
+    pub value: B256,
+}
+
+/// The state of the VM right after a test contract's `setUp()`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetupFixture {
+    /// Hash of everything the setup depends on, see [SetupFixture::hash_inputs].
+    pub hash: B256,
+    /// The address of the deployed test contract.
+    pub address: Address,
+    /// Addresses labeled during setup.
+    pub labeled_addresses: BTreeMap<Address, String>,
+    /// All storage slots modified since the executor was created.
+    pub storage: Vec<FixtureSlot>,
+    /// Bytecodes deployed during setup.
+    pub bytecodes: BTreeMap<B256, Bytes>,
+    /// Known codes recorded during setup.
+    pub known_codes: BTreeMap<B256, Bytes>,
+    /// The block number after setup.
+    pub block_number: U256,
+    /// The block timestamp after setup.
+    pub block_timestamp: U256,
+    /// The chain id after setup.
+    pub chain_id: u64,
+    /// The environment variables read by cheatcodes so far when the fixture was captured, with
+    /// their values. The fixture is stale once any of them changes.
+    pub env: BTreeMap<String, Option<String>>,
+}
+
+impl SetupFixture {
+    /// Hashes the environment the setups of all test contracts share: the fork they start from
+    /// and the settings of the cheatcodes. The environment variables read with `vm.env*` are
+    /// recorded in the fixtures instead, see [SetupFixture::env].
+    ///
+    /// Returns `None` if `vm.ffi` is enabled, as the output of the commands it runs can't be
+    /// known without running them, so setups can't be cached.
+    pub fn hash_environment(fork: Option<&CreateFork>, config: &CheatsConfig) -> Option<B256> {
+        if config.ffi {
+            return None
+        }
+
+        let mut bytes = Vec::new();
+        if let Some(fork) = fork {
+            bytes.extend_from_slice(fork.url.as_bytes());
+            bytes.extend_from_slice(
+                &fork.evm_opts.fork_block_number.unwrap_or_default().to_be_bytes(),
+            );
+        }
+        bytes.extend(serde_json::to_vec(&config.fs_permissions).unwrap_or_default());
+        bytes.extend(serde_json::to_vec(&config.cheatcode_access).unwrap_or_default());
+        bytes.extend_from_slice(config.root.to_string_lossy().as_bytes());
+        for (name, url) in config.rpc_endpoints.iter() {
+            bytes.extend_from_slice(name.as_bytes());
+            bytes.extend_from_slice(url.as_deref().unwrap_or_default().as_bytes());
+        }
+        Some(keccak256(bytes))
+    }
+
+    /// Hashes the inputs of a test contract's setup: its code, the libraries deployed before it,
+    /// the sender and initial balance, the genesis accounts resolved from `zk_genesis`, the block
+    /// the executor starts from and the shared environment, see [SetupFixture::hash_environment].
+    pub fn hash_inputs(
+        executor: &Executor,
+        code: &Bytes,
+        libs: &[Bytes],
+        sender: Address,
+        initial_balance: U256,
+        genesis: &[GenesisAccount],
+        environment: B256,
+    ) -> B256 {
+        let mut bytes = environment.to_vec();
+        for account in genesis {
+            account.hash_into(&mut bytes);
+        }
+        bytes.extend_from_slice(keccak256(code).as_slice());
+        for lib in libs {
+            bytes.extend_from_slice(keccak256(lib).as_slice());
+        }
+        bytes.extend_from_slice(sender.as_slice());
+        bytes.extend_from_slice(&initial_balance.to_be_bytes::<32>());
+        bytes.extend_from_slice(&executor.env.block.number.to_be_bytes::<32>());
+        bytes.extend_from_slice(&executor.env.block.timestamp.to_be_bytes::<32>());
+        bytes.extend_from_slice(&executor.env.cfg.chain_id.to_be_bytes());
+        keccak256(bytes)
+    }
+
+    /// Captures the current state of `executor`, which must have just run `setUp()` on the
+    /// test contract at `address`.
+    pub fn capture(
+        executor: &Executor,
+        hash: B256,
+        address: Address,
+        labeled_addresses: BTreeMap<Address, String>,
+    ) -> Self {
+        let modifications = &executor.inspector.storage_modifications;
+        let mut storage = modifications
+            .keys()
+            .iter()
+            .map(|(key, value)| FixtureSlot {
+                address: h160_to_address(*key.address()),
+                key: h256_to_b256(*key.key()),
+                value: h256_to_b256(*value),
+            })
+            .collect::<Vec<_>>();
+        storage.sort_by(|a, b| (a.address, a.key).cmp(&(b.address, b.key)));
+
+        let to_map = |codes: &HashMap<H256, Vec<u8>>| {
+            codes
+                .iter()
+                .map(|(hash, code)| (h256_to_b256(*hash), Bytes::from(code.clone())))
+                .collect()
+        };
+
+        Self {
+            hash,
+            address,
+            labeled_addresses,
+            storage,
+            bytecodes: to_map(modifications.bytecodes()),
+            known_codes: to_map(modifications.known_codes()),
+            block_number: executor.env.block.number,
+            block_timestamp: executor.env.block.timestamp,
+            chain_id: executor.env.cfg.chain_id,
+            env: foundry_common::env_reads::snapshot(),
+        }
+    }
+
+    /// Applies the fixture to `executor`, recording the restored storage as modifications so it
+    /// persists across forks.
+    pub fn apply(&self, executor: &mut Executor) -> Result<()> {
+        for slot in &self.storage {
+            executor.set_storage(
+                slot.address,
+                U256::from_be_bytes(slot.key.0),
+                U256::from_be_bytes(slot.value.0),
+            )?;
+        }
+
+        let modifications = &mut executor.inspector.storage_modifications;
+        for (hash, code) in &self.bytecodes {
+            modifications.insert_bytecode(H256(hash.0), code.to_vec());
+        }
+        for (hash, code) in &self.known_codes {
+            modifications.insert_known_code(H256(hash.0), code.to_vec());
+        }
+
+        executor.env.block.number = self.block_number;
+        executor.env.block.timestamp = self.block_timestamp;
+        executor.env.cfg.chain_id = self.chain_id;
+        executor.backend.set_test_contract(self.address);
+        Ok(())
+    }
+
+    /// Loads the fixture stored at `path`, if it exists and was created from the same inputs and
+    /// environment variables.
+    pub fn load(path: &Path, hash: B256) -> Option<Self> {
+        let fixture: Self = foundry_common::fs::read_json_file(path).ok()?;
+        (fixture.hash == hash && foundry_common::env_reads::matches(&fixture.env))
+            .then_some(fixture)
+    }
+
+    /// Writes the fixture to `path`, creating parent directories as needed.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        foundry_common::fs::write_json_file(path, self)?;
+        Ok(())
+    }
+
+    /// Returns the path of the fixture of the test contract `name` in `dir`.
+    pub fn path(dir: &Path, name: &str) -> PathBuf {
+        dir.join(format!("{}.json", name.replace(['/', '\\', ':'], "_")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(hash: B256) -> SetupFixture {
+        SetupFixture {
+            hash,
+            address: Address::with_last_byte(1),
+            labeled_addresses: BTreeMap::from([(Address::with_last_byte(2), "0x1234".to_string())]),
+            storage: vec![FixtureSlot {
+                address: Address::with_last_byte(1),
+                key: B256::with_last_byte(3),
+                value: B256::with_last_byte(4),
+            }],
+            bytecodes: BTreeMap::from([(B256::with_last_byte(5), Bytes::from(vec![0u8; 32]))]),
+            known_codes: Default::default(),
+            block_number: U256::from(2),
+            block_timestamp: U256::from(3),
+            chain_id: 260,
+            env: Default::default(),
+        }
+    }
+
+    #[test]
+    fn roundtrips_fixture() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = SetupFixture::path(dir.path(), "test/Counter.t.sol:CounterTest");
+        assert_eq!(path.file_name().unwrap(), "test_Counter.t.sol_CounterTest.json");
+
+        let hash = B256::with_last_byte(0xaa);
+        fixture(hash).save(&path).unwrap();
+        assert_eq!(SetupFixture::load(&path, hash), Some(fixture(hash)));
+    }
+
+    #[test]
+    fn hashes_environment() {
+        let config = CheatsConfig::default();
+        let hash = SetupFixture::hash_environment(None, &config).unwrap();
+        assert_eq!(SetupFixture::hash_environment(None, &config), Some(hash));
+
+        let fork = CreateFork {
+            enable_caching: false,
+            url: "http://localhost:8011".to_string(),
+            env: Default::default(),
+            evm_opts: Default::default(),
+        };
+        assert_ne!(SetupFixture::hash_environment(Some(&fork), &config), Some(hash));
+
+        let config = CheatsConfig { ffi: true, ..Default::default() };
+        assert_eq!(SetupFixture::hash_environment(None, &config), None);
+    }
+
+    #[test]
+    fn ignores_stale_fixture() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = SetupFixture::path(dir.path(), "CounterTest");
+
+        fixture(B256::with_last_byte(0xaa)).save(&path).unwrap();
+        assert_eq!(SetupFixture::load(&path, B256::with_last_byte(0xbb)), None);
+    }
+
+    #[test]
+    fn ignores_fixture_with_changed_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = SetupFixture::path(dir.path(), "CounterTest");
+
+        let key = "_foundrySetupFixtureTestKey";
+        std::env::set_var(key, "1");
+        let hash = B256::with_last_byte(0xaa);
+        let fixture = SetupFixture {
+            env: BTreeMap::from([(key.to_string(), Some("1".to_string()))]),
+            ..fixture(hash)
+        };
+        fixture.save(&path).unwrap();
+        assert_eq!(SetupFixture::load(&path, hash), Some(fixture));
+
+        std::env::set_var(key, "2");
+        assert_eq!(SetupFixture::load(&path, hash), None);
+    }
+}
@@ -0,0 +1,109 @@
+//! Seeding of the accounts configured with `zk_genesis` before the test contracts are deployed.
+
+use alloy_primitives::{keccak256, Address, Bytes, U256};
+use eyre::Result;
+use foundry_common::{
+    contracts::ContractsByArtifact,
+    conversion_utils::{address_to_h160, h160_to_address},
+};
+use foundry_config::ZkGenesisAccount;
+use foundry_evm::{backend::DatabaseResult, executors::Executor};
+use std::collections::BTreeMap;
+use zksync_types::{storage_key_for_eth_balance, L2_ETH_TOKEN_ADDRESS};
+
+/// A `zk_genesis` account whose artifact was resolved to its bytecode.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GenesisAccount {
+    pub address: Address,
+    pub balance: Option<U256>,
+    pub code: Option<Bytes>,
+    pub storage: BTreeMap<U256, U256>,
+}
+
+impl GenesisAccount {
+    /// Resolves the artifact of a configured account among the compiled contracts.
+    pub fn resolve(account: &ZkGenesisAccount, contracts: &ContractsByArtifact) -> Result<Self> {
+        let code = match &account.artifact {
+            Some(artifact) => {
+                let (_, (_, code)) =
+                    contracts.find_by_name_or_identifier(artifact)?.ok_or_else(|| {
+                        eyre::eyre!("no contract found for the genesis artifact {artifact}")
+                    })?;
+                Some(Bytes::from(code.clone()))
+            }
+            None => None,
+        };
+        Ok(Self {
+            address: account.address,
+            balance: account.balance,
+            code,
+            storage: account.storage.clone(),
+        })
+    }
+
+    /// Writes the account into the state of `executor`.
+    ///
+    /// The zkEVM reads balances from the storage of the L2EthToken contract, where the balance is
+    /// written too. A zero balance is read as the default balance of unfunded accounts though.
+    pub fn apply(&self, executor: &mut Executor) -> DatabaseResult<()> {
+        if let Some(balance) = self.balance {
+            executor.set_balance(self.address, balance)?;
+            let key = storage_key_for_eth_balance(&address_to_h160(self.address));
+            executor.set_storage(
+                h160_to_address(L2_ETH_TOKEN_ADDRESS),
+                U256::from_be_bytes(key.key().0),
+                balance,
+            )?;
+        }
+        if let Some(code) = &self.code {
+            executor.set_code(self.address, code.clone())?;
+        }
+        for (slot, value) in &self.storage {
+            executor.set_storage(self.address, *slot, *value)?;
+        }
+        Ok(())
+    }
+
+    /// Appends everything the account seeds to `bytes`, to tell apart fixtures set up on top of
+    /// different genesis states.
+    pub fn hash_into(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(self.address.as_slice());
+        bytes.extend_from_slice(&self.balance.unwrap_or_default().to_be_bytes::<32>());
+        bytes.extend_from_slice(keccak256(self.code.as_deref().unwrap_or_default()).as_slice());
+        for (slot, value) in &self.storage {
+            bytes.extend_from_slice(&slot.to_be_bytes::<32>());
+            bytes.extend_from_slice(&value.to_be_bytes::<32>());
+        }
+    }
+}
+
+/// Resolves the artifacts of all configured accounts.
+pub fn resolve_genesis(
+    accounts: &[ZkGenesisAccount],
+    contracts: &ContractsByArtifact,
+) -> Result<Vec<GenesisAccount>> {
+    accounts.iter().map(|account| GenesisAccount::resolve(account, contracts)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_accounts() {
+        let account = ZkGenesisAccount {
+            address: Address::with_last_byte(1),
+            balance: Some(U256::from(1)),
+            artifact: None,
+            storage: BTreeMap::from([(U256::ZERO, U256::from(2))]),
+        };
+        let contracts = ContractsByArtifact::default();
+        let resolved = GenesisAccount::resolve(&account, &contracts).unwrap();
+        assert_eq!(resolved.code, None);
+        assert_eq!(resolved.balance, account.balance);
+        assert_eq!(resolved.storage, account.storage);
+
+        let missing = ZkGenesisAccount { artifact: Some("Missing".to_string()), ..account };
+        assert!(GenesisAccount::resolve(&missing, &contracts).is_err());
+    }
+}
@@ -5,26 +5,38 @@ use alloy_primitives::B256;
 use foundry_compilers::ProjectCompileOutput;
 use foundry_config::{
     validate_profiles, Config, FuzzConfig, InlineConfig, InlineConfigError, InlineConfigParser,
-    InvariantConfig, NatSpec,
+    InvariantConfig, NatSpec, ZkForkRetriesConfig, ZkGasConfig,
 };
 
 use proptest::test_runner::{RngAlgorithm, TestRng, TestRunner};
 use std::path::Path;
 
+pub mod corpus;
+
 pub mod coverage;
 
+pub mod fixture;
+
 pub mod gas_report;
 
+pub mod genesis;
+
 pub mod link;
 
+pub mod mutate;
+
 mod multi_runner;
 pub use multi_runner::{MultiContractRunner, MultiContractRunnerBuilder};
 
 mod runner;
 pub use runner::ContractRunner;
 
+pub mod replay;
+
 pub mod result;
 
+pub mod retry;
+
 // TODO: remove
 pub use foundry_common::traits::TestFilter;
 pub use foundry_evm::*;
@@ -42,6 +54,14 @@ pub struct TestOptions {
     pub inline_fuzz: InlineConfig<FuzzConfig>,
     /// Contains per-test specific "invariant" configurations.
     pub inline_invariant: InlineConfig<InvariantConfig>,
+    /// The base "zk" gas configuration of the test transactions.
+    pub zk_gas: ZkGasConfig,
+    /// Contains per-test specific "zk" gas configurations.
+    pub inline_zk_gas: InlineConfig<ZkGasConfig>,
+    /// The base "zk-fork" configuration of the retries of tests failing with provider errors.
+    pub zk_fork_retries: ZkForkRetriesConfig,
+    /// Contains per-test specific "zk-fork" retry configurations.
+    pub inline_zk_fork_retries: InlineConfig<ZkForkRetriesConfig>,
 }
 
 impl TestOptions {
@@ -53,16 +73,22 @@ impl TestOptions {
         profiles: Vec<String>,
         base_fuzz: FuzzConfig,
         base_invariant: InvariantConfig,
+        base_zk_gas: ZkGasConfig,
+        base_zk_fork_retries: ZkForkRetriesConfig,
     ) -> Result<Self, InlineConfigError> {
         let natspecs: Vec<NatSpec> = NatSpec::parse(output, root);
         let mut inline_invariant = InlineConfig::<InvariantConfig>::default();
         let mut inline_fuzz = InlineConfig::<FuzzConfig>::default();
+        let mut inline_zk_gas = InlineConfig::<ZkGasConfig>::default();
+        let mut inline_zk_fork_retries = InlineConfig::<ZkForkRetriesConfig>::default();
 
         for natspec in natspecs {
             // Perform general validation
             validate_profiles(&natspec, &profiles)?;
             FuzzConfig::validate_configs(&natspec)?;
             InvariantConfig::validate_configs(&natspec)?;
+            ZkGasConfig::validate_configs(&natspec)?;
+            ZkForkRetriesConfig::validate_configs(&natspec)?;
 
             // Apply in-line configurations for the current profile
             let configs: Vec<String> = natspec.current_profile_configs().collect();
@@ -81,9 +107,30 @@ impl TestOptions {
                 Ok(None) => { /* No inline config found, do nothing */ }
                 Err(e) => Err(InlineConfigError { line: line.clone(), source: e })?,
             }
+
+            match base_zk_gas.try_merge(&configs) {
+                Ok(Some(conf)) => inline_zk_gas.insert(c, f, conf),
+                Ok(None) => { /* No inline config found, do nothing */ }
+                Err(e) => Err(InlineConfigError { line: line.clone(), source: e })?,
+            }
+
+            match base_zk_fork_retries.try_merge(&configs) {
+                Ok(Some(conf)) => inline_zk_fork_retries.insert(c, f, conf),
+                Ok(None) => { /* No inline config found, do nothing */ }
+                Err(e) => Err(InlineConfigError { line: line.clone(), source: e })?,
+            }
         }
 
-        Ok(Self { fuzz: base_fuzz, invariant: base_invariant, inline_fuzz, inline_invariant })
+        Ok(Self {
+            fuzz: base_fuzz,
+            invariant: base_invariant,
+            inline_fuzz,
+            inline_invariant,
+            zk_gas: base_zk_gas,
+            inline_zk_gas,
+            zk_fork_retries: base_zk_fork_retries,
+            inline_zk_fork_retries,
+        })
     }
 
     /// Returns a "fuzz" test runner instance. Parameters are used to select tight scoped fuzz
@@ -144,6 +191,34 @@ impl TestOptions {
         self.inline_invariant.get(contract_id, test_fn).unwrap_or(&self.invariant)
     }
 
+    /// Returns the "zk" gas configuration of the test transactions. Parameters are used to select
+    /// tight scoped configs that apply for a contract-function pair. A fallback configuration is
+    /// applied if no specific setup is found for a given input.
+    ///
+    /// - `contract_id` is the id of the test contract, expressed as a relative path from the
+    ///   project root.
+    /// - `test_fn` is the name of the test function declared inside the test contract.
+    pub fn zk_gas_config<S>(&self, contract_id: S, test_fn: S) -> &ZkGasConfig
+    where
+        S: Into<String>,
+    {
+        self.inline_zk_gas.get(contract_id, test_fn).unwrap_or(&self.zk_gas)
+    }
+
+    /// Returns how the test is retried when failing with a provider error of its fork. Parameters
+    /// are used to select tight scoped configs that apply for a contract-function pair. A fallback
+    /// configuration is applied if no specific setup is found for a given input.
+    ///
+    /// - `contract_id` is the id of the test contract, expressed as a relative path from the
+    ///   project root.
+    /// - `test_fn` is the name of the test function declared inside the test contract.
+    pub fn zk_fork_retries_config<S>(&self, contract_id: S, test_fn: S) -> &ZkForkRetriesConfig
+    where
+        S: Into<String>,
+    {
+        self.inline_zk_fork_retries.get(contract_id, test_fn).unwrap_or(&self.zk_fork_retries)
+    }
+
     pub fn fuzzer_with_cases(&self, cases: u32) -> TestRunner {
         // TODO: Add Options to modify the persistence
         let cfg = proptest::test_runner::Config {
@@ -170,6 +245,8 @@ impl TestOptions {
 pub struct TestOptionsBuilder {
     fuzz: Option<FuzzConfig>,
     invariant: Option<InvariantConfig>,
+    zk_gas: Option<ZkGasConfig>,
+    zk_fork_retries: Option<ZkForkRetriesConfig>,
     profiles: Option<Vec<String>>,
 }
 
@@ -186,6 +263,18 @@ impl TestOptionsBuilder {
         self
     }
 
+    /// Sets a [`ZkGasConfig`] to be used as base "zk" gas configuration.
+    pub fn zk_gas(mut self, conf: ZkGasConfig) -> Self {
+        self.zk_gas = Some(conf);
+        self
+    }
+
+    /// Sets a [`ZkForkRetriesConfig`] to be used as base "zk-fork" retry configuration.
+    pub fn zk_fork_retries(mut self, conf: ZkForkRetriesConfig) -> Self {
+        self.zk_fork_retries = Some(conf);
+        self
+    }
+
     /// Sets available configuration profiles. Profiles are useful to validate existing in-line
     /// configurations. This argument is necessary in case a `compile_output`is provided.
     pub fn profiles(mut self, p: Vec<String>) -> Self {
@@ -208,7 +297,17 @@ impl TestOptionsBuilder {
             self.profiles.unwrap_or_else(|| vec![Config::selected_profile().into()]);
         let base_fuzz = self.fuzz.unwrap_or_default();
         let base_invariant = self.invariant.unwrap_or_default();
-        TestOptions::new(output, root, profiles, base_fuzz, base_invariant)
+        let base_zk_gas = self.zk_gas.unwrap_or_default();
+        let base_zk_fork_retries = self.zk_fork_retries.unwrap_or_default();
+        TestOptions::new(
+            output,
+            root,
+            profiles,
+            base_fuzz,
+            base_invariant,
+            base_zk_gas,
+            base_zk_fork_retries,
+        )
     }
 }
 
@@ -1,22 +1,29 @@
 //! Forge test runner for multiple contracts.
 
 use crate::{
+    fixture::SetupFixture,
+    genesis::{resolve_genesis, GenesisAccount},
     link::{link_with_nonce_or_address, PostLinkInput, ResolvedDependency},
     result::SuiteResult,
     ContractRunner, TestFilter, TestOptions,
 };
 use alloy_json_abi::{Function, JsonAbi as Abi};
-use alloy_primitives::{Address, Bytes, U256};
+use alloy_primitives::{Address, Bytes, B256, U256};
+use era_cheatcodes::plugins::TracerPlugins;
 use eyre::Result;
-use foundry_common::{zk_compile::ContractBytecodes, ContractsByArtifact, TestFunctionExt};
+use foundry_common::{
+    zk_compile::ContractBytecodes, zk_utils::source_map::ContractSourceMaps, ContractsByArtifact,
+    TestFunctionExt,
+};
 use foundry_compilers::{
     artifacts::CompactContractBytecode, contracts::ArtifactContracts, Artifact, ArtifactId,
     ArtifactOutput, ProjectCompileOutput,
 };
+use foundry_config::ZkGenesisAccount;
 use foundry_evm::{
     backend::Backend,
     executors::{Executor, ExecutorBuilder},
-    fork::CreateFork,
+    fork::{CreateFork, FetchedState},
     inspectors::CheatsConfig,
     opts::EvmOpts,
     revm,
@@ -26,7 +33,7 @@ use revm::primitives::SpecId;
 use std::{
     collections::{BTreeMap, HashSet},
     iter::Iterator,
-    path::Path,
+    path::{Path, PathBuf},
     sync::{mpsc, Arc},
 };
 
@@ -42,6 +49,8 @@ pub struct MultiContractRunner {
     pub known_contracts: ContractsByArtifact,
     /// Compiled contracts bytecodes by name
     pub contract_bytecodes: ContractBytecodes,
+    /// Source maps of the compiled contracts, used to locate reverts
+    pub source_maps: ContractSourceMaps,
     /// The EVM instance used in the test runner
     pub evm_opts: EvmOpts,
     /// The configured evm
@@ -64,6 +73,18 @@ pub struct MultiContractRunner {
     pub debug: bool,
     /// Settings related to fuzz and/or invariant tests
     pub test_options: TestOptions,
+    /// The directory the state after `setUp()` of each test contract is cached in, if enabled
+    pub setup_fixtures: Option<PathBuf>,
+    /// Plugins hooked into the execution of every test
+    pub tracer_plugins: TracerPlugins,
+    /// Accounts seeded before each test contract is deployed
+    pub genesis: Vec<GenesisAccount>,
+    /// The directory replay bundles of failing tests are written to, if enabled
+    pub replay_dir: Option<PathBuf>,
+    /// The directory fuzz corpora are persisted in
+    pub corpus_dir: Option<PathBuf>,
+    /// The recorded state of a fork to run against instead, when replaying a test
+    pub fetched_state: Option<FetchedState>,
 }
 
 impl MultiContractRunner {
@@ -168,6 +189,12 @@ impl MultiContractRunner {
     ) {
         trace!("running all tests");
 
+        // the environment setups are cached for, `None` if they can't be cached
+        let fixture_environment = self
+            .setup_fixtures
+            .as_ref()
+            .and_then(|_| SetupFixture::hash_environment(self.fork.as_ref(), &self.cheats_config));
+
         // the db backend that serves all the data, each contract gets its own instance
         let db = Backend::spawn(self.fork.take()).await;
 
@@ -183,6 +210,7 @@ impl MultiContractRunner {
                             .trace(self.evm_opts.verbosity >= 3 || self.debug)
                             .debug(self.debug)
                             .coverage(self.coverage)
+                            .tracer_plugins(self.tracer_plugins.clone())
                     })
                     .spec(self.evm_spec)
                     .gas_limit(self.evm_opts.gas_limit())
@@ -198,6 +226,7 @@ impl MultiContractRunner {
                     libs,
                     filter,
                     test_options.clone(),
+                    fixture_environment,
                 );
                 trace!(contract=?identifier, "executed all tests in contract");
 
@@ -216,8 +245,9 @@ impl MultiContractRunner {
         libs: &[Bytes],
         filter: &dyn TestFilter,
         test_options: TestOptions,
+        fixture_environment: Option<B256>,
     ) -> SuiteResult {
-        let runner = ContractRunner::new(
+        let mut runner = ContractRunner::new(
             name,
             executor,
             contract,
@@ -229,6 +259,16 @@ impl MultiContractRunner {
             libs,
             self.debug,
         );
+        runner.setup_fixture = self
+            .setup_fixtures
+            .as_ref()
+            .zip(fixture_environment)
+            .map(|(dir, environment)| (SetupFixture::path(dir, name), environment));
+        runner.source_maps = Some(&self.source_maps);
+        runner.genesis = &self.genesis;
+        runner.replay_dir = self.replay_dir.clone();
+        runner.corpus_dir = self.corpus_dir.clone();
+        runner.fetched_state = self.fetched_state.as_ref();
         runner.run_tests(filter, test_options, Some(&self.known_contracts))
     }
 }
@@ -253,6 +293,16 @@ pub struct MultiContractRunnerBuilder {
     pub debug: bool,
     /// Settings related to fuzz and/or invariant tests
     pub test_options: Option<TestOptions>,
+    /// The directory to cache the state after `setUp()` in
+    pub setup_fixtures: Option<PathBuf>,
+    /// Accounts to seed before each test contract is deployed
+    pub genesis: Vec<ZkGenesisAccount>,
+    /// The directory to write replay bundles of failing tests to
+    pub replay_dir: Option<PathBuf>,
+    /// The directory fuzz corpora are persisted in
+    pub corpus_dir: Option<PathBuf>,
+    /// The recorded state of a fork to run against instead
+    pub fetched_state: Option<FetchedState>,
 }
 
 impl MultiContractRunnerBuilder {
@@ -351,10 +401,12 @@ impl MultiContractRunnerBuilder {
         )?;
 
         let execution_info = known_contracts.flatten();
+        let genesis = resolve_genesis(&self.genesis, &known_contracts)?;
         Ok(MultiContractRunner {
             contracts: deployable_contracts,
             known_contracts,
             contract_bytecodes: Default::default(),
+            source_maps: Default::default(),
             evm_opts,
             env,
             evm_spec: self.evm_spec.unwrap_or(SpecId::MERGE),
@@ -366,6 +418,12 @@ impl MultiContractRunnerBuilder {
             coverage: self.coverage,
             debug: self.debug,
             test_options: self.test_options.unwrap_or_default(),
+            setup_fixtures: self.setup_fixtures,
+            tracer_plugins: Default::default(),
+            genesis,
+            replay_dir: self.replay_dir,
+            corpus_dir: self.corpus_dir,
+            fetched_state: self.fetched_state,
         })
     }
 
@@ -411,6 +469,36 @@ impl MultiContractRunnerBuilder {
         self
     }
 
+    #[must_use]
+    pub fn with_setup_fixtures(mut self, setup_fixtures: Option<PathBuf>) -> Self {
+        self.setup_fixtures = setup_fixtures;
+        self
+    }
+
+    #[must_use]
+    pub fn with_genesis(mut self, genesis: Vec<ZkGenesisAccount>) -> Self {
+        self.genesis = genesis;
+        self
+    }
+
+    #[must_use]
+    pub fn with_replay_dir(mut self, replay_dir: Option<PathBuf>) -> Self {
+        self.replay_dir = replay_dir;
+        self
+    }
+
+    #[must_use]
+    pub fn with_corpus_dir(mut self, corpus_dir: Option<PathBuf>) -> Self {
+        self.corpus_dir = corpus_dir;
+        self
+    }
+
+    #[must_use]
+    pub fn with_fetched_state(mut self, fetched_state: Option<FetchedState>) -> Self {
+        self.fetched_state = fetched_state;
+        self
+    }
+
     #[must_use]
     pub fn set_debug(mut self, enable: bool) -> Self {
         self.debug = enable;
@@ -0,0 +1,151 @@
+//! Mutation testing: small changes to the sources, e.g. an operator swapped for another one,
+//! that a thorough test suite should detect.
+
+use solang_parser::lexer::{Lexer, Token};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// The operators and literals replaced by mutants, and their replacement.
+const MUTATIONS: &[(&str, &str)] = &[
+    ("+", "-"),
+    ("-", "+"),
+    ("*", "/"),
+    ("/", "*"),
+    ("%", "*"),
+    ("<", ">="),
+    (">", "<="),
+    ("<=", ">"),
+    (">=", "<"),
+    ("==", "!="),
+    ("!=", "=="),
+    ("&&", "||"),
+    ("||", "&&"),
+    ("true", "false"),
+    ("false", "true"),
+];
+
+/// A mutation of a source file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mutant {
+    /// The mutated source file.
+    pub path: PathBuf,
+    /// The 1-based line of the mutation.
+    pub line: usize,
+    /// The byte offset of the replaced token.
+    pub offset: usize,
+    /// The replaced token.
+    pub original: &'static str,
+    /// The token it is replaced with.
+    pub replacement: &'static str,
+}
+
+impl Mutant {
+    /// Returns the mutants of the given source, one per mutable token of its function bodies.
+    ///
+    /// Comments and string literals are skipped by the lexer, and tokens outside of function
+    /// bodies, e.g. in pragmas or imports, are never mutated.
+    pub fn generate(path: &Path, source: &str) -> Vec<Self> {
+        let mut comments = vec![];
+        let mut errors = vec![];
+        let mut depth = 0usize;
+        let mut mutants = vec![];
+        for (start, token, end) in Lexer::new(source, 0, &mut comments, &mut errors) {
+            match token {
+                Token::OpenCurlyBrace => depth += 1,
+                Token::CloseCurlyBrace => depth = depth.saturating_sub(1),
+                // contracts are at depth 1, their functions' bodies at depth 2 and more
+                _ if depth >= 2 => {
+                    let text = &source[start..end];
+                    if let Some((original, replacement)) =
+                        MUTATIONS.iter().find(|(original, _)| *original == text)
+                    {
+                        mutants.push(Self {
+                            path: path.to_path_buf(),
+                            line: source[..start].matches('\n').count() + 1,
+                            offset: start,
+                            original,
+                            replacement,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        mutants
+    }
+
+    /// Returns the mutated source.
+    pub fn apply(&self, source: &str) -> String {
+        let end = self.offset + self.original.len();
+        format!("{}{}{}", &source[..self.offset], self.replacement, &source[end..])
+    }
+}
+
+impl fmt::Display for Mutant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: `{}` -> `{}`",
+            self.path.display(),
+            self.line,
+            self.original,
+            self.replacement
+        )
+    }
+}
+
+/// Whether the test suite detected a mutant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MutantStatus {
+    /// A test failed with the mutant.
+    Killed,
+    /// All the tests passed with the mutant.
+    Survived,
+    /// The mutant doesn't compile.
+    Invalid,
+}
+
+impl fmt::Display for MutantStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MutantStatus::Killed => f.write_str("killed"),
+            MutantStatus::Survived => f.write_str("survived"),
+            MutantStatus::Invalid => f.write_str("invalid"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mutates_function_bodies() {
+        let source = r#"// SPDX-License-Identifier: UNLICENSED
+pragma solidity >=0.8.0 <0.9.0;
+
+contract Counter {
+    uint256 public number = 1 + 1;
+
+    function increment(uint256 by) public returns (bool) {
+        // number - by
+        require(by > 0, "a + b");
+        number = number + by;
+        return true;
+    }
+}
+"#;
+        let mutants = Mutant::generate(Path::new("src/Counter.sol"), source);
+        assert_eq!(
+            mutants.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "src/Counter.sol:9: `>` -> `<=`",
+                "src/Counter.sol:10: `+` -> `-`",
+                "src/Counter.sol:11: `true` -> `false`",
+            ]
+        );
+        assert!(mutants[1].apply(source).contains("number = number - by;"));
+    }
+}
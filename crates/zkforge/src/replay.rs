@@ -0,0 +1,117 @@
+//! Replay bundles: everything needed to re-execute a failing test exactly, and offline.
+
+use crate::result::TestResult;
+use alloy_primitives::{Bytes, U256};
+use eyre::Result;
+use foundry_evm::{constants::CHEATCODE_ADDRESS, fork::FetchedState, traces::TraceKind};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A failing test, along with the inputs it ran with.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayBundle {
+    /// The identifier of the test contract, `<path>:<name>`.
+    pub contract: String,
+    /// The signature of the test function.
+    pub test: String,
+    /// The seed the fuzzer was run with, if any.
+    pub fuzz_seed: Option<U256>,
+    /// The state fetched by the fork selected at launch or in `setUp()`, if any.
+    pub fork: Option<FetchedState>,
+    /// The calldata of the cheatcodes called by the test, in order.
+    pub cheatcodes: Vec<Bytes>,
+}
+
+impl ReplayBundle {
+    pub fn new(
+        contract: &str,
+        test: String,
+        fuzz_seed: Option<U256>,
+        fork: Option<FetchedState>,
+        result: &TestResult,
+    ) -> Self {
+        Self {
+            contract: contract.to_string(),
+            test,
+            fuzz_seed,
+            fork,
+            cheatcodes: Self::cheatcode_calls(result),
+        }
+    }
+
+    /// Returns the calldata of the cheatcodes called while executing the test, which are only
+    /// traced when tracing is enabled.
+    pub fn cheatcode_calls(result: &TestResult) -> Vec<Bytes> {
+        result
+            .traces
+            .iter()
+            .filter(|(kind, _)| *kind == TraceKind::Execution)
+            .flat_map(|(_, arena)| arena.arena.iter())
+            .filter(|node| node.trace.address == CHEATCODE_ADDRESS)
+            .map(|node| Bytes::copy_from_slice(node.trace.data.as_bytes()))
+            .collect()
+    }
+
+    /// Returns the index of the first cheatcode call of a replayed run that differs from the
+    /// recorded ones, if the replay diverged.
+    pub fn divergence(&self, result: &TestResult) -> Option<usize> {
+        let calls = Self::cheatcode_calls(result);
+        if calls == self.cheatcodes {
+            return None
+        }
+        Some(calls.iter().zip(&self.cheatcodes).take_while(|(a, b)| a == b).count())
+    }
+
+    /// Returns the name of the test contract.
+    pub fn contract_name(&self) -> &str {
+        self.contract.rsplit(':').next().unwrap_or(&self.contract)
+    }
+
+    /// Returns the name of the test function.
+    pub fn test_name(&self) -> &str {
+        self.test.split('(').next().unwrap_or(&self.test)
+    }
+
+    /// Loads the bundle stored at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(foundry_common::fs::read_json_file(path)?)
+    }
+
+    /// Writes the bundle to `dir`, creating it as needed, and returns its path.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!(
+            "{}.{}.json",
+            self.contract.replace(['/', '\\', ':'], "_"),
+            self.test_name()
+        ));
+        foundry_common::fs::write_json_file(&path, self)?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrips_bundle() {
+        let bundle = ReplayBundle {
+            contract: "test/Swap.t.sol:SwapTest".to_string(),
+            test: "testFuzzSwap(uint256)".to_string(),
+            fuzz_seed: Some(U256::from(1)),
+            fork: Some(FetchedState { chain_id: 324, ..Default::default() }),
+            cheatcodes: vec![Bytes::from(vec![1, 2, 3, 4])],
+        };
+        assert_eq!(bundle.contract_name(), "SwapTest");
+        assert_eq!(bundle.test_name(), "testFuzzSwap");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = bundle.save(dir.path()).unwrap();
+        assert_eq!(path.file_name().unwrap(), "test_Swap.t.sol_SwapTest.testFuzzSwap.json");
+        assert_eq!(ReplayBundle::load(&path).unwrap(), bundle);
+
+        assert_eq!(bundle.divergence(&TestResult::default()), Some(0));
+    }
+}
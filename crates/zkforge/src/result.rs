@@ -1,6 +1,6 @@
 //! Test outcomes.
 
-use alloy_primitives::Address;
+use alloy_primitives::{Address, Bytes};
 use ethers_core::types::Log;
 use foundry_common::evm::Breakpoints;
 use foundry_evm::{
@@ -14,6 +14,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     collections::BTreeMap,
     fmt::{self, Write},
+    path::PathBuf,
     time::Duration,
 };
 use yansi::Paint;
@@ -43,9 +44,19 @@ impl SuiteResult {
         self.tests().filter(|(_, t)| t.status == TestStatus::Success)
     }
 
-    /// Iterator over all failing tests and their names
+    /// Iterator over all failing tests and their names, excluding quarantined ones
     pub fn failures(&self) -> impl Iterator<Item = (&String, &TestResult)> {
-        self.tests().filter(|(_, t)| t.status == TestStatus::Failure)
+        self.tests().filter(|(_, t)| t.status == TestStatus::Failure && !t.quarantined)
+    }
+
+    /// Iterator over all quarantined tests and their names
+    pub fn quarantined(&self) -> impl Iterator<Item = (&String, &TestResult)> {
+        self.tests().filter(|(_, t)| t.quarantined)
+    }
+
+    /// Iterator over all retried tests and their names
+    pub fn retried(&self) -> impl Iterator<Item = (&String, &TestResult)> {
+        self.tests().filter(|(_, t)| t.retries > 0)
     }
 
     /// Iterator over all tests and their names
@@ -105,6 +116,14 @@ pub struct TestResult {
     /// still be successful (i.e self.success == true) when it's expected to fail.
     pub reason: Option<String>,
 
+    /// The raw data the test reverted with, which `reason` was decoded from
+    #[serde(skip)]
+    pub revert_data: Option<Bytes>,
+
+    /// Where the zkEVM reverted, as a source location if the reverting contract was compiled with
+    /// debug info.
+    pub revert_location: Option<String>,
+
     /// Minimal reproduction test case for failing test
     pub counterexample: Option<CounterExample>,
 
@@ -133,6 +152,15 @@ pub struct TestResult {
 
     /// pc breakpoint char map
     pub breakpoints: Breakpoints,
+
+    /// How many times the test was retried, if it depends on a fork
+    pub retries: u32,
+
+    /// Whether the test depends on a fork and was quarantined after failing all its retries, which
+    /// doesn't fail the run
+    pub quarantined: bool,
+    /// The replay bundle written for the failing test, if enabled
+    pub replay_bundle: Option<PathBuf>,
 }
 
 impl fmt::Display for TestResult {
@@ -141,7 +169,11 @@ impl fmt::Display for TestResult {
             TestStatus::Success => Paint::green("[PASS]").fmt(f),
             TestStatus::Skipped => Paint::yellow("[SKIP]").fmt(f),
             TestStatus::Failure => {
-                let mut s = String::from("[FAIL. Reason: ");
+                let mut s = String::from(if self.quarantined {
+                    "[QUARANTINED. Reason: "
+                } else {
+                    "[FAIL. Reason: "
+                });
 
                 let reason = self.reason.as_deref().unwrap_or("assertion failed");
                 s.push_str(reason);
@@ -162,7 +194,11 @@ impl fmt::Display for TestResult {
                     s.push(']');
                 }
 
-                Paint::red(s).fmt(f)
+                if self.quarantined {
+                    Paint::yellow(s).fmt(f)
+                } else {
+                    Paint::red(s).fmt(f)
+                }
             }
         }
     }
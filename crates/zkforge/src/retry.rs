@@ -0,0 +1,138 @@
+//! Retrying and quarantining of fork tests, whose failures may come from a flaky RPC.
+
+use crate::result::{TestResult, TestStatus};
+use foundry_config::ZkForkRetriesConfig;
+use std::time::{Duration, Instant};
+
+/// Fragments of the errors raised when the provider of a fork fails to serve a request, either
+/// as displayed by the backend or as debug-printed by the zkEVM storage.
+const PROVIDER_ERRORS: &[&str] = &[
+    "failed to get account for",
+    "failed to get storage for",
+    "failed to get block hash for",
+    "failed to get full block for",
+    "failed to get bytecode for",
+    "failed to get transaction",
+    "GetAccount(",
+    "GetStorage(",
+    "GetBlockHash(",
+    "GetFullBlock(",
+    "GetBytecode(",
+    "GetTransaction(",
+    "error sending request",
+    "Too Many Requests",
+    "connection refused",
+    "timed out",
+];
+
+/// How tests failing with provider errors are retried and quarantined.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ForkTestRetries {
+    /// How many times a failing test is retried.
+    pub retries: u32,
+    /// Delay before the first retry, doubled for every further retry.
+    pub backoff: Duration,
+    /// Whether tests still failing after all retries are quarantined.
+    pub quarantine: bool,
+}
+
+impl From<&ZkForkRetriesConfig> for ForkTestRetries {
+    fn from(config: &ZkForkRetriesConfig) -> Self {
+        Self {
+            retries: config.retries,
+            backoff: Duration::from_millis(config.retry_backoff),
+            quarantine: config.quarantine,
+        }
+    }
+}
+
+impl ForkTestRetries {
+    /// Returns whether a test failed because the provider of its fork failed, rather than because
+    /// of an assertion or a revert of the tested code.
+    pub fn is_provider_failure(result: &TestResult) -> bool {
+        result.status == TestStatus::Failure &&
+            result
+                .reason
+                .as_deref()
+                .is_some_and(|reason| PROVIDER_ERRORS.iter().any(|error| reason.contains(error)))
+    }
+
+    /// Returns the delay before the given retry, starting at 1.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.backoff.checked_mul(1 << retry.saturating_sub(1).min(16)).unwrap_or(Duration::MAX)
+    }
+
+    /// Runs a test, retrying it while it fails with a provider error.
+    pub fn run(&self, mut run: impl FnMut() -> TestResult) -> TestResult {
+        let mut result = run();
+
+        let mut retry = 0;
+        while Self::is_provider_failure(&result) && retry < self.retries {
+            retry += 1;
+            debug!(retry, reason = ?result.reason, "retrying fork test failing with a provider error");
+            wait(self.backoff(retry));
+            result = run();
+        }
+        result.retries = retry;
+        result.quarantined = self.quarantine && Self::is_provider_failure(&result);
+        result
+    }
+}
+
+/// Waits for the given delay, running other tests queued on the rayon pool in the meantime
+/// instead of blocking the worker the test runs on.
+fn wait(delay: Duration) {
+    let deadline = Instant::now() + delay;
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            break
+        }
+        match rayon::yield_now() {
+            Some(rayon::Yield::Executed) => {}
+            // nothing else to run, or not on a rayon worker
+            _ => std::thread::sleep((deadline - now).min(Duration::from_millis(10))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_tests_failing_with_provider_errors() {
+        let retries =
+            ForkTestRetries { retries: 2, backoff: Duration::from_millis(1), quarantine: false };
+        assert_eq!(retries.backoff(1), Duration::from_millis(1));
+        assert_eq!(retries.backoff(3), Duration::from_millis(4));
+
+        let mut attempts = 0;
+        let result = retries.run(|| {
+            attempts += 1;
+            if attempts < 2 {
+                TestResult::fail("failed to get storage for 0x00: error sending request".into())
+            } else {
+                TestResult { status: TestStatus::Success, ..Default::default() }
+            }
+        });
+        assert_eq!(result.status, TestStatus::Success);
+        assert_eq!(result.retries, 1);
+        assert!(!result.quarantined);
+
+        let mut attempts = 0;
+        let result = retries.run(|| {
+            attempts += 1;
+            TestResult::fail("assertion failed".to_string())
+        });
+        assert_eq!((attempts, result.retries), (1, 0));
+
+        let retries = ForkTestRetries { quarantine: true, ..retries };
+        let result = retries.run(|| TestResult::fail("HTTP error 429 Too Many Requests".into()));
+        assert_eq!(result.retries, 2);
+        assert!(result.quarantined);
+
+        let result = retries.run(|| TestResult::fail("assertion failed".to_string()));
+        assert!(!result.quarantined);
+    }
+}
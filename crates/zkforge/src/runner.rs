@@ -1,21 +1,28 @@
 //! The Forge test runner.
 
 use crate::{
+    corpus::FuzzCorpus,
+    fixture::SetupFixture,
+    genesis::GenesisAccount,
+    replay::ReplayBundle,
     result::{SuiteResult, TestKind, TestResult, TestSetup, TestStatus},
+    retry::ForkTestRetries,
     TestFilter, TestOptions,
 };
 use alloy_json_abi::{Function, JsonAbi as Abi};
-use alloy_primitives::{Address, Bytes, U256};
+use alloy_primitives::{Address, Bytes, B256, U256};
 use ethers_core::types::H256;
 use eyre::Result;
 use foundry_common::{
     contracts::{ContractsByAddress, ContractsByArtifact},
     conversion_utils::{address_to_h160, h256_to_h160},
     zk_compile::ContractBytecodes,
+    zk_utils::source_map::ContractSourceMaps,
     TestFunctionExt,
 };
-use foundry_config::{FuzzConfig, InvariantConfig};
+use foundry_config::{FuzzConfig, InvariantConfig, ZkGasConfig};
 use foundry_evm::{
+    backend::DatabaseExt,
     constants::CALLER,
     coverage::HitMaps,
     decode::decode_console_logs,
@@ -24,6 +31,7 @@ use foundry_evm::{
         invariant::{replay_run, InvariantExecutor, InvariantFuzzError, InvariantFuzzTestResult},
         CallResult, EvmError, ExecutionErr, Executor,
     },
+    fork::FetchedState,
     fuzz::{invariant::InvariantContract, CounterExample},
     traces::TraceKind,
 };
@@ -31,6 +39,7 @@ use proptest::test_runner::{TestError, TestRunner};
 use rayon::prelude::*;
 use std::{
     collections::{BTreeMap, HashMap},
+    path::PathBuf,
     str::FromStr,
     time::Instant,
 };
@@ -57,6 +66,19 @@ pub struct ContractRunner<'a> {
     pub sender: Address,
     /// Should generate debug traces
     pub debug: bool,
+    /// The file the state after `setUp()` is cached in, if enabled, and the hash of the
+    /// environment it's run in, see [SetupFixture::hash_environment]
+    pub setup_fixture: Option<(PathBuf, B256)>,
+    /// Source maps of the compiled contracts, used to locate reverts
+    pub source_maps: Option<&'a ContractSourceMaps>,
+    /// Accounts seeded before the test contract is deployed
+    pub genesis: &'a [GenesisAccount],
+    /// The directory replay bundles of failing tests are written to, if enabled
+    pub replay_dir: Option<PathBuf>,
+    /// The directory the fuzz corpora are persisted in, if enabled
+    pub corpus_dir: Option<PathBuf>,
+    /// The recorded state of a fork to run against instead, when replaying a test
+    pub fetched_state: Option<&'a FetchedState>,
 }
 
 impl<'a> ContractRunner<'a> {
@@ -84,6 +106,12 @@ impl<'a> ContractRunner<'a> {
             errors,
             predeploy_libs,
             debug,
+            setup_fixture: None,
+            source_maps: None,
+            genesis: &[],
+            replay_dir: None,
+            corpus_dir: None,
+            fetched_state: None,
         }
     }
 }
@@ -101,6 +129,11 @@ impl<'a> ContractRunner<'a> {
     fn _setup(&mut self, setup: bool) -> Result<TestSetup> {
         trace!(?setup, "Setting test contract");
 
+        // Run against the recorded state of a fork when replaying
+        if let Some(state) = self.fetched_state {
+            state.apply(&mut self.executor.backend)?;
+        }
+
         // We max out their balance so that they can deploy and make calls.
         self.executor.set_balance(self.sender, U256::MAX)?;
         self.executor.set_balance(CALLER, U256::MAX)?;
@@ -108,6 +141,39 @@ impl<'a> ContractRunner<'a> {
         // We set the nonce of the deployer accounts to 1 to get the same addresses as DappTools
         self.executor.set_nonce(self.sender, 1)?;
 
+        // Seed the configured genesis accounts
+        for account in self.genesis {
+            account.apply(&mut self.executor)?;
+        }
+
+        // Reuse the state after `setUp` from a previous run if nothing it depends on changed
+        let fixture = self.setup_fixture.as_ref().filter(|_| setup).map(|(path, environment)| {
+            let hash = SetupFixture::hash_inputs(
+                &self.executor,
+                &self.code,
+                self.predeploy_libs,
+                self.sender,
+                self.initial_balance,
+                self.genesis,
+                *environment,
+            );
+            (path, hash)
+        });
+        if let Some((path, hash)) = fixture {
+            if let Some(fixture) = SetupFixture::load(path, hash) {
+                trace!(?path, "reusing setup fixture");
+                fixture.apply(&mut self.executor)?;
+                return Ok(TestSetup::success(
+                    fixture.address,
+                    vec![],
+                    vec![],
+                    fixture.labeled_addresses,
+                    None,
+                ))
+            }
+        }
+        let fork_id = self.executor.backend.active_fork_id();
+
         // Deploy libraries
         let mut logs = Vec::new();
         let mut traces = Vec::with_capacity(self.predeploy_libs.len());
@@ -193,6 +259,15 @@ impl<'a> ContractRunner<'a> {
             traces.extend(setup_traces.map(|traces| (TraceKind::Setup, traces)));
             logs.extend(setup_logs);
 
+            if let (Some((path, hash)), None) = (fixture, &reason) {
+                // the state of a fork selected during setup can't be restored from a fixture
+                if self.executor.backend.active_fork_id() == fork_id {
+                    SetupFixture::capture(&self.executor, hash, address, labeled_addresses.clone())
+                        .save(path)
+                        .unwrap_or_else(|err| warn!(?path, %err, "failed to save setup fixture"));
+                }
+            }
+
             TestSetup { address, logs, traces, labeled_addresses, reason, coverage }
         } else {
             TestSetup::success(address, logs, traces, Default::default(), None)
@@ -279,13 +354,26 @@ impl<'a> ContractRunner<'a> {
             .filter(|&&func| func.is_test() && filter.matches_test(&func.signature()))
             .map(|&func| {
                 let should_fail = func.is_test_fail();
-                let res: TestResult = if func.is_fuzz_test() {
-                    let runner = test_options.fuzz_runner(self.name, &func.name);
-                    let fuzz_config = test_options.fuzz_config(self.name, &func.name);
-                    self.run_fuzz_test(func, should_fail, runner, setup.clone(), *fuzz_config)
-                } else {
-                    self.run_test(func, should_fail, setup.clone())
-                };
+                let zk_gas = test_options.zk_gas_config(self.name, &func.name);
+                let retries = ForkTestRetries::from(
+                    test_options.zk_fork_retries_config(self.name, &func.name),
+                );
+                let res: TestResult = retries.run(|| {
+                    if func.is_fuzz_test() {
+                        let runner = test_options.fuzz_runner(self.name, &func.name);
+                        let fuzz_config = test_options.fuzz_config(self.name, &func.name);
+                        self.run_fuzz_test(
+                            func,
+                            should_fail,
+                            runner,
+                            setup.clone(),
+                            *fuzz_config,
+                            zk_gas,
+                        )
+                    } else {
+                        self.run_test(func, should_fail, setup.clone(), zk_gas)
+                    }
+                });
                 (func.signature(), res)
             })
             .collect::<BTreeMap<_, _>>();
@@ -331,6 +419,28 @@ impl<'a> ContractRunner<'a> {
             test_results.extend(results);
         }
 
+        if let Some(dir) = &self.replay_dir {
+            let fork = FetchedState::from_active_fork(&self.executor.backend, &self.executor.env)
+                .or_else(|| self.fetched_state.cloned());
+            let failures =
+                test_results.iter_mut().filter(|(_, result)| result.status == TestStatus::Failure);
+            for (signature, result) in failures {
+                let name = signature.split('(').next().unwrap_or(signature);
+                let fuzz_seed = test_options.fuzz_config(self.name, name).seed;
+                let bundle = ReplayBundle::new(
+                    self.name,
+                    signature.clone(),
+                    fuzz_seed,
+                    fork.clone(),
+                    result,
+                );
+                match bundle.save(dir) {
+                    Ok(path) => result.replay_bundle = Some(path),
+                    Err(err) => warn!(?dir, %err, "failed to write replay bundle"),
+                }
+            }
+        }
+
         let duration = start.elapsed();
         if !test_results.is_empty() {
             let successful =
@@ -346,6 +456,16 @@ impl<'a> ContractRunner<'a> {
         SuiteResult::new(duration, test_results, warnings)
     }
 
+    /// Returns the executor to run a test with, using the gas limit of its "zk" gas configuration
+    /// if set.
+    fn test_executor(&self, zk_gas: &ZkGasConfig) -> Executor {
+        let mut executor = self.executor.clone();
+        if let Some(gas_limit) = zk_gas.gas_limit {
+            executor.set_gas_limit(U256::from(gas_limit));
+        }
+        executor
+    }
+
     /// Runs a single test
     ///
     /// Calls the given functions and returns the `TestResult`.
@@ -353,16 +473,24 @@ impl<'a> ContractRunner<'a> {
     /// State modifications are not committed to the evm database but discarded after the call,
     /// similar to `eth_call`.
     #[instrument(name = "test", skip_all, fields(name = %func.signature(), %should_fail))]
-    pub fn run_test(&self, func: &Function, should_fail: bool, setup: TestSetup) -> TestResult {
+    pub fn run_test(
+        &self,
+        func: &Function,
+        should_fail: bool,
+        setup: TestSetup,
+        zk_gas: &ZkGasConfig,
+    ) -> TestResult {
         trace!("executing test {:?}", func.name);
         let TestSetup {
             address, mut logs, mut traces, mut labeled_addresses, mut coverage, ..
         } = setup;
 
         // Run unit test
-        let mut executor = self.executor.clone();
+        let mut executor = self.test_executor(zk_gas);
         let start = Instant::now();
         let debug_arena;
+        let mut revert_location = None;
+        let mut revert_data = None;
         let (reverted, reason, gas, stipend, coverage, state_changeset, breakpoints) =
             match executor.execute_test::<_, _>(
                 self.sender,
@@ -398,6 +526,12 @@ impl<'a> ContractRunner<'a> {
                     labeled_addresses.extend(err.labels);
                     logs.extend(err.logs);
                     debug_arena = err.debug;
+                    revert_location = err.revert_site.map(|site| {
+                        self.source_maps
+                            .and_then(|maps| maps.describe(&site.bytecode_hash, site.pc))
+                            .unwrap_or_else(|| format!("{:?}, pc {}", site.address, site.pc))
+                    });
+                    revert_data = Some(err.result);
                     (
                         err.reverted,
                         Some(err.reason),
@@ -454,6 +588,8 @@ impl<'a> ContractRunner<'a> {
                 false => TestStatus::Failure,
             },
             reason,
+            revert_data,
+            revert_location: if success { None } else { revert_location },
             counterexample: None,
             decoded_logs: decode_console_logs(&logs),
             logs,
@@ -463,6 +599,7 @@ impl<'a> ContractRunner<'a> {
             labeled_addresses,
             debug: debug_arena,
             breakpoints,
+            ..Default::default()
         }
     }
 
@@ -603,18 +740,45 @@ impl<'a> ContractRunner<'a> {
         runner: TestRunner,
         setup: TestSetup,
         fuzz_config: FuzzConfig,
+        zk_gas: &ZkGasConfig,
     ) -> TestResult {
         let TestSetup {
             address, mut logs, mut traces, mut labeled_addresses, mut coverage, ..
         } = setup;
 
+        // Run the inputs recorded in the corpus first, then fuzz. The inputs recorded for a
+        // previous version of the test are dropped.
+        let corpus_path =
+            self.corpus_dir.as_ref().map(|dir| FuzzCorpus::path(dir, self.name, &func.name));
+        let mut corpus = corpus_path.as_deref().map(FuzzCorpus::load).unwrap_or_default();
+        let mut corpus_changed = corpus.retain_valid(func);
+
         // Run fuzz test
         let start = Instant::now();
-        let fuzzed_executor =
-            FuzzedExecutor::new(self.executor.clone(), runner.clone(), self.sender, fuzz_config);
+        let fuzzed_executor = FuzzedExecutor::new(
+            self.test_executor(zk_gas),
+            runner.clone(),
+            self.sender,
+            fuzz_config,
+        )
+        .with_corpus(corpus.calldata());
         let state = fuzzed_executor.build_fuzz_state();
         let mut result = fuzzed_executor.fuzz(func, address, should_fail, self.errors);
 
+        if let Some(path) = &corpus_path {
+            for input in std::mem::take(&mut result.new_coverage_inputs) {
+                corpus_changed |= corpus.add(func, input);
+            }
+            if let Some(CounterExample::Single(counterexample)) = &result.counterexample {
+                corpus_changed |= corpus.add(func, counterexample.calldata.clone());
+            }
+            if corpus_changed {
+                corpus
+                    .save(path)
+                    .unwrap_or_else(|err| warn!(?path, %err, "failed to save fuzz corpus"));
+            }
+        }
+
         let mut debug = Default::default();
         let mut breakpoints = Default::default();
 
@@ -707,6 +871,7 @@ impl<'a> ContractRunner<'a> {
             labeled_addresses,
             debug,
             breakpoints,
+            ..Default::default()
         }
     }
 }
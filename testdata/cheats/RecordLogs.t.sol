@@ -228,4 +228,35 @@ contract RecordLogsTest is DSTest {
         assertEq(entries[0].topics.length, 4);
         assertEq(entries[0].emitter, address(emitter));
     }
+
+    function testRecordFilteredByEmitterAndDepth() public {
+        Emitterv2 emitter2 = new Emitterv2();
+        address[] memory emitters = new address[](0);
+
+        vm.recordLogs();
+        emit LogTopic(1, generateTestData(16));
+        emitter.emitEvent(2, 3, generateTestData(20));
+        emitter2.emitEvent(4, 5, 6, generateTestData(24));
+
+        Vm.Log[] memory entries = vm.getRecordedLogs(emitters, 1);
+        assertEq(entries.length, 2);
+        assertEq(entries[0].emitter, address(this));
+        assertEq(entries[1].emitter, address(emitter));
+
+        vm.recordLogs();
+        emit LogTopic(1, generateTestData(16));
+        emitter.emitEvent(2, 3, generateTestData(20));
+        emitter2.emitEvent(4, 5, 6, generateTestData(24));
+
+        emitters = new address[](1);
+        emitters[0] = emitter2.getEmitterAddr();
+        entries = vm.getRecordedLogs(emitters, 2);
+        assertEq(entries.length, 1);
+        assertEq(entries[0].topics[1], bytes32(uint256(4)));
+
+        emit LogTopic(1, generateTestData(16));
+        entries = vm.getRecordedLogs(new address[](0), 0);
+        assertEq(entries.length, 1);
+        assertEq(entries[0].emitter, address(this));
+    }
 }
@@ -26,6 +26,22 @@ contract StoreTest is DSTest {
         assertEq(store.slot1(), 20, "store failed");
     }
 
+    function testStoreBatch() public {
+        Storage other = new Storage();
+        address[] memory targets = new address[](3);
+        bytes32[] memory slots = new bytes32[](3);
+        bytes32[] memory values = new bytes32[](3);
+        (targets[0], slots[0], values[0]) = (address(store), bytes32(0), bytes32(uint256(1)));
+        (targets[1], slots[1], values[1]) = (address(other), bytes32(uint256(1)), bytes32(uint256(2)));
+        (targets[2], slots[2], values[2]) = (address(store), bytes32(0), bytes32(uint256(3)));
+
+        vm.storeBatch(targets, slots, values);
+        assertEq(store.slot0(), 3, "store batch failed");
+        assertEq(store.slot1(), 20, "store batch failed");
+        assertEq(other.slot0(), 10, "store batch failed");
+        assertEq(other.slot1(), 2, "store batch failed");
+    }
+
     function testStoreNotAvailableOnPrecompiles() public {
         assertEq(store.slot0(), 10, "initial value for slot 0 is incorrect");
         assertEq(store.slot1(), 20, "initial value for slot 1 is incorrect");
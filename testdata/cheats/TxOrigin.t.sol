@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity 0.8.18;
+
+import "ds-test/test.sol";
+import "./Vm.sol";
+
+contract OriginReader {
+    function origin() public view returns (address) {
+        return tx.origin;
+    }
+}
+
+contract TxOriginTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function testSetTxOrigin() public {
+        address newOrigin = address(0xBEEF);
+        vm.setTxOrigin(newOrigin);
+
+        assertEq(tx.origin, newOrigin);
+        assertEq(vm.txOrigin(), newOrigin);
+        assertEq(new OriginReader().origin(), newOrigin);
+    }
+
+    function testTxOriginIsIndependentOfPrank() public {
+        address newOrigin = address(0xBEEF);
+        vm.setTxOrigin(newOrigin);
+
+        vm.startPrank(address(0xCAFE));
+        (, address sender, address origin) = vm.readCallers();
+        vm.stopPrank();
+
+        assertEq(sender, address(0xCAFE));
+        assertEq(origin, newOrigin);
+        assertEq(vm.txOrigin(), newOrigin);
+    }
+}
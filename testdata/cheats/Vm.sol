@@ -107,16 +107,35 @@ interface Vm {
         bool reverted;
     }
 
+    struct StorageOverride {
+        address account;
+        bytes32 slot;
+        bytes32 value;
+    }
+
+    struct BalanceOverride {
+        address account;
+        uint256 balance;
+    }
+
+    struct CodeOverride {
+        address account;
+        bytes code;
+    }
+
     function accesses(address target) external returns (bytes32[] memory readSlots, bytes32[] memory writeSlots);
     function activeFork() external view returns (uint256 forkId);
     function addr(uint256 privateKey) external pure returns (address keyAddr);
     function allowCheatcodes(address account) external;
     function assume(bool condition) external pure;
+    function assumeNoZkOutOfGas() external;
+    function assumeZkGasBelow(uint256 gas) external;
     function breakpoint(string calldata char) external;
     function breakpoint(string calldata char, bool value) external;
     function broadcast() external;
     function broadcast(address signer) external;
     function broadcast(uint256 privateKey) external;
+    function broadcastWallet(uint256 senderIndex) external;
     function chainId(uint256 newChainId) external;
     function clearMockedCalls() external;
     function closeFile(string calldata path) external;
@@ -153,7 +172,14 @@ interface Vm {
         external
         pure
         returns (uint256 privateKey);
+    function diffSnapshots(uint256 fromSnapshotId, uint256 toSnapshotId) external view returns (string memory diff);
     function difficulty(uint256 newDifficulty) external;
+    function erc20Allowance(address token, address owner, address spender) external view returns (uint256 allowance);
+    function erc20BalanceOf(address token, address account) external view returns (uint256 balance);
+    function erc721BalanceOf(address token, address owner) external view returns (uint256 balance);
+    function erc721GetApproved(address token, uint256 tokenId) external view returns (address approved);
+    function erc721IsApprovedForAll(address token, address owner, address operator) external view returns (bool approved);
+    function erc721OwnerOf(address token, uint256 tokenId) external view returns (address owner);
     function envAddress(string calldata name) external view returns (address value);
     function envAddress(string calldata name, string calldata delim) external view returns (address[] memory value);
     function envBool(string calldata name) external view returns (bool value);
@@ -215,16 +241,26 @@ interface Vm {
         external;
     function expectEmit() external;
     function expectEmit(address emitter) external;
+    function expectEmit(uint8 topicWildcards, bool checkData) external;
     function expectRevert() external;
     function expectRevert(bytes4 revertData) external;
     function expectRevert(bytes calldata revertData) external;
+    function expectRevert(uint64 count) external;
+    function expectRevert(bytes4 revertData, uint64 count) external;
+    function expectRevert(bytes calldata revertData, uint64 count) external;
+    function expectRevert(address reverter, uint64 count) external;
+    function expectRevert(bytes4 revertData, address reverter, uint64 count) external;
+    function expectRevert(bytes calldata revertData, address reverter, uint64 count) external;
     function expectSafeMemory(uint64 min, uint64 max) external;
     function expectSafeMemoryCall(uint64 min, uint64 max) external;
     function fee(uint256 newBasefee) external;
     function ffi(string[] calldata commandInput) external returns (bytes memory result);
     function fsMetadata(string calldata path) external view returns (FsMetadata memory metadata);
+    function getCalls(address callee, bytes calldata data) external view returns (uint256 count);
     function getCode(string calldata artifactPath) external view returns (bytes memory creationBytecode);
     function getDeployedCode(string calldata artifactPath) external view returns (bytes memory runtimeBytecode);
+    function getDeployedCode(address account) external view returns (bytes memory runtimeBytecode);
+    function getFoundryVersion() external view returns (string memory version);
     function getLabel(address account) external returns (string memory currentLabel);
     function getMappingKeyAndParentOf(address target, bytes32 elementSlot)
         external
@@ -234,9 +270,12 @@ interface Vm {
     function getNonce(address account) external view returns (uint64 nonce);
     function getNonce(Wallet calldata wallet) external returns (uint64 nonce);
     function getRecordedLogs() external returns (Log[] memory logs);
+    function getRecordedLogs(address[] calldata emitters, uint256 maxDepth) external returns (Log[] memory logs);
+    function getScriptWallets() external returns (address[] memory wallets);
     function isDir(string calldata path) external returns (bool result);
     function isFile(string calldata path) external returns (bool result);
     function isPersistent(address account) external view returns (bool persistent);
+    function isZkVm() external view returns (bool isZk);
     function keyExists(string calldata json, string calldata key) external view returns (bool);
     function label(address account, string calldata newLabel) external;
     function load(address target, bytes32 slot) external view returns (bytes32 data);
@@ -250,6 +289,7 @@ interface Vm {
         external;
     function mockCall(address callee, bytes calldata data, bytes calldata returnData) external;
     function mockCall(address callee, uint256 msgValue, bytes calldata data, bytes calldata returnData) external;
+    function mockFunction(address callee, address target, bytes calldata data) external;
     function parseAddress(string calldata stringifiedValue) external pure returns (address parsedValue);
     function parseBool(string calldata stringifiedValue) external pure returns (bool parsedValue);
     function parseBytes(string calldata stringifiedValue) external pure returns (bytes memory parsedValue);
@@ -284,6 +324,7 @@ interface Vm {
     function prank(address msgSender, address txOrigin) external;
     function prevrandao(bytes32 newPrevrandao) external;
     function projectRoot() external view returns (string memory path);
+    function publicKeyP256(uint256 privateKey) external pure returns (uint256 publicKeyX, uint256 publicKeyY);
     function readCallers() external returns (CallerMode callerMode, address msgSender, address txOrigin);
     function readDir(string calldata path) external view returns (DirEntry[] memory entries);
     function readDir(string calldata path, uint64 maxDepth) external view returns (DirEntry[] memory entries);
@@ -296,6 +337,7 @@ interface Vm {
     function readLine(string calldata path) external view returns (string memory line);
     function readLink(string calldata linkPath) external view returns (string memory targetPath);
     function record() external;
+    function recordCalls() external;
     function recordLogs() external;
     function rememberKey(uint256 privateKey) external returns (address keyAddr);
     function removeDir(string calldata path, bool recursive) external;
@@ -303,6 +345,7 @@ interface Vm {
     function resetNonce(address account) external;
     function resumeGasMetering() external;
     function revertTo(uint256 snapshotId) external returns (bool success);
+    function revertToNamed(string calldata name) external returns (bool success);
     function revokePersistent(address account) external;
     function revokePersistent(address[] calldata accounts) external;
     function roll(uint256 newHeight) external;
@@ -361,14 +404,18 @@ interface Vm {
     function setEnv(string calldata name, string calldata value) external;
     function setNonce(address account, uint64 newNonce) external;
     function setNonceUnsafe(address account, uint64 newNonce) external;
+    function setTxOrigin(address newOrigin) external;
     function sign(uint256 privateKey, bytes32 digest) external pure returns (uint8 v, bytes32 r, bytes32 s);
     function sign(Wallet calldata wallet, bytes32 digest) external returns (uint8 v, bytes32 r, bytes32 s);
+    function signP256(uint256 privateKey, bytes32 digest) external pure returns (bytes32 r, bytes32 s);
     function skip(bool skipTest) external;
     function sleep(uint256 duration) external;
     function snapshot() external returns (uint256 snapshotId);
+    function snapshotNamed(string calldata name) external returns (uint256 snapshotId);
     function startBroadcast() external;
     function startBroadcast(address signer) external;
     function startBroadcast(uint256 privateKey) external;
+    function startBroadcastWallet(uint256 senderIndex) external;
     function startMappingRecording() external;
     function startPrank(address msgSender) external;
     function startPrank(address msgSender, address txOrigin) external;
@@ -378,6 +425,7 @@ interface Vm {
     function stopMappingRecording() external;
     function stopPrank() external;
     function store(address target, bytes32 slot, bytes32 value) external;
+    function storeBatch(address[] calldata targets, bytes32[] calldata slots, bytes32[] calldata values) external;
     function toString(address value) external pure returns (string memory stringifiedValue);
     function toString(bytes calldata value) external pure returns (string memory stringifiedValue);
     function toString(bytes32 value) external pure returns (string memory stringifiedValue);
@@ -388,11 +436,23 @@ interface Vm {
     function transact(uint256 forkId, bytes32 txHash) external;
     function tryFfi(string[] calldata commandInput) external returns (FfiResult memory result);
     function txGasPrice(uint256 newGasPrice) external;
+    function txOrigin() external view returns (address origin);
     function unixTime() external returns (uint256 milliseconds);
     function warp(uint256 newTimestamp) external;
+    function withStateOverrides(StorageOverride[] calldata overrides) external;
+    function withStateOverrides(BalanceOverride[] calldata balances, CodeOverride[] calldata codes, StorageOverride[] calldata slots) external;
     function writeFile(string calldata path, string calldata data) external;
     function writeFileBinary(string calldata path, bytes calldata data) external;
     function writeJson(string calldata json, string calldata path) external;
     function writeJson(string calldata json, string calldata path, string calldata valueKey) external;
     function writeLine(string calldata path, string calldata data) external;
+    function zkAssertCodeMatchesArtifact(address account, string calldata artifactPath) external view;
+    function zkFailNextStorageWrite(address target, bytes32 slot) external;
+    function zkFinalizeWithdrawal(string calldata urlOrAlias, bytes32 withdrawalHash) external returns (address l1Target, bytes memory data);
+    function zkProtocolVersion() external view returns (uint16 protocolVersion, string memory vmVersion);
+    function zkSetGasPerPubdataLimit(uint256 gasPerPubdataLimit) external;
+    function zkSetMaxFeePerGas(uint256 maxFeePerGas) external;
+    function zkSetEmitOrdering(uint8 ordering) external;
+    function zkSetNextCallGas(uint256 gas) external;
+    function zkSetTracing(uint8 level) external;
 }